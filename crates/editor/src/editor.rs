@@ -18021,9 +18021,9 @@ impl Editor {
         &mut self,
         _: &ShowCharacterPalette,
         window: &mut Window,
-        _: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) {
-        window.show_character_palette();
+        window.show_character_palette(cx);
     }
 
     fn refresh_active_diagnostics(&mut self, cx: &mut Context<Editor>) {
@@ -3,8 +3,8 @@ use std::time::Duration;
 use anyhow::Result;
 use gpui::{
     Animation, AnimationExt as _, App, Application, AssetSource, Bounds, Context, SharedString,
-    Transformation, Window, WindowBounds, WindowOptions, bounce, div, ease_in_out, percentage,
-    prelude::*, px, size, svg,
+    Transformation, Window, WindowBounds, WindowOptions, bounce, div, ease_in_out, oklch,
+    percentage, prelude::*, px, size, svg,
 };
 
 struct Assets {}
@@ -81,6 +81,17 @@ impl Render for AnimationExample {
                                             ))
                                         },
                                     ),
+                            )
+                            .child(
+                                div().size_4().rounded_full().with_color_animation(
+                                    "hue",
+                                    Animation::new(Duration::from_secs(2))
+                                        .repeat()
+                                        .with_easing(bounce(ease_in_out)),
+                                    oklch(0.65, 0.2, 30., 1.),
+                                    oklch(0.65, 0.2, 250., 1.),
+                                    |this, color| this.bg(color),
+                                ),
                             ),
                     )
                     .child(
@@ -4,7 +4,7 @@ use gpui::{
     Animation, AnimationExt, App, Application, Asset, AssetLogger, AssetSource, Bounds, Context,
     Hsla, ImageAssetLoader, ImageCacheError, ImgResourceLoader, LOADING_DELAY, Length, RenderImage,
     Resource, SharedString, Window, WindowBounds, WindowOptions, black, div, img, prelude::*,
-    pulsating_between, px, red, size,
+    pulsating_between, px, red, size, transparent_black,
 };
 
 struct Assets {}
@@ -69,12 +69,14 @@ struct ImageLoadingExample {}
 impl ImageLoadingExample {
     fn loading_element() -> impl IntoElement {
         div().size_full().flex_none().p_0p5().rounded_xs().child(
-            div().size_full().with_animation(
+            div().size_full().with_color_animation(
                 "loading-bg",
                 Animation::new(Duration::from_secs(3))
                     .repeat()
                     .with_easing(pulsating_between(0.04, 0.24)),
-                move |this, delta| this.bg(black().opacity(delta)),
+                transparent_black(),
+                black(),
+                |this, color| this.bg(color),
             ),
         )
     }
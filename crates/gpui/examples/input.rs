@@ -123,9 +123,9 @@ impl TextInput {
         &mut self,
         _: &ShowCharacterPalette,
         window: &mut Window,
-        _: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) {
-        window.show_character_palette();
+        window.show_character_palette(cx);
     }
 
     fn paste(&mut self, _: &Paste, window: &mut Window, cx: &mut Context<Self>) {
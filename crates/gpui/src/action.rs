@@ -420,6 +420,49 @@ pub fn generate_list_of_all_registered_actions() -> impl Iterator<Item = MacroAc
         .map(|builder| builder.0())
 }
 
+/// What an action interceptor registered with [`App::intercept_actions`](crate::App::intercept_actions)
+/// or [`Window::intercept_actions`](crate::Window::intercept_actions) decides to do with an action
+/// that is about to be dispatched.
+pub enum ActionInterception {
+    /// Dispatch the action unchanged.
+    Continue,
+    /// Dispatch the given action in place of the intercepted one. Interceptors that run later
+    /// see the replacement.
+    Replace(Box<dyn Action>),
+    /// Don't dispatch the action, treating it as handled. Interceptors that run later aren't
+    /// invoked.
+    Block,
+}
+
+/// Runs the given interceptors in registration order, starting from `action` as already replaced
+/// by `replacement`, if any.
+pub(crate) fn run_action_interceptors<Callback: 'static>(
+    interceptors: &crate::SubscriberSet<(), Callback>,
+    action: &dyn Action,
+    mut replacement: Option<Box<dyn Action>>,
+    mut invoke: impl FnMut(&mut Callback, &dyn Action) -> ActionInterception,
+) -> ActionInterception {
+    let mut blocked = false;
+    interceptors.clone().retain(&(), |interceptor| {
+        if !blocked {
+            match invoke(interceptor, replacement.as_deref().unwrap_or(action)) {
+                ActionInterception::Continue => {}
+                ActionInterception::Replace(action) => replacement = Some(action),
+                ActionInterception::Block => blocked = true,
+            }
+        }
+        true
+    });
+
+    if blocked {
+        ActionInterception::Block
+    } else if let Some(replacement) = replacement {
+        ActionInterception::Replace(replacement)
+    } else {
+        ActionInterception::Continue
+    }
+}
+
 mod no_action {
     use crate as gpui;
     use std::any::Any as _;
@@ -438,3 +481,73 @@ mod no_action {
         action.as_any().type_id() == (NoAction {}).type_id()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{ActionInterception, TestAppContext, actions};
+
+    #[crate::test]
+    fn test_intercept_actions(cx: &mut TestAppContext) {
+        actions!(test_intercept, [First, Second, Third]);
+
+        let handled = Rc::new(RefCell::new(Vec::new()));
+        let intercepted = Rc::new(RefCell::new(Vec::new()));
+        let _subscriptions = cx.update(|cx| {
+            cx.on_action({
+                let handled = handled.clone();
+                move |_: &First, _| handled.borrow_mut().push("first")
+            });
+            cx.on_action({
+                let handled = handled.clone();
+                move |_: &Second, _| handled.borrow_mut().push("second")
+            });
+            cx.on_action({
+                let handled = handled.clone();
+                move |_: &Third, _| handled.borrow_mut().push("third")
+            });
+
+            [
+                cx.intercept_actions({
+                    let intercepted = intercepted.clone();
+                    move |action, _| {
+                        intercepted.borrow_mut().push(action.name());
+                        if action.as_any().is::<First>() {
+                            ActionInterception::Replace(Box::new(Second))
+                        } else {
+                            ActionInterception::Continue
+                        }
+                    }
+                }),
+                cx.intercept_actions({
+                    let intercepted = intercepted.clone();
+                    move |action, _| {
+                        intercepted.borrow_mut().push(action.name());
+                        if action.as_any().is::<Third>() {
+                            ActionInterception::Block
+                        } else {
+                            ActionInterception::Continue
+                        }
+                    }
+                }),
+            ]
+        });
+
+        cx.update(|cx| cx.dispatch_action(&First));
+        assert_eq!(*handled.borrow(), ["second"]);
+        assert_eq!(
+            *intercepted.borrow(),
+            ["test_intercept::First", "test_intercept::Second"]
+        );
+
+        handled.borrow_mut().clear();
+        intercepted.borrow_mut().clear();
+        cx.update(|cx| cx.dispatch_action(&Third));
+        assert!(handled.borrow().is_empty());
+        assert_eq!(
+            *intercepted.borrow(),
+            ["test_intercept::Third", "test_intercept::Third"]
+        );
+    }
+}
@@ -1,9 +1,9 @@
 use std::{
     any::{TypeId, type_name},
-    cell::{BorrowMutError, Ref, RefCell, RefMut},
+    cell::{BorrowMutError, Cell, Ref, RefCell, RefMut},
     marker::PhantomData,
     mem,
-    ops::{Deref, DerefMut},
+    ops::{Deref, DerefMut, Range},
     path::{Path, PathBuf},
     rc::{Rc, Weak},
     sync::{Arc, atomic::Ordering::SeqCst},
@@ -22,8 +22,11 @@ use parking_lot::RwLock;
 use slotmap::SlotMap;
 
 pub use async_context::*;
+pub use binding::*;
 use collections::{FxHashMap, FxHashSet, HashMap, VecDeque};
+pub use computed::*;
 pub use context::*;
+pub use entity_history::*;
 pub use entity_map::*;
 use http_client::{HttpClient, Url};
 use smallvec::SmallVec;
@@ -34,27 +37,63 @@ use util::{ResultExt, debug_panic};
 #[cfg(any(feature = "inspector", debug_assertions))]
 use crate::InspectorElementRegistry;
 use crate::{
-    Action, ActionBuildError, ActionRegistry, Any, AnyView, AnyWindowHandle, AppContext, Asset,
-    AssetSource, BackgroundExecutor, Bounds, ClipboardItem, CursorStyle, DispatchPhase, DisplayId,
-    EventEmitter, FocusHandle, FocusMap, ForegroundExecutor, Global, KeyBinding, KeyContext,
-    Keymap, Keystroke, LayoutId, Menu, MenuItem, OwnedMenu, PathPromptOptions, Pixels, Platform,
-    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, Point, PromptBuilder,
+    Action, ActionBuildError, ActionInterception, ActionRegistry, Any, AnyView, AnyWindowHandle,
+    AppContext, Asset, AssetSource, BackgroundExecutor, Bounds, ClipboardItem, CursorStyle,
+    DispatchPhase, DisplayId, EventEmitter, FocusHandle, FocusMap, ForegroundExecutor,
+    FrameTimestampSource, Global, ImageColorManagement, KeyBinding, KeyContext, Keymap, Keystroke,
+    LayoutId, Menu, MenuItem, OwnedMenu, PathPromptOptions, Pixels, Platform, PlatformDisplay,
+    PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformSpellChecker, Point, PromptBuilder,
     PromptButton, PromptHandle, PromptLevel, Render, RenderImage, RenderablePromptHandle,
-    Reservation, ScreenCaptureSource, SharedString, SubscriberSet, Subscription, SvgRenderer, Task,
-    TextSystem, Window, WindowAppearance, WindowHandle, WindowId, WindowInvalidator,
+    Reservation, ScreenCaptureSource, SharedString, StyleRefinement, SubscriberSet, Subscription,
+    SubscriptionAudit, SubscriptionKind, SvgRenderer, Task, TextSystem, Window, WindowAppearance,
+    WindowHandle, WindowId, WindowInvalidator, WindowOpenError,
+    action::run_action_interceptors,
     colors::{Colors, GlobalColors},
     current_platform, hash, init_app_menus,
 };
 
 mod async_context;
+mod binding;
+mod computed;
 mod context;
+mod entity_history;
 mod entity_map;
 #[cfg(any(test, feature = "test-support"))]
 mod test_context;
 
 /// The duration for which futures returned from [Context::on_app_quit] can run before the application fully quits.
+/// Each [`ShutdownPhase`] is given this long to complete, unless changed with
+/// [`App::set_shutdown_timeout`].
 pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_millis(100);
 
+/// A phase of the application's shutdown sequence, in the order they run.
+/// See [`App::on_shutdown`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShutdownPhase {
+    /// Runs while all windows are still open, alongside the [`App::on_app_quit`] callbacks.
+    CloseWindows,
+    /// Runs once all windows are closed, to persist state such as unsaved data.
+    FlushEntities,
+    /// Runs last, to let background work such as network requests finish. Tasks passed to
+    /// [`App::drain_on_shutdown`] are awaited in this phase.
+    DrainBackgroundTasks,
+}
+
+/// The order in which handlers registered for the same [`ShutdownPhase`] run. The futures of
+/// all handlers with one priority complete before the handlers with the next priority are
+/// invoked. See [`App::on_shutdown`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShutdownPriority {
+    /// Runs first, for work that other handlers in the phase depend on.
+    High,
+    /// Runs alongside the [`App::on_app_quit`] callbacks and the tasks passed to
+    /// [`App::drain_on_shutdown`].
+    #[default]
+    Normal,
+    /// Runs once the handlers with a higher priority have completed.
+    Low,
+}
+
 /// Temporary(?) wrapper around [`RefCell<App>`] to help us debug any double borrows.
 /// Strongly consider removing after stabilization.
 #[doc(hidden)]
@@ -122,7 +161,7 @@ impl Drop for AppRefMut<'_> {
 
 /// A reference to a GPUI application, typically constructed in the `main` function of your app.
 /// You won't interact with this type much outside of initial configuration and startup.
-pub struct Application(Rc<AppCell>);
+pub struct Application(pub(crate) Rc<AppCell>);
 
 /// Represents an application before it is fully launched. Once your app is
 /// configured, you'll start the app with `App::run`.
@@ -176,20 +215,91 @@ impl Application {
         self
     }
 
+    /// Configures how the application integrates with the desktop. Applications embedded in
+    /// another one, such as a plugin's editor, should use [`AppMode::Embedded`], usually along
+    /// with [`Application::attach`].
+    pub fn with_mode(self, mode: AppMode) -> Self {
+        self.0.borrow_mut().app_mode = mode;
+        self
+    }
+
+    /// Keep the application from quitting when its last window closes, for as long as the
+    /// returned guard is held. See [`App::keep_alive_guard`].
+    pub fn keep_alive_guard(&self) -> KeepAliveGuard {
+        self.0.borrow().keep_alive_guard()
+    }
+
+    /// Read the timestamps of every window's frames from the given clock rather than
+    /// [`Instant::now`]. See [`App::set_frame_clock`].
+    pub fn with_frame_clock(self, clock: impl Fn() -> Instant + 'static) -> Self {
+        self.0.borrow_mut().set_frame_clock(clock);
+        self
+    }
+
     /// Start the application. The provided callback will be called once the
     /// app is fully launched.
+    ///
+    /// If the process already runs the platform's event loop, such as a plugin host on macOS
+    /// whose `NSApplication` is running, this attaches to it like [`Application::attach`] and
+    /// returns once the callback has been called, rather than starting another event loop. The
+    /// application then lives until [`runtime::shutdown`](crate::runtime::shutdown).
     pub fn run<F>(self, on_finish_launching: F)
     where
         F: 'static + FnOnce(&mut App),
     {
         let this = self.0.clone();
         let platform = self.0.borrow().platform.clone();
+        if platform.is_event_loop_running() {
+            log::info!("attaching to the event loop that's already running");
+            self.attach(on_finish_launching);
+            // Nothing returns from the host's event loop to drop the application after
+            crate::runtime::keep_attached_application(self);
+            return;
+        }
         platform.run(Box::new(move || {
             let cx = &mut *this.borrow_mut();
             on_finish_launching(cx);
         }));
     }
 
+    /// Start the application inside an event loop that the host process already runs, such as
+    /// a plugin host's, instead of taking over the thread like [`Application::run`]. The provided
+    /// callback is called before this returns.
+    ///
+    /// On macOS and Windows, the host's event loop processes GPUI's work along with its own.
+    /// On Linux, GPUI's work is only processed by [`Application::pump`], which hosts should call
+    /// regularly, for example from their idle or timer callbacks. [`App::quit`] shuts the
+    /// application down without stopping the host's event loop, and closing the last window
+    /// doesn't quit unless [`QuitMode::LastWindowClosed`] is set.
+    pub fn attach<F>(&self, on_finish_launching: F)
+    where
+        F: 'static + FnOnce(&mut App),
+    {
+        let this = self.0.clone();
+        let platform = {
+            let mut cx = self.0.borrow_mut();
+            cx.attached = true;
+            cx.platform.clone()
+        };
+        platform.attach(Box::new(move || {
+            let cx = &mut *this.borrow_mut();
+            on_finish_launching(cx);
+        }));
+    }
+
+    /// Shut down an application that's attached to the host's event loop, closing its windows.
+    pub(crate) fn shutdown(self) {
+        self.0.borrow_mut().shutdown();
+    }
+
+    /// Process the work that GPUI has pending on the main thread, such as tasks and input, and
+    /// return without waiting for more. Call this regularly from the host's event loop after
+    /// [`Application::attach`]. It must not be called from within GPUI's own callbacks.
+    pub fn pump(&self) {
+        let platform = self.0.borrow().platform.clone();
+        platform.pump();
+    }
+
     /// Register a handler to be invoked when the platform instructs the application
     /// to open one or more URLs.
     pub fn on_open_urls<F>(&self, mut callback: F) -> &Self
@@ -240,6 +350,8 @@ type Handler = Box<dyn FnMut(&mut App) -> bool + 'static>;
 type Listener = Box<dyn FnMut(&dyn Any, &mut App) -> bool + 'static>;
 pub(crate) type KeystrokeObserver =
     Box<dyn FnMut(&KeystrokeEvent, &mut Window, &mut App) -> bool + 'static>;
+pub(crate) type ActionInterceptor =
+    Box<dyn FnMut(&dyn Action, &mut App) -> ActionInterception + 'static>;
 type QuitHandler = Box<dyn FnOnce(&mut App) -> LocalBoxFuture<'static, ()> + 'static>;
 type WindowClosedHandler = Box<dyn FnMut(&mut App)>;
 type ReleaseListener = Box<dyn FnOnce(&mut dyn Any, &mut App) + 'static>;
@@ -248,7 +360,9 @@ type NewEntityListener = Box<dyn FnMut(AnyEntity, &mut Option<&mut Window>, &mut
 /// Defines when the application should automatically quit.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum QuitMode {
-    /// Use [`QuitMode::Explicit`] on macOS and [`QuitMode::LastWindowClosed`] on other platforms.
+    /// Use [`QuitMode::Explicit`] on macOS, for applications started with
+    /// [`Application::attach`] and for [`AppMode::Embedded`] applications, and
+    /// [`QuitMode::LastWindowClosed`] otherwise.
     #[default]
     Default,
     /// Quit automatically when the last window is closed.
@@ -257,6 +371,29 @@ pub enum QuitMode {
     Explicit,
 }
 
+/// Keeps the application from quitting when its last window closes, whatever its [`QuitMode`],
+/// until it's dropped. Created with [`App::keep_alive_guard`].
+#[must_use = "the application can quit as soon as the guard is dropped"]
+pub struct KeepAliveGuard {
+    _keep_alive: Rc<()>,
+}
+
+/// How the application integrates with the desktop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AppMode {
+    /// A standalone application, which owns the main menu, the dock or taskbar integration, and
+    /// the app-wide keyboard shortcuts.
+    #[default]
+    Standalone,
+    /// An application embedded in its host's process, such as a plugin's editor, whose host owns
+    /// the main menu, the dock and the app-wide keyboard shortcuts. GPUI leaves them to the host:
+    /// [`App::set_menus`], [`App::set_dock_menu`], [`App::add_recent_document`] and
+    /// [`App::update_jump_list`] do nothing, and on macOS, windows only handle key equivalents
+    /// while they have keyboard focus, rather than whenever their native window is the key
+    /// window. Key bindings keep working in windows that have focus.
+    Embedded,
+}
+
 #[doc(hidden)]
 #[derive(Clone, PartialEq, Eq)]
 pub struct SystemWindowTab {
@@ -562,10 +699,13 @@ pub struct App {
     pending_updates: usize,
     pub(crate) actions: Rc<ActionRegistry>,
     pub(crate) active_drag: Option<AnyDrag>,
+    pub(crate) active_drag_window: Option<AnyWindowHandle>,
     pub(crate) background_executor: BackgroundExecutor,
     pub(crate) foreground_executor: ForegroundExecutor,
     pub(crate) loading_assets: FxHashMap<(TypeId, u64), Box<dyn Any>>,
     asset_source: Arc<dyn AssetSource>,
+    image_color_management: ImageColorManagement,
+    style_classes: FxHashMap<SharedString, Rc<dyn Fn(&App) -> StyleRefinement>>,
     pub(crate) svg_renderer: SvgRenderer,
     http_client: Arc<dyn HttpClient>,
     pub(crate) globals_by_type: FxHashMap<TypeId, Box<dyn Any>>,
@@ -578,6 +718,7 @@ pub struct App {
     pub(crate) keymap: Rc<RefCell<Keymap>>,
     pub(crate) keyboard_layout: Box<dyn PlatformKeyboardLayout>,
     pub(crate) keyboard_mapper: Rc<dyn PlatformKeyboardMapper>,
+    spell_checker: Option<Rc<dyn PlatformSpellChecker>>,
     pub(crate) global_action_listeners:
         FxHashMap<TypeId, Vec<Rc<dyn Fn(&dyn Any, DispatchPhase, &mut Self)>>>,
     pending_effects: VecDeque<Effect>,
@@ -587,11 +728,19 @@ pub struct App {
     // TypeId is the type of the event that the listener callback expects
     pub(crate) event_listeners: SubscriberSet<EntityId, (TypeId, Listener)>,
     pub(crate) keystroke_observers: SubscriberSet<(), KeystrokeObserver>,
+    pub(crate) last_input_time: Instant,
+    pub(crate) user_input_observers: SubscriberSet<(), Handler>,
+    pub(crate) history_trigger: Option<SharedString>,
     pub(crate) keystroke_interceptors: SubscriberSet<(), KeystrokeObserver>,
+    pub(crate) action_interceptors: SubscriberSet<(), ActionInterceptor>,
     pub(crate) keyboard_layout_observers: SubscriberSet<(), Handler>,
     pub(crate) release_listeners: SubscriberSet<EntityId, ReleaseListener>,
     pub(crate) global_observers: SubscriberSet<TypeId, Handler>,
     pub(crate) quit_observers: SubscriberSet<(), QuitHandler>,
+    pub(crate) shutdown_handlers: SubscriberSet<(ShutdownPhase, ShutdownPriority), QuitHandler>,
+    shutdown_timeout: Duration,
+    drained_tasks: Vec<Task<()>>,
+    pending_quit_request: Option<Shared<Task<bool>>>,
     pub(crate) restart_observers: SubscriberSet<(), Handler>,
     pub(crate) restart_path: Option<PathBuf>,
     pub(crate) window_closed_observers: SubscriberSet<(), WindowClosedHandler>,
@@ -608,6 +757,10 @@ pub struct App {
     #[cfg(any(test, feature = "test-support", debug_assertions))]
     pub(crate) name: Option<&'static str>,
     quit_mode: QuitMode,
+    pub(crate) app_mode: AppMode,
+    keep_alive: Rc<()>,
+    pub(crate) frame_timestamp_source: Option<FrameTimestampSource>,
+    attached: bool,
     quitting: bool,
 }
 
@@ -629,6 +782,8 @@ impl App {
         let entities = EntityMap::new();
         let keyboard_layout = platform.keyboard_layout();
         let keyboard_mapper = platform.keyboard_mapper();
+        let spell_checker = platform.spell_checker();
+        let launch_time = executor.now();
 
         let app = Rc::new_cyclic(|this| AppCell {
             app: RefCell::new(App {
@@ -639,11 +794,14 @@ impl App {
                 flushing_effects: false,
                 pending_updates: 0,
                 active_drag: None,
+                active_drag_window: None,
                 background_executor: executor,
                 foreground_executor,
                 svg_renderer: SvgRenderer::new(asset_source.clone()),
                 loading_assets: Default::default(),
                 asset_source,
+                image_color_management: ImageColorManagement::default(),
+                style_classes: FxHashMap::default(),
                 http_client,
                 globals_by_type: FxHashMap::default(),
                 entities,
@@ -655,6 +813,7 @@ impl App {
                 keymap: Rc::new(RefCell::new(Keymap::default())),
                 keyboard_layout,
                 keyboard_mapper,
+                spell_checker,
                 global_action_listeners: FxHashMap::default(),
                 pending_effects: VecDeque::new(),
                 pending_notifications: FxHashSet::default(),
@@ -665,10 +824,18 @@ impl App {
                 event_listeners: SubscriberSet::new(),
                 release_listeners: SubscriberSet::new(),
                 keystroke_observers: SubscriberSet::new(),
+                last_input_time: launch_time,
+                user_input_observers: SubscriberSet::new(),
+                history_trigger: None,
                 keystroke_interceptors: SubscriberSet::new(),
+                action_interceptors: SubscriberSet::new(),
                 keyboard_layout_observers: SubscriberSet::new(),
                 global_observers: SubscriberSet::new(),
                 quit_observers: SubscriberSet::new(),
+                shutdown_handlers: SubscriberSet::new(),
+                shutdown_timeout: SHUTDOWN_TIMEOUT,
+                drained_tasks: Vec::new(),
+                pending_quit_request: None,
                 restart_observers: SubscriberSet::new(),
                 restart_path: None,
                 window_closed_observers: SubscriberSet::new(),
@@ -680,6 +847,10 @@ impl App {
                 #[cfg(any(feature = "inspector", debug_assertions))]
                 inspector_element_registry: InspectorElementRegistry::default(),
                 quit_mode: QuitMode::default(),
+                app_mode: AppMode::default(),
+                keep_alive: Rc::new(()),
+                frame_timestamp_source: None,
+                attached: false,
                 quitting: false,
 
                 #[cfg(any(test, feature = "test-support", debug_assertions))]
@@ -714,30 +885,101 @@ impl App {
         app
     }
 
-    /// Quit the application gracefully. Handlers registered with [`Context::on_app_quit`]
-    /// will be given 100ms to complete before exiting.
+    /// Quit the application gracefully, running each [`ShutdownPhase`] in order. Handlers registered
+    /// with [`Context::on_app_quit`] run in the first phase, and each phase is given the shutdown
+    /// timeout to complete before moving on to the next one, see [`App::set_shutdown_timeout`].
     pub fn shutdown(&mut self) {
-        let mut futures = Vec::new();
-
-        for observer in self.quit_observers.remove(&()) {
-            futures.push(observer(self));
-        }
+        self.run_shutdown_phase(ShutdownPhase::CloseWindows);
 
         self.windows.clear();
         self.window_handles.clear();
         self.flush_effects();
         self.quitting = true;
 
-        let futures = futures::future::join_all(futures);
-        if self
-            .background_executor
-            .block_with_timeout(SHUTDOWN_TIMEOUT, futures)
-            .is_err()
-        {
-            log::error!("timed out waiting on app_will_quit");
+        self.run_shutdown_phase(ShutdownPhase::FlushEntities);
+        self.run_shutdown_phase(ShutdownPhase::DrainBackgroundTasks);
+        self.quitting = false;
+    }
+
+    fn run_shutdown_phase(&mut self, phase: ShutdownPhase) {
+        let deadline = self.background_executor.now() + self.shutdown_timeout;
+        for priority in [
+            ShutdownPriority::High,
+            ShutdownPriority::Normal,
+            ShutdownPriority::Low,
+        ] {
+            let mut futures = Vec::new();
+            if priority == ShutdownPriority::Normal {
+                match phase {
+                    ShutdownPhase::CloseWindows => {
+                        for observer in self.quit_observers.remove(&()) {
+                            futures.push(observer(self));
+                        }
+                    }
+                    ShutdownPhase::FlushEntities => {}
+                    ShutdownPhase::DrainBackgroundTasks => {
+                        futures.extend(
+                            mem::take(&mut self.drained_tasks)
+                                .into_iter()
+                                .map(FutureExt::boxed_local),
+                        );
+                    }
+                }
+            }
+            for handler in self.shutdown_handlers.remove(&(phase, priority)) {
+                futures.push(handler(self));
+            }
+            self.flush_effects();
+            if futures.is_empty() {
+                continue;
+            }
+
+            let timeout = deadline.saturating_duration_since(self.background_executor.now());
+            let futures = futures::future::join_all(futures);
+            if self
+                .background_executor
+                .block_with_timeout(timeout, futures)
+                .is_err()
+            {
+                log::error!("timed out waiting on {priority:?} priority handlers of {phase:?}");
+            }
         }
+    }
 
-        self.quitting = false;
+    /// Register a callback to be invoked during the given phase of the application's shutdown.
+    /// All futures returned by callbacks with the same phase and priority are polled
+    /// concurrently, and complete before the callbacks with the next priority are invoked. Each
+    /// phase is given the shutdown timeout to complete, see [`App::set_shutdown_timeout`]. It is
+    /// not possible to cancel the shutdown at this point, see [`App::request_quit`] for that.
+    pub fn on_shutdown<Fut>(
+        &self,
+        phase: ShutdownPhase,
+        priority: ShutdownPriority,
+        on_shutdown: impl FnOnce(&mut App) -> Fut + 'static,
+    ) -> Subscription
+    where
+        Fut: 'static + Future<Output = ()>,
+    {
+        let (subscription, activate) = self.shutdown_handlers.insert(
+            (phase, priority),
+            Box::new(move |cx| on_shutdown(cx).boxed_local()),
+        );
+        activate();
+        subscription
+    }
+
+    /// Set how long each [`ShutdownPhase`] is given to complete before the next one starts.
+    /// Defaults to [`SHUTDOWN_TIMEOUT`].
+    pub fn set_shutdown_timeout(&mut self, timeout: Duration) {
+        self.shutdown_timeout = timeout;
+    }
+
+    /// Keep the given task running until it completes or the application shuts down, awaiting it
+    /// during [`ShutdownPhase::DrainBackgroundTasks`] instead of cancelling it on quit. Use this
+    /// for background work that shouldn't be cut short, such as writing a file.
+    pub fn drain_on_shutdown(&mut self, task: Task<()>) {
+        self.drained_tasks.retain(|task| !task.is_finished());
+        self.drained_tasks.push(task);
     }
 
     /// Get the id of the current keyboard layout
@@ -750,6 +992,35 @@ impl App {
         &self.keyboard_mapper
     }
 
+    /// Get the spell checker for the text users type: the one set with
+    /// [`App::set_spell_checker`], or else the platform's, if it has one.
+    pub fn spell_checker(&self) -> Option<&Rc<dyn PlatformSpellChecker>> {
+        self.spell_checker.as_ref()
+    }
+
+    /// Replace the spell checker, such as with one backed by hunspell on platforms without one,
+    /// or with `None` to disable spell checking.
+    pub fn set_spell_checker(&mut self, spell_checker: Option<Rc<dyn PlatformSpellChecker>>) {
+        self.spell_checker = spell_checker;
+    }
+
+    /// Find the misspelled words in the given text with the [`App::spell_checker`], as byte ranges
+    /// into it. Returns no ranges without a spell checker. Decorate them with
+    /// [`misspelling_highlights`](crate::misspelling_highlights).
+    pub fn check_spelling(&self, text: &str, language: Option<&str>) -> Vec<Range<usize>> {
+        self.spell_checker
+            .as_ref()
+            .map_or_else(Vec::new, |checker| checker.check(text, language))
+    }
+
+    /// Suggest corrections for a misspelled word with the [`App::spell_checker`], the most likely
+    /// first.
+    pub fn spelling_suggestions(&self, word: &str, language: Option<&str>) -> Vec<String> {
+        self.spell_checker
+            .as_ref()
+            .map_or_else(Vec::new, |checker| checker.suggestions(word, language))
+    }
+
     /// Invokes a handler when the current keyboard layout changes
     pub fn on_keyboard_layout_change<F>(&self, mut callback: F) -> Subscription
     where
@@ -768,7 +1039,61 @@ impl App {
 
     /// Gracefully quit the application via the platform's standard routine.
     pub fn quit(&self) {
-        self.platform.quit();
+        if self.attached {
+            // The host owns the event loop, so shut down without stopping it.
+            let this = self.this.clone();
+            self.foreground_executor
+                .spawn(async move {
+                    if let Some(this) = this.upgrade() {
+                        this.borrow_mut().shutdown();
+                    }
+                })
+                .detach();
+        } else {
+            self.platform.quit();
+        }
+    }
+
+    /// Quit the application once every window approved closing, as if the user asked to close
+    /// each of them, see [`Window::on_close_requested`]. Windows are asked one at a time, and the
+    /// returned task resolves to false without quitting as soon as one of them vetoes.
+    ///
+    /// Requesting to quit again while a request is pending joins it rather than asking the
+    /// windows again, as does a window whose close request is already pending.
+    pub fn request_quit(&mut self) -> Task<bool> {
+        let request = match &self.pending_quit_request {
+            Some(request) => request.clone(),
+            None => {
+                let windows = self.windows();
+                let request = self
+                    .spawn(async move |cx| {
+                        let mut approved = true;
+                        for window in windows {
+                            let Ok(approval) = window.update(cx, |_, window, cx| {
+                                window.shared_close_request_approval(cx)
+                            }) else {
+                                continue;
+                            };
+                            if !approval.await {
+                                approved = false;
+                                break;
+                            }
+                        }
+                        cx.update(|cx| {
+                            cx.pending_quit_request = None;
+                            if approved {
+                                cx.quit();
+                            }
+                            approved
+                        })
+                        .unwrap_or(false)
+                    })
+                    .shared();
+                self.pending_quit_request = Some(request.clone());
+                request
+            }
+        };
+        self.foreground_executor.spawn(request)
     }
 
     /// Schedules all windows in the application to be redrawn. This can be called
@@ -793,11 +1118,13 @@ impl App {
             self.flushing_effects = true;
             self.flush_effects();
             self.flushing_effects = false;
+            self.history_trigger = None;
         }
         self.pending_updates -= 1;
     }
 
     /// Arrange a callback to be invoked when the given entity calls `notify` on its respective context.
+    #[track_caller]
     pub fn observe<W>(
         &mut self,
         entity: &Entity<W>,
@@ -806,7 +1133,7 @@ impl App {
     where
         W: 'static,
     {
-        self.observe_internal(entity, move |e, cx| {
+        self.observe_internal(entity, None, move |e, cx| {
             on_notify(e, cx);
             true
         })
@@ -853,15 +1180,23 @@ impl App {
             .insert(window_handle.id, tracked_entities);
     }
 
-    pub(crate) fn new_observer(&mut self, key: EntityId, value: Handler) -> Subscription {
-        let (subscription, activate) = self.observers.insert(key, value);
+    #[track_caller]
+    pub(crate) fn new_observer(
+        &mut self,
+        key: EntityId,
+        subscriber: Option<EntityId>,
+        value: Handler,
+    ) -> Subscription {
+        let (subscription, activate) = self.observers.insert_for(key, subscriber, value);
         self.defer(move |_| activate());
         subscription
     }
 
+    #[track_caller]
     pub(crate) fn observe_internal<W>(
         &mut self,
         entity: &Entity<W>,
+        subscriber: Option<EntityId>,
         mut on_notify: impl FnMut(Entity<W>, &mut App) -> bool + 'static,
     ) -> Subscription
     where
@@ -871,6 +1206,7 @@ impl App {
         let handle = entity.downgrade();
         self.new_observer(
             entity_id,
+            subscriber,
             Box::new(move |cx| {
                 if let Some(entity) = handle.upgrade() {
                     on_notify(entity, cx)
@@ -883,6 +1219,7 @@ impl App {
 
     /// Arrange for the given callback to be invoked whenever the given entity emits an event of a given type.
     /// The callback is provided a handle to the emitting entity and a reference to the emitted event.
+    #[track_caller]
     pub fn subscribe<T, Event>(
         &mut self,
         entity: &Entity<T>,
@@ -892,24 +1229,52 @@ impl App {
         T: 'static + EventEmitter<Event>,
         Event: 'static,
     {
-        self.subscribe_internal(entity, move |entity, event, cx| {
+        self.subscribe_internal(entity, None, move |entity, event, cx| {
             on_event(entity, event, cx);
             true
         })
     }
 
+    /// Report the subscriptions to entities that are likely to be leaking: those made on behalf
+    /// of an entity that has since been released, and those that have never been invoked. Each
+    /// subscription records where it was made, to help track down where it should be dropped.
+    pub fn audit_subscriptions(&self) -> SubscriptionAudit {
+        let mut audit = SubscriptionAudit::default();
+        let subscriptions = self
+            .observers
+            .audit(SubscriptionKind::Observe)
+            .into_iter()
+            .chain(self.event_listeners.audit(SubscriptionKind::Subscribe));
+        for subscription in subscriptions {
+            if subscription
+                .subscriber
+                .is_some_and(|subscriber| !self.entities.is_alive(subscriber))
+            {
+                audit.dangling.push(subscription);
+            } else if subscription.invocations == 0 {
+                audit.never_fired.push(subscription);
+            }
+        }
+        audit
+    }
+
+    #[track_caller]
     pub(crate) fn new_subscription(
         &mut self,
         key: EntityId,
+        subscriber: Option<EntityId>,
         value: (TypeId, Listener),
     ) -> Subscription {
-        let (subscription, activate) = self.event_listeners.insert(key, value);
+        let (subscription, activate) = self.event_listeners.insert_for(key, subscriber, value);
         self.defer(move |_| activate());
         subscription
     }
+
+    #[track_caller]
     pub(crate) fn subscribe_internal<T, Evt>(
         &mut self,
         entity: &Entity<T>,
+        subscriber: Option<EntityId>,
         mut on_event: impl FnMut(Entity<T>, &Evt, &mut App) -> bool + 'static,
     ) -> Subscription
     where
@@ -920,6 +1285,7 @@ impl App {
         let handle = entity.downgrade();
         self.new_subscription(
             entity_id,
+            subscriber,
             (
                 TypeId::of::<Evt>(),
                 Box::new(move |event, cx| {
@@ -958,6 +1324,21 @@ impl App {
         self.platform.active_window()
     }
 
+    /// Dispatch a mouse move event to the active window at the cursor's current position, at
+    /// the end of the current effect cycle. This updates hover states and lets drag handlers
+    /// observe the cursor when it moved without the platform reporting it, such as when content
+    /// scrolled under a stationary cursor. Use [`Window::post_synthetic_mouse_move`] to target
+    /// another window, such as one whose cursor was moved with [`Window::warp_cursor`].
+    pub fn post_synthetic_mouse_move(&mut self) {
+        self.defer(|cx| {
+            if let Some(window) = cx.active_window() {
+                window
+                    .update(cx, |_, window, cx| window.dispatch_synthetic_mouse_move(cx))
+                    .ok();
+            }
+        });
+    }
+
     /// Opens a new window with the given option and the root view returned by the given function.
     /// The function is invoked with a `Window`, which can be used to interact with window-specific
     /// functionality.
@@ -966,6 +1347,20 @@ impl App {
         options: crate::WindowOptions,
         build_root_view: impl FnOnce(&mut Window, &mut App) -> Entity<V>,
     ) -> anyhow::Result<WindowHandle<V>> {
+        self.try_open_window(options, build_root_view)
+            .map_err(|error| match error {
+                WindowOpenError::Platform(error) => error,
+                error => error.into(),
+            })
+    }
+
+    /// Like [`App::open_window`], but tells the caller why the window couldn't be opened, so that
+    /// hosts embedding GPUI can report the failure instead of treating it as opaque.
+    pub fn try_open_window<V: 'static + Render>(
+        &mut self,
+        options: crate::WindowOptions,
+        build_root_view: impl FnOnce(&mut Window, &mut App) -> Entity<V>,
+    ) -> Result<WindowHandle<V>, WindowOpenError> {
         self.update(|cx| {
             let id = cx.windows.insert(None);
             let handle = WindowHandle::new(id);
@@ -990,7 +1385,7 @@ impl App {
                 }
                 Err(e) => {
                     cx.windows.remove(id);
-                    Err(e)
+                    Err(WindowOpenError::from_platform(e))
                 }
             }
         })
@@ -1199,6 +1594,37 @@ impl App {
         self.quit_mode = mode;
     }
 
+    /// Keep the application from quitting when its last window closes, for as long as the
+    /// returned guard is held, such as a plugin keeping the app its editors share alive while
+    /// the host has the plugin loaded, so its editor can be closed and reopened. It doesn't
+    /// prevent [`App::quit`].
+    pub fn keep_alive_guard(&self) -> KeepAliveGuard {
+        KeepAliveGuard {
+            _keep_alive: self.keep_alive.clone(),
+        }
+    }
+
+    /// How the application integrates with the desktop, as configured with
+    /// [`Application::with_mode`].
+    pub fn app_mode(&self) -> AppMode {
+        self.app_mode
+    }
+
+    /// Read the timestamps of every window's frames from the given clock rather than
+    /// [`Instant::now`], such as a plugin mapping its host's transport position onto
+    /// `Instant`s. Animations, [`Window::frame_timestamp`] and the timing passed to
+    /// [`Window::on_next_frame_with_timing`] all follow it. Windows opened afterwards use the
+    /// clock, and their animations start from its time when they open.
+    pub fn set_frame_clock(&mut self, clock: impl Fn() -> Instant + 'static) {
+        self.frame_timestamp_source = Some(Rc::new(clock));
+    }
+
+    /// Read the timestamps of windows opened afterwards from [`Instant::now`] again, after
+    /// [`App::set_frame_clock`].
+    pub fn reset_frame_clock(&mut self) {
+        self.frame_timestamp_source = None;
+    }
+
     /// Returns the SVG renderer used by the application.
     pub fn svg_renderer(&self) -> SvgRenderer {
         self.svg_renderer.clone()
@@ -1267,7 +1693,10 @@ impl App {
                     .values()
                     .filter_map(|window| {
                         let window = window.as_deref()?;
-                        window.invalidator.is_dirty().then_some(window.handle)
+                        // Windows driven externally only draw when their host renders a frame.
+                        (window.invalidator.is_dirty()
+                            && window.frame_driver == crate::FrameDriver::Platform)
+                            .then_some(window.handle)
                     })
                     .collect::<Vec<_>>()
                 {
@@ -1334,15 +1763,11 @@ impl App {
     }
 
     fn apply_emit_effect(&mut self, emitter: EntityId, event_type: TypeId, event: Box<dyn Any>) {
-        self.event_listeners
-            .clone()
-            .retain(&emitter, |(stored_type, handler)| {
-                if *stored_type == event_type {
-                    handler(event.as_ref(), self)
-                } else {
-                    true
-                }
-            });
+        self.event_listeners.clone().retain_matching(
+            &emitter,
+            |(stored_type, _)| *stored_type == event_type,
+            |(_, handler)| handler(event.as_ref(), self),
+        );
     }
 
     fn apply_refresh_effect(&mut self) {
@@ -1410,10 +1835,15 @@ impl App {
                 let quit_on_empty = match cx.quit_mode {
                     QuitMode::Explicit => false,
                     QuitMode::LastWindowClosed => true,
-                    QuitMode::Default => cfg!(not(target_os = "macos")),
+                    QuitMode::Default => {
+                        cfg!(not(target_os = "macos"))
+                            && !cx.attached
+                            && cx.app_mode == AppMode::Standalone
+                    }
                 };
+                let kept_alive = Rc::strong_count(&cx.keep_alive) > 1;
 
-                if quit_on_empty && cx.windows.is_empty() {
+                if quit_on_empty && !kept_alive && cx.windows.is_empty() {
                     cx.quit();
                 }
             } else {
@@ -1479,6 +1909,45 @@ impl App {
         &self.asset_source
     }
 
+    /// Returns how embedded color profiles are treated when decoding images.
+    pub fn image_color_management(&self) -> ImageColorManagement {
+        self.image_color_management
+    }
+
+    /// Set how embedded color profiles are treated when decoding images. Images that were already
+    /// decoded and are still cached keep the colors they were decoded with.
+    pub fn set_image_color_management(&mut self, color_management: ImageColorManagement) {
+        self.image_color_management = color_management;
+    }
+
+    /// Define a named style bundle that elements can apply with
+    /// [`InteractiveElement::class`](crate::InteractiveElement::class). The style is built when
+    /// an element using the class is laid out, so it can read the current theme from a global.
+    /// Redefining a class replaces it and redraws every window, which allows hot-reloading
+    /// classes loaded from disk.
+    pub fn define_style_class(
+        &mut self,
+        name: impl Into<SharedString>,
+        build: impl Fn(&App) -> StyleRefinement + 'static,
+    ) {
+        self.style_classes.insert(name.into(), Rc::new(build));
+        self.refresh_windows();
+    }
+
+    /// Remove a style class defined with [`App::define_style_class`]. Elements using it are
+    /// drawn without it from the next frame on.
+    pub fn remove_style_class(&mut self, name: &str) {
+        if self.style_classes.remove(name).is_some() {
+            self.refresh_windows();
+        }
+    }
+
+    /// Build the style of a class defined with [`App::define_style_class`], if there is one.
+    pub fn style_class(&self, name: &str) -> Option<StyleRefinement> {
+        let build = self.style_classes.get(name)?;
+        Some(build(self))
+    }
+
     /// Accessor for the text system.
     pub fn text_system(&self) -> &Arc<TextSystem> {
         &self.text_system
@@ -1710,6 +2179,94 @@ impl App {
         )
     }
 
+    /// Register a callback to be invoked with every action before it is dispatched, whether to a
+    /// window or to global action listeners. The callback can observe the action, replace it with
+    /// another action, or block it entirely, which is useful for telemetry, permission checks,
+    /// macro recording or modal input layers.
+    ///
+    /// Interceptors run in the order they were registered, and interceptors registered here run
+    /// before those registered on a window with [`Window::intercept_actions`]. Each interceptor
+    /// sees the action as replaced by the interceptors before it, and once one of them blocks the
+    /// action, the remaining interceptors aren't invoked.
+    pub fn intercept_actions(
+        &mut self,
+        f: impl FnMut(&dyn Action, &mut App) -> ActionInterception + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.action_interceptors.insert((), Box::new(f));
+        activate();
+        subscription
+    }
+
+    pub(crate) fn intercept_action(&mut self, action: &dyn Action) -> ActionInterception {
+        let interceptors = self.action_interceptors.clone();
+        run_action_interceptors(&interceptors, action, None, |interceptor, action| {
+            interceptor(action, self)
+        })
+    }
+
+    /// The time at which the user last provided input to any of the application's windows.
+    /// Before any input is received, this is the time the application started.
+    pub fn last_input_time(&self) -> Instant {
+        self.last_input_time
+    }
+
+    /// How long it has been since the user last interacted with the system as a whole, in any
+    /// application. Returns `None` on platforms where this can't be determined.
+    pub fn system_idle_time(&self) -> Option<Duration> {
+        self.platform.system_idle_time()
+    }
+
+    /// Register a callback to be invoked once the user has provided no input to the application
+    /// for the given duration. After firing, the callback is invoked again only after further
+    /// input followed by another period of inactivity. Useful for dimming interfaces, pausing
+    /// expensive animations or triggering autosave.
+    pub fn on_user_idle(
+        &self,
+        duration: Duration,
+        callback: impl FnMut(&mut App) + 'static,
+    ) -> Subscription {
+        let callback: Rc<RefCell<dyn FnMut(&mut App)>> = Rc::new(RefCell::new(callback));
+        let armed = Rc::new(Cell::new(false));
+        let mut _timer = self.arm_user_idle_timer(duration, callback.clone(), armed.clone());
+        let (subscription, activate) = self.user_input_observers.insert(
+            (),
+            Box::new(move |cx| {
+                // A pending timer accounts for input that arrived while it waited when it wakes.
+                if !armed.get() {
+                    _timer = cx.arm_user_idle_timer(duration, callback.clone(), armed.clone());
+                }
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    fn arm_user_idle_timer(
+        &self,
+        duration: Duration,
+        callback: Rc<RefCell<dyn FnMut(&mut App)>>,
+        armed: Rc<Cell<bool>>,
+    ) -> Task<()> {
+        armed.set(true);
+        self.spawn(async move |cx| {
+            loop {
+                let Ok((last_input_time, now)) =
+                    cx.update(|cx| (cx.last_input_time, cx.background_executor.now()))
+                else {
+                    return;
+                };
+                let idle_at = last_input_time + duration;
+                if now >= idle_at {
+                    break;
+                }
+                cx.background_executor().timer(idle_at - now).await;
+            }
+            armed.set(false);
+            cx.update(|cx| (callback.borrow_mut())(cx)).ok();
+        })
+    }
+
     /// Register key bindings.
     pub fn bind_keys(&mut self, bindings: impl IntoIterator<Item = KeyBinding>) {
         self.keymap.borrow_mut().add_bindings(bindings);
@@ -1874,7 +2431,11 @@ impl App {
     }
 
     /// Sets the menu bar for this application. This will replace any existing menu bar.
+    /// Does nothing in [`AppMode::Embedded`], where the menu bar belongs to the host.
     pub fn set_menus(&self, menus: Vec<Menu>) {
+        if self.app_mode == AppMode::Embedded {
+            return;
+        }
         self.platform.set_menus(menus, &self.keymap.borrow());
     }
 
@@ -1883,8 +2444,12 @@ impl App {
         self.platform.get_menus()
     }
 
-    /// Sets the right click menu for the app icon in the dock
+    /// Sets the right click menu for the app icon in the dock.
+    /// Does nothing in [`AppMode::Embedded`], where the dock icon belongs to the host.
     pub fn set_dock_menu(&self, menus: Vec<MenuItem>) {
+        if self.app_mode == AppMode::Embedded {
+            return;
+        }
         self.platform.set_dock_menu(menus, &self.keymap.borrow())
     }
 
@@ -1897,17 +2462,26 @@ impl App {
     /// The list is usually shown on the application icon's context menu in the dock,
     /// and allows to open the recent files via that context menu.
     /// If the path is already in the list, it will be moved to the bottom of the list.
+    /// Does nothing in [`AppMode::Embedded`], where the list belongs to the host.
     pub fn add_recent_document(&self, path: &Path) {
+        if self.app_mode == AppMode::Embedded {
+            return;
+        }
         self.platform.add_recent_document(path);
     }
 
     /// Updates the jump list with the updated list of recent paths for the application, only used on Windows for now.
     /// Note that this also sets the dock menu on Windows.
+    /// Does nothing in [`AppMode::Embedded`], where the jump list belongs to the host, returning
+    /// no entries.
     pub fn update_jump_list(
         &self,
         menus: Vec<MenuItem>,
         entries: Vec<SmallVec<[PathBuf; 2]>>,
     ) -> Vec<SmallVec<[PathBuf; 2]>> {
+        if self.app_mode == AppMode::Embedded {
+            return Vec::new();
+        }
         self.platform.update_jump_list(menus, entries)
     }
 
@@ -1926,7 +2500,18 @@ impl App {
     }
 
     fn dispatch_global_action(&mut self, action: &dyn Action) {
+        let replacement = match self.intercept_action(action) {
+            ActionInterception::Continue => None,
+            ActionInterception::Replace(replacement) => Some(replacement),
+            ActionInterception::Block => {
+                self.propagate_event = false;
+                return;
+            }
+        };
+        let action = replacement.as_deref().unwrap_or(action);
+
         self.propagate_event = true;
+        self.history_trigger = Some(SharedString::new_static(action.name()));
 
         if let Some(mut global_listeners) = self
             .global_action_listeners
@@ -1977,6 +2562,12 @@ impl App {
         self.active_drag.is_some()
     }
 
+    /// The window the cursor is over while something is being dragged, which draws the dragged
+    /// element. Drags can move between the app's windows and be dropped in any of them.
+    pub fn active_drag_window(&self) -> Option<AnyWindowHandle> {
+        self.active_drag.as_ref().and(self.active_drag_window)
+    }
+
     /// Gets the cursor style of the currently active drag operation.
     pub fn active_drag_cursor_style(&self) -> Option<CursorStyle> {
         self.active_drag.as_ref().and_then(|drag| drag.cursor_style)
@@ -2456,9 +3047,22 @@ impl<'a, T> Drop for GpuiBorrow<'a, T> {
 
 #[cfg(test)]
 mod test {
-    use std::{cell::RefCell, rc::Rc};
-
-    use crate::{AppContext, TestAppContext};
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering::SeqCst},
+        },
+        time::Duration,
+    };
+
+    use crate::{
+        AppContext, AppMode, Application, Context, Entity, InteractiveElement, IntoElement, Menu,
+        Modifiers, MouseButton, MouseDownEvent, MouseMoveEvent, ParentElement, PlatformInput,
+        QuitMode, Render, ShutdownPhase, ShutdownPriority, Styled, TestAppContext, VisualContext,
+        Window, WindowOpenError, div, point, px, size,
+    };
 
     #[test]
     fn test_gpui_borrow() {
@@ -2490,4 +3094,441 @@ mod test {
 
         assert_eq!(*observation_count.borrow(), 2);
     }
+
+    #[crate::test]
+    fn test_on_user_idle(cx: &mut TestAppContext) {
+        let idle_count = Rc::new(RefCell::new(0));
+        let _subscription = cx.update(|cx| {
+            cx.on_user_idle(Duration::from_secs(5), {
+                let idle_count = idle_count.clone();
+                move |_| *idle_count.borrow_mut() += 1
+            })
+        });
+
+        cx.executor().advance_clock(Duration::from_secs(4));
+        assert_eq!(*idle_count.borrow(), 0);
+        cx.executor().advance_clock(Duration::from_secs(2));
+        assert_eq!(*idle_count.borrow(), 1);
+
+        // Staying idle doesn't fire the callback again.
+        cx.executor().advance_clock(Duration::from_secs(20));
+        assert_eq!(*idle_count.borrow(), 1);
+
+        let cx = cx.add_empty_window();
+        cx.simulate_keystrokes("a");
+        cx.executor().advance_clock(Duration::from_secs(4));
+        assert_eq!(*idle_count.borrow(), 1);
+        cx.executor().advance_clock(Duration::from_secs(2));
+        assert_eq!(*idle_count.borrow(), 2);
+
+        // Input while waiting postpones the callback until the user has been idle long enough.
+        cx.simulate_keystrokes("a");
+        cx.executor().advance_clock(Duration::from_secs(3));
+        cx.simulate_keystrokes("b");
+        cx.executor().advance_clock(Duration::from_secs(3));
+        assert_eq!(*idle_count.borrow(), 2);
+        cx.executor().advance_clock(Duration::from_secs(2));
+        assert_eq!(*idle_count.borrow(), 3);
+    }
+
+    #[crate::test]
+    fn test_request_quit_joins_pending_requests(cx: &mut TestAppContext) {
+        let window = cx.add_empty_window();
+        let handle = window.window_handle();
+        let requests = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = window.update(|window, _| {
+            window.on_close_requested({
+                let requests = requests.clone();
+                move |_, _| {
+                    let (tx, rx) = futures::channel::oneshot::channel();
+                    requests.borrow_mut().push(tx);
+                    async move { rx.await.unwrap_or(false) }
+                }
+            })
+        });
+
+        // The window is asked once, however many times closing and quitting are requested.
+        assert!(!window.simulate_close());
+        let first_quit = window.update(|_, cx| cx.request_quit());
+        let second_quit = window.update(|_, cx| cx.request_quit());
+        window.run_until_parked();
+        assert_eq!(requests.borrow().len(), 1);
+
+        requests.borrow_mut().pop().unwrap().send(false).unwrap();
+        window.run_until_parked();
+        assert!(!window.executor().block_test(first_quit));
+        assert!(!window.executor().block_test(second_quit));
+        assert!(window.windows().contains(&handle));
+
+        // Once the request is settled, quitting asks again.
+        let quit = window.update(|_, cx| cx.request_quit());
+        window.run_until_parked();
+        assert_eq!(requests.borrow().len(), 1);
+        requests.borrow_mut().pop().unwrap().send(true).unwrap();
+        window.run_until_parked();
+        assert!(window.executor().block_test(quit));
+    }
+
+    #[crate::test]
+    fn test_shutdown_phases(cx: &mut TestAppContext) {
+        cx.add_empty_window();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            for phase in [
+                ShutdownPhase::DrainBackgroundTasks,
+                ShutdownPhase::FlushEntities,
+                ShutdownPhase::CloseWindows,
+            ] {
+                let log = log.clone();
+                cx.on_shutdown(phase, ShutdownPriority::Normal, move |cx| {
+                    let window_count = cx.windows().len();
+                    async move { log.borrow_mut().push((phase, window_count)) }
+                })
+                .detach();
+            }
+        });
+
+        cx.quit();
+        assert_eq!(
+            *log.borrow(),
+            [
+                (ShutdownPhase::CloseWindows, 1),
+                (ShutdownPhase::FlushEntities, 0),
+                (ShutdownPhase::DrainBackgroundTasks, 0),
+            ]
+        );
+    }
+
+    #[crate::test]
+    fn test_shutdown_priorities(cx: &mut TestAppContext) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            for priority in [
+                ShutdownPriority::Low,
+                ShutdownPriority::Normal,
+                ShutdownPriority::High,
+            ] {
+                let log = log.clone();
+                cx.on_shutdown(ShutdownPhase::FlushEntities, priority, move |_| {
+                    log.borrow_mut().push(format!("invoke {priority:?}"));
+                    async move {
+                        // Yield so that handlers invoked without waiting would run in between.
+                        smol::future::yield_now().await;
+                        log.borrow_mut().push(format!("complete {priority:?}"));
+                    }
+                })
+                .detach();
+            }
+        });
+
+        cx.quit();
+        assert_eq!(
+            *log.borrow(),
+            [
+                "invoke High",
+                "complete High",
+                "invoke Normal",
+                "complete Normal",
+                "invoke Low",
+                "complete Low",
+            ]
+        );
+    }
+
+    #[crate::test]
+    fn test_drain_on_shutdown(cx: &mut TestAppContext) {
+        let drained = Arc::new(AtomicBool::new(false));
+        cx.update(|cx| {
+            let drained = drained.clone();
+            let task = cx.background_spawn(async move {
+                smol::future::yield_now().await;
+                drained.store(true, SeqCst);
+            });
+            cx.drain_on_shutdown(task);
+        });
+        assert!(!drained.load(SeqCst));
+
+        cx.quit();
+        assert!(drained.load(SeqCst));
+    }
+
+    /// A view that records the mouse events dispatched to it.
+    struct KnobView {
+        events: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl Render for KnobView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let events = self.events.clone();
+            let down_events = self.events.clone();
+            div()
+                .size(px(100.))
+                .on_mouse_move(move |_, _, _| events.borrow_mut().push("move"))
+                .on_mouse_down(MouseButton::Left, move |_, _, _| {
+                    down_events.borrow_mut().push("down")
+                })
+        }
+    }
+
+    #[crate::test]
+    fn test_low_latency_input(cx: &mut TestAppContext) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let (_, cx) = cx.add_window_view(|_, _| KnobView {
+            events: events.clone(),
+        });
+        cx.update(|window, _| window.set_low_latency_input(true));
+        cx.run_until_parked();
+
+        // The window already painted since the last frame request, so moves are coalesced
+        // until the next frame.
+        cx.simulate_mouse_move(point(px(10.), px(10.)), None, Modifiers::none());
+        cx.simulate_mouse_move(point(px(20.), px(20.)), None, Modifiers::none());
+        assert!(events.take().is_empty());
+        cx.update(|window, cx| window.flush_latched_input(cx));
+        assert_eq!(events.take(), ["move"]);
+        assert_eq!(
+            cx.update(|window, _| window.mouse_position()),
+            point(px(20.), px(20.))
+        );
+
+        // Input that can't be latched dispatches the latched input first.
+        cx.simulate_mouse_move(point(px(30.), px(30.)), None, Modifiers::none());
+        cx.simulate_mouse_down(
+            point(px(30.), px(30.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(events.take(), ["move", "down"]);
+    }
+
+    #[crate::test]
+    fn test_dispatch_embedded_input(cx: &mut TestAppContext) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let (_, cx) = cx.add_window_view(|_, _| KnobView {
+            events: events.clone(),
+        });
+        cx.update(|window, _| window.set_low_latency_input(true));
+        cx.run_until_parked();
+
+        // Moves forwarded by the host are latched like the platform's moves.
+        let position = point(px(20.), px(20.));
+        cx.update(|window, cx| {
+            window.dispatch_embedded_input(
+                PlatformInput::MouseMove(MouseMoveEvent {
+                    position,
+                    pressed_button: None,
+                    modifiers: Modifiers::none(),
+                }),
+                cx,
+            )
+        });
+        assert!(events.take().is_empty());
+
+        let result = cx.update(|window, cx| {
+            window.dispatch_embedded_input(
+                PlatformInput::MouseDown(MouseDownEvent {
+                    button: MouseButton::Left,
+                    position,
+                    modifiers: Modifiers::none(),
+                    click_count: 1,
+                    first_mouse: false,
+                }),
+                cx,
+            )
+        });
+        assert_eq!(events.take(), ["move", "down"]);
+        assert!(result.propagate);
+        assert_eq!(cx.update(|window, _| window.mouse_position()), position);
+    }
+
+    #[crate::test]
+    fn test_attached_application(cx: &mut TestAppContext) {
+        let application = Application(cx.app.clone());
+        let launched = Rc::new(Cell::new(false));
+        application.attach({
+            let launched = launched.clone();
+            move |_| launched.set(true)
+        });
+        assert!(launched.get());
+
+        let ran = Rc::new(Cell::new(false));
+        application
+            .foreground_executor()
+            .spawn({
+                let ran = ran.clone();
+                async move { ran.set(true) }
+            })
+            .detach();
+        application.pump();
+        assert!(ran.get());
+
+        // Closing the last window doesn't quit an attached application by default.
+        let shut_down = Rc::new(Cell::new(false));
+        cx.update(|cx| {
+            let shut_down = shut_down.clone();
+            cx.on_shutdown(
+                ShutdownPhase::CloseWindows,
+                ShutdownPriority::Normal,
+                move |_| async move { shut_down.set(true) },
+            )
+            .detach();
+        });
+        let window = cx.add_empty_window().window_handle();
+        window
+            .update(cx, |_, window, _| window.remove_window())
+            .unwrap();
+        application.pump();
+        assert!(!shut_down.get());
+
+        cx.update(|cx| cx.quit());
+        application.pump();
+        assert!(shut_down.get());
+    }
+
+    #[crate::test]
+    fn test_run_attaches_to_running_event_loop(cx: &mut TestAppContext) {
+        cx.simulate_running_event_loop();
+        let strong_count = Rc::strong_count(&cx.app);
+        let launched = Rc::new(Cell::new(false));
+        Application(cx.app.clone()).run({
+            let launched = launched.clone();
+            move |_| launched.set(true)
+        });
+        assert!(launched.get());
+        assert!(cx.app.borrow().attached);
+        // The runtime keeps the application alive until it's shut down.
+        assert_eq!(Rc::strong_count(&cx.app), strong_count + 1);
+
+        crate::runtime::release_thread_state();
+        assert_eq!(Rc::strong_count(&cx.app), strong_count);
+    }
+
+    #[crate::test]
+    async fn test_background_handle(cx: &mut TestAppContext) {
+        struct TestGlobal(usize);
+        impl crate::Global for TestGlobal {}
+
+        let handle = cx.update(|cx| cx.background_handle());
+        let result = std::thread::spawn(move || {
+            let result = handle.run_on_main_thread(|cx| {
+                cx.set_global(TestGlobal(1));
+                cx.global::<TestGlobal>().0 + 1
+            });
+            drop(handle);
+            result
+        })
+        .join()
+        .unwrap();
+
+        cx.run_until_parked();
+        assert_eq!(result.await.unwrap(), 2);
+        cx.read(|cx| assert_eq!(cx.global::<TestGlobal>().0, 1));
+    }
+
+    #[crate::test]
+    fn test_context_values(cx: &mut TestAppContext) {
+        #[derive(Debug, PartialEq)]
+        struct TextScale(f32);
+
+        struct Label {
+            rendered_scale: Rc<Cell<Option<f32>>>,
+        }
+
+        impl Render for Label {
+            fn render(&mut self, window: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                self.rendered_scale
+                    .set(window.context_value::<TextScale>().map(|scale| scale.0));
+                div()
+            }
+        }
+
+        struct Panel {
+            outer: Entity<Label>,
+            inner: Entity<Label>,
+        }
+
+        impl Render for Panel {
+            fn render(&mut self, window: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                assert_eq!(window.context_value::<TextScale>(), None);
+                div()
+                    .provide_context(TextScale(1.5))
+                    .child(self.outer.clone())
+                    .child(
+                        div()
+                            .provide_context(TextScale(2.))
+                            .child(self.inner.clone()),
+                    )
+            }
+        }
+
+        let outer_scale = Rc::new(Cell::new(None));
+        let inner_scale = Rc::new(Cell::new(None));
+        cx.add_window(|_, cx| Panel {
+            outer: cx.new(|_| Label {
+                rendered_scale: outer_scale.clone(),
+            }),
+            inner: cx.new(|_| Label {
+                rendered_scale: inner_scale.clone(),
+            }),
+        });
+        cx.run_until_parked();
+
+        // Each label sees the value provided by its nearest ancestor.
+        assert_eq!(outer_scale.get(), Some(1.5));
+        assert_eq!(inner_scale.get(), Some(2.));
+    }
+
+    #[test]
+    fn test_window_open_error_from_platform() {
+        let error = anyhow::Error::from(WindowOpenError::InvalidHandle("null NSView".into()))
+            .context("opening embedded window");
+        assert!(matches!(
+            WindowOpenError::from_platform(error),
+            WindowOpenError::InvalidHandle(message) if message == "null NSView"
+        ));
+
+        let error = WindowOpenError::from_platform(anyhow::anyhow!("out of memory"));
+        assert!(matches!(error, WindowOpenError::Platform(_)));
+        assert_eq!(error.to_string(), "out of memory");
+    }
+
+    #[crate::test]
+    fn test_embedded_app_mode_leaves_menus_to_host(cx: &mut TestAppContext) {
+        let file_menu = || Menu {
+            name: "File".into(),
+            items: Vec::new(),
+        };
+
+        cx.update(|cx| cx.set_menus(vec![file_menu()]));
+        assert_eq!(
+            cx.update(|cx| cx.get_menus()).map(|menus| menus.len()),
+            Some(1)
+        );
+
+        Application(cx.app.clone()).with_mode(AppMode::Embedded);
+        cx.update(|cx| cx.set_menus(vec![file_menu(), file_menu()]));
+        assert_eq!(
+            cx.update(|cx| cx.get_menus()).map(|menus| menus.len()),
+            Some(1)
+        );
+    }
+
+    #[crate::test]
+    fn test_keep_alive_guard_outlives_last_window(cx: &mut TestAppContext) {
+        cx.update(|cx| cx.set_quit_mode(QuitMode::LastWindowClosed));
+        let guard = cx.update(|cx| cx.keep_alive_guard());
+
+        let window = cx.add_empty_window().window_handle();
+        window
+            .update(cx, |_, window, _| window.remove_window())
+            .unwrap();
+        assert!(!cx.did_quit());
+
+        drop(guard);
+        let window = cx.add_empty_window().window_handle();
+        window
+            .update(cx, |_, window, _| window.remove_window())
+            .unwrap();
+        assert!(cx.did_quit());
+    }
 }
@@ -128,6 +128,29 @@ impl AsyncApp {
         Ok(())
     }
 
+    /// Process the work that GPUI has pending on the main thread, like [`Application::pump`], for
+    /// code that runs from the host's event loop with only an [`AsyncApp`] at hand, such as a
+    /// plugin's editor. It must not be called from within GPUI's own callbacks.
+    ///
+    /// [`Application::pump`]: crate::Application::pump
+    pub fn pump(&self) -> Result<()> {
+        let app = self.app.upgrade().context("app was released")?;
+        let platform = app.borrow().platform.clone();
+        platform.pump();
+        Ok(())
+    }
+
+    /// A file descriptor that becomes readable when GPUI has input or tasks pending, for hosts
+    /// that watch file descriptors in their event loop, so that they call [`AsyncApp::pump`]
+    /// when it does. Timers don't make it readable, so hosts also have to pump regularly, such
+    /// as from a timer.
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    pub fn event_fd(&self) -> Result<Option<std::os::fd::RawFd>> {
+        let app = self.app.upgrade().context("app was released")?;
+        let platform = app.borrow().platform.clone();
+        Ok(platform.event_fd())
+    }
+
     /// Get an executor which can be used to spawn futures in the background.
     pub fn background_executor(&self) -> &BackgroundExecutor {
         &self.background_executor
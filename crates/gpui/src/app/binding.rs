@@ -0,0 +1,233 @@
+use std::rc::Rc;
+
+use crate::{AnyWeakEntity, App, Entity, EntityId, Subscription, Window};
+
+/// Create a [`Binding`] to a field of an entity, which reads a copy of the field and writes
+/// values back to it, notifying the entity's observers.
+///
+/// For example:
+///
+/// ```
+/// # use gpui::{App, AppContext as _, Binding, bind};
+/// struct Settings {
+///     font_size: f32,
+/// }
+///
+/// fn increase_font_size(cx: &mut App) {
+///     let settings = cx.new(|_| Settings { font_size: 12. });
+///     let font_size: Binding<f32> = bind!(settings.font_size);
+///     font_size.update(cx, |font_size| *font_size += 1.);
+/// }
+/// ```
+///
+/// The field can be nested, as in `bind!(settings.editor.font_size)`. When the entity isn't
+/// a plain variable, pass it as an expression followed by a comma, as in
+/// `bind!(self.settings, font_size)`.
+#[macro_export]
+macro_rules! bind {
+    ($entity:ident . $($field:tt).+) => {
+        $crate::bind!($entity, $($field).+)
+    };
+    ($entity:expr, $($field:tt).+) => {
+        $crate::Binding::new(
+            &$entity,
+            |this| ::std::clone::Clone::clone(&this.$($field).+),
+            |this, value| this.$($field).+ = value,
+        )
+    };
+}
+
+/// A two-way binding to a value held by an entity, usually created with [`bind!`]. Widgets
+/// that edit a value can accept a binding to read the value when rendering and write changes
+/// back, without each form or settings panel wiring up its own listeners.
+///
+/// Reading a binding while rendering a view makes the view depend on the bound entity, and
+/// writing to it notifies the entity, so the view is redrawn with the new value.
+pub struct Binding<T> {
+    entity: AnyWeakEntity,
+    get: Rc<dyn Fn(&App) -> Option<T>>,
+    set: Rc<dyn Fn(T, &mut App)>,
+}
+
+impl<T> Clone for Binding<T> {
+    fn clone(&self) -> Self {
+        Self {
+            entity: self.entity.clone(),
+            get: self.get.clone(),
+            set: self.set.clone(),
+        }
+    }
+}
+
+impl<T: 'static> Binding<T> {
+    /// Bind to a value of the given entity, which is read with `get` and written with `set`.
+    pub fn new<E: 'static>(
+        entity: &Entity<E>,
+        get: impl Fn(&E) -> T + 'static,
+        set: impl Fn(&mut E, T) + 'static,
+    ) -> Self {
+        let read_entity = entity.downgrade();
+        let write_entity = entity.downgrade();
+        Self {
+            entity: entity.downgrade().into(),
+            get: Rc::new(move |cx| Some(get(read_entity.upgrade()?.read(cx)))),
+            set: Rc::new(move |value, cx| {
+                write_entity
+                    .update(cx, |entity, cx| {
+                        set(entity, value);
+                        cx.notify();
+                    })
+                    .ok();
+            }),
+        }
+    }
+
+    /// The id of the bound entity.
+    pub fn entity_id(&self) -> EntityId {
+        self.entity.entity_id()
+    }
+
+    /// Read the bound value, or `None` if the entity has been released.
+    pub fn get(&self, cx: &App) -> Option<T> {
+        (self.get)(cx)
+    }
+
+    /// Write a value back to the entity and notify its observers. Does nothing if the entity
+    /// has been released.
+    pub fn set(&self, value: T, cx: &mut App) {
+        (self.set)(value, cx)
+    }
+
+    /// Register a callback to be invoked in the given window whenever the bound entity is
+    /// notified, so a widget that keeps its own copy of the value can pick up changes made
+    /// elsewhere. The callback stops when the entity or the window is released.
+    pub fn observe(
+        &self,
+        window: &Window,
+        cx: &mut App,
+        mut on_notify: impl FnMut(&mut Window, &mut App) + 'static,
+    ) -> Subscription {
+        let entity = self.entity.clone();
+        let window_handle = window.handle;
+        cx.new_observer(
+            self.entity.entity_id(),
+            None,
+            Box::new(move |cx| {
+                if entity.upgrade().is_none() {
+                    return false;
+                }
+                window_handle
+                    .update(cx, |_, window, cx| on_notify(window, cx))
+                    .is_ok()
+            }),
+        )
+    }
+
+    /// Modify the bound value in place and write it back to the entity.
+    pub fn update(&self, cx: &mut App, update: impl FnOnce(&mut T)) {
+        if let Some(mut value) = self.get(cx) {
+            update(&mut value);
+            self.set(value, cx);
+        }
+    }
+
+    /// Adapt this binding to a widget that edits values of another type, converting values
+    /// read from the entity with `to` and values written back with `from`.
+    pub fn map<U: 'static>(
+        self,
+        to: impl Fn(T) -> U + 'static,
+        from: impl Fn(U) -> T + 'static,
+    ) -> Binding<U> {
+        let get = self.get;
+        let set = self.set;
+        Binding {
+            entity: self.entity,
+            get: Rc::new(move |cx| get(cx).map(&to)),
+            set: Rc::new(move |value, cx| set(from(value), cx)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::{AppContext as _, TestAppContext};
+
+    struct Settings {
+        editor: EditorSettings,
+        theme: String,
+    }
+
+    struct EditorSettings {
+        font_size: f32,
+    }
+
+    #[crate::test]
+    fn test_binding(cx: &mut TestAppContext) {
+        let settings = cx.new(|_| Settings {
+            editor: EditorSettings { font_size: 12. },
+            theme: "One Dark".into(),
+        });
+        let notifications = Rc::new(Cell::new(0));
+        cx.update(|cx| {
+            let notifications = notifications.clone();
+            cx.observe(&settings, move |_, _| {
+                notifications.set(notifications.get() + 1)
+            })
+            .detach();
+        });
+
+        let font_size = bind!(settings.editor.font_size);
+        let theme = bind!(settings.clone(), theme);
+        assert_eq!(cx.update(|cx| font_size.get(cx)), Some(12.));
+        assert_eq!(cx.update(|cx| theme.get(cx)).as_deref(), Some("One Dark"));
+
+        cx.update(|cx| font_size.set(14., cx));
+        assert_eq!(
+            settings.read_with(cx, |settings, _| settings.editor.font_size),
+            14.
+        );
+        assert_eq!(notifications.get(), 1);
+
+        cx.update(|cx| theme.update(cx, |theme| theme.push_str(" Pro")));
+        assert_eq!(
+            settings.read_with(cx, |settings, _| settings.theme.clone()),
+            "One Dark Pro"
+        );
+        assert_eq!(notifications.get(), 2);
+
+        let percent = font_size.map(|size| (size * 10.) as u32, |percent| percent as f32 / 10.);
+        assert_eq!(cx.update(|cx| percent.get(cx)), Some(140));
+        cx.update(|cx| percent.set(160, cx));
+        assert_eq!(
+            settings.read_with(cx, |settings, _| settings.editor.font_size),
+            16.
+        );
+
+        drop(settings);
+        assert_eq!(cx.update(|cx| percent.get(cx)), None);
+        cx.update(|cx| percent.set(100, cx));
+    }
+
+    #[crate::test]
+    fn test_binding_observe(cx: &mut TestAppContext) {
+        let settings = cx.new(|_| Settings {
+            editor: EditorSettings { font_size: 12. },
+            theme: "One Dark".into(),
+        });
+        let font_size = bind!(settings.editor.font_size);
+        let observed = Rc::new(Cell::new(None));
+        let cx = cx.add_empty_window();
+        let _subscription = cx.update(|window, cx| {
+            let observed = observed.clone();
+            let read_font_size = font_size.clone();
+            font_size.observe(window, cx, move |_, cx| {
+                observed.set(read_font_size.get(cx))
+            })
+        });
+
+        cx.update(|_, cx| font_size.set(14., cx));
+        assert_eq!(observed.get(), Some(14.));
+    }
+}
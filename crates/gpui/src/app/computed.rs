@@ -0,0 +1,181 @@
+use std::{mem, rc::Rc};
+
+use collections::FxHashSet;
+
+use crate::{App, AppContext as _, Entity, EntityId, Subscription};
+
+/// A value derived from the state of other entities, created with [`App::computed`].
+///
+/// The value is computed when it's created and memoized until one of the entities read while
+/// computing it notifies, at which point it is recomputed on the next read. Dependencies are
+/// tracked automatically on each computation, so a value that only reads some entities
+/// conditionally only depends on the ones it read last time.
+///
+/// Reading a computed value while rendering a view makes the view depend on it like on any
+/// other entity, so the view is redrawn whenever the value is invalidated.
+pub struct Computed<T> {
+    state: Entity<ComputedState<T>>,
+}
+
+struct ComputedState<T> {
+    compute: Rc<dyn Fn(&App) -> T>,
+    value: T,
+    dirty: bool,
+    dependencies: FxHashSet<EntityId>,
+    _observe_dependencies: Vec<Subscription>,
+}
+
+impl<T> Clone for Computed<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl App {
+    /// Create a value derived from the state of other entities, which is recomputed lazily
+    /// when any of the entities read by `compute` notify. See [`Computed`].
+    pub fn computed<T: 'static>(&mut self, compute: impl Fn(&App) -> T + 'static) -> Computed<T> {
+        let compute: Rc<dyn Fn(&App) -> T> = Rc::new(compute);
+        let (value, dependencies) = compute_tracked(&compute, self);
+        let computed = Computed {
+            state: self.new(|_| ComputedState {
+                compute,
+                value,
+                dirty: false,
+                dependencies: FxHashSet::default(),
+                _observe_dependencies: Vec::new(),
+            }),
+        };
+        computed.set_dependencies(dependencies, self);
+        computed
+    }
+}
+
+impl<T: 'static> Computed<T> {
+    /// Read the value, computing it first if it has been invalidated.
+    pub fn read<'a>(&self, cx: &'a mut App) -> &'a T {
+        if self.state.read(cx).dirty {
+            let compute = self.state.read(cx).compute.clone();
+            let (value, dependencies) = compute_tracked(&compute, cx);
+            self.state.update(cx, |state, _| {
+                state.value = value;
+                state.dirty = false;
+            });
+            self.set_dependencies(dependencies, cx);
+        }
+        &self.state.read(cx).value
+    }
+
+    /// Read a copy of the value, computing it first if it has been invalidated.
+    pub fn get(&self, cx: &mut App) -> T
+    where
+        T: Clone,
+    {
+        self.read(cx).clone()
+    }
+
+    /// Whether the value has been invalidated and will be recomputed on the next read.
+    pub fn is_dirty(&self, cx: &App) -> bool {
+        self.state.read(cx).dirty
+    }
+
+    /// The entities the value depended on when it was last computed.
+    pub fn dependencies<'a>(&self, cx: &'a App) -> impl Iterator<Item = EntityId> + 'a {
+        self.state.read(cx).dependencies.iter().copied()
+    }
+
+    fn set_dependencies(&self, dependencies: FxHashSet<EntityId>, cx: &mut App) {
+        self.state.update(cx, |state, cx| {
+            if state.dependencies != dependencies {
+                state._observe_dependencies = dependencies
+                    .iter()
+                    .map(|dependency| observe_dependency(*dependency, &self.state, cx))
+                    .collect();
+                state.dependencies = dependencies;
+            }
+        });
+    }
+}
+
+/// Track the entities read by a computation alone, restoring those read so far by the caller,
+/// which will depend on the computed value rather than on its dependencies.
+fn compute_tracked<T>(compute: &Rc<dyn Fn(&App) -> T>, cx: &mut App) -> (T, FxHashSet<EntityId>) {
+    let accessed = mem::take(&mut *cx.entities.accessed_entities.borrow_mut());
+    let value = compute(cx);
+    let dependencies = mem::replace(&mut *cx.entities.accessed_entities.borrow_mut(), accessed);
+    (value, dependencies)
+}
+
+fn observe_dependency<T: 'static>(
+    dependency: EntityId,
+    state: &Entity<ComputedState<T>>,
+    cx: &mut App,
+) -> Subscription {
+    let state = state.downgrade();
+    cx.new_observer(
+        dependency,
+        Some(state.entity_id()),
+        Box::new(move |cx| {
+            state
+                .update(cx, |state, cx| {
+                    if !mem::replace(&mut state.dirty, true) {
+                        cx.notify();
+                    }
+                })
+                .is_ok()
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::{AppContext as _, TestAppContext};
+
+    #[crate::test]
+    fn test_computed(cx: &mut TestAppContext) {
+        let a = cx.new(|_| 1);
+        let b = cx.new(|_| 2);
+        let computations = Rc::new(Cell::new(0));
+        let sum = cx.update(|cx| {
+            let a = a.clone();
+            let b = b.clone();
+            let computations = computations.clone();
+            cx.computed(move |cx| {
+                computations.set(computations.get() + 1);
+                let a = *a.read(cx);
+                if a > 0 { a + *b.read(cx) } else { a }
+            })
+        });
+
+        assert_eq!(cx.update(|cx| sum.get(cx)), 3);
+        assert_eq!(cx.update(|cx| sum.get(cx)), 3);
+        assert_eq!(computations.get(), 1);
+
+        // Values are recomputed lazily.
+        b.update(cx, |b, cx| {
+            *b = 5;
+            cx.notify();
+        });
+        assert!(cx.update(|cx| sum.is_dirty(cx)));
+        assert_eq!(computations.get(), 1);
+        assert_eq!(cx.update(|cx| sum.get(cx)), 6);
+        assert_eq!(computations.get(), 2);
+
+        // Once `b` is no longer read, it is no longer a dependency.
+        a.update(cx, |a, cx| {
+            *a = -1;
+            cx.notify();
+        });
+        assert_eq!(cx.update(|cx| sum.get(cx)), -1);
+        b.update(cx, |b, cx| {
+            *b = 10;
+            cx.notify();
+        });
+        assert!(!cx.update(|cx| sum.is_dirty(cx)));
+        assert_eq!(computations.get(), 3);
+    }
+}
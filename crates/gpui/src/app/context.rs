@@ -1,7 +1,8 @@
 use crate::{
     AnyView, AnyWindowHandle, AppContext, AsyncApp, DispatchPhase, Effect, EntityId, EventEmitter,
-    FocusHandle, FocusOutEvent, Focusable, Global, KeystrokeObserver, Reservation, SubscriberSet,
-    Subscription, Task, WeakEntity, WeakFocusHandle, Window, WindowHandle,
+    FocusHandle, FocusOutEvent, Focusable, Global, KeystrokeObserver, Reservation, ShutdownPhase,
+    ShutdownPriority, SubscriberSet, Subscription, Task, WeakEntity, WeakFocusHandle, Window,
+    WindowHandle, WindowState,
 };
 use anyhow::Result;
 use futures::FutureExt;
@@ -60,6 +61,7 @@ impl<'a, T: 'static> Context<'a, T> {
 
     /// Arranges for the given function to be called whenever [`Context::notify`] is
     /// called with the given entity.
+    #[track_caller]
     pub fn observe<W>(
         &mut self,
         entity: &Entity<W>,
@@ -70,7 +72,8 @@ impl<'a, T: 'static> Context<'a, T> {
         W: 'static,
     {
         let this = self.weak_entity();
-        self.app.observe_internal(entity, move |e, cx| {
+        let subscriber = Some(this.entity_id());
+        self.app.observe_internal(entity, subscriber, move |e, cx| {
             if let Some(this) = this.upgrade() {
                 this.update(cx, |this, cx| on_notify(this, e, cx));
                 true
@@ -95,6 +98,7 @@ impl<'a, T: 'static> Context<'a, T> {
     }
 
     /// Subscribe to an event type from another entity
+    #[track_caller]
     pub fn subscribe<T2, Evt>(
         &mut self,
         entity: &Entity<T2>,
@@ -106,14 +110,16 @@ impl<'a, T: 'static> Context<'a, T> {
         Evt: 'static,
     {
         let this = self.weak_entity();
-        self.app.subscribe_internal(entity, move |e, event, cx| {
-            if let Some(this) = this.upgrade() {
-                this.update(cx, |this, cx| on_event(this, e, event, cx));
-                true
-            } else {
-                false
-            }
-        })
+        let subscriber = Some(this.entity_id());
+        self.app
+            .subscribe_internal(entity, subscriber, move |e, event, cx| {
+                if let Some(this) = this.upgrade() {
+                    this.update(cx, |this, cx| on_event(this, e, event, cx));
+                    true
+                } else {
+                    false
+                }
+            })
     }
 
     /// Subscribe to an event type from ourself
@@ -225,6 +231,29 @@ impl<'a, T: 'static> Context<'a, T> {
         })
     }
 
+    /// Arrange for the given function to be invoked during the given phase of the application's
+    /// shutdown, see [`App::on_shutdown`].
+    pub fn on_shutdown<Fut>(
+        &self,
+        phase: ShutdownPhase,
+        priority: ShutdownPriority,
+        on_shutdown: impl FnOnce(&mut T, &mut Context<T>) -> Fut + 'static,
+    ) -> Subscription
+    where
+        Fut: 'static + Future<Output = ()>,
+        T: 'static,
+    {
+        let handle = self.weak_entity();
+        self.app.on_shutdown(phase, priority, move |cx| {
+            let future = handle.update(cx, on_shutdown).ok();
+            async move {
+                if let Some(future) = future {
+                    future.await;
+                }
+            }
+        })
+    }
+
     /// Tell GPUI that this entity has changed and observers of it should be notified.
     pub fn notify(&mut self) {
         self.app.notify(self.entity_state.entity_id);
@@ -314,6 +343,7 @@ impl<'a, T: 'static> Context<'a, T> {
     }
 
     /// Observe another entity for changes to its state, as tracked by [`Context::notify`].
+    #[track_caller]
     pub fn observe_in<V2>(
         &mut self,
         observed: &Entity<V2>,
@@ -330,6 +360,7 @@ impl<'a, T: 'static> Context<'a, T> {
         let observer = self.weak_entity();
         self.new_observer(
             observed_id,
+            Some(observer.entity_id()),
             Box::new(move |cx| {
                 window_handle
                     .update(cx, |_, window, cx| {
@@ -352,6 +383,7 @@ impl<'a, T: 'static> Context<'a, T> {
     /// Subscribe to events emitted by another entity.
     /// The entity to which you're subscribing must implement the [`EventEmitter`] trait.
     /// The callback will be invoked with a reference to the current view, a handle to the emitting `Entity`, the event, a mutable reference to the `Window`, and the context for the entity.
+    #[track_caller]
     pub fn subscribe_in<Emitter, Evt>(
         &mut self,
         emitter: &Entity<Emitter>,
@@ -367,6 +399,7 @@ impl<'a, T: 'static> Context<'a, T> {
         let subscriber = self.weak_entity();
         self.new_subscription(
             emitter.entity_id(),
+            Some(subscriber.entity_id()),
             (
                 TypeId::of::<Evt>(),
                 Box::new(move |event, cx| {
@@ -479,6 +512,44 @@ impl<'a, T: 'static> Context<'a, T> {
         subscription
     }
 
+    /// Registers a callback to be invoked when the state of the host's native window of an
+    /// embedded window changes, see [`Window::host_info`].
+    pub fn observe_host_info(
+        &self,
+        window: &mut Window,
+        mut callback: impl FnMut(&mut T, &mut Window, &mut Context<T>) + 'static,
+    ) -> Subscription {
+        let view = self.weak_entity();
+        let (subscription, activate) = window.host_info_observers.insert(
+            (),
+            Box::new(move |window, cx| {
+                view.update(cx, |view, cx| callback(view, window, cx))
+                    .is_ok()
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Registers a callback to be invoked when the window is minimized, maximized, made
+    /// fullscreen, tiled or restored.
+    pub fn observe_window_state(
+        &self,
+        window: &mut Window,
+        mut callback: impl FnMut(&mut T, WindowState, &mut Window, &mut Context<T>) + 'static,
+    ) -> Subscription {
+        let view = self.weak_entity();
+        let (subscription, activate) = window.window_state_observers.insert(
+            (),
+            Box::new(move |window_state, window, cx| {
+                view.update(cx, |view, cx| callback(view, window_state, window, cx))
+                    .is_ok()
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Register a callback to be invoked when a keystroke is received by the application
     /// in any window. Note that this fires after all other action and event mechanisms have resolved
     /// and that this API will not be invoked if the event's propagation is stopped.
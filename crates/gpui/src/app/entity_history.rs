@@ -0,0 +1,308 @@
+use std::time::Instant;
+
+use serde::{Serialize, de::DeserializeOwned};
+use util::ResultExt as _;
+
+use crate::{
+    App, AppContext as _, Context, Entity, FontWeight, InteractiveElement, IntoElement,
+    ParentElement, PlatformInput, Render, SharedString, StatefulInteractiveElement, Styled,
+    Subscription, WeakEntity, Window, div, hsla, prelude::FluentBuilder as _, white,
+};
+
+/// The state of an entity at one point in its [`EntityHistory`].
+#[derive(Clone, Debug)]
+pub struct EntitySnapshot {
+    /// The entity's state, serialized as JSON.
+    pub state: serde_json::Value,
+    /// The name of the action or input event being dispatched when the entity changed, if the
+    /// change happened while dispatching one.
+    pub trigger: Option<SharedString>,
+    /// When the snapshot was taken.
+    pub timestamp: Instant,
+}
+
+/// A record of the states an entity has been in, for stepping backward and forward through
+/// them while debugging. See [`App::record_entity_history`].
+///
+/// A snapshot is taken each time the entity notifies its observers with a state that differs
+/// from the current snapshot. Stepping through the history restores the entity to the selected
+/// snapshot, and changing the entity after stepping back discards the snapshots after it, as
+/// an undo stack would. Rendering the history as a view shows the snapshots with their
+/// triggers, and buttons to step through them.
+pub struct EntityHistory<T> {
+    entity: WeakEntity<T>,
+    snapshots: Vec<EntitySnapshot>,
+    cursor: usize,
+    capacity: usize,
+    _observe_entity: Subscription,
+}
+
+impl App {
+    /// Start recording the history of the given entity's state, keeping at most `capacity`
+    /// snapshots. The entity's state is serialized on each notification, so this is meant for
+    /// debugging rather than for undo in production: it stops when the returned history is
+    /// dropped.
+    pub fn record_entity_history<T>(
+        &mut self,
+        entity: &Entity<T>,
+        capacity: usize,
+    ) -> Entity<EntityHistory<T>>
+    where
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        self.new(|cx| {
+            let mut history = EntityHistory {
+                entity: entity.downgrade(),
+                snapshots: Vec::new(),
+                cursor: 0,
+                capacity: capacity.max(1),
+                _observe_entity: cx.observe(entity, |this, entity, cx| this.record(&entity, cx)),
+            };
+            history.record(entity, cx);
+            history
+        })
+    }
+}
+
+impl<T> EntityHistory<T>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    /// The snapshots recorded so far, oldest first.
+    pub fn snapshots(&self) -> &[EntitySnapshot] {
+        &self.snapshots
+    }
+
+    /// The index of the snapshot the entity is currently in.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Restore the entity to the snapshot before the current one, returning whether there was
+    /// one.
+    pub fn step_back(&mut self, cx: &mut Context<Self>) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+        self.jump_to(self.cursor - 1, cx)
+    }
+
+    /// Restore the entity to the snapshot after the current one, returning whether there was
+    /// one.
+    pub fn step_forward(&mut self, cx: &mut Context<Self>) -> bool {
+        self.jump_to(self.cursor + 1, cx)
+    }
+
+    /// Restore the entity to the snapshot at the given index, returning whether it was
+    /// restored.
+    pub fn jump_to(&mut self, index: usize, cx: &mut Context<Self>) -> bool {
+        let Some(snapshot) = self.snapshots.get(index) else {
+            return false;
+        };
+        let Some(entity) = self.entity.upgrade() else {
+            return false;
+        };
+        let Some(state) = serde_json::from_value::<T>(snapshot.state.clone()).log_err() else {
+            return false;
+        };
+
+        self.cursor = index;
+        entity.update(cx, |entity, cx| {
+            *entity = state;
+            cx.notify();
+        });
+        cx.notify();
+        true
+    }
+
+    fn record(&mut self, entity: &Entity<T>, cx: &mut Context<Self>) {
+        let Some(state) = serde_json::to_value(entity.read(cx)).log_err() else {
+            return;
+        };
+        // Restoring a snapshot notifies the entity's observers with the snapshot's state.
+        if self
+            .snapshots
+            .get(self.cursor)
+            .is_some_and(|snapshot| snapshot.state == state)
+        {
+            return;
+        }
+
+        if !self.snapshots.is_empty() {
+            self.snapshots.truncate(self.cursor + 1);
+        }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.remove(0);
+        }
+        self.snapshots.push(EntitySnapshot {
+            state,
+            trigger: cx.history_trigger.clone(),
+            timestamp: cx.background_executor().now(),
+        });
+        self.cursor = self.snapshots.len() - 1;
+        cx.notify();
+    }
+}
+
+impl<T> Render for EntityHistory<T>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(first) = self.snapshots.first() else {
+            return div();
+        };
+        let start = first.timestamp;
+        let state = self
+            .snapshots
+            .get(self.cursor)
+            .and_then(|snapshot| serde_json::to_string_pretty(&snapshot.state).log_err())
+            .unwrap_or_default();
+        let button = |id: &'static str, label: &'static str| {
+            div()
+                .id(id)
+                .px_1()
+                .rounded_sm()
+                .bg(hsla(0., 0., 1., 0.1))
+                .hover(|style| style.bg(hsla(0., 0., 1., 0.2)))
+                .child(label)
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .gap_1()
+            .p_1()
+            .bg(hsla(0., 0., 0., 0.85))
+            .text_color(white())
+            .text_xs()
+            .child(
+                div()
+                    .flex()
+                    .gap_1()
+                    .child(
+                        button("step-back", "Back").on_click(cx.listener(|this, _, _, cx| {
+                            this.step_back(cx);
+                        })),
+                    )
+                    .child(button("step-forward", "Forward").on_click(cx.listener(
+                        |this, _, _, cx| {
+                            this.step_forward(cx);
+                        },
+                    )))
+                    .child(format!("{} / {}", self.cursor + 1, self.snapshots.len())),
+            )
+            .child(
+                div()
+                    .id("snapshots")
+                    .flex()
+                    .flex_col()
+                    .max_h_48()
+                    .overflow_y_scroll()
+                    .children(self.snapshots.iter().enumerate().map(|(ix, snapshot)| {
+                        let elapsed = snapshot.timestamp.duration_since(start);
+                        let trigger = snapshot.trigger.clone().unwrap_or("unknown".into());
+                        div()
+                            .id(ix)
+                            .px_1()
+                            .when(ix == self.cursor, |this| {
+                                this.bg(hsla(0.6, 0.6, 0.5, 0.5))
+                                    .font_weight(FontWeight::BOLD)
+                            })
+                            .hover(|style| style.bg(hsla(0., 0., 1., 0.1)))
+                            .child(format!("{ix}: +{}ms {trigger}", elapsed.as_millis()))
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.jump_to(ix, cx);
+                            }))
+                    })),
+            )
+            .child(div().font_family(".ZedMono").child(state))
+    }
+}
+
+impl PlatformInput {
+    /// The name recorded as the trigger of entity changes made while dispatching this input.
+    pub(crate) fn history_trigger(&self) -> SharedString {
+        SharedString::new_static(match self {
+            PlatformInput::KeyDown(_) => "KeyDown",
+            PlatformInput::KeyUp(_) => "KeyUp",
+            PlatformInput::ModifiersChanged(_) => "ModifiersChanged",
+            PlatformInput::MouseDown(_) => "MouseDown",
+            PlatformInput::MouseUp(_) => "MouseUp",
+            PlatformInput::MouseMove(_) => "MouseMove",
+            PlatformInput::MouseExited(_) => "MouseExited",
+            PlatformInput::ScrollWheel(_) => "ScrollWheel",
+            PlatformInput::FileDrop(_) => "FileDrop",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{AppContext as _, TestAppContext, actions};
+
+    #[derive(Serialize, Deserialize)]
+    struct Counter {
+        count: usize,
+    }
+
+    actions!(entity_history_test, [Increment]);
+
+    #[crate::test]
+    fn test_entity_history(cx: &mut TestAppContext) {
+        let counter = cx.new(|_| Counter { count: 0 });
+        let history = cx.update(|cx| cx.record_entity_history(&counter, 3));
+        cx.update(|cx| {
+            let counter = counter.clone();
+            cx.on_action(move |_: &Increment, cx| {
+                counter.update(cx, |counter, cx| {
+                    counter.count += 1;
+                    cx.notify();
+                })
+            });
+        });
+
+        for _ in 0..3 {
+            cx.update(|cx| cx.dispatch_action(&Increment));
+        }
+        // Notifying without changing the state doesn't record a snapshot.
+        counter.update(cx, |_, cx| cx.notify());
+        history.read_with(cx, |history, _| {
+            let counts = history
+                .snapshots()
+                .iter()
+                .map(|snapshot| snapshot.state["count"].as_u64().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(counts, [1, 2, 3]);
+            assert_eq!(
+                history.snapshots()[2].trigger.as_deref(),
+                Some("entity_history_test::Increment")
+            );
+            assert_eq!(history.cursor(), 2);
+        });
+
+        history.update(cx, |history, cx| {
+            assert!(history.step_back(cx));
+            assert!(history.step_back(cx));
+            assert!(!history.step_back(cx));
+        });
+        assert_eq!(counter.read_with(cx, |counter, _| counter.count), 1);
+        history.update(cx, |history, cx| assert!(history.step_forward(cx)));
+        assert_eq!(counter.read_with(cx, |counter, _| counter.count), 2);
+
+        // Changing the entity after stepping back discards the snapshots after the cursor.
+        counter.update(cx, |counter, cx| {
+            counter.count = 10;
+            cx.notify();
+        });
+        history.read_with(cx, |history, _| {
+            assert_eq!(history.snapshots().len(), 3);
+            assert_eq!(history.snapshots()[2].state["count"], 10);
+            assert_eq!(history.snapshots()[2].trigger, None);
+            assert_eq!(history.cursor(), 2);
+        });
+    }
+}
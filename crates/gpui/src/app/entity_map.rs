@@ -84,6 +84,15 @@ impl EntityMap {
         }
     }
 
+    /// Whether the entity with the given id still has strong handles.
+    pub fn is_alive(&self, entity_id: EntityId) -> bool {
+        self.ref_counts
+            .read()
+            .counts
+            .get(entity_id)
+            .is_some_and(|count| count.load(SeqCst) > 0)
+    }
+
     /// Reserve a slot for an entity, which you can subsequently use with `insert`.
     pub fn reserve<T: 'static>(&self) -> Slot<T> {
         let id = self.ref_counts.write().counts.insert(1.into());
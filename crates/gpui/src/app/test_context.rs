@@ -1,17 +1,26 @@
 use crate::{
     Action, AnyView, AnyWindowHandle, App, AppCell, AppContext, AsyncApp, AvailableSpace,
-    BackgroundExecutor, BorrowAppContext, Bounds, Capslock, ClipboardItem, DrawPhase, Drawable,
-    Element, Empty, EventEmitter, ForegroundExecutor, Global, InputEvent, Keystroke, Modifiers,
-    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels,
-    Platform, Point, Render, Result, Size, Task, TestDispatcher, TestPlatform,
-    TestScreenCaptureSource, TestWindow, TextSystem, VisualContext, Window, WindowBounds,
-    WindowHandle, WindowOptions,
+    BackgroundExecutor, BorrowAppContext, Bounds, Capslock, ClipboardItem, DisplayId, DrawPhase,
+    Drawable, Element, Empty, EventEmitter, ForegroundExecutor, Global, InputEvent, Keystroke,
+    Modifiers, ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
+    Pixels, Platform, PlatformDisplay, PlatformWindow, Point, PowerState, Render, Result, Size,
+    Task, TestDispatcher, TestPlatform, TestScreenCaptureSource, TestWindow, TextSystem,
+    VisualContext, Window, WindowBounds, WindowHandle, WindowOptions,
 };
 use anyhow::{anyhow, bail};
 use futures::{Stream, StreamExt, channel::oneshot};
 use rand::{SeedableRng, rngs::StdRng};
 use std::{
-    cell::RefCell, future::Future, ops::Deref, path::PathBuf, rc::Rc, sync::Arc, time::Duration,
+    cell::RefCell,
+    future::{self, Future},
+    mem,
+    ops::Deref,
+    panic::Location,
+    path::PathBuf,
+    rc::Rc,
+    sync::Arc,
+    task::Poll,
+    time::{Duration, Instant},
 };
 
 /// A TestAppContext is provided to tests created with `#[gpui::test]`, it provides
@@ -160,6 +169,12 @@ impl TestAppContext {
         self.test_platform.did_prompt_for_new_path()
     }
 
+    /// Checks whether the app has asked the platform to quit, such as when its last window
+    /// closed.
+    pub fn did_quit(&self) -> bool {
+        self.test_platform.did_quit()
+    }
+
     /// returns a new `TestAppContext` re-using the same executors to interleave tasks.
     pub fn new_app(&self) -> TestAppContext {
         Self::build(self.dispatcher.clone(), self.fn_name)
@@ -333,6 +348,67 @@ impl TestAppContext {
         self.test_window(window_handle).simulate_resize(size);
     }
 
+    /// Simulates connecting a display with the given bounds in the global coordinate space and
+    /// scale factor, returning its id. Tests start with a single 1920x1080 primary display with
+    /// a scale factor of 2.
+    pub fn add_display(&self, bounds: Bounds<Pixels>, scale_factor: f32) -> DisplayId {
+        self.test_platform.add_display(bounds, scale_factor)
+    }
+
+    /// Simulates disconnecting a display. Windows on it move to the center of the primary display.
+    pub fn remove_display(&self, display_id: DisplayId) {
+        self.test_platform.remove_display(display_id);
+        let primary_display = self.test_platform.primary_test_display();
+        for window in self.windows_on_display(display_id) {
+            let bounds =
+                Bounds::centered_at(primary_display.bounds().center(), window.bounds().size);
+            window.simulate_display_change(bounds, primary_display.clone());
+        }
+    }
+
+    /// Simulates the user changing the scaling of a display, which changes the scale factor of
+    /// the windows on it.
+    pub fn set_display_scale_factor(&self, display_id: DisplayId, scale_factor: f32) {
+        let display = self
+            .test_platform
+            .set_display_scale_factor(display_id, scale_factor);
+        for window in self.windows_on_display(display_id) {
+            window.simulate_display_change(window.bounds(), display.clone());
+        }
+    }
+
+    /// Simulates the user dragging a window to a new position. The window moves to the display
+    /// that contains most of it, taking on that display's scale factor.
+    pub fn simulate_window_move(&self, window_handle: AnyWindowHandle, origin: Point<Pixels>) {
+        let window = self.test_window(window_handle);
+        let bounds = Bounds::new(origin, window.bounds().size);
+        let display = self
+            .test_platform
+            .test_display_for_bounds(bounds)
+            .or_else(|| self.test_platform.test_display(window.display_id()))
+            .unwrap_or_else(|| self.test_platform.primary_test_display());
+        window.simulate_display_change(bounds, display);
+    }
+
+    /// Simulates moving a window to the center of the given display.
+    pub fn move_window_to_display(&self, window_handle: AnyWindowHandle, display_id: DisplayId) {
+        let display = self
+            .test_platform
+            .test_display(display_id)
+            .expect("no display with the given id");
+        let window = self.test_window(window_handle);
+        let bounds = Bounds::centered_at(display.bounds().center(), window.bounds().size);
+        window.simulate_display_change(bounds, display);
+    }
+
+    fn windows_on_display(&self, display_id: DisplayId) -> Vec<TestWindow> {
+        self.windows()
+            .into_iter()
+            .map(|window| self.test_window(window))
+            .filter(|window| window.display_id() == display_id)
+            .collect()
+    }
+
     /// Returns true if there's an alert dialog open.
     pub fn expect_restart(&self) -> oneshot::Receiver<Option<PathBuf>> {
         let (tx, rx) = futures::channel::oneshot::channel();
@@ -346,6 +422,18 @@ impl TestAppContext {
         self.test_platform.set_screen_capture_sources(sources);
     }
 
+    /// Causes the given power state to be reported by the platform. Observers registered with
+    /// [`App::observe_power_state`] are notified the next time the state is checked.
+    pub fn simulate_power_state(&self, power_state: PowerState) {
+        self.test_platform.set_power_state(power_state);
+    }
+
+    /// Report that the process already runs the platform's event loop, as it does when GPUI is
+    /// loaded into a plugin host, so that [`Application::run`](crate::Application::run) attaches to it.
+    pub fn simulate_running_event_loop(&self) {
+        self.test_platform.set_event_loop_running(true);
+    }
+
     /// Returns all windows open in the test.
     pub fn windows(&self) -> Vec<AnyWindowHandle> {
         self.app.borrow().windows()
@@ -540,6 +628,124 @@ impl TestAppContext {
         .unwrap();
     }
 
+    /// Returns a future that runs tasks and advances the simulated clock until the given
+    /// condition holds, checking it again after every task. Panics with the caller's location
+    /// and the scheduler's pending tasks if it doesn't hold within `timeout` of simulated time.
+    #[track_caller]
+    pub fn wait_for_condition(
+        &mut self,
+        timeout: Duration,
+        mut condition: impl FnMut(&mut App) -> bool,
+    ) -> impl Future<Output = ()> {
+        let caller = Location::caller();
+        async move {
+            self.wait_until(timeout, caller, "condition", |cx| {
+                cx.update(&mut condition).then_some(())
+            })
+            .await
+        }
+    }
+
+    /// Returns a future that runs tasks and advances the simulated clock until the given entity
+    /// emits an event matching the predicate, then resolves to that event. Events emitted before
+    /// this is called are ignored. Panics if no matching event is emitted within `timeout` of
+    /// simulated time.
+    #[track_caller]
+    pub fn wait_for_event<Evt, T>(
+        &mut self,
+        entity: &Entity<T>,
+        timeout: Duration,
+        mut predicate: impl FnMut(&Evt) -> bool + 'static,
+    ) -> impl Future<Output = Evt>
+    where
+        Evt: 'static + Clone,
+        T: 'static + EventEmitter<Evt>,
+    {
+        let caller = Location::caller();
+        let matched = Rc::new(RefCell::new(None));
+        let subscription = self.update(|cx| {
+            cx.subscribe(entity, {
+                let matched = matched.clone();
+                move |_, event: &Evt, _| {
+                    let mut matched = matched.borrow_mut();
+                    if matched.is_none() && predicate(event) {
+                        *matched = Some(event.clone());
+                    }
+                }
+            })
+        });
+        let description = format!("an event from {}", std::any::type_name::<T>());
+        async move {
+            let event = self
+                .wait_until(timeout, caller, &description, |_| {
+                    matched.borrow_mut().take()
+                })
+                .await;
+            drop(subscription);
+            event
+        }
+    }
+
+    /// Yields to the test scheduler until `check` returns a value, checking it again after every
+    /// task the scheduler runs and advancing the simulated clock when nothing is left to run.
+    async fn wait_until<R>(
+        &mut self,
+        timeout: Duration,
+        caller: &'static Location<'static>,
+        description: &str,
+        mut check: impl FnMut(&mut Self) -> Option<R>,
+    ) -> R {
+        let deadline = self.executor().now() + timeout;
+        let real_deadline = Instant::now() + timeout;
+        loop {
+            if let Some(result) = check(self) {
+                return result;
+            }
+            let pending_tasks = self.dispatcher.pending_tasks();
+            if pending_tasks.foreground > 0
+                || pending_tasks.background > 0
+                || pending_tasks.deprioritized > 0
+            {
+                // The scheduler runs a task before polling again.
+                smol::future::yield_now().await;
+                continue;
+            }
+            if self
+                .dispatcher
+                .next_delayed_time()
+                .is_some_and(|time| time <= deadline)
+            {
+                self.dispatcher.advance_clock_to_next_delayed();
+                continue;
+            }
+            // Tasks that do real work, like disk access, can only be waited for in real time.
+            // Without waking itself, this lets the scheduler park until they make progress.
+            if self.dispatcher.parking_allowed() && Instant::now() < real_deadline {
+                let mut parked = false;
+                future::poll_fn(|_| {
+                    if mem::replace(&mut parked, true) {
+                        Poll::Ready(())
+                    } else {
+                        Poll::Pending
+                    }
+                })
+                .await;
+                continue;
+            }
+            break;
+        }
+
+        let waiting_hint = self
+            .dispatcher
+            .waiting_hint()
+            .map(|hint| format!("\n  waiting on: {hint}"))
+            .unwrap_or_default();
+        panic!(
+            "timed out after {timeout:?} waiting for {description} at {caller}\n  pending tasks: {:?}{waiting_hint}",
+            self.dispatcher.pending_tasks(),
+        )
+    }
+
     /// Set a name for this App.
     #[cfg(any(test, feature = "test-support"))]
     pub fn set_name(&mut self, name: &'static str) {
@@ -809,6 +1015,72 @@ impl VisualTestContext {
         })
     }
 
+    /// Returns a future that runs tasks and advances the simulated clock until the window has
+    /// drawn a frame for which the predicate holds, such as one in which
+    /// [`VisualTestContext::debug_bounds`] finds a given element. Panics if no such frame is
+    /// drawn within `timeout` of simulated time.
+    #[track_caller]
+    pub fn wait_for_render(
+        &mut self,
+        timeout: Duration,
+        mut predicate: impl FnMut(&mut Window, &mut App) -> bool,
+    ) -> impl Future<Output = ()> {
+        let caller = Location::caller();
+        let window = self.window;
+        async move {
+            self.cx
+                .wait_until(timeout, caller, "a matching frame", |cx| {
+                    cx.update_window(window, |_, window, cx| {
+                        (!window.invalidator.is_dirty() && predicate(window, cx)).then_some(())
+                    })
+                    .expect("window was closed while waiting for it to render")
+                })
+                .await
+        }
+    }
+
+    /// Returns a future that runs tasks and advances the simulated clock until the window's
+    /// layout settles: nothing is left to run, the window has drawn its latest changes, and
+    /// either no timer is due within `timeout` or firing the next one didn't move any element.
+    /// Panics if the layout doesn't settle within `timeout` of simulated time, for example
+    /// because a timer keeps moving elements.
+    #[track_caller]
+    pub fn wait_for_layout(&mut self, timeout: Duration) -> impl Future<Output = ()> {
+        let caller = Location::caller();
+        let window = self.window;
+        async move {
+            let deadline = self.executor().now() + timeout;
+            let mut last_layout = None;
+            self.cx
+                .wait_until(timeout, caller, "layout to settle", |cx| {
+                    let pending_tasks = cx.dispatcher.pending_tasks();
+                    if pending_tasks.foreground > 0 || pending_tasks.background > 0 {
+                        return None;
+                    }
+                    let layout = cx
+                        .update_window(window, |_, window, _| {
+                            (!window.invalidator.is_dirty()).then(|| {
+                                window
+                                    .rendered_frame
+                                    .hitboxes
+                                    .iter()
+                                    .map(|hitbox| hitbox.bounds)
+                                    .collect::<Vec<_>>()
+                            })
+                        })
+                        .expect("window was closed while waiting for its layout to settle")?;
+                    let no_timers_due = cx
+                        .dispatcher
+                        .next_delayed_time()
+                        .is_none_or(|time| time > deadline);
+                    let settled = no_timers_due || last_layout.as_ref() == Some(&layout);
+                    last_layout = Some(layout);
+                    settled.then_some(())
+                })
+                .await
+        }
+    }
+
     /// Simulates the user resizing the window to the new size.
     pub fn simulate_resize(&self, size: Size<Pixels>) {
         self.simulate_window_resize(self.window, size)
@@ -1054,3 +1326,101 @@ impl AnyWindowHandle {
             .unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Context, InteractiveElement as _, StatefulInteractiveElement as _, Styled as _, div, px,
+    };
+
+    #[crate::test]
+    async fn test_wait_for_condition_and_event(cx: &mut TestAppContext) {
+        struct Counter(usize);
+        impl EventEmitter<usize> for Counter {}
+
+        let counter = cx.new(|_| Counter(0));
+        cx.spawn({
+            let counter = counter.clone();
+            async move |cx| {
+                for _ in 0..3 {
+                    cx.background_executor().timer(Duration::from_secs(1)).await;
+                    counter
+                        .update(cx, |counter, cx| {
+                            counter.0 += 1;
+                            cx.emit(counter.0);
+                        })
+                        .unwrap();
+                }
+            }
+        })
+        .detach();
+
+        let event = cx
+            .wait_for_event(&counter, Duration::from_secs(5), |count: &usize| {
+                *count == 2
+            })
+            .await;
+        assert_eq!(event, 2);
+
+        cx.wait_for_condition(Duration::from_secs(5), |cx| counter.read(cx).0 == 3)
+            .await;
+    }
+
+    struct Growing {
+        width: f32,
+    }
+
+    impl Render for Growing {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl crate::IntoElement {
+            div()
+                .id("growing")
+                .debug_selector(|| "growing".into())
+                .w(px(self.width))
+                .h(px(10.))
+                .on_click(|_, _, _| {})
+        }
+    }
+
+    fn grow_every(interval: Duration, steps: usize, cx: &mut Context<Growing>) {
+        cx.spawn(async move |this, cx| {
+            for _ in 0..steps {
+                cx.background_executor().timer(interval).await;
+                this.update(cx, |this, cx| {
+                    this.width += 10.;
+                    cx.notify();
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+
+    #[crate::test]
+    async fn test_wait_for_render(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| Growing { width: 10. });
+        view.update(cx, |_, cx| grow_every(Duration::from_secs(1), 3, cx));
+
+        cx.wait_for_render(Duration::from_secs(5), |window, _| {
+            window
+                .rendered_frame
+                .debug_bounds
+                .get("growing")
+                .is_some_and(|bounds| bounds.size.width == px(30.))
+        })
+        .await;
+        assert_eq!(view.read_with(cx, |view, _| view.width), 30.);
+    }
+
+    #[crate::test]
+    async fn test_wait_for_layout(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| Growing { width: 10. });
+        view.update(cx, |_, cx| grow_every(Duration::from_millis(100), 3, cx));
+
+        cx.wait_for_layout(Duration::from_secs(5)).await;
+        assert_eq!(
+            cx.debug_bounds("growing").map(|bounds| bounds.size.width),
+            Some(px(40.))
+        );
+    }
+}
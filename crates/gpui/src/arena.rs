@@ -109,6 +109,12 @@ impl Arena {
         self.current_chunk_index = 0;
     }
 
+    /// Clear the arena and free every chunk but the first.
+    pub fn trim(&mut self) {
+        self.clear();
+        self.chunks.truncate(1);
+    }
+
     #[inline(always)]
     pub fn alloc<T>(&mut self, f: impl FnOnce() -> T) -> ArenaBox<T> {
         #[inline(always)]
@@ -256,6 +262,10 @@ mod tests {
         arena.alloc(|| 4u32);
 
         assert_eq!(arena.capacity(), 24);
+
+        arena.trim();
+        assert_eq!(arena.capacity(), 8);
+        assert_eq!(*arena.alloc(|| 5u64), 5);
     }
 
     #[test]
@@ -1,12 +1,24 @@
 use crate::{DevicePixels, Pixels, Result, SharedString, Size, size};
+use anyhow::anyhow;
+use collections::HashMap;
+use memmap2::Mmap;
+use parking_lot::Mutex;
 use smallvec::SmallVec;
+use util::ResultExt as _;
 
-use image::{Delay, Frame};
+use image::{AnimationDecoder, Delay, DynamicImage, Frame, ImageDecoder, ImageResult, RgbaImage};
 use std::{
     borrow::Cow,
     fmt,
+    fs::File,
     hash::Hash,
-    sync::atomic::{AtomicUsize, Ordering::SeqCst},
+    io,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicUsize, Ordering::SeqCst},
+    },
 };
 
 /// A source of assets for this app to use.
@@ -14,6 +26,12 @@ pub trait AssetSource: 'static + Send + Sync {
     /// Load the given asset from the source path.
     fn load(&self, path: &str) -> Result<Option<Cow<'static, [u8]>>>;
 
+    /// Load the given asset as bytes that can be shared without copying them. Sources that
+    /// already hold their assets in memory, such as [`MappedAssetSource`], should override this.
+    fn load_bytes(&self, path: &str) -> Result<Option<AssetBytes>> {
+        Ok(self.load(path)?.map(AssetBytes::from))
+    }
+
     /// List the assets at the given path.
     fn list(&self, path: &str) -> Result<Vec<SharedString>>;
 }
@@ -28,10 +46,247 @@ impl AssetSource for () {
     }
 }
 
+/// The contents of an asset, shared by reference count. See [`AssetSource::load_bytes`].
+#[derive(Clone)]
+pub struct AssetBytes(Arc<dyn AsRef<[u8]> + Send + Sync>);
+
+impl AssetBytes {
+    /// Share the bytes of the given owner, such as a memory mapping, without copying them.
+    pub fn new(owner: impl AsRef<[u8]> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(owner))
+    }
+}
+
+impl Deref for AssetBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        AsRef::<[u8]>::as_ref(&*self.0)
+    }
+}
+
+impl AsRef<[u8]> for AssetBytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl From<Cow<'static, [u8]>> for AssetBytes {
+    fn from(bytes: Cow<'static, [u8]>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl From<Vec<u8>> for AssetBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl fmt::Debug for AssetBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetBytes")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// An [`AssetSource`] that memory-maps files from a directory instead of reading them into memory.
+///
+/// Large assets such as background images or bundled fonts are paged in by the OS on demand and
+/// shared between every process and app instance mapping the same file. The bytes returned by
+/// [`AssetSource::load_bytes`] share the mapping, which is unmapped once they are all dropped,
+/// while [`AssetSource::load`] returns a copy. Files must not be modified while they are mapped.
+pub struct MappedAssetSource {
+    root: PathBuf,
+    mapped: Mutex<HashMap<SharedString, Weak<Mmap>>>,
+}
+
+impl MappedAssetSource {
+    /// Create a source that loads assets relative to the given directory.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            mapped: Mutex::default(),
+        }
+    }
+
+    fn list_recursive(&self, dir: &Path, assets: &mut Vec<SharedString>) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.list_recursive(&path, assets)?;
+            } else if let Ok(relative_path) = path.strip_prefix(&self.root) {
+                assets.push(relative_path.to_string_lossy().into_owned().into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AssetSource for MappedAssetSource {
+    fn load(&self, path: &str) -> Result<Option<Cow<'static, [u8]>>> {
+        Ok(self
+            .load_bytes(path)?
+            .map(|bytes| Cow::Owned(bytes.to_vec())))
+    }
+
+    fn load_bytes(&self, path: &str) -> Result<Option<AssetBytes>> {
+        let mut mapped = self.mapped.lock();
+        if let Some(mapping) = mapped.get(path).and_then(Weak::upgrade) {
+            return Ok(Some(AssetBytes(mapping)));
+        }
+
+        let file = match File::open(self.root.join(path)) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        // Empty files can't be mapped.
+        if file.metadata()?.len() == 0 {
+            return Ok(Some(AssetBytes::new(&[] as &'static [u8])));
+        }
+        // SAFETY: Mapped assets are required not to be modified while they are mapped.
+        let mapping = Arc::new(unsafe { Mmap::map(&file)? });
+        mapped.retain(|_, mapping| mapping.strong_count() > 0);
+        mapped.insert(
+            SharedString::from(path.to_string()),
+            Arc::downgrade(&mapping),
+        );
+        Ok(Some(AssetBytes(mapping)))
+    }
+
+    fn list(&self, path: &str) -> Result<Vec<SharedString>> {
+        let mut assets = Vec::new();
+        let dir = self.root.join(path);
+        if dir.is_dir() {
+            self.list_recursive(&dir, &mut assets)?;
+        }
+        Ok(assets)
+    }
+}
+
+/// How embedded ICC color profiles are treated when decoding images.
+/// See [`App::set_image_color_management`](crate::App::set_image_color_management).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageColorManagement {
+    /// Convert images with an embedded color profile to the given color space, which should be
+    /// the one their pixels are displayed in, so that they show the colors they were authored
+    /// with.
+    ConvertTo(ImageColorSpace),
+    /// Ignore embedded color profiles, assuming all images are already in the color space they
+    /// are displayed in.
+    Ignore,
+}
+
+impl Default for ImageColorManagement {
+    fn default() -> Self {
+        Self::ConvertTo(ImageColorSpace::default())
+    }
+}
+
+/// A color space that decoded images can be converted to.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ImageColorSpace {
+    /// sRGB, the color space windows are rendered in.
+    #[default]
+    Srgb,
+    /// Display P3, for pixels shown on a wide-gamut surface, e.g. when they are read back with
+    /// [`RenderImage::as_bytes`] and handed to a renderer outside of GPUI.
+    DisplayP3,
+}
+
+impl ImageColorSpace {
+    fn profile(self) -> moxcms::ColorProfile {
+        match self {
+            Self::Srgb => moxcms::ColorProfile::new_srgb(),
+            Self::DisplayP3 => moxcms::ColorProfile::new_display_p3(),
+        }
+    }
+}
+
+/// Decode a still image into RGBA pixels, converting them from its embedded color profile
+/// unless color management is ignored. Profiles that can't be applied are logged and ignored
+/// rather than failing the whole image.
+pub(crate) fn decode_rgba8(
+    mut decoder: impl ImageDecoder,
+    color_management: ImageColorManagement,
+) -> ImageResult<RgbaImage> {
+    let profile = icc_profile(&mut decoder, color_management);
+    let mut image = DynamicImage::from_decoder(decoder)?.into_rgba8();
+    if let Some((profile, target)) = profile {
+        convert_color_space(&profile, target, [&mut image]).log_err();
+    }
+    Ok(image)
+}
+
+/// Decode every frame of an animated image into RGBA pixels, converting them like
+/// [`decode_rgba8`].
+pub(crate) fn decode_rgba8_frames<'a>(
+    mut decoder: impl ImageDecoder + AnimationDecoder<'a>,
+    color_management: ImageColorManagement,
+) -> ImageResult<Vec<Frame>> {
+    let profile = icc_profile(&mut decoder, color_management);
+    let mut frames = decoder.into_frames().collect_frames()?;
+    if let Some((profile, target)) = profile {
+        convert_color_space(
+            &profile,
+            target,
+            frames.iter_mut().map(|frame| frame.buffer_mut()),
+        )
+        .log_err();
+    }
+    Ok(frames)
+}
+
+fn icc_profile(
+    decoder: &mut impl ImageDecoder,
+    color_management: ImageColorManagement,
+) -> Option<(Vec<u8>, ImageColorSpace)> {
+    match color_management {
+        ImageColorManagement::ConvertTo(target) => {
+            Some((decoder.icc_profile().ok().flatten()?, target))
+        }
+        ImageColorManagement::Ignore => None,
+    }
+}
+
+fn convert_color_space<'a>(
+    profile: &[u8],
+    target: ImageColorSpace,
+    images: impl IntoIterator<Item = &'a mut RgbaImage>,
+) -> Result<()> {
+    let source = moxcms::ColorProfile::new_from_slice(profile)
+        .map_err(|error| anyhow!("invalid ICC profile: {error}"))?;
+    let transform = source
+        .create_transform_8bit(
+            moxcms::Layout::Rgba,
+            &target.profile(),
+            moxcms::Layout::Rgba,
+            moxcms::TransformOptions::default(),
+        )
+        .map_err(|error| anyhow!("unsupported ICC profile: {error}"))?;
+    for image in images {
+        let pixels = image.as_raw().clone();
+        transform
+            .transform(&pixels, image)
+            .map_err(|error| anyhow!("failed to apply ICC profile: {error}"))?;
+    }
+    Ok(())
+}
+
 /// A unique identifier for the image cache
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct ImageId(pub usize);
 
+impl ImageId {
+    /// Allocate an id that no other image uses.
+    pub(crate) fn next() -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        Self(NEXT_ID.fetch_add(1, SeqCst))
+    }
+}
+
 #[derive(PartialEq, Eq, Hash, Clone)]
 pub(crate) struct RenderImageParams {
     pub(crate) image_id: ImageId,
@@ -58,10 +313,8 @@ impl Eq for RenderImage {}
 impl RenderImage {
     /// Create a new image from the given data.
     pub fn new(data: impl Into<SmallVec<[Frame; 1]>>) -> Self {
-        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
-
         Self {
-            id: ImageId(NEXT_ID.fetch_add(1, SeqCst)),
+            id: ImageId::next(),
             scale_factor: 1.0,
             data: data.into(),
         }
@@ -105,3 +358,160 @@ impl fmt::Debug for RenderImage {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{
+        ColorType, ExtendedColorType, Frames, ImageEncoder as _,
+        codecs::png::{PngDecoder, PngEncoder},
+    };
+    use std::io::Cursor;
+
+    #[test]
+    fn test_mapped_asset_source() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("fonts")).unwrap();
+        std::fs::write(root.path().join("fonts/mono.ttf"), b"font data").unwrap();
+        std::fs::write(root.path().join("empty.svg"), b"").unwrap();
+        let source = MappedAssetSource::new(root.path());
+
+        let bytes = source.load_bytes("fonts/mono.ttf").unwrap().unwrap();
+        assert_eq!(&*bytes, b"font data");
+        assert_eq!(
+            source.load("fonts/mono.ttf").unwrap().as_deref(),
+            Some(&b"font data"[..])
+        );
+        // The mapping is shared while it's in use.
+        let reloaded = source.load_bytes("fonts/mono.ttf").unwrap().unwrap();
+        assert_eq!(reloaded.as_ptr(), bytes.as_ptr());
+
+        assert_eq!(
+            source.load_bytes("empty.svg").unwrap().as_deref(),
+            Some(&[][..])
+        );
+        assert!(source.load_bytes("missing.png").unwrap().is_none());
+
+        let mut assets = source.list("").unwrap();
+        assets.sort();
+        assert_eq!(
+            assets,
+            [
+                SharedString::from("empty.svg"),
+                SharedString::from(
+                    Path::new("fonts")
+                        .join("mono.ttf")
+                        .to_string_lossy()
+                        .into_owned()
+                )
+            ]
+        );
+        assert!(source.list("missing").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_mapped_asset_source_unmaps_unused_files() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("image.png"), b"image data").unwrap();
+        let source = MappedAssetSource::new(root.path());
+
+        let bytes = source.load_bytes("image.png").unwrap().unwrap();
+        let mapping = Arc::downgrade(&bytes.0);
+        drop(bytes);
+        assert!(mapping.upgrade().is_none());
+
+        std::fs::write(root.path().join("image.png"), b"new image data").unwrap();
+        let bytes = source.load_bytes("image.png").unwrap().unwrap();
+        assert_eq!(&*bytes, b"new image data");
+    }
+
+    const PIXEL: [u8; 4] = [200, 100, 50, 255];
+
+    fn display_p3_profile() -> Vec<u8> {
+        moxcms::ColorProfile::new_display_p3().encode().unwrap()
+    }
+
+    fn decode_display_p3_png(color_management: ImageColorManagement) -> [u8; 4] {
+        let mut png = Vec::new();
+        let mut encoder = PngEncoder::new(&mut png);
+        encoder.set_icc_profile(display_p3_profile()).unwrap();
+        encoder
+            .write_image(&PIXEL, 1, 1, ExtendedColorType::Rgba8)
+            .unwrap();
+
+        let decoder = PngDecoder::new(Cursor::new(png)).unwrap();
+        decode_rgba8(decoder, color_management)
+            .unwrap()
+            .get_pixel(0, 0)
+            .0
+    }
+
+    #[test]
+    fn test_decode_rgba8_color_management() {
+        let srgb = decode_display_p3_png(ImageColorManagement::default());
+        // Display P3 has a wider gamut, so its colors are more saturated in sRGB.
+        assert!(srgb[0] > PIXEL[0] && srgb[1] < PIXEL[1], "{srgb:?}");
+        assert_eq!(srgb[3], PIXEL[3]);
+
+        assert_eq!(
+            decode_display_p3_png(ImageColorManagement::ConvertTo(ImageColorSpace::DisplayP3)),
+            PIXEL
+        );
+        assert_eq!(decode_display_p3_png(ImageColorManagement::Ignore), PIXEL);
+    }
+
+    struct Animation {
+        profile: Vec<u8>,
+        frame_count: usize,
+    }
+
+    impl ImageDecoder for Animation {
+        fn dimensions(&self) -> (u32, u32) {
+            (1, 1)
+        }
+
+        fn color_type(&self) -> ColorType {
+            ColorType::Rgba8
+        }
+
+        fn icc_profile(&mut self) -> ImageResult<Option<Vec<u8>>> {
+            Ok(Some(self.profile.clone()))
+        }
+
+        fn read_image(self, _: &mut [u8]) -> ImageResult<()> {
+            unimplemented!("animations are decoded frame by frame")
+        }
+
+        fn read_image_boxed(self: Box<Self>, buffer: &mut [u8]) -> ImageResult<()> {
+            (*self).read_image(buffer)
+        }
+    }
+
+    impl AnimationDecoder<'static> for Animation {
+        fn into_frames(self) -> Frames<'static> {
+            Frames::new(Box::new((0..self.frame_count).map(|_| {
+                Ok(Frame::new(RgbaImage::from_pixel(1, 1, image::Rgba(PIXEL))))
+            })))
+        }
+    }
+
+    #[test]
+    fn test_decode_rgba8_frames_color_management() {
+        let animation = || Animation {
+            profile: display_p3_profile(),
+            frame_count: 2,
+        };
+        let still = decode_display_p3_png(ImageColorManagement::default());
+
+        let frames = decode_rgba8_frames(animation(), ImageColorManagement::default()).unwrap();
+        assert_eq!(frames.len(), 2);
+        for frame in &frames {
+            assert_eq!(frame.buffer().get_pixel(0, 0).0, still);
+        }
+
+        let frames = decode_rgba8_frames(animation(), ImageColorManagement::Ignore).unwrap();
+        for frame in &frames {
+            assert_eq!(frame.buffer().get_pixel(0, 0).0, PIXEL);
+        }
+    }
+}
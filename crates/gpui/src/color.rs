@@ -504,6 +504,71 @@ impl Hsla {
         }
     }
 
+    /// Interpolate between this and another color by `delta` in the range 0.0 to 1.0, mixing
+    /// them in [`Oklab`] so that midpoints keep a consistent perceived lightness instead of
+    /// turning muddy or grey. Use this when animating between colors.
+    pub fn interpolate(self, other: Hsla, delta: f32) -> Hsla {
+        Oklab::from(self)
+            .interpolate(Oklab::from(other), delta)
+            .into()
+    }
+
+    /// The relative luminance of this color as defined by WCAG 2, from 0.0 for black to 1.0
+    /// for white. Alpha is ignored.
+    pub fn relative_luminance(&self) -> f32 {
+        let color = Rgba::from(*self);
+        let [r, g, b] = [color.r, color.g, color.b].map(srgb_to_linear);
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// The WCAG 2 contrast ratio of this color drawn on top of `background`, ranging from 1.0
+    /// for identical colors to 21.0 for black on white. A translucent foreground is composited
+    /// over the background first.
+    pub fn contrast_ratio(&self, background: Hsla) -> f32 {
+        let foreground = background.blend(*self).relative_luminance();
+        let background = background.relative_luminance();
+        (foreground.max(background) + 0.05) / (foreground.min(background) + 0.05)
+    }
+
+    /// The APCA lightness contrast (Lc) of this color used as text on top of `background`.
+    ///
+    /// Unlike [`Hsla::contrast_ratio`], the result is signed: positive for dark text on a light
+    /// background and negative for light text on a dark background. Its magnitude ranges from
+    /// 0 to roughly 106; body text generally needs an Lc of at least 75.
+    ///
+    /// References:
+    /// - <https://github.com/Myndex/apca-w3>
+    pub fn apca_contrast(&self, background: Hsla) -> f32 {
+        let text = apca_luminance(background.blend(*self));
+        let background = apca_luminance(background);
+
+        if (background - text).abs() < 0.0005 {
+            return 0.;
+        }
+
+        let contrast = if background > text {
+            let contrast = (background.powf(0.56) - text.powf(0.57)) * 1.14;
+            if contrast < 0.1 { 0. } else { contrast - 0.027 }
+        } else {
+            let contrast = (background.powf(0.65) - text.powf(0.62)) * 1.14;
+            if contrast > -0.1 {
+                0.
+            } else {
+                contrast + 0.027
+            }
+        };
+        contrast * 100.
+    }
+
+    /// Returns black or white, whichever is more legible on top of `background`.
+    pub fn readable_on(background: Hsla) -> Hsla {
+        if black().contrast_ratio(background) >= white().contrast_ratio(background) {
+            black()
+        } else {
+            white()
+        }
+    }
+
     /// Returns a new HSLA color with the same hue, and lightness, but with no saturation.
     pub fn grayscale(&self) -> Self {
         Hsla {
@@ -652,6 +717,299 @@ impl<'de> Deserialize<'de> for Hsla {
     }
 }
 
+/// A color in the Oklab perceptual color space, where equal distances between colors look
+/// roughly equally different.
+///
+/// Reference: <https://bottosson.github.io/posts/oklab/>
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Oklab {
+    /// Perceived lightness, in the range 0.0 to 1.0
+    pub l: f32,
+    /// How green (negative) or red (positive) the color is, roughly in the range -0.4 to 0.4
+    pub a: f32,
+    /// How blue (negative) or yellow (positive) the color is, roughly in the range -0.4 to 0.4
+    pub b: f32,
+    /// The alpha component of the color, in the range 0.0 to 1.0
+    pub alpha: f32,
+}
+
+/// Construct an [`Oklab`] color
+pub fn oklab(l: f32, a: f32, b: f32, alpha: f32) -> Oklab {
+    Oklab { l, a, b, alpha }
+}
+
+impl Oklab {
+    /// Interpolate between this and another color by `delta` in the range 0.0 to 1.0, with
+    /// premultiplied alpha so that fading to a transparent color doesn't pass through its hue.
+    pub fn interpolate(self, other: Oklab, delta: f32) -> Oklab {
+        let alpha = lerp(self.alpha, other.alpha, delta);
+        let premultiplied = |from: f32, to: f32| {
+            if alpha <= 0. {
+                lerp(from, to, delta)
+            } else {
+                lerp(from * self.alpha, to * other.alpha, delta) / alpha
+            }
+        };
+        Oklab {
+            l: premultiplied(self.l, other.l),
+            a: premultiplied(self.a, other.a),
+            b: premultiplied(self.b, other.b),
+            alpha,
+        }
+    }
+}
+
+impl From<Rgba> for Oklab {
+    fn from(color: Rgba) -> Self {
+        let [r, g, b] = [color.r, color.g, color.b].map(srgb_to_linear);
+
+        let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+        Oklab {
+            l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+            alpha: color.a,
+        }
+    }
+}
+
+impl From<Oklab> for Rgba {
+    fn from(color: Oklab) -> Self {
+        let l = (color.l + 0.396_337_78 * color.a + 0.215_803_76 * color.b).powi(3);
+        let m = (color.l - 0.105_561_346 * color.a - 0.063_854_17 * color.b).powi(3);
+        let s = (color.l - 0.089_484_18 * color.a - 1.291_485_5 * color.b).powi(3);
+
+        let [r, g, b] = [
+            4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+            -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+            -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+        ]
+        .map(|channel| linear_to_srgb(channel).clamp(0., 1.));
+
+        Rgba {
+            r,
+            g,
+            b,
+            a: color.alpha.clamp(0., 1.),
+        }
+    }
+}
+
+impl From<Hsla> for Oklab {
+    fn from(color: Hsla) -> Self {
+        Rgba::from(color).into()
+    }
+}
+
+impl From<Oklab> for Hsla {
+    fn from(color: Oklab) -> Self {
+        Rgba::from(color).into()
+    }
+}
+
+/// A color in Oklch, the polar form of [`Oklab`], which describes colors by lightness, chroma
+/// and hue like [`Hsla`] does, but with perceptually uniform steps.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Oklch {
+    /// Perceived lightness, in the range 0.0 to 1.0
+    pub l: f32,
+    /// Chroma, how colorful the color is, from 0.0 for greys up to roughly 0.4
+    pub c: f32,
+    /// Hue, in degrees from 0.0 to 360.0
+    pub h: f32,
+    /// The alpha component of the color, in the range 0.0 to 1.0
+    pub alpha: f32,
+}
+
+/// Construct an [`Oklch`] color
+pub fn oklch(l: f32, c: f32, h: f32, alpha: f32) -> Oklch {
+    Oklch { l, c, h, alpha }
+}
+
+impl Oklch {
+    /// Interpolate between this and another color by `delta` in the range 0.0 to 1.0, taking the
+    /// shorter way around the hue circle. Unlike interpolating in [`Oklab`], this keeps the
+    /// chroma of the midpoints between two saturated colors instead of passing through grey.
+    pub fn interpolate(self, other: Oklch, delta: f32) -> Oklch {
+        // Greys have no meaningful hue, so take the other color's.
+        let (from_hue, to_hue) = match (self.c == 0., other.c == 0.) {
+            (true, false) => (other.h, other.h),
+            (false, true) => (self.h, self.h),
+            _ => (self.h, other.h),
+        };
+        let hue_delta = (to_hue - from_hue + 180.).rem_euclid(360.) - 180.;
+
+        let alpha = lerp(self.alpha, other.alpha, delta);
+        let premultiplied = |from: f32, to: f32| {
+            if alpha <= 0. {
+                lerp(from, to, delta)
+            } else {
+                lerp(from * self.alpha, to * other.alpha, delta) / alpha
+            }
+        };
+        Oklch {
+            l: premultiplied(self.l, other.l),
+            c: premultiplied(self.c, other.c),
+            h: (from_hue + hue_delta * delta).rem_euclid(360.),
+            alpha,
+        }
+    }
+}
+
+impl From<Oklab> for Oklch {
+    fn from(color: Oklab) -> Self {
+        let c = color.a.hypot(color.b);
+        let h = if c < 1e-4 {
+            0.
+        } else {
+            color.b.atan2(color.a).to_degrees().rem_euclid(360.)
+        };
+        Oklch {
+            l: color.l,
+            c,
+            h,
+            alpha: color.alpha,
+        }
+    }
+}
+
+impl From<Oklch> for Oklab {
+    fn from(color: Oklch) -> Self {
+        let (sin, cos) = color.h.to_radians().sin_cos();
+        Oklab {
+            l: color.l,
+            a: color.c * cos,
+            b: color.c * sin,
+            alpha: color.alpha,
+        }
+    }
+}
+
+impl From<Rgba> for Oklch {
+    fn from(color: Rgba) -> Self {
+        Oklab::from(color).into()
+    }
+}
+
+impl From<Oklch> for Rgba {
+    fn from(color: Oklch) -> Self {
+        Oklab::from(color).into()
+    }
+}
+
+impl From<Hsla> for Oklch {
+    fn from(color: Hsla) -> Self {
+        Oklab::from(color).into()
+    }
+}
+
+impl From<Oklch> for Hsla {
+    fn from(color: Oklch) -> Self {
+        Oklab::from(color).into()
+    }
+}
+
+fn lerp(from: f32, to: f32, delta: f32) -> f32 {
+    from + (to - from) * delta
+}
+
+fn srgb_to_linear(channel: f32) -> f32 {
+    if channel <= 0.040_45 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub(crate) fn linear_to_srgb(channel: f32) -> f32 {
+    if channel <= 0.003_130_8 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1. / 2.4) - 0.055
+    }
+}
+
+fn apca_luminance(color: Hsla) -> f32 {
+    let color = Rgba::from(color);
+    let luminance = 0.212_672_9 * color.r.powf(2.4)
+        + 0.715_152_2 * color.g.powf(2.4)
+        + 0.072_175 * color.b.powf(2.4);
+    // Soft clamp near black, where the display flare dominates perceived lightness.
+    if luminance < 0.022 {
+        luminance + (0.022 - luminance).powf(1.414)
+    } else {
+        luminance
+    }
+}
+
+/// A minimum contrast between text and its background.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContrastLevel {
+    /// WCAG 2 level AA for large text, a contrast ratio of 3:1.
+    AaLarge,
+    /// WCAG 2 level AA for body text, a contrast ratio of 4.5:1.
+    Aa,
+    /// WCAG 2 level AAA for body text, a contrast ratio of 7:1.
+    Aaa,
+    /// An APCA lightness contrast (Lc) of at least the given magnitude, e.g. 75 for body text.
+    Apca(f32),
+}
+
+impl ContrastLevel {
+    /// Returns true if `foreground` drawn on `background` meets this level.
+    pub fn is_met(&self, foreground: Hsla, background: Hsla) -> bool {
+        match self {
+            ContrastLevel::AaLarge => foreground.contrast_ratio(background) >= 3.,
+            ContrastLevel::Aa => foreground.contrast_ratio(background) >= 4.5,
+            ContrastLevel::Aaa => foreground.contrast_ratio(background) >= 7.,
+            ContrastLevel::Apca(lc) => foreground.apca_contrast(background).abs() >= *lc,
+        }
+    }
+}
+
+/// Returns `foreground` adjusted so that it meets `level` on top of `background`.
+///
+/// Colors that already meet the level are returned unchanged. Otherwise the lightness is pushed
+/// away from the background by the smallest amount that reaches the level, keeping hue,
+/// saturation and alpha. If neither lightening nor darkening gets there, the more legible of
+/// black and white is returned, which may still fall short for mid-tone backgrounds.
+pub fn ensure_contrast(foreground: Hsla, background: Hsla, level: ContrastLevel) -> Hsla {
+    if level.is_met(foreground, background) {
+        return foreground;
+    }
+
+    let with_lightness = |l: f32| Hsla { l, ..foreground };
+    let prefer_light = white().contrast_ratio(background) > black().contrast_ratio(background);
+    let targets = if prefer_light { [1., 0.] } else { [0., 1.] };
+
+    for target in targets {
+        if !level.is_met(with_lightness(target), background) {
+            continue;
+        }
+
+        // Contrast changes monotonically as lightness moves towards the target, so bisect for
+        // the closest lightness that still meets the level.
+        let (mut failing, mut passing) = (foreground.l, target);
+        for _ in 0..16 {
+            let mid = (failing + passing) / 2.;
+            if level.is_met(with_lightness(mid), background) {
+                passing = mid;
+            } else {
+                failing = mid;
+            }
+        }
+        return with_lightness(passing);
+    }
+
+    Hsla {
+        a: foreground.a,
+        ..Hsla::readable_on(background)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[repr(C)]
 pub(crate) enum BackgroundTag {
@@ -904,6 +1262,87 @@ mod tests {
         assert_eq!(actual, rgba(0xdeadbeef))
     }
 
+    #[test]
+    fn test_oklab_conversions() {
+        let white = Oklab::from(rgb(0xffffff));
+        assert!((white.l - 1.).abs() < 1e-3);
+        assert!(white.a.abs() < 1e-3 && white.b.abs() < 1e-3);
+
+        let red = Oklab::from(rgb(0xff0000));
+        assert!((red.l - 0.628).abs() < 1e-3);
+        assert!((red.a - 0.225).abs() < 1e-3);
+        assert!((red.b - 0.126).abs() < 1e-3);
+
+        for hex in [0xff0000, 0x00ff00, 0x3366cc, 0x808080] {
+            let color = rgb(hex);
+            for round_trip in [
+                Rgba::from(Oklab::from(color)),
+                Rgba::from(Oklch::from(color)),
+            ] {
+                for (actual, expected) in [
+                    (round_trip.r, color.r),
+                    (round_trip.g, color.g),
+                    (round_trip.b, color.b),
+                ] {
+                    assert!((actual - expected).abs() < 1e-3, "{hex:06x}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_perceptual_interpolation() {
+        let black = Hsla::from(rgb(0x000000));
+        let white = Hsla::from(rgb(0xffffff));
+        let midpoint = Oklab::from(black.interpolate(white, 0.5));
+        assert!((midpoint.l - 0.5).abs() < 1e-2);
+
+        // Fading out keeps the color instead of darkening towards transparent black.
+        let transparent = hsla(0., 0., 0., 0.);
+        let faded = Rgba::from(Hsla::from(rgb(0xff0000)).interpolate(transparent, 0.5));
+        assert!((faded.r - 1.).abs() < 1e-2 && (faded.a - 0.5).abs() < 1e-2);
+
+        // Oklch takes the shorter way around the hue circle.
+        let from = oklch(0.7, 0.1, 350., 1.);
+        let to = oklch(0.7, 0.1, 10., 1.);
+        let hue = from.interpolate(to, 0.5).h;
+        assert!(hue.min(360. - hue) < 1e-3);
+    }
+
+    #[test]
+    fn test_contrast() {
+        assert!((black().contrast_ratio(white()) - 21.).abs() < 1e-3);
+        assert!((white().contrast_ratio(black()) - 21.).abs() < 1e-3);
+        assert!((red().contrast_ratio(red()) - 1.).abs() < 1e-3);
+
+        assert!((black().apca_contrast(white()) - 106.04).abs() < 0.1);
+        assert!((white().apca_contrast(black()) + 107.88).abs() < 0.1);
+
+        assert_eq!(Hsla::readable_on(white()), black());
+        assert_eq!(Hsla::readable_on(hsla(0.6, 0.8, 0.2, 1.)), white());
+    }
+
+    #[test]
+    fn test_ensure_contrast() {
+        let background = hsla(0., 0., 0.95, 1.);
+        let foreground = hsla(0.6, 0.7, 0.75, 1.);
+        assert!(!ContrastLevel::Aa.is_met(foreground, background));
+
+        let adjusted = ensure_contrast(foreground, background, ContrastLevel::Aa);
+        assert!(ContrastLevel::Aa.is_met(adjusted, background));
+        assert!(adjusted.contrast_ratio(background) < 4.6);
+        assert_eq!((adjusted.h, adjusted.s, adjusted.a), (0.6, 0.7, 1.));
+
+        let readable = hsla(0., 0., 0.1, 1.);
+        assert_eq!(
+            ensure_contrast(readable, background, ContrastLevel::Aaa),
+            readable
+        );
+
+        let adjusted = ensure_contrast(foreground, background, ContrastLevel::Apca(75.));
+        assert!(adjusted.apca_contrast(background) >= 75.);
+    }
+
     #[test]
     fn test_background_solid() {
         let color = Hsla::from(rgba(0xff0099ff));
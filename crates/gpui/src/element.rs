@@ -416,6 +416,12 @@ impl<E: Element> Drawable<E> {
                 }
 
                 let bounds = window.layout_bounds(layout_id);
+                #[cfg(any(test, feature = "test-support"))]
+                let pushed_layout_node = window.next_frame.layout_tree.push::<E>(
+                    self.element.id().as_ref(),
+                    layout_id,
+                    bounds,
+                );
                 let node_id = window.next_frame.dispatch_tree.push_node();
                 let prepaint = self.element.prepaint(
                     global_id.as_ref(),
@@ -426,6 +432,10 @@ impl<E: Element> Drawable<E> {
                     cx,
                 );
                 window.next_frame.dispatch_tree.pop_node();
+                #[cfg(any(test, feature = "test-support"))]
+                if pushed_layout_node {
+                    window.next_frame.layout_tree.pop();
+                }
 
                 if global_id.is_some() {
                     window.element_id_stack.pop();
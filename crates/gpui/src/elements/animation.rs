@@ -1,10 +1,8 @@
-use std::{
-    rc::Rc,
-    time::{Duration, Instant},
-};
+use std::{rc::Rc, time::Duration};
 
 use crate::{
-    AnyElement, App, Element, ElementId, GlobalElementId, InspectorElementId, IntoElement, Window,
+    AnyElement, App, Element, ElementId, GlobalElementId, Hsla, InspectorElementId, IntoElement,
+    Oklab, Oklch, Window,
 };
 
 pub use easing::*;
@@ -68,6 +66,26 @@ pub trait AnimationExt {
         }
     }
 
+    /// Render this component or element with an animation from one color to another. Colors
+    /// given as [`Hsla`] or [`Oklab`] are interpolated in Oklab, so that the midpoints don't turn
+    /// muddy, while colors given as [`Oklch`] keep their chroma and travel around the hue circle,
+    /// which suits transitions between two saturated colors.
+    fn with_color_animation<C: AnimatableColor>(
+        self,
+        id: impl Into<ElementId>,
+        animation: Animation,
+        from: C,
+        to: C,
+        animator: impl Fn(Self, Hsla) -> Self + 'static,
+    ) -> AnimationElement<Self>
+    where
+        Self: Sized,
+    {
+        self.with_animation(id, animation, move |this, delta| {
+            animator(this, from.interpolate(to, delta).into())
+        })
+    }
+
     /// Render this component or element with a chain of animations
     fn with_animations(
         self,
@@ -89,6 +107,31 @@ pub trait AnimationExt {
 
 impl<E: IntoElement + 'static> AnimationExt for E {}
 
+/// A color that can be animated with [`AnimationExt::with_color_animation`], which determines the
+/// color space its midpoints are interpolated in.
+pub trait AnimatableColor: Copy + Into<Hsla> + 'static {
+    /// Interpolate between this and another color by `delta` in the range 0.0 to 1.0.
+    fn interpolate(self, other: Self, delta: f32) -> Self;
+}
+
+impl AnimatableColor for Hsla {
+    fn interpolate(self, other: Self, delta: f32) -> Self {
+        Hsla::interpolate(self, other, delta)
+    }
+}
+
+impl AnimatableColor for Oklab {
+    fn interpolate(self, other: Self, delta: f32) -> Self {
+        Oklab::interpolate(self, other, delta)
+    }
+}
+
+impl AnimatableColor for Oklch {
+    fn interpolate(self, other: Self, delta: f32) -> Self {
+        Oklch::interpolate(self, other, delta)
+    }
+}
+
 /// A GPUI element that applies an animation to another element
 pub struct AnimationElement<E> {
     id: ElementId,
@@ -115,7 +158,7 @@ impl<E: IntoElement + 'static> IntoElement for AnimationElement<E> {
 }
 
 struct AnimationState {
-    start: Instant,
+    start: Duration,
     animation_ix: usize,
 }
 
@@ -139,13 +182,20 @@ impl<E: IntoElement + 'static> Element for AnimationElement<E> {
         cx: &mut App,
     ) -> (crate::LayoutId, Self::RequestLayoutState) {
         window.with_element_state(global_id.unwrap(), |state, window| {
+            let animation_time = window.animation_time();
             let mut state = state.unwrap_or_else(|| AnimationState {
-                start: Instant::now(),
+                start: animation_time,
                 animation_ix: 0,
             });
+            // The window's animation clock may be driven by a host that can rewind it.
+            if animation_time < state.start {
+                state.start = animation_time;
+            }
             let animation_ix = state.animation_ix;
 
-            let mut delta = state.start.elapsed().as_secs_f32()
+            // Progress is measured at the frame's animation time rather than the current time,
+            // so that it advances by the actual time between frames whatever the refresh rate.
+            let mut delta = animation_time.saturating_sub(state.start).as_secs_f32()
                 / self.animations[animation_ix].duration.as_secs_f32();
 
             let mut done = false;
@@ -154,7 +204,7 @@ impl<E: IntoElement + 'static> Element for AnimationElement<E> {
                     if animation_ix >= self.animations.len() - 1 {
                         done = true;
                     } else {
-                        state.start = Instant::now();
+                        state.start = animation_time;
                         state.animation_ix += 1;
                     }
                     delta = 1.0;
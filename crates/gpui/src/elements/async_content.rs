@@ -0,0 +1,284 @@
+//! Content that's loaded by a task, with a placeholder while it loads and an error view if it
+//! fails, such as a file's preview or a list fetched from a server.
+
+use std::{cell::RefCell, rc::Rc};
+
+use anyhow::Result;
+
+use crate::{
+    AnyElement, App, Bounds, Element, ElementId, GlobalElementId, InspectorElementId, IntoElement,
+    LayoutId, Pixels, Styled as _, Task, Window, skeleton,
+};
+
+/// Create an element that starts loading its content with `load` when it's first rendered, and
+/// renders it with `render` once the task resolves. Until then, it renders a shimmering
+/// [`skeleton`] filling its parent, or the placeholder given to [`AsyncContent::placeholder`].
+///
+/// The task is only started once for as long as the element is rendered in consecutive frames,
+/// so the element needs an ID that's stable across frames. It's cancelled when the element stops
+/// being rendered before the task resolves, and is started again if it's rendered later.
+pub fn async_content<T, E>(
+    id: impl Into<ElementId>,
+    load: impl FnOnce(&mut Window, &mut App) -> Task<Result<T>> + 'static,
+    render: impl Fn(&T, &mut Window, &mut App) -> E + 'static,
+) -> AsyncContent<T>
+where
+    T: 'static,
+    E: IntoElement,
+{
+    AsyncContent {
+        id: id.into(),
+        load: Some(Box::new(load)),
+        render: Box::new(move |value, window, cx| render(value, window, cx).into_any_element()),
+        render_placeholder: None,
+        render_error: None,
+        child: None,
+    }
+}
+
+/// An element that renders content loaded by a task, created with [`async_content`].
+pub struct AsyncContent<T> {
+    id: ElementId,
+    load: Option<Box<dyn FnOnce(&mut Window, &mut App) -> Task<Result<T>>>>,
+    render: Box<dyn Fn(&T, &mut Window, &mut App) -> AnyElement>,
+    render_placeholder: Option<Box<dyn Fn(&mut Window, &mut App) -> AnyElement>>,
+    render_error: Option<Box<dyn Fn(&anyhow::Error, &mut Window, &mut App) -> AnyElement>>,
+    child: Option<AnyElement>,
+}
+
+impl<T: 'static> AsyncContent<T> {
+    /// Render the given placeholder while the content loads, rather than a skeleton.
+    pub fn placeholder<E: IntoElement>(
+        mut self,
+        render: impl Fn(&mut Window, &mut App) -> E + 'static,
+    ) -> Self {
+        self.render_placeholder = Some(Box::new(move |window, cx| {
+            render(window, cx).into_any_element()
+        }));
+        self
+    }
+
+    /// Render the given view of the error if the content fails to load. Without it, nothing is
+    /// rendered.
+    pub fn error<E: IntoElement>(
+        mut self,
+        render: impl Fn(&anyhow::Error, &mut Window, &mut App) -> E + 'static,
+    ) -> Self {
+        self.render_error = Some(Box::new(move |error, window, cx| {
+            render(error, window, cx).into_any_element()
+        }));
+        self
+    }
+}
+
+/// The state of an [`AsyncContent`] kept across frames.
+struct AsyncContentState<T> {
+    result: Rc<RefCell<Option<Result<T>>>>,
+    /// Dropped along with the state when the element stops being rendered, cancelling the load.
+    _task: Task<()>,
+}
+
+impl<T: 'static> IntoElement for AsyncContent<T> {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl<T: 'static> Element for AsyncContent<T> {
+    type RequestLayoutState = ();
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        Some(self.id.clone())
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let result = window.with_element_state(
+            global_id.unwrap(),
+            |state: Option<AsyncContentState<T>>, window| {
+                let state = state.unwrap_or_else(|| {
+                    let result = Rc::new(RefCell::new(None));
+                    let load = self.load.take().map(|load| load(window, cx));
+                    let current_view = window.current_view();
+                    let task = window.spawn(cx, {
+                        let result = result.clone();
+                        async move |cx| {
+                            let Some(load) = load else {
+                                return;
+                            };
+                            let loaded = load.await;
+                            result.replace(Some(loaded));
+                            cx.update(|_, cx| cx.notify(current_view)).ok();
+                        }
+                    });
+                    AsyncContentState {
+                        result,
+                        _task: task,
+                    }
+                });
+                (state.result.clone(), state)
+            },
+        );
+
+        let mut child = match &*result.borrow() {
+            Some(Ok(value)) => (self.render)(value, window, cx),
+            Some(Err(error)) => match self.render_error.as_ref() {
+                Some(render_error) => render_error(error, window, cx),
+                None => crate::Empty.into_any_element(),
+            },
+            None => match self.render_placeholder.as_ref() {
+                Some(render_placeholder) => render_placeholder(window, cx),
+                None => skeleton().size_full().into_any_element(),
+            },
+        };
+        let layout_id = child.request_layout(window, cx);
+        self.child = Some(child);
+        (layout_id, ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        _: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        if let Some(child) = self.child.as_mut() {
+            child.prepaint(window, cx);
+        }
+    }
+
+    fn paint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        _: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        _: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        if let Some(child) = self.child.as_mut() {
+            child.paint(window, cx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::channel::oneshot;
+
+    use super::*;
+    use crate::{
+        Context, Entity, ParentElement as _, Render, TestAppContext, VisualTestContext, div,
+        prelude::FluentBuilder as _, px,
+    };
+
+    struct Preview {
+        shown: bool,
+        receiver: Option<oneshot::Receiver<Result<&'static str>>>,
+        rendered: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Render for Preview {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let receiver = self.receiver.take();
+            let rendered = self.rendered.clone();
+            let content = async_content(
+                "preview",
+                move |_, cx| {
+                    cx.background_spawn(async move {
+                        receiver.unwrap().await.unwrap_or_else(|_| Ok("cancelled"))
+                    })
+                },
+                {
+                    let rendered = rendered.clone();
+                    move |text: &&str, _, _| {
+                        rendered.borrow_mut().push(text.to_string());
+                        div()
+                    }
+                },
+            )
+            .placeholder({
+                let rendered = rendered.clone();
+                move |_, _| {
+                    rendered.borrow_mut().push("loading".into());
+                    div()
+                }
+            })
+            .error(move |error, _, _| {
+                rendered.borrow_mut().push(format!("error: {error}"));
+                div()
+            });
+            div()
+                .size(px(100.))
+                .when(self.shown, |this| this.child(content))
+        }
+    }
+
+    fn open_preview(
+        cx: &mut TestAppContext,
+    ) -> (
+        Entity<Preview>,
+        &mut VisualTestContext,
+        oneshot::Sender<Result<&'static str>>,
+        Rc<RefCell<Vec<String>>>,
+    ) {
+        let (sender, receiver) = oneshot::channel();
+        let rendered = Rc::new(RefCell::new(Vec::new()));
+        let (view, cx) = cx.add_window_view({
+            let rendered = rendered.clone();
+            |_, _| Preview {
+                shown: true,
+                receiver: Some(receiver),
+                rendered,
+            }
+        });
+        cx.run_until_parked();
+        (view, cx, sender, rendered)
+    }
+
+    #[crate::test]
+    fn test_async_content_renders_loaded_value(cx: &mut TestAppContext) {
+        let (_, cx, sender, rendered) = open_preview(cx);
+        assert_eq!(rendered.borrow().last().unwrap(), "loading");
+
+        sender.send(Ok("loaded")).unwrap();
+        cx.run_until_parked();
+        assert_eq!(rendered.borrow().last().unwrap(), "loaded");
+    }
+
+    #[crate::test]
+    fn test_async_content_renders_error(cx: &mut TestAppContext) {
+        let (_, cx, sender, rendered) = open_preview(cx);
+        sender.send(Err(anyhow::anyhow!("not found"))).unwrap();
+        cx.run_until_parked();
+        assert_eq!(rendered.borrow().last().unwrap(), "error: not found");
+    }
+
+    #[crate::test]
+    fn test_async_content_cancels_when_unmounted(cx: &mut TestAppContext) {
+        let (view, cx, sender, rendered) = open_preview(cx);
+        view.update(cx, |preview, cx| {
+            preview.shown = false;
+            cx.notify();
+        });
+        cx.run_until_parked();
+        assert!(sender.is_canceled());
+        assert!(!rendered.borrow().iter().any(|text| text == "cancelled"));
+    }
+}
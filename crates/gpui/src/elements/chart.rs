@@ -0,0 +1,916 @@
+//! Basic charting elements for plotting one or more data series.
+//!
+//! Charts lay out their axes and tick labels with the text system, downsample large
+//! series down to roughly one bucket per horizontal pixel, and render lines as paths and
+//! bars and scatter points as quads. Hovering a chart highlights the nearest data point
+//! and shows its value in a small tooltip.
+//!
+//! Like text, a chart's axes and labels are drawn in the inherited text color, which can be
+//! set with [`Styled::text_color`]. Its series are drawn in their own colors.
+
+use std::{cell::Cell, ops::Range, rc::Rc, sync::Arc};
+
+use refineable::Refineable as _;
+
+use crate::{
+    App, Background, Bounds, DispatchPhase, Element, ElementId, GlobalElementId, Hitbox,
+    HitboxBehavior, Hsla, InspectorElementId, IntoElement, LayoutId, MouseMoveEvent, PathBuilder,
+    Pixels, Point, ShapedLine, SharedString, Style, StyleRefinement, Styled, TextStyle, Window,
+    fill, point, px, size,
+};
+
+/// The way a [`Chart`] renders its series.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChartKind {
+    /// Connect consecutive points of each series with a line.
+    #[default]
+    Line,
+    /// Draw one bar per point, grouping the bars of each series side by side.
+    Bar,
+    /// Draw each point as a dot.
+    Scatter,
+}
+
+/// A named series of data points plotted by a [`Chart`].
+///
+/// For line charts, points are expected to be sorted by their `x` coordinate.
+#[derive(Clone, Debug)]
+pub struct ChartSeries {
+    /// The label shown in the hover tooltip.
+    pub label: SharedString,
+    /// The data points of this series.
+    pub points: Arc<[Point<f32>]>,
+    /// The color used to draw this series.
+    pub color: Hsla,
+}
+
+impl ChartSeries {
+    /// Create a new series with the given label, points and color.
+    pub fn new(
+        label: impl Into<SharedString>,
+        points: impl Into<Arc<[Point<f32>]>>,
+        color: impl Into<Hsla>,
+    ) -> Self {
+        Self {
+            label: label.into(),
+            points: points.into(),
+            color: color.into(),
+        }
+    }
+}
+
+/// Create a chart that plots its series as lines.
+pub fn line_chart(id: impl Into<ElementId>) -> Chart {
+    Chart::new(id.into(), ChartKind::Line)
+}
+
+/// Create a chart that plots its series as grouped bars.
+pub fn bar_chart(id: impl Into<ElementId>) -> Chart {
+    Chart::new(id.into(), ChartKind::Bar)
+}
+
+/// Create a chart that plots its series as dots.
+pub fn scatter_chart(id: impl Into<ElementId>) -> Chart {
+    Chart::new(id.into(), ChartKind::Scatter)
+}
+
+/// An element that plots data series with axes, tick labels and a hover tooltip.
+pub struct Chart {
+    id: ElementId,
+    kind: ChartKind,
+    series: Vec<ChartSeries>,
+    x_range: Option<Range<f32>>,
+    y_range: Option<Range<f32>>,
+    tick_count: usize,
+    axis_color: Option<Hsla>,
+    grid_color: Option<Hsla>,
+    stroke_width: Pixels,
+    point_radius: Pixels,
+    tooltip_background: Option<Hsla>,
+    tooltip_text_color: Option<Hsla>,
+    format_value: Rc<dyn Fn(f32) -> SharedString>,
+    style: StyleRefinement,
+}
+
+impl Chart {
+    fn new(id: ElementId, kind: ChartKind) -> Self {
+        Self {
+            id,
+            kind,
+            series: Vec::new(),
+            x_range: None,
+            y_range: None,
+            tick_count: 5,
+            axis_color: None,
+            grid_color: None,
+            stroke_width: px(1.5),
+            point_radius: px(3.),
+            tooltip_background: None,
+            tooltip_text_color: None,
+            format_value: Rc::new(|value| format_tick(value).into()),
+            style: StyleRefinement::default(),
+        }
+    }
+
+    /// Add a series to this chart.
+    pub fn series(mut self, series: ChartSeries) -> Self {
+        self.series.push(series);
+        self
+    }
+
+    /// Fix the range of the x axis instead of deriving it from the data.
+    pub fn x_range(mut self, range: Range<f32>) -> Self {
+        self.x_range = Some(range);
+        self
+    }
+
+    /// Fix the range of the y axis instead of deriving it from the data.
+    pub fn y_range(mut self, range: Range<f32>) -> Self {
+        self.y_range = Some(range);
+        self
+    }
+
+    /// Set the approximate number of ticks drawn on each axis. Defaults to 5.
+    pub fn tick_count(mut self, tick_count: usize) -> Self {
+        self.tick_count = tick_count.max(2);
+        self
+    }
+
+    /// Set the color of the axes and their labels. Defaults to the inherited text color.
+    pub fn axis_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.axis_color = Some(color.into());
+        self
+    }
+
+    /// Draw grid lines at each tick in the given color. No grid is drawn by default.
+    pub fn grid_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.grid_color = Some(color.into());
+        self
+    }
+
+    /// Set the width of the lines drawn for line charts.
+    pub fn stroke_width(mut self, width: impl Into<Pixels>) -> Self {
+        self.stroke_width = width.into();
+        self
+    }
+
+    /// Set the radius of the dots drawn for scatter charts and hovered points.
+    pub fn point_radius(mut self, radius: impl Into<Pixels>) -> Self {
+        self.point_radius = radius.into();
+        self
+    }
+
+    /// Set the background color of the hover tooltip. Defaults to the chart's own background.
+    pub fn tooltip_background(mut self, color: impl Into<Hsla>) -> Self {
+        self.tooltip_background = Some(color.into());
+        self
+    }
+
+    /// Set the text color of the hover tooltip. Defaults to the inherited text color.
+    pub fn tooltip_text_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.tooltip_text_color = Some(color.into());
+        self
+    }
+
+    /// Set the function used to format values in tick labels and tooltips.
+    pub fn format_value(mut self, format: impl Fn(f32) -> SharedString + 'static) -> Self {
+        self.format_value = Rc::new(format);
+        self
+    }
+
+    fn data_ranges(&self) -> (Range<f32>, Range<f32>) {
+        let mut x_range = f32::INFINITY..f32::NEG_INFINITY;
+        let mut y_range = f32::INFINITY..f32::NEG_INFINITY;
+        for point in self.series.iter().flat_map(|series| series.points.iter()) {
+            x_range.start = x_range.start.min(point.x);
+            x_range.end = x_range.end.max(point.x);
+            y_range.start = y_range.start.min(point.y);
+            y_range.end = y_range.end.max(point.y);
+        }
+        if self.kind == ChartKind::Bar {
+            // Bars grow from zero, so the baseline must always be visible.
+            y_range.start = y_range.start.min(0.);
+            y_range.end = y_range.end.max(0.);
+        }
+
+        let x_range = self.x_range.clone().unwrap_or(x_range);
+        let y_range = self.y_range.clone().unwrap_or(y_range);
+        (non_empty_range(x_range), non_empty_range(y_range))
+    }
+
+    fn max_series_len(&self) -> usize {
+        self.series
+            .iter()
+            .map(|series| series.points.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl IntoElement for Chart {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Styled for Chart {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+/// The layout computed for a [`Chart`] during prepaint.
+pub struct ChartPrepaintState {
+    hitbox: Hitbox,
+    plot_bounds: Bounds<Pixels>,
+    x_range: Range<f32>,
+    y_range: Range<f32>,
+    x_labels: Vec<(Pixels, ShapedLine)>,
+    y_labels: Vec<(Pixels, ShapedLine)>,
+    text_style: TextStyle,
+    axis_color: Hsla,
+    line_height: Pixels,
+    hovered: Rc<Cell<Option<HoveredPoint>>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct HoveredPoint {
+    series_ix: usize,
+    point_ix: usize,
+}
+
+#[derive(Default)]
+struct ChartElementState {
+    hovered: Rc<Cell<Option<HoveredPoint>>>,
+}
+
+const LABEL_PADDING: Pixels = px(4.);
+
+impl Element for Chart {
+    type RequestLayoutState = Style;
+    type PrepaintState = ChartPrepaintState;
+
+    fn id(&self) -> Option<ElementId> {
+        Some(self.id.clone())
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        let layout_id = window.request_layout(style.clone(), [], cx);
+        (layout_id, style)
+    }
+
+    fn prepaint(
+        &mut self,
+        id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        style: &mut Style,
+        window: &mut Window,
+        _cx: &mut App,
+    ) -> ChartPrepaintState {
+        let hovered = window.with_element_state(id.unwrap(), |state, _| {
+            let state: ChartElementState = state.unwrap_or_default();
+            (state.hovered.clone(), state)
+        });
+
+        let (x_range, y_range) = self.data_ranges();
+        let (text_style, line_height) = window
+            .with_text_style(style.text_style().cloned(), |window| {
+                (window.text_style(), window.line_height())
+            });
+        let axis_color = self.axis_color.unwrap_or(text_style.color);
+        let font_size = text_style.font_size.to_pixels(window.rem_size());
+        let shape_label = |text: SharedString, window: &mut Window| {
+            let run = crate::TextRun {
+                color: axis_color,
+                ..text_style.to_run(text.len())
+            };
+            window
+                .text_system()
+                .shape_line(text, font_size, &[run], None)
+        };
+
+        let y_ticks = nice_ticks(y_range.clone(), self.tick_count);
+        let y_labels = y_ticks
+            .iter()
+            .map(|value| (*value, shape_label((self.format_value)(*value), window)))
+            .collect::<Vec<_>>();
+        let y_label_width = y_labels
+            .iter()
+            .map(|(_, line)| line.width)
+            .max()
+            .unwrap_or_default();
+
+        let plot_bounds = Bounds::from_corners(
+            point(
+                bounds.left() + y_label_width + LABEL_PADDING * 2.,
+                bounds.top() + line_height / 2.,
+            ),
+            point(
+                bounds.right() - LABEL_PADDING,
+                bounds.bottom() - line_height - LABEL_PADDING,
+            ),
+        );
+
+        let y_labels = y_labels
+            .into_iter()
+            .map(|(value, line)| {
+                (
+                    project(value, &y_range, plot_bounds.bottom(), plot_bounds.top()),
+                    line,
+                )
+            })
+            .collect();
+
+        let x_labels = if self.kind == ChartKind::Bar {
+            let slot_count = self.max_series_len();
+            let slot_width = plot_bounds.size.width / slot_count.max(1) as f32;
+            let labels = self
+                .series
+                .iter()
+                .max_by_key(|series| series.points.len())
+                .map(|series| {
+                    series
+                        .points
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, point)| {
+                            let center = plot_bounds.left() + slot_width * (ix as f32 + 0.5);
+                            (center, shape_label((self.format_value)(point.x), window))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            skip_overlapping_labels(labels)
+        } else {
+            let labels = nice_ticks(x_range.clone(), self.tick_count)
+                .into_iter()
+                .map(|value| {
+                    (
+                        project(value, &x_range, plot_bounds.left(), plot_bounds.right()),
+                        shape_label((self.format_value)(value), window),
+                    )
+                })
+                .collect();
+            skip_overlapping_labels(labels)
+        };
+
+        let hitbox = window.insert_hitbox(plot_bounds, HitboxBehavior::Normal);
+
+        ChartPrepaintState {
+            hitbox,
+            plot_bounds,
+            x_range,
+            y_range,
+            x_labels,
+            y_labels,
+            text_style,
+            axis_color,
+            line_height,
+            hovered,
+        }
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        style: &mut Style,
+        prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        style.paint(bounds, window, cx, |window, cx| {
+            self.paint_axes(prepaint, window, cx);
+            window.with_content_mask(
+                Some(crate::ContentMask {
+                    bounds: prepaint.plot_bounds,
+                }),
+                |window| self.paint_series(prepaint, window),
+            );
+            self.paint_hovered_point(prepaint, style, window, cx);
+        });
+
+        let hitbox = prepaint.hitbox.clone();
+        let hovered = prepaint.hovered.clone();
+        let layout = ChartHitTestLayout {
+            kind: self.kind,
+            series: self.series.clone(),
+            plot_bounds: prepaint.plot_bounds,
+            x_range: prepaint.x_range.clone(),
+            y_range: prepaint.y_range.clone(),
+            slot_count: self.max_series_len(),
+            max_distance: self.point_radius * 4.,
+        };
+        let current_view = window.current_view();
+        window.on_mouse_event(move |event: &MouseMoveEvent, phase, window, cx| {
+            if phase != DispatchPhase::Bubble {
+                return;
+            }
+            let next = if hitbox.is_hovered(window) {
+                layout.hit_test(event.position)
+            } else {
+                None
+            };
+            if hovered.replace(next) != next {
+                cx.notify(current_view);
+            }
+        });
+    }
+}
+
+impl Chart {
+    fn paint_axes(&self, prepaint: &ChartPrepaintState, window: &mut Window, cx: &mut App) {
+        let plot = prepaint.plot_bounds;
+        let line_height = prepaint.line_height;
+
+        for (y, line) in &prepaint.y_labels {
+            if let Some(grid_color) = self.grid_color {
+                window.paint_quad(fill(
+                    Bounds::new(point(plot.left(), *y), size(plot.size.width, px(1.))),
+                    grid_color,
+                ));
+            }
+            let origin = point(
+                plot.left() - LABEL_PADDING - line.width,
+                *y - line_height / 2.,
+            );
+            line.paint(origin, line_height, window, cx).ok();
+        }
+
+        for (x, line) in &prepaint.x_labels {
+            if let Some(grid_color) = self.grid_color
+                && self.kind != ChartKind::Bar
+            {
+                window.paint_quad(fill(
+                    Bounds::new(point(*x, plot.top()), size(px(1.), plot.size.height)),
+                    grid_color,
+                ));
+            }
+            let origin = point(*x - line.width / 2., plot.bottom() + LABEL_PADDING);
+            line.paint(origin, line_height, window, cx).ok();
+        }
+
+        window.paint_quad(fill(
+            Bounds::new(plot.origin, size(px(1.), plot.size.height)),
+            prepaint.axis_color,
+        ));
+        window.paint_quad(fill(
+            Bounds::new(plot.bottom_left(), size(plot.size.width, px(1.))),
+            prepaint.axis_color,
+        ));
+    }
+
+    fn paint_series(&self, prepaint: &ChartPrepaintState, window: &mut Window) {
+        let plot = prepaint.plot_bounds;
+        let x_range = &prepaint.x_range;
+        let y_range = &prepaint.y_range;
+        let to_screen = |point: &Point<f32>| {
+            crate::point(
+                project(point.x, x_range, plot.left(), plot.right()),
+                project(point.y, y_range, plot.bottom(), plot.top()),
+            )
+        };
+
+        match self.kind {
+            ChartKind::Line => {
+                let buckets = f32::from(plot.size.width).max(1.) as usize;
+                for series in &self.series {
+                    if series.points.len() < 2 {
+                        continue;
+                    }
+                    let points = downsample_min_max(&series.points, buckets)
+                        .iter()
+                        .map(to_screen)
+                        .collect::<Vec<_>>();
+                    let mut builder = PathBuilder::stroke(self.stroke_width);
+                    builder.add_polygon(&points, false);
+                    if let Ok(path) = builder.build() {
+                        window.paint_path(path, series.color);
+                    }
+                }
+            }
+            ChartKind::Bar => {
+                let slot_count = self.max_series_len().max(1);
+                let slot_width = plot.size.width / slot_count as f32;
+                let bar_width = slot_width * 0.8 / self.series.len().max(1) as f32;
+                let baseline = project(
+                    0f32.max(y_range.start).min(y_range.end),
+                    y_range,
+                    plot.bottom(),
+                    plot.top(),
+                );
+                for (series_ix, series) in self.series.iter().enumerate() {
+                    for (point_ix, point) in series.points.iter().enumerate() {
+                        let left = plot.left()
+                            + slot_width * point_ix as f32
+                            + slot_width * 0.1
+                            + bar_width * series_ix as f32;
+                        let top = project(point.y, y_range, plot.bottom(), plot.top());
+                        window.paint_quad(fill(
+                            Bounds::from_corners(
+                                crate::point(left, top.min(baseline)),
+                                crate::point(left + bar_width, top.max(baseline)),
+                            ),
+                            series.color,
+                        ));
+                    }
+                }
+            }
+            ChartKind::Scatter => {
+                let radius = self.point_radius;
+                for series in &self.series {
+                    for point in series.points.iter() {
+                        let center = to_screen(point);
+                        window.paint_quad(
+                            fill(dot_bounds(center, radius), series.color).corner_radii(radius),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn paint_hovered_point(
+        &self,
+        prepaint: &ChartPrepaintState,
+        style: &Style,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let Some(hovered) = prepaint.hovered.get() else {
+            return;
+        };
+        let Some(series) = self.series.get(hovered.series_ix) else {
+            return;
+        };
+        let Some(data_point) = series.points.get(hovered.point_ix) else {
+            return;
+        };
+
+        let plot = prepaint.plot_bounds;
+        let anchor = if self.kind == ChartKind::Bar {
+            let slot_width = plot.size.width / self.max_series_len().max(1) as f32;
+            point(
+                plot.left() + slot_width * (hovered.point_ix as f32 + 0.5),
+                project(data_point.y, &prepaint.y_range, plot.bottom(), plot.top()),
+            )
+        } else {
+            let center = point(
+                project(data_point.x, &prepaint.x_range, plot.left(), plot.right()),
+                project(data_point.y, &prepaint.y_range, plot.bottom(), plot.top()),
+            );
+            let radius = self.point_radius * 1.5;
+            window.paint_quad(fill(dot_bounds(center, radius), series.color).corner_radii(radius));
+            center
+        };
+
+        let text: SharedString = format!(
+            "{}: {}, {}",
+            series.label,
+            (self.format_value)(data_point.x),
+            (self.format_value)(data_point.y)
+        )
+        .into();
+        let text_style = &prepaint.text_style;
+        let font_size = text_style.font_size.to_pixels(window.rem_size());
+        let run = crate::TextRun {
+            color: self.tooltip_text_color.unwrap_or(text_style.color),
+            ..text_style.to_run(text.len())
+        };
+        let line = window
+            .text_system()
+            .shape_line(text, font_size, &[run], None);
+
+        let line_height = prepaint.line_height;
+        let tooltip_size = size(
+            line.width + LABEL_PADDING * 2.,
+            line_height + LABEL_PADDING * 2.,
+        );
+        let mut origin = point(
+            anchor.x + LABEL_PADDING * 2.,
+            anchor.y - tooltip_size.height - LABEL_PADDING * 2.,
+        );
+        // Keep the tooltip inside the plot area when the hovered point is near an edge.
+        if origin.x + tooltip_size.width > plot.right() {
+            origin.x = anchor.x - tooltip_size.width - LABEL_PADDING * 2.;
+        }
+        if origin.y < plot.top() {
+            origin.y = anchor.y + LABEL_PADDING * 2.;
+        }
+
+        let background = self.tooltip_background.map(Background::from).or_else(|| {
+            style
+                .background
+                .as_ref()
+                .and_then(|background| background.color())
+        });
+        if let Some(background) = background {
+            window.paint_quad(
+                fill(Bounds::new(origin, tooltip_size), background).corner_radii(LABEL_PADDING),
+            );
+        }
+        line.paint(
+            point(origin.x + LABEL_PADDING, origin.y + LABEL_PADDING),
+            line_height,
+            window,
+            cx,
+        )
+        .ok();
+    }
+}
+
+/// The subset of a chart's state needed to hit test the mouse against its data points
+/// after the element itself has been painted.
+struct ChartHitTestLayout {
+    kind: ChartKind,
+    series: Vec<ChartSeries>,
+    plot_bounds: Bounds<Pixels>,
+    x_range: Range<f32>,
+    y_range: Range<f32>,
+    slot_count: usize,
+    max_distance: Pixels,
+}
+
+impl ChartHitTestLayout {
+    fn hit_test(&self, position: Point<Pixels>) -> Option<HoveredPoint> {
+        let plot = self.plot_bounds;
+        match self.kind {
+            ChartKind::Bar => {
+                let slot_width = plot.size.width / self.slot_count.max(1) as f32;
+                let point_ix = ((position.x - plot.left()) / slot_width).floor();
+                if point_ix < 0. {
+                    return None;
+                }
+                let point_ix = point_ix as usize;
+                let bar_width = slot_width * 0.8 / self.series.len().max(1) as f32;
+                let offset =
+                    position.x - plot.left() - slot_width * point_ix as f32 - slot_width * 0.1;
+                let series_ix = (offset / bar_width).floor();
+                if series_ix < 0. {
+                    return None;
+                }
+                let series_ix = series_ix as usize;
+                let series = self.series.get(series_ix)?;
+                (point_ix < series.points.len()).then_some(HoveredPoint {
+                    series_ix,
+                    point_ix,
+                })
+            }
+            ChartKind::Line | ChartKind::Scatter => {
+                let mut closest = None;
+                let mut closest_distance = self.max_distance;
+                for (series_ix, series) in self.series.iter().enumerate() {
+                    let candidates = if self.kind == ChartKind::Line {
+                        // Line series are sorted by x, so only the neighbors of the
+                        // cursor's x position need to be considered.
+                        let x = unproject(position.x, &self.x_range, plot.left(), plot.right());
+                        let ix = series.points.partition_point(|point| point.x < x);
+                        ix.saturating_sub(1)..(ix + 1).min(series.points.len())
+                    } else {
+                        0..series.points.len()
+                    };
+                    for point_ix in candidates {
+                        let point = series.points[point_ix];
+                        let screen = crate::point(
+                            project(point.x, &self.x_range, plot.left(), plot.right()),
+                            project(point.y, &self.y_range, plot.bottom(), plot.top()),
+                        );
+                        let distance = if self.kind == ChartKind::Line {
+                            (screen.x - position.x).abs()
+                        } else {
+                            px(f32::from(screen.x - position.x)
+                                .hypot(f32::from(screen.y - position.y)))
+                        };
+                        if distance <= closest_distance {
+                            closest_distance = distance;
+                            closest = Some(HoveredPoint {
+                                series_ix,
+                                point_ix,
+                            });
+                        }
+                    }
+                }
+                closest
+            }
+        }
+    }
+}
+
+fn dot_bounds(center: Point<Pixels>, radius: Pixels) -> Bounds<Pixels> {
+    Bounds::new(
+        point(center.x - radius, center.y - radius),
+        size(radius * 2., radius * 2.),
+    )
+}
+
+fn non_empty_range(range: Range<f32>) -> Range<f32> {
+    if !range.start.is_finite() || !range.end.is_finite() {
+        0.0..1.0
+    } else if range.end - range.start <= f32::EPSILON {
+        range.start - 0.5..range.end + 0.5
+    } else {
+        range
+    }
+}
+
+fn project(value: f32, range: &Range<f32>, start: Pixels, end: Pixels) -> Pixels {
+    let t = (value - range.start) / (range.end - range.start);
+    start + (end - start) * t
+}
+
+fn unproject(position: Pixels, range: &Range<f32>, start: Pixels, end: Pixels) -> f32 {
+    let t = (position - start) / (end - start);
+    range.start + (range.end - range.start) * t
+}
+
+fn skip_overlapping_labels(labels: Vec<(Pixels, ShapedLine)>) -> Vec<(Pixels, ShapedLine)> {
+    let mut last_right = None;
+    labels
+        .into_iter()
+        .filter(|(center, line)| {
+            let left = *center - line.width / 2.;
+            if last_right.is_some_and(|right| left < right + LABEL_PADDING) {
+                return false;
+            }
+            last_right = Some(*center + line.width / 2.);
+            true
+        })
+        .collect()
+}
+
+fn format_tick(value: f32) -> String {
+    if value.fract() == 0. && value.abs() < 1e7 {
+        format!("{}", value as i64)
+    } else if value.abs() >= 1e4 || value.abs() < 1e-3 {
+        format!("{:.2e}", value)
+    } else {
+        let formatted = format!("{:.3}", value);
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    }
+}
+
+/// Compute evenly spaced, human friendly tick values (multiples of 1, 2 or 5 times a power
+/// of ten) covering the given range, aiming for approximately `target_count` ticks.
+pub(crate) fn nice_ticks(range: Range<f32>, target_count: usize) -> Vec<f32> {
+    let span = range.end - range.start;
+    if !span.is_finite() || span <= 0. || target_count == 0 {
+        return Vec::new();
+    }
+
+    let raw_step = span / target_count as f32;
+    let magnitude = 10f32.powi(raw_step.log10().floor() as i32);
+    let step = [1., 2., 5., 10.]
+        .into_iter()
+        .map(|multiple| multiple * magnitude)
+        .find(|step| *step >= raw_step)
+        .unwrap_or(10. * magnitude);
+
+    let first = (range.start / step).ceil() as i64;
+    let last = (range.end / step).floor() as i64;
+    (first..=last).map(|ix| ix as f32 * step).collect()
+}
+
+/// Reduce a series sorted by `x` to at most about `2 * buckets` points by keeping the
+/// minimum and maximum point of each bucket, which preserves peaks when a large series
+/// is drawn into a small number of pixels.
+pub(crate) fn downsample_min_max(points: &[Point<f32>], buckets: usize) -> Vec<Point<f32>> {
+    if buckets == 0 || points.len() <= buckets * 2 {
+        return points.to_vec();
+    }
+
+    let bucket_size = points.len().div_ceil(buckets);
+    let mut result = Vec::with_capacity(buckets * 2);
+    for bucket in points.chunks(bucket_size) {
+        let (mut min_ix, mut max_ix) = (0, 0);
+        for (ix, point) in bucket.iter().enumerate() {
+            if point.y < bucket[min_ix].y {
+                min_ix = ix;
+            }
+            if point.y > bucket[max_ix].y {
+                max_ix = ix;
+            }
+        }
+        let (first, second) = if min_ix <= max_ix {
+            (min_ix, max_ix)
+        } else {
+            (max_ix, min_ix)
+        };
+        result.push(bucket[first]);
+        if second != first {
+            result.push(bucket[second]);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, Render, TestAppContext, blue, green, red};
+
+    #[test]
+    fn test_nice_ticks() {
+        assert_eq!(nice_ticks(0.0..10.0, 5), vec![0., 2., 4., 6., 8., 10.]);
+        assert_eq!(nice_ticks(-1.0..1.0, 4), vec![-1., -0.5, 0., 0.5, 1.]);
+        assert_eq!(nice_ticks(3.0..97.0, 5), vec![20., 40., 60., 80.]);
+        assert!(nice_ticks(1.0..1.0, 5).is_empty());
+    }
+
+    #[test]
+    fn test_downsample_min_max_preserves_peaks() {
+        let points = (0..1000)
+            .map(|ix| {
+                let y = if ix == 421 {
+                    100.
+                } else if ix == 733 {
+                    -100.
+                } else {
+                    0.
+                };
+                point(ix as f32, y)
+            })
+            .collect::<Vec<_>>();
+
+        let downsampled = downsample_min_max(&points, 50);
+        assert!(downsampled.len() <= 100);
+        assert!(downsampled.contains(&point(421., 100.)));
+        assert!(downsampled.contains(&point(733., -100.)));
+        assert!(downsampled.windows(2).all(|pair| pair[0].x < pair[1].x));
+
+        let small = &points[..20];
+        assert_eq!(downsample_min_max(small, 50), small.to_vec());
+    }
+
+    #[crate::test]
+    fn test_chart_colors(cx: &mut TestAppContext) {
+        struct ChartView {
+            grid: bool,
+        }
+
+        impl Render for ChartView {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                let series =
+                    ChartSeries::new("series", vec![point(0., 0.), point(10., 10.)], blue());
+                let chart = line_chart("chart")
+                    .size(px(200.))
+                    .text_color(red())
+                    .series(series);
+                if self.grid {
+                    chart.grid_color(green())
+                } else {
+                    chart
+                }
+            }
+        }
+
+        let (view, cx) = cx.add_window_view(|_, _| ChartView { grid: false });
+        cx.run_until_parked();
+        let quad_colors = |cx: &mut crate::VisualTestContext| {
+            cx.update(|window, _| {
+                window
+                    .rendered_frame
+                    .scene
+                    .quads
+                    .iter()
+                    .map(|quad| quad.background)
+                    .collect::<Vec<_>>()
+            })
+        };
+        // The axes take the inherited text color, and there's no grid unless asked for.
+        assert_eq!(quad_colors(cx), [Background::from(red()); 2]);
+
+        view.update(cx, |view, cx| {
+            view.grid = true;
+            cx.notify();
+        });
+        cx.run_until_parked();
+        let colors = quad_colors(cx);
+        assert!(colors.contains(&Background::from(green())));
+        assert_eq!(
+            colors
+                .iter()
+                .filter(|color| **color == Background::from(red()))
+                .count(),
+            2
+        );
+    }
+}
@@ -19,11 +19,11 @@ use crate::{
     AbsoluteLength, Action, AnyDrag, AnyElement, AnyTooltip, AnyView, App, Bounds, ClickEvent,
     DispatchPhase, Display, Element, ElementId, Entity, FocusHandle, Global, GlobalElementId,
     Hitbox, HitboxBehavior, HitboxId, InspectorElementId, IntoElement, IsZero, KeyContext,
-    KeyDownEvent, KeyUpEvent, KeyboardButton, KeyboardClickEvent, LayoutId, ModifiersChangedEvent,
-    MouseButton, MouseClickEvent, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Overflow,
-    ParentElement, Pixels, Point, Render, ScrollWheelEvent, SharedString, Size, Style,
-    StyleRefinement, Styled, Task, TooltipId, Visibility, Window, WindowControlArea, point, px,
-    size,
+    KeyDownEvent, KeyUpEvent, KeyboardButton, KeyboardClickEvent, Keystroke, LayoutId,
+    LongPressEvent, ModifiersChangedEvent, MouseButton, MouseClickEvent, MouseDownEvent,
+    MouseMoveEvent, MouseUpEvent, Overflow, ParentElement, Pixels, Point, Render, ScrollWheelEvent,
+    SharedString, Size, Style, StyleRefinement, Styled, Task, TooltipId, Visibility, Window,
+    WindowControlArea, point, px, size,
 };
 use collections::HashMap;
 use refineable::Refineable;
@@ -47,6 +47,17 @@ use super::ImageCacheProvider;
 const DRAG_THRESHOLD: f64 = 2.;
 const TOOLTIP_SHOW_DELAY: Duration = Duration::from_millis(500);
 const HOVERABLE_TOOLTIP_HIDE_DELAY: Duration = Duration::from_millis(500);
+const HOVER_INTENT_DELAY: Duration = Duration::from_millis(300);
+const HOVER_INTENT_MOVE_TOLERANCE: f64 = 4.;
+const LONG_PRESS_DURATION: Duration = Duration::from_millis(500);
+const LONG_PRESS_MOVE_THRESHOLD: f64 = 8.;
+
+/// A style that applies while the window or a named container has a certain size.
+pub(crate) struct SizeStyle {
+    container: Option<SharedString>,
+    predicate: Box<dyn Fn(Size<Pixels>) -> bool>,
+    style: Box<StyleRefinement>,
+}
 
 /// The styling information for a given group.
 pub struct GroupStyle {
@@ -305,7 +316,9 @@ impl Interactivity {
             }));
     }
 
-    /// Bind the given callback to scroll wheel events during the bubble phase.
+    /// Bind the given callback to scroll wheel events during the bubble phase. Call
+    /// [`App::stop_propagation`] to keep containing elements from scrolling, or
+    /// [`Window::stop_scroll_propagation`] to only consume one axis and let the other bubble.
     /// The imperative API equivalent to [`InteractiveElement::on_scroll_wheel`].
     ///
     /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
@@ -321,6 +334,23 @@ impl Interactivity {
             }));
     }
 
+    /// Bind the given callback to scroll wheel events during the capture phase, before the elements
+    /// it contains handle them.
+    /// The imperative API equivalent to [`InteractiveElement::capture_scroll_wheel`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    pub fn capture_scroll_wheel(
+        &mut self,
+        listener: impl Fn(&ScrollWheelEvent, &mut Window, &mut App) + 'static,
+    ) {
+        self.scroll_wheel_listeners
+            .push(Box::new(move |event, phase, hitbox, window, cx| {
+                if phase == DispatchPhase::Capture && hitbox.should_handle_scroll(window) {
+                    (listener)(event, window, cx);
+                }
+            }));
+    }
+
     /// Bind the given callback to an action dispatch during the capture phase.
     /// The imperative API equivalent to [`InteractiveElement::capture_action`].
     ///
@@ -455,6 +485,41 @@ impl Interactivity {
             }));
     }
 
+    /// Bind the given callback to modifiers changing events while this element is hovered, whether
+    /// or not it contains the focus and whether or not the mouse moves.
+    /// The imperative API equivalent to [`InteractiveElement::on_hover_modifiers_changed`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    pub fn on_hover_modifiers_changed(
+        &mut self,
+        listener: impl Fn(&ModifiersChangedEvent, &mut Window, &mut App) + 'static,
+    ) {
+        self.hover_modifiers_changed_listeners
+            .push(Box::new(move |event, window, cx| {
+                listener(event, window, cx)
+            }));
+    }
+
+    /// Bind the given callback to a keystroke, such as `"cmd-s"`, typed while this element contains
+    /// the focus. This works like a key binding scoped to this element, without having to define an
+    /// action and add it to the keymap. Shortcuts of elements closer to the focused element take
+    /// precedence. Key bindings whose context is set by an element inside this one take precedence
+    /// over the shortcut, which in turn takes precedence over all other key bindings. Keystrokes
+    /// that can't be parsed are logged and ignored.
+    /// The imperative API equivalent to [`InteractiveElement::on_shortcut`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    pub fn on_shortcut(
+        &mut self,
+        keystroke: &str,
+        listener: impl Fn(&KeyDownEvent, &mut Window, &mut App) + 'static,
+    ) {
+        let Some(keystroke) = Keystroke::parse(keystroke).log_err() else {
+            return;
+        };
+        self.shortcuts.push((keystroke, Box::new(listener)));
+    }
+
     /// Bind the given callback to drop events of the given type, whether or not the drag started on this element.
     /// The imperative API equivalent to [`InteractiveElement::on_drop`].
     ///
@@ -490,6 +555,27 @@ impl Interactivity {
         }));
     }
 
+    /// Bind the given callback to click events of this element with the given click count, such as 2 for
+    /// double clicks. The click count is determined by the platform, according to the user's double click
+    /// speed and distance settings. Note that the earlier clicks of a sequence are also delivered to
+    /// handlers for their own click counts, and that a press which starts a drag is never a click.
+    /// The imperative API equivalent to [`StatefulInteractiveElement::on_multi_click`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    pub fn on_multi_click(
+        &mut self,
+        click_count: usize,
+        listener: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+    ) where
+        Self: Sized,
+    {
+        self.click_listeners.push(Rc::new(move |event, window, cx| {
+            if event.click_count() == click_count {
+                listener(event, window, cx)
+            }
+        }));
+    }
+
     /// On drag initiation, this callback will be used to create a new view to render the dragged value for a
     /// drag and drop operation. This API should also be used as the equivalent of 'on drag start' with
     /// the [`Self::on_drag_move`] API.
@@ -533,6 +619,44 @@ impl Interactivity {
         self.hover_listener = Some(Box::new(listener));
     }
 
+    /// Bind the given callback to the hover intent start and end events of this element. Hover intent
+    /// starts once the mouse has rested over the element for a short delay without moving more than a
+    /// few pixels, and ends when the mouse leaves. The boolean passed to the callback is true when the
+    /// hover intent starts and false when it ends.
+    /// The imperative API equivalent to [`StatefulInteractiveElement::on_hover_intent`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    pub fn on_hover_intent(&mut self, listener: impl Fn(&bool, &mut Window, &mut App) + 'static)
+    where
+        Self: Sized,
+    {
+        debug_assert!(
+            self.hover_intent_listener.is_none(),
+            "calling on_hover_intent more than once on the same element is not supported"
+        );
+        self.hover_intent_listener = Some(Rc::new(listener));
+    }
+
+    /// Bind the given callback to long press events of this element. A long press is recognized when
+    /// the left mouse button is held down over the element without moving past a small threshold. It
+    /// is cancelled by releasing the button, leaving the element or starting a drag. Once recognized,
+    /// the press no longer produces a click or a drag when the button is released or the mouse moves.
+    /// The imperative API equivalent to [`StatefulInteractiveElement::on_long_press`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    pub fn on_long_press(
+        &mut self,
+        listener: impl Fn(&LongPressEvent, &mut Window, &mut App) + 'static,
+    ) where
+        Self: Sized,
+    {
+        debug_assert!(
+            self.long_press_listener.is_none(),
+            "calling on_long_press more than once on the same element is not supported"
+        );
+        self.long_press_listener = Some(Rc::new(listener));
+    }
+
     /// Use the given callback to construct a new tooltip view when the mouse hovers over this element.
     /// The imperative API equivalent to [`StatefulInteractiveElement::tooltip`].
     pub fn tooltip(&mut self, build_tooltip: impl Fn(&mut Window, &mut App) -> AnyView + 'static)
@@ -603,6 +727,84 @@ pub trait InteractiveElement: Sized {
         self
     }
 
+    /// Apply a style class defined with [`App::define_style_class`]. Classes are applied in the
+    /// order they were added, and styles set directly on the element take precedence over them.
+    fn class(mut self, name: impl Into<SharedString>) -> Self {
+        self.interactivity().classes.push(name.into());
+        self
+    }
+
+    /// Provide a value to this element's descendants, which they can read while rendering with
+    /// [`Window::context_value`]. Values provided by nearer ancestors take precedence.
+    fn provide_context<T: 'static>(mut self, value: T) -> Self {
+        self.interactivity().context_values.push(Rc::new(value));
+        self
+    }
+
+    /// Apply the given style while the window is narrower than `width`, e.g. to switch to a
+    /// compact layout. Size-dependent styles are applied during layout, on top of the element's
+    /// base style.
+    fn when_window_width_below(
+        self,
+        width: impl Into<Pixels>,
+        f: impl FnOnce(StyleRefinement) -> StyleRefinement,
+    ) -> Self {
+        let width = width.into();
+        self.when_window_size(move |size| size.width < width, f)
+    }
+
+    /// Apply the given style while the window's content size satisfies `predicate`.
+    fn when_window_size(
+        mut self,
+        predicate: impl Fn(Size<Pixels>) -> bool + 'static,
+        f: impl FnOnce(StyleRefinement) -> StyleRefinement,
+    ) -> Self {
+        self.interactivity().size_styles.push(SizeStyle {
+            container: None,
+            predicate: Box::new(predicate),
+            style: Box::new(f(StyleRefinement::default())),
+        });
+        self
+    }
+
+    /// Name this element as a container, so that its descendants can style themselves based on
+    /// its size with [`InteractiveElement::when_container_size`].
+    fn container(mut self, name: impl Into<SharedString>) -> Self {
+        self.interactivity().container = Some(name.into());
+        self
+    }
+
+    /// Apply the given style while the named container is narrower than `width`.
+    fn when_container_width_below(
+        self,
+        container: impl Into<SharedString>,
+        width: impl Into<Pixels>,
+        f: impl FnOnce(StyleRefinement) -> StyleRefinement,
+    ) -> Self {
+        let width = width.into();
+        self.when_container_size(container, move |size| size.width < width, f)
+    }
+
+    /// Apply the given style while the size of the named container satisfies `predicate`.
+    ///
+    /// The container's size is measured when it's drawn, so the query is evaluated against the
+    /// size from the previous frame, and the window is redrawn whenever that size changes.
+    /// Nothing is applied before the container has been drawn once. Styles applied this way
+    /// shouldn't change the size of the container itself, or the layout may never settle.
+    fn when_container_size(
+        mut self,
+        container: impl Into<SharedString>,
+        predicate: impl Fn(Size<Pixels>) -> bool + 'static,
+        f: impl FnOnce(StyleRefinement) -> StyleRefinement,
+    ) -> Self {
+        self.interactivity().size_styles.push(SizeStyle {
+            container: Some(container.into()),
+            predicate: Box::new(predicate),
+            style: Box::new(f(StyleRefinement::default())),
+        });
+        self
+    }
+
     /// Assign this element an ID, so that it can be used with interactivity
     fn id(mut self, id: impl Into<ElementId>) -> Stateful<Self> {
         self.interactivity().element_id = Some(id.into());
@@ -823,7 +1025,9 @@ pub trait InteractiveElement: Sized {
         self
     }
 
-    /// Bind the given callback to scroll wheel events during the bubble phase.
+    /// Bind the given callback to scroll wheel events during the bubble phase. Call
+    /// [`App::stop_propagation`] to keep containing elements from scrolling, or
+    /// [`Window::stop_scroll_propagation`] to only consume one axis and let the other bubble.
     /// The fluent API equivalent to [`Interactivity::on_scroll_wheel`].
     ///
     /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
@@ -835,6 +1039,19 @@ pub trait InteractiveElement: Sized {
         self
     }
 
+    /// Bind the given callback to scroll wheel events during the capture phase, before the elements
+    /// it contains handle them.
+    /// The fluent API equivalent to [`Interactivity::capture_scroll_wheel`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    fn capture_scroll_wheel(
+        mut self,
+        listener: impl Fn(&ScrollWheelEvent, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.interactivity().capture_scroll_wheel(listener);
+        self
+    }
+
     /// Capture the given action, before normal action dispatch can fire.
     /// The fluent API equivalent to [`Interactivity::capture_action`].
     ///
@@ -934,6 +1151,19 @@ pub trait InteractiveElement: Sized {
         self
     }
 
+    /// Bind the given callback to modifiers changing events while this element is hovered, whether
+    /// or not it contains the focus and whether or not the mouse moves.
+    /// The fluent API equivalent to [`Interactivity::on_hover_modifiers_changed`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    fn on_hover_modifiers_changed(
+        mut self,
+        listener: impl Fn(&ModifiersChangedEvent, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.interactivity().on_hover_modifiers_changed(listener);
+        self
+    }
+
     /// Apply the given style when the given data type is dragged over this element
     fn drag_over<S: 'static>(
         mut self,
@@ -969,6 +1199,22 @@ pub trait InteractiveElement: Sized {
         self
     }
 
+    /// Bind the given callback to a keystroke, such as `"cmd-s"`, typed while this element contains
+    /// the focus. This works like a key binding scoped to this element, without having to define an
+    /// action and add it to the keymap. See [`Interactivity::on_shortcut`] for how shortcuts and key
+    /// bindings take precedence over each other.
+    /// The fluent API equivalent to [`Interactivity::on_shortcut`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    fn on_shortcut(
+        mut self,
+        keystroke: &str,
+        listener: impl Fn(&KeyDownEvent, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.interactivity().on_shortcut(keystroke, listener);
+        self
+    }
+
     /// Bind the given callback to drop events of the given type, whether or not the drag started on this element.
     /// The fluent API equivalent to [`Interactivity::on_drop`].
     ///
@@ -1134,6 +1380,24 @@ pub trait StatefulInteractiveElement: InteractiveElement {
         self
     }
 
+    /// Bind the given callback to click events of this element with the given click count, such as 2 for
+    /// double clicks. The click count is determined by the platform, according to the user's double click
+    /// speed and distance settings.
+    /// The fluent API equivalent to [`Interactivity::on_multi_click`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    fn on_multi_click(
+        mut self,
+        click_count: usize,
+        listener: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        self.interactivity().on_multi_click(click_count, listener);
+        self
+    }
+
     /// On drag initiation, this callback will be used to create a new view to render the dragged value for a
     /// drag and drop operation. This API should also be used as the equivalent of 'on drag start' with
     /// the [`InteractiveElement::on_drag_move`] API.
@@ -1168,6 +1432,38 @@ pub trait StatefulInteractiveElement: InteractiveElement {
         self
     }
 
+    /// Bind the given callback to the hover intent start and end events of this element. Hover intent
+    /// starts once the mouse has rested over the element for a short delay without moving more than a
+    /// few pixels, and ends when the mouse leaves. The boolean passed to the callback is true when the
+    /// hover intent starts and false when it ends.
+    /// The fluent API equivalent to [`Interactivity::on_hover_intent`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    fn on_hover_intent(mut self, listener: impl Fn(&bool, &mut Window, &mut App) + 'static) -> Self
+    where
+        Self: Sized,
+    {
+        self.interactivity().on_hover_intent(listener);
+        self
+    }
+
+    /// Bind the given callback to long press events of this element. A long press is recognized when
+    /// the left mouse button is held down over the element without moving past a small threshold, and
+    /// suppresses the click or drag that the press would otherwise produce.
+    /// The fluent API equivalent to [`Interactivity::on_long_press`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    fn on_long_press(
+        mut self,
+        listener: impl Fn(&LongPressEvent, &mut Window, &mut App) + 'static,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        self.interactivity().on_long_press(listener);
+        self
+    }
+
     /// Use the given callback to construct a new tooltip view when the mouse hovers over this element.
     /// The fluent API equivalent to [`Interactivity::tooltip`].
     fn tooltip(mut self, build_tooltip: impl Fn(&mut Window, &mut App) -> AnyView + 'static) -> Self
@@ -1206,6 +1502,10 @@ pub(crate) type ScrollWheelListener =
 
 pub(crate) type ClickListener = Rc<dyn Fn(&ClickEvent, &mut Window, &mut App) + 'static>;
 
+pub(crate) type HoverIntentListener = Rc<dyn Fn(&bool, &mut Window, &mut App) + 'static>;
+
+pub(crate) type LongPressListener = Rc<dyn Fn(&LongPressEvent, &mut Window, &mut App) + 'static>;
+
 pub(crate) type DragListener =
     Box<dyn Fn(&dyn Any, Point<Pixels>, &mut Window, &mut App) -> AnyView + 'static>;
 
@@ -1227,6 +1527,8 @@ pub(crate) type KeyUpListener =
 pub(crate) type ModifiersChangedListener =
     Box<dyn Fn(&ModifiersChangedEvent, &mut Window, &mut App) + 'static>;
 
+pub(crate) type ShortcutListener = Box<dyn Fn(&KeyDownEvent, &mut Window, &mut App) + 'static>;
+
 pub(crate) type ActionListener =
     Box<dyn Fn(&dyn Any, DispatchPhase, &mut Window, &mut App) + 'static>;
 
@@ -1422,9 +1724,26 @@ impl Element for Div {
                     return hitbox;
                 }
 
+                let baseline_shifts = style
+                    .align_baselines
+                    .then(|| baseline_shifts(&request_layout.child_layout_ids, window));
                 window.with_element_offset(scroll_offset, |window| {
-                    for child in &mut self.children {
-                        child.prepaint(window, cx);
+                    let order = window.z_index_order(&request_layout.child_layout_ids);
+                    if order.is_none() && baseline_shifts.is_none() {
+                        for child in &mut self.children {
+                            child.prepaint(window, cx);
+                        }
+                        return;
+                    }
+
+                    let order = order.unwrap_or_else(|| (0..self.children.len()).collect());
+                    for ix in order {
+                        let shift = baseline_shifts
+                            .as_ref()
+                            .map_or(Pixels::ZERO, |shifts| shifts[ix]);
+                        window.with_element_offset(point(Pixels::ZERO, shift), |window| {
+                            self.children[ix].prepaint(window, cx)
+                        });
                     }
                 });
 
@@ -1443,7 +1762,7 @@ impl Element for Div {
         global_id: Option<&GlobalElementId>,
         inspector_id: Option<&InspectorElementId>,
         bounds: Bounds<Pixels>,
-        _request_layout: &mut Self::RequestLayoutState,
+        request_layout: &mut Self::RequestLayoutState,
         hitbox: &mut Option<Hitbox>,
         window: &mut Window,
         cx: &mut App,
@@ -1467,8 +1786,17 @@ impl Element for Div {
                         return;
                     }
 
-                    for child in &mut self.children {
-                        child.paint(window, cx);
+                    match window.z_index_order(&request_layout.child_layout_ids) {
+                        Some(order) => {
+                            for ix in order {
+                                self.children[ix].paint(window, cx);
+                            }
+                        }
+                        None => {
+                            for child in &mut self.children {
+                                child.paint(window, cx);
+                            }
+                        }
                     }
                 },
             )
@@ -1484,6 +1812,22 @@ impl IntoElement for Div {
     }
 }
 
+/// How far to move each child down so that their first baselines line up.
+fn baseline_shifts(child_layout_ids: &[LayoutId], window: &mut Window) -> SmallVec<[Pixels; 8]> {
+    let baselines = child_layout_ids
+        .iter()
+        .map(|layout_id| {
+            let top = window.layout_bounds(*layout_id).top();
+            top + window.layout_baseline(*layout_id)
+        })
+        .collect::<SmallVec<[Pixels; 8]>>();
+    let shared_baseline = baselines.iter().copied().max().unwrap_or_default();
+    baselines
+        .into_iter()
+        .map(|baseline| shared_baseline - baseline)
+        .collect()
+}
+
 /// The interactivity struct. Powers all of the general-purpose
 /// interactivity in the `Div` element.
 #[derive(Default)]
@@ -1504,6 +1848,10 @@ pub struct Interactivity {
     pub(crate) scroll_anchor: Option<ScrollAnchor>,
     pub(crate) scroll_offset: Option<Rc<RefCell<Point<Pixels>>>>,
     pub(crate) group: Option<SharedString>,
+    pub(crate) classes: SmallVec<[SharedString; 2]>,
+    pub(crate) container: Option<SharedString>,
+    pub(crate) size_styles: Vec<SizeStyle>,
+    pub(crate) context_values: Vec<Rc<dyn Any>>,
     /// The base style of the element, before any modifications are applied
     /// by focus, active, etc.
     pub base_style: Box<StyleRefinement>,
@@ -1526,12 +1874,16 @@ pub struct Interactivity {
     pub(crate) key_down_listeners: Vec<KeyDownListener>,
     pub(crate) key_up_listeners: Vec<KeyUpListener>,
     pub(crate) modifiers_changed_listeners: Vec<ModifiersChangedListener>,
+    pub(crate) hover_modifiers_changed_listeners: Vec<ModifiersChangedListener>,
+    pub(crate) shortcuts: Vec<(Keystroke, ShortcutListener)>,
     pub(crate) action_listeners: Vec<(TypeId, ActionListener)>,
     pub(crate) drop_listeners: Vec<(TypeId, DropListener)>,
     pub(crate) can_drop_predicate: Option<CanDropPredicate>,
     pub(crate) click_listeners: Vec<ClickListener>,
     pub(crate) drag_listener: Option<(Arc<dyn Any>, DragListener)>,
     pub(crate) hover_listener: Option<Box<dyn Fn(&bool, &mut Window, &mut App)>>,
+    pub(crate) hover_intent_listener: Option<HoverIntentListener>,
+    pub(crate) long_press_listener: Option<LongPressListener>,
     pub(crate) tooltip_builder: Option<TooltipBuilder>,
     pub(crate) window_control: Option<WindowControlArea>,
     pub(crate) hitbox_behavior: HitboxBehavior,
@@ -1556,6 +1908,27 @@ impl Interactivity {
         cx: &mut App,
         f: impl FnOnce(Style, &mut Window, &mut App) -> LayoutId,
     ) -> LayoutId {
+        if !self.classes.is_empty() {
+            let mut style = StyleRefinement::default();
+            for class in mem::take(&mut self.classes) {
+                if let Some(class_style) = cx.style_class(&class) {
+                    style.refine(&class_style);
+                }
+            }
+            style.refine(&self.base_style);
+            *self.base_style = style;
+        }
+
+        for size_style in mem::take(&mut self.size_styles) {
+            let size = match &size_style.container {
+                Some(container) => window.container_size(container),
+                None => Some(window.viewport_size()),
+            };
+            if size.is_some_and(|size| (size_style.predicate)(size)) {
+                self.base_style.refine(&size_style.style);
+            }
+        }
+
         #[cfg(any(feature = "inspector", debug_assertions))]
         window.with_inspector_state(
             _inspector_id,
@@ -1626,7 +1999,11 @@ impl Interactivity {
                 }
 
                 let style = self.compute_style_internal(None, element_state.as_mut(), window, cx);
-                let layout_id = f(style, window, cx);
+                let layout_id = window.with_context_values(&self.context_values, |window| {
+                    window.with_z_index(style.z_index, style.stacking_context, |window| {
+                        f(style, window, cx)
+                    })
+                });
                 (layout_id, element_state)
             },
         )
@@ -1644,6 +2021,9 @@ impl Interactivity {
         f: impl FnOnce(&Style, Point<Pixels>, Option<Hitbox>, &mut Window, &mut App) -> R,
     ) -> R {
         self.content_size = content_size;
+        if let Some(container) = self.container.clone() {
+            window.record_container_size(container, bounds.size, cx);
+        }
 
         #[cfg(any(feature = "inspector", debug_assertions))]
         window.with_inspector_state(
@@ -1694,7 +2074,10 @@ impl Interactivity {
 
                             let scroll_offset =
                                 self.clamp_scroll_position(bounds, &style, window, cx);
-                            let result = f(&style, scroll_offset, hitbox, window, cx);
+                            let result = window
+                                .with_context_values(&self.context_values, |window| {
+                                    f(&style, scroll_offset, hitbox, window, cx)
+                                });
                             (result, element_state)
                         },
                     )
@@ -1713,11 +2096,14 @@ impl Interactivity {
             || self.hover_style.is_some()
             || self.group_hover_style.is_some()
             || self.hover_listener.is_some()
+            || self.hover_intent_listener.is_some()
+            || self.long_press_listener.is_some()
             || !self.mouse_up_listeners.is_empty()
             || !self.mouse_down_listeners.is_empty()
             || !self.mouse_move_listeners.is_empty()
             || !self.click_listeners.is_empty()
             || !self.scroll_wheel_listeners.is_empty()
+            || !self.hover_modifiers_changed_listeners.is_empty()
             || self.drag_listener.is_some()
             || !self.drop_listeners.is_empty()
             || self.tooltip_builder.is_some()
@@ -2078,6 +2464,10 @@ impl Interactivity {
             })
         }
 
+        for listener in self.hover_modifiers_changed_listeners.drain(..) {
+            window.on_hovered_modifiers_changed(hitbox, listener);
+        }
+
         if self.hover_style.is_some()
             || self.base_style.mouse_cursor.is_some()
             || cx.active_drag.is_some() && !self.drag_over_styles.is_empty()
@@ -2289,6 +2679,160 @@ impl Interactivity {
                 });
             }
 
+            if let Some(hover_intent_listener) = self.hover_intent_listener.take() {
+                let hitbox = hitbox.clone();
+                let hover_intent_state = element_state
+                    .hover_intent_state
+                    .get_or_insert_with(Default::default)
+                    .clone();
+                let has_mouse_down = element_state
+                    .pending_mouse_down
+                    .get_or_insert_with(Default::default)
+                    .clone();
+
+                window.on_mouse_event(move |event: &MouseMoveEvent, phase, window, cx| {
+                    if phase != DispatchPhase::Bubble {
+                        return;
+                    }
+                    let is_hovered = has_mouse_down.borrow().is_none()
+                        && !cx.has_active_drag()
+                        && hitbox.is_hovered(window);
+
+                    if !is_hovered {
+                        let previous_state = hover_intent_state.replace(HoverIntentState::Idle);
+                        if matches!(previous_state, HoverIntentState::Active) {
+                            hover_intent_listener(&false, window, cx);
+                        }
+                        return;
+                    }
+
+                    // Restart the delay whenever the mouse strays too far from where it started resting.
+                    let should_schedule = match &*hover_intent_state.borrow() {
+                        HoverIntentState::Idle => true,
+                        HoverIntentState::Pending { origin, .. } => {
+                            (event.position - *origin).magnitude() > HOVER_INTENT_MOVE_TOLERANCE
+                        }
+                        HoverIntentState::Active => false,
+                    };
+                    if should_schedule {
+                        let task = window.spawn(cx, {
+                            let hover_intent_state = hover_intent_state.clone();
+                            let hover_intent_listener = hover_intent_listener.clone();
+                            let source_bounds = hitbox.bounds;
+                            async move |cx| {
+                                cx.background_executor().timer(HOVER_INTENT_DELAY).await;
+                                cx.update(|window, cx| {
+                                    let is_pending = matches!(
+                                        *hover_intent_state.borrow(),
+                                        HoverIntentState::Pending { .. }
+                                    );
+                                    if is_pending
+                                        && source_bounds.contains(&window.mouse_position())
+                                    {
+                                        *hover_intent_state.borrow_mut() = HoverIntentState::Active;
+                                        hover_intent_listener(&true, window, cx);
+                                    }
+                                })
+                                .ok();
+                            }
+                        });
+                        *hover_intent_state.borrow_mut() = HoverIntentState::Pending {
+                            origin: event.position,
+                            _task: task,
+                        };
+                    }
+                });
+            }
+
+            if let Some(long_press_listener) = self.long_press_listener.take() {
+                let pending_long_press = element_state
+                    .pending_long_press
+                    .get_or_insert_with(Default::default)
+                    .clone();
+                let pending_mouse_down = element_state
+                    .pending_mouse_down
+                    .get_or_insert_with(Default::default)
+                    .clone();
+                let clicked_state = element_state
+                    .clicked_state
+                    .get_or_insert_with(Default::default)
+                    .clone();
+
+                window.on_mouse_event({
+                    let pending_long_press = pending_long_press.clone();
+                    let hitbox = hitbox.clone();
+                    move |event: &MouseDownEvent, phase, window, cx| {
+                        if phase != DispatchPhase::Bubble
+                            || event.button != MouseButton::Left
+                            || !hitbox.is_hovered(window)
+                        {
+                            return;
+                        }
+
+                        let task = window.spawn(cx, {
+                            let pending_long_press = pending_long_press.clone();
+                            let pending_mouse_down = pending_mouse_down.clone();
+                            let clicked_state = clicked_state.clone();
+                            let long_press_listener = long_press_listener.clone();
+                            let down = event.clone();
+                            async move |cx| {
+                                cx.background_executor().timer(LONG_PRESS_DURATION).await;
+                                if pending_long_press.borrow_mut().take().is_none() {
+                                    return;
+                                }
+                                cx.update(|window, cx| {
+                                    if cx.has_active_drag() {
+                                        return;
+                                    }
+                                    // Consume the press, so that releasing the button doesn't
+                                    // produce a click and moving the mouse doesn't start a drag.
+                                    pending_mouse_down.borrow_mut().take();
+                                    *clicked_state.borrow_mut() = ElementClickedState::default();
+                                    let long_press = LongPressEvent {
+                                        down,
+                                        position: window.mouse_position(),
+                                    };
+                                    long_press_listener(&long_press, window, cx);
+                                    window.refresh();
+                                })
+                                .ok();
+                            }
+                        });
+                        *pending_long_press.borrow_mut() = Some(PendingLongPress {
+                            origin: event.position,
+                            _task: task,
+                        });
+                    }
+                });
+
+                // Cancel during the capture phase, so that it happens even if another event
+                // handler stops propagation, such as when a drag starts.
+                window.on_mouse_event({
+                    let pending_long_press = pending_long_press.clone();
+                    let hitbox = hitbox.clone();
+                    move |event: &MouseMoveEvent, phase, window, cx| {
+                        if phase != DispatchPhase::Capture {
+                            return;
+                        }
+                        let mut pending_long_press = pending_long_press.borrow_mut();
+                        if let Some(long_press) = pending_long_press.as_ref()
+                            && (cx.has_active_drag()
+                                || !hitbox.is_hovered(window)
+                                || (event.position - long_press.origin).magnitude()
+                                    > LONG_PRESS_MOVE_THRESHOLD)
+                        {
+                            pending_long_press.take();
+                        }
+                    }
+                });
+
+                window.on_mouse_event(move |_: &MouseUpEvent, phase, _window, _cx| {
+                    if phase == DispatchPhase::Capture {
+                        pending_long_press.borrow_mut().take();
+                    }
+                });
+            }
+
             if let Some(tooltip_builder) = self.tooltip_builder.take() {
                 let active_tooltip = element_state
                     .active_tooltip
@@ -2360,6 +2904,32 @@ impl Interactivity {
                     }
                 });
             }
+
+            // Holding enter or space on a focused element shows its active style, matching the
+            // keyboard click that fires when the key is released.
+            let active_state = element_state
+                .clicked_state
+                .get_or_insert_with(Default::default)
+                .clone();
+            if active_state.borrow().element {
+                window.on_key_event(move |event: &KeyUpEvent, phase, window, _cx| {
+                    if phase == DispatchPhase::Capture && is_keyboard_click_key(&event.keystroke) {
+                        *active_state.borrow_mut() = ElementClickedState::default();
+                        window.refresh();
+                    }
+                });
+            } else if is_focused && self.active_style.is_some() {
+                window.on_key_event(move |event: &KeyDownEvent, phase, window, _cx| {
+                    if phase.bubble()
+                        && !window.default_prevented()
+                        && is_keyboard_click_key(&event.keystroke)
+                        && !event.keystroke.modifiers.modified()
+                    {
+                        active_state.borrow_mut().element = true;
+                        window.refresh();
+                    }
+                });
+            }
         }
     }
 
@@ -2367,6 +2937,7 @@ impl Interactivity {
         let key_down_listeners = mem::take(&mut self.key_down_listeners);
         let key_up_listeners = mem::take(&mut self.key_up_listeners);
         let modifiers_changed_listeners = mem::take(&mut self.modifiers_changed_listeners);
+        let shortcuts = mem::take(&mut self.shortcuts);
         let action_listeners = mem::take(&mut self.action_listeners);
         if let Some(context) = self.key_context.clone() {
             window.set_key_context(context);
@@ -2390,6 +2961,10 @@ impl Interactivity {
             })
         }
 
+        for (keystroke, listener) in shortcuts {
+            window.on_shortcut(keystroke, listener);
+        }
+
         for (action_type, listener) in action_listeners {
             window.on_action(action_type, listener)
         }
@@ -2431,7 +3006,7 @@ impl Interactivity {
                 if phase == DispatchPhase::Bubble && hitbox.should_handle_scroll(window) {
                     let mut scroll_offset = scroll_offset.borrow_mut();
                     let old_scroll_offset = *scroll_offset;
-                    let delta = event.delta.pixel_delta(line_height);
+                    let delta = window.unconsumed_scroll_delta(event, line_height);
 
                     let mut delta_x = Pixels::ZERO;
                     if overflow.x == Overflow::Scroll {
@@ -2589,6 +3164,8 @@ pub struct InteractiveElementState {
     pub(crate) focus_handle: Option<FocusHandle>,
     pub(crate) clicked_state: Option<Rc<RefCell<ElementClickedState>>>,
     pub(crate) hover_state: Option<Rc<RefCell<bool>>>,
+    pub(crate) hover_intent_state: Option<Rc<RefCell<HoverIntentState>>>,
+    pub(crate) pending_long_press: Option<Rc<RefCell<Option<PendingLongPress>>>>,
     pub(crate) pending_mouse_down: Option<Rc<RefCell<Option<MouseDownEvent>>>>,
     pub(crate) scroll_offset: Option<Rc<RefCell<Point<Pixels>>>>,
     pub(crate) active_tooltip: Option<Rc<RefCell<Option<ActiveTooltip>>>>,
@@ -2610,6 +3187,33 @@ impl ElementClickedState {
     }
 }
 
+fn is_keyboard_click_key(keystroke: &Keystroke) -> bool {
+    keystroke.key == "enter" || keystroke.key == "space"
+}
+
+/// The progress of recognizing hover intent on an element.
+#[derive(Default)]
+pub(crate) enum HoverIntentState {
+    /// The element isn't hovered.
+    #[default]
+    Idle,
+    /// The element is hovered, and the mouse is resting near `origin`. Waiting for the delay to
+    /// elapse before starting hover intent.
+    Pending {
+        origin: Point<Pixels>,
+        _task: Task<()>,
+    },
+    /// Hover intent has started and lasts until the element is no longer hovered.
+    Active,
+}
+
+/// A left mouse button press that becomes a long press once its task's delay elapses, unless it
+/// is cancelled first.
+pub(crate) struct PendingLongPress {
+    origin: Point<Pixels>,
+    _task: Task<()>,
+}
+
 pub(crate) enum ActiveTooltip {
     /// Currently delaying before showing the tooltip.
     WaitingForShow { _task: Task<()> },
@@ -3268,3 +3872,221 @@ impl ScrollHandle {
         self.0.borrow().child_bounds.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, Modifiers, TestAppContext, VisualTestContext};
+
+    #[crate::test]
+    fn test_style_classes(cx: &mut TestAppContext) {
+        struct PanelView;
+
+        impl Render for PanelView {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                div().child(
+                    div()
+                        .class("panel")
+                        .class("wide")
+                        .h(px(10.))
+                        .debug_selector(|| "panel".into()),
+                )
+            }
+        }
+
+        cx.update(|cx| {
+            cx.define_style_class("panel", |_| {
+                StyleRefinement::default().w(px(20.)).h(px(20.))
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| PanelView);
+        cx.run_until_parked();
+        assert_eq!(
+            cx.debug_bounds("panel").unwrap().size,
+            size(px(20.), px(10.))
+        );
+
+        cx.update(|_, cx| {
+            cx.define_style_class("wide", |_| StyleRefinement::default().w(px(50.)));
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            cx.debug_bounds("panel").unwrap().size,
+            size(px(50.), px(10.))
+        );
+
+        cx.update(|_, cx| cx.remove_style_class("wide"));
+        cx.run_until_parked();
+        assert_eq!(
+            cx.debug_bounds("panel").unwrap().size,
+            size(px(20.), px(10.))
+        );
+    }
+
+    #[crate::test]
+    fn test_keyboard_active_style(cx: &mut TestAppContext) {
+        struct ButtonView(FocusHandle);
+
+        impl Render for ButtonView {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                div().child(
+                    div()
+                        .id("button")
+                        .track_focus(&self.0)
+                        .w(px(10.))
+                        .h(px(10.))
+                        .active(|style| style.w(px(20.)))
+                        .debug_selector(|| "button".into()),
+                )
+            }
+        }
+
+        let (view, cx) = cx.add_window_view(|_, cx| ButtonView(cx.focus_handle()));
+        cx.update(|window, cx| view.read(cx).0.focus(window));
+        cx.run_until_parked();
+        assert_eq!(cx.debug_bounds("button").unwrap().size.width, px(10.));
+
+        let space = Keystroke::parse("space").unwrap();
+        cx.simulate_event(KeyDownEvent {
+            keystroke: space.clone(),
+            is_held: false,
+            prefer_character_input: false,
+        });
+        cx.run_until_parked();
+        assert_eq!(cx.debug_bounds("button").unwrap().size.width, px(20.));
+
+        cx.simulate_event(KeyUpEvent { keystroke: space });
+        cx.run_until_parked();
+        assert_eq!(cx.debug_bounds("button").unwrap().size.width, px(10.));
+    }
+
+    #[crate::test]
+    fn test_size_dependent_styles(cx: &mut TestAppContext) {
+        struct ResponsiveView;
+
+        impl Render for ResponsiveView {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                div()
+                    .child(
+                        div()
+                            .h(px(10.))
+                            .w(px(10.))
+                            .when_window_width_below(px(500.), |style| style.w(px(5.)))
+                            .debug_selector(|| "window".into()),
+                    )
+                    .child(
+                        div().container("sidebar").w(px(100.)).child(
+                            div()
+                                .h(px(10.))
+                                .when_container_width_below("sidebar", px(200.), |style| {
+                                    style.h(px(20.))
+                                })
+                                .debug_selector(|| "container".into()),
+                        ),
+                    )
+            }
+        }
+
+        let (_, cx) = cx.add_window_view(|_, _| ResponsiveView);
+        cx.simulate_resize(size(px(800.), px(600.)));
+        cx.run_until_parked();
+        assert_eq!(cx.debug_bounds("window").unwrap().size.width, px(10.));
+        assert_eq!(cx.debug_bounds("container").unwrap().size.height, px(20.));
+        assert_eq!(
+            cx.update(|window, _| window.container_size("sidebar")),
+            Some(size(px(100.), px(20.)))
+        );
+
+        cx.simulate_resize(size(px(400.), px(600.)));
+        cx.run_until_parked();
+        assert_eq!(cx.debug_bounds("window").unwrap().size.width, px(5.));
+    }
+
+    #[derive(Default)]
+    struct HoverIntentView {
+        events: Vec<bool>,
+    }
+
+    impl Render for HoverIntentView {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().child(
+                div()
+                    .id("target")
+                    .size(px(50.))
+                    .on_hover_intent(cx.listener(|this, hovered, _, _| this.events.push(*hovered))),
+            )
+        }
+    }
+
+    fn move_mouse(position: Point<Pixels>, cx: &mut VisualTestContext) {
+        cx.simulate_mouse_move(position, None, Modifiers::none());
+    }
+
+    fn advance_clock(duration: Duration, cx: &mut VisualTestContext) {
+        cx.executor().advance_clock(duration);
+        cx.run_until_parked();
+    }
+
+    #[crate::test]
+    fn test_hover_intent_delay(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| HoverIntentView::default());
+
+        move_mouse(point(px(10.), px(10.)), cx);
+        advance_clock(HOVER_INTENT_DELAY / 2, cx);
+        view.read_with(cx, |view, _| assert!(view.events.is_empty()));
+
+        advance_clock(HOVER_INTENT_DELAY / 2, cx);
+        view.read_with(cx, |view, _| assert_eq!(view.events, [true]));
+
+        // Moving within the element once the intent is reported doesn't report it again.
+        move_mouse(point(px(30.), px(30.)), cx);
+        advance_clock(HOVER_INTENT_DELAY, cx);
+        view.read_with(cx, |view, _| assert_eq!(view.events, [true]));
+
+        move_mouse(point(px(100.), px(100.)), cx);
+        view.read_with(cx, |view, _| assert_eq!(view.events, [true, false]));
+    }
+
+    #[crate::test]
+    fn test_hover_intent_move_tolerance(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| HoverIntentView::default());
+
+        // Small movements don't restart the delay.
+        move_mouse(point(px(10.), px(10.)), cx);
+        advance_clock(HOVER_INTENT_DELAY / 2, cx);
+        move_mouse(point(px(12.), px(12.)), cx);
+        advance_clock(HOVER_INTENT_DELAY / 2, cx);
+        view.read_with(cx, |view, _| assert_eq!(view.events, [true]));
+
+        move_mouse(point(px(100.), px(100.)), cx);
+        view.read_with(cx, |view, _| assert_eq!(view.events, [true, false]));
+
+        // Larger ones do.
+        move_mouse(point(px(10.), px(10.)), cx);
+        advance_clock(HOVER_INTENT_DELAY / 2, cx);
+        move_mouse(point(px(30.), px(30.)), cx);
+        advance_clock(HOVER_INTENT_DELAY / 2, cx);
+        view.read_with(cx, |view, _| assert_eq!(view.events, [true, false]));
+
+        advance_clock(HOVER_INTENT_DELAY / 2, cx);
+        view.read_with(cx, |view, _| assert_eq!(view.events, [true, false, true]));
+    }
+
+    #[crate::test]
+    fn test_hover_intent_cancelled_on_leave(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| HoverIntentView::default());
+
+        move_mouse(point(px(10.), px(10.)), cx);
+        advance_clock(HOVER_INTENT_DELAY / 2, cx);
+        move_mouse(point(px(100.), px(100.)), cx);
+        advance_clock(HOVER_INTENT_DELAY, cx);
+        view.read_with(cx, |view, _| assert!(view.events.is_empty()));
+
+        // Coming back starts the delay over.
+        move_mouse(point(px(10.), px(10.)), cx);
+        advance_clock(HOVER_INTENT_DELAY / 2, cx);
+        view.read_with(cx, |view, _| assert!(view.events.is_empty()));
+        advance_clock(HOVER_INTENT_DELAY / 2, cx);
+        view.read_with(cx, |view, _| assert_eq!(view.events, [true]));
+    }
+}
@@ -0,0 +1,628 @@
+use std::{
+    future::Future,
+    sync::{Arc, LazyLock},
+    task::{Poll, Waker},
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, ensure};
+#[cfg(target_os = "macos")]
+use core_video::pixel_buffer::CVPixelBuffer;
+use parking_lot::{Condvar, Mutex};
+use refineable::Refineable as _;
+use util::ResultExt as _;
+
+use crate::{
+    App, Bounds, DevicePixels, Element, ElementId, GlobalElementId, InspectorElementId,
+    IntoElement, LayoutId, ObjectFit, Pixels, Point, Size, StreamingTexture, Style,
+    StyleRefinement, Styled, Window, size,
+};
+
+/// The pixel layout and color encoding of frames submitted to an [`ExternalImageHandle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalPixelFormat {
+    /// 8-bit BGRA with sRGB encoded color.
+    Bgra8,
+    /// 8-bit RGBA with sRGB encoded color.
+    Rgba8,
+    /// 8-bit RGBA with linear color, as produced by many renderers.
+    Rgba8Linear,
+    /// 4:2:0 Y'CbCr in BT.709 limited range, as produced by most cameras and video decoders: a
+    /// full resolution luma plane followed by an interleaved, half resolution chroma plane.
+    Nv12,
+}
+
+impl ExternalPixelFormat {
+    fn byte_len(&self, width: usize, height: usize) -> usize {
+        match self {
+            Self::Bgra8 | Self::Rgba8 | Self::Rgba8Linear => width * height * 4,
+            Self::Nv12 => width * height + width.div_ceil(2) * height.div_ceil(2) * 2,
+        }
+    }
+}
+
+/// A frame produced outside of GPUI, to be submitted to an [`ExternalImageHandle`].
+pub struct ExternalFrame<'a> {
+    /// The width of the frame, in pixels.
+    pub width: u32,
+    /// The height of the frame, in pixels.
+    pub height: u32,
+    /// How the frame's pixels are laid out and encoded.
+    pub format: ExternalPixelFormat,
+    /// The frame's pixels, tightly packed.
+    pub data: &'a [u8],
+}
+
+/// Signaled once a frame submitted to an [`ExternalImageHandle`] has been drawn to a window or
+/// replaced by a newer frame, so producers can pace themselves instead of queueing frames.
+///
+/// Producers running on their own thread can block on [`ExternalImageFence::wait`], while async
+/// producers can await [`ExternalImageFence::signaled`].
+#[derive(Clone, Debug, Default)]
+pub struct ExternalImageFence(Arc<FenceState>);
+
+#[derive(Debug, Default)]
+struct FenceState {
+    inner: Mutex<FenceInner>,
+    condvar: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct FenceInner {
+    signaled: bool,
+    wakers: Vec<Waker>,
+}
+
+impl ExternalImageFence {
+    /// Returns whether the frame has been drawn or replaced.
+    pub fn is_signaled(&self) -> bool {
+        self.0.inner.lock().signaled
+    }
+
+    /// Block the current thread until the frame has been drawn or replaced.
+    pub fn wait(&self) {
+        let mut inner = self.0.inner.lock();
+        while !inner.signaled {
+            self.0.condvar.wait(&mut inner);
+        }
+    }
+
+    /// Block the current thread until the frame has been drawn or replaced, or until `timeout`
+    /// elapses. Returns whether the fence was signaled.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let mut inner = self.0.inner.lock();
+        self.0
+            .condvar
+            .wait_while_for(&mut inner, |inner| !inner.signaled, timeout);
+        inner.signaled
+    }
+
+    /// Returns a future that resolves once the frame has been drawn or replaced.
+    pub fn signaled(&self) -> impl Future<Output = ()> + use<> {
+        let fence = self.clone();
+        std::future::poll_fn(move |cx| {
+            let mut inner = fence.0.inner.lock();
+            if inner.signaled {
+                return Poll::Ready(());
+            }
+            if !inner.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                inner.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+    }
+
+    fn signal(&self) {
+        let wakers = {
+            let mut inner = self.0.inner.lock();
+            inner.signaled = true;
+            std::mem::take(&mut inner.wakers)
+        };
+        self.0.condvar.notify_all();
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// A handle through which a producer outside of GPUI, such as a camera capture or a video
+/// decoder, updates the contents of an [`ExternalImage`] element.
+///
+/// Frames can be submitted from any thread. Frames in memory are converted to sRGB encoded BGRA
+/// on the submitting thread and uploaded in place into a texture the element keeps across
+/// frames. On macOS, frames that already live on the GPU can be submitted as a
+/// [`CVPixelBuffer`] with [`ExternalImageHandle::submit_surface`], which draws them without
+/// copying their pixels.
+#[derive(Clone)]
+pub struct ExternalImageHandle(Arc<Mutex<ExternalImageState>>);
+
+struct ExternalImageState {
+    contents: Option<ExternalImageContents>,
+    fence: Option<ExternalImageFence>,
+    generation: usize,
+    live: bool,
+}
+
+#[derive(Clone)]
+enum ExternalImageContents {
+    /// Converted BGRA pixels.
+    Pixels {
+        size: Size<DevicePixels>,
+        bytes: Arc<[u8]>,
+    },
+    #[cfg(target_os = "macos")]
+    Surface(CVPixelBuffer),
+}
+
+impl Default for ExternalImageHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExternalImageHandle {
+    /// Create a handle with no frame yet, which keeps redrawing elements displaying it until
+    /// [`ExternalImageHandle::finish`] is called.
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(ExternalImageState {
+            contents: None,
+            fence: None,
+            generation: 0,
+            live: true,
+        })))
+    }
+
+    /// Replace the displayed frame, returning a fence that is signaled once it has been drawn.
+    pub fn submit(&self, frame: ExternalFrame) -> Result<ExternalImageFence> {
+        let (width, height) = (frame.width as usize, frame.height as usize);
+        let expected_len = frame.format.byte_len(width, height);
+        ensure!(
+            frame.data.len() >= expected_len,
+            "external frame of {width}x{height} {:?} needs {expected_len} bytes, got {}",
+            frame.format,
+            frame.data.len()
+        );
+        let frame_size = size(
+            i32::try_from(frame.width).context("external frame is too wide")?,
+            i32::try_from(frame.height).context("external frame is too tall")?,
+        )
+        .map(DevicePixels);
+
+        let bytes = convert_to_bgra(&frame, width, height);
+        Ok(self.replace(ExternalImageContents::Pixels {
+            size: frame_size,
+            bytes: bytes.into(),
+        }))
+    }
+
+    /// Replace the displayed frame with a pixel buffer that is drawn directly by the GPU,
+    /// returning a fence that is signaled once it has been drawn.
+    #[cfg(target_os = "macos")]
+    pub fn submit_surface(&self, surface: CVPixelBuffer) -> ExternalImageFence {
+        self.replace(ExternalImageContents::Surface(surface))
+    }
+
+    /// Stop redrawing elements displaying this handle every frame, keeping the last frame
+    /// displayed. Further frames can still be submitted, but are only shown once the elements
+    /// are redrawn for another reason.
+    pub fn finish(&self) {
+        self.0.lock().live = false;
+    }
+
+    fn replace(&self, contents: ExternalImageContents) -> ExternalImageFence {
+        let fence = ExternalImageFence::default();
+        let mut state = self.0.lock();
+        if let Some(replaced) = state.fence.replace(fence.clone()) {
+            replaced.signal();
+        }
+        state.contents = Some(contents);
+        state.generation += 1;
+        fence
+    }
+
+    fn current(&self) -> (Option<ExternalImageContents>, usize, bool) {
+        let state = self.0.lock();
+        (state.contents.clone(), state.generation, state.live)
+    }
+
+    fn signal_drawn(&self, generation: usize) {
+        let state = self.0.lock();
+        if state.generation == generation
+            && let Some(fence) = &state.fence
+        {
+            fence.signal();
+        }
+    }
+}
+
+/// Maps linear 8-bit channel values to sRGB encoded ones.
+static LINEAR_TO_SRGB: LazyLock<[u8; 256]> = LazyLock::new(|| {
+    std::array::from_fn(|value| {
+        (crate::color::linear_to_srgb(value as f32 / 255.) * 255.).round() as u8
+    })
+});
+
+fn convert_to_bgra(frame: &ExternalFrame, width: usize, height: usize) -> Vec<u8> {
+    let data = frame.data;
+    let mut pixels = vec![0; width * height * 4];
+    match frame.format {
+        ExternalPixelFormat::Bgra8 => pixels.copy_from_slice(&data[..pixels.len()]),
+        ExternalPixelFormat::Rgba8 => {
+            for (target, source) in pixels.chunks_exact_mut(4).zip(data.chunks_exact(4)) {
+                target.copy_from_slice(&[source[2], source[1], source[0], source[3]]);
+            }
+        }
+        ExternalPixelFormat::Rgba8Linear => {
+            let encode = &*LINEAR_TO_SRGB;
+            for (target, source) in pixels.chunks_exact_mut(4).zip(data.chunks_exact(4)) {
+                target.copy_from_slice(&[
+                    encode[source[2] as usize],
+                    encode[source[1] as usize],
+                    encode[source[0] as usize],
+                    source[3],
+                ]);
+            }
+        }
+        ExternalPixelFormat::Nv12 => {
+            let (luma, chroma) = data.split_at(width * height);
+            let chroma_width = width.div_ceil(2);
+            for y in 0..height {
+                for x in 0..width {
+                    let chroma_offset = ((y / 2) * chroma_width + x / 2) * 2;
+                    let [r, g, b] = bt709_to_srgb(
+                        luma[y * width + x],
+                        chroma[chroma_offset],
+                        chroma[chroma_offset + 1],
+                    );
+                    let offset = (y * width + x) * 4;
+                    pixels[offset..offset + 4].copy_from_slice(&[b, g, r, 255]);
+                }
+            }
+        }
+    }
+    pixels
+}
+
+/// Converts a BT.709 limited range Y'CbCr sample to R'G'B'. BT.709 and sRGB share primaries, and
+/// the transfer functions are close enough to display the result as sRGB.
+fn bt709_to_srgb(y: u8, cb: u8, cr: u8) -> [u8; 3] {
+    let y = (y as f32 - 16.) / 219.;
+    let cb = (cb as f32 - 128.) / 224.;
+    let cr = (cr as f32 - 128.) / 224.;
+    let r = y + 1.5748 * cr;
+    let g = y - 0.1873 * cb - 0.4681 * cr;
+    let b = y + 1.8556 * cb;
+    [r, g, b].map(|channel| (channel.clamp(0., 1.) * 255.).round() as u8)
+}
+
+/// Create an element that displays the latest frame submitted to the given handle.
+pub fn external_image(handle: ExternalImageHandle) -> ExternalImage {
+    ExternalImage {
+        id: None,
+        handle,
+        object_fit: ObjectFit::Contain,
+        style: StyleRefinement::default(),
+    }
+}
+
+/// An element that displays frames produced outside of GPUI, see [`ExternalImageHandle`].
+///
+/// Frames in memory are uploaded in place into a texture, and only when a new frame has been
+/// submitted since the last draw. Give the element an id with [`ExternalImage::id`] so the
+/// texture can be reused across frames.
+pub struct ExternalImage {
+    id: Option<ElementId>,
+    handle: ExternalImageHandle,
+    object_fit: ObjectFit,
+    style: StyleRefinement,
+}
+
+impl ExternalImage {
+    /// Assign an id to this element, allowing its texture to be cached between frames.
+    pub fn id(mut self, id: impl Into<ElementId>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Set how frames are fit into the element's bounds.
+    pub fn object_fit(mut self, object_fit: ObjectFit) -> Self {
+        self.object_fit = object_fit;
+        self
+    }
+}
+
+impl IntoElement for ExternalImage {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Styled for ExternalImage {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+/// The texture holding the pixels of a frame, along with the frame's generation.
+struct ExternalImageTexture {
+    texture: StreamingTexture,
+    generation: usize,
+}
+
+impl ExternalImageTexture {
+    /// Returns a texture holding the given frame, updating this one in place when it has the
+    /// right size.
+    fn upload(
+        texture: Option<Self>,
+        frame_size: Size<DevicePixels>,
+        bytes: &[u8],
+        generation: usize,
+        window: &mut Window,
+    ) -> Option<Self> {
+        let region = Bounds::new(Point::default(), frame_size);
+        if let Some(mut texture) = texture
+            && texture.texture.size() == frame_size
+        {
+            if texture.generation == generation {
+                return Some(texture);
+            }
+            if texture.texture.update(region, bytes) {
+                texture.generation = generation;
+                return Some(texture);
+            }
+        }
+
+        let texture = window.create_streaming_texture(frame_size).log_err()?;
+        texture.update(region, bytes).then_some(Self {
+            texture,
+            generation,
+        })
+    }
+}
+
+/// The frame to draw, along with its generation.
+type ExternalImagePrepaintState = Option<(ExternalImageContents, usize)>;
+
+impl Element for ExternalImage {
+    type RequestLayoutState = Style;
+    type PrepaintState = ExternalImagePrepaintState;
+
+    fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        let layout_id = window.request_layout(style.clone(), [], cx);
+        (layout_id, style)
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _style: &mut Style,
+        window: &mut Window,
+        _cx: &mut App,
+    ) -> Self::PrepaintState {
+        let (contents, generation, live) = self.handle.current();
+        if live {
+            window.request_animation_frame();
+        }
+        Some((contents?, generation))
+    }
+
+    fn paint(
+        &mut self,
+        id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        style: &mut Style,
+        frame: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let Some((contents, generation)) = frame.take() else {
+            return;
+        };
+        style.paint(bounds, window, cx, |window, _| match contents {
+            ExternalImageContents::Pixels {
+                size: frame_size,
+                bytes,
+            } => {
+                let fitted_bounds = self.object_fit.get_bounds(bounds, frame_size);
+                let corner_radii = style
+                    .corner_radii
+                    .to_pixels(window.rem_size())
+                    .clamp_radii_for_quad_size(fitted_bounds.size);
+                window.with_optional_element_state::<Option<ExternalImageTexture>, _>(
+                    id,
+                    |state, window| {
+                        let cached = state.is_some();
+                        let texture = ExternalImageTexture::upload(
+                            state.flatten().flatten(),
+                            frame_size,
+                            &bytes,
+                            generation,
+                            window,
+                        );
+                        if let Some(texture) = &texture {
+                            window
+                                .paint_streaming_texture(
+                                    fitted_bounds,
+                                    corner_radii,
+                                    &texture.texture,
+                                    Bounds::new(Point::default(), frame_size),
+                                )
+                                .log_err();
+                        }
+                        if cached {
+                            ((), Some(texture))
+                        } else {
+                            // Without an id there is no state to remember this texture in, so
+                            // release it once the frame has been drawn.
+                            window.on_next_frame(move |_, _| drop(texture));
+                            ((), None)
+                        }
+                    },
+                );
+            }
+            #[cfg(target_os = "macos")]
+            ExternalImageContents::Surface(surface) => {
+                let surface_size = size(surface.get_width().into(), surface.get_height().into());
+                let fitted_bounds = self.object_fit.get_bounds(bounds, surface_size);
+                window.paint_surface(fitted_bounds, surface);
+            }
+        });
+        self.handle.signal_drawn(generation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, Entity, Render, TestAppContext, VisualTestContext, px};
+
+    #[test]
+    fn test_convert_to_bgra() {
+        let rgba = ExternalFrame {
+            width: 1,
+            height: 1,
+            format: ExternalPixelFormat::Rgba8,
+            data: &[10, 20, 30, 40],
+        };
+        assert_eq!(convert_to_bgra(&rgba, 1, 1), [30, 20, 10, 40]);
+
+        let linear = ExternalFrame {
+            format: ExternalPixelFormat::Rgba8Linear,
+            data: &[0, 255, 55, 128],
+            ..rgba
+        };
+        assert_eq!(convert_to_bgra(&linear, 1, 1), [128, 255, 0, 128]);
+
+        // Limited range black and white, with neutral chroma.
+        let nv12 = ExternalFrame {
+            width: 2,
+            height: 1,
+            format: ExternalPixelFormat::Nv12,
+            data: &[16, 235, 128, 128],
+        };
+        assert_eq!(
+            convert_to_bgra(&nv12, 2, 1),
+            [0, 0, 0, 255, 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn test_submit_signals_replaced_fence() {
+        let handle = ExternalImageHandle::new();
+        let frame = |data: &'static [u8]| ExternalFrame {
+            width: 1,
+            height: 1,
+            format: ExternalPixelFormat::Bgra8,
+            data,
+        };
+        assert!(handle.submit(frame(&[0, 0, 0])).is_err());
+
+        let first = handle.submit(frame(&[0, 0, 0, 255])).unwrap();
+        assert!(!first.is_signaled());
+        let second = handle.submit(frame(&[255, 255, 255, 255])).unwrap();
+        assert!(first.is_signaled());
+        assert!(!second.is_signaled());
+
+        handle.signal_drawn(1);
+        assert!(!second.is_signaled());
+        handle.signal_drawn(2);
+        assert!(second.is_signaled());
+    }
+
+    #[crate::test]
+    async fn test_fence_wakes_waiters(cx: &mut TestAppContext) {
+        let fence = ExternalImageFence::default();
+        assert!(!fence.wait_timeout(Duration::from_millis(1)));
+
+        let waiter = std::thread::spawn({
+            let fence = fence.clone();
+            move || fence.wait_timeout(Duration::from_secs(10))
+        });
+        let signaled = cx.executor().spawn(fence.signaled());
+        fence.signal();
+        assert!(waiter.join().unwrap());
+        signaled.await;
+        fence.wait();
+    }
+
+    struct Player {
+        handle: ExternalImageHandle,
+    }
+
+    impl Render for Player {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            external_image(self.handle.clone())
+                .id("player")
+                .size(px(8.))
+        }
+    }
+
+    fn submit_and_draw(
+        handle: &ExternalImageHandle,
+        frame: ExternalFrame,
+        view: &Entity<Player>,
+        cx: &mut VisualTestContext,
+    ) -> ExternalImageFence {
+        let fence = handle.submit(frame).unwrap();
+        view.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        fence
+    }
+
+    #[crate::test]
+    fn test_external_image_reuses_its_texture(cx: &mut TestAppContext) {
+        let handle = ExternalImageHandle::new();
+        // Draw only when notified, rather than on every animation frame.
+        handle.finish();
+        let (view, cx) = cx.add_window_view(|_, _| Player {
+            handle: handle.clone(),
+        });
+        let frame = |width: u32, data: &'static [u8]| ExternalFrame {
+            width,
+            height: 1,
+            format: ExternalPixelFormat::Bgra8,
+            data,
+        };
+
+        let fence = submit_and_draw(&handle, frame(1, &[0, 0, 0, 255]), &view, cx);
+        assert!(fence.is_signaled());
+        let stats = cx.update(|window, _| window.frame_stats());
+        assert_eq!(stats.atlas_textures, 1);
+        assert_eq!(stats.polychrome_sprites, 1);
+
+        // Frames of the same size are uploaded into the same texture.
+        let fence = submit_and_draw(&handle, frame(1, &[255, 255, 255, 255]), &view, cx);
+        assert!(fence.is_signaled());
+        let stats = cx.update(|window, _| window.frame_stats());
+        assert_eq!(stats.atlas_textures, 1);
+        assert_eq!(stats.atlas_uploads, 0);
+
+        // A frame of another size replaces the texture.
+        let fence = submit_and_draw(&handle, frame(2, &[0; 8]), &view, cx);
+        assert!(fence.is_signaled());
+        let stats = cx.update(|window, _| window.frame_stats());
+        assert_eq!(stats.atlas_textures, 1);
+        assert_eq!(stats.atlas_uploads, 1);
+    }
+}
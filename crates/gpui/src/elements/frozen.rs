@@ -0,0 +1,275 @@
+//! Frozen elements, which lay out, prepaint and paint an expensive but static subtree, such as
+//! rendered markdown or a complex SVG panel, once and then replay what it drew in later frames.
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    mem,
+    ops::Range,
+};
+
+use collections::FxHashSet;
+
+use crate::{
+    AnyElement, App, Bounds, ContentMask, Element, ElementId, EntityId, GlobalElementId,
+    InspectorElementId, IntoElement, LayoutId, PaintIndex, Pixels, PrepaintStateIndex, Size, Style,
+    StyleRefinement, Styled, TextStyle, Window,
+};
+use refineable::Refineable as _;
+
+/// An extension trait for freezing elements.
+pub trait FreezeExt {
+    /// Draw this element once and replay what it drew in later frames, skipping its layout,
+    /// prepaint and paint, for as long as `key` stays the same. The element is drawn again when
+    /// the key changes, when its bounds, clipping or text style change, when the window's scale
+    /// factor changes, so that it's rasterized at the new resolution, or when the window is
+    /// refreshed. Changes to anything else it renders, such as entities it reads, only show once
+    /// the key changes.
+    ///
+    /// While it's frozen, the element isn't laid out either, so it keeps the size it was drawn at.
+    /// To keep sizing it with its parent, such as when the window resizes, style the frozen
+    /// element like the element's own size, for example with `size_full`.
+    ///
+    /// Listeners, hitboxes and focus in a frozen element keep working, but the element is still
+    /// built every frame its parent renders; only drawing it is skipped.
+    fn freeze(self, id: impl Into<ElementId>, key: impl Hash) -> Frozen<Self>
+    where
+        Self: Sized,
+    {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        Frozen {
+            id: id.into(),
+            key: hasher.finish(),
+            style: None,
+            element: Some(self),
+        }
+    }
+}
+
+impl<E: IntoElement> FreezeExt for E {}
+
+/// An element that replays what its child drew until it's invalidated, created with
+/// [`FreezeExt::freeze`].
+pub struct Frozen<E> {
+    id: ElementId,
+    key: u64,
+    style: Option<StyleRefinement>,
+    element: Option<E>,
+}
+
+impl<E> Styled for Frozen<E> {
+    fn style(&mut self) -> &mut StyleRefinement {
+        self.style.get_or_insert_default()
+    }
+}
+
+/// The state of a [`Frozen`] element kept across frames, which must be replayed every frame to
+/// stay valid.
+struct FrozenState {
+    cache_key: FrozenCacheKey,
+    size: Size<Pixels>,
+    prepaint_range: Range<PrepaintStateIndex>,
+    paint_range: Range<PaintIndex>,
+    accessed_entities: FxHashSet<EntityId>,
+}
+
+#[derive(PartialEq)]
+struct FrozenCacheKey {
+    key: u64,
+    scale_factor: f32,
+    bounds: Bounds<Pixels>,
+    content_mask: ContentMask<Pixels>,
+    text_style: TextStyle,
+}
+
+impl<E: IntoElement + 'static> IntoElement for Frozen<E> {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl<E: IntoElement + 'static> Element for Frozen<E> {
+    type RequestLayoutState = Option<AnyElement>;
+    type PrepaintState = Option<AnyElement>;
+
+    fn id(&self) -> Option<ElementId> {
+        Some(self.id.clone())
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let frozen_size =
+            window.with_element_state(global_id.unwrap(), |state: Option<FrozenState>, window| {
+                let size = state.as_ref().and_then(|state| {
+                    let valid = state.cache_key.key == self.key
+                        && state.cache_key.scale_factor == window.scale_factor()
+                        && !window.refreshing
+                        && !window.is_inspector_picking(cx);
+                    valid.then_some(state.size)
+                });
+                (size, state)
+            });
+
+        if let Some(size) = frozen_size {
+            let mut style = Style::default();
+            if let Some(refinement) = self.style.as_ref() {
+                style.refine(refinement);
+            } else {
+                style.size = size.map(Into::into);
+                style.flex_shrink = 0.;
+            }
+            return (window.request_layout(style, None, cx), None);
+        }
+
+        let mut element = self.element.take().unwrap().into_any_element();
+        let layout_id = element.request_layout(window, cx);
+        (layout_id, Some(element))
+    }
+
+    fn prepaint(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        element: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<AnyElement> {
+        window.with_element_state(global_id.unwrap(), |state: Option<FrozenState>, window| {
+            let cache_key = FrozenCacheKey {
+                key: self.key,
+                scale_factor: window.scale_factor(),
+                bounds,
+                content_mask: window.content_mask(),
+                text_style: window.text_style(),
+            };
+
+            if element.is_none()
+                && let Some(mut state) = state
+                && state.cache_key == cache_key
+            {
+                let prepaint_start = window.prepaint_index();
+                window.reuse_prepaint(state.prepaint_range.clone());
+                cx.entities.extend_accessed(&state.accessed_entities);
+                let prepaint_end = window.prepaint_index();
+                state.prepaint_range = prepaint_start..prepaint_end;
+                return (None, state);
+            }
+
+            let refreshing = mem::replace(&mut window.refreshing, true);
+            let prepaint_start = window.prepaint_index();
+            let (element, accessed_entities) = cx.detect_accessed_entities(|cx| {
+                if let Some(mut element) = element.take() {
+                    element.prepaint(window, cx);
+                    return element;
+                }
+                // The element was laid out at its frozen size, but has to be drawn anyway.
+                let mut element = self.element.take().unwrap().into_any_element();
+                element.layout_as_root(bounds.size.into(), window, cx);
+                element.prepaint_at(bounds.origin, window, cx);
+                element
+            });
+            let prepaint_end = window.prepaint_index();
+            window.refreshing = refreshing;
+
+            (
+                Some(element),
+                FrozenState {
+                    cache_key,
+                    size: bounds.size,
+                    prepaint_range: prepaint_start..prepaint_end,
+                    paint_range: PaintIndex::default()..PaintIndex::default(),
+                    accessed_entities,
+                },
+            )
+        })
+    }
+
+    fn paint(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        _: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        element: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        window.with_element_state(global_id.unwrap(), |state: Option<FrozenState>, window| {
+            let mut state = state.unwrap();
+            let paint_start = window.paint_index();
+            if let Some(element) = element {
+                let refreshing = mem::replace(&mut window.refreshing, true);
+                element.paint(window, cx);
+                window.refreshing = refreshing;
+            } else {
+                window.reuse_paint(state.paint_range.clone());
+            }
+            let paint_end = window.paint_index();
+            state.paint_range = paint_start..paint_end;
+            ((), state)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+    use crate::{Context, ParentElement as _, Render, TestAppContext, canvas, div, px};
+
+    struct Panel {
+        key: usize,
+        paints: Rc<Cell<usize>>,
+    }
+
+    impl Render for Panel {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let paints = self.paints.clone();
+            div().size(px(200.)).child(
+                canvas(|_, _, _| {}, move |_, _, _, _| paints.set(paints.get() + 1))
+                    .size(px(100.))
+                    .freeze("panel", self.key),
+            )
+        }
+    }
+
+    #[crate::test]
+    fn test_frozen_element_replays_until_invalidated(cx: &mut TestAppContext) {
+        let paints = Rc::new(Cell::new(0));
+        let (view, cx) = cx.add_window_view(|_, _| Panel {
+            key: 0,
+            paints: paints.clone(),
+        });
+        cx.run_until_parked();
+        assert_eq!(paints.get(), 1);
+
+        view.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(paints.get(), 1);
+
+        view.update(cx, |panel, cx| {
+            panel.key += 1;
+            cx.notify();
+        });
+        cx.run_until_parked();
+        assert_eq!(paints.get(), 2);
+
+        let display = cx.update(|window, cx| window.display(cx)).unwrap().id();
+        cx.set_display_scale_factor(display, 3.);
+        cx.run_until_parked();
+        assert_eq!(paints.get(), 3);
+    }
+}
@@ -1,14 +1,15 @@
 use crate::{
-    AnyElement, AnyImageCache, App, Asset, AssetLogger, Bounds, DefiniteLength, Element, ElementId,
-    Entity, GlobalElementId, Hitbox, Image, ImageCache, InspectorElementId, InteractiveElement,
-    Interactivity, IntoElement, LayoutId, Length, ObjectFit, Pixels, RenderImage, Resource,
-    SharedString, SharedUri, StyleRefinement, Styled, Task, Window, px,
+    AnyElement, AnyImageCache, App, Asset, AssetBytes, AssetLogger, Bounds, DefiniteLength,
+    Element, ElementId, Entity, GlobalElementId, Hitbox, Image, ImageCache, InspectorElementId,
+    InteractiveElement, Interactivity, IntoElement, LayoutId, Length, ObjectFit, Pixels,
+    RenderImage, Resource, SharedString, SharedUri, StyleRefinement, Styled, Task, Window,
+    decode_rgba8, decode_rgba8_frames, px,
 };
 use anyhow::{Context as _, Result};
 
 use futures::{AsyncReadExt, Future};
 use image::{
-    AnimationDecoder, DynamicImage, Frame, ImageError, ImageFormat, Rgba,
+    Frame, ImageError, ImageFormat, ImageReader, Rgba,
     codecs::{gif::GifDecoder, webp::WebPDecoder},
 };
 use smallvec::SmallVec;
@@ -577,7 +578,12 @@ impl Asset for ImageDecoder {
         cx: &mut App,
     ) -> impl Future<Output = Self::Output> + Send + 'static {
         let renderer = cx.svg_renderer();
-        async move { source.to_image_data(renderer).map_err(Into::into) }
+        let color_management = cx.image_color_management();
+        async move {
+            source
+                .decode(renderer, color_management)
+                .map_err(Into::into)
+        }
     }
 }
 
@@ -598,9 +604,10 @@ impl Asset for ImageAssetLoader {
         // let scale_factor = cx.scale_factor();
         let svg_renderer = cx.svg_renderer();
         let asset_source = cx.asset_source().clone();
+        let color_management = cx.image_color_management();
         async move {
-            let bytes = match source.clone() {
-                Resource::Path(uri) => fs::read(uri.as_ref())?,
+            let bytes: AssetBytes = match source.clone() {
+                Resource::Path(uri) => fs::read(uri.as_ref())?.into(),
                 Resource::Uri(uri) => {
                     let mut response = client
                         .get(uri.as_ref(), ().into(), true)
@@ -618,12 +625,12 @@ impl Asset for ImageAssetLoader {
                             body,
                         });
                     }
-                    body
+                    body.into()
                 }
                 Resource::Embedded(path) => {
-                    let data = asset_source.load(&path).ok().flatten();
+                    let data = asset_source.load_bytes(&path).ok().flatten();
                     if let Some(data) = data {
-                        data.to_vec()
+                        data
                     } else {
                         return Err(ImageCacheError::Asset(
                             format!("Embedded resource not found: {}", path).into(),
@@ -636,38 +643,32 @@ impl Asset for ImageAssetLoader {
                 let data = match format {
                     ImageFormat::Gif => {
                         let decoder = GifDecoder::new(Cursor::new(&bytes))?;
-                        let mut frames = SmallVec::new();
-
-                        for frame in decoder.into_frames() {
-                            let mut frame = frame?;
+                        let mut frames = decode_rgba8_frames(decoder, color_management)?;
+                        for frame in &mut frames {
                             // Convert from RGBA to BGRA.
                             for pixel in frame.buffer_mut().chunks_exact_mut(4) {
                                 pixel.swap(0, 2);
                             }
-                            frames.push(frame);
                         }
 
-                        frames
+                        SmallVec::from_vec(frames)
                     }
                     ImageFormat::WebP => {
                         let mut decoder = WebPDecoder::new(Cursor::new(&bytes))?;
 
                         if decoder.has_animation() {
                             let _ = decoder.set_background_color(Rgba([0, 0, 0, 0]));
-                            let mut frames = SmallVec::new();
-
-                            for frame in decoder.into_frames() {
-                                let mut frame = frame?;
+                            let mut frames = decode_rgba8_frames(decoder, color_management)?;
+                            for frame in &mut frames {
                                 // Convert from RGBA to BGRA.
                                 for pixel in frame.buffer_mut().chunks_exact_mut(4) {
                                     pixel.swap(0, 2);
                                 }
-                                frames.push(frame);
                             }
 
-                            frames
+                            SmallVec::from_vec(frames)
                         } else {
-                            let mut data = DynamicImage::from_decoder(decoder)?.into_rgba8();
+                            let mut data = decode_rgba8(decoder, color_management)?;
 
                             // Convert from RGBA to BGRA.
                             for pixel in data.chunks_exact_mut(4) {
@@ -678,8 +679,9 @@ impl Asset for ImageAssetLoader {
                         }
                     }
                     _ => {
-                        let mut data =
-                            image::load_from_memory_with_format(&bytes, format)?.into_rgba8();
+                        let decoder =
+                            ImageReader::with_format(Cursor::new(&bytes), format).into_decoder()?;
+                        let mut data = decode_rgba8(decoder, color_management)?;
 
                         // Convert from RGBA to BGRA.
                         for pixel in data.chunks_exact_mut(4) {
@@ -0,0 +1,368 @@
+//! A scrollable masonry layout, which packs items of varying heights into columns of equal width,
+//! placing each item at the bottom of the shortest column. It suits gallery-style browsers such
+//! as sample packs or image boards, whose items have their own aspect ratios.
+//!
+//! Like [`uniform_list`](crate::uniform_list), only the items in view are rendered. Their heights
+//! are computed up front from the column width, such as from the aspect ratios of images, rather
+//! than measured, so every item can be placed without rendering it.
+
+use crate::{
+    AnyElement, App, AvailableSpace, Bounds, ContentMask, Element, ElementId, GlobalElementId,
+    Hitbox, InspectorElementId, InteractiveElement, Interactivity, IntoElement, LayoutId, Overflow,
+    Pixels, Size, StatefulInteractiveElement, StyleRefinement, Styled, Window, point, px, size,
+};
+use smallvec::SmallVec;
+
+/// Create a masonry layout of `item_count` items, which only renders the items in view when
+/// it's given a fixed (or max) height. `item_height` returns the height of an item laid out at
+/// the given column width, and `render_item` renders an item. Set the spacing between items
+/// with the `gap` style, and the number of columns with [`Masonry::columns`] or
+/// [`Masonry::min_column_width`].
+#[track_caller]
+pub fn masonry<E>(
+    id: impl Into<ElementId>,
+    item_count: usize,
+    item_height: impl Fn(usize, Pixels, &mut Window, &mut App) -> Pixels + 'static,
+    render_item: impl Fn(usize, &mut Window, &mut App) -> E + 'static,
+) -> Masonry
+where
+    E: IntoElement,
+{
+    let mut base_style = StyleRefinement::default();
+    base_style.overflow.y = Some(Overflow::Scroll);
+
+    Masonry {
+        item_count,
+        columns: MasonryColumns::Count(2),
+        item_height: Box::new(item_height),
+        render_item: Box::new(move |ix, window, cx| render_item(ix, window, cx).into_any_element()),
+        interactivity: Interactivity {
+            element_id: Some(id.into()),
+            base_style: Box::new(base_style),
+            ..Interactivity::new()
+        },
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MasonryColumns {
+    Count(usize),
+    MinWidth(Pixels),
+}
+
+/// A masonry layout element, created with [`masonry`].
+pub struct Masonry {
+    item_count: usize,
+    columns: MasonryColumns,
+    item_height: Box<dyn Fn(usize, Pixels, &mut Window, &mut App) -> Pixels>,
+    render_item: Box<dyn Fn(usize, &mut Window, &mut App) -> AnyElement>,
+    interactivity: Interactivity,
+}
+
+/// Frame state used by the [`Masonry`].
+pub struct MasonryFrameState {
+    items: SmallVec<[AnyElement; 32]>,
+}
+
+impl Masonry {
+    /// Lay the items out in the given number of columns. Defaults to 2.
+    pub fn columns(mut self, count: usize) -> Self {
+        self.columns = MasonryColumns::Count(count.max(1));
+        self
+    }
+
+    /// Lay the items out in as many columns as fit in the element's width, each at least the
+    /// given width, such as for thumbnails that should keep a minimum size as a browser is
+    /// resized.
+    pub fn min_column_width(mut self, width: Pixels) -> Self {
+        self.columns = MasonryColumns::MinWidth(width);
+        self
+    }
+
+    fn column_count(&self, width: Pixels, column_gap: Pixels) -> usize {
+        match self.columns {
+            MasonryColumns::Count(count) => count,
+            MasonryColumns::MinWidth(min_width) => {
+                let count = (width + column_gap) / (min_width + column_gap).max(px(1.));
+                (count.floor() as usize).max(1)
+            }
+        }
+    }
+}
+
+/// Place items of the given heights in the shortest of `column_count` columns, returning each
+/// item's bounds relative to the top left corner of the first column, and the height of the
+/// tallest column.
+fn pack_columns(
+    heights: impl IntoIterator<Item = Pixels>,
+    column_count: usize,
+    column_width: Pixels,
+    gap: Size<Pixels>,
+) -> (Vec<Bounds<Pixels>>, Pixels) {
+    let mut column_heights = vec![Pixels::ZERO; column_count.max(1)];
+    let item_bounds = heights
+        .into_iter()
+        .map(|height| {
+            let (column, top) = column_heights
+                .iter()
+                .copied()
+                .enumerate()
+                .min_by_key(|(_, column_height)| *column_height)
+                .unwrap_or_default();
+            column_heights[column] = top + height + gap.height;
+            Bounds::new(
+                point((column_width + gap.width) * column as f32, top),
+                size(column_width, height),
+            )
+        })
+        .collect();
+    let content_height = column_heights
+        .into_iter()
+        .map(|height| (height - gap.height).max(Pixels::ZERO))
+        .max()
+        .unwrap_or_default();
+    (item_bounds, content_height)
+}
+
+impl Styled for Masonry {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.interactivity.base_style
+    }
+}
+
+impl Element for Masonry {
+    type RequestLayoutState = MasonryFrameState;
+    type PrepaintState = Option<Hitbox>;
+
+    fn id(&self) -> Option<ElementId> {
+        self.interactivity.element_id.clone()
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let layout_id = self.interactivity.request_layout(
+            global_id,
+            inspector_id,
+            window,
+            cx,
+            |style, window, cx| {
+                window.with_text_style(style.text_style().cloned(), |window| {
+                    window.request_layout(style, None, cx)
+                })
+            },
+        );
+        (
+            layout_id,
+            MasonryFrameState {
+                items: SmallVec::new(),
+            },
+        )
+    }
+
+    fn prepaint(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        frame_state: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Hitbox> {
+        let style = self
+            .interactivity
+            .compute_style(global_id, None, window, cx);
+        let rem_size = window.rem_size();
+        let border = style.border_widths.to_pixels(rem_size);
+        let padding = style.padding.to_pixels(bounds.size.into(), rem_size);
+        let padded_bounds = Bounds::from_corners(
+            bounds.origin + point(border.left + padding.left, border.top + padding.top),
+            bounds.bottom_right()
+                - point(border.right + padding.right, border.bottom + padding.bottom),
+        );
+        let gap = size(
+            style
+                .gap
+                .width
+                .to_pixels(padded_bounds.size.width.into(), rem_size),
+            style
+                .gap
+                .height
+                .to_pixels(padded_bounds.size.height.into(), rem_size),
+        );
+
+        let column_count = self.column_count(padded_bounds.size.width, gap.width);
+        let column_width = ((padded_bounds.size.width - gap.width * (column_count - 1) as f32)
+            / column_count as f32)
+            .max(Pixels::ZERO);
+        let heights = (0..self.item_count)
+            .map(|ix| (self.item_height)(ix, column_width, window, cx).max(Pixels::ZERO))
+            .collect::<Vec<_>>();
+        let (item_bounds, content_height) = pack_columns(heights, column_count, column_width, gap);
+        let content_size = size(padded_bounds.size.width, content_height);
+
+        self.interactivity.prepaint(
+            global_id,
+            inspector_id,
+            bounds,
+            content_size,
+            window,
+            cx,
+            |_style, scroll_offset, hitbox, window, cx| {
+                let visible_top = -scroll_offset.y - padding.top;
+                let visible_bottom = visible_top + bounds.size.height;
+                let content_mask = ContentMask { bounds };
+                window.with_content_mask(Some(content_mask), |window| {
+                    for (ix, item_bounds) in item_bounds.iter().enumerate() {
+                        if item_bounds.bottom() < visible_top || item_bounds.top() > visible_bottom
+                        {
+                            continue;
+                        }
+                        let mut item = (self.render_item)(ix, window, cx);
+                        let available_space = size(
+                            AvailableSpace::Definite(item_bounds.size.width),
+                            AvailableSpace::Definite(item_bounds.size.height),
+                        );
+                        item.layout_as_root(available_space, window, cx);
+                        item.prepaint_at(
+                            padded_bounds.origin + scroll_offset + item_bounds.origin,
+                            window,
+                            cx,
+                        );
+                        frame_state.items.push(item);
+                    }
+                });
+                hitbox
+            },
+        )
+    }
+
+    fn paint(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        frame_state: &mut Self::RequestLayoutState,
+        hitbox: &mut Option<Hitbox>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        self.interactivity.paint(
+            global_id,
+            inspector_id,
+            bounds,
+            hitbox.as_ref(),
+            window,
+            cx,
+            |_, window, cx| {
+                for item in &mut frame_state.items {
+                    item.paint(window, cx);
+                }
+            },
+        )
+    }
+}
+
+impl IntoElement for Masonry {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl InteractiveElement for Masonry {
+    fn interactivity(&mut self) -> &mut Interactivity {
+        &mut self.interactivity
+    }
+}
+
+impl StatefulInteractiveElement for Masonry {}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::{Context, Render, ScrollHandle, TestAppContext, div};
+
+    #[test]
+    fn test_pack_columns() {
+        let heights = [100., 50., 80., 20., 60.].map(px);
+        let (bounds, content_height) = pack_columns(heights, 2, px(90.), size(px(10.), px(5.)));
+        let origins = bounds
+            .iter()
+            .map(|bounds| bounds.origin)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            origins,
+            [
+                point(px(0.), px(0.)),
+                point(px(100.), px(0.)),
+                point(px(100.), px(55.)),
+                point(px(0.), px(105.)),
+                point(px(0.), px(130.)),
+            ]
+        );
+        assert_eq!(content_height, px(190.));
+    }
+
+    #[crate::test]
+    fn test_only_visible_items_render(cx: &mut TestAppContext) {
+        struct Gallery {
+            scroll_handle: ScrollHandle,
+            rendered: Rc<RefCell<Vec<usize>>>,
+        }
+
+        impl Render for Gallery {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                let rendered = self.rendered.clone();
+                rendered.borrow_mut().clear();
+                div().size(px(300.)).child(
+                    masonry(
+                        "gallery",
+                        100,
+                        |ix, column_width, _, _| column_width * if ix % 2 == 0 { 1. } else { 0.5 },
+                        move |ix, _, _| {
+                            rendered.borrow_mut().push(ix);
+                            div().size_full()
+                        },
+                    )
+                    .columns(3)
+                    .track_scroll(&self.scroll_handle)
+                    .size_full(),
+                )
+            }
+        }
+
+        let rendered = Rc::new(RefCell::new(Vec::new()));
+        let (view, cx) = cx.add_window_view(|_, _| Gallery {
+            scroll_handle: ScrollHandle::new(),
+            rendered: rendered.clone(),
+        });
+        cx.run_until_parked();
+
+        // Columns are 100px wide, so items alternate between 100px and 50px tall.
+        let first_frame = rendered.borrow().clone();
+        assert!(first_frame.contains(&0));
+        assert!(!first_frame.contains(&99));
+        assert!(first_frame.len() < 30);
+
+        view.update(cx, |view, cx| {
+            view.scroll_handle.set_offset(point(px(0.), px(-1500.)));
+            cx.notify();
+        });
+        cx.run_until_parked();
+        let scrolled_frame = rendered.borrow().clone();
+        assert!(!scrolled_frame.contains(&0));
+        assert!(!scrolled_frame.is_empty());
+    }
+}
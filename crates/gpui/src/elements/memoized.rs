@@ -0,0 +1,269 @@
+use std::{mem, ops::Range};
+
+use collections::FxHashSet;
+use refineable::Refineable;
+
+use crate::{
+    AnyElement, App, Bounds, ContentMask, Element, ElementId, EntityId, GlobalElementId,
+    InspectorElementId, IntoElement, LayoutId, PaintIndex, Pixels, PrepaintStateIndex, RenderOnce,
+    Style, StyleRefinement, TextStyle, Window,
+};
+
+/// An extension trait for rendering components only when their props change.
+///
+/// A component's fields are its props: when a memoized component is rendered with props equal to
+/// the ones it was rendered with in the previous frame, its previous layout and paint are recycled
+/// instead of calling [`RenderOnce::render`]. State used through [`Window::use_state`] and other
+/// entities read while rendering are tracked, so the component still renders again when they're
+/// notified, as it does when [`Window::refresh`] is called.
+/// Values provided with [`InteractiveElement::provide_context`](crate::InteractiveElement::provide_context)
+/// aren't tracked, so components that depend on them should take them as props.
+pub trait MemoizeExt: RenderOnce + Clone + PartialEq + Sized {
+    /// Render this component only when its props differ from the previous frame's. As the
+    /// component's elements aren't built when its render is skipped, the component is laid out
+    /// with the given style, like [`AnyView::cached`](crate::AnyView::cached) views.
+    fn memoized(self, id: impl Into<ElementId>, style: StyleRefinement) -> Memoized<Self> {
+        Memoized {
+            id: id.into(),
+            component: Some(self),
+            style,
+        }
+    }
+}
+
+impl<C: RenderOnce + Clone + PartialEq> MemoizeExt for C {}
+
+/// A GPUI element that renders a component only when its props change, see [`MemoizeExt`].
+pub struct Memoized<C> {
+    id: ElementId,
+    component: Option<C>,
+    style: StyleRefinement,
+}
+
+impl<C: RenderOnce + Clone + PartialEq> IntoElement for Memoized<C> {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+struct MemoizedState<C> {
+    props: C,
+    prepaint_range: Range<PrepaintStateIndex>,
+    paint_range: Range<PaintIndex>,
+    bounds: Bounds<Pixels>,
+    content_mask: ContentMask<Pixels>,
+    text_style: TextStyle,
+    accessed_entities: FxHashSet<EntityId>,
+}
+
+impl<C: RenderOnce + Clone + PartialEq> Element for Memoized<C> {
+    type RequestLayoutState = ();
+    type PrepaintState = Option<AnyElement>;
+
+    fn id(&self) -> Option<ElementId> {
+        Some(self.id.clone())
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        (window.request_layout(style, None, cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<AnyElement> {
+        let component = self.component.take().unwrap();
+        // Disable memoization when inspecting so that mouse_hit_test has all hitboxes.
+        let memoization_disabled = window.is_inspector_picking(cx);
+
+        window.with_element_state::<MemoizedState<C>, _>(
+            global_id.unwrap(),
+            |element_state, window| {
+                let content_mask = window.content_mask();
+                let text_style = window.text_style();
+
+                if let Some(mut element_state) = element_state
+                    && element_state.props == component
+                    && element_state.bounds == bounds
+                    && element_state.content_mask == content_mask
+                    && element_state.text_style == text_style
+                    && !element_state
+                        .accessed_entities
+                        .iter()
+                        .any(|entity_id| window.notified_entities.contains(entity_id))
+                    && !window.refreshing
+                    && !memoization_disabled
+                {
+                    let prepaint_start = window.prepaint_index();
+                    window.reuse_prepaint(element_state.prepaint_range.clone());
+                    cx.entities
+                        .extend_accessed(&element_state.accessed_entities);
+                    let prepaint_end = window.prepaint_index();
+                    element_state.prepaint_range = prepaint_start..prepaint_end;
+
+                    return (None, element_state);
+                }
+
+                let props = component.clone();
+                let refreshing = mem::replace(&mut window.refreshing, true);
+                let prepaint_start = window.prepaint_index();
+                let (element, accessed_entities) = cx.detect_accessed_entities(|cx| {
+                    let mut element = component.render(window, cx).into_any_element();
+                    element.layout_as_root(bounds.size.into(), window, cx);
+                    element.prepaint_at(bounds.origin, window, cx);
+                    element
+                });
+                let prepaint_end = window.prepaint_index();
+                window.refreshing = refreshing;
+
+                (
+                    Some(element),
+                    MemoizedState {
+                        props,
+                        prepaint_range: prepaint_start..prepaint_end,
+                        paint_range: PaintIndex::default()..PaintIndex::default(),
+                        bounds,
+                        content_mask,
+                        text_style,
+                        accessed_entities,
+                    },
+                )
+            },
+        )
+    }
+
+    fn paint(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        element: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        window.with_element_state::<MemoizedState<C>, _>(
+            global_id.unwrap(),
+            |element_state, window| {
+                let mut element_state = element_state.unwrap();
+
+                let paint_start = window.paint_index();
+                if let Some(element) = element {
+                    let refreshing = mem::replace(&mut window.refreshing, true);
+                    element.paint(window, cx);
+                    window.refreshing = refreshing;
+                } else {
+                    window.reuse_paint(element_state.paint_range.clone());
+                }
+                let paint_end = window.paint_index();
+                element_state.paint_range = paint_start..paint_end;
+
+                ((), element_state)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
+
+    use crate::{
+        App, AppContext as _, Context, Entity, IntoElement, MemoizeExt as _, ParentElement as _,
+        Render, RenderOnce, SharedString, StyleRefinement, Styled as _, TestAppContext, Window,
+        div,
+    };
+
+    #[derive(Clone, IntoElement)]
+    struct Badge {
+        label: SharedString,
+        renders: Rc<Cell<usize>>,
+        clicks: Rc<RefCell<Option<Entity<usize>>>>,
+    }
+
+    impl PartialEq for Badge {
+        fn eq(&self, other: &Self) -> bool {
+            self.label == other.label
+        }
+    }
+
+    impl RenderOnce for Badge {
+        fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+            self.renders.set(self.renders.get() + 1);
+            let clicks = window.use_state(cx, |_, _| 0usize);
+            self.clicks.replace(Some(clicks.clone()));
+            div().child(format!("{} ({})", self.label, clicks.read(cx)))
+        }
+    }
+
+    struct Toolbar {
+        badge: Badge,
+    }
+
+    impl Render for Toolbar {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().child(
+                self.badge
+                    .clone()
+                    .memoized("badge", StyleRefinement::default().size_full()),
+            )
+        }
+    }
+
+    #[crate::test]
+    fn test_memoized_component(cx: &mut TestAppContext) {
+        let renders = Rc::new(Cell::new(0));
+        let clicks = Rc::new(RefCell::new(None));
+        let (toolbar, cx) = cx.add_window_view(|_, _| Toolbar {
+            badge: Badge {
+                label: "Draft".into(),
+                renders: renders.clone(),
+                clicks: clicks.clone(),
+            },
+        });
+        assert_eq!(renders.get(), 1);
+
+        // Rendering the parent again with the same props reuses the component's last frame.
+        toolbar.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(renders.get(), 1);
+
+        toolbar.update(cx, |toolbar, cx| {
+            toolbar.badge.label = "Saved".into();
+            cx.notify();
+        });
+        cx.run_until_parked();
+        assert_eq!(renders.get(), 2);
+
+        // Changes to the component's own state render it again.
+        let clicks = clicks.borrow().clone().unwrap();
+        clicks.update(cx, |clicks, cx| {
+            *clicks += 1;
+            cx.notify();
+        });
+        cx.run_until_parked();
+        assert_eq!(renders.get(), 3);
+    }
+}
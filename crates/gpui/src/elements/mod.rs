@@ -1,25 +1,57 @@
 mod anchored;
 mod animation;
+mod async_content;
 mod canvas;
+mod chart;
 mod deferred;
 mod div;
+mod external_image;
+mod frozen;
 mod image_cache;
 mod img;
 mod list;
+mod masonry;
+mod memoized;
+mod node_graph;
+mod paged_list;
+mod portal;
+mod reorder;
+mod skeleton;
+mod spectrogram;
+mod split;
 mod surface;
 mod svg;
 mod text;
+mod thumbnail;
+mod toolbar;
 mod uniform_list;
+mod waveform;
 
 pub use anchored::*;
 pub use animation::*;
+pub use async_content::*;
 pub use canvas::*;
+pub use chart::*;
 pub use deferred::*;
 pub use div::*;
+pub use external_image::*;
+pub use frozen::*;
 pub use image_cache::*;
 pub use img::*;
 pub use list::*;
+pub use masonry::*;
+pub use memoized::*;
+pub use node_graph::*;
+pub use paged_list::*;
+pub use portal::*;
+pub use reorder::*;
+pub use skeleton::*;
+pub use spectrogram::*;
+pub use split::*;
 pub use surface::*;
 pub use svg::*;
 pub use text::*;
+pub use thumbnail::*;
+pub use toolbar::*;
 pub use uniform_list::*;
+pub use waveform::*;
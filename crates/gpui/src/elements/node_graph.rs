@@ -0,0 +1,1328 @@
+//! A canvas for editing node graphs, as used by modular synthesizers and visual scripting
+//! tools.
+//!
+//! The graph itself is plain data in a [`NodeGraph`], usually held in an entity so that it can
+//! be observed and edited from anywhere in the application. The [`NodeGraphElement`] renders
+//! that data and implements the interactions: dragging nodes, wiring outputs to inputs,
+//! rubber-band selection, and panning and zooming the viewport. Interaction state such as the
+//! viewport and selection lives in the element's state, not in the model.
+
+use std::{cell::RefCell, rc::Rc};
+
+use refineable::Refineable as _;
+use util::ResultExt as _;
+
+use crate::{
+    App, BorderStyle, Bounds, DispatchPhase, Element, ElementId, Entity, GlobalElementId, Hitbox,
+    HitboxBehavior, Hsla, InspectorElementId, IntoElement, LayoutId, MouseButton, MouseDownEvent,
+    MouseMoveEvent, MouseUpEvent, PathBuilder, Pixels, Point, ScrollWheelEvent, ShapedLine,
+    SharedString, Style, StyleRefinement, Styled, Window, fill, hsla, outline, point, px, quad,
+    size,
+};
+
+const HEADER_HEIGHT: Pixels = px(24.);
+const PORT_ROW_HEIGHT: Pixels = px(20.);
+const PORT_RADIUS: Pixels = px(5.);
+const NODE_PADDING: Pixels = px(6.);
+const CORNER_RADIUS: Pixels = px(4.);
+const GRID_SPACING: Pixels = px(24.);
+const CONNECTION_HIT_DISTANCE: Pixels = px(6.);
+
+/// Below this zoom level, titles and port labels are not drawn.
+const LOD_TEXT_ZOOM: f32 = 0.5;
+/// Below this zoom level, connections are drawn as straight lines.
+const LOD_CURVE_ZOOM: f32 = 0.3;
+/// The smallest zoom level [`NodeGraphElement::zoom_range`] allows.
+const MIN_ZOOM: f32 = 0.01;
+
+/// Identifies a node within a [`NodeGraph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub usize);
+
+/// Identifies an input or output port of a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PortRef {
+    /// The node the port belongs to.
+    pub node: NodeId,
+    /// The index of the port among the node's inputs or outputs.
+    pub port: usize,
+}
+
+/// A connection from an output port to an input port.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GraphConnection {
+    /// The output port the connection starts from.
+    pub from: PortRef,
+    /// The input port the connection ends at.
+    pub to: PortRef,
+}
+
+/// A node in a [`NodeGraph`].
+#[derive(Clone, Debug)]
+pub struct GraphNode {
+    /// The id of this node, assigned by [`NodeGraph::add_node`].
+    pub id: NodeId,
+    /// The title shown in the node's header.
+    pub title: SharedString,
+    /// The position of the node's top-left corner, in graph coordinates.
+    pub position: Point<Pixels>,
+    /// The width of the node, in graph coordinates. The height follows from its ports.
+    pub width: Pixels,
+    /// The labels of the node's input ports, drawn on its left edge.
+    pub inputs: Vec<SharedString>,
+    /// The labels of the node's output ports, drawn on its right edge.
+    pub outputs: Vec<SharedString>,
+    /// The color of the node's header, or `None` to use the element's default.
+    pub color: Option<Hsla>,
+}
+
+impl GraphNode {
+    /// The bounds of this node, in graph coordinates.
+    pub fn bounds(&self) -> Bounds<Pixels> {
+        let rows = self.inputs.len().max(self.outputs.len()) as f32;
+        Bounds::new(
+            self.position,
+            size(
+                self.width,
+                HEADER_HEIGHT + PORT_ROW_HEIGHT * rows + NODE_PADDING,
+            ),
+        )
+    }
+
+    /// The position of the given input port, in graph coordinates.
+    pub fn input_position(&self, port: usize) -> Point<Pixels> {
+        point(self.position.x, self.port_y(port))
+    }
+
+    /// The position of the given output port, in graph coordinates.
+    pub fn output_position(&self, port: usize) -> Point<Pixels> {
+        point(self.position.x + self.width, self.port_y(port))
+    }
+
+    fn port_y(&self, port: usize) -> Pixels {
+        self.position.y + HEADER_HEIGHT + PORT_ROW_HEIGHT * (port as f32 + 0.5)
+    }
+}
+
+/// The data model of a node graph: a set of nodes and the connections between their ports.
+///
+/// The model knows nothing about rendering, so it can be edited, serialized or evaluated
+/// independently of any [`NodeGraphElement`] displaying it.
+#[derive(Clone, Debug, Default)]
+pub struct NodeGraph {
+    nodes: Vec<GraphNode>,
+    connections: Vec<GraphConnection>,
+    next_node_id: usize,
+}
+
+impl NodeGraph {
+    /// Create an empty graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a node at the given position in graph coordinates and return its id.
+    pub fn add_node(
+        &mut self,
+        title: impl Into<SharedString>,
+        position: Point<Pixels>,
+        inputs: impl IntoIterator<Item = impl Into<SharedString>>,
+        outputs: impl IntoIterator<Item = impl Into<SharedString>>,
+    ) -> NodeId {
+        let id = NodeId(self.next_node_id);
+        self.next_node_id += 1;
+        self.nodes.push(GraphNode {
+            id,
+            title: title.into(),
+            position,
+            width: px(160.),
+            inputs: inputs.into_iter().map(Into::into).collect(),
+            outputs: outputs.into_iter().map(Into::into).collect(),
+            color: None,
+        });
+        id
+    }
+
+    /// Remove a node along with all of its connections.
+    pub fn remove_node(&mut self, id: NodeId) -> Option<GraphNode> {
+        let ix = self.nodes.iter().position(|node| node.id == id)?;
+        self.connections
+            .retain(|connection| connection.from.node != id && connection.to.node != id);
+        Some(self.nodes.remove(ix))
+    }
+
+    /// The nodes in the graph, in painting order (the last node is drawn on top).
+    pub fn nodes(&self) -> &[GraphNode] {
+        &self.nodes
+    }
+
+    /// Look up a node by id.
+    pub fn node(&self, id: NodeId) -> Option<&GraphNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Look up a node by id for editing.
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut GraphNode> {
+        self.nodes.iter_mut().find(|node| node.id == id)
+    }
+
+    /// Move a node to the top of the painting order.
+    pub fn raise_node(&mut self, id: NodeId) {
+        if let Some(ix) = self.nodes.iter().position(|node| node.id == id) {
+            let node = self.nodes.remove(ix);
+            self.nodes.push(node);
+        }
+    }
+
+    /// The connections in the graph.
+    pub fn connections(&self) -> &[GraphConnection] {
+        &self.connections
+    }
+
+    /// Whether `from` (an output) could be connected to `to` (an input): both ports must exist,
+    /// belong to different nodes, and not already be connected to each other.
+    pub fn can_connect(&self, from: PortRef, to: PortRef) -> bool {
+        if from.node == to.node {
+            return false;
+        }
+        let (Some(source), Some(target)) = (self.node(from.node), self.node(to.node)) else {
+            return false;
+        };
+        from.port < source.outputs.len()
+            && to.port < target.inputs.len()
+            && !self.connections.contains(&GraphConnection { from, to })
+    }
+
+    /// Connect an output port to an input port. Returns whether the connection was added.
+    pub fn connect(&mut self, from: PortRef, to: PortRef) -> bool {
+        if !self.can_connect(from, to) {
+            return false;
+        }
+        self.connections.push(GraphConnection { from, to });
+        true
+    }
+
+    /// Remove a connection. Returns whether it existed.
+    pub fn disconnect(&mut self, connection: GraphConnection) -> bool {
+        let len = self.connections.len();
+        self.connections.retain(|existing| *existing != connection);
+        self.connections.len() != len
+    }
+
+    /// The connections ending at the given input port.
+    pub fn connections_to(&self, input: PortRef) -> impl Iterator<Item = &GraphConnection> {
+        self.connections
+            .iter()
+            .filter(move |connection| connection.to == input)
+    }
+}
+
+/// The part of a node graph visible in a [`NodeGraphElement`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GraphViewport {
+    /// The screen offset of the graph origin, relative to the element's top-left corner.
+    pub offset: Point<Pixels>,
+    /// The scale applied to graph coordinates.
+    pub zoom: f32,
+}
+
+impl Default for GraphViewport {
+    fn default() -> Self {
+        Self {
+            offset: Point::default(),
+            zoom: 1.,
+        }
+    }
+}
+
+/// The nodes and connections selected in a [`NodeGraphElement`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeGraphSelection {
+    /// The selected nodes.
+    pub nodes: Vec<NodeId>,
+    /// The selected connections.
+    pub connections: Vec<GraphConnection>,
+}
+
+impl NodeGraphSelection {
+    /// Whether nothing is selected.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.connections.is_empty()
+    }
+}
+
+/// Create an element that displays and edits the given graph.
+pub fn node_graph(id: impl Into<ElementId>, graph: Entity<NodeGraph>) -> NodeGraphElement {
+    NodeGraphElement {
+        id: id.into(),
+        graph,
+        style: StyleRefinement::default(),
+        colors: NodeGraphColors::default(),
+        zoom_range: (0.1, 4.),
+        can_connect: None,
+        on_selection_change: None,
+    }
+}
+
+#[derive(Clone, Copy)]
+struct NodeGraphColors {
+    grid: Option<Hsla>,
+    node_background: Hsla,
+    node_header: Hsla,
+    text: Hsla,
+    port: Hsla,
+    connection: Hsla,
+    selection: Hsla,
+}
+
+impl Default for NodeGraphColors {
+    fn default() -> Self {
+        Self {
+            grid: Some(hsla(0., 0., 0.5, 0.1)),
+            node_background: hsla(0., 0., 0.18, 1.),
+            node_header: hsla(0.6, 0.3, 0.32, 1.),
+            text: hsla(0., 0., 0.9, 1.),
+            port: hsla(0.1, 0.7, 0.6, 1.),
+            connection: hsla(0., 0., 0.7, 1.),
+            selection: hsla(0.58, 0.9, 0.6, 1.),
+        }
+    }
+}
+
+type CanConnectFn = dyn Fn(&NodeGraph, PortRef, PortRef) -> bool;
+type SelectionChangeFn = dyn Fn(&NodeGraphSelection, &mut Window, &mut App);
+
+/// An element that renders a [`NodeGraph`] and lets the user edit it with the mouse.
+///
+/// - Dragging a node moves it, along with the rest of the selection.
+/// - Dragging from an output port to an input port connects them. Dragging from a connected
+///   input port picks up its connection so it can be moved elsewhere or dropped to remove it.
+/// - Dragging on the background selects the nodes within the dragged rectangle. Holding
+///   shift extends the selection instead of replacing it.
+/// - Scrolling pans the graph, scrolling with the platform's secondary modifier zooms it
+///   around the cursor, and dragging with the middle mouse button pans it.
+///
+/// At low zoom levels, text is skipped and connections are drawn as straight lines.
+pub struct NodeGraphElement {
+    id: ElementId,
+    graph: Entity<NodeGraph>,
+    style: StyleRefinement,
+    colors: NodeGraphColors,
+    zoom_range: (f32, f32),
+    can_connect: Option<Rc<CanConnectFn>>,
+    on_selection_change: Option<Rc<SelectionChangeFn>>,
+}
+
+impl NodeGraphElement {
+    /// Set the color of the background grid, or `None` to hide it.
+    pub fn grid_color(mut self, color: Option<Hsla>) -> Self {
+        self.colors.grid = color;
+        self
+    }
+
+    /// Set the background color of nodes.
+    pub fn node_background(mut self, color: impl Into<Hsla>) -> Self {
+        self.colors.node_background = color.into();
+        self
+    }
+
+    /// Set the header color of nodes that don't specify their own.
+    pub fn node_header_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.colors.node_header = color.into();
+        self
+    }
+
+    /// Set the color of node titles and port labels.
+    pub fn text_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.colors.text = color.into();
+        self
+    }
+
+    /// Set the color of ports.
+    pub fn port_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.colors.port = color.into();
+        self
+    }
+
+    /// Set the color of connections.
+    pub fn connection_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.colors.connection = color.into();
+        self
+    }
+
+    /// Set the color used to highlight selected nodes, connections and the selection rectangle.
+    pub fn selection_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.colors.selection = color.into();
+        self
+    }
+
+    /// Set the minimum and maximum zoom levels. Defaults to `0.1..=4.0`. Both are clamped to at
+    /// least 0.01, since the graph can't be drawn at a zoom level of zero or below.
+    pub fn zoom_range(mut self, min: f32, max: f32) -> Self {
+        self.zoom_range = (min.min(max).max(MIN_ZOOM), max.max(min).max(MIN_ZOOM));
+        self
+    }
+
+    /// Restrict which connections the user may create, in addition to the checks made by
+    /// [`NodeGraph::can_connect`]. Use this to enforce port types.
+    pub fn can_connect(
+        mut self,
+        can_connect: impl Fn(&NodeGraph, PortRef, PortRef) -> bool + 'static,
+    ) -> Self {
+        self.can_connect = Some(Rc::new(can_connect));
+        self
+    }
+
+    /// Register a callback to be invoked when the user changes the selection.
+    pub fn on_selection_change(
+        mut self,
+        listener: impl Fn(&NodeGraphSelection, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_selection_change = Some(Rc::new(listener));
+        self
+    }
+}
+
+impl IntoElement for NodeGraphElement {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Styled for NodeGraphElement {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+#[derive(Default)]
+struct NodeGraphInteraction {
+    viewport: GraphViewport,
+    selection: NodeGraphSelection,
+    drag: Option<GraphDrag>,
+    hovered_connection: Option<GraphConnection>,
+}
+
+#[derive(Clone)]
+enum GraphDrag {
+    Nodes {
+        last_position: Point<Pixels>,
+    },
+    Pan {
+        last_position: Point<Pixels>,
+    },
+    Connect {
+        from: PortRef,
+        position: Point<Pixels>,
+    },
+    Select {
+        start: Point<Pixels>,
+        position: Point<Pixels>,
+        /// The selection when the drag started, which the nodes within the rectangle are added
+        /// to. Empty unless shift was held.
+        base: NodeGraphSelection,
+    },
+}
+
+/// What to draw for a visible node, gathered while the graph is borrowed so that painting,
+/// which needs the app context for text, doesn't have to copy the graph.
+struct NodePaint {
+    bounds: Bounds<Pixels>,
+    header_color: Hsla,
+    selected: bool,
+    labels: Vec<(ShapedLine, Point<Pixels>)>,
+    ports: Vec<Point<Pixels>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum GraphHit {
+    Input(PortRef),
+    Output(PortRef),
+    Node(NodeId),
+    Connection(GraphConnection),
+}
+
+/// Maps between graph and screen coordinates for a given element origin and viewport.
+#[derive(Clone, Copy)]
+struct GraphLayout {
+    origin: Point<Pixels>,
+    viewport: GraphViewport,
+}
+
+impl GraphLayout {
+    fn to_screen(&self, position: Point<Pixels>) -> Point<Pixels> {
+        self.origin + self.viewport.offset + position * self.viewport.zoom
+    }
+
+    fn to_graph(&self, position: Point<Pixels>) -> Point<Pixels> {
+        (position - self.origin - self.viewport.offset) / self.viewport.zoom
+    }
+
+    fn node_bounds(&self, node: &GraphNode) -> Bounds<Pixels> {
+        let bounds = node.bounds();
+        Bounds::new(
+            self.to_screen(bounds.origin),
+            bounds.size * self.viewport.zoom,
+        )
+    }
+
+    fn connection_curve(
+        &self,
+        graph: &NodeGraph,
+        connection: &GraphConnection,
+    ) -> Option<[Point<Pixels>; 4]> {
+        let from = graph.node(connection.from.node)?;
+        let to = graph.node(connection.to.node)?;
+        Some(connection_curve(
+            self.to_screen(from.output_position(connection.from.port)),
+            self.to_screen(to.input_position(connection.to.port)),
+            self.viewport.zoom,
+        ))
+    }
+
+    fn hit_test(&self, graph: &NodeGraph, position: Point<Pixels>) -> Option<GraphHit> {
+        let port_radius = (PORT_RADIUS * 1.5) * self.viewport.zoom.max(1.);
+        for node in graph.nodes().iter().rev() {
+            let input = (0..node.inputs.len()).find(|port| {
+                distance(self.to_screen(node.input_position(*port)), position) <= port_radius
+            });
+            if let Some(port) = input {
+                return Some(GraphHit::Input(PortRef {
+                    node: node.id,
+                    port,
+                }));
+            }
+            let output = (0..node.outputs.len()).find(|port| {
+                distance(self.to_screen(node.output_position(*port)), position) <= port_radius
+            });
+            if let Some(port) = output {
+                return Some(GraphHit::Output(PortRef {
+                    node: node.id,
+                    port,
+                }));
+            }
+            if self.node_bounds(node).contains(&position) {
+                return Some(GraphHit::Node(node.id));
+            }
+        }
+
+        graph
+            .connections()
+            .iter()
+            .rev()
+            .find(|connection| {
+                self.connection_curve(graph, connection)
+                    .is_some_and(|curve| {
+                        distance_to_curve(&curve, position) <= CONNECTION_HIT_DISTANCE
+                    })
+            })
+            .map(|connection| GraphHit::Connection(*connection))
+    }
+
+    /// The nodes whose screen bounds intersect the given rectangle.
+    fn nodes_in_rect(&self, graph: &NodeGraph, rect: Bounds<Pixels>) -> Vec<NodeId> {
+        graph
+            .nodes()
+            .iter()
+            .filter(|node| self.node_bounds(node).intersects(&rect))
+            .map(|node| node.id)
+            .collect()
+    }
+}
+
+/// Add the nodes in a selection rectangle to the selection a rubber-band drag started from.
+fn extend_selection(base: &NodeGraphSelection, nodes_in_rect: Vec<NodeId>) -> NodeGraphSelection {
+    let mut selection = base.clone();
+    for node in nodes_in_rect {
+        if !selection.nodes.contains(&node) {
+            selection.nodes.push(node);
+        }
+    }
+    selection
+}
+
+/// Control points for a connection that leaves `from` to the right and enters `to` from the
+/// left, bending further the more the ports are horizontally apart.
+fn connection_curve(from: Point<Pixels>, to: Point<Pixels>, zoom: f32) -> [Point<Pixels>; 4] {
+    let bend = ((to.x - from.x).abs() / 2.).max(px(40.) * zoom);
+    [
+        from,
+        point(from.x + bend, from.y),
+        point(to.x - bend, to.y),
+        to,
+    ]
+}
+
+fn cubic_bezier_point(curve: &[Point<Pixels>; 4], t: f32) -> Point<Pixels> {
+    let u = 1. - t;
+    curve[0] * (u * u * u)
+        + curve[1] * (3. * u * u * t)
+        + curve[2] * (3. * u * t * t)
+        + curve[3] * (t * t * t)
+}
+
+fn distance(a: Point<Pixels>, b: Point<Pixels>) -> Pixels {
+    let delta = b - a;
+    px(f32::from(delta.x).hypot(f32::from(delta.y)))
+}
+
+fn distance_to_segment(position: Point<Pixels>, a: Point<Pixels>, b: Point<Pixels>) -> Pixels {
+    let segment = b - a;
+    let length_squared = f32::from(segment.x).powi(2) + f32::from(segment.y).powi(2);
+    if length_squared == 0. {
+        return distance(position, a);
+    }
+    let offset = position - a;
+    let t = ((f32::from(offset.x) * f32::from(segment.x)
+        + f32::from(offset.y) * f32::from(segment.y))
+        / length_squared)
+        .clamp(0., 1.);
+    distance(position, a + segment * t)
+}
+
+/// Approximate the distance from `position` to a cubic bezier curve by flattening it.
+fn distance_to_curve(curve: &[Point<Pixels>; 4], position: Point<Pixels>) -> Pixels {
+    const SEGMENTS: usize = 24;
+    let mut previous = curve[0];
+    let mut min_distance = distance(position, previous);
+    for step in 1..=SEGMENTS {
+        let next = cubic_bezier_point(curve, step as f32 / SEGMENTS as f32);
+        min_distance = min_distance.min(distance_to_segment(position, previous, next));
+        previous = next;
+    }
+    min_distance
+}
+
+/// The state carried from prepaint to paint for a [`NodeGraphElement`].
+pub struct NodeGraphPrepaintState {
+    hitbox: Hitbox,
+    interaction: Rc<RefCell<NodeGraphInteraction>>,
+}
+
+impl Element for NodeGraphElement {
+    type RequestLayoutState = Style;
+    type PrepaintState = NodeGraphPrepaintState;
+
+    fn id(&self) -> Option<ElementId> {
+        Some(self.id.clone())
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        let layout_id = window.request_layout(style.clone(), [], cx);
+        (layout_id, style)
+    }
+
+    fn prepaint(
+        &mut self,
+        id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _style: &mut Style,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> NodeGraphPrepaintState {
+        let interaction = window.with_element_state(id.unwrap(), |state, _| {
+            let state: Rc<RefCell<NodeGraphInteraction>> = state.unwrap_or_default();
+            (state.clone(), state)
+        });
+
+        // Drop selected items that no longer exist in the model.
+        let graph = self.graph.read(cx);
+        let mut interaction_ref = interaction.borrow_mut();
+        let selection = &mut interaction_ref.selection;
+        selection.nodes.retain(|id| graph.node(*id).is_some());
+        selection
+            .connections
+            .retain(|connection| graph.connections().contains(connection));
+        drop(interaction_ref);
+
+        let hitbox = window.insert_hitbox(bounds, HitboxBehavior::Normal);
+        NodeGraphPrepaintState {
+            hitbox,
+            interaction,
+        }
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        style: &mut Style,
+        prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let interaction = prepaint.interaction.borrow();
+        let layout = GraphLayout {
+            origin: bounds.origin,
+            viewport: interaction.viewport,
+        };
+
+        style.paint(bounds, window, cx, |window, cx| {
+            window.with_content_mask(Some(crate::ContentMask { bounds }), |window| {
+                self.paint_grid(bounds, &layout, window);
+                let graph = self.graph.read(cx);
+                self.paint_connections(graph, &interaction, &layout, window);
+                let nodes = self.layout_nodes(graph, &interaction, &layout, window);
+                self.paint_nodes(nodes, &layout, window, cx);
+                if let Some(GraphDrag::Select {
+                    start, position, ..
+                }) = interaction.drag
+                {
+                    let rect = Bounds::from_corners(start.min(&position), start.max(&position));
+                    window.paint_quad(fill(rect, self.colors.selection.opacity(0.1)));
+                    window.paint_quad(outline(rect, self.colors.selection, BorderStyle::Solid));
+                }
+            });
+        });
+        drop(interaction);
+
+        self.register_mouse_listeners(bounds, prepaint, window);
+    }
+}
+
+impl NodeGraphElement {
+    fn paint_grid(&self, bounds: Bounds<Pixels>, layout: &GraphLayout, window: &mut Window) {
+        let Some(color) = self.colors.grid else {
+            return;
+        };
+        let mut spacing = GRID_SPACING * layout.viewport.zoom;
+        // Coarsen the grid when zoomed out so it never turns into a solid fill.
+        while spacing < px(8.) {
+            spacing = spacing * 4.;
+        }
+        let origin = layout.to_screen(Point::default());
+        let first_x = origin.x - spacing * ((origin.x - bounds.left()) / spacing).floor();
+        let first_y = origin.y - spacing * ((origin.y - bounds.top()) / spacing).floor();
+
+        let mut x = first_x;
+        while x < bounds.right() {
+            window.paint_quad(fill(
+                Bounds::new(point(x, bounds.top()), size(px(1.), bounds.size.height)),
+                color,
+            ));
+            x += spacing;
+        }
+        let mut y = first_y;
+        while y < bounds.bottom() {
+            window.paint_quad(fill(
+                Bounds::new(point(bounds.left(), y), size(bounds.size.width, px(1.))),
+                color,
+            ));
+            y += spacing;
+        }
+    }
+
+    fn paint_connections(
+        &self,
+        graph: &NodeGraph,
+        interaction: &NodeGraphInteraction,
+        layout: &GraphLayout,
+        window: &mut Window,
+    ) {
+        let zoom = layout.viewport.zoom;
+        let width = px(2.) * zoom.max(0.5);
+        for connection in graph.connections() {
+            let Some(curve) = layout.connection_curve(graph, connection) else {
+                continue;
+            };
+            let highlighted = interaction.selection.connections.contains(connection)
+                || interaction.hovered_connection == Some(*connection);
+            let color = if highlighted {
+                self.colors.selection
+            } else {
+                self.colors.connection
+            };
+            paint_curve(&curve, width, zoom, color, window);
+        }
+
+        if let Some(GraphDrag::Connect { from, position }) = interaction.drag
+            && let Some(node) = graph.node(from.node)
+        {
+            let start = layout.to_screen(node.output_position(from.port));
+            let curve = connection_curve(start, position, zoom);
+            paint_curve(&curve, width, zoom, self.colors.selection, window);
+        }
+    }
+
+    fn layout_nodes(
+        &self,
+        graph: &NodeGraph,
+        interaction: &NodeGraphInteraction,
+        layout: &GraphLayout,
+        window: &mut Window,
+    ) -> Vec<NodePaint> {
+        let zoom = layout.viewport.zoom;
+        let show_text = zoom >= LOD_TEXT_ZOOM;
+        let text_style = window.text_style();
+        let font_size = text_style.font_size.to_pixels(window.rem_size()) * zoom;
+        let line_height = window.line_height() * zoom;
+        let header_height = HEADER_HEIGHT * zoom;
+        let port_radius = PORT_RADIUS * zoom.max(0.5);
+        let text_inset = NODE_PADDING * 2. * zoom;
+
+        let mut nodes = Vec::new();
+        for node in graph.nodes() {
+            let node_bounds = layout.node_bounds(node);
+            if !node_bounds
+                .dilate(port_radius)
+                .intersects(&window.content_mask().bounds)
+            {
+                continue;
+            }
+
+            let mut labels = Vec::new();
+            if show_text {
+                let mut shape_text =
+                    |text: &SharedString, origin: Point<Pixels>, align_right: bool| {
+                        let run = crate::TextRun {
+                            color: self.colors.text,
+                            ..text_style.to_run(text.len())
+                        };
+                        let line =
+                            window
+                                .text_system()
+                                .shape_line(text.clone(), font_size, &[run], None);
+                        let x = if align_right {
+                            origin.x - line.width
+                        } else {
+                            origin.x
+                        };
+                        labels.push((line, point(x, origin.y - line_height / 2.)));
+                    };
+
+                shape_text(
+                    &node.title,
+                    point(
+                        node_bounds.left() + text_inset,
+                        node_bounds.top() + header_height / 2.,
+                    ),
+                    false,
+                );
+                for (port, label) in node.inputs.iter().enumerate() {
+                    let center = layout.to_screen(node.input_position(port));
+                    shape_text(label, point(center.x + text_inset, center.y), false);
+                }
+                for (port, label) in node.outputs.iter().enumerate() {
+                    let center = layout.to_screen(node.output_position(port));
+                    shape_text(label, point(center.x - text_inset, center.y), true);
+                }
+            }
+
+            let ports = (0..node.inputs.len())
+                .map(|port| node.input_position(port))
+                .chain((0..node.outputs.len()).map(|port| node.output_position(port)))
+                .map(|center| layout.to_screen(center))
+                .collect();
+
+            nodes.push(NodePaint {
+                bounds: node_bounds,
+                header_color: node.color.unwrap_or(self.colors.node_header),
+                selected: interaction.selection.nodes.contains(&node.id),
+                labels,
+                ports,
+            });
+        }
+        nodes
+    }
+
+    fn paint_nodes(
+        &self,
+        nodes: Vec<NodePaint>,
+        layout: &GraphLayout,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let zoom = layout.viewport.zoom;
+        let line_height = window.line_height() * zoom;
+        let corner_radius = CORNER_RADIUS * zoom;
+        let header_height = HEADER_HEIGHT * zoom;
+        let port_radius = PORT_RADIUS * zoom.max(0.5);
+
+        for node in nodes {
+            let border_color = if node.selected {
+                self.colors.selection
+            } else {
+                self.colors.node_background
+            };
+            window.paint_quad(quad(
+                node.bounds,
+                corner_radius,
+                self.colors.node_background,
+                px(1.),
+                border_color,
+                BorderStyle::Solid,
+            ));
+            window.paint_quad(
+                fill(
+                    Bounds::new(
+                        node.bounds.origin,
+                        size(node.bounds.size.width, header_height),
+                    ),
+                    node.header_color,
+                )
+                .corner_radii(crate::Corners {
+                    top_left: corner_radius,
+                    top_right: corner_radius,
+                    ..Default::default()
+                }),
+            );
+
+            for (line, origin) in node.labels {
+                line.paint(origin, line_height, window, cx).log_err();
+            }
+
+            for center in node.ports {
+                window.paint_quad(
+                    fill(
+                        Bounds::new(
+                            point(center.x - port_radius, center.y - port_radius),
+                            size(port_radius * 2., port_radius * 2.),
+                        ),
+                        self.colors.port,
+                    )
+                    .corner_radii(port_radius),
+                );
+            }
+        }
+    }
+
+    fn register_mouse_listeners(
+        &self,
+        bounds: Bounds<Pixels>,
+        prepaint: &NodeGraphPrepaintState,
+        window: &mut Window,
+    ) {
+        let current_view = window.current_view();
+        let zoom_range = self.zoom_range;
+
+        window.on_mouse_event({
+            let hitbox = prepaint.hitbox.clone();
+            let interaction = prepaint.interaction.clone();
+            let graph = self.graph.clone();
+            let on_selection_change = self.on_selection_change.clone();
+            move |event: &MouseDownEvent, phase, window, cx| {
+                if phase != DispatchPhase::Bubble || !hitbox.is_hovered(window) {
+                    return;
+                }
+                let mut interaction = interaction.borrow_mut();
+                if event.button == MouseButton::Middle {
+                    interaction.drag = Some(GraphDrag::Pan {
+                        last_position: event.position,
+                    });
+                    return;
+                }
+                if event.button != MouseButton::Left {
+                    return;
+                }
+
+                let layout = GraphLayout {
+                    origin: bounds.origin,
+                    viewport: interaction.viewport,
+                };
+                let previous_selection = interaction.selection.clone();
+                let extend = event.modifiers.shift;
+                let hit = layout.hit_test(graph.read(cx), event.position);
+                match hit {
+                    Some(GraphHit::Output(from)) => {
+                        interaction.drag = Some(GraphDrag::Connect {
+                            from,
+                            position: event.position,
+                        });
+                    }
+                    Some(GraphHit::Input(to)) => {
+                        let existing = graph.read(cx).connections_to(to).last().copied();
+                        if let Some(existing) = existing {
+                            graph.update(cx, |graph, cx| {
+                                graph.disconnect(existing);
+                                cx.notify();
+                            });
+                            interaction.drag = Some(GraphDrag::Connect {
+                                from: existing.from,
+                                position: event.position,
+                            });
+                        }
+                    }
+                    Some(GraphHit::Node(node)) => {
+                        let selection = &mut interaction.selection;
+                        if extend {
+                            if let Some(ix) = selection.nodes.iter().position(|id| *id == node) {
+                                selection.nodes.remove(ix);
+                            } else {
+                                selection.nodes.push(node);
+                            }
+                        } else if !selection.nodes.contains(&node) {
+                            *selection = NodeGraphSelection {
+                                nodes: vec![node],
+                                connections: Vec::new(),
+                            };
+                        }
+                        graph.update(cx, |graph, cx| {
+                            graph.raise_node(node);
+                            cx.notify();
+                        });
+                        interaction.drag = Some(GraphDrag::Nodes {
+                            last_position: event.position,
+                        });
+                    }
+                    Some(GraphHit::Connection(connection)) => {
+                        let selection = &mut interaction.selection;
+                        if !extend {
+                            selection.nodes.clear();
+                            selection.connections.clear();
+                        }
+                        if !selection.connections.contains(&connection) {
+                            selection.connections.push(connection);
+                        }
+                    }
+                    None => {
+                        if !extend {
+                            interaction.selection = NodeGraphSelection::default();
+                        }
+                        interaction.drag = Some(GraphDrag::Select {
+                            start: event.position,
+                            position: event.position,
+                            base: interaction.selection.clone(),
+                        });
+                    }
+                }
+
+                let selection = interaction.selection.clone();
+                drop(interaction);
+                if selection != previous_selection
+                    && let Some(on_selection_change) = on_selection_change.as_ref()
+                {
+                    on_selection_change(&selection, window, cx);
+                }
+                cx.notify(current_view);
+            }
+        });
+
+        window.on_mouse_event({
+            let hitbox = prepaint.hitbox.clone();
+            let interaction = prepaint.interaction.clone();
+            let graph = self.graph.clone();
+            let on_selection_change = self.on_selection_change.clone();
+            move |event: &MouseMoveEvent, phase, window, cx| {
+                if phase != DispatchPhase::Bubble {
+                    return;
+                }
+                let mut interaction = interaction.borrow_mut();
+                let layout = GraphLayout {
+                    origin: bounds.origin,
+                    viewport: interaction.viewport,
+                };
+                let drag = interaction.drag.take();
+                match drag {
+                    Some(GraphDrag::Nodes { last_position }) => {
+                        let delta = (event.position - last_position) / layout.viewport.zoom;
+                        let selected = interaction.selection.nodes.clone();
+                        graph.update(cx, |graph, cx| {
+                            for id in selected {
+                                if let Some(node) = graph.node_mut(id) {
+                                    node.position = node.position + delta;
+                                }
+                            }
+                            cx.notify();
+                        });
+                        interaction.drag = Some(GraphDrag::Nodes {
+                            last_position: event.position,
+                        });
+                    }
+                    Some(GraphDrag::Pan { last_position }) => {
+                        interaction.viewport.offset =
+                            interaction.viewport.offset + (event.position - last_position);
+                        interaction.drag = Some(GraphDrag::Pan {
+                            last_position: event.position,
+                        });
+                        cx.notify(current_view);
+                    }
+                    Some(GraphDrag::Connect { from, .. }) => {
+                        interaction.drag = Some(GraphDrag::Connect {
+                            from,
+                            position: event.position,
+                        });
+                        cx.notify(current_view);
+                    }
+                    Some(GraphDrag::Select { start, base, .. }) => {
+                        let rect = Bounds::from_corners(
+                            start.min(&event.position),
+                            start.max(&event.position),
+                        );
+                        let selection =
+                            extend_selection(&base, layout.nodes_in_rect(graph.read(cx), rect));
+                        interaction.drag = Some(GraphDrag::Select {
+                            start,
+                            position: event.position,
+                            base,
+                        });
+                        if selection != interaction.selection {
+                            interaction.selection = selection.clone();
+                            drop(interaction);
+                            if let Some(on_selection_change) = on_selection_change.as_ref() {
+                                on_selection_change(&selection, window, cx);
+                            }
+                        }
+                        cx.notify(current_view);
+                    }
+                    None => {
+                        let hovered = if hitbox.is_hovered(window) {
+                            match layout.hit_test(graph.read(cx), event.position) {
+                                Some(GraphHit::Connection(connection)) => Some(connection),
+                                _ => None,
+                            }
+                        } else {
+                            None
+                        };
+                        if interaction.hovered_connection != hovered {
+                            interaction.hovered_connection = hovered;
+                            cx.notify(current_view);
+                        }
+                    }
+                }
+            }
+        });
+
+        window.on_mouse_event({
+            let interaction = prepaint.interaction.clone();
+            let graph = self.graph.clone();
+            let can_connect = self.can_connect.clone();
+            move |event: &MouseUpEvent, phase, _window, cx| {
+                if phase != DispatchPhase::Bubble {
+                    return;
+                }
+                let mut interaction = interaction.borrow_mut();
+                let Some(drag) = interaction.drag.take() else {
+                    return;
+                };
+                if let GraphDrag::Connect { from, .. } = drag {
+                    let layout = GraphLayout {
+                        origin: bounds.origin,
+                        viewport: interaction.viewport,
+                    };
+                    if let Some(GraphHit::Input(to)) =
+                        layout.hit_test(graph.read(cx), event.position)
+                    {
+                        let allowed = can_connect
+                            .as_ref()
+                            .is_none_or(|can_connect| can_connect(graph.read(cx), from, to));
+                        if allowed {
+                            graph.update(cx, |graph, cx| {
+                                if graph.connect(from, to) {
+                                    cx.notify();
+                                }
+                            });
+                        }
+                    }
+                }
+                cx.notify(current_view);
+            }
+        });
+
+        window.on_mouse_event({
+            let hitbox = prepaint.hitbox.clone();
+            let interaction = prepaint.interaction.clone();
+            move |event: &ScrollWheelEvent, phase, window, cx| {
+                if phase != DispatchPhase::Bubble || !hitbox.is_hovered(window) {
+                    return;
+                }
+                let mut interaction = interaction.borrow_mut();
+                let delta = event.delta.pixel_delta(window.line_height());
+                let viewport = &mut interaction.viewport;
+                if event.modifiers.secondary() {
+                    let layout = GraphLayout {
+                        origin: bounds.origin,
+                        viewport: *viewport,
+                    };
+                    let anchor = layout.to_graph(event.position);
+                    let zoom = (viewport.zoom * (f32::from(delta.y) / 200.).exp())
+                        .clamp(zoom_range.0, zoom_range.1);
+                    viewport.zoom = zoom;
+                    viewport.offset = event.position - bounds.origin - anchor * zoom;
+                } else {
+                    viewport.offset = viewport.offset + delta;
+                }
+                cx.stop_propagation();
+                cx.notify(current_view);
+            }
+        });
+    }
+}
+
+fn paint_curve(
+    curve: &[Point<Pixels>; 4],
+    width: Pixels,
+    zoom: f32,
+    color: Hsla,
+    window: &mut Window,
+) {
+    let mut builder = PathBuilder::stroke(width);
+    builder.move_to(curve[0]);
+    if zoom < LOD_CURVE_ZOOM {
+        builder.line_to(curve[3]);
+    } else {
+        builder.cubic_bezier_to(curve[3], curve[1], curve[2]);
+    }
+    if let Ok(path) = builder.build() {
+        window.paint_path(path, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppContext as _, TestAppContext};
+
+    #[test]
+    fn test_node_graph_connections() {
+        let mut graph = NodeGraph::new();
+        let oscillator = graph.add_node("Oscillator", Point::default(), ["pitch"], ["out"]);
+        let filter = graph.add_node("Filter", point(px(200.), px(0.)), ["in", "cutoff"], ["out"]);
+        let output = PortRef {
+            node: oscillator,
+            port: 0,
+        };
+        let input = PortRef {
+            node: filter,
+            port: 0,
+        };
+
+        assert!(graph.connect(output, input));
+        assert!(!graph.connect(output, input), "duplicate connection");
+        assert!(
+            !graph.connect(
+                output,
+                PortRef {
+                    node: filter,
+                    port: 2
+                }
+            ),
+            "missing port"
+        );
+        assert!(
+            !graph.connect(
+                PortRef {
+                    node: filter,
+                    port: 0
+                },
+                PortRef {
+                    node: filter,
+                    port: 1
+                }
+            ),
+            "self connection"
+        );
+        assert_eq!(graph.connections_to(input).count(), 1);
+
+        graph.remove_node(oscillator);
+        assert!(graph.connections().is_empty());
+        assert_eq!(graph.nodes().len(), 1);
+    }
+
+    #[test]
+    fn test_node_graph_hit_test() {
+        let mut graph = NodeGraph::new();
+        let source = graph.add_node(
+            "Source",
+            Point::default(),
+            Vec::<SharedString>::new(),
+            ["out"],
+        );
+        let sink = graph.add_node(
+            "Sink",
+            point(px(400.), px(200.)),
+            ["in"],
+            Vec::<SharedString>::new(),
+        );
+        let connection = GraphConnection {
+            from: PortRef {
+                node: source,
+                port: 0,
+            },
+            to: PortRef {
+                node: sink,
+                port: 0,
+            },
+        };
+        graph.connect(connection.from, connection.to);
+
+        let layout = GraphLayout {
+            origin: point(px(10.), px(10.)),
+            viewport: GraphViewport {
+                offset: point(px(20.), px(0.)),
+                zoom: 0.5,
+            },
+        };
+        let output_position = layout.to_screen(graph.nodes()[0].output_position(0));
+        assert_eq!(
+            layout.to_graph(output_position),
+            graph.nodes()[0].output_position(0)
+        );
+        assert_eq!(
+            layout.hit_test(&graph, output_position),
+            Some(GraphHit::Output(connection.from))
+        );
+        assert_eq!(
+            layout.hit_test(&graph, layout.to_screen(point(px(80.), px(10.)))),
+            Some(GraphHit::Node(source))
+        );
+
+        let curve = layout.connection_curve(&graph, &connection).unwrap();
+        let midpoint = cubic_bezier_point(&curve, 0.5);
+        assert_eq!(
+            layout.hit_test(&graph, midpoint),
+            Some(GraphHit::Connection(connection))
+        );
+        assert_eq!(
+            layout.hit_test(&graph, midpoint + point(px(0.), px(40.))),
+            None
+        );
+
+        let rect = Bounds::from_corners(
+            layout.to_screen(point(px(300.), px(150.))),
+            layout.to_screen(point(px(500.), px(300.))),
+        );
+        assert_eq!(layout.nodes_in_rect(&graph, rect), vec![sink]);
+    }
+
+    #[test]
+    fn test_extend_selection() {
+        let base = NodeGraphSelection {
+            nodes: vec![NodeId(0)],
+            connections: Vec::new(),
+        };
+
+        // Growing the rectangle over a node and shrinking it again must not leave the node
+        // selected, so each move starts over from the selection the drag started with.
+        let grown = extend_selection(&base, vec![NodeId(1), NodeId(2)]);
+        assert_eq!(grown.nodes, vec![NodeId(0), NodeId(1), NodeId(2)]);
+        let shrunk = extend_selection(&base, vec![NodeId(1)]);
+        assert_eq!(shrunk.nodes, vec![NodeId(0), NodeId(1)]);
+        let empty = extend_selection(&base, Vec::new());
+        assert_eq!(empty, base);
+    }
+
+    #[crate::test]
+    fn test_zoom_range_is_positive(cx: &mut TestAppContext) {
+        let graph = cx.new(|_| NodeGraph::new());
+        let element = node_graph("graph", graph).zoom_range(0., 2.);
+        assert_eq!(element.zoom_range, (MIN_ZOOM, 2.));
+        let element = element.zoom_range(-1., -2.);
+        assert_eq!(element.zoom_range, (MIN_ZOOM, MIN_ZOOM));
+    }
+}
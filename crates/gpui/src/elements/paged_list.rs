@@ -0,0 +1,325 @@
+//! Lists whose items are fetched asynchronously a page at a time, such as search results or
+//! presets browsed from a server. Implement [`ListDataSource`] to fetch a range of items, keep a
+//! [`PagedList`] entity on your view, and render it with [`paged_list`], which fetches the pages
+//! of the items that the list lays out and renders placeholders until they arrive.
+
+use std::{collections::BTreeMap, ops::Range};
+
+use anyhow::Result;
+
+use crate::{
+    AnyElement, App, Context, Entity, List, ListAlignment, ListState, SharedString, Task, Window,
+    list, px,
+};
+
+/// A source of list items that are fetched asynchronously, a page at a time.
+pub trait ListDataSource: 'static {
+    /// The type of the items in the list.
+    type Item: Clone + 'static;
+
+    /// The number of items fetched at a time.
+    fn page_size(&self) -> usize {
+        50
+    }
+
+    /// The total number of items, if known. Without a hint, the list grows a page at a time as
+    /// it's scrolled to its end, until a fetch returns fewer items than requested.
+    fn total_count_hint(&self) -> Option<usize>;
+
+    /// Fetch the items in the given range. When the total count is known, the whole range must
+    /// be returned.
+    fn fetch(&mut self, range: Range<usize>, cx: &mut App) -> Task<Result<Vec<Self::Item>>>;
+}
+
+/// The state of an item in a [`PagedList`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum PagedListEntry<T> {
+    /// The item's page is being fetched.
+    Loading,
+    /// The item was fetched.
+    Loaded(T),
+    /// Fetching the item's page failed with the given error, and can be retried with
+    /// [`PagedList::retry`].
+    Failed(SharedString),
+}
+
+enum Page<T> {
+    Loading(Task<()>),
+    Loaded(Vec<T>),
+    Failed(SharedString),
+}
+
+/// The pages fetched from a [`ListDataSource`], and the state of the list that renders them.
+/// Keep one in an entity on your view, and render it with [`paged_list`].
+pub struct PagedList<S: ListDataSource> {
+    source: S,
+    pages: BTreeMap<usize, Page<S::Item>>,
+    list_state: ListState,
+    loaded_end: usize,
+    reached_end: bool,
+}
+
+impl<S: ListDataSource> PagedList<S> {
+    /// Create a list of the items in the given source. No items are fetched until they're
+    /// rendered or [`PagedList::load`] is called.
+    pub fn new(source: S, alignment: ListAlignment) -> Self {
+        let item_count = source.total_count_hint().unwrap_or(1);
+        Self {
+            source,
+            pages: BTreeMap::new(),
+            list_state: ListState::new(item_count, alignment, px(1000.)),
+            loaded_end: 0,
+            reached_end: false,
+        }
+    }
+
+    /// The data source.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// The data source, for changing what it fetches. Call [`PagedList::invalidate_all`]
+    /// afterwards to fetch its items again.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// The state of the [`list`] element that renders the items.
+    pub fn list_state(&self) -> &ListState {
+        &self.list_state
+    }
+
+    /// The number of rows in the list. Without a total count hint, this includes a row for the
+    /// next page until the end of the items is reached.
+    pub fn item_count(&self) -> usize {
+        match self.source.total_count_hint() {
+            Some(count) => count,
+            None => self.loaded_end + usize::from(!self.reached_end),
+        }
+    }
+
+    /// The state of the item at the given index.
+    pub fn entry(&self, ix: usize) -> PagedListEntry<S::Item> {
+        let page_size = self.page_size();
+        match self.pages.get(&(ix / page_size)) {
+            None | Some(Page::Loading(_)) => PagedListEntry::Loading,
+            Some(Page::Failed(error)) => PagedListEntry::Failed(error.clone()),
+            Some(Page::Loaded(items)) => match items.get(ix % page_size) {
+                Some(item) => PagedListEntry::Loaded(item.clone()),
+                None => PagedListEntry::Failed("The data source didn't return this item".into()),
+            },
+        }
+    }
+
+    /// Fetch the page of the item at the given index, unless it was already fetched or is being
+    /// fetched.
+    pub fn load(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let page = ix / self.page_size();
+        if ix >= self.item_count() || self.pages.contains_key(&page) {
+            return;
+        }
+
+        let page_size = self.page_size();
+        let start = page * page_size;
+        let end = match self.source.total_count_hint() {
+            Some(count) => (start + page_size).min(count),
+            None => start + page_size,
+        };
+        let fetch = self.source.fetch(start..end, cx);
+        let task = cx.spawn(async move |this, cx| {
+            let result = fetch.await;
+            this.update(cx, |this, cx| this.finish_fetch(page, result, cx))
+                .ok();
+        });
+        self.pages.insert(page, Page::Loading(task));
+    }
+
+    /// Fetch the page of the item at the given index again, if fetching it failed.
+    pub fn retry(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let page = ix / self.page_size();
+        if let Some(Page::Failed(_)) = self.pages.get(&page) {
+            self.pages.remove(&page);
+            self.load(ix, cx);
+            let start = page * self.page_size();
+            self.remeasure(start..start + self.page_size(), cx);
+        }
+    }
+
+    /// Discard the fetched items in the given range, so that they're fetched again when they're
+    /// next rendered. Fetches in progress for these items are cancelled.
+    pub fn invalidate(&mut self, range: Range<usize>, cx: &mut Context<Self>) {
+        if range.is_empty() {
+            return;
+        }
+        let page_size = self.page_size();
+        let pages = range.start / page_size..range.end.div_ceil(page_size);
+        self.pages.retain(|page, _| !pages.contains(page));
+        self.remeasure(pages.start * page_size..pages.end * page_size, cx);
+    }
+
+    /// Discard every fetched item, such as when the source's query changed. Without a total
+    /// count hint, the list shrinks back to its first page.
+    pub fn invalidate_all(&mut self, cx: &mut Context<Self>) {
+        self.pages.clear();
+        self.loaded_end = 0;
+        self.reached_end = false;
+        self.list_state.reset(self.item_count());
+        cx.notify();
+    }
+
+    fn page_size(&self) -> usize {
+        self.source.page_size().max(1)
+    }
+
+    fn finish_fetch(&mut self, page: usize, result: Result<Vec<S::Item>>, cx: &mut Context<Self>) {
+        let page_size = self.page_size();
+        let start = page * page_size;
+        let page_state = match result {
+            Ok(items) => {
+                if self.source.total_count_hint().is_none() {
+                    self.loaded_end = self.loaded_end.max(start + items.len());
+                    if items.len() < page_size {
+                        self.reached_end = true;
+                    }
+                }
+                Page::Loaded(items)
+            }
+            Err(error) => Page::Failed(format!("{error:#}").into()),
+        };
+        self.pages.insert(page, page_state);
+        self.remeasure(start..start + page_size, cx);
+    }
+
+    /// Tell the list that the items in the given range changed, along with the rows after them
+    /// if the number of items changed.
+    fn remeasure(&mut self, range: Range<usize>, cx: &mut Context<Self>) {
+        let old_count = self.list_state.item_count();
+        let new_count = self.item_count();
+        let start = range.start.min(old_count).min(new_count);
+        if old_count == new_count {
+            let end = range.end.clamp(start, old_count);
+            self.list_state.splice(start..end, end - start);
+        } else {
+            self.list_state.splice(start..old_count, new_count - start);
+        }
+        cx.notify();
+    }
+}
+
+/// Render the items of a [`PagedList`] in a [`list`], fetching the pages of the items that the
+/// list lays out. `render_entry` renders each item, or a placeholder while it's loading and a
+/// retry button when fetching it failed, which calls [`PagedList::retry`].
+pub fn paged_list<S, F>(paged: &Entity<PagedList<S>>, cx: &App, render_entry: F) -> List
+where
+    S: ListDataSource,
+    F: Fn(usize, PagedListEntry<S::Item>, &mut Window, &mut App) -> AnyElement + 'static,
+{
+    let list_state = paged.read(cx).list_state.clone();
+    let paged = paged.clone();
+    list(list_state, move |ix, window, cx| {
+        let entry = paged.update(cx, |paged, cx| {
+            paged.load(ix, cx);
+            paged.entry(ix)
+        });
+        render_entry(ix, entry, window, cx)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+    use crate::{AppContext as _, TestAppContext};
+
+    struct Numbers {
+        count: usize,
+        count_known: bool,
+        fail: Rc<Cell<bool>>,
+    }
+
+    impl ListDataSource for Numbers {
+        type Item = usize;
+
+        fn page_size(&self) -> usize {
+            10
+        }
+
+        fn total_count_hint(&self) -> Option<usize> {
+            self.count_known.then_some(self.count)
+        }
+
+        fn fetch(&mut self, range: Range<usize>, _: &mut App) -> Task<Result<Vec<usize>>> {
+            if self.fail.get() {
+                return Task::ready(Err(anyhow::anyhow!("offline")));
+            }
+            Task::ready(Ok((range.start..range.end.min(self.count)).collect()))
+        }
+    }
+
+    #[crate::test]
+    fn test_infinite_scrolling(cx: &mut TestAppContext) {
+        let fail = Rc::new(Cell::new(false));
+        let numbers = cx.new(|_| {
+            PagedList::new(
+                Numbers {
+                    count: 25,
+                    count_known: false,
+                    fail: fail.clone(),
+                },
+                ListAlignment::Top,
+            )
+        });
+        numbers.read_with(cx, |numbers, _| {
+            assert_eq!(numbers.item_count(), 1);
+            assert_eq!(numbers.entry(0), PagedListEntry::Loading);
+        });
+
+        for (ix, expected_count) in [(0, 11), (10, 21), (20, 25)] {
+            numbers.update(cx, |numbers, cx| numbers.load(ix, cx));
+            cx.run_until_parked();
+            numbers.read_with(cx, |numbers, _| {
+                assert_eq!(numbers.item_count(), expected_count);
+                assert_eq!(numbers.list_state().item_count(), expected_count);
+                assert_eq!(numbers.entry(ix), PagedListEntry::Loaded(ix));
+            });
+        }
+
+        numbers.update(cx, |numbers, cx| numbers.invalidate_all(cx));
+        numbers.read_with(cx, |numbers, _| assert_eq!(numbers.item_count(), 1));
+    }
+
+    #[crate::test]
+    fn test_failed_pages(cx: &mut TestAppContext) {
+        let fail = Rc::new(Cell::new(true));
+        let numbers = cx.new(|_| {
+            PagedList::new(
+                Numbers {
+                    count: 100,
+                    count_known: true,
+                    fail: fail.clone(),
+                },
+                ListAlignment::Top,
+            )
+        });
+        numbers.update(cx, |numbers, cx| numbers.load(42, cx));
+        cx.run_until_parked();
+        numbers.read_with(cx, |numbers, _| {
+            assert_eq!(numbers.item_count(), 100);
+            assert_eq!(numbers.entry(45), PagedListEntry::Failed("offline".into()));
+            assert_eq!(numbers.entry(50), PagedListEntry::Loading);
+        });
+
+        fail.set(false);
+        numbers.update(cx, |numbers, cx| numbers.retry(45, cx));
+        cx.run_until_parked();
+        numbers.read_with(cx, |numbers, _| {
+            assert_eq!(numbers.entry(45), PagedListEntry::Loaded(45))
+        });
+
+        numbers.update(cx, |numbers, cx| numbers.invalidate(40..41, cx));
+        numbers.read_with(cx, |numbers, _| {
+            assert_eq!(numbers.entry(49), PagedListEntry::Loading)
+        });
+    }
+}
@@ -0,0 +1,323 @@
+//! Portals, which render content owned by one part of the tree somewhere else: above everything
+//! else in the window, escaping the clipping and stacking of their ancestors, such as a menu
+//! opened from a scrolled list, or in another window, such as a popup window.
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    AnyElement, AnyWindowHandle, App, AsyncApp, AvailableSpace, Bounds, Element, ElementId,
+    EntityId, GlobalElementId, InspectorElementId, IntoElement, LayoutId, Pixels, Point, Style,
+    Window,
+};
+
+/// Where a [`portal`] renders its content.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PortalTarget {
+    /// Above the rest of the window, at the portal's position. Layers with a higher number are
+    /// drawn above those with a lower one, in the same order as [`deferred`](crate::deferred)
+    /// elements of the same priority.
+    Layer(usize),
+    /// Above the root view of the given window, laid out in its full size. The content is
+    /// rendered in that window's frames, so it keeps up when that window redraws on its own.
+    /// A portal targeting its own window renders above all of its layers.
+    Window(AnyWindowHandle),
+}
+
+impl From<AnyWindowHandle> for PortalTarget {
+    fn from(window: AnyWindowHandle) -> Self {
+        PortalTarget::Window(window)
+    }
+}
+
+/// Create a portal, which renders the content returned by `render` at the given target rather
+/// than where the portal is in the tree. The portal itself takes up no space.
+///
+/// The content stays owned by the view rendering the portal: it's rendered on that view's
+/// behalf, so its listeners and notifications go to that view, and it's removed as soon as
+/// the portal stops being rendered. It's given as a function, since a portal targeting another
+/// window renders it again whenever that window draws.
+pub fn portal<E: IntoElement>(
+    id: impl Into<ElementId>,
+    target: impl Into<PortalTarget>,
+    render: impl Fn(&mut Window, &mut App) -> E + 'static,
+) -> Portal {
+    Portal {
+        id: id.into(),
+        target: target.into(),
+        render: Rc::new(move |window, cx| render(window, cx).into_any_element()),
+    }
+}
+
+type RenderPortal = Rc<dyn Fn(&mut Window, &mut App) -> AnyElement>;
+
+/// An element that renders its content at another place, created with [`portal`].
+pub struct Portal {
+    id: ElementId,
+    target: PortalTarget,
+    render: RenderPortal,
+}
+
+/// The content portals in other windows render into a window, drawn above its root view.
+#[derive(Clone, Default)]
+pub(crate) struct PortalHost(Rc<RefCell<PortalHostState>>);
+
+#[derive(Default)]
+struct PortalHostState {
+    next_id: usize,
+    portals: Vec<(usize, PortalContent)>,
+}
+
+#[derive(Clone)]
+pub(crate) struct PortalContent {
+    pub(crate) source_view: EntityId,
+    pub(crate) render: RenderPortal,
+}
+
+impl PortalHost {
+    pub(crate) fn contents(&self) -> Vec<PortalContent> {
+        let state = self.0.borrow();
+        state
+            .portals
+            .iter()
+            .map(|(_, content)| content.clone())
+            .collect()
+    }
+
+    fn insert(&self, content: PortalContent) -> usize {
+        let mut state = self.0.borrow_mut();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.portals.push((id, content));
+        id
+    }
+
+    fn update(&self, id: usize, content: PortalContent) {
+        let mut state = self.0.borrow_mut();
+        if let Some((_, existing)) = state.portals.iter_mut().find(|(ix, _)| *ix == id) {
+            *existing = content;
+        }
+    }
+
+    fn remove(&self, id: usize) {
+        self.0.borrow_mut().portals.retain(|(ix, _)| *ix != id);
+    }
+}
+
+/// The state of a [`Portal`] targeting another window, which removes its content from that
+/// window when the portal stops being rendered.
+struct PortalRegistration {
+    target: AnyWindowHandle,
+    host: PortalHost,
+    id: usize,
+    cx: AsyncApp,
+}
+
+impl PortalRegistration {
+    fn new(target: AnyWindowHandle, content: PortalContent, cx: &App) -> Option<Self> {
+        let window = cx.windows.get(target.window_id())?.as_deref()?;
+        let host = window.portal_host.clone();
+        let id = host.insert(content);
+        let registration = Self {
+            target,
+            host,
+            id,
+            cx: cx.to_async(),
+        };
+        registration.refresh_target();
+        Some(registration)
+    }
+
+    fn update(&self, content: PortalContent) {
+        self.host.update(self.id, content);
+        self.refresh_target();
+    }
+
+    /// Redraw the target window once the current frame is done, since it can't be updated while
+    /// the portal's window draws, nor when the registration is dropped along with its frame.
+    fn refresh_target(&self) {
+        let target = self.target;
+        let mut cx = self.cx.clone();
+        self.cx
+            .foreground_executor()
+            .spawn(async move {
+                target.update(&mut cx, |_, window, _| window.refresh()).ok();
+            })
+            .detach();
+    }
+}
+
+impl Drop for PortalRegistration {
+    fn drop(&mut self) {
+        self.host.remove(self.id);
+        self.refresh_target();
+    }
+}
+
+impl IntoElement for Portal {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for Portal {
+    type RequestLayoutState = ();
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        Some(self.id.clone())
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        (window.request_layout(Style::default(), None, cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let target = match self.target {
+            PortalTarget::Window(target) if target == window.window_handle() => {
+                let mut element = (self.render)(window, cx);
+                element.layout_as_root(window.viewport_size().into(), window, cx);
+                window.defer_draw(element, Point::default(), usize::MAX);
+                return;
+            }
+            PortalTarget::Layer(priority) => {
+                let mut element = (self.render)(window, cx);
+                element.layout_as_root(AvailableSpace::min_size(), window, cx);
+                window.defer_draw(element, bounds.origin, priority);
+                return;
+            }
+            PortalTarget::Window(target) => target,
+        };
+
+        let content = PortalContent {
+            source_view: window.current_view(),
+            render: self.render.clone(),
+        };
+        window.with_element_state(
+            global_id.unwrap(),
+            |registration: Option<Option<PortalRegistration>>, _| {
+                let registration = match registration.flatten() {
+                    Some(registration) if registration.target == target => {
+                        registration.update(content);
+                        Some(registration)
+                    }
+                    _ => PortalRegistration::new(target, content, cx),
+                };
+                ((), registration)
+            },
+        );
+    }
+
+    fn paint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        _: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        _: &mut Self::PrepaintState,
+        _: &mut Window,
+        _: &mut App,
+    ) {
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::{
+        Context, InteractiveElement as _, Modifiers, ParentElement as _, Render, Styled as _,
+        TestAppContext, VisualTestContext, div, point, prelude::FluentBuilder as _, px,
+    };
+
+    struct List {
+        target: PortalTarget,
+        shown: bool,
+        clicks: Rc<Cell<usize>>,
+    }
+
+    impl Render for List {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let clicks = self.clicks.clone();
+            let menu = portal("menu", self.target, move |_, _| {
+                let clicks = clicks.clone();
+                div()
+                    .id("menu")
+                    .size(px(100.))
+                    .on_click(move |_, _, _| clicks.set(clicks.get() + 1))
+            });
+            div()
+                .size(px(50.))
+                .overflow_hidden()
+                .when(self.shown, |this| this.child(menu))
+        }
+    }
+
+    struct Popup;
+
+    impl Render for Popup {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size_full()
+        }
+    }
+
+    #[crate::test]
+    fn test_layer_portal_escapes_clipping(cx: &mut TestAppContext) {
+        let clicks = Rc::new(Cell::new(0));
+        let (_, cx) = cx.add_window_view(|_, _| List {
+            target: PortalTarget::Layer(0),
+            shown: true,
+            clicks: clicks.clone(),
+        });
+
+        cx.simulate_click(point(px(80.), px(80.)), Modifiers::none());
+        assert_eq!(clicks.get(), 1);
+    }
+
+    #[crate::test]
+    fn test_window_portal_renders_in_target_window(cx: &mut TestAppContext) {
+        let clicks = Rc::new(Cell::new(0));
+        let popup = cx.add_window(|_, _| Popup);
+        let list = cx.add_window(|_, _| List {
+            target: PortalTarget::Window(popup.into()),
+            shown: true,
+            clicks: clicks.clone(),
+        });
+        cx.run_until_parked();
+
+        let mut popup_cx = VisualTestContext::from_window(popup.into(), cx);
+        popup_cx.simulate_click(point(px(80.), px(80.)), Modifiers::none());
+        assert_eq!(clicks.get(), 1);
+
+        list.update(cx, |list, _, cx| {
+            list.shown = false;
+            cx.notify();
+        })
+        .unwrap();
+        cx.run_until_parked();
+
+        let mut popup_cx = VisualTestContext::from_window(popup.into(), cx);
+        popup_cx.simulate_click(point(px(80.), px(80.)), Modifiers::none());
+        assert_eq!(clicks.get(), 1);
+    }
+}
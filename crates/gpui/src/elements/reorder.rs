@@ -0,0 +1,527 @@
+use std::{cell::RefCell, rc::Rc, time::Instant};
+
+use collections::FxHashMap;
+
+use crate::{
+    AnyElement, App, Bounds, DispatchPhase, Element, ElementId, GlobalElementId, Hitbox,
+    HitboxBehavior, InspectorElementId, IntoElement, LayoutId, ListState, MouseButton,
+    MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, Point, ScrollHandle, Window, point, px,
+};
+
+/// How far the mouse must move while pressed on an item before it's dragged.
+const DRAG_THRESHOLD: f64 = 4.;
+/// How close to the edges of the scrolled viewport the mouse must be to scroll it.
+const AUTO_SCROLL_EDGE: Pixels = px(32.);
+/// How fast the viewport scrolls when the mouse is at its edge, in pixels per second.
+const AUTO_SCROLL_SPEED: f32 = 800.;
+/// The fraction of the remaining distance that items moving out of the way cover each frame.
+const GAP_ANIMATION_STEP: f32 = 0.3;
+
+/// Emitted by a [`ReorderState`] when a dragged item is dropped at a new index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReorderEvent {
+    /// The index of the dragged item.
+    pub from: usize,
+    /// The index the item should end up at, as when removing it from `from` and then inserting
+    /// it at `to`.
+    pub to: usize,
+}
+
+/// How dragging an item to reorder it starts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReorderActivation {
+    /// Pressing anywhere on the item and moving the mouse.
+    #[default]
+    Item,
+    /// Only through [`ReorderState::press`] or [`ReorderState::start_drag`], called from the
+    /// mouse down or long press listeners of a drag handle inside the item.
+    Handle,
+}
+
+enum AutoScroll {
+    Scroll(ScrollHandle),
+    List(ListState),
+}
+
+struct ActiveDrag {
+    from: usize,
+    to: usize,
+    /// Where the mouse was when the drag started, moved along with the items as they scroll.
+    origin: Point<Pixels>,
+    position: Point<Pixels>,
+}
+
+struct ReorderStateInner {
+    on_reorder: Rc<dyn Fn(&ReorderEvent, &mut Window, &mut App)>,
+    activation: ReorderActivation,
+    auto_scroll: Option<AutoScroll>,
+    pressed: Option<(usize, Point<Pixels>)>,
+    drag: Option<ActiveDrag>,
+    item_bounds: FxHashMap<usize, Bounds<Pixels>>,
+    shifts: FxHashMap<usize, Point<Pixels>>,
+    last_auto_scroll: Option<Instant>,
+}
+
+/// The state of the items of a list or grid that can be reordered by dragging them, which views
+/// hold on behalf of the [`reorderable`] elements wrapping the items.
+///
+/// While an item is dragged, it follows the mouse above the other items, which move out of the
+/// way to open a gap where it would be dropped, and the list's viewport scrolls when the mouse
+/// nears its edges. Dropping the item emits a [`ReorderEvent`], and the view reorders its items.
+#[derive(Clone)]
+pub struct ReorderState(Rc<RefCell<ReorderStateInner>>);
+
+impl ReorderState {
+    /// Create the state of reorderable items, calling `on_reorder` when an item is dropped at
+    /// a new index.
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from
+    /// this callback.
+    pub fn new(on_reorder: impl Fn(&ReorderEvent, &mut Window, &mut App) + 'static) -> Self {
+        Self(Rc::new(RefCell::new(ReorderStateInner {
+            on_reorder: Rc::new(on_reorder),
+            activation: ReorderActivation::default(),
+            auto_scroll: None,
+            pressed: None,
+            drag: None,
+            item_bounds: FxHashMap::default(),
+            shifts: FxHashMap::default(),
+            last_auto_scroll: None,
+        })))
+    }
+
+    /// Set how dragging an item starts.
+    pub fn with_activation(self, activation: ReorderActivation) -> Self {
+        self.0.borrow_mut().activation = activation;
+        self
+    }
+
+    /// Scroll the element with the given scroll handle while an item is dragged near its edges.
+    pub fn with_auto_scroll(self, scroll_handle: ScrollHandle) -> Self {
+        self.0.borrow_mut().auto_scroll = Some(AutoScroll::Scroll(scroll_handle));
+        self
+    }
+
+    /// Scroll the [`list`](crate::list) with the given state while an item is dragged near its
+    /// edges.
+    pub fn with_list_auto_scroll(self, list_state: ListState) -> Self {
+        self.0.borrow_mut().auto_scroll = Some(AutoScroll::List(list_state));
+        self
+    }
+
+    /// The index of the item being dragged, if any.
+    pub fn dragged_ix(&self) -> Option<usize> {
+        self.0.borrow().drag.as_ref().map(|drag| drag.from)
+    }
+
+    /// The index the dragged item would move to if it was dropped now, if any.
+    pub fn target_ix(&self) -> Option<usize> {
+        self.0.borrow().drag.as_ref().map(|drag| drag.to)
+    }
+
+    /// Start dragging the item at the given index once the mouse, pressed at `position`, moves
+    /// past a small threshold. Call this from a drag handle's mouse down listener with
+    /// [`ReorderActivation::Handle`].
+    pub fn press(&self, ix: usize, position: Point<Pixels>) {
+        let mut state = self.0.borrow_mut();
+        if state.drag.is_none() {
+            state.pressed = Some((ix, position));
+        }
+    }
+
+    /// Start dragging the item at the given index right away, with the mouse at `position`,
+    /// such as from a long press listener.
+    pub fn start_drag(&self, ix: usize, position: Point<Pixels>, window: &mut Window) {
+        let mut state = self.0.borrow_mut();
+        state.pressed = None;
+        state.shifts.clear();
+        state.drag = Some(ActiveDrag {
+            from: ix,
+            to: ix,
+            origin: position,
+            position,
+        });
+        window.refresh();
+    }
+
+    /// Stop dragging without emitting a [`ReorderEvent`].
+    pub fn cancel(&self, window: &mut Window) {
+        let mut state = self.0.borrow_mut();
+        state.pressed = None;
+        if state.drag.take().is_some() {
+            state.end_drag();
+            window.refresh();
+        }
+    }
+
+    fn mouse_move(&self, position: Point<Pixels>, window: &mut Window) {
+        let pressed = self.0.borrow().pressed;
+        if let Some((ix, origin)) = pressed
+            && (position - origin).magnitude() > DRAG_THRESHOLD
+        {
+            self.start_drag(ix, origin, window);
+        }
+
+        let mut state = self.0.borrow_mut();
+        let Some(drag) = state.drag.as_mut() else {
+            return;
+        };
+        // Every reorderable item listens to the mouse, so only the first of them handles it.
+        if drag.position != position {
+            drag.position = position;
+            state.update_target();
+            window.refresh();
+        }
+    }
+
+    /// Drop the dragged item, returning whether an item was dragged.
+    fn mouse_up(&self, window: &mut Window, cx: &mut App) -> bool {
+        let (drag, on_reorder) = {
+            let mut state = self.0.borrow_mut();
+            state.pressed = None;
+            let Some(drag) = state.drag.take() else {
+                return false;
+            };
+            state.end_drag();
+            (drag, state.on_reorder.clone())
+        };
+        if drag.from != drag.to {
+            on_reorder(
+                &ReorderEvent {
+                    from: drag.from,
+                    to: drag.to,
+                },
+                window,
+                cx,
+            );
+        }
+        window.refresh();
+        true
+    }
+}
+
+impl ReorderStateInner {
+    /// Forget the items' positions once a drag ends, including those of items that were removed,
+    /// which the next frame records again.
+    fn end_drag(&mut self) {
+        self.item_bounds.clear();
+        self.shifts.clear();
+        self.last_auto_scroll = None;
+    }
+
+    /// Move the dragged item's target to the item whose center is nearest to the center of
+    /// the dragged item.
+    fn update_target(&mut self) {
+        let Some(drag) = self.drag.as_mut() else {
+            return;
+        };
+        let Some(from_bounds) = self.item_bounds.get(&drag.from) else {
+            return;
+        };
+        let center = from_bounds.center() + (drag.position - drag.origin);
+        if let Some((&ix, _)) = self.item_bounds.iter().min_by(|(_, a), (_, b)| {
+            let a = (a.center() - center).magnitude();
+            let b = (b.center() - center).magnitude();
+            a.total_cmp(&b)
+        }) {
+            drag.to = ix;
+        }
+    }
+
+    /// Where the item at the given index moves to make room for the dragged item: the items
+    /// between the dragged item and its target take the place of their neighbor.
+    fn target_shift(&self, ix: usize) -> Point<Pixels> {
+        let Some(drag) = self.drag.as_ref() else {
+            return Point::default();
+        };
+        let neighbor = if drag.from < ix && ix <= drag.to {
+            ix - 1
+        } else if drag.to <= ix && ix < drag.from {
+            ix + 1
+        } else {
+            return Point::default();
+        };
+        match (self.item_bounds.get(&neighbor), self.item_bounds.get(&ix)) {
+            (Some(neighbor), Some(bounds)) => neighbor.origin - bounds.origin,
+            _ => Point::default(),
+        }
+    }
+
+    /// Scroll the viewport if the dragged item is near its edges, returning whether it
+    /// scrolled.
+    fn auto_scroll(&mut self) -> bool {
+        let Some(drag) = self.drag.as_ref() else {
+            return false;
+        };
+        let Some(auto_scroll) = self.auto_scroll.as_ref() else {
+            return false;
+        };
+        let viewport = match auto_scroll {
+            AutoScroll::Scroll(handle) => handle.bounds(),
+            AutoScroll::List(list_state) => list_state.viewport_bounds(),
+        };
+        let edge_speed = |distance: Pixels| {
+            AUTO_SCROLL_SPEED * (1. - (distance / AUTO_SCROLL_EDGE).clamp(0., 1.))
+        };
+        let position = drag.position;
+        let speed = point(
+            edge_speed(position.x - viewport.left()) - edge_speed(viewport.right() - position.x),
+            edge_speed(position.y - viewport.top()) - edge_speed(viewport.bottom() - position.y),
+        );
+
+        let now = Instant::now();
+        let elapsed = self
+            .last_auto_scroll
+            .replace(now)
+            .map_or(0., |last| (now - last).as_secs_f32());
+        if speed.x == 0. && speed.y == 0. {
+            self.last_auto_scroll = None;
+            return false;
+        }
+        let distance = point(px(speed.x * elapsed), px(speed.y * elapsed));
+
+        // How far the items moved, which is less than the distance at the ends of the viewport.
+        let delta = match auto_scroll {
+            AutoScroll::Scroll(handle) => {
+                let old_offset = handle.offset();
+                let max_offset = handle.max_offset();
+                let new_offset = point(
+                    (old_offset.x + distance.x).clamp(-max_offset.width, px(0.)),
+                    (old_offset.y + distance.y).clamp(-max_offset.height, px(0.)),
+                );
+                handle.set_offset(new_offset);
+                new_offset - old_offset
+            }
+            AutoScroll::List(list_state) => {
+                let old_offset = list_state.scroll_px_offset_for_scrollbar();
+                list_state.scroll_by(-distance.y);
+                list_state.scroll_px_offset_for_scrollbar() - old_offset
+            }
+        };
+        if let Some(drag) = self.drag.as_mut() {
+            drag.origin += delta;
+        }
+        for bounds in self.item_bounds.values_mut() {
+            bounds.origin += delta;
+        }
+        self.update_target();
+        true
+    }
+}
+
+/// Wrap an item of a list or grid, so that it can be dragged to reorder it with the given
+/// state. Items must be wrapped with consecutive indices.
+pub fn reorderable(state: &ReorderState, ix: usize, child: impl IntoElement) -> Reorderable {
+    Reorderable {
+        state: state.clone(),
+        ix,
+        child: Some(child.into_any_element()),
+    }
+}
+
+/// An item that can be dragged to reorder it. See [`reorderable`].
+pub struct Reorderable {
+    state: ReorderState,
+    ix: usize,
+    child: Option<AnyElement>,
+}
+
+impl IntoElement for Reorderable {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for Reorderable {
+    type RequestLayoutState = ();
+    type PrepaintState = Option<Hitbox>;
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, ()) {
+        let layout_id = self.child.as_mut().unwrap().request_layout(window, cx);
+        (layout_id, ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Hitbox> {
+        let mut state = self.state.0.borrow_mut();
+        state.item_bounds.insert(self.ix, bounds);
+        let hitbox = (state.activation == ReorderActivation::Item)
+            .then(|| window.insert_hitbox(bounds, HitboxBehavior::Normal));
+
+        let drag_offset = state
+            .drag
+            .as_ref()
+            .filter(|drag| drag.from == self.ix)
+            .map(|drag| drag.position - drag.origin);
+        if let Some(drag_offset) = drag_offset {
+            // The dragged item scrolls the viewport, as there's exactly one of it in each frame.
+            if state.auto_scroll() {
+                window.request_animation_frame();
+            }
+            drop(state);
+            let child = self.child.take().unwrap();
+            let offset = window.element_offset() + drag_offset;
+            window.defer_draw(child, offset, 1);
+            return hitbox;
+        }
+
+        let target = state.target_shift(self.ix);
+        let shift = state.shifts.entry(self.ix).or_default();
+        *shift += (target - *shift) * GAP_ANIMATION_STEP;
+        if (target - *shift).magnitude() < 0.5 {
+            *shift = target;
+        } else {
+            window.request_animation_frame();
+        }
+        let shift = *shift;
+        drop(state);
+
+        let child = self.child.as_mut().unwrap();
+        window.with_element_offset(shift, |window| child.prepaint(window, cx));
+        hitbox
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        hitbox: &mut Option<Hitbox>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        if let Some(child) = self.child.as_mut() {
+            child.paint(window, cx);
+        }
+
+        if let Some(hitbox) = hitbox.clone() {
+            let state = self.state.clone();
+            let ix = self.ix;
+            window.on_mouse_event(move |event: &MouseDownEvent, phase, window, _| {
+                if phase == DispatchPhase::Bubble
+                    && event.button == MouseButton::Left
+                    && hitbox.is_hovered(window)
+                {
+                    state.press(ix, event.position);
+                }
+            });
+        }
+
+        let state = self.state.clone();
+        window.on_mouse_event(move |event: &MouseMoveEvent, phase, window, _| {
+            if phase == DispatchPhase::Capture {
+                state.mouse_move(event.position, window);
+            }
+        });
+
+        let state = self.state.clone();
+        window.on_mouse_event(move |event: &MouseUpEvent, phase, window, cx| {
+            if phase == DispatchPhase::Capture
+                && event.button == MouseButton::Left
+                && state.mouse_up(window, cx)
+            {
+                // Dropping the item doesn't click the item under the mouse.
+                cx.stop_propagation();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::{Context, Modifiers, ParentElement as _, Render, Styled as _, TestAppContext, div};
+
+    struct Items {
+        items: Rc<RefCell<Vec<&'static str>>>,
+        reorder: ReorderState,
+    }
+
+    impl Render for Items {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().flex().flex_col().children(
+                self.items.borrow().iter().enumerate().map(|(ix, item)| {
+                    reorderable(&self.reorder, ix, div().h(px(20.)).child(*item))
+                }),
+            )
+        }
+    }
+
+    #[crate::test]
+    fn test_drag_to_reorder(cx: &mut TestAppContext) {
+        let items = Rc::new(RefCell::new(vec!["a", "b", "c", "d"]));
+        let (view, cx) = cx.add_window_view(|_, cx| Items {
+            items: items.clone(),
+            reorder: ReorderState::new(cx.listener(
+                |this: &mut Items, event: &ReorderEvent, _, cx| {
+                    let mut items = this.items.borrow_mut();
+                    let item = items.remove(event.from);
+                    items.insert(event.to, item);
+                    cx.notify();
+                },
+            )),
+        });
+        let reorder = view.read_with(cx, |view, _| view.reorder.clone());
+
+        // Small movements don't start dragging, so items can still be clicked.
+        cx.simulate_mouse_down(
+            point(px(10.), px(10.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        cx.simulate_mouse_move(
+            point(px(11.), px(11.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(reorder.dragged_ix(), None);
+
+        cx.simulate_mouse_move(
+            point(px(10.), px(30.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(reorder.dragged_ix(), Some(0));
+        assert_eq!(reorder.target_ix(), Some(1));
+        cx.simulate_mouse_move(
+            point(px(10.), px(50.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(reorder.target_ix(), Some(2));
+
+        cx.simulate_mouse_up(
+            point(px(10.), px(50.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(reorder.dragged_ix(), None);
+        assert_eq!(*items.borrow(), ["b", "c", "a", "d"]);
+    }
+}
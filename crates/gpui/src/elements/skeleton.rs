@@ -0,0 +1,145 @@
+//! Skeletons, shimmering placeholders shaped like the content that's loading, such as while
+//! [`async_content`](crate::async_content) waits for its task.
+
+use std::time::Duration;
+
+use crate::{
+    App, Bounds, ColorSpace, ContentMask, Element, ElementId, GlobalElementId, Hsla,
+    InspectorElementId, IntoElement, LayoutId, Pixels, Style, StyleRefinement, Styled, Window,
+    hsla, linear_color_stop, linear_gradient, point, quad, size, transparent_black,
+};
+
+/// The width of the shimmer's highlight, relative to the width of the skeleton.
+const SHIMMER_WIDTH: f32 = 0.5;
+
+/// Create a skeleton, a placeholder for content that's loading, which shimmers until it's
+/// replaced. Size it like the content it stands in for, and set its color and shape with the
+/// `bg` and `rounded` styles. Skeletons shimmer in unison, following the window's
+/// [animation time](Window::animation_time).
+pub fn skeleton() -> Skeleton {
+    let mut style = StyleRefinement::default();
+    style.background = Some(hsla(0., 0., 0.5, 0.2).into());
+    Skeleton {
+        style,
+        shimmer_color: hsla(0., 0., 1., 0.25),
+        shimmer_duration: Duration::from_millis(1500),
+    }
+}
+
+/// A shimmering placeholder for content that's loading, created with [`skeleton`].
+pub struct Skeleton {
+    style: StyleRefinement,
+    shimmer_color: Hsla,
+    shimmer_duration: Duration,
+}
+
+impl Skeleton {
+    /// The color of the highlight that sweeps across the skeleton.
+    pub fn shimmer_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.shimmer_color = color.into();
+        self
+    }
+
+    /// How long the highlight takes to sweep across the skeleton. Defaults to 1.5 seconds.
+    pub fn shimmer_duration(mut self, duration: Duration) -> Self {
+        self.shimmer_duration = duration;
+        self
+    }
+
+    fn paint_shimmer(&self, bounds: Bounds<Pixels>, window: &mut Window) {
+        let duration = self.shimmer_duration.as_secs_f32();
+        if duration <= 0. || self.shimmer_color.a <= 0. {
+            return;
+        }
+        let progress = window.animation_time().as_secs_f32() % duration / duration;
+        let band_width = bounds.size.width * SHIMMER_WIDTH;
+        let left = bounds.left() - band_width + (bounds.size.width + band_width) * progress;
+        let half_band = size(band_width / 2., bounds.size.height);
+        let clear = self.shimmer_color.opacity(0.);
+
+        window.with_content_mask(Some(ContentMask { bounds }), |window| {
+            let rising = Bounds::new(point(left, bounds.top()), half_band);
+            let falling = Bounds::new(point(left + band_width / 2., bounds.top()), half_band);
+            for (bounds, from, to) in [
+                (rising, clear, self.shimmer_color),
+                (falling, self.shimmer_color, clear),
+            ] {
+                window.paint_quad(quad(
+                    bounds,
+                    Pixels::ZERO,
+                    linear_gradient(90., linear_color_stop(from, 0.), linear_color_stop(to, 1.))
+                        .color_space(ColorSpace::Oklab),
+                    Pixels::ZERO,
+                    transparent_black(),
+                    Default::default(),
+                ));
+            }
+        });
+        window.request_animation_frame();
+    }
+}
+
+impl Styled for Skeleton {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl IntoElement for Skeleton {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for Skeleton {
+    type RequestLayoutState = Style;
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        (window.request_layout(style.clone(), None, cx), style)
+    }
+
+    fn prepaint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        _: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        _: &mut Window,
+        _: &mut App,
+    ) {
+    }
+
+    fn paint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        style: &mut Self::RequestLayoutState,
+        _: &mut (),
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        style.paint(bounds, window, cx, |window, _| {
+            self.paint_shimmer(bounds, window)
+        });
+    }
+}
@@ -0,0 +1,440 @@
+use std::{ops::Range, sync::Arc};
+
+use parking_lot::Mutex;
+use refineable::Refineable as _;
+use util::ResultExt as _;
+
+use crate::{
+    App, Bounds, Corners, DevicePixels, Element, ElementId, GlobalElementId, InspectorElementId,
+    IntoElement, LayoutId, Pixels, Rgba, StreamingTexture, Style, StyleRefinement, Styled, Window,
+    point, px, size, util::ring_ranges,
+};
+
+/// A scrolling history of magnitude spectra, rendered by a [`Spectrogram`].
+///
+/// Each pushed row is one spectrum (low frequencies first) and becomes one column of the
+/// rendered texture, with the newest row on the right. Rows are converted to pixels as they
+/// are pushed, so the buffer can be written from another thread and redrawing only copies
+/// the new columns into the texture.
+#[derive(Clone)]
+pub struct SpectrogramBuffer(Arc<Mutex<SpectrogramBufferState>>);
+
+struct SpectrogramBufferState {
+    bins: usize,
+    history: usize,
+    /// BGRA pixels laid out like the texture: `history` columns of `bins` pixels, with the
+    /// lowest frequency at the bottom. Rows are written to column `pushed % history`, so the
+    /// columns form a ring.
+    pixels: Vec<u8>,
+    pushed: usize,
+    /// The number of times the history has been cleared, so spectrograms know to upload all of
+    /// it again.
+    clears: usize,
+    color_map: Box<dyn Fn(f32) -> Rgba + Send>,
+}
+
+impl SpectrogramBufferState {
+    /// The pixels of the given range of columns, row by row.
+    fn columns(&self, columns: Range<usize>) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(columns.len() * self.bins * 4);
+        for y in 0..self.bins {
+            let row = y * self.history;
+            bytes.extend_from_slice(
+                &self.pixels[(row + columns.start) * 4..(row + columns.end) * 4],
+            );
+        }
+        bytes
+    }
+}
+
+impl SpectrogramBuffer {
+    /// Create a buffer for spectra with `bins` frequency bins, keeping the last `history` rows.
+    pub fn new(bins: usize, history: usize) -> Self {
+        let bins = bins.max(1);
+        let history = history.max(1);
+        Self(Arc::new(Mutex::new(SpectrogramBufferState {
+            bins,
+            history,
+            pixels: vec![0; bins * history * 4],
+            pushed: 0,
+            clears: 0,
+            color_map: Box::new(heat_color),
+        })))
+    }
+
+    /// Set the function that maps a normalized magnitude in `0.0..=1.0` to a color.
+    /// Only rows pushed after this call are affected.
+    pub fn with_color_map(self, color_map: impl Fn(f32) -> Rgba + Send + 'static) -> Self {
+        self.0.lock().color_map = Box::new(color_map);
+        self
+    }
+
+    /// Append a spectrum, with each magnitude normalized to `0.0..=1.0`. Extra bins are
+    /// ignored and missing bins are treated as silence.
+    pub fn push_row(&self, magnitudes: &[f32]) {
+        let mut state = self.0.lock();
+        let (bins, history) = (state.bins, state.history);
+        let column = state.pushed % history;
+        for bin in 0..bins {
+            let magnitude = magnitudes.get(bin).copied().unwrap_or(0.).clamp(0., 1.);
+            let color = (state.color_map)(magnitude);
+            let offset = ((bins - 1 - bin) * history + column) * 4;
+            state.pixels[offset..offset + 4].copy_from_slice(&[
+                (color.b * 255.) as u8,
+                (color.g * 255.) as u8,
+                (color.r * 255.) as u8,
+                (color.a * 255.) as u8,
+            ]);
+        }
+        state.pushed += 1;
+    }
+
+    /// Reset the history to silence.
+    pub fn clear(&self) {
+        let mut state = self.0.lock();
+        state.pixels.fill(0);
+        state.pushed = 0;
+        state.clears += 1;
+    }
+}
+
+/// The default color map: black through red and yellow to white.
+fn heat_color(magnitude: f32) -> Rgba {
+    Rgba {
+        r: (magnitude * 3.).min(1.),
+        g: (magnitude * 3. - 1.).clamp(0., 1.),
+        b: (magnitude * 3. - 2.).clamp(0., 1.),
+        a: 1.,
+    }
+}
+
+/// Create a spectrogram element that renders the given buffer stretched to its bounds.
+pub fn spectrogram(buffer: SpectrogramBuffer) -> Spectrogram {
+    Spectrogram {
+        id: None,
+        buffer,
+        style: StyleRefinement::default(),
+    }
+}
+
+/// An element that renders a [`SpectrogramBuffer`] as a scrolling texture.
+///
+/// The texture holds the history as a ring of columns and is drawn in two parts, split at the
+/// oldest column, so each frame only uploads the rows pushed since the last one. Give the
+/// element an id with [`Spectrogram::id`] so the texture can be reused across frames.
+pub struct Spectrogram {
+    id: Option<ElementId>,
+    buffer: SpectrogramBuffer,
+    style: StyleRefinement,
+}
+
+impl Spectrogram {
+    /// Assign an id to this element, allowing its texture to be cached between frames.
+    pub fn id(mut self, id: impl Into<ElementId>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Bring the texture up to date with the buffer, returning the column that holds the
+    /// oldest row, or `None` if the texture is no longer in the atlas.
+    fn sync(&self, state: &mut SpectrogramState) -> Option<usize> {
+        let buffer = self.buffer.0.lock();
+        if state.clears != buffer.clears {
+            state.clears = buffer.clears;
+            state.uploaded = None;
+        }
+
+        let unsent = state.uploaded.map_or(buffer.history, |uploaded| {
+            buffer.pushed.saturating_sub(uploaded)
+        });
+        for columns in ring_ranges(buffer.pushed, unsent, buffer.history) {
+            let region = Bounds::new(
+                point(DevicePixels(columns.start as i32), DevicePixels(0)),
+                size(
+                    DevicePixels(columns.len() as i32),
+                    DevicePixels(buffer.bins as i32),
+                ),
+            );
+            if !state.texture.update(region, &buffer.columns(columns)) {
+                return None;
+            }
+        }
+        state.uploaded = Some(buffer.pushed);
+
+        Some(buffer.pushed % buffer.history)
+    }
+}
+
+impl IntoElement for Spectrogram {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Styled for Spectrogram {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+struct SpectrogramState {
+    texture: StreamingTexture,
+    /// How many rows had been pushed when the texture was last brought up to date, or `None`
+    /// if every column needs uploading.
+    uploaded: Option<usize>,
+    clears: usize,
+}
+
+impl Element for Spectrogram {
+    type RequestLayoutState = Style;
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        let layout_id = window.request_layout(style.clone(), [], cx);
+        (layout_id, style)
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _style: &mut Style,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) {
+    }
+
+    fn paint(
+        &mut self,
+        id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        style: &mut Style,
+        _prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let (bins, history) = {
+            let buffer = self.buffer.0.lock();
+            (buffer.bins, buffer.history)
+        };
+        let texture_size = size(DevicePixels(history as i32), DevicePixels(bins as i32));
+
+        style.paint(bounds, window, cx, |window, _| {
+            let corner_radii = style
+                .corner_radii
+                .to_pixels(window.rem_size())
+                .clamp_radii_for_quad_size(bounds.size);
+
+            window.with_optional_element_state::<Option<SpectrogramState>, _>(
+                id,
+                |state, window| {
+                    let mut state = state
+                        .flatten()
+                        .flatten()
+                        .filter(|state| state.texture.size() == texture_size)
+                        .or_else(|| {
+                            let texture =
+                                window.create_streaming_texture(texture_size).log_err()?;
+                            Some(SpectrogramState {
+                                texture,
+                                uploaded: None,
+                                clears: 0,
+                            })
+                        });
+
+                    let oldest = state.as_mut().and_then(|state| self.sync(state));
+                    if let Some((state, oldest)) = state.as_ref().zip(oldest) {
+                        for (columns, bounds, corner_radii) in
+                            scroll_parts(bounds, corner_radii, history, oldest)
+                        {
+                            let source = Bounds::new(
+                                point(DevicePixels(columns.start as i32), DevicePixels(0)),
+                                size(
+                                    DevicePixels(columns.len() as i32),
+                                    DevicePixels(bins as i32),
+                                ),
+                            );
+                            window
+                                .paint_streaming_texture(
+                                    bounds,
+                                    corner_radii,
+                                    &state.texture,
+                                    source,
+                                )
+                                .log_err();
+                        }
+                    } else {
+                        // The texture was evicted from the atlas, so create a new one next frame.
+                        state = None;
+                    }
+
+                    if id.is_some() {
+                        ((), Some(state))
+                    } else {
+                        // Without an id there is no state to keep the texture in, so release it
+                        // once the frame using it has been drawn.
+                        window.on_next_frame(move |_, _| drop(state));
+                        ((), None)
+                    }
+                },
+            );
+        });
+    }
+}
+
+/// Split a ring of `history` columns whose oldest column is `oldest` into the parts to draw,
+/// oldest first, along with where to draw each part and which corners it rounds.
+fn scroll_parts(
+    bounds: Bounds<Pixels>,
+    corner_radii: Corners<Pixels>,
+    history: usize,
+    oldest: usize,
+) -> Vec<(Range<usize>, Bounds<Pixels>, Corners<Pixels>)> {
+    if oldest == 0 {
+        return vec![(0..history, bounds, corner_radii)];
+    }
+
+    let split = bounds.left() + bounds.size.width * ((history - oldest) as f32 / history as f32);
+    vec![
+        (
+            oldest..history,
+            Bounds::from_corners(bounds.origin, point(split, bounds.bottom())),
+            Corners {
+                top_right: px(0.),
+                bottom_right: px(0.),
+                ..corner_radii
+            },
+        ),
+        (
+            0..oldest,
+            Bounds::from_corners(point(split, bounds.top()), bounds.bottom_right()),
+            Corners {
+                top_left: px(0.),
+                bottom_left: px(0.),
+                ..corner_radii
+            },
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, FluentBuilder as _, ParentElement as _, Render, TestAppContext, div};
+
+    #[test]
+    fn test_spectrogram_buffer_layout() {
+        let buffer = SpectrogramBuffer::new(2, 3).with_color_map(|magnitude| Rgba {
+            r: magnitude,
+            g: 0.,
+            b: 0.,
+            a: 1.,
+        });
+        buffer.push_row(&[1., 0.]);
+        buffer.push_row(&[0.]);
+
+        let state = buffer.0.lock();
+        // The lowest bin is in the bottom row, and each row fills the next column.
+        assert_eq!(
+            state.columns(0..2),
+            vec![0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 255, 255, 0, 0, 0, 255]
+        );
+        assert_eq!(state.columns(2..3), vec![0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_scroll_parts() {
+        let bounds = Bounds::new(point(px(0.), px(0.)), size(px(40.), px(10.)));
+        let corner_radii = Corners::all(px(2.));
+
+        let parts = scroll_parts(bounds, corner_radii, 4, 0);
+        assert_eq!(parts, vec![(0..4, bounds, corner_radii)]);
+
+        let parts = scroll_parts(bounds, corner_radii, 4, 1);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].0, 1..4);
+        assert_eq!(
+            parts[0].1,
+            Bounds::new(point(px(0.), px(0.)), size(px(30.), px(10.)))
+        );
+        assert_eq!(parts[0].2.top_left, px(2.));
+        assert_eq!(parts[0].2.top_right, px(0.));
+        assert_eq!(parts[1].0, 0..1);
+        assert_eq!(
+            parts[1].1,
+            Bounds::new(point(px(30.), px(0.)), size(px(10.), px(10.)))
+        );
+        assert_eq!(parts[1].2.top_left, px(0.));
+        assert_eq!(parts[1].2.bottom_right, px(2.));
+    }
+
+    struct SpectrogramView {
+        buffer: SpectrogramBuffer,
+        visible: bool,
+    }
+
+    impl Render for SpectrogramView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size(px(200.)).when(self.visible, |this| {
+                this.child(
+                    spectrogram(self.buffer.clone())
+                        .id("spectrogram")
+                        .size(px(100.)),
+                )
+            })
+        }
+    }
+
+    #[crate::test]
+    fn test_spectrogram_scrolls_its_texture(cx: &mut TestAppContext) {
+        let buffer = SpectrogramBuffer::new(8, 4);
+        let (view, cx) = cx.add_window_view(|_, _| SpectrogramView {
+            buffer: buffer.clone(),
+            visible: true,
+        });
+        cx.run_until_parked();
+        let stats = cx.update(|window, _| window.frame_stats());
+        assert_eq!(stats.atlas_textures, 1);
+        assert_eq!(stats.polychrome_sprites, 1);
+
+        for rows in 1..=10 {
+            buffer.push_row(&[0.5; 8]);
+            view.update(cx, |_, cx| cx.notify());
+            cx.run_until_parked();
+            let stats = cx.update(|window, _| window.frame_stats());
+            // The texture is updated in place rather than replaced by a new one.
+            assert_eq!(stats.atlas_textures, 1);
+            assert_eq!(stats.atlas_uploads, 0);
+            let parts = if rows % 4 == 0 { 1 } else { 2 };
+            assert_eq!(stats.polychrome_sprites, parts);
+        }
+
+        view.update(cx, |view, cx| {
+            view.visible = false;
+            cx.notify();
+        });
+        cx.run_until_parked();
+        let stats = cx.update(|window, _| window.frame_stats());
+        assert_eq!(stats.atlas_textures, 0);
+    }
+}
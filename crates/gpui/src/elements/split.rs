@@ -0,0 +1,836 @@
+//! Split panes, which lay their children out side by side ([`h_split`]) or stacked
+//! ([`v_split`]), separated by dividers that resize the panes on either side of them.
+//!
+//! A split's proportions live in a [`SplitState`] kept on your view, which can be saved with a
+//! workspace through [`SplitState::proportions`] and restored with [`SplitState::restore`].
+//! Dividers can be dragged, double-clicked to reset the proportions, and moved with the
+//! keyboard once clicked: the arrow keys move the divider, `home` and `end` move it as far as
+//! the panes allow, and `enter` resets the proportions.
+
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Along, AnyElement, App, AvailableSpace, Axis, Bounds, CursorStyle, DispatchPhase, Element,
+    ElementId, FocusHandle, GlobalElementId, Hitbox, HitboxBehavior, Hsla, InspectorElementId,
+    IntoElement, KeyDownEvent, LayoutId, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
+    Pixels, Style, StyleRefinement, Styled, Window, fill, hsla, px,
+};
+
+const DEFAULT_DIVIDER_SIZE: Pixels = px(4.);
+const DEFAULT_KEYBOARD_STEP: Pixels = px(10.);
+
+/// How far beyond a divider's edges it can be grabbed, so thin dividers are easy to hit.
+const DIVIDER_HIT_SLOP: Pixels = px(3.);
+
+/// The proportions of a split's panes, which can be saved with a workspace and restored with
+/// [`SplitState::restore`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SplitProportions {
+    /// The relative size of each pane. Expanded panes share the split's size in proportion to
+    /// these, and collapsed panes keep theirs for when they're expanded again.
+    pub proportions: Vec<f32>,
+    /// Whether each pane is collapsed.
+    pub collapsed: Vec<bool>,
+}
+
+/// The state of a split: the proportions of its panes, and the divider being dragged. Keep it
+/// on your view and pass it to [`h_split`] or [`v_split`] on each frame.
+#[derive(Clone)]
+pub struct SplitState(Rc<RefCell<SplitStateInner>>);
+
+struct SplitStateInner {
+    proportions: Vec<f32>,
+    default_proportions: Vec<f32>,
+    collapsed: Vec<bool>,
+    last_sizes: Vec<Pixels>,
+    active_divider: usize,
+    drag: Option<SplitDrag>,
+    focus_handle: Option<FocusHandle>,
+}
+
+struct SplitDrag {
+    divider: usize,
+    start_position: Pixels,
+    start_sizes: Vec<Pixels>,
+    start_collapsed: Vec<bool>,
+}
+
+impl SplitState {
+    /// Create the state of a split whose panes start at, and reset to, the given relative sizes.
+    /// Panes beyond the given proportions start at a relative size of 1.
+    pub fn new(default_proportions: impl IntoIterator<Item = f32>) -> Self {
+        let default_proportions = default_proportions
+            .into_iter()
+            .map(|proportion| proportion.max(0.))
+            .collect::<Vec<_>>();
+        Self(Rc::new(RefCell::new(SplitStateInner {
+            proportions: default_proportions.clone(),
+            collapsed: vec![false; default_proportions.len()],
+            default_proportions,
+            last_sizes: Vec::new(),
+            active_divider: 0,
+            drag: None,
+            focus_handle: None,
+        })))
+    }
+
+    /// The current proportions of the panes, for saving them.
+    pub fn proportions(&self) -> SplitProportions {
+        let state = self.0.borrow();
+        SplitProportions {
+            proportions: state.proportions.clone(),
+            collapsed: state.collapsed.clone(),
+        }
+    }
+
+    /// Restore proportions saved with [`SplitState::proportions`].
+    pub fn restore(&self, proportions: SplitProportions) {
+        let mut state = self.0.borrow_mut();
+        state.proportions = proportions.proportions;
+        state.collapsed = proportions.collapsed;
+        state.drag = None;
+    }
+
+    /// Reset the panes to their default proportions, expanding any collapsed panes.
+    pub fn reset(&self) {
+        let mut state = self.0.borrow_mut();
+        state.proportions = state.default_proportions.clone();
+        state
+            .collapsed
+            .iter_mut()
+            .for_each(|collapsed| *collapsed = false);
+        state.drag = None;
+    }
+
+    /// Whether the given pane is collapsed.
+    pub fn is_collapsed(&self, pane: usize) -> bool {
+        self.0
+            .borrow()
+            .collapsed
+            .get(pane)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Collapse or expand the given pane. A collapsed pane takes no space, and the other panes
+    /// share its space until it's expanded again at its previous proportion.
+    pub fn set_collapsed(&self, pane: usize, collapsed: bool) {
+        let mut state = self.0.borrow_mut();
+        state.sync(pane + 1);
+        state.collapsed[pane] = collapsed;
+    }
+
+    /// Collapse the given pane if it's expanded, or expand it if it's collapsed.
+    pub fn toggle_collapsed(&self, pane: usize) {
+        self.set_collapsed(pane, !self.is_collapsed(pane));
+    }
+
+    /// The size of each pane along the split's axis when it was last laid out.
+    pub fn pane_sizes(&self) -> Vec<Pixels> {
+        self.0.borrow().last_sizes.clone()
+    }
+
+    /// Whether a divider is being dragged.
+    pub fn is_dragging(&self) -> bool {
+        self.0.borrow().drag.is_some()
+    }
+
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.0
+            .borrow_mut()
+            .focus_handle
+            .get_or_insert_with(|| cx.focus_handle())
+            .clone()
+    }
+}
+
+impl SplitStateInner {
+    /// Grow the per-pane state to the number of panes being rendered.
+    fn sync(&mut self, pane_count: usize) {
+        if self.proportions.len() < pane_count {
+            self.proportions.resize(pane_count, 1.);
+        }
+        if self.default_proportions.len() < pane_count {
+            self.default_proportions.resize(pane_count, 1.);
+        }
+        if self.collapsed.len() < pane_count {
+            self.collapsed.resize(pane_count, false);
+        }
+    }
+
+    /// Move the given divider by `delta` from the given sizes and collapsed states.
+    fn move_divider(
+        &mut self,
+        divider: usize,
+        delta: Pixels,
+        sizes: &[Pixels],
+        collapsed: &[bool],
+        constraints: &[PaneConstraints],
+    ) {
+        let (leading, trailing) = (divider, divider + 1);
+        let (Some(leading_pane), Some(trailing_pane)) =
+            (constraints.get(leading), constraints.get(trailing))
+        else {
+            return;
+        };
+        let total = sizes[leading] + sizes[trailing];
+        let mut leading_size = leading_pane.resolve(sizes[leading] + delta);
+        let trailing_size = trailing_pane.resolve(total - leading_size);
+        if leading_size + trailing_size != total && !leading_pane.collapses_at(leading_size) {
+            leading_size = leading_pane.resolve(total - trailing_size);
+        }
+
+        let mut new_sizes = sizes.to_vec();
+        new_sizes[leading] = leading_size;
+        new_sizes[trailing] = trailing_size;
+        self.collapsed = collapsed.to_vec();
+        self.collapsed[leading] = leading_pane.collapses_at(leading_size);
+        self.collapsed[trailing] = trailing_pane.collapses_at(trailing_size);
+
+        // Keep the total weight of the expanded panes, so collapsed panes come back at the same
+        // share of the split.
+        let expanded = || (0..new_sizes.len()).filter(|ix| !self.collapsed[*ix]);
+        let total_weight = expanded().map(|ix| self.proportions[ix]).sum::<f32>();
+        let total_weight = if total_weight > 0. { total_weight } else { 1. };
+        let total_size = expanded()
+            .map(|ix| new_sizes[ix])
+            .fold(Pixels::ZERO, |total, size| total + size);
+        if total_size > Pixels::ZERO {
+            for ix in expanded().collect::<Vec<_>>() {
+                self.proportions[ix] = total_weight * (new_sizes[ix] / total_size);
+            }
+        }
+    }
+}
+
+/// The size limits of a pane along the split's axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PaneConstraints {
+    min_size: Pixels,
+    max_size: Option<Pixels>,
+    collapsible: bool,
+}
+
+impl PaneConstraints {
+    /// Whether a pane resized to the given size collapses, which happens once a collapsible
+    /// pane is dragged below half of its minimum size.
+    fn collapses_at(&self, size: Pixels) -> bool {
+        self.collapsible && size < self.min_size / 2.
+    }
+
+    fn resolve(&self, size: Pixels) -> Pixels {
+        if self.collapses_at(size) {
+            Pixels::ZERO
+        } else {
+            self.clamp(size)
+        }
+    }
+
+    fn clamp(&self, size: Pixels) -> Pixels {
+        size.max(self.min_size)
+            .min(self.max_size.unwrap_or(Pixels::MAX).max(self.min_size))
+    }
+}
+
+/// Share `available` between the panes in proportion to their weights, respecting their
+/// constraints. Collapsed panes get no space.
+fn layout_panes(
+    proportions: &[f32],
+    collapsed: &[bool],
+    constraints: &[PaneConstraints],
+    available: Pixels,
+) -> Vec<Pixels> {
+    let mut sizes = vec![Pixels::ZERO; constraints.len()];
+    let mut resolved = collapsed.to_vec();
+    let mut remaining = available;
+    loop {
+        let unresolved = (0..sizes.len())
+            .filter(|ix| !resolved[*ix])
+            .collect::<Vec<_>>();
+        if unresolved.is_empty() {
+            break;
+        }
+        let total_weight = unresolved.iter().map(|ix| proportions[*ix]).sum::<f32>();
+        let share = |ix: usize| {
+            if total_weight > 0. {
+                remaining * (proportions[ix] / total_weight)
+            } else {
+                remaining / unresolved.len() as f32
+            }
+        };
+
+        // Panes whose share violates their constraints get their limit, and the others share
+        // what's left.
+        let mut clamped_any = false;
+        for &ix in &unresolved {
+            let size = share(ix).max(Pixels::ZERO);
+            let clamped = constraints[ix].clamp(size);
+            if clamped != size {
+                sizes[ix] = clamped;
+                resolved[ix] = true;
+                clamped_any = true;
+            }
+        }
+        if clamped_any {
+            remaining = available
+                - (0..sizes.len())
+                    .filter(|ix| resolved[*ix])
+                    .map(|ix| sizes[ix])
+                    .fold(Pixels::ZERO, |total, size| total + size);
+        } else {
+            for &ix in &unresolved {
+                sizes[ix] = share(ix).max(Pixels::ZERO);
+            }
+            break;
+        }
+    }
+    sizes
+}
+
+/// Create a split that lays its panes out side by side, with vertical dividers between them.
+pub fn h_split(state: &SplitState) -> Split {
+    Split::new(Axis::Horizontal, state)
+}
+
+/// Create a split that stacks its panes, with horizontal dividers between them.
+pub fn v_split(state: &SplitState) -> Split {
+    Split::new(Axis::Vertical, state)
+}
+
+/// A pane of a [`Split`] with size limits, created with [`split_pane`].
+pub struct SplitPane {
+    child: AnyElement,
+    constraints: PaneConstraints,
+}
+
+/// Wrap an element in a pane, to limit its size within a [`Split`].
+pub fn split_pane(child: impl IntoElement) -> SplitPane {
+    SplitPane {
+        child: child.into_any_element(),
+        constraints: PaneConstraints {
+            min_size: Pixels::ZERO,
+            max_size: None,
+            collapsible: false,
+        },
+    }
+}
+
+impl SplitPane {
+    /// The smallest size the pane can be resized to along the split's axis.
+    pub fn min_size(mut self, min_size: Pixels) -> Self {
+        self.constraints.min_size = min_size.max(Pixels::ZERO);
+        self
+    }
+
+    /// The largest size the pane can be resized to along the split's axis.
+    pub fn max_size(mut self, max_size: Pixels) -> Self {
+        self.constraints.max_size = Some(max_size);
+        self
+    }
+
+    /// Allow the pane to be collapsed by dragging its divider below half of its minimum size,
+    /// and expanded by dragging it back. Panes can always be collapsed with
+    /// [`SplitState::set_collapsed`].
+    pub fn collapsible(mut self) -> Self {
+        self.constraints.collapsible = true;
+        self
+    }
+}
+
+/// An element that lays out panes separated by draggable dividers, created with [`h_split`] or
+/// [`v_split`].
+pub struct Split {
+    axis: Axis,
+    state: SplitState,
+    panes: Vec<SplitPane>,
+    style: StyleRefinement,
+    divider_size: Pixels,
+    divider_color: Hsla,
+    active_divider_color: Hsla,
+    keyboard_step: Pixels,
+    on_resize: Option<Rc<dyn Fn(&SplitProportions, &mut Window, &mut App)>>,
+}
+
+impl Split {
+    fn new(axis: Axis, state: &SplitState) -> Self {
+        Self {
+            axis,
+            state: state.clone(),
+            panes: Vec::new(),
+            style: StyleRefinement::default(),
+            divider_size: DEFAULT_DIVIDER_SIZE,
+            divider_color: hsla(0., 0., 0.5, 0.3),
+            active_divider_color: hsla(0.58, 0.8, 0.6, 1.),
+            keyboard_step: DEFAULT_KEYBOARD_STEP,
+            on_resize: None,
+        }
+    }
+
+    /// Add a pane without size limits.
+    pub fn child(self, child: impl IntoElement) -> Self {
+        self.pane(split_pane(child))
+    }
+
+    /// Add a pane created with [`split_pane`].
+    pub fn pane(mut self, pane: SplitPane) -> Self {
+        self.panes.push(pane);
+        self
+    }
+
+    /// The thickness of the dividers. Defaults to 4px.
+    pub fn divider_size(mut self, divider_size: Pixels) -> Self {
+        self.divider_size = divider_size;
+        self
+    }
+
+    /// The color of the dividers.
+    pub fn divider_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.divider_color = color.into();
+        self
+    }
+
+    /// The color of a divider that's hovered, dragged or moved with the keyboard.
+    pub fn active_divider_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.active_divider_color = color.into();
+        self
+    }
+
+    /// How far the arrow keys move a divider. Defaults to 10px.
+    pub fn keyboard_step(mut self, step: Pixels) -> Self {
+        self.keyboard_step = step;
+        self
+    }
+
+    /// Called with the new proportions whenever the user resizes, collapses, expands or resets
+    /// the panes, such as to save them.
+    pub fn on_resize(
+        mut self,
+        listener: impl Fn(&SplitProportions, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_resize = Some(Rc::new(listener));
+        self
+    }
+
+    fn constraints(&self) -> Vec<PaneConstraints> {
+        self.panes.iter().map(|pane| pane.constraints).collect()
+    }
+}
+
+impl Styled for Split {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl IntoElement for Split {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+/// The state of a [`Split`] after prepaint.
+pub struct SplitPrepaintState {
+    pane_sizes: Vec<Pixels>,
+    dividers: Vec<(Bounds<Pixels>, Hitbox)>,
+    focus_handle: FocusHandle,
+}
+
+impl Element for Split {
+    type RequestLayoutState = ();
+    type PrepaintState = SplitPrepaintState;
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        (window.request_layout(style, None, cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        let axis = self.axis;
+        let constraints = self.constraints();
+        let divider_count = self.panes.len().saturating_sub(1);
+        let available =
+            (bounds.size.along(axis) - self.divider_size * divider_count as f32).max(Pixels::ZERO);
+        let pane_sizes = {
+            let mut state = self.state.0.borrow_mut();
+            state.sync(self.panes.len());
+            let sizes = layout_panes(
+                &state.proportions,
+                &state.collapsed,
+                &constraints,
+                available,
+            );
+            state.last_sizes = sizes.clone();
+            sizes
+        };
+
+        let mut offset = Pixels::ZERO;
+        let mut dividers = Vec::with_capacity(divider_count);
+        for (ix, pane) in self.panes.iter_mut().enumerate() {
+            let pane_size = pane_sizes[ix];
+            if pane_size > Pixels::ZERO {
+                let origin = bounds.origin.apply_along(axis, |origin| origin + offset);
+                let size = bounds.size.apply_along(axis, |_| pane_size);
+                pane.child
+                    .layout_as_root(size.map(AvailableSpace::Definite), window, cx);
+                pane.child.prepaint_at(origin, window, cx);
+            }
+            offset += pane_size;
+
+            if ix < divider_count {
+                let divider_bounds = Bounds::new(
+                    bounds.origin.apply_along(axis, |origin| origin + offset),
+                    bounds.size.apply_along(axis, |_| self.divider_size),
+                );
+                let hitbox_bounds = Bounds::new(
+                    divider_bounds
+                        .origin
+                        .apply_along(axis, |origin| origin - DIVIDER_HIT_SLOP),
+                    divider_bounds
+                        .size
+                        .apply_along(axis, |size| size + DIVIDER_HIT_SLOP * 2.),
+                );
+                let hitbox = window.insert_hitbox(hitbox_bounds, HitboxBehavior::Normal);
+                dividers.push((divider_bounds, hitbox));
+                offset += self.divider_size;
+            }
+        }
+
+        let focus_handle = self.state.focus_handle(cx);
+        window.set_focus_handle(&focus_handle, cx);
+        SplitPrepaintState {
+            pane_sizes,
+            dividers,
+            focus_handle,
+        }
+    }
+
+    fn paint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        _: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        for (pane, size) in self.panes.iter_mut().zip(&prepaint.pane_sizes) {
+            if *size > Pixels::ZERO {
+                pane.child.paint(window, cx);
+            }
+        }
+
+        let axis = self.axis;
+        let cursor_style = match axis {
+            Axis::Horizontal => CursorStyle::ResizeLeftRight,
+            Axis::Vertical => CursorStyle::ResizeUpDown,
+        };
+        let focused = prepaint.focus_handle.is_focused(window);
+        let (dragged_divider, active_divider) = {
+            let state = self.state.0.borrow();
+            (
+                state.drag.as_ref().map(|drag| drag.divider),
+                state.active_divider,
+            )
+        };
+        for (ix, (bounds, hitbox)) in prepaint.dividers.iter().enumerate() {
+            let active = dragged_divider == Some(ix)
+                || hitbox.is_hovered(window)
+                || (focused && active_divider == ix);
+            let color = if active {
+                self.active_divider_color
+            } else {
+                self.divider_color
+            };
+            window.paint_quad(fill(*bounds, color));
+            window.set_cursor_style(cursor_style, hitbox);
+        }
+        if dragged_divider.is_some() {
+            window.set_window_cursor_style(cursor_style);
+        }
+
+        let constraints: Rc<[PaneConstraints]> = self.constraints().into();
+        let hitboxes = prepaint
+            .dividers
+            .iter()
+            .map(|(_, hitbox)| hitbox.clone())
+            .collect::<Vec<_>>();
+        let resized = {
+            let state = self.state.clone();
+            let on_resize = self.on_resize.clone();
+            move |window: &mut Window, cx: &mut App| {
+                if let Some(on_resize) = on_resize.as_ref() {
+                    on_resize(&state.proportions(), window, cx);
+                }
+                window.refresh();
+            }
+        };
+
+        window.on_mouse_event({
+            let state = self.state.clone();
+            let focus_handle = prepaint.focus_handle.clone();
+            let resized = resized.clone();
+            move |event: &MouseDownEvent, phase, window, cx| {
+                if phase != DispatchPhase::Bubble || event.button != MouseButton::Left {
+                    return;
+                }
+                let Some(divider) = hitboxes.iter().position(|hitbox| hitbox.is_hovered(window))
+                else {
+                    return;
+                };
+                cx.stop_propagation();
+                window.focus(&focus_handle);
+                if event.click_count == 2 {
+                    state.reset();
+                    state.0.borrow_mut().active_divider = divider;
+                    resized(window, cx);
+                    return;
+                }
+                let mut state = state.0.borrow_mut();
+                state.active_divider = divider;
+                state.drag = Some(SplitDrag {
+                    divider,
+                    start_position: event.position.along(axis),
+                    start_sizes: state.last_sizes.clone(),
+                    start_collapsed: state.collapsed.clone(),
+                });
+                window.refresh();
+            }
+        });
+
+        window.on_mouse_event({
+            let state = self.state.clone();
+            let constraints = constraints.clone();
+            let resized = resized.clone();
+            move |event: &MouseMoveEvent, phase, window, cx| {
+                if phase != DispatchPhase::Capture {
+                    return;
+                }
+                {
+                    let mut state = state.0.borrow_mut();
+                    let Some(drag) = state.drag.take() else {
+                        return;
+                    };
+                    let delta = event.position.along(axis) - drag.start_position;
+                    state.move_divider(
+                        drag.divider,
+                        delta,
+                        &drag.start_sizes,
+                        &drag.start_collapsed,
+                        &constraints,
+                    );
+                    state.drag = Some(drag);
+                }
+                resized(window, cx);
+            }
+        });
+
+        window.on_mouse_event({
+            let state = self.state.clone();
+            move |_: &MouseUpEvent, phase, window, _| {
+                if phase == DispatchPhase::Capture && state.0.borrow_mut().drag.take().is_some() {
+                    window.refresh();
+                }
+            }
+        });
+
+        window.on_key_event({
+            let state = self.state.clone();
+            let focus_handle = prepaint.focus_handle.clone();
+            let keyboard_step = self.keyboard_step;
+            move |event: &KeyDownEvent, phase, window, cx| {
+                if phase != DispatchPhase::Bubble || !focus_handle.is_focused(window) {
+                    return;
+                }
+                let (backward, forward) = match axis {
+                    Axis::Horizontal => ("left", "right"),
+                    Axis::Vertical => ("up", "down"),
+                };
+                let key = event.keystroke.key.as_str();
+                let delta = match key {
+                    _ if key == backward => -keyboard_step,
+                    _ if key == forward => keyboard_step,
+                    "home" => Pixels::MIN,
+                    "end" => Pixels::MAX,
+                    "enter" => {
+                        state.reset();
+                        cx.stop_propagation();
+                        resized(window, cx);
+                        return;
+                    }
+                    _ => return,
+                };
+                {
+                    let mut state = state.0.borrow_mut();
+                    let divider = state.active_divider;
+                    let sizes = state.last_sizes.clone();
+                    let collapsed = state.collapsed.clone();
+                    state.move_divider(divider, delta, &sizes, &collapsed, &constraints);
+                }
+                cx.stop_propagation();
+                resized(window, cx);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Context, Modifiers, ParentElement as _, Render, TestAppContext, VisualTestContext, div,
+        point,
+    };
+
+    fn pane(min_size: f32, max_size: Option<f32>, collapsible: bool) -> PaneConstraints {
+        PaneConstraints {
+            min_size: px(min_size),
+            max_size: max_size.map(px),
+            collapsible,
+        }
+    }
+
+    #[test]
+    fn test_layout_panes() {
+        let constraints = [pane(0., None, false); 3];
+        assert_eq!(
+            layout_panes(&[1., 2., 1.], &[false; 3], &constraints, px(400.)),
+            [px(100.), px(200.), px(100.)]
+        );
+
+        // Collapsed panes give their space to the others.
+        assert_eq!(
+            layout_panes(&[1., 2., 1.], &[false, true, false], &constraints, px(400.)),
+            [px(200.), px(0.), px(200.)]
+        );
+
+        // Panes that would be too small or too large get their limits.
+        let constraints = [
+            pane(150., None, false),
+            pane(0., Some(100.), false),
+            pane(0., None, false),
+        ];
+        assert_eq!(
+            layout_panes(&[1., 2., 1.], &[false; 3], &constraints, px(400.)),
+            [px(150.), px(100.), px(150.)]
+        );
+    }
+
+    #[test]
+    fn test_move_divider() {
+        let state = SplitState::new([1., 1.]);
+        let constraints = [pane(100., None, true), pane(50., Some(300.), false)];
+        let sizes = [px(200.), px(200.)];
+        let mut inner = state.0.borrow_mut();
+
+        inner.move_divider(0, px(50.), &sizes, &[false; 2], &constraints);
+        assert_eq!(inner.proportions, [1.25, 0.75]);
+
+        // The trailing pane can't grow past its maximum size.
+        inner.move_divider(0, px(-150.), &sizes, &[false; 2], &constraints);
+        assert_eq!(inner.proportions, [0.5, 1.5]);
+        assert_eq!(inner.collapsed, [false, false]);
+
+        // Dragging the collapsible pane below half its minimum size collapses it, keeping its
+        // proportion for when it's expanded.
+        inner.move_divider(0, px(-160.), &sizes, &[false; 2], &constraints);
+        assert_eq!(inner.collapsed, [true, false]);
+        assert_eq!(inner.proportions[0], 0.5);
+    }
+
+    fn rounded_sizes(split: &SplitState) -> Vec<Pixels> {
+        split.pane_sizes().iter().map(Pixels::round).collect()
+    }
+
+    struct Editor {
+        split: SplitState,
+        saved: Rc<RefCell<Option<SplitProportions>>>,
+    }
+
+    impl Render for Editor {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let saved = self.saved.clone();
+            div().size(px(404.)).child(
+                h_split(&self.split)
+                    .size_full()
+                    .pane(split_pane(div()).min_size(px(100.)))
+                    .child(div())
+                    .on_resize(move |proportions, _, _| {
+                        saved.replace(Some(proportions.clone()));
+                    }),
+            )
+        }
+    }
+
+    #[crate::test]
+    fn test_resize_with_mouse_and_keyboard(cx: &mut TestAppContext) {
+        let split = SplitState::new([1., 1.]);
+        let saved = Rc::new(RefCell::new(None));
+        let (_, cx) = cx.add_window_view(|_, _| Editor {
+            split: split.clone(),
+            saved: saved.clone(),
+        });
+        cx.run_until_parked();
+        assert_eq!(rounded_sizes(&split), [px(200.), px(200.)]);
+
+        let drag = |cx: &mut VisualTestContext, from: f32, to: f32| {
+            cx.simulate_mouse_down(
+                point(px(from), px(10.)),
+                MouseButton::Left,
+                Modifiers::none(),
+            );
+            cx.simulate_mouse_move(point(px(to), px(10.)), MouseButton::Left, Modifiers::none());
+            cx.simulate_mouse_up(point(px(to), px(10.)), MouseButton::Left, Modifiers::none());
+            cx.run_until_parked();
+        };
+
+        // Drag the divider right, then left past the first pane's minimum size.
+        drag(cx, 202., 302.);
+        assert_eq!(rounded_sizes(&split), [px(300.), px(100.)]);
+        drag(cx, 302., 2.);
+        assert_eq!(rounded_sizes(&split), [px(100.), px(300.)]);
+        assert_eq!(saved.borrow().as_ref(), Some(&split.proportions()));
+
+        // Once clicked, the divider moves with the arrow keys.
+        cx.simulate_keystrokes("right right");
+        assert_eq!(rounded_sizes(&split), [px(120.), px(280.)]);
+
+        cx.simulate_keystrokes("enter");
+        assert_eq!(rounded_sizes(&split), [px(200.), px(200.)]);
+
+        // The proportions round-trip through serialization.
+        split.set_collapsed(1, true);
+        let json = serde_json::to_string(&split.proportions()).unwrap();
+        let restored = SplitState::new([1., 1.]);
+        restored.restore(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.proportions(), split.proportions());
+        assert!(restored.is_collapsed(1));
+    }
+}
@@ -319,6 +319,44 @@ struct TextLayoutInner {
     wrap_width: Option<Pixels>,
     size: Option<Size<Pixels>>,
     bounds: Option<Bounds<Pixels>>,
+    truncated: bool,
+}
+
+/// How wide the fade out of text with [`TextOverflow::Fade`] is, relative to its font size.
+const FADE_WIDTH_IN_EMS: f32 = 2.;
+
+/// Drop the lines past the first `max_lines` visual lines, returning whether any text was cut
+/// off, either by dropping lines or because the last line was clamped while shaping.
+fn clamp_lines(lines: &mut SmallVec<[WrappedLine; 1]>, max_lines: usize) -> bool {
+    let mut visual_lines = 0;
+    let Some(last_ix) = lines.iter().position(|line| {
+        visual_lines += line.wrap_boundaries.len() + 1;
+        visual_lines >= max_lines
+    }) else {
+        return false;
+    };
+    let truncated = last_ix + 1 < lines.len() || overflows_last_row(&lines[last_ix]);
+    lines.truncate(last_ix + 1);
+    truncated
+}
+
+/// Whether the last visual line of a wrapped line is wider than its wrap width, which happens
+/// when shaping stopped wrapping it to clamp the number of lines.
+fn overflows_last_row(line: &WrappedLine) -> bool {
+    let Some(wrap_width) = line.layout.wrap_width else {
+        return false;
+    };
+    let layout = &line.layout.unwrapped_layout;
+    let row_start = line
+        .layout
+        .wrap_boundaries
+        .last()
+        .map_or(Pixels::ZERO, |boundary| {
+            layout.runs[boundary.run_ix].glyphs[boundary.glyph_ix]
+                .position
+                .x
+        });
+    layout.width - row_start > wrap_width
 }
 
 impl TextLayout {
@@ -340,7 +378,12 @@ impl TextLayout {
         } else {
             vec![text_style.to_run(text.len())]
         };
-        window.request_measured_layout(Default::default(), {
+        let font_id = window.text_system().resolve_font(&text_style.font());
+        let baseline = window
+            .text_system()
+            .baseline_offset(font_id, font_size, line_height);
+
+        let layout_id = window.request_measured_layout(Default::default(), {
             let element_state = self.clone();
 
             move |known_dimensions, available_space, window, cx| {
@@ -353,22 +396,15 @@ impl TextLayout {
                     None
                 };
 
-                let (truncate_width, truncation_suffix) =
-                    if let Some(text_overflow) = text_style.text_overflow.clone() {
-                        let width = known_dimensions.width.or(match available_space.width {
-                            crate::AvailableSpace::Definite(x) => match text_style.line_clamp {
-                                Some(max_lines) => Some(x * max_lines),
-                                None => Some(x),
-                            },
-                            _ => None,
-                        });
-
-                        match text_overflow {
-                            TextOverflow::Truncate(s) => (width, s),
-                        }
-                    } else {
-                        (None, "".into())
-                    };
+                let truncate_width = text_style.text_overflow.as_ref().and_then(|_| {
+                    known_dimensions.width.or(match available_space.width {
+                        crate::AvailableSpace::Definite(x) => match text_style.line_clamp {
+                            Some(max_lines) => Some(x * max_lines),
+                            None => Some(x),
+                        },
+                        _ => None,
+                    })
+                });
 
                 if let Some(text_layout) = element_state.0.borrow().as_ref()
                     && text_layout.size.is_some()
@@ -378,19 +414,30 @@ impl TextLayout {
                 }
 
                 let mut line_wrapper = cx.text_system().line_wrapper(text_style.font(), font_size);
-                let (text, runs) = if let Some(truncate_width) = truncate_width {
-                    line_wrapper.truncate_line(
+                let (text, runs) = match (truncate_width, &text_style.text_overflow) {
+                    (Some(truncate_width), Some(TextOverflow::Truncate(suffix))) => {
+                        line_wrapper.truncate_line(text.clone(), truncate_width, suffix, &runs)
+                    }
+                    (Some(truncate_width), Some(TextOverflow::TruncateMiddle(separator))) => {
+                        line_wrapper.truncate_line_middle(
+                            text.clone(),
+                            truncate_width,
+                            separator,
+                            &runs,
+                        )
+                    }
+                    (Some(truncate_width), Some(TextOverflow::Fade)) => line_wrapper.fade_line(
                         text.clone(),
                         truncate_width,
-                        &truncation_suffix,
+                        font_size * FADE_WIDTH_IN_EMS,
                         &runs,
-                    )
-                } else {
-                    (text.clone(), Cow::Borrowed(&*runs))
+                    ),
+                    _ => (text.clone(), Cow::Borrowed(&*runs)),
                 };
-                let len = text.len();
+                let mut truncated = matches!(runs, Cow::Owned(_));
+                let mut len = text.len();
 
-                let Some(lines) = window
+                let Some(mut lines) = window
                     .text_system()
                     .shape_text(
                         text,
@@ -408,10 +455,17 @@ impl TextLayout {
                         wrap_width,
                         size: Some(Size::default()),
                         bounds: None,
+                        truncated: false,
                     });
                     return Size::default();
                 };
 
+                if let Some(max_lines) = text_style.line_clamp {
+                    truncated |= clamp_lines(&mut lines, max_lines);
+                    len = lines.iter().map(|line| line.len()).sum::<usize>()
+                        + lines.len().saturating_sub(1);
+                }
+
                 let mut size: Size<Pixels> = Size::default();
                 for line in &lines {
                     let line_size = line.size(line_height);
@@ -426,11 +480,14 @@ impl TextLayout {
                     wrap_width,
                     size: Some(size),
                     bounds: None,
+                    truncated,
                 });
 
                 size
             }
-        })
+        });
+        window.set_layout_baseline(layout_id, baseline);
+        layout_id
     }
 
     fn prepaint(&self, bounds: Bounds<Pixels>, text: &str) {
@@ -579,6 +636,15 @@ impl TextLayout {
         self.0.borrow().as_ref().unwrap().bounds.unwrap()
     }
 
+    /// Whether the text was cut off to fit when it was laid out, by its [`TextOverflow`] or its
+    /// line clamp. Useful for only showing the full text in a tooltip when it doesn't fit.
+    pub fn is_truncated(&self) -> bool {
+        self.0
+            .borrow()
+            .as_ref()
+            .is_some_and(|element_state| element_state.truncated)
+    }
+
     /// The line height for this layout.
     pub fn line_height(&self) -> Pixels {
         self.0.borrow().as_ref().unwrap().line_height
@@ -692,6 +758,8 @@ impl InteractiveText {
     }
 
     /// tooltip lets you specify a tooltip for a given character index in the string.
+    /// Return `None` for no tooltip, such as to only show the full text when
+    /// [`TextLayout::is_truncated`].
     pub fn tooltip(
         mut self,
         builder: impl Fn(usize, &mut Window, &mut App) -> Option<AnyView> + 'static,
@@ -912,3 +980,49 @@ impl IntoElement for InteractiveText {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, ParentElement as _, Render, Styled as _, TestAppContext, div, px};
+
+    struct Labels(Rc<RefCell<Vec<TextLayout>>>);
+
+    impl Render for Labels {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let mut layouts = self.0.borrow_mut();
+            layouts.clear();
+            let mut label = |text: &str| {
+                let text = StyledText::new(text.to_string());
+                layouts.push(text.layout().clone());
+                text
+            };
+            let long = "lorem ipsum ".repeat(20);
+            div()
+                .flex()
+                .flex_col()
+                .w(px(100.))
+                .child(div().truncate().child(label("a")))
+                .child(div().truncate().child(label(&long)))
+                .child(div().truncate_middle().child(label(&long)))
+                .child(div().truncate_fade().child(label(&long)))
+                .child(div().line_clamp(2).child(label("a\nb")))
+                .child(div().line_clamp(2).child(label("a\nb\nc")))
+                .child(div().line_clamp(2).child(label(&long)))
+        }
+    }
+
+    #[crate::test]
+    fn test_text_reports_truncation(cx: &mut TestAppContext) {
+        let layouts = Rc::new(RefCell::new(Vec::new()));
+        cx.add_window_view(|_, _| Labels(layouts.clone()));
+        cx.run_until_parked();
+
+        let truncated = layouts
+            .borrow()
+            .iter()
+            .map(TextLayout::is_truncated)
+            .collect::<Vec<_>>();
+        assert_eq!(truncated, [false, true, true, true, false, true, true]);
+    }
+}
@@ -0,0 +1,323 @@
+use std::{rc::Rc, time::Duration, time::Instant};
+
+use refineable::Refineable as _;
+
+use crate::{
+    AnyElement, AnyView, App, Bounds, DispatchPhase, Element, ElementId, GlobalElementId, Hitbox,
+    HitboxBehavior, InspectorElementId, IntoElement, LayoutId, MouseButton, MouseDownEvent,
+    PaintOperation, Pixels, Point, Style, StyleRefinement, Styled, Task, Window,
+};
+
+/// How long a thumbnail keeps showing its copy of the source view before painting it again, by
+/// default.
+pub const DEFAULT_THUMBNAIL_REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Create an element that renders a miniature copy of another view, for minimaps, window
+/// switchers and preset preview grids.
+///
+/// The view is laid out in the thumbnail's bounds divided by `scale` and everything it paints is
+/// scaled down to fit, including sizes given in absolute pixels. The copy is captured and
+/// replayed on subsequent frames, and only painted again once its refresh interval has elapsed,
+/// so a thumbnail of a view that changes every frame doesn't cost a full render every frame.
+///
+/// The copy does not respond to the mouse: the thumbnail occludes its contents, and clicks
+/// can be handled with [`Thumbnail::on_click`] instead.
+pub fn thumbnail_of(view: impl Into<AnyView>, scale: f32) -> Thumbnail {
+    Thumbnail {
+        view: view.into(),
+        scale: scale.max(f32::EPSILON),
+        refresh_interval: DEFAULT_THUMBNAIL_REFRESH_INTERVAL,
+        style: StyleRefinement::default(),
+        on_click: None,
+    }
+}
+
+/// A scaled-down rendering of another view. See [`thumbnail_of`].
+pub struct Thumbnail {
+    view: AnyView,
+    scale: f32,
+    refresh_interval: Duration,
+    style: StyleRefinement,
+    on_click: Option<Rc<dyn Fn(Point<Pixels>, &mut Window, &mut App)>>,
+}
+
+impl Thumbnail {
+    /// Set how long the copy is shown before the source view is painted again. Defaults to
+    /// [`DEFAULT_THUMBNAIL_REFRESH_INTERVAL`].
+    pub fn refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    /// Register a callback to be invoked when the thumbnail is clicked. The callback receives
+    /// the clicked position in the source view's unscaled coordinates, relative to its origin,
+    /// which is useful for minimap-style navigation.
+    pub fn on_click(
+        mut self,
+        listener: impl Fn(Point<Pixels>, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_click = Some(Rc::new(listener));
+        self
+    }
+}
+
+impl IntoElement for Thumbnail {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Styled for Thumbnail {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+/// Prepaint state for a [`Thumbnail`].
+pub struct ThumbnailPrepaint {
+    hitbox: Hitbox,
+    /// The source view, when its copy is painted again this frame.
+    child: Option<AnyElement>,
+}
+
+#[derive(Default)]
+struct ThumbnailState {
+    capture: Option<ThumbnailCapture>,
+    refresh: Option<Task<()>>,
+}
+
+/// The scaled paint operations of the source view, which are positioned absolutely and so can
+/// only be replayed while the thumbnail stays where it was captured.
+struct ThumbnailCapture {
+    bounds: Bounds<Pixels>,
+    scale: f32,
+    painted_at: Instant,
+    operations: Vec<PaintOperation>,
+}
+
+impl Element for Thumbnail {
+    type RequestLayoutState = Style;
+    type PrepaintState = ThumbnailPrepaint;
+
+    fn id(&self) -> Option<ElementId> {
+        // Distinguishes the copy from the source view when both are rendered in the same
+        // parent, since views are otherwise identified by their entity id alone.
+        Some(ElementId::NamedInteger(
+            "thumbnail".into(),
+            self.view.entity_id().as_u64(),
+        ))
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        let layout_id = window.request_layout(style.clone(), None, cx);
+        (layout_id, style)
+    }
+
+    fn prepaint(
+        &mut self,
+        id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _style: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> ThumbnailPrepaint {
+        let now = cx.background_executor().now();
+        let repaint =
+            window.with_optional_element_state::<ThumbnailState, _>(id, |state, window| {
+                let mut state = state.flatten().unwrap_or_default();
+                let refresh_at = state.capture.as_ref().and_then(|capture| {
+                    (capture.bounds == bounds && capture.scale == self.scale)
+                        .then(|| capture.painted_at + self.refresh_interval)
+                });
+                match refresh_at {
+                    Some(refresh_at) if now < refresh_at => {
+                        // The source view isn't rendered while its copy is replayed, so its
+                        // notifications can't invalidate this frame. Paint it again once the
+                        // interval elapses in case it changed in the meantime.
+                        if state.refresh.is_none() {
+                            let current_view = window.current_view();
+                            state.refresh = Some(window.spawn(cx, async move |cx| {
+                                cx.background_executor().timer(refresh_at - now).await;
+                                cx.update(move |_, cx| cx.notify(current_view)).ok();
+                            }));
+                        }
+                        (false, Some(state))
+                    }
+                    _ => {
+                        state.refresh = None;
+                        (true, Some(state))
+                    }
+                }
+            });
+
+        let child = repaint.then(|| {
+            let mut child = self.view.clone().into_any_element();
+            window.prepaint_scaled(bounds, self.scale, |window| {
+                child.prepaint_as_root(
+                    bounds.origin,
+                    (bounds.size / self.scale).into(),
+                    window,
+                    cx,
+                );
+            });
+            child
+        });
+        // Inserted after the child so that it sits on top of anything else the copy inserts.
+        let hitbox = window.insert_hitbox(bounds, HitboxBehavior::BlockMouse);
+        ThumbnailPrepaint { hitbox, child }
+    }
+
+    fn paint(
+        &mut self,
+        id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        style: &mut Self::RequestLayoutState,
+        prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        style.paint(bounds, window, cx, |window, cx| {
+            window.with_optional_element_state::<ThumbnailState, _>(id, |state, window| {
+                let mut state = state.flatten().unwrap_or_default();
+                if let Some(child) = prepaint.child.as_mut() {
+                    let operations =
+                        window.paint_scaled(bounds, self.scale, |window| child.paint(window, cx));
+                    state.capture = Some(ThumbnailCapture {
+                        bounds,
+                        scale: self.scale,
+                        painted_at: cx.background_executor().now(),
+                        operations,
+                    });
+                } else if let Some(capture) = &state.capture {
+                    window.replay_scaled(&capture.operations);
+                }
+                ((), Some(state))
+            });
+        });
+
+        if let Some(on_click) = self.on_click.clone() {
+            let hitbox = prepaint.hitbox.clone();
+            let scale = self.scale;
+            window.on_mouse_event(move |event: &MouseDownEvent, phase, window, cx| {
+                if phase == DispatchPhase::Bubble
+                    && event.button == MouseButton::Left
+                    && hitbox.is_hovered(window)
+                {
+                    on_click((event.position - bounds.origin) / scale, window, cx);
+                    cx.stop_propagation();
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AppContext as _, Context, Entity, Modifiers, ParentElement as _, Render, TestAppContext,
+        div, point, px, red,
+    };
+    use std::cell::Cell;
+
+    struct Source {
+        renders: usize,
+    }
+
+    impl Render for Source {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            self.renders += 1;
+            div().size(px(200.)).bg(red())
+        }
+    }
+
+    struct Host {
+        source: Entity<Source>,
+        clicked: Rc<Cell<Option<Point<Pixels>>>>,
+    }
+
+    impl Render for Host {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let clicked = self.clicked.clone();
+            div().pl(px(20.)).pt(px(10.)).child(
+                thumbnail_of(self.source.clone(), 0.25)
+                    .size(px(50.))
+                    .on_click(move |position, _, _| clicked.set(Some(position))),
+            )
+        }
+    }
+
+    fn add_host(cx: &mut TestAppContext) -> (Entity<Host>, &mut crate::VisualTestContext) {
+        let source = cx.new(|_| Source { renders: 0 });
+        cx.add_window_view(|_, _| Host {
+            source,
+            clicked: Rc::default(),
+        })
+    }
+
+    #[crate::test]
+    fn test_thumbnail_scales_absolute_sizes(cx: &mut TestAppContext) {
+        let (_, cx) = add_host(cx);
+        cx.run_until_parked();
+
+        cx.update(|window, _| {
+            let scale_factor = window.scale_factor();
+            let quads = &window.rendered_frame.scene.quads;
+            assert_eq!(quads.len(), 1);
+            assert_eq!(
+                quads[0].bounds,
+                Bounds::new(point(px(20.), px(10.)), crate::size(px(50.), px(50.)))
+                    .scale(scale_factor)
+            );
+        });
+    }
+
+    #[crate::test]
+    fn test_thumbnail_refresh_is_throttled(cx: &mut TestAppContext) {
+        let (host, cx) = add_host(cx);
+        cx.run_until_parked();
+        let source = host.read_with(cx, |host, _| host.source.clone());
+        let renders = source.read_with(cx, |source, _| source.renders);
+
+        source.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(source.read_with(cx, |source, _| source.renders), renders);
+        // The copy is replayed in the meantime.
+        cx.update(|window, _| assert_eq!(window.rendered_frame.scene.quads.len(), 1));
+
+        cx.executor()
+            .advance_clock(DEFAULT_THUMBNAIL_REFRESH_INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(
+            source.read_with(cx, |source, _| source.renders),
+            renders + 1
+        );
+        cx.update(|window, _| assert_eq!(window.rendered_frame.scene.quads.len(), 1));
+    }
+
+    #[crate::test]
+    fn test_thumbnail_click_position_is_unscaled(cx: &mut TestAppContext) {
+        let (host, cx) = add_host(cx);
+        cx.run_until_parked();
+
+        cx.simulate_click(point(px(30.), px(20.)), Modifiers::none());
+        let clicked = host.read_with(cx, |host, _| host.clicked.get());
+        assert_eq!(clicked, Some(point(px(40.), px(40.))));
+    }
+}
@@ -0,0 +1,437 @@
+//! A row of items that collapses the items that don't fit into an overflow menu, such as a
+//! toolbar of buttons or a breadcrumb trail.
+//!
+//! Items are measured at their preferred width on every frame, so the toolbar reflows as the
+//! window is resized. Items with a lower [priority](ToolbarItem::priority) collapse first, and
+//! items of the same priority collapse from the end. The overflow menu's trigger, such as a `…`
+//! button, is rendered with the indices of the collapsed items, so it can list them in a menu.
+
+use std::cmp::Reverse;
+
+use crate::{
+    AnyElement, App, AvailableSpace, Bounds, Element, ElementId, GlobalElementId,
+    InspectorElementId, IntoElement, LayoutId, Pixels, Size, Style, StyleRefinement, Styled,
+    Window, point, size,
+};
+
+/// How many times the overflow trigger is re-rendered when the items it collapses change its
+/// width.
+const MAX_OVERFLOW_REFLOWS: usize = 2;
+
+/// Create a toolbar, which lays its items out in a row and collapses the ones that don't fit
+/// into an overflow menu rendered with [`Toolbar::overflow`]. Set the spacing between items with
+/// the `gap` style.
+pub fn toolbar() -> Toolbar {
+    Toolbar {
+        items: Vec::new(),
+        render_overflow: None,
+        overflow_placement: ToolbarOverflowPlacement::default(),
+        style: StyleRefinement::default(),
+    }
+}
+
+/// An item of a [`Toolbar`] with a priority, created with [`toolbar_item`].
+pub struct ToolbarItem {
+    child: AnyElement,
+    priority: i32,
+}
+
+/// Wrap an element in a toolbar item, to give it a priority.
+pub fn toolbar_item(child: impl IntoElement) -> ToolbarItem {
+    ToolbarItem {
+        child: child.into_any_element(),
+        priority: 0,
+    }
+}
+
+impl ToolbarItem {
+    /// How long the item stays in the toolbar as it shrinks: items with a lower priority
+    /// collapse into the overflow menu first. Defaults to 0.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Where the overflow menu's trigger is placed in a [`Toolbar`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ToolbarOverflowPlacement {
+    /// After the last item, as in a toolbar.
+    #[default]
+    End,
+    /// In place of the first collapsed item, as in a breadcrumb trail whose middle items
+    /// collapse.
+    InPlace,
+}
+
+/// A row of items that collapses into an overflow menu, created with [`toolbar`].
+pub struct Toolbar {
+    items: Vec<ToolbarItem>,
+    render_overflow: Option<Box<dyn Fn(&[usize], &mut Window, &mut App) -> AnyElement>>,
+    overflow_placement: ToolbarOverflowPlacement,
+    style: StyleRefinement,
+}
+
+impl Toolbar {
+    /// Add an item with the default priority.
+    pub fn child(self, child: impl IntoElement) -> Self {
+        self.item(toolbar_item(child))
+    }
+
+    /// Add items with the default priority.
+    pub fn children(mut self, children: impl IntoIterator<Item = impl IntoElement>) -> Self {
+        self.items.extend(children.into_iter().map(toolbar_item));
+        self
+    }
+
+    /// Add an item created with [`toolbar_item`].
+    pub fn item(mut self, item: ToolbarItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    /// Render the trigger of the overflow menu, given the indices of the collapsed items in
+    /// order. It's only rendered when items are collapsed. Without it, collapsed items are
+    /// hidden.
+    pub fn overflow<E: IntoElement>(
+        mut self,
+        render: impl Fn(&[usize], &mut Window, &mut App) -> E + 'static,
+    ) -> Self {
+        self.render_overflow = Some(Box::new(move |collapsed, window, cx| {
+            render(collapsed, window, cx).into_any_element()
+        }));
+        self
+    }
+
+    /// Where to place the overflow menu's trigger. Defaults to after the last item.
+    pub fn overflow_placement(mut self, placement: ToolbarOverflowPlacement) -> Self {
+        self.overflow_placement = placement;
+        self
+    }
+}
+
+/// Which items to collapse so the rest, and the overflow trigger if any collapse, fit in
+/// `available`. Items with the lowest priority collapse first, and among those, the last ones.
+fn collapse_items(
+    widths: &[Pixels],
+    priorities: &[i32],
+    gap: Pixels,
+    available: Pixels,
+    overflow_width: Pixels,
+) -> Vec<bool> {
+    let mut collapsed = vec![false; widths.len()];
+    let row_width = |collapsed: &[bool]| {
+        let (width, count) = widths
+            .iter()
+            .zip(collapsed)
+            .filter(|(_, collapsed)| !**collapsed)
+            .fold((Pixels::ZERO, 0), |(total, count), (width, _)| {
+                (total + *width, count + 1)
+            });
+        width + gap * count.saturating_sub(1) as f32
+    };
+    if row_width(&collapsed) <= available {
+        return collapsed;
+    }
+
+    let mut order = (0..widths.len()).collect::<Vec<_>>();
+    order.sort_by_key(|ix| (priorities[*ix], Reverse(*ix)));
+    for ix in order {
+        collapsed[ix] = true;
+        let visible = collapsed.iter().any(|collapsed| !collapsed);
+        let overflow_width = if visible {
+            overflow_width + gap
+        } else {
+            overflow_width
+        };
+        if row_width(&collapsed) + overflow_width <= available {
+            break;
+        }
+    }
+    collapsed
+}
+
+impl Styled for Toolbar {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl IntoElement for Toolbar {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+/// The measured items of a [`Toolbar`].
+pub struct ToolbarLayoutState {
+    item_sizes: Vec<Size<Pixels>>,
+}
+
+/// The items of a [`Toolbar`] that are shown after prepaint.
+pub struct ToolbarPrepaintState {
+    visible: Vec<usize>,
+    overflow: Option<AnyElement>,
+}
+
+impl Element for Toolbar {
+    type RequestLayoutState = ToolbarLayoutState;
+    type PrepaintState = ToolbarPrepaintState;
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        window.with_text_style(style.text_style().cloned(), |window| {
+            let max_content = size(AvailableSpace::MaxContent, AvailableSpace::MaxContent);
+            let item_sizes = self
+                .items
+                .iter_mut()
+                .map(|item| item.child.layout_as_root(max_content, window, cx))
+                .collect::<Vec<_>>();
+            let gap = style
+                .gap
+                .width
+                .to_pixels(Pixels::ZERO.into(), window.rem_size());
+            let content_width = item_sizes
+                .iter()
+                .fold(Pixels::ZERO, |total, size| total + size.width)
+                + gap * item_sizes.len().saturating_sub(1) as f32;
+            let content_height = item_sizes
+                .iter()
+                .map(|size| size.height)
+                .max()
+                .unwrap_or_default();
+
+            let layout_id = window.request_measured_layout(
+                style,
+                move |known_dimensions, available_space, _, _| {
+                    let width = known_dimensions
+                        .width
+                        .unwrap_or(match available_space.width {
+                            AvailableSpace::Definite(width) => content_width.min(width),
+                            AvailableSpace::MinContent => Pixels::ZERO,
+                            AvailableSpace::MaxContent => content_width,
+                        });
+                    size(width, known_dimensions.height.unwrap_or(content_height))
+                },
+            );
+            (layout_id, ToolbarLayoutState { item_sizes })
+        })
+    }
+
+    fn prepaint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        let rem_size = window.rem_size();
+        let border = style.border_widths.to_pixels(rem_size);
+        let padding = style.padding.to_pixels(bounds.size.into(), rem_size);
+        let padded_bounds = Bounds::from_corners(
+            bounds.origin + point(border.left + padding.left, border.top + padding.top),
+            bounds.bottom_right()
+                - point(border.right + padding.right, border.bottom + padding.bottom),
+        );
+        let gap = style
+            .gap
+            .width
+            .to_pixels(padded_bounds.size.width.into(), rem_size);
+
+        let widths = layout
+            .item_sizes
+            .iter()
+            .map(|size| size.width)
+            .collect::<Vec<_>>();
+        let priorities = self
+            .items
+            .iter()
+            .map(|item| item.priority)
+            .collect::<Vec<_>>();
+        let available = padded_bounds.size.width;
+        let mut collapsed = collapse_items(&widths, &priorities, gap, available, Pixels::ZERO);
+
+        // The trigger's width can depend on the items it collapses, such as when it shows how
+        // many there are, so collapse the items again once it's measured.
+        let mut overflow = None;
+        if collapsed.contains(&true)
+            && let Some(render_overflow) = self.render_overflow.as_ref()
+        {
+            for attempt in 0..MAX_OVERFLOW_REFLOWS {
+                let collapsed_indices = (0..collapsed.len())
+                    .filter(|ix| collapsed[*ix])
+                    .collect::<Vec<_>>();
+                let mut element = render_overflow(&collapsed_indices, window, cx);
+                let max_content = size(AvailableSpace::MaxContent, AvailableSpace::MaxContent);
+                let overflow_size = element.layout_as_root(max_content, window, cx);
+                overflow = Some((element, overflow_size));
+                let reflowed =
+                    collapse_items(&widths, &priorities, gap, available, overflow_size.width);
+                if reflowed == collapsed || attempt + 1 == MAX_OVERFLOW_REFLOWS {
+                    break;
+                }
+                collapsed = reflowed;
+            }
+        }
+
+        let visible = (0..self.items.len())
+            .filter(|ix| !collapsed[*ix])
+            .collect::<Vec<_>>();
+        let overflow_position = overflow.as_ref().map(|_| match self.overflow_placement {
+            ToolbarOverflowPlacement::End => visible.len(),
+            ToolbarOverflowPlacement::InPlace => {
+                let first_collapsed = collapsed.iter().position(|collapsed| *collapsed);
+                visible
+                    .iter()
+                    .position(|ix| Some(*ix) > first_collapsed)
+                    .unwrap_or(visible.len())
+            }
+        });
+
+        let mut x = padded_bounds.left();
+        let center_y = padded_bounds.center().y;
+        let mut place = |element: &mut AnyElement,
+                         item_size: Size<Pixels>,
+                         window: &mut Window,
+                         cx: &mut App| {
+            let origin = point(x, center_y - item_size.height / 2.);
+            element.prepaint_at(origin, window, cx);
+            x += item_size.width + gap;
+        };
+        let mut overflow_element = None;
+        for position in 0..=visible.len() {
+            if overflow_position == Some(position)
+                && let Some((mut element, overflow_size)) = overflow.take()
+            {
+                place(&mut element, overflow_size, window, cx);
+                overflow_element = Some(element);
+            }
+            if let Some(&ix) = visible.get(position) {
+                place(&mut self.items[ix].child, layout.item_sizes[ix], window, cx);
+            }
+        }
+
+        ToolbarPrepaintState {
+            visible,
+            overflow: overflow_element,
+        }
+    }
+
+    fn paint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        _: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        for ix in &prepaint.visible {
+            self.items[*ix].child.paint(window, cx);
+        }
+        if let Some(overflow) = prepaint.overflow.as_mut() {
+            overflow.paint(window, cx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::{Context, ParentElement as _, Render, TestAppContext, div, px};
+
+    #[test]
+    fn test_collapse_items() {
+        let widths = [px(50.), px(100.), px(50.), px(100.)];
+        let gap = px(10.);
+
+        // Everything fits, without room for the trigger.
+        assert_eq!(
+            collapse_items(&widths, &[0; 4], gap, px(330.), px(40.)),
+            [false; 4]
+        );
+
+        // Items collapse from the end, making room for the trigger.
+        assert_eq!(
+            collapse_items(&widths, &[0; 4], gap, px(250.), px(40.)),
+            [false, false, true, true]
+        );
+
+        // Items with a lower priority collapse first.
+        assert_eq!(
+            collapse_items(&widths, &[1, 0, 0, 1], gap, px(250.), px(40.)),
+            [false, true, true, false]
+        );
+        assert_eq!(
+            collapse_items(&widths, &[1, 0, 0, 1], gap, px(30.), px(40.)),
+            [true; 4]
+        );
+    }
+
+    struct Breadcrumbs {
+        collapsed: Rc<RefCell<Vec<usize>>>,
+    }
+
+    impl Render for Breadcrumbs {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let collapsed = self.collapsed.clone();
+            collapsed.borrow_mut().clear();
+            div().size_full().child(
+                toolbar()
+                    .w_full()
+                    .item(toolbar_item(div().w(px(100.)).h(px(20.))).priority(1))
+                    .children((0..3).map(|_| div().w(px(100.)).h(px(20.))))
+                    .item(toolbar_item(div().w(px(100.)).h(px(20.))).priority(1))
+                    .overflow(move |indices, _, _| {
+                        collapsed.borrow_mut().extend_from_slice(indices);
+                        div().w(px(20.)).h(px(20.))
+                    })
+                    .overflow_placement(ToolbarOverflowPlacement::InPlace),
+            )
+        }
+    }
+
+    #[crate::test]
+    fn test_toolbar_reflows_on_resize(cx: &mut TestAppContext) {
+        let collapsed = Rc::new(RefCell::new(Vec::new()));
+        let (_, cx) = cx.add_window_view(|_, _| Breadcrumbs {
+            collapsed: collapsed.clone(),
+        });
+
+        cx.simulate_resize(size(px(600.), px(100.)));
+        cx.run_until_parked();
+        assert!(collapsed.borrow().is_empty());
+
+        cx.simulate_resize(size(px(350.), px(100.)));
+        cx.run_until_parked();
+        assert_eq!(*collapsed.borrow(), [2, 3]);
+
+        cx.simulate_resize(size(px(150.), px(100.)));
+        cx.run_until_parked();
+        assert_eq!(*collapsed.borrow(), [1, 2, 3, 4]);
+    }
+}
@@ -7,8 +7,8 @@
 use crate::{
     AnyElement, App, AvailableSpace, Bounds, ContentMask, Element, ElementId, Entity,
     GlobalElementId, Hitbox, InspectorElementId, InteractiveElement, Interactivity, IntoElement,
-    IsZero, LayoutId, ListSizingBehavior, Overflow, Pixels, Point, ScrollHandle, Size,
-    StyleRefinement, Styled, Window, point, size,
+    IsZero, LayoutId, ListSizingBehavior, Overflow, Pixels, Point, ScrollHandle, SelectionModel,
+    Size, StyleRefinement, Styled, Window, point, size,
 };
 use smallvec::SmallVec;
 use std::{cell::RefCell, cmp, ops::Range, rc::Rc};
@@ -156,6 +156,14 @@ impl UniformListScrollHandle {
         });
     }
 
+    /// Scroll the list so that the cursor of the given selection is visible, such as after the
+    /// selection was changed from the keyboard. Does nothing if nothing was selected.
+    pub fn scroll_to_selection(&self, selection: &SelectionModel) {
+        if let Some(cursor) = selection.cursor() {
+            self.scroll_to_item(cursor, ScrollStrategy::Nearest);
+        }
+    }
+
     /// Scroll the list so that the given item index is at scroll strategy position.
     ///
     /// This uses strict scrolling: the item will always be scrolled to match the strategy position,
@@ -0,0 +1,439 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use refineable::Refineable as _;
+use util::ResultExt as _;
+
+use crate::{
+    App, Bounds, DevicePixels, Element, ElementId, GlobalElementId, Hsla, InspectorElementId,
+    IntoElement, LayoutId, Pixels, Rgba, StreamingTexture, Style, StyleRefinement, Styled, Window,
+    fill, hsla, point, px, size, util::ring_ranges,
+};
+
+/// The largest number of peaks a [`PeakBuffer`] can hold, so that its texture fits in the
+/// sprite atlas on every platform.
+pub const MAX_PEAK_BUFFER_CAPACITY: usize = 4094;
+
+/// A fixed-capacity ring buffer of `(min, max)` sample peaks, rendered by a [`Waveform`].
+///
+/// The buffer is cheap to clone and can be written from another thread (e.g. an audio
+/// callback) while the UI reads from it. Once the buffer is full, pushing a new peak
+/// discards the oldest one.
+#[derive(Clone)]
+pub struct PeakBuffer(Arc<Mutex<PeakBufferState>>);
+
+struct PeakBufferState {
+    /// A ring of peaks, written at `pushed % capacity`.
+    peaks: Vec<(f32, f32)>,
+    len: usize,
+    /// The number of peaks pushed since the buffer was created, used to find the slots a
+    /// waveform hasn't uploaded yet.
+    pushed: usize,
+}
+
+impl PeakBuffer {
+    /// Create an empty buffer that holds at most `capacity` peaks, up to
+    /// [`MAX_PEAK_BUFFER_CAPACITY`].
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.clamp(1, MAX_PEAK_BUFFER_CAPACITY);
+        Self(Arc::new(Mutex::new(PeakBufferState {
+            peaks: vec![(0., 0.); capacity],
+            len: 0,
+            pushed: 0,
+        })))
+    }
+
+    /// Append a single `(min, max)` peak, evicting the oldest peak if the buffer is full.
+    pub fn push(&self, min: f32, max: f32) {
+        let mut state = self.0.lock();
+        let capacity = state.peaks.len();
+        let slot = state.pushed % capacity;
+        state.peaks[slot] = (min.min(max), max.max(min));
+        state.pushed += 1;
+        state.len = (state.len + 1).min(capacity);
+    }
+
+    /// Append one peak summarizing the given block of raw samples.
+    pub fn push_samples(&self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        let (min, max) = samples
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), sample| {
+                (min.min(*sample), max.max(*sample))
+            });
+        self.push(min, max);
+    }
+
+    /// Remove all peaks from the buffer.
+    pub fn clear(&self) {
+        self.0.lock().len = 0;
+    }
+
+    /// The number of peaks currently stored.
+    pub fn len(&self) -> usize {
+        self.0.lock().len
+    }
+
+    /// Whether the buffer holds no peaks.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The maximum number of peaks this buffer holds.
+    pub fn capacity(&self) -> usize {
+        self.0.lock().peaks.len()
+    }
+
+    /// The stored peaks, oldest first.
+    #[cfg(test)]
+    fn peaks(&self) -> Vec<(f32, f32)> {
+        let state = self.0.lock();
+        ring_ranges(state.pushed, state.len, state.peaks.len())
+            .into_iter()
+            .flat_map(|range| state.peaks[range].to_vec())
+            .collect()
+    }
+}
+
+/// Encode a peak as one texel of a waveform texture, as described by [`Window::paint_waveform`].
+fn encode_peak((min, max): (f32, f32), amplitude: f32) -> [u8; 4] {
+    let encode = |value: f32| ((value / amplitude).clamp(-1., 1.) * 0.5 + 0.5) * u16::MAX as f32;
+    let min = encode(min).round() as u16;
+    let max = encode(max).round() as u16;
+    // Texels are stored as BGRA, so this puts the minimum in red and green and the maximum in
+    // blue and alpha, high bytes first.
+    [
+        (max >> 8) as u8,
+        (min & 0xff) as u8,
+        (min >> 8) as u8,
+        (max & 0xff) as u8,
+    ]
+}
+
+/// Encode the waveform's color and the slot of its oldest peak as the first two texels of a
+/// waveform texture.
+fn encode_header(color: Hsla, oldest: usize) -> [u8; 8] {
+    let color = Rgba::from(color);
+    let oldest = oldest as u16;
+    [
+        (color.b * 255.).round() as u8,
+        (color.g * 255.).round() as u8,
+        (color.r * 255.).round() as u8,
+        (color.a * 255.).round() as u8,
+        0,
+        (oldest & 0xff) as u8,
+        (oldest >> 8) as u8,
+        0,
+    ]
+}
+
+/// Create a waveform element that renders the peaks stored in the given buffer, oldest
+/// on the left and newest on the right.
+pub fn waveform(buffer: PeakBuffer) -> Waveform {
+    Waveform {
+        id: None,
+        buffer,
+        color: hsla(0.55, 0.8, 0.6, 1.),
+        center_line_color: Some(hsla(0., 0., 0.5, 0.3)),
+        amplitude: 1.,
+        live: false,
+        style: StyleRefinement::default(),
+    }
+}
+
+/// An element that draws an audio waveform from a [`PeakBuffer`].
+///
+/// The peaks are kept in a texture on the GPU and reduced to one `(min, max)` extent per
+/// column of pixels while drawing, so only newly pushed peaks are uploaded each frame. Give
+/// the element an id with [`Waveform::id`] so the texture can be reused across frames.
+pub struct Waveform {
+    id: Option<ElementId>,
+    buffer: PeakBuffer,
+    color: Hsla,
+    center_line_color: Option<Hsla>,
+    amplitude: f32,
+    live: bool,
+    style: StyleRefinement,
+}
+
+impl Waveform {
+    /// Assign an id to this element, allowing its texture to be cached between frames.
+    pub fn id(mut self, id: impl Into<ElementId>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Set the color of the waveform.
+    pub fn color(mut self, color: impl Into<Hsla>) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    /// Set the color of the horizontal zero line, or `None` to hide it.
+    pub fn center_line_color(mut self, color: Option<Hsla>) -> Self {
+        self.center_line_color = color;
+        self
+    }
+
+    /// Set the sample value that maps to the full height of the element. Defaults to 1.0.
+    pub fn amplitude(mut self, amplitude: f32) -> Self {
+        self.amplitude = amplitude.abs().max(f32::EPSILON);
+        self
+    }
+
+    /// Redraw on every frame so the waveform follows a buffer that is being written to
+    /// continuously.
+    pub fn live(mut self, live: bool) -> Self {
+        self.live = live;
+        self
+    }
+
+    /// Bring the texture up to date with the buffer, returning the number of peaks to draw, or
+    /// `None` if the texture is no longer in the atlas.
+    fn sync(&self, state: &mut WaveformState) -> Option<usize> {
+        let buffer = self.buffer.0.lock();
+        let capacity = buffer.peaks.len();
+        if state.amplitude != self.amplitude {
+            state.amplitude = self.amplitude;
+            state.uploaded = None;
+        }
+
+        let unsent = state
+            .uploaded
+            .map_or(capacity, |uploaded| buffer.pushed.saturating_sub(uploaded));
+        for range in ring_ranges(buffer.pushed, unsent, capacity) {
+            let bytes = buffer.peaks[range.clone()]
+                .iter()
+                .flat_map(|peak| encode_peak(*peak, self.amplitude))
+                .collect::<Vec<_>>();
+            let region = Bounds::new(
+                point(DevicePixels(2 + range.start as i32), DevicePixels(0)),
+                size(DevicePixels(range.len() as i32), DevicePixels(1)),
+            );
+            if !state.texture.update(region, &bytes) {
+                return None;
+            }
+        }
+        state.uploaded = Some(buffer.pushed);
+
+        let oldest = (buffer.pushed + capacity - buffer.len) % capacity;
+        let header = encode_header(self.color, oldest);
+        if state.header != Some(header) {
+            let region = Bounds::new(
+                point(DevicePixels(0), DevicePixels(0)),
+                size(DevicePixels(2), DevicePixels(1)),
+            );
+            if !state.texture.update(region, &header) {
+                return None;
+            }
+            state.header = Some(header);
+        }
+
+        Some(buffer.len)
+    }
+}
+
+impl IntoElement for Waveform {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Styled for Waveform {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+struct WaveformState {
+    texture: StreamingTexture,
+    /// How many peaks had been pushed when the texture was last brought up to date, or `None`
+    /// if every slot needs uploading.
+    uploaded: Option<usize>,
+    header: Option<[u8; 8]>,
+    amplitude: f32,
+}
+
+impl Element for Waveform {
+    type RequestLayoutState = Style;
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        let layout_id = window.request_layout(style.clone(), [], cx);
+        (layout_id, style)
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _style: &mut Style,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) {
+    }
+
+    fn paint(
+        &mut self,
+        id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        style: &mut Style,
+        _prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        style.paint(bounds, window, cx, |window, _| {
+            if let Some(center_line_color) = self.center_line_color {
+                window.paint_quad(fill(
+                    Bounds::new(
+                        point(bounds.left(), bounds.center().y),
+                        size(bounds.size.width, px(1.)),
+                    ),
+                    center_line_color,
+                ));
+            }
+
+            window.with_optional_element_state::<Option<WaveformState>, _>(id, |state, window| {
+                let capacity = self.buffer.capacity();
+                let mut state = state
+                    .flatten()
+                    .flatten()
+                    .filter(|state| state.texture.size().width.0 as usize == capacity + 2)
+                    .or_else(|| {
+                        let texture = window
+                            .create_streaming_texture(size(
+                                DevicePixels(2 + capacity as i32),
+                                DevicePixels(1),
+                            ))
+                            .log_err()?;
+                        Some(WaveformState {
+                            texture,
+                            uploaded: None,
+                            header: None,
+                            amplitude: self.amplitude,
+                        })
+                    });
+
+                let peak_count = state.as_mut().and_then(|state| self.sync(state));
+                if let Some((state, peak_count)) = state.as_ref().zip(peak_count) {
+                    window
+                        .paint_waveform(bounds, &state.texture, peak_count)
+                        .log_err();
+                } else {
+                    // The texture was evicted from the atlas, so create a new one next frame.
+                    state = None;
+                }
+
+                if id.is_some() {
+                    ((), Some(state))
+                } else {
+                    // Without an id there is no state to keep the texture in, so release it
+                    // once the frame using it has been drawn.
+                    window.on_next_frame(move |_, _| drop(state));
+                    ((), None)
+                }
+            });
+        });
+
+        if self.live {
+            window.request_animation_frame();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, FluentBuilder as _, ParentElement as _, Render, TestAppContext, div};
+
+    #[test]
+    fn test_peak_buffer_evicts_oldest() {
+        let buffer = PeakBuffer::new(2);
+        buffer.push_samples(&[0.1, -0.2, 0.3]);
+        buffer.push(-0.5, 0.5);
+        buffer.push(0.4, -0.4);
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.peaks(), vec![(-0.5, 0.5), (-0.4, 0.4)]);
+
+        buffer.clear();
+        assert!(buffer.is_empty());
+        buffer.push(-0.1, 0.1);
+        assert_eq!(buffer.peaks(), vec![(-0.1, 0.1)]);
+    }
+
+    #[test]
+    fn test_encode_peak() {
+        assert_eq!(encode_peak((-1., 1.), 1.), [0xff, 0x00, 0x00, 0xff]);
+        assert_eq!(encode_peak((-2., 2.), 1.), [0xff, 0x00, 0x00, 0xff]);
+        assert_eq!(encode_peak((-0.5, 0.5), 0.5), [0xff, 0x00, 0x00, 0xff]);
+        // Zero maps to the middle of the 16-bit range.
+        assert_eq!(encode_peak((0., 0.), 1.), [0x80, 0x00, 0x80, 0x00]);
+    }
+
+    struct WaveformView {
+        buffer: PeakBuffer,
+        visible: bool,
+    }
+
+    impl Render for WaveformView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size(px(200.)).when(self.visible, |this| {
+                this.child(waveform(self.buffer.clone()).id("waveform").size(px(100.)))
+            })
+        }
+    }
+
+    #[crate::test]
+    fn test_waveform_reuses_its_texture(cx: &mut TestAppContext) {
+        let buffer = PeakBuffer::new(16);
+        let (view, cx) = cx.add_window_view(|_, _| WaveformView {
+            buffer: buffer.clone(),
+            visible: true,
+        });
+        cx.run_until_parked();
+        let stats = cx.update(|window, _| window.frame_stats());
+        assert_eq!(stats.atlas_textures, 1);
+        // Nothing is drawn until there are peaks.
+        assert_eq!(stats.polychrome_sprites, 0);
+
+        for _ in 0..40 {
+            buffer.push(-0.5, 0.5);
+            view.update(cx, |_, cx| cx.notify());
+            cx.run_until_parked();
+        }
+        let stats = cx.update(|window, _| window.frame_stats());
+        assert_eq!(stats.atlas_textures, 1);
+        assert_eq!(stats.atlas_uploads, 0);
+        assert_eq!(stats.polychrome_sprites, 1);
+
+        view.update(cx, |view, cx| {
+            view.visible = false;
+            cx.notify();
+        });
+        cx.run_until_parked();
+        let stats = cx.update(|window, _| window.frame_stats());
+        assert_eq!(stats.atlas_textures, 0);
+    }
+}
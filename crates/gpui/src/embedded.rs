@@ -1,7 +1,35 @@
 // Public API for embedded window support (plugins, etc.)
 
-use crate::WindowOptions;
+use crate::{
+    EmbeddingMode, FrameDriver, HostGpuDevice, WindowBackgroundAppearance, WindowOptions,
+};
 use raw_window_handle;
+use std::sync::LazyLock;
+use std::time::Instant;
+
+/// Set this environment variable to `1` or `true` to log every call that GPUI makes on the native
+/// window of an embedded window's host, with the time since the first call. This helps debugging
+/// embedded windows that stay blank in a host that can't be debugged directly.
+const EMBEDDED_TRACE_ENV: &str = "GPUI_EMBEDDED_TRACE";
+
+static EMBEDDED_TRACE_START: LazyLock<Option<Instant>> = LazyLock::new(|| {
+    std::env::var(EMBEDDED_TRACE_ENV)
+        .is_ok_and(|value| value == "true" || value == "1")
+        .then(Instant::now)
+});
+
+/// Log a call made on the host of an embedded window, if enabled with `GPUI_EMBEDDED_TRACE`.
+/// The message is only built when tracing is enabled.
+pub(crate) fn trace_embedded(message: impl FnOnce() -> String) {
+    if let Some(start) = *EMBEDDED_TRACE_START {
+        log::info!(
+            target: "gpui::embedded",
+            "[{:>10.3}ms] {}",
+            start.elapsed().as_secs_f64() * 1000.,
+            message()
+        );
+    }
+}
 
 /// Extensions to WindowOptions for embedded contexts
 impl WindowOptions {
@@ -43,4 +71,112 @@ impl WindowOptions {
             ..Default::default()
         }
     }
+
+    /// Create window options for embedding GPUI into its own native window, which GPUI creates
+    /// inside the host's window and keeps filling it as the host is resized.
+    ///
+    /// Many hosts don't allow plugins to subclass or take over their windows. On Windows, this
+    /// creates a child `HWND` with GPUI's own window class instead of attaching to the host's, see
+    /// [`EmbeddingMode::Child`]. Elsewhere, GPUI always renders into its own subview, child
+    /// window or subsurface, so this is the same as [`WindowOptions::for_embedded_window`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// let options = WindowOptions::for_embedded_child_window(plugin_host.raw_window_handle());
+    /// cx.open_window(options, |window, cx| cx.new(|_| MyPluginUI::new()));
+    /// ```
+    pub fn for_embedded_child_window(raw_handle: raw_window_handle::RawWindowHandle) -> Self {
+        Self::for_embedded_window(raw_handle).with_embedding_mode(EmbeddingMode::Child)
+    }
+
+    /// Create window options for embedding GPUI into an existing native window, together with
+    /// the display connection that the window belongs to.
+    ///
+    /// On Linux, this lets GPUI share the host's connection. See
+    /// [`WindowOptions::with_raw_display_handle`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// use raw_window_handle::{XcbDisplayHandle, XcbWindowHandle};
+    ///
+    /// let options = WindowOptions::for_embedded_window_with_display(
+    ///     XcbWindowHandle::new(host_window).into(),
+    ///     XcbDisplayHandle::new(Some(connection), screen).into(),
+    /// );
+    /// ```
+    pub fn for_embedded_window_with_display(
+        raw_handle: raw_window_handle::RawWindowHandle,
+        raw_display_handle: raw_window_handle::RawDisplayHandle,
+    ) -> Self {
+        Self::for_embedded_window(raw_handle).with_raw_display_handle(raw_display_handle)
+    }
+
+    /// Set how the embedded window renders into the host's window. See [`EmbeddingMode`].
+    ///
+    /// On Windows, [`EmbeddingMode::Child`] avoids subclassing the host's window, at the cost of
+    /// an extra window between the host's and GPUI's content. A host's
+    /// window can only have one GPUI window attached to it at a time, while any number of child
+    /// windows can be embedded in it.
+    pub fn with_embedding_mode(mut self, embedding_mode: EmbeddingMode) -> Self {
+        self.embedding_mode = embedding_mode;
+        self
+    }
+
+    /// Set the host's GPU device for the embedded window to render on, instead of creating its
+    /// own. See [`HostGpuDevice`]. Only used on Windows.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let device = NonNull::new(host_d3d11_device.as_raw()).unwrap();
+    /// let options = WindowOptions::for_embedded_window(raw_handle)
+    ///     .with_host_gpu_device(unsafe { HostGpuDevice::direct3d11(device) });
+    /// ```
+    pub fn with_host_gpu_device(mut self, device: HostGpuDevice) -> Self {
+        self.host_gpu_device = Some(device);
+        self
+    }
+
+    /// Composite the embedded window over whatever its host draws underneath it, for overlays and
+    /// HUDs, rather than covering the host. Only what the window renders is drawn, so the host
+    /// shows through wherever the window's background is transparent.
+    ///
+    /// On Windows, windows attached to the host's window are composited on top of it, and child
+    /// windows are layered windows, presented with GDI. On Wayland, the subsurface is blended over
+    /// the host's surface. X11 doesn't composite child windows with their parents, so they're
+    /// drawn over a black background instead.
+    pub fn with_transparent_background(mut self) -> Self {
+        self.window_background = WindowBackgroundAppearance::Transparent;
+        self
+    }
+
+    /// Set what drives the embedded window's frames. See [`FrameDriver`].
+    ///
+    /// With [`FrameDriver::External`], call [`AnyWindowHandle::render_frame`] from the host's
+    /// idle or timer callback, such as CLAP's `timer_support` or VST3's `IRunLoop` timers.
+    ///
+    /// [`AnyWindowHandle::render_frame`]: crate::AnyWindowHandle::render_frame
+    pub fn with_frame_driver(mut self, frame_driver: FrameDriver) -> Self {
+        self.frame_driver = frame_driver;
+        self
+    }
+
+    /// Set the display connection that the embedded window's handle belongs to.
+    ///
+    /// This is required on Wayland, where the host's surface can only be used on the host's
+    /// connection. GPUI moves onto that connection, so it can't have other windows open.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use raw_window_handle::{WaylandDisplayHandle, WaylandWindowHandle};
+    ///
+    /// let options = WindowOptions::for_embedded_window(WaylandWindowHandle::new(surface).into())
+    ///     .with_raw_display_handle(WaylandDisplayHandle::new(display).into());
+    /// ```
+    pub fn with_raw_display_handle(
+        mut self,
+        raw_display_handle: raw_window_handle::RawDisplayHandle,
+    ) -> Self {
+        self.raw_display_handle = Some(raw_display_handle);
+        self
+    }
 }
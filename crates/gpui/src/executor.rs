@@ -78,6 +78,14 @@ impl<T> Task<T> {
             Task(TaskState::Spawned(task)) => task.detach(),
         }
     }
+
+    /// Returns whether the task has run to completion.
+    pub(crate) fn is_finished(&self) -> bool {
+        match &self.0 {
+            TaskState::Ready(_) => true,
+            TaskState::Spawned(task) => task.is_finished(),
+        }
+    }
 }
 
 impl<E, T> Task<Result<T, E>>
@@ -448,6 +456,39 @@ impl BackgroundExecutor {
         self.dispatcher.as_test().unwrap().forbid_parking();
     }
 
+    /// in tests, allows parking like [`Self::allow_parking`] until the returned guard is dropped,
+    /// then restores the previous setting.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn allow_parking_scoped(&self) -> crate::ParkingAllowed {
+        self.dispatcher.as_test().unwrap().allow_parking_scoped()
+    }
+
+    /// in tests, returns the seed the scheduler was created with, if it was created from a seed.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn seed(&self) -> Option<u64> {
+        self.dispatcher.as_test().unwrap().seed()
+    }
+
+    /// in tests, returns the number of tasks waiting to run.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn pending_tasks(&self) -> crate::PendingTasks {
+        self.dispatcher.as_test().unwrap().pending_tasks()
+    }
+
+    /// in tests, starts or stops recording the order in which the scheduler runs tasks.
+    /// The recorded tasks are returned by [`Self::take_task_order`].
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn record_task_order(&self, record: bool) {
+        self.dispatcher.as_test().unwrap().record_task_order(record);
+    }
+
+    /// in tests, returns the tasks run since recording started or since the last call, in the
+    /// order they ran.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn take_task_order(&self) -> Vec<crate::ScheduledTask> {
+        self.dispatcher.as_test().unwrap().take_task_order()
+    }
+
     /// adds detail to the "parked with nothing let to run" message.
     #[cfg(any(test, feature = "test-support"))]
     pub fn set_waiting_hint(&self, msg: Option<String>) {
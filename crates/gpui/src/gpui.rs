@@ -28,11 +28,16 @@ mod inspector;
 mod interactive;
 mod key_dispatch;
 mod keymap;
+mod main_thread;
 mod path_builder;
 mod platform;
+mod power;
 pub mod prelude;
 mod profiler;
+pub mod runtime;
 mod scene;
+mod selection;
+mod session;
 mod shared_string;
 mod shared_uri;
 mod style;
@@ -77,6 +82,7 @@ pub use color::*;
 pub use ctor::ctor;
 pub use element::*;
 pub use elements::*;
+pub(crate) use embedded::trace_embedded;
 pub use executor::*;
 pub use geometry::*;
 pub use global::*;
@@ -87,11 +93,15 @@ pub use inspector::*;
 pub use interactive::*;
 use key_dispatch::*;
 pub use keymap::*;
+pub use main_thread::*;
 pub use path_builder::*;
 pub use platform::*;
+pub use power::*;
 pub use profiler::*;
 pub use refineable::*;
 pub use scene::*;
+pub use selection::*;
+pub use session::*;
 pub use shared_string::*;
 pub use shared_uri::*;
 pub use smol::Timer;
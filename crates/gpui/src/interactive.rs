@@ -294,6 +294,17 @@ impl ClickEvent {
     }
 }
 
+/// A long press event, generated when the left mouse button is held down over an element
+/// without moving for long enough.
+#[derive(Clone, Debug, Default)]
+pub struct LongPressEvent {
+    /// The mouse event when the button was pressed.
+    pub down: MouseDownEvent,
+
+    /// The position of the mouse when the press was recognized.
+    pub position: Point<Pixels>,
+}
+
 /// An enum representing the keyboard button that was pressed for a click event.
 #[derive(Hash, PartialEq, Eq, Copy, Clone, Debug, Default)]
 pub enum KeyboardButton {
@@ -614,9 +625,13 @@ impl PlatformInput {
 #[cfg(test)]
 mod test {
 
+    use std::time::Duration;
+
     use crate::{
-        self as gpui, AppContext as _, Context, FocusHandle, InteractiveElement, IntoElement,
-        KeyBinding, Keystroke, ParentElement, Render, TestAppContext, Window, div,
+        self as gpui, AppContext as _, Axis, Context, FocusHandle, InteractiveElement, IntoElement,
+        KeyBinding, Keystroke, Modifiers, MouseButton, MouseDownEvent, MouseUpEvent, ParentElement,
+        Render, ScrollDelta, ScrollHandle, ScrollWheelEvent, StatefulInteractiveElement, Styled,
+        TestAppContext, Window, div, point, px,
     };
 
     struct TestView {
@@ -683,4 +698,291 @@ mod test {
             })
             .unwrap();
     }
+
+    #[derive(Default)]
+    struct LongPressView {
+        clicks: usize,
+        long_presses: usize,
+    }
+
+    impl Render for LongPressView {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .id("long-press")
+                .size_full()
+                .on_click(cx.listener(|this, _, _, _| this.clicks += 1))
+                .on_long_press(cx.listener(|this, _, _, _| this.long_presses += 1))
+        }
+    }
+
+    #[gpui::test]
+    fn test_long_press(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| LongPressView::default());
+        let position = point(px(10.), px(10.));
+
+        // A quick press is a click.
+        cx.simulate_mouse_down(position, MouseButton::Left, Modifiers::none());
+        cx.executor().advance_clock(Duration::from_millis(100));
+        cx.simulate_mouse_up(position, MouseButton::Left, Modifiers::none());
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.clicks, 1);
+            assert_eq!(view.long_presses, 0);
+        });
+
+        // Holding the button is a long press, and releasing it afterwards isn't a click.
+        cx.simulate_mouse_down(position, MouseButton::Left, Modifiers::none());
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.simulate_mouse_up(position, MouseButton::Left, Modifiers::none());
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.clicks, 1);
+            assert_eq!(view.long_presses, 1);
+        });
+
+        // Moving too far while holding the button cancels the long press.
+        cx.simulate_mouse_down(position, MouseButton::Left, Modifiers::none());
+        cx.simulate_mouse_move(
+            point(px(40.), px(40.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.simulate_mouse_up(
+            point(px(40.), px(40.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        view.read_with(cx, |view, _| assert_eq!(view.long_presses, 1));
+    }
+
+    #[derive(Default)]
+    struct MultiClickView {
+        single_clicks: usize,
+        double_clicks: usize,
+    }
+
+    impl Render for MultiClickView {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .id("multi-click")
+                .size_full()
+                .on_multi_click(1, cx.listener(|this, _, _, _| this.single_clicks += 1))
+                .on_multi_click(2, cx.listener(|this, _, _, _| this.double_clicks += 1))
+        }
+    }
+
+    #[gpui::test]
+    fn test_multi_click(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| MultiClickView::default());
+        let position = point(px(10.), px(10.));
+
+        for click_count in 1..=3 {
+            cx.simulate_event(MouseDownEvent {
+                position,
+                button: MouseButton::Left,
+                click_count,
+                ..Default::default()
+            });
+            cx.simulate_event(MouseUpEvent {
+                position,
+                button: MouseButton::Left,
+                click_count,
+                ..Default::default()
+            });
+        }
+
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.single_clicks, 1);
+            assert_eq!(view.double_clicks, 1);
+        });
+    }
+
+    struct ModifiersView {
+        focused_changes: usize,
+        hovered_changes: usize,
+        focus_handle: FocusHandle,
+    }
+
+    impl Render for ModifiersView {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .track_focus(&self.focus_handle)
+                .on_modifiers_changed(cx.listener(|this, _, _, _| this.focused_changes += 1))
+                .on_hover_modifiers_changed(
+                    cx.listener(|this, _, _, _| this.hovered_changes += 1),
+                )
+        }
+    }
+
+    #[gpui::test]
+    fn test_modifiers_changed_while_hovered(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, cx| ModifiersView {
+            focused_changes: 0,
+            hovered_changes: 0,
+            focus_handle: cx.focus_handle(),
+        });
+
+        // Only the hover listener receives modifier changes while the element isn't focused.
+        cx.simulate_mouse_move(point(px(10.), px(10.)), None, Modifiers::none());
+        cx.simulate_modifiers_change(Modifiers::shift());
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.focused_changes, 0);
+            assert_eq!(view.hovered_changes, 1);
+        });
+
+        view.update_in(cx, |view, window, _| window.focus(&view.focus_handle));
+        cx.simulate_modifiers_change(Modifiers::none());
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.focused_changes, 1);
+            assert_eq!(view.hovered_changes, 2);
+        });
+
+        // Once the mouse leaves, only the focus listener is called.
+        cx.simulate_mouse_move(point(px(-10.), px(-10.)), None, Modifiers::none());
+        cx.simulate_modifiers_change(Modifiers::shift());
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.focused_changes, 2);
+            assert_eq!(view.hovered_changes, 2);
+        });
+    }
+
+    struct ScrollView {
+        scroll_handle: ScrollHandle,
+    }
+
+    impl Render for ScrollView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .id("scroll-container")
+                .size_full()
+                .overflow_scroll()
+                .track_scroll(&self.scroll_handle)
+                .child(div().flex_none().w(px(2000.)).h(px(2000.)).on_scroll_wheel(
+                    |_, window, _| window.stop_scroll_propagation(Axis::Horizontal),
+                ))
+        }
+    }
+
+    #[gpui::test]
+    fn test_stop_scroll_propagation(cx: &mut TestAppContext) {
+        let scroll_handle = ScrollHandle::new();
+        let (_, cx) = cx.add_window_view(|_, _| ScrollView {
+            scroll_handle: scroll_handle.clone(),
+        });
+
+        // The horizontal part of the scroll is consumed, while the vertical part scrolls the container.
+        cx.simulate_event(ScrollWheelEvent {
+            position: point(px(10.), px(10.)),
+            delta: ScrollDelta::Pixels(point(px(-30.), px(-40.))),
+            ..Default::default()
+        });
+        assert_eq!(scroll_handle.offset(), point(px(0.), px(-40.)));
+    }
+
+    struct ShortcutView {
+        saves: usize,
+        focus_handle: FocusHandle,
+    }
+
+    impl Render for ShortcutView {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div().child(
+                div()
+                    .track_focus(&self.focus_handle)
+                    .on_shortcut("ctrl-s", cx.listener(|this, _, _, _| this.saves += 1)),
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn test_on_shortcut(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|cx| ShortcutView {
+                    saves: 0,
+                    focus_handle: cx.focus_handle(),
+                })
+            })
+            .unwrap()
+        });
+
+        // The shortcut is inactive while its element doesn't contain the focus.
+        cx.dispatch_keystroke(*window, Keystroke::parse("ctrl-s").unwrap());
+        window
+            .update(cx, |view, _, _| assert_eq!(view.saves, 0))
+            .unwrap();
+
+        window
+            .update(cx, |view, window, _| window.focus(&view.focus_handle))
+            .unwrap();
+        cx.dispatch_keystroke(*window, Keystroke::parse("ctrl-s").unwrap());
+        cx.dispatch_keystroke(*window, Keystroke::parse("ctrl-d").unwrap());
+        window
+            .update(cx, |view, _, _| assert_eq!(view.saves, 1))
+            .unwrap();
+    }
+
+    struct ShortcutPrecedenceView {
+        shortcuts: usize,
+        actions: usize,
+        focus_handle: FocusHandle,
+    }
+
+    impl Render for ShortcutPrecedenceView {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .key_context("outer")
+                .on_shortcut("ctrl-s", cx.listener(|this, _, _, _| this.shortcuts += 1))
+                .on_shortcut("ctrl-t", cx.listener(|this, _, _, _| this.shortcuts += 1))
+                .on_shortcut("ctrl-a-b", |_, _, _| {})
+                .child(
+                    div()
+                        .key_context("inner")
+                        .track_focus(&self.focus_handle)
+                        .on_action(cx.listener(|this, _: &TestAction, _, _| this.actions += 1)),
+                )
+        }
+    }
+
+    #[gpui::test]
+    fn test_shortcut_precedence(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|cx| ShortcutPrecedenceView {
+                    shortcuts: 0,
+                    actions: 0,
+                    focus_handle: cx.focus_handle(),
+                })
+            })
+            .unwrap()
+        });
+        cx.update(|cx| {
+            cx.bind_keys(vec![
+                KeyBinding::new("ctrl-s", TestAction, Some("inner")),
+                KeyBinding::new("ctrl-t", TestAction, Some("outer")),
+            ]);
+        });
+        window
+            .update(cx, |view, window, _| window.focus(&view.focus_handle))
+            .unwrap();
+
+        // A binding whose context is set inside the shortcut's element takes precedence.
+        cx.dispatch_keystroke(*window, Keystroke::parse("ctrl-s").unwrap());
+        window
+            .update(cx, |view, _, _| {
+                assert_eq!(view.actions, 1);
+                assert_eq!(view.shortcuts, 0);
+            })
+            .unwrap();
+
+        // The shortcut takes precedence over a binding whose context is set by its own element.
+        cx.dispatch_keystroke(*window, Keystroke::parse("ctrl-t").unwrap());
+        window
+            .update(cx, |view, _, _| {
+                assert_eq!(view.actions, 1);
+                assert_eq!(view.shortcuts, 1);
+            })
+            .unwrap();
+    }
+
 }
@@ -50,8 +50,8 @@
 //!  KeyBinding::new("cmd-k left", pane::SplitLeft, Some("Pane"))
 
 use crate::{
-    Action, ActionRegistry, App, DispatchPhase, EntityId, FocusId, KeyBinding, KeyContext, Keymap,
-    Keystroke, ModifiersChangedEvent, Window,
+    Action, ActionRegistry, App, DispatchPhase, EntityId, FocusId, KeyBinding, KeyContext,
+    KeyDownEvent, KeybindingKeystroke, Keymap, Keystroke, ModifiersChangedEvent, Window,
 };
 use collections::FxHashMap;
 use smallvec::SmallVec;
@@ -61,6 +61,7 @@ use std::{
     mem,
     ops::Range,
     rc::Rc,
+    slice,
 };
 
 /// ID of a node within `DispatchTree`. Note that these are **not** stable between frames, and so a
@@ -84,6 +85,7 @@ pub(crate) struct DispatchNode {
     pub key_listeners: Vec<KeyListener>,
     pub action_listeners: Vec<DispatchActionListener>,
     pub modifiers_changed_listeners: Vec<ModifiersChangedListener>,
+    pub shortcuts: Vec<DispatchShortcut>,
     pub context: Option<KeyContext>,
     pub focus_id: Option<FocusId>,
     view_id: Option<EntityId>,
@@ -129,6 +131,13 @@ pub(crate) struct DispatchResult {
 
 type KeyListener = Rc<dyn Fn(&dyn Any, DispatchPhase, &mut Window, &mut App)>;
 type ModifiersChangedListener = Rc<dyn Fn(&ModifiersChangedEvent, &mut Window, &mut App)>;
+pub(crate) type ShortcutListener = Rc<dyn Fn(&KeyDownEvent, &mut Window, &mut App)>;
+
+#[derive(Clone)]
+pub(crate) struct DispatchShortcut {
+    pub(crate) keystroke: KeybindingKeystroke,
+    pub(crate) listener: ShortcutListener,
+}
 
 #[derive(Clone)]
 pub(crate) struct DispatchActionListener {
@@ -259,6 +268,7 @@ impl DispatchTree {
         target.key_listeners = mem::take(&mut source.key_listeners);
         target.action_listeners = mem::take(&mut source.action_listeners);
         target.modifiers_changed_listeners = mem::take(&mut source.modifiers_changed_listeners);
+        target.shortcuts = mem::take(&mut source.shortcuts);
     }
 
     pub fn reuse_subtree(
@@ -330,6 +340,13 @@ impl DispatchTree {
             .push(listener);
     }
 
+    pub fn on_shortcut(&mut self, keystroke: KeybindingKeystroke, listener: ShortcutListener) {
+        self.active_node().shortcuts.push(DispatchShortcut {
+            keystroke,
+            listener,
+        });
+    }
+
     pub fn on_action(
         &mut self,
         action_type: TypeId,
@@ -549,6 +566,45 @@ impl DispatchTree {
         (input, to_replay)
     }
 
+    /// Find the listener of the innermost shortcut along the dispatch path that matches the given
+    /// keystroke. A shortcut is shadowed by key bindings for the same keystroke whose context is
+    /// set by an element inside the shortcut's element, just as those elements' own shortcuts
+    /// would shadow it.
+    pub fn shortcut_listener(
+        &self,
+        keystroke: &Keystroke,
+        dispatch_path: &SmallVec<[DispatchNodeId; 32]>,
+    ) -> Option<ShortcutListener> {
+        let context_stack: Vec<KeyContext> = dispatch_path
+            .iter()
+            .filter_map(|node_id| self.node(*node_id).context.clone())
+            .collect();
+        let binding_depth = self
+            .keymap
+            .borrow()
+            .deepest_binding_depth(slice::from_ref(keystroke), &context_stack);
+
+        let mut context_depth = context_stack.len();
+        for node_id in dispatch_path.iter().rev() {
+            if binding_depth.is_some_and(|binding_depth| binding_depth > context_depth) {
+                return None;
+            }
+            let node = self.node(*node_id);
+            if let Some(shortcut) = node
+                .shortcuts
+                .iter()
+                .rev()
+                .find(|shortcut| keystroke.should_match(&shortcut.keystroke))
+            {
+                return Some(shortcut.listener.clone());
+            }
+            if node.context.is_some() {
+                context_depth -= 1;
+            }
+        }
+        None
+    }
+
     pub fn dispatch_path(&self, target: DispatchNodeId) -> SmallVec<[DispatchNodeId; 32]> {
         let mut dispatch_path: SmallVec<[DispatchNodeId; 32]> = SmallVec::new();
         let mut current_node_id = Some(target);
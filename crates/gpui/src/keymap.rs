@@ -206,6 +206,24 @@ impl Keymap {
 
         (bindings, !pending.is_empty())
     }
+    /// The deepest context depth at which a binding for the given input, or one starting with it,
+    /// is enabled. Bindings without a context predicate and bindings to [`NoAction`](crate::NoAction)
+    /// aren't considered.
+    pub(crate) fn deepest_binding_depth(
+        &self,
+        input: &[impl AsKeystroke],
+        context_stack: &[KeyContext],
+    ) -> Option<usize> {
+        self.bindings()
+            .filter(|binding| {
+                binding.context_predicate.is_some()
+                    && !is_no_action(&*binding.action)
+                    && binding.match_keystrokes(input).is_some()
+            })
+            .filter_map(|binding| self.binding_enabled(binding, context_stack))
+            .max()
+    }
+
     /// Check if the given binding is enabled, given a certain key context.
     /// Returns the deepest depth at which the binding matches, or None if it doesn't match.
     fn binding_enabled(&self, binding: &KeyBinding, contexts: &[KeyContext]) -> Option<usize> {
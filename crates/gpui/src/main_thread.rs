@@ -0,0 +1,322 @@
+use std::{
+    any::type_name,
+    future::Future,
+    mem::ManuallyDrop,
+    panic::Location,
+    thread::{self, ThreadId},
+    time::Duration,
+};
+
+use anyhow::{Result, anyhow};
+use futures::{
+    StreamExt as _,
+    channel::{mpsc, oneshot},
+};
+
+use crate::{App, BackgroundExecutor};
+
+/// Limits on how much work GPUI does on the main thread each time it's woken up to run
+/// foreground tasks. See [`App::set_main_thread_budget`].
+///
+/// Inside a DAW or another host application, GPUI shares its main thread with the host. Without
+/// a budget, GPUI runs every queued task before returning to the host's message loop, which can
+/// starve the host's own input handling and painting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MainThreadBudget {
+    /// The maximum number of tasks to run per wakeup. At least one task always runs, so that
+    /// GPUI keeps making progress. `None` means no limit, which is the default.
+    pub max_tasks_per_wakeup: Option<usize>,
+    /// The maximum time to spend running tasks per wakeup. A task that's already running isn't
+    /// interrupted, so a wakeup can exceed this by the duration of one task. `None` means no
+    /// limit, which is the default.
+    pub max_time_per_wakeup: Option<Duration>,
+    /// How to resume the remaining tasks once the budget is exhausted.
+    pub yield_behavior: MainThreadYield,
+}
+
+impl MainThreadBudget {
+    /// Whether a wakeup that has run `tasks_run` tasks over `elapsed` should stop and yield.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub(crate) fn is_exhausted(&self, tasks_run: usize, elapsed: Duration) -> bool {
+        tasks_run > 0
+            && (self
+                .max_tasks_per_wakeup
+                .is_some_and(|max_tasks| tasks_run >= max_tasks)
+                || self
+                    .max_time_per_wakeup
+                    .is_some_and(|max_time| elapsed >= max_time))
+    }
+}
+
+/// How GPUI hands the main thread back to the host once a [`MainThreadBudget`] is exhausted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MainThreadYield {
+    /// Schedule another wakeup behind the messages that are already queued. Other posted
+    /// messages run first, but the platform may still prioritize GPUI's wakeup over user input.
+    #[default]
+    Requeue,
+    /// Resume after at least the given delay, once the host has processed its pending input and
+    /// paint messages. Platforms may round the delay up to their timer resolution.
+    Defer(Duration),
+}
+
+/// Counters describing GPUI's main thread usage since the application started. See
+/// [`App::main_thread_metrics`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MainThreadMetrics {
+    /// How many times GPUI was woken up to run foreground tasks.
+    pub wakeups: u64,
+    /// How many foreground tasks have run.
+    pub tasks_run: u64,
+    /// How many wakeups stopped early because the [`MainThreadBudget`] was exhausted.
+    pub budget_exhaustions: u64,
+    /// The total time spent running foreground tasks.
+    pub busy_time: Duration,
+    /// The longest single wakeup.
+    pub longest_wakeup: Duration,
+}
+
+impl MainThreadMetrics {
+    /// Record a wakeup that ran `tasks_run` tasks over `elapsed`.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub(crate) fn record_wakeup(&mut self, tasks_run: usize, elapsed: Duration, exhausted: bool) {
+        self.wakeups += 1;
+        self.tasks_run += tasks_run as u64;
+        self.budget_exhaustions += exhausted as u64;
+        self.busy_time += elapsed;
+        self.longest_wakeup = self.longest_wakeup.max(elapsed);
+    }
+}
+
+/// A value that can only be used on the thread it was created on, which should be GPUI's main
+/// thread.
+///
+/// The wrapper is `Send` and `Sync`, so that it can be stored in state that a host application
+/// shares with its other threads, such as a plugin instance. Using the value from another thread
+/// panics with a message naming the value's type and the caller, rather than risking undefined
+/// behavior, and [`MainThreadOnly::try_get`] returns the same message as an error.
+pub struct MainThreadOnly<T> {
+    value: ManuallyDrop<T>,
+    thread_id: ThreadId,
+}
+
+// SAFETY: The value is only accessed and dropped on the thread that it was created on.
+unsafe impl<T> Send for MainThreadOnly<T> {}
+unsafe impl<T> Sync for MainThreadOnly<T> {}
+
+impl<T> MainThreadOnly<T> {
+    /// Wrap a value, so that it can only be used on the current thread.
+    pub fn new(value: T) -> Self {
+        Self {
+            value: ManuallyDrop::new(value),
+            thread_id: thread::current().id(),
+        }
+    }
+
+    /// Whether the value can be used on the current thread.
+    pub fn is_accessible(&self) -> bool {
+        thread::current().id() == self.thread_id
+    }
+
+    /// The value, or an error naming its type and the caller if this isn't the thread that the
+    /// value was created on.
+    #[track_caller]
+    pub fn try_get(&self) -> Result<&T> {
+        self.check_thread(Location::caller())?;
+        Ok(&self.value)
+    }
+
+    /// The value, panicking if this isn't the thread that the value was created on.
+    #[track_caller]
+    pub fn get(&self) -> &T {
+        if let Err(error) = self.check_thread(Location::caller()) {
+            panic!("{error}");
+        }
+        &self.value
+    }
+
+    /// The value, panicking if this isn't the thread that the value was created on.
+    #[track_caller]
+    pub fn get_mut(&mut self) -> &mut T {
+        if let Err(error) = self.check_thread(Location::caller()) {
+            panic!("{error}");
+        }
+        &mut self.value
+    }
+
+    /// Unwrap the value, panicking if this isn't the thread that the value was created on.
+    #[track_caller]
+    pub fn into_inner(self) -> T {
+        if let Err(error) = self.check_thread(Location::caller()) {
+            panic!("{error}");
+        }
+        let mut this = ManuallyDrop::new(self);
+        unsafe { ManuallyDrop::take(&mut this.value) }
+    }
+
+    fn check_thread(&self, caller: &Location) -> Result<()> {
+        if self.is_accessible() {
+            return Ok(());
+        }
+        Err(anyhow!(
+            "{} can only be used on {:?}, where it was created, but {caller} used it on {:?}",
+            type_name::<T>(),
+            self.thread_id,
+            thread::current().id(),
+        ))
+    }
+}
+
+impl<T> Drop for MainThreadOnly<T> {
+    fn drop(&mut self) {
+        if self.is_accessible() {
+            unsafe { ManuallyDrop::drop(&mut self.value) };
+        } else {
+            // Dropping the value here could race with the thread that owns it, so leak it.
+            log::error!(
+                "leaking {}, as it was dropped off the thread it was created on",
+                type_name::<T>()
+            );
+        }
+    }
+}
+
+type MainThreadCallback = Box<dyn FnOnce(&mut App) + Send>;
+
+/// A handle for reaching the [`App`] from other threads, see [`App::background_handle`].
+///
+/// Unlike [`AsyncApp`](crate::AsyncApp), this handle is `Send`. In exchange, it can only spawn
+/// background tasks and queue work to run on the main thread.
+#[derive(Clone)]
+pub struct BackgroundHandle {
+    background_executor: BackgroundExecutor,
+    main_thread_sender: mpsc::UnboundedSender<MainThreadCallback>,
+}
+
+impl BackgroundHandle {
+    /// The executor for spawning background tasks.
+    pub fn background_executor(&self) -> &BackgroundExecutor {
+        &self.background_executor
+    }
+
+    /// Queue a function to run on the main thread, resolving to its result. Fails if the app is
+    /// released before the function runs.
+    pub fn run_on_main_thread<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut App) -> R + Send + 'static,
+    ) -> impl Future<Output = Result<R>> + Send + 'static {
+        let (result_tx, result_rx) = oneshot::channel();
+        let sent = self.main_thread_sender.unbounded_send(Box::new(move |cx| {
+            result_tx.send(f(cx)).ok();
+        }));
+        async move {
+            sent.map_err(|_| anyhow!("app was released"))?;
+            result_rx.await.map_err(|_| anyhow!("app was released"))
+        }
+    }
+}
+
+impl App {
+    /// Limit how much work GPUI does on the main thread per wakeup, so that a host application
+    /// sharing the thread stays responsive. Only enforced on Windows for now.
+    pub fn set_main_thread_budget(&mut self, budget: MainThreadBudget) {
+        self.platform.set_main_thread_budget(budget);
+    }
+
+    /// The budget passed to [`App::set_main_thread_budget`].
+    pub fn main_thread_budget(&self) -> MainThreadBudget {
+        self.platform.main_thread_budget()
+    }
+
+    /// Counters describing GPUI's main thread usage, for tuning the [`MainThreadBudget`].
+    /// Platforms that don't enforce a budget report no usage.
+    pub fn main_thread_metrics(&self) -> MainThreadMetrics {
+        self.platform.main_thread_metrics()
+    }
+
+    /// Create a [`BackgroundHandle`], which other threads can use to queue work for the main
+    /// thread. The work stops being run once every clone of the handle has been dropped.
+    pub fn background_handle(&self) -> BackgroundHandle {
+        let (main_thread_sender, mut main_thread_receiver) =
+            mpsc::unbounded::<MainThreadCallback>();
+        self.spawn(async move |cx| {
+            while let Some(callback) = main_thread_receiver.next().await {
+                if cx.update(callback).is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+
+        BackgroundHandle {
+            background_executor: self.background_executor.clone(),
+            main_thread_sender,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_main_thread_budget() {
+        let unlimited = MainThreadBudget::default();
+        assert!(!unlimited.is_exhausted(1000, Duration::from_secs(1)));
+
+        let budget = MainThreadBudget {
+            max_tasks_per_wakeup: Some(4),
+            max_time_per_wakeup: Some(Duration::from_millis(2)),
+            ..Default::default()
+        };
+        assert!(!budget.is_exhausted(3, Duration::from_millis(1)));
+        assert!(budget.is_exhausted(4, Duration::from_millis(1)));
+        assert!(budget.is_exhausted(1, Duration::from_millis(2)));
+
+        // At least one task runs per wakeup.
+        let budget = MainThreadBudget {
+            max_tasks_per_wakeup: Some(0),
+            ..Default::default()
+        };
+        assert!(!budget.is_exhausted(0, Duration::ZERO));
+        assert!(budget.is_exhausted(1, Duration::ZERO));
+    }
+
+    #[test]
+    fn test_main_thread_metrics() {
+        let mut metrics = MainThreadMetrics::default();
+        metrics.record_wakeup(3, Duration::from_millis(2), false);
+        metrics.record_wakeup(5, Duration::from_millis(4), true);
+        assert_eq!(
+            metrics,
+            MainThreadMetrics {
+                wakeups: 2,
+                tasks_run: 8,
+                budget_exhaustions: 1,
+                busy_time: Duration::from_millis(6),
+                longest_wakeup: Duration::from_millis(4),
+            }
+        );
+    }
+
+    #[test]
+    fn test_main_thread_only() {
+        let value = MainThreadOnly::new(std::rc::Rc::new(5));
+        assert_eq!(**value.get(), 5);
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                assert!(!value.is_accessible());
+                let error = value.try_get().unwrap_err().to_string();
+                assert!(error.contains("alloc::rc::Rc<i32>"), "{error}");
+                assert!(error.contains(file!()), "{error}");
+            });
+        });
+
+        // Dropping the value on another thread leaks it instead.
+        let rc = value.into_inner();
+        let value = MainThreadOnly::new(rc.clone());
+        std::thread::spawn(move || drop(value)).join().unwrap();
+        assert_eq!(std::rc::Rc::strong_count(&rc), 2);
+    }
+}
@@ -1,6 +1,7 @@
 mod app_menu;
 mod keyboard;
 mod keystroke;
+mod spell_check;
 
 #[cfg(any(target_os = "linux", target_os = "freebsd"))]
 mod linux;
@@ -36,18 +37,20 @@ mod windows;
 pub(crate) mod scap_screen_capture;
 
 use crate::{
-    Action, AnyWindowHandle, App, AsyncWindowContext, BackgroundExecutor, Bounds,
-    DEFAULT_WINDOW_SIZE, DevicePixels, DispatchEventResult, Font, FontId, FontMetrics, FontRun,
-    ForegroundExecutor, GlyphId, GpuSpecs, ImageSource, Keymap, LineLayout, Pixels, PlatformInput,
-    Point, RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams, Scene, ShapedGlyph,
+    Action, AnyWindowHandle, App, AppMode, AssetBytes, AsyncWindowContext, BackgroundExecutor,
+    Bounds, DEFAULT_WINDOW_SIZE, DevicePixels, DispatchEventResult, Font, FontId, FontMetrics,
+    FontRun, ForegroundExecutor, GlyphId, GpuSpecs, ImageColorManagement, ImageSource, Keymap,
+    LineLayout, MainThreadBudget, MainThreadMetrics, Pixels, PlatformInput, Point, PowerState,
+    RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams, Scene, ShapedGlyph,
     ShapedRun, SharedString, Size, SvgRenderer, SystemWindowTab, Task, TaskLabel, TaskTiming,
-    ThreadTaskTimings, Window, WindowControlArea, hash, point, px, size,
+    ThreadTaskTimings, Window, WindowControlArea, decode_rgba8, decode_rgba8_frames, hash, point,
+    px, size,
 };
 use anyhow::Result;
 use async_task::Runnable;
 use futures::channel::oneshot;
+use image::Frame;
 use image::codecs::gif::GifDecoder;
-use image::{AnimationDecoder as _, Frame};
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use schemars::JsonSchema;
 use seahash::SeaHasher;
@@ -59,9 +62,11 @@ use std::io::Cursor;
 use std::ops;
 use std::time::{Duration, Instant};
 use std::{
+    ffi::c_void,
     fmt::{self, Debug},
     ops::Range,
     path::{Path, PathBuf},
+    ptr::NonNull,
     rc::Rc,
     sync::Arc,
 };
@@ -71,6 +76,7 @@ use uuid::Uuid;
 pub use app_menu::*;
 pub use keyboard::*;
 pub use keystroke::*;
+pub use spell_check::*;
 
 #[cfg(any(target_os = "linux", target_os = "freebsd"))]
 pub(crate) use linux::*;
@@ -84,9 +90,14 @@ pub(crate) use windows::*;
 
 #[cfg(all(target_os = "linux", feature = "wayland"))]
 pub use linux::layer_shell;
+#[cfg(target_os = "macos")]
+pub use mac::audio_unit;
 
 #[cfg(any(test, feature = "test-support"))]
-pub use test::{TestDispatcher, TestScreenCaptureSource, TestScreenCaptureStream};
+pub use test::{
+    ParkingAllowed, PendingTasks, ScheduledTask, TestDispatcher, TestScreenCaptureSource,
+    TestScreenCaptureStream,
+};
 
 /// Returns a background executor for the current platform.
 pub fn background_executor() -> BackgroundExecutor {
@@ -132,6 +143,35 @@ pub(crate) fn current_platform(_headless: bool) -> Rc<dyn Platform> {
     )
 }
 
+/// Release the platform's process-wide state, see [`crate::runtime::shutdown`].
+#[cfg(target_os = "macos")]
+pub(crate) fn shutdown_platform() {
+    unsafe { dispose_classes() }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn shutdown_platform() {
+    unregister_window_classes();
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) fn shutdown_platform() {}
+
+/// Release the state that the platform keeps in the calling thread's thread-locals, see
+/// [`crate::runtime::shutdown`].
+#[cfg(target_os = "macos")]
+pub(crate) fn release_platform_thread_state() {
+    audio_unit::release_thread_state();
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn release_platform_thread_state() {
+    release_host_hooks();
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) fn release_platform_thread_state() {}
+
 /// Return which compositor we're guessing we'll use.
 /// Does not attempt to connect to the given compositor
 #[cfg(any(target_os = "linux", target_os = "freebsd"))]
@@ -169,6 +209,23 @@ pub(crate) trait Platform: 'static {
     fn text_system(&self) -> Arc<dyn PlatformTextSystem>;
 
     fn run(&self, on_finish_launching: Box<dyn 'static + FnOnce()>);
+    /// Start the application inside an event loop that the host process runs, see
+    /// [`Application::attach`].
+    fn attach(&self, on_finish_launching: Box<dyn 'static + FnOnce()>);
+    /// Whether the process already runs the platform's event loop, such as when GPUI is loaded
+    /// into a plugin host, so [`Application::run`] has to attach to it rather than start one.
+    fn is_event_loop_running(&self) -> bool {
+        false
+    }
+    /// Process the work that's pending on the main thread without waiting for more, see
+    /// [`Application::pump`].
+    fn pump(&self);
+    /// A file descriptor that becomes readable when there's work pending on the main thread, see
+    /// [`AsyncApp::event_fd`](crate::AsyncApp::event_fd).
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    fn event_fd(&self) -> Option<std::os::fd::RawFd> {
+        None
+    }
     fn quit(&self);
     fn restart(&self, binary_path: Option<PathBuf>);
     fn activate(&self, ignoring_other_apps: bool);
@@ -276,6 +333,29 @@ pub(crate) trait Platform: 'static {
     fn keyboard_layout(&self) -> Box<dyn PlatformKeyboardLayout>;
     fn keyboard_mapper(&self) -> Rc<dyn PlatformKeyboardMapper>;
     fn on_keyboard_layout_change(&self, callback: Box<dyn FnMut()>);
+
+    /// The time since the last input event anywhere on the system, if the platform reports it.
+    fn system_idle_time(&self) -> Option<Duration> {
+        None
+    }
+
+    /// The current battery and thermal state of the system.
+    fn power_state(&self) -> PowerState {
+        PowerState::default()
+    }
+
+    /// The system's spell checker, if the platform has one.
+    fn spell_checker(&self) -> Option<Rc<dyn PlatformSpellChecker>> {
+        None
+    }
+
+    fn set_main_thread_budget(&self, _budget: MainThreadBudget) {}
+    fn main_thread_budget(&self) -> MainThreadBudget {
+        MainThreadBudget::default()
+    }
+    fn main_thread_metrics(&self) -> MainThreadMetrics {
+        MainThreadMetrics::default()
+    }
 }
 
 /// A handle to a platform's display, e.g. a monitor or laptop screen.
@@ -453,6 +533,24 @@ impl Tiling {
     }
 }
 
+/// The state of a window as managed by the platform's window manager, as returned by
+/// [`Window::window_state`](crate::Window::window_state).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum WindowState {
+    /// The window is shown at its own size and position
+    #[default]
+    Normal,
+    /// The window is minimized and not visible on screen (on Wayland, which doesn't report
+    /// minimizing, whenever the compositor has suspended the window)
+    Minimized,
+    /// The window is maximized to fill the display's work area
+    Maximized,
+    /// The window covers the entire display
+    Fullscreen,
+    /// Some of the window's edges are tiled against the display or other windows (Wayland and X11)
+    Tiled(Tiling),
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
 pub(crate) struct RequestFrameOptions {
     pub(crate) require_presentation: bool,
@@ -466,6 +564,45 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn window_bounds(&self) -> WindowBounds;
     fn content_size(&self) -> Size<Pixels>;
     fn resize(&mut self, size: Size<Pixels>);
+    /// Called by the host of an embedded window when the native window it provided was resized.
+    fn notify_embedded_resize(&mut self, size: Size<Pixels>) {
+        self.resize(size);
+    }
+    /// Called by the host of an embedded window when the native window it provided moved to a
+    /// display with a different scale factor. Platforms that detect this by themselves ignore it.
+    fn set_embedded_scale_factor(&mut self, _scale_factor: f32) {}
+    /// Give the window native keyboard focus, taking it from the host of an embedded window.
+    fn request_keyboard_focus(&self) {}
+    /// Hand native keyboard focus back to the host of an embedded window.
+    fn release_keyboard_focus(&self) {}
+    /// Release the host's native window of an embedded window right away, rather than once the
+    /// window is dropped, so that the host can destroy it.
+    fn detach_embedded(&self) {}
+    /// Move an embedded window into another native window provided by its host, keeping its
+    /// native surface and GPU resources.
+    fn reparent_embedded(&self, _raw_handle: raw_window_handle::RawWindowHandle) -> Result<()> {
+        Err(WindowOpenError::Unsupported(
+            "Moving embedded windows to another host window isn't supported on this platform"
+                .into(),
+        )
+        .into())
+    }
+    /// Take an embedded window out of the native window provided by its host and hide it,
+    /// without closing it, so that the host can destroy its window. The window is shown again
+    /// once it's moved into another native window with `reparent_embedded`.
+    fn park_embedded(&self) -> Result<()> {
+        Err(WindowOpenError::Unsupported(
+            "Parking embedded windows isn't supported on this platform".into(),
+        )
+        .into())
+    }
+    /// The state of the host's native window of an embedded window.
+    fn host_info(&self) -> Option<HostWindowInfo> {
+        None
+    }
+    /// Register a callback for when the state of the host's native window of an embedded window
+    /// changes, see `host_info`.
+    fn on_host_info_changed(&self, _callback: Box<dyn FnMut()>) {}
     fn scale_factor(&self) -> f32;
     fn appearance(&self) -> WindowAppearance;
     fn display(&self) -> Option<Rc<dyn PlatformDisplay>>;
@@ -490,6 +627,18 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn zoom(&self);
     fn toggle_fullscreen(&self);
     fn is_fullscreen(&self) -> bool;
+    fn is_minimized(&self) -> bool {
+        false
+    }
+    fn restore(&self) {
+        if self.is_minimized() {
+            self.activate();
+        } else if self.is_fullscreen() {
+            self.toggle_fullscreen();
+        } else if self.is_maximized() {
+            self.zoom();
+        }
+    }
     fn on_request_frame(&self, callback: Box<dyn FnMut(RequestFrameOptions)>);
     fn on_input(&self, callback: Box<dyn FnMut(PlatformInput) -> DispatchEventResult>);
     fn on_active_status_change(&self, callback: Box<dyn FnMut(bool)>);
@@ -502,6 +651,12 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn on_appearance_changed(&self, callback: Box<dyn FnMut()>);
     fn draw(&self, scene: &Scene);
     fn completed_frame(&self) {}
+    fn refresh_rate(&self) -> Option<f32> {
+        None
+    }
+    fn trigger_gpu_capture(&self) -> Result<()> {
+        anyhow::bail!("GPU capture is not supported on this platform")
+    }
     fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas>;
 
     // macOS specific methods
@@ -515,7 +670,28 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
         false
     }
     fn set_edited(&mut self, _edited: bool) {}
-    fn show_character_palette(&self) {}
+    /// Show the platform's emoji and symbol picker for the focused text input, returning whether
+    /// the platform has one.
+    fn show_character_palette(&self) -> bool {
+        false
+    }
+    /// Move the cursor to the given position in the window's content, returning whether the
+    /// platform supports it.
+    fn warp_cursor(&self, _position: Point<Pixels>) -> bool {
+        false
+    }
+    /// Keep delivering mouse events to the window while the cursor is outside of its bounds,
+    /// until `end_mouse_capture`, returning whether the platform supports it.
+    fn start_mouse_capture(&self) -> bool {
+        false
+    }
+    /// Stop delivering mouse events from outside of the window, see `start_mouse_capture`.
+    fn end_mouse_capture(&self) {}
+    /// Keep the window above other applications' windows, returning whether the platform
+    /// supports it.
+    fn set_always_on_top(&self, _always_on_top: bool) -> bool {
+        false
+    }
     fn titlebar_double_click(&self) {}
     fn on_move_tab_to_new_window(&self, _callback: Box<dyn FnMut()>) {}
     fn on_merge_all_windows(&self, _callback: Box<dyn FnMut()>) {}
@@ -597,6 +773,14 @@ pub trait PlatformDispatcher: Send + Sync {
 
 pub(crate) trait PlatformTextSystem: Send + Sync {
     fn add_fonts(&self, fonts: Vec<Cow<'static, [u8]>>) -> Result<()>;
+    fn add_font_bytes(&self, fonts: Vec<AssetBytes>) -> Result<()> {
+        self.add_fonts(
+            fonts
+                .into_iter()
+                .map(|font| Cow::Owned(font.to_vec()))
+                .collect(),
+        )
+    }
     fn all_font_names(&self) -> Vec<String>;
     fn font_id(&self, descriptor: &Font) -> Result<FontId>;
     fn font_metrics(&self, font_id: FontId) -> FontMetrics;
@@ -822,6 +1006,11 @@ pub(crate) trait PlatformAtlas: Send + Sync {
         build: &mut dyn FnMut() -> Result<Option<(Size<DevicePixels>, Cow<'a, [u8]>)>>,
     ) -> Result<Option<AtlasTile>>;
     fn remove(&self, key: &AtlasKey);
+    /// Replace the pixels of a region of the tile stored under the given key, with the region
+    /// relative to the tile's origin. Returns false if there is no such tile.
+    fn update(&self, key: &AtlasKey, region: Bounds<DevicePixels>, bytes: &[u8]) -> bool;
+    /// The number of textures the atlas currently holds on the GPU.
+    fn texture_count(&self) -> usize;
 }
 
 struct AtlasTextureList<T> {
@@ -996,7 +1185,11 @@ impl PlatformInputHandler {
 
     fn bounds_for_range(&mut self, range_utf16: Range<usize>) -> Option<Bounds<Pixels>> {
         self.cx
-            .update(|window, cx| self.handler.bounds_for_range(range_utf16, window, cx))
+            .update(|window, cx| {
+                let bounds = self.handler.bounds_for_range(range_utf16, window, cx)?;
+                let zoom = window.content_zoom();
+                Some(bounds.map(|c| c * zoom))
+            })
             .ok()
             .flatten()
     }
@@ -1012,7 +1205,7 @@ impl PlatformInputHandler {
 
     pub fn selected_bounds(&mut self, window: &mut Window, cx: &mut App) -> Option<Bounds<Pixels>> {
         let selection = self.handler.selected_text_range(true, window, cx)?;
-        self.handler.bounds_for_range(
+        let bounds = self.handler.bounds_for_range(
             if selection.reversed {
                 selection.range.start..selection.range.start
             } else {
@@ -1020,13 +1213,18 @@ impl PlatformInputHandler {
             },
             window,
             cx,
-        )
+        )?;
+        let zoom = window.content_zoom();
+        Some(bounds.map(|c| c * zoom))
     }
 
     #[allow(unused)]
     pub fn character_index_for_point(&mut self, point: Point<Pixels>) -> Option<usize> {
         self.cx
-            .update(|window, cx| self.handler.character_index_for_point(point, window, cx))
+            .update(|window, cx| {
+                let point = point.map(|c| c / window.content_zoom());
+                self.handler.character_index_for_point(point, window, cx)
+            })
             .ok()
             .flatten()
     }
@@ -1203,6 +1401,51 @@ pub struct WindowOptions {
     /// This is useful for embedding GPUI in plugins (VST, CLAP, etc.) or other host applications.
     /// When set, GPUI will not create a new OS window, but will render into the provided handle.
     pub raw_window_handle: Option<raw_window_handle::RawWindowHandle>,
+
+    /// The display connection that `raw_window_handle` belongs to. Wayland surfaces can only be
+    /// used on the connection that created them, so this is required to embed on Wayland.
+    /// On X11 it selects the screen the host window lives on.
+    pub raw_display_handle: Option<raw_window_handle::RawDisplayHandle>,
+
+    /// How to render into `raw_window_handle`. Only used on Windows.
+    pub embedding_mode: EmbeddingMode,
+
+    /// The host's GPU device for the embedded window to render on, instead of GPUI's own. Only
+    /// used on Windows.
+    pub host_gpu_device: Option<HostGpuDevice>,
+
+    /// What drives the window's frames. See [`FrameDriver`].
+    pub frame_driver: FrameDriver,
+
+    /// The zoom factor applied to the window's content, where `1.0` renders at the natural size.
+    /// Layout is performed in zoomed pixels, so the window's viewport shrinks as the zoom grows.
+    pub content_zoom: f32,
+}
+
+/// The reason that a window couldn't be opened, returned by [`App::try_open_window`].
+#[derive(Debug, thiserror::Error)]
+pub enum WindowOpenError {
+    /// The native handle passed with [`WindowOptions::raw_window_handle`] or
+    /// [`WindowOptions::raw_display_handle`] is of the wrong kind for this platform, or doesn't
+    /// refer to a live window or display.
+    #[error("invalid window handle: {0}")]
+    InvalidHandle(String),
+    /// The GPU renderer for the window couldn't be created.
+    #[error("failed to initialize the GPU renderer: {0:#}")]
+    GpuInit(anyhow::Error),
+    /// The platform can't open the requested kind of window.
+    #[error("unsupported window: {0}")]
+    Unsupported(String),
+    /// The platform failed to open the window for another reason.
+    #[error(transparent)]
+    Platform(anyhow::Error),
+}
+
+impl WindowOpenError {
+    /// Recovers the [`WindowOpenError`] that a platform returned inside an [`anyhow::Error`].
+    pub(crate) fn from_platform(error: anyhow::Error) -> Self {
+        error.downcast().unwrap_or_else(Self::Platform)
+    }
 }
 
 /// The variables that can be configured when creating a new window
@@ -1255,6 +1498,187 @@ pub(crate) struct WindowParams {
 
     /// An existing native window handle to attach to instead of creating a new window
     pub raw_window_handle: Option<raw_window_handle::RawWindowHandle>,
+
+    /// The display connection that `raw_window_handle` belongs to
+    #[cfg_attr(
+        not(all(
+            any(target_os = "linux", target_os = "freebsd"),
+            any(feature = "wayland", feature = "x11")
+        )),
+        allow(dead_code)
+    )]
+    pub raw_display_handle: Option<raw_window_handle::RawDisplayHandle>,
+
+    /// How to render into `raw_window_handle`
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub embedding_mode: EmbeddingMode,
+
+    /// The host's GPU device to render on
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub host_gpu_device: Option<HostGpuDevice>,
+
+    /// The background the window opens with
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub background_appearance: WindowBackgroundAppearance,
+
+    /// What drives the window's frames
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub frame_driver: FrameDriver,
+
+    /// Whether the window's application is embedded in its host's
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub app_mode: AppMode,
+}
+
+/// How an embedded window renders into the native window handle provided by its host.
+/// See [`WindowOptions::raw_window_handle`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum EmbeddingMode {
+    /// Render directly into the host's window. GPUI subclasses the window to handle its own
+    /// messages on top of the host's window procedure, leaving the window's user data untouched.
+    #[default]
+    Attach,
+    /// Create a child window that fills the host's window, and render into that. The host's
+    /// window procedure and user data are left untouched, and the child window follows the host's
+    /// size without subclassing it. See [`WindowOptions::for_embedded_child_window`].
+    Child,
+}
+
+/// A GPU device created by the host of an embedded window, for the window to render on instead
+/// of creating its own, which saves synchronizing the host's rendering with GPUI's across devices.
+/// See [`WindowOptions::with_host_gpu_device`].
+///
+/// GPUI keeps its own reference to the device. It renders from the thread that runs the app, so
+/// the host must not use the device's immediate context from other threads at the same time. If
+/// the device is lost, the window moves to GPUI's own device, since only the host can recreate
+/// its own.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HostGpuDevice {
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub(crate) api: HostGpuApi,
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub(crate) device: NonNull<c_void>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum HostGpuApi {
+    Direct3D11,
+    Direct3D12,
+}
+
+impl HostGpuDevice {
+    /// The host's `ID3D11Device`, given as its COM interface pointer. The window renders on the
+    /// device and its immediate context. The device must have been created with
+    /// `D3D11_CREATE_DEVICE_BGRA_SUPPORT`.
+    ///
+    /// # Safety
+    /// `device` must point to a live `ID3D11Device`.
+    pub unsafe fn direct3d11(device: NonNull<c_void>) -> Self {
+        Self {
+            api: HostGpuApi::Direct3D11,
+            device,
+        }
+    }
+
+    /// The host's `ID3D12Device`, given as its COM interface pointer. GPUI renders with Direct3D
+    /// 11, so the window renders on a device that GPUI creates on the same adapter as the host's,
+    /// which still saves copying between GPUs.
+    ///
+    /// # Safety
+    /// `device` must point to a live `ID3D12Device`.
+    pub unsafe fn direct3d12(device: NonNull<c_void>) -> Self {
+        Self {
+            api: HostGpuApi::Direct3D12,
+            device,
+        }
+    }
+}
+
+/// The state of the host's native window that an embedded window renders into, see
+/// [`Window::host_info`]. Plugins can use it to pause rendering while the host's window isn't
+/// shown, or to fit their layout to the space the host gives them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HostWindowInfo {
+    /// The size of the host's window, or view on macOS, that the embedded window fills.
+    pub client_size: Size<Pixels>,
+    /// The scale factor of the display the host's window is on.
+    pub scale_factor: f32,
+    /// Whether the host's window and its ancestors are shown. Hosts often hide the windows of
+    /// plugins whose editors are closed, instead of destroying them.
+    pub visible: bool,
+    /// Whether the host's top-level window is minimized.
+    pub minimized: bool,
+    /// Whether the host's top-level window is the foreground window, in front of the windows of
+    /// other applications and receiving keyboard input.
+    pub foreground: bool,
+}
+
+/// What drives the frames of a window. See [`WindowOptions::frame_driver`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum FrameDriver {
+    /// The platform's vsync source, such as a display link on macOS, requests frames.
+    #[default]
+    Platform,
+    /// The window only renders when [`AnyWindowHandle::render_frame`] is called, such as from an
+    /// embedded window's host on each tick of its idle or timer callback. Many hosts never let
+    /// vsync callbacks reach the windows of their plugins, which would otherwise stay blank.
+    External,
+}
+
+/// The sizes an embedded window accepts, which hosts negotiate with through
+/// [`Window::adjust_embedded_size`], like the `adjust_size` calls of plugin APIs such as CLAP and
+/// VST3. Set with [`Window::set_size_constraints`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SizeConstraints {
+    /// The size the window would like to open at, if any.
+    pub preferred_size: Option<Size<Pixels>>,
+    /// The smallest size the window can be laid out at.
+    pub min_size: Option<Size<Pixels>>,
+    /// The largest size the window can be laid out at.
+    pub max_size: Option<Size<Pixels>>,
+    /// The ratio of width to height the window keeps, if any.
+    pub aspect_ratio: Option<f32>,
+    /// Whether the host may resize the window at all.
+    pub resizable: bool,
+}
+
+impl Default for SizeConstraints {
+    fn default() -> Self {
+        Self {
+            preferred_size: None,
+            min_size: None,
+            max_size: None,
+            aspect_ratio: None,
+            resizable: true,
+        }
+    }
+}
+
+impl SizeConstraints {
+    /// Return the size closest to the given one that satisfies these constraints. The aspect
+    /// ratio is kept by adjusting the height, unless that would leave the allowed heights.
+    /// This ignores [`SizeConstraints::resizable`].
+    pub fn constrain(&self, size: Size<Pixels>) -> Size<Pixels> {
+        let clamp_width = |width: Pixels| {
+            let width = self.min_size.map_or(width, |min| width.max(min.width));
+            self.max_size.map_or(width, |max| width.min(max.width))
+        };
+        let clamp_height = |height: Pixels| {
+            let height = self.min_size.map_or(height, |min| height.max(min.height));
+            self.max_size.map_or(height, |max| height.min(max.height))
+        };
+
+        let width = clamp_width(size.width);
+        let height = clamp_height(size.height);
+        match self.aspect_ratio {
+            Some(ratio) if ratio > 0. => {
+                let height = clamp_height(px(width.0 / ratio));
+                let width = clamp_width(height * ratio);
+                Size { width, height }
+            }
+            _ => Size { width, height },
+        }
+    }
 }
 
 /// Represents the status of how a window should be opened.
@@ -1314,6 +1738,11 @@ impl Default for WindowOptions {
             window_decorations: None,
             tabbing_identifier: None,
             raw_window_handle: None,
+            raw_display_handle: None,
+            embedding_mode: EmbeddingMode::default(),
+            host_gpu_device: None,
+            frame_driver: FrameDriver::default(),
+            content_zoom: 1.0,
         }
     }
 }
@@ -1457,11 +1886,25 @@ impl PromptButton {
         PromptButton::Cancel(label.into())
     }
 
-    #[allow(dead_code)]
     pub(crate) fn is_cancel(&self) -> bool {
         matches!(self, PromptButton::Cancel(_))
     }
 
+    /// The index of the answer activated by pressing enter: the first [`PromptButton::Ok`],
+    /// or the first answer that isn't a cancel button.
+    pub fn default_index(answers: &[PromptButton]) -> Option<usize> {
+        answers
+            .iter()
+            .position(|answer| matches!(answer, PromptButton::Ok(_)))
+            .or_else(|| answers.iter().position(|answer| !answer.is_cancel()))
+    }
+
+    /// The index of the answer activated by pressing escape: the first
+    /// [`PromptButton::Cancel`].
+    pub fn cancel_index(answers: &[PromptButton]) -> Option<usize> {
+        answers.iter().position(PromptButton::is_cancel)
+    }
+
     /// Returns the label of the button
     pub fn label(&self) -> &SharedString {
         match self {
@@ -1834,35 +2277,40 @@ impl Image {
 
     /// Convert the clipboard image to an `ImageData` object.
     pub fn to_image_data(&self, svg_renderer: SvgRenderer) -> Result<Arc<RenderImage>> {
-        fn frames_for_image(
-            bytes: &[u8],
-            format: image::ImageFormat,
-        ) -> Result<SmallVec<[Frame; 1]>> {
-            let mut data = image::load_from_memory_with_format(bytes, format)?.into_rgba8();
-
-            // Convert from RGBA to BGRA.
-            for pixel in data.chunks_exact_mut(4) {
-                pixel.swap(0, 2);
-            }
+        self.decode(svg_renderer, ImageColorManagement::default())
+    }
 
-            Ok(SmallVec::from_elem(Frame::new(data), 1))
-        }
+    pub(crate) fn decode(
+        &self,
+        svg_renderer: SvgRenderer,
+        color_management: ImageColorManagement,
+    ) -> Result<Arc<RenderImage>> {
+        let frames_for_image =
+            |bytes: &[u8], format: image::ImageFormat| -> Result<SmallVec<[Frame; 1]>> {
+                let decoder =
+                    image::ImageReader::with_format(Cursor::new(bytes), format).into_decoder()?;
+                let mut data = decode_rgba8(decoder, color_management)?;
+
+                // Convert from RGBA to BGRA.
+                for pixel in data.chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                }
+
+                Ok(SmallVec::from_elem(Frame::new(data), 1))
+            };
 
         let frames = match self.format {
             ImageFormat::Gif => {
                 let decoder = GifDecoder::new(Cursor::new(&self.bytes))?;
-                let mut frames = SmallVec::new();
-
-                for frame in decoder.into_frames() {
-                    let mut frame = frame?;
+                let mut frames = decode_rgba8_frames(decoder, color_management)?;
+                for frame in &mut frames {
                     // Convert from RGBA to BGRA.
                     for pixel in frame.buffer_mut().chunks_exact_mut(4) {
                         pixel.swap(0, 2);
                     }
-                    frames.push(frame);
                 }
 
-                frames
+                SmallVec::from_vec(frames)
             }
             ImageFormat::Png => frames_for_image(&self.bytes, image::ImageFormat::Png)?,
             ImageFormat::Jpeg => frames_for_image(&self.bytes, image::ImageFormat::Jpeg)?,
@@ -101,6 +101,23 @@ impl PlatformAtlas for BladeAtlas {
         }
     }
 
+    fn update(&self, key: &AtlasKey, region: Bounds<DevicePixels>, bytes: &[u8]) -> bool {
+        let mut lock = self.0.lock();
+        let Some(tile) = lock.tiles_by_key.get(key).cloned() else {
+            return false;
+        };
+        let region = Bounds::new(tile.bounds.origin + region.origin, region.size);
+        lock.upload_texture(tile.texture_id, region, bytes);
+        true
+    }
+
+    fn texture_count(&self) -> usize {
+        let lock = self.0.lock();
+        let monochrome = &lock.storage.monochrome_textures;
+        let polychrome = &lock.storage.polychrome_textures;
+        monochrome.textures.iter().flatten().count() + polychrome.textures.iter().flatten().count()
+    }
+
     fn remove(&self, key: &AtlasKey) {
         let mut lock = self.0.lock();
 
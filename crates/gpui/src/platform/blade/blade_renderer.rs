@@ -641,6 +641,11 @@ impl BladeRenderer {
         }
     }
 
+    #[cfg(target_os = "macos")]
+    pub fn capture_next_frame(&mut self) -> anyhow::Result<()> {
+        anyhow::bail!("GPU capture is not supported by the Blade renderer")
+    }
+
     pub fn draw(&mut self, scene: &Scene) {
         self.command_encoder.start();
         self.atlas.before_frame(&mut self.command_encoder);
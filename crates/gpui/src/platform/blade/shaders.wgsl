@@ -1198,7 +1198,7 @@ fn fs_mono_sprite(input: MonoSpriteVarying) -> @location(0) vec4<f32> {
 
 struct PolychromeSprite {
     order: u32,
-    pad: u32,
+    peak_count: u32,
     grayscale: u32,
     opacity: f32,
     bounds: Bounds,
@@ -1240,6 +1240,9 @@ fn fs_poly_sprite(input: PolySpriteVarying) -> @location(0) vec4<f32> {
     let distance = quad_sdf(input.position.xy, sprite.bounds, sprite.corner_radii);
 
     var color = sample;
+    if (sprite.peak_count > 0u) {
+        color = waveform_color(input.position.xy, sprite);
+    }
     if ((sprite.grayscale & 0xFFu) != 0u) {
         let grayscale = dot(color.rgb, GRAYSCALE_FACTORS);
         color = vec4<f32>(vec3<f32>(grayscale), sample.a);
@@ -1247,6 +1250,46 @@ fn fs_poly_sprite(input: PolySpriteVarying) -> @location(0) vec4<f32> {
     return blend_color(color, sprite.opacity * saturate(0.5 - distance));
 }
 
+fn decode_waveform_value(high: f32, low: f32) -> f32 {
+    let value = round(high * 255.0) * 256.0 + round(low * 255.0);
+    return value / 65535.0 * 2.0 - 1.0;
+}
+
+// Reduces the peaks of a waveform tile that fall within this pixel's column to their overall
+// extent, returning the waveform's color if the pixel lies within it. See
+// `Window::paint_waveform` for the layout of the tile.
+fn waveform_color(position: vec2<f32>, sprite: PolychromeSprite) -> vec4<f32> {
+    let origin = sprite.tile.bounds.origin;
+    let capacity = u32(sprite.tile.bounds.size.x) - 2u;
+    let color = textureLoad(t_sprite, origin, 0);
+    let header = textureLoad(t_sprite, origin + vec2<i32>(1, 0), 0);
+    let oldest = u32(round(header.r * 255.0)) * 256u + u32(round(header.g * 255.0));
+
+    let count = f32(sprite.peak_count);
+    let left = (position.x - 0.5 - sprite.bounds.origin.x) / sprite.bounds.size.x;
+    let right = (position.x + 0.5 - sprite.bounds.origin.x) / sprite.bounds.size.x;
+    let first = u32(clamp(floor(left * count), 0.0, count - 1.0));
+    let last = u32(clamp(ceil(right * count) - 1.0, f32(first), count - 1.0));
+
+    var minimum = 1.0;
+    var maximum = -1.0;
+    for (var peak = first; peak <= last; peak += 1u) {
+        let slot = (oldest + peak) % capacity;
+        let texel = textureLoad(t_sprite, origin + vec2<i32>(i32(2u + slot), 0), 0);
+        minimum = min(minimum, decode_waveform_value(texel.r, texel.g));
+        maximum = max(maximum, decode_waveform_value(texel.b, texel.a));
+    }
+
+    // Values run from 1 at the top of the bounds to -1 at the bottom. Every column covers at
+    // least one pixel, so that silence still shows as a line.
+    let half_pixel = 1.0 / sprite.bounds.size.y;
+    let value = 1.0 - 2.0 * (position.y - sprite.bounds.origin.y) / sprite.bounds.size.y;
+    if (value - half_pixel > maximum || value + half_pixel < minimum) {
+        return vec4<f32>(0.0);
+    }
+    return color;
+}
+
 // --- surfaces --- //
 
 struct SurfaceParams {
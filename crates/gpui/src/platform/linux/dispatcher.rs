@@ -22,7 +22,6 @@ pub(crate) struct LinuxDispatcher {
     main_sender: Sender<RunnableVariant>,
     timer_sender: Sender<TimerAfter>,
     background_sender: flume::Sender<RunnableVariant>,
-    _background_threads: Vec<thread::JoinHandle<()>>,
     main_thread_id: thread::ThreadId,
 }
 
@@ -93,8 +92,13 @@ impl LinuxDispatcher {
 
                 let handle = event_loop.handle();
                 let timer_handle = event_loop.handle();
+                let signal = event_loop.get_signal();
                 handle
                     .insert_source(timer_channel, move |e, _, _| {
+                        // Stop once the dispatcher is dropped, so that the runtime can join this thread.
+                        if let channel::Event::Closed = e {
+                            signal.stop();
+                        }
                         if let channel::Event::Msg(timer) = e {
                             // This has to be in an option to satisfy the borrow checker. The callback below should only be scheduled once.
                             let mut runnable = Some(timer.runnable);
@@ -147,12 +151,14 @@ impl LinuxDispatcher {
             .unwrap();
 
         background_threads.push(timer_thread);
+        for thread in background_threads {
+            crate::runtime::register_background_thread(thread);
+        }
 
         Self {
             main_sender,
             timer_sender,
             background_sender,
-            _background_threads: background_threads,
             main_thread_id: thread::current().id(),
         }
     }
@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::os::fd::{AsFd as _, AsRawFd as _, RawFd};
 use std::rc::Rc;
 
 use calloop::{EventLoop, LoopHandle};
@@ -8,7 +9,7 @@ use crate::platform::linux::LinuxClient;
 use crate::platform::{LinuxCommon, PlatformWindow};
 use crate::{
     AnyWindowHandle, CursorStyle, DisplayId, LinuxKeyboardLayout, PlatformDisplay,
-    PlatformKeyboardLayout, WindowParams,
+    PlatformKeyboardLayout, WindowOpenError, WindowParams,
 };
 
 pub struct HeadlessClientState {
@@ -99,7 +100,10 @@ impl LinuxClient for HeadlessClient {
         _handle: AnyWindowHandle,
         _params: WindowParams,
     ) -> anyhow::Result<Box<dyn PlatformWindow>> {
-        anyhow::bail!("neither DISPLAY nor WAYLAND_DISPLAY is set. You can run in headless mode");
+        Err(WindowOpenError::Unsupported(
+            "neither DISPLAY nor WAYLAND_DISPLAY is set. You can run in headless mode".into(),
+        )
+        .into())
     }
 
     fn compositor_name(&self) -> &'static str {
@@ -134,4 +138,21 @@ impl LinuxClient for HeadlessClient {
 
         event_loop.run(None, &mut self.clone(), |_| {}).log_err();
     }
+
+    fn pump(&self) {
+        // The event loop is taken while it's running or being pumped already.
+        let Some(mut event_loop) = self.0.borrow_mut().event_loop.take() else {
+            return;
+        };
+        event_loop
+            .dispatch(Some(std::time::Duration::ZERO), &mut self.clone())
+            .log_err();
+        self.0.borrow_mut().event_loop = Some(event_loop);
+    }
+
+    fn event_fd(&self) -> Option<RawFd> {
+        let state = self.0.borrow();
+        let event_loop = state.event_loop.as_ref()?;
+        Some(event_loop.as_fd().as_raw_fd())
+    }
 }
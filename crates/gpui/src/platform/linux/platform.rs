@@ -1,5 +1,6 @@
 use std::{
     env,
+    os::fd::RawFd,
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
@@ -25,8 +26,8 @@ use crate::{
     Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DisplayId,
     ForegroundExecutor, Keymap, LinuxDispatcher, Menu, MenuItem, OwnedMenu, PathPromptOptions,
     Pixels, Platform, PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper,
-    PlatformTextSystem, PlatformWindow, Point, Result, RunnableVariant, Task, WindowAppearance,
-    WindowParams, px,
+    PlatformTextSystem, PlatformWindow, Point, PowerState, Result, RunnableVariant, Task,
+    WindowAppearance, WindowParams, px,
 };
 
 #[cfg(any(feature = "wayland", feature = "x11"))]
@@ -73,6 +74,8 @@ pub trait LinuxClient {
     fn active_window(&self) -> Option<AnyWindowHandle>;
     fn window_stack(&self) -> Option<Vec<AnyWindowHandle>>;
     fn run(&self);
+    fn pump(&self);
+    fn event_fd(&self) -> Option<RawFd>;
 
     #[cfg(any(feature = "wayland", feature = "x11"))]
     fn window_identifier(
@@ -159,6 +162,10 @@ impl<P: LinuxClient + 'static> Platform for P {
         self.with_common(|common| common.callbacks.keyboard_layout_change = Some(callback));
     }
 
+    fn power_state(&self) -> PowerState {
+        power_supply_state()
+    }
+
     fn run(&self, on_finish_launching: Box<dyn FnOnce()>) {
         on_finish_launching();
 
@@ -170,6 +177,18 @@ impl<P: LinuxClient + 'static> Platform for P {
         }
     }
 
+    fn attach(&self, on_finish_launching: Box<dyn FnOnce()>) {
+        on_finish_launching();
+    }
+
+    fn pump(&self) {
+        LinuxClient::pump(self);
+    }
+
+    fn event_fd(&self) -> Option<RawFd> {
+        LinuxClient::event_fd(self)
+    }
+
     fn quit(&self) {
         self.with_common(|common| common.signal.stop());
     }
@@ -657,6 +676,48 @@ pub(super) fn reveal_path_internal(
         .detach();
 }
 
+/// Read the power state from the kernel's power supply class, which is also what UPower
+/// reports from. Thermal pressure and power profiles aren't exposed there.
+fn power_supply_state() -> PowerState {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return PowerState::default();
+    };
+    let read = |path: &Path, attribute: &str| {
+        std::fs::read_to_string(path.join(attribute))
+            .map(|value| value.trim().to_string())
+            .ok()
+    };
+
+    let mut mains_online = false;
+    let mut discharging = false;
+    let mut battery_level = None;
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        match read(&path, "type").as_deref() {
+            Some("Mains") => mains_online |= read(&path, "online").as_deref() == Some("1"),
+            Some("Battery") => {
+                // Skip batteries of peripherals such as mice and headsets.
+                if read(&path, "scope").as_deref() == Some("Device") {
+                    continue;
+                }
+                discharging |= read(&path, "status").as_deref() == Some("Discharging");
+                if battery_level.is_none() {
+                    battery_level = read(&path, "capacity")
+                        .and_then(|capacity| capacity.parse::<f32>().ok())
+                        .map(|capacity| (capacity / 100.).clamp(0., 1.));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    PowerState {
+        on_battery: discharging && !mains_online,
+        battery_level,
+        ..Default::default()
+    }
+}
+
 #[allow(unused)]
 pub(super) fn is_within_click_distance(a: Point<Pixels>, b: Point<Pixels>) -> bool {
     let diff = a - b;
@@ -1,7 +1,8 @@
 use crate::{
-    Bounds, DevicePixels, Font, FontFeatures, FontId, FontMetrics, FontRun, FontStyle, FontWeight,
-    GlyphId, LineLayout, Pixels, PlatformTextSystem, Point, RenderGlyphParams, SUBPIXEL_VARIANTS_X,
-    SUBPIXEL_VARIANTS_Y, ShapedGlyph, ShapedRun, SharedString, Size, point, size,
+    AssetBytes, Bounds, DevicePixels, Font, FontFeatures, FontId, FontMetrics, FontRun, FontStyle,
+    FontWeight, GlyphId, LineLayout, Pixels, PlatformTextSystem, Point, RenderGlyphParams,
+    SUBPIXEL_VARIANTS_X, SUBPIXEL_VARIANTS_Y, ShapedGlyph, ShapedRun, SharedString, Size, point,
+    size,
 };
 use anyhow::{Context as _, Ok, Result};
 use collections::HashMap;
@@ -76,6 +77,11 @@ impl PlatformTextSystem for CosmicTextSystem {
         self.0.write().add_fonts(fonts)
     }
 
+    fn add_font_bytes(&self, fonts: Vec<AssetBytes>) -> Result<()> {
+        self.0.write().add_font_bytes(fonts);
+        Ok(())
+    }
+
     fn all_font_names(&self) -> Vec<String> {
         let mut result = self
             .0
@@ -196,7 +202,11 @@ impl CosmicTextSystemState {
         for bytes in fonts {
             match bytes {
                 Cow::Borrowed(embedded_font) => {
-                    db.load_font_data(embedded_font.to_vec());
+                    // Borrowed fonts live for the rest of the process, e.g. when embedded in the
+                    // binary, so they don't need to be copied.
+                    db.load_font_source(cosmic_text::fontdb::Source::Binary(Arc::new(
+                        embedded_font,
+                    )));
                 }
                 Cow::Owned(bytes) => {
                     db.load_font_data(bytes);
@@ -206,6 +216,13 @@ impl CosmicTextSystemState {
         Ok(())
     }
 
+    fn add_font_bytes(&mut self, fonts: Vec<AssetBytes>) {
+        let db = self.font_system.db_mut();
+        for font in fonts {
+            db.load_font_source(cosmic_text::fontdb::Source::Binary(Arc::new(font)));
+        }
+    }
+
     #[profiling::function]
     fn load_family(
         &mut self,
@@ -1,12 +1,16 @@
 use std::{
     cell::{RefCell, RefMut},
+    ffi::c_void,
     hash::Hash,
-    os::fd::{AsRawFd, BorrowedFd},
+    os::fd::{AsFd as _, AsRawFd, BorrowedFd, RawFd},
     path::PathBuf,
+    ptr::NonNull,
     rc::{Rc, Weak},
     time::{Duration, Instant},
 };
 
+use anyhow::Context as _;
+
 use ashpd::WindowIdentifier;
 use calloop::{
     EventLoop, LoopHandle,
@@ -16,9 +20,10 @@ use calloop_wayland_source::WaylandSource;
 use collections::HashMap;
 use filedescriptor::Pipe;
 use http_client::Url;
+use raw_window_handle as rwh;
 use smallvec::SmallVec;
 use util::ResultExt;
-use wayland_backend::client::ObjectId;
+use wayland_backend::client::{Backend, ObjectId};
 use wayland_backend::protocol::WEnum;
 use wayland_client::event_created_child;
 use wayland_client::globals::{GlobalList, GlobalListContents, registry_queue_init};
@@ -33,7 +38,7 @@ use wayland_client::{
     Connection, Dispatch, Proxy, QueueHandle, delegate_noop,
     protocol::{
         wl_buffer, wl_compositor, wl_keyboard, wl_pointer, wl_registry, wl_seat, wl_shm,
-        wl_shm_pool, wl_surface,
+        wl_shm_pool, wl_subcompositor, wl_subsurface, wl_surface,
     },
 };
 use wayland_protocols::wp::cursor_shape::v1::client::{
@@ -77,7 +82,7 @@ use crate::{
     LinuxKeyboardLayout, Modifiers, ModifiersChangedEvent, MouseButton, MouseDownEvent,
     MouseExitEvent, MouseMoveEvent, MouseUpEvent, NavigationDirection, Pixels, PlatformDisplay,
     PlatformInput, PlatformKeyboardLayout, Point, SCROLL_LINES, ScrollDelta, ScrollWheelEvent,
-    Size, TouchPhase, WindowParams, point, px, size,
+    Size, TouchPhase, WindowOpenError, WindowParams, point, px, size, trace_embedded,
 };
 use crate::{
     LinuxDispatcher, RunnableVariant, TaskTiming,
@@ -108,6 +113,7 @@ pub struct Globals {
     pub qh: QueueHandle<WaylandClientStatePtr>,
     pub activation: Option<xdg_activation_v1::XdgActivationV1>,
     pub compositor: wl_compositor::WlCompositor,
+    pub subcompositor: Option<wl_subcompositor::WlSubcompositor>,
     pub cursor_shape_manager: Option<wp_cursor_shape_manager_v1::WpCursorShapeManagerV1>,
     pub data_device_manager: Option<wl_data_device_manager::WlDataDeviceManager>,
     pub primary_selection_manager:
@@ -142,6 +148,7 @@ impl Globals {
                     (),
                 )
                 .unwrap(),
+            subcompositor: globals.bind(&qh, 1..=1, ()).ok(),
             cursor_shape_manager: globals.bind(&qh, 1..=1, ()).ok(),
             data_device_manager: globals
                 .bind(
@@ -153,7 +160,7 @@ impl Globals {
             primary_selection_manager: globals.bind(&qh, 1..=1, ()).ok(),
             shm: globals.bind(&qh, 1..=1, ()).unwrap(),
             seat,
-            wm_base: globals.bind(&qh, 2..=5, ()).unwrap(),
+            wm_base: globals.bind(&qh, 2..=6, ()).unwrap(),
             viewporter: globals.bind(&qh, 1..=1, ()).ok(),
             fractional_scale_manager: globals.bind(&qh, 1..=1, ()).ok(),
             decoration_manager: globals.bind(&qh, 1..=1, ()).ok(),
@@ -447,6 +454,40 @@ fn wl_output_version(version: u32) -> u32 {
     version.clamp(WL_OUTPUT_MIN_VERSION, WL_OUTPUT_MAX_VERSION)
 }
 
+#[allow(clippy::mutable_key_type)]
+fn bind_seat_and_outputs(
+    globals: &GlobalList,
+    qh: &QueueHandle<WaylandClientStatePtr>,
+) -> (Option<wl_seat::WlSeat>, HashMap<ObjectId, InProgressOutput>) {
+    let mut seat: Option<wl_seat::WlSeat> = None;
+    let mut in_progress_outputs = HashMap::default();
+    globals.contents().with_list(|list| {
+        for global in list {
+            match &global.interface[..] {
+                "wl_seat" => {
+                    seat = Some(globals.registry().bind::<wl_seat::WlSeat, _, _>(
+                        global.name,
+                        wl_seat_version(global.version),
+                        qh,
+                        (),
+                    ));
+                }
+                "wl_output" => {
+                    let output = globals.registry().bind::<wl_output::WlOutput, _, _>(
+                        global.name,
+                        wl_output_version(global.version),
+                        qh,
+                        (),
+                    );
+                    in_progress_outputs.insert(output.id(), InProgressOutput::default());
+                }
+                _ => {}
+            }
+        }
+    });
+    (seat, in_progress_outputs)
+}
+
 impl WaylandClient {
     pub(crate) fn new() -> Self {
         let conn = Connection::connect_to_env().unwrap();
@@ -455,33 +496,8 @@ impl WaylandClient {
             registry_queue_init::<WaylandClientStatePtr>(&conn).unwrap();
         let qh = event_queue.handle();
 
-        let mut seat: Option<wl_seat::WlSeat> = None;
         #[allow(clippy::mutable_key_type)]
-        let mut in_progress_outputs = HashMap::default();
-        globals.contents().with_list(|list| {
-            for global in list {
-                match &global.interface[..] {
-                    "wl_seat" => {
-                        seat = Some(globals.registry().bind::<wl_seat::WlSeat, _, _>(
-                            global.name,
-                            wl_seat_version(global.version),
-                            &qh,
-                            (),
-                        ));
-                    }
-                    "wl_output" => {
-                        let output = globals.registry().bind::<wl_output::WlOutput, _, _>(
-                            global.name,
-                            wl_output_version(global.version),
-                            &qh,
-                            (),
-                        );
-                        in_progress_outputs.insert(output.id(), InProgressOutput::default());
-                    }
-                    _ => {}
-                }
-            }
-        });
+        let (seat, in_progress_outputs) = bind_seat_and_outputs(&globals, &qh);
 
         let event_loop = EventLoop::<WaylandClientStatePtr>::try_new().unwrap();
 
@@ -656,6 +672,129 @@ impl WaylandClient {
 
         Self(state)
     }
+
+    fn open_embedded_window(
+        &self,
+        handle: AnyWindowHandle,
+        params: WindowParams,
+        raw_handle: rwh::RawWindowHandle,
+    ) -> anyhow::Result<Box<dyn PlatformWindow>> {
+        let rwh::RawWindowHandle::Wayland(window_handle) = raw_handle else {
+            return Err(WindowOpenError::InvalidHandle(format!(
+                "Expected a Wayland window handle for Wayland, got {raw_handle:?}"
+            ))
+            .into());
+        };
+        let Some(rwh::RawDisplayHandle::Wayland(display_handle)) = params.raw_display_handle else {
+            return Err(WindowOpenError::InvalidHandle(
+                "Embedding on Wayland requires the host's display handle, \
+                see WindowOptions::with_raw_display_handle"
+                    .into(),
+            )
+            .into());
+        };
+
+        let conn = self.attach_to_host_display(display_handle.display)?;
+        // Safety: the host keeps its surface alive while GPUI is embedded in it.
+        let host_surface_id = unsafe {
+            ObjectId::from_ptr(
+                wl_surface::WlSurface::interface(),
+                window_handle.surface.as_ptr().cast(),
+            )
+        }
+        .map_err(|_| WindowOpenError::InvalidHandle("Invalid Wayland surface handle".into()))?;
+        let host_surface = wl_surface::WlSurface::from_id(&conn, host_surface_id)
+            .map_err(|_| WindowOpenError::InvalidHandle("Invalid Wayland surface handle".into()))?;
+
+        let mut state = self.0.borrow_mut();
+        let (window, surface_id) = WaylandWindow::new_embedded(
+            handle,
+            state.globals.clone(),
+            &state.gpu_context,
+            WaylandClientStatePtr(Rc::downgrade(&self.0)),
+            params,
+            state.common.appearance,
+            &host_surface,
+        )?;
+        state.windows.insert(surface_id, window.0.clone());
+
+        Ok(Box::new(window))
+    }
+
+    /// Wayland objects can only be used on the connection that created them, so to create a
+    /// subsurface of the host's surface GPUI has to talk to the compositor through the host's
+    /// connection. This moves the client's globals, seat and outputs onto that connection.
+    fn attach_to_host_display(&self, display: NonNull<c_void>) -> anyhow::Result<Connection> {
+        let mut state = self.0.borrow_mut();
+        let current_backend = state
+            .globals
+            .compositor
+            .backend()
+            .upgrade()
+            .context("The Wayland connection was closed")?;
+        if current_backend.display_ptr() == display.as_ptr().cast() {
+            return Ok(Connection::from_backend(current_backend));
+        }
+        if !state.windows.is_empty() {
+            return Err(WindowOpenError::Unsupported(
+                "Can't embed into a Wayland host while other windows are open, \
+                since they were created on a different Wayland connection"
+                    .into(),
+            )
+            .into());
+        }
+
+        trace_embedded(|| format!("attaching to host wl_display {:?}", display));
+        // Safety: the host keeps its display connection open while GPUI is embedded in it.
+        let backend = unsafe { Backend::from_foreign_display(display.as_ptr().cast()) };
+        let conn = Connection::from_backend(backend);
+        let (globals, event_queue) = registry_queue_init::<WaylandClientStatePtr>(&conn)
+            .context("Failed to read the globals of the host's Wayland display")?;
+        let qh = event_queue.handle();
+
+        #[allow(clippy::mutable_key_type)]
+        let (seat, in_progress_outputs) = bind_seat_and_outputs(&globals, &qh);
+        let seat = seat.context("The host's Wayland display has no seat")?;
+        let globals = Globals::new(
+            globals,
+            state.common.foreground_executor.clone(),
+            qh.clone(),
+            seat.clone(),
+        );
+
+        // The seat's capabilities event creates new devices on the host's connection.
+        if let Some(wl_pointer) = state.wl_pointer.take() {
+            wl_pointer.release();
+        }
+        if let Some(wl_keyboard) = state.wl_keyboard.take() {
+            wl_keyboard.release();
+        }
+        state.cursor_shape_device = None;
+        state.text_input = None;
+        state.data_device = globals
+            .data_device_manager
+            .as_ref()
+            .map(|data_device_manager| data_device_manager.get_data_device(&seat, &qh, ()));
+        state.primary_selection = globals
+            .primary_selection_manager
+            .as_ref()
+            .map(|primary_selection_manager| primary_selection_manager.get_device(&seat, &qh, ()));
+        state.cursor = Cursor::new(&conn, &globals, 24);
+        state.clipboard = Clipboard::new(conn.clone(), state.loop_handle.clone());
+        state.outputs.clear();
+        state.in_progress_outputs = in_progress_outputs;
+        state.wl_seat = seat;
+        state.globals = globals;
+
+        let loop_handle = state.loop_handle.clone();
+        drop(state);
+        WaylandSource::new(conn.clone(), event_queue)
+            .insert(loop_handle)
+            .map_err(|error| error.error)
+            .context("Failed to listen to the host's Wayland display")?;
+
+        Ok(conn)
+    }
 }
 
 impl LinuxClient for WaylandClient {
@@ -726,11 +865,10 @@ impl LinuxClient for WaylandClient {
         handle: AnyWindowHandle,
         params: WindowParams,
     ) -> anyhow::Result<Box<dyn PlatformWindow>> {
-        // TODO: Implement embedded window support for Wayland
-        if params.raw_window_handle.is_some() {
-            anyhow::bail!("Embedded window support for Wayland is not yet implemented");
+        if let Some(raw_handle) = params.raw_window_handle {
+            return self.open_embedded_window(handle, params, raw_handle);
         }
-        
+
         let mut state = self.0.borrow_mut();
 
         let parent = state
@@ -840,6 +978,26 @@ impl LinuxClient for WaylandClient {
             .log_err();
     }
 
+    fn pump(&self) {
+        // The event loop is taken while it's running or being pumped already.
+        let Some(mut event_loop) = self.0.borrow_mut().event_loop.take() else {
+            return;
+        };
+        event_loop
+            .dispatch(
+                Some(std::time::Duration::ZERO),
+                &mut WaylandClientStatePtr(Rc::downgrade(&self.0)),
+            )
+            .log_err();
+        self.0.borrow_mut().event_loop = Some(event_loop);
+    }
+
+    fn event_fd(&self) -> Option<RawFd> {
+        let state = self.0.borrow();
+        let event_loop = state.event_loop.as_ref()?;
+        Some(event_loop.as_fd().as_raw_fd())
+    }
+
     fn write_to_primary(&self, item: crate::ClipboardItem) {
         let mut state = self.0.borrow_mut();
         let (Some(primary_selection_manager), Some(primary_selection)) = (
@@ -976,6 +1134,8 @@ impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for WaylandClientStat
 
 delegate_noop!(WaylandClientStatePtr: ignore xdg_activation_v1::XdgActivationV1);
 delegate_noop!(WaylandClientStatePtr: ignore wl_compositor::WlCompositor);
+delegate_noop!(WaylandClientStatePtr: ignore wl_subcompositor::WlSubcompositor);
+delegate_noop!(WaylandClientStatePtr: ignore wl_subsurface::WlSubsurface);
 delegate_noop!(WaylandClientStatePtr: ignore wp_cursor_shape_device_v1::WpCursorShapeDeviceV1);
 delegate_noop!(WaylandClientStatePtr: ignore wp_cursor_shape_manager_v1::WpCursorShapeManagerV1);
 delegate_noop!(WaylandClientStatePtr: ignore wl_data_device_manager::WlDataDeviceManager);
@@ -10,10 +10,14 @@ use blade_graphics as gpu;
 use collections::HashMap;
 use futures::channel::oneshot::Receiver;
 
+use anyhow::Context as _;
 use raw_window_handle as rwh;
 use wayland_backend::client::ObjectId;
 use wayland_client::WEnum;
-use wayland_client::{Proxy, protocol::wl_surface};
+use wayland_client::{
+    Proxy,
+    protocol::{wl_subsurface, wl_surface},
+};
 use wayland_protocols::wp::viewporter::client::wp_viewport;
 use wayland_protocols::xdg::decoration::zv1::client::zxdg_toplevel_decoration_v1;
 use wayland_protocols::xdg::shell::client::xdg_surface;
@@ -29,8 +33,8 @@ use crate::{
     AnyWindowHandle, Bounds, Decorations, Globals, GpuSpecs, Modifiers, Output, Pixels,
     PlatformDisplay, PlatformInput, Point, PromptButton, PromptLevel, RequestFrameOptions,
     ResizeEdge, Size, Tiling, WaylandClientStatePtr, WindowAppearance, WindowBackgroundAppearance,
-    WindowBounds, WindowControlArea, WindowControls, WindowDecorations, WindowParams,
-    layer_shell::LayerShellNotSupportedError, px, size,
+    WindowBounds, WindowControlArea, WindowControls, WindowDecorations, WindowOpenError,
+    WindowParams, layer_shell::LayerShellNotSupportedError, px, size, trace_embedded,
 };
 use crate::{
     Capslock,
@@ -80,6 +84,7 @@ struct InProgressConfigure {
     size: Option<Size<Pixels>>,
     fullscreen: bool,
     maximized: bool,
+    suspended: bool,
     resizing: bool,
     tiling: Tiling,
 }
@@ -103,6 +108,7 @@ pub struct WaylandWindowState {
     background_appearance: WindowBackgroundAppearance,
     fullscreen: bool,
     maximized: bool,
+    suspended: bool,
     tiling: Tiling,
     window_bounds: Bounds<Pixels>,
     client: WaylandClientStatePtr,
@@ -119,6 +125,7 @@ pub struct WaylandWindowState {
 pub enum WaylandSurfaceState {
     Xdg(WaylandXdgSurfaceState),
     LayerShell(WaylandLayerSurfaceState),
+    Subsurface(WaylandSubsurfaceState),
 }
 
 impl WaylandSurfaceState {
@@ -212,6 +219,10 @@ pub struct WaylandLayerSurfaceState {
     layer_surface: zwlr_layer_surface_v1::ZwlrLayerSurfaceV1,
 }
 
+pub struct WaylandSubsurfaceState {
+    subsurface: wl_subsurface::WlSubsurface,
+}
+
 impl WaylandSurfaceState {
     fn ack_configure(&self, serial: u32) {
         match self {
@@ -221,6 +232,8 @@ impl WaylandSurfaceState {
             WaylandSurfaceState::LayerShell(WaylandLayerSurfaceState { layer_surface, .. }) => {
                 layer_surface.ack_configure(serial);
             }
+            // Subsurfaces aren't configured by the compositor.
+            WaylandSurfaceState::Subsurface(_) => {}
         }
     }
 
@@ -249,6 +262,8 @@ impl WaylandSurfaceState {
                 // cannot set window position of a layer surface
                 layer_surface.set_size(width as u32, height as u32);
             }
+            // A subsurface's size is the size of its buffer, and the host positions it.
+            WaylandSurfaceState::Subsurface(_) => {}
         }
     }
 
@@ -267,6 +282,9 @@ impl WaylandSurfaceState {
             WaylandSurfaceState::LayerShell(WaylandLayerSurfaceState { layer_surface }) => {
                 layer_surface.destroy();
             }
+            WaylandSurfaceState::Subsurface(WaylandSubsurfaceState { subsurface }) => {
+                subsurface.destroy();
+            }
         }
     }
 }
@@ -307,7 +325,8 @@ impl WaylandWindowState {
                 },
                 transparent: true,
             };
-            BladeRenderer::new(gpu_context, &raw_window, config)?
+            BladeRenderer::new(gpu_context, &raw_window, config)
+                .map_err(WindowOpenError::GpuInit)?
         };
 
         if let WaylandSurfaceState::Xdg(ref xdg_state) = surface_state {
@@ -334,6 +353,7 @@ impl WaylandWindowState {
             background_appearance: WindowBackgroundAppearance::Opaque,
             fullscreen: false,
             maximized: false,
+            suspended: false,
             tiling: Tiling::default(),
             window_bounds: options.bounds,
             in_progress_configure: None,
@@ -482,6 +502,74 @@ impl WaylandWindow {
 
         Ok((this, surface.id()))
     }
+
+    /// Create a window that renders into a subsurface of a host application's surface. The
+    /// host's surface must be on the same connection as `globals`.
+    pub fn new_embedded(
+        handle: AnyWindowHandle,
+        globals: Globals,
+        gpu_context: &BladeContext,
+        client: WaylandClientStatePtr,
+        params: WindowParams,
+        appearance: WindowAppearance,
+        host_surface: &wl_surface::WlSurface,
+    ) -> anyhow::Result<(Self, ObjectId)> {
+        let subcompositor = globals
+            .subcompositor
+            .clone()
+            .context("The Wayland compositor doesn't support subsurfaces")?;
+        let surface = globals.compositor.create_surface(&globals.qh, ());
+        let subsurface = subcompositor.get_subsurface(&surface, host_surface, &globals.qh, ());
+        trace_embedded(|| {
+            format!(
+                "wl_subcompositor.get_subsurface({}, parent {})",
+                surface.id(),
+                host_surface.id()
+            )
+        });
+        subsurface.set_position(0, 0);
+        // Let GPUI present frames without waiting for the host to commit its own surface.
+        subsurface.set_desync();
+
+        if let Some(fractional_scale_manager) = globals.fractional_scale_manager.as_ref() {
+            fractional_scale_manager.get_fractional_scale(&surface, &globals.qh, surface.id());
+        }
+
+        let viewport = globals
+            .viewporter
+            .as_ref()
+            .map(|viewporter| viewporter.get_viewport(&surface, &globals.qh, ()));
+
+        let executor = globals.executor.clone();
+        let mut state = WaylandWindowState::new(
+            handle,
+            surface.clone(),
+            WaylandSurfaceState::Subsurface(WaylandSubsurfaceState { subsurface }),
+            appearance,
+            viewport,
+            client,
+            globals,
+            gpu_context,
+            params,
+        )?;
+        // There's no configure event to wait for before drawing.
+        state.acknowledged_first_configure = true;
+        // The host decides whether to draw decorations around the embedded window.
+        state.decorations = WindowDecorations::Server;
+
+        let this = Self(WaylandWindowStatePtr {
+            state: Rc::new(RefCell::new(state)),
+            callbacks: Rc::new(RefCell::new(Callbacks::default())),
+        });
+
+        surface.commit();
+
+        // Request the first frame once GPUI has registered the window's callbacks.
+        let state_ptr = this.0.clone();
+        executor.spawn(async move { state_ptr.frame() }).detach();
+
+        Ok((this, surface.id()))
+    }
 }
 
 impl WaylandWindowStatePtr {
@@ -532,9 +620,20 @@ impl WaylandWindowStatePtr {
 
                 if let Some(mut configure) = state.in_progress_configure.take() {
                     let got_unmaximized = state.maximized && !configure.maximized;
+                    let suspended_changed = state.suspended != configure.suspended;
                     state.fullscreen = configure.fullscreen;
                     state.maximized = configure.maximized;
+                    state.suspended = configure.suspended;
                     state.tiling = configure.tiling;
+                    if suspended_changed {
+                        // Suspending doesn't move or resize the window, so report it as a move
+                        // for GPUI to notice that the window state changed.
+                        drop(state);
+                        if let Some(moved) = self.callbacks.borrow_mut().moved.as_mut() {
+                            moved();
+                        }
+                        state = self.state.borrow_mut();
+                    }
                     // Limit interactive resizes to once per vblank
                     if configure.resizing && state.resize_throttle {
                         return;
@@ -641,6 +740,7 @@ impl WaylandWindowStatePtr {
                 let mut tiling = Tiling::default();
                 let mut fullscreen = false;
                 let mut maximized = false;
+                let mut suspended = false;
                 let mut resizing = false;
 
                 for state in states {
@@ -652,6 +752,7 @@ impl WaylandWindowStatePtr {
                             fullscreen = true;
                         }
                         xdg_toplevel::State::Resizing => resizing = true,
+                        xdg_toplevel::State::Suspended => suspended = true,
                         xdg_toplevel::State::TiledTop => {
                             tiling.top = true;
                         }
@@ -679,6 +780,7 @@ impl WaylandWindowStatePtr {
                     size,
                     fullscreen,
                     maximized,
+                    suspended,
                     resizing,
                     tiling,
                 });
@@ -748,6 +850,7 @@ impl WaylandWindowStatePtr {
                     size,
                     fullscreen: false,
                     maximized: false,
+                    suspended: false,
                     resizing: false,
                     tiling: Tiling::default(),
                 });
@@ -1172,6 +1275,13 @@ impl PlatformWindow for WaylandWindow {
         self.borrow().fullscreen
     }
 
+    // Wayland doesn't tell clients whether they are minimized, so this reports whether the
+    // compositor has suspended the window, which it does when the window is minimized or
+    // otherwise completely hidden.
+    fn is_minimized(&self) -> bool {
+        self.borrow().suspended
+    }
+
     fn on_request_frame(&self, callback: Box<dyn FnMut(RequestFrameOptions)>) {
         self.0.callbacks.borrow_mut().request_frame = Some(callback);
     }
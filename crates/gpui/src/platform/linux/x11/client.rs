@@ -14,6 +14,7 @@ use std::{
     cell::RefCell,
     collections::{BTreeMap, HashSet},
     ops::Deref,
+    os::fd::{AsFd as _, AsRawFd as _, RawFd},
     path::PathBuf,
     rc::{Rc, Weak},
     time::{Duration, Instant},
@@ -752,6 +753,22 @@ impl X11Client {
         }
     }
 
+    /// The windows embedded in `host_window`. A host can have several, e.g. when a plugin is
+    /// instantiated more than once in the same editor.
+    fn get_embedded_windows(&self, host_window: xproto::Window) -> Vec<X11WindowStatePtr> {
+        let state = self.0.borrow();
+        state
+            .windows
+            .values()
+            .map(|window_reference| &window_reference.window)
+            .filter(|window| {
+                let window_state = window.state.borrow();
+                !window_state.destroyed && window_state.embed_parent == Some(host_window)
+            })
+            .cloned()
+            .collect()
+    }
+
     fn get_window(&self, win: xproto::Window) -> Option<X11WindowStatePtr> {
         let state = self.0.borrow();
         state
@@ -769,6 +786,8 @@ impl X11Client {
                     window_ref.is_mapped = false;
                 }
                 state.update_refresh_loop(event.window);
+                drop(state);
+                self.get_window(event.window)?.set_mapped(false);
             }
             Event::MapNotify(event) => {
                 let mut state = self.0.borrow_mut();
@@ -776,6 +795,8 @@ impl X11Client {
                     window_ref.is_mapped = true;
                 }
                 state.update_refresh_loop(event.window);
+                drop(state);
+                self.get_window(event.window)?.set_mapped(true);
             }
             Event::VisibilityNotify(event) => {
                 let mut state = self.0.borrow_mut();
@@ -906,6 +927,16 @@ impl X11Client {
                 }
             }
             Event::ConfigureNotify(event) => {
+                let embedded_windows = self.get_embedded_windows(event.window);
+                if !embedded_windows.is_empty() {
+                    for window in embedded_windows {
+                        window
+                            .fit_to_host(event.width, event.height)
+                            .context("X11: Failed to resize embedded window")
+                            .log_err();
+                    }
+                    return Some(());
+                }
                 let bounds = Bounds {
                     origin: Point {
                         x: event.x.into(),
@@ -1102,6 +1133,7 @@ impl X11Client {
             }
             Event::XinputButtonPress(event) => {
                 let window = self.get_window(event.event)?;
+                window.focus_embedded();
                 let mut state = self.0.borrow_mut();
 
                 let modifiers = modifiers_from_xinput_info(event.mods);
@@ -1474,11 +1506,6 @@ impl LinuxClient for X11Client {
         handle: AnyWindowHandle,
         params: WindowParams,
     ) -> anyhow::Result<Box<dyn PlatformWindow>> {
-        // TODO: Implement embedded window support for X11
-        if params.raw_window_handle.is_some() {
-            anyhow::bail!("Embedded window support for X11 is not yet implemented");
-        }
-        
         let mut state = self.0.borrow_mut();
         let parent_window = state
             .keyboard_focused_window
@@ -1489,21 +1516,40 @@ impl LinuxClient for X11Client {
             .generate_id()
             .context("X11: Failed to generate window ID")?;
 
-        let window = X11Window::new(
-            handle,
-            X11ClientStatePtr(Rc::downgrade(&self.0)),
-            state.common.foreground_executor.clone(),
-            &state.gpu_context,
-            params,
-            &state.xcb_connection,
-            state.client_side_decorations_supported,
-            state.x_root_index,
-            x_window,
-            &state.atoms,
-            state.scale_factor,
-            state.common.appearance,
-            parent_window,
-        )?;
+        let window = if let Some(raw_handle) = params.raw_window_handle {
+            X11Window::new_embedded(
+                handle,
+                X11ClientStatePtr(Rc::downgrade(&self.0)),
+                state.common.foreground_executor.clone(),
+                &state.gpu_context,
+                params,
+                &state.xcb_connection,
+                state.client_side_decorations_supported,
+                state.x_root_index,
+                x_window,
+                &state.atoms,
+                state.scale_factor,
+                state.common.appearance,
+                raw_handle,
+            )?
+        } else {
+            X11Window::new(
+                handle,
+                X11ClientStatePtr(Rc::downgrade(&self.0)),
+                state.common.foreground_executor.clone(),
+                &state.gpu_context,
+                params,
+                &state.xcb_connection,
+                state.client_side_decorations_supported,
+                state.x_root_index,
+                x_window,
+                &state.atoms,
+                state.scale_factor,
+                state.common.appearance,
+                parent_window,
+                None,
+            )?
+        };
         check_reply(
             || "Failed to set XdndAware property",
             state.xcb_connection.change_property32(
@@ -1639,6 +1685,23 @@ impl LinuxClient for X11Client {
         event_loop.run(None, &mut self.clone(), |_| {}).log_err();
     }
 
+    fn pump(&self) {
+        // The event loop is taken while it's running or being pumped already.
+        let Some(mut event_loop) = self.0.borrow_mut().event_loop.take() else {
+            return;
+        };
+        event_loop
+            .dispatch(Some(Duration::ZERO), &mut self.clone())
+            .log_err();
+        self.0.borrow_mut().event_loop = Some(event_loop);
+    }
+
+    fn event_fd(&self) -> Option<RawFd> {
+        let state = self.0.borrow();
+        let event_loop = state.event_loop.as_ref()?;
+        Some(event_loop.as_fd().as_raw_fd())
+    }
+
     fn active_window(&self) -> Option<AnyWindowHandle> {
         let state = self.0.borrow();
         state.keyboard_focused_window.and_then(|focused_window| {
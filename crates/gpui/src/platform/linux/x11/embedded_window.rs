@@ -0,0 +1,245 @@
+// Support for embedding GPUI windows into existing X11 windows.
+// This is useful for plugins (VST, CLAP, LV2) and other host applications.
+
+use std::rc::Rc;
+
+use raw_window_handle as rwh;
+use util::ResultExt;
+use x11rb::{
+    connection::Connection as _,
+    protocol::xproto::{self, ConnectionExt as _},
+    xcb_ffi::XCBConnection,
+};
+
+use crate::platform::blade::BladeContext;
+use crate::{
+    AnyWindowHandle, Bounds, ForegroundExecutor, Point, WindowAppearance, WindowOpenError,
+    WindowParams, X11ClientStatePtr, px, size, trace_embedded,
+};
+
+use super::{X11Window, X11WindowStatePtr, XcbAtoms, check_reply, get_reply, xcb_flush};
+
+impl X11Window {
+    /// Create an X11Window inside an existing window, instead of creating a new top-level window.
+    ///
+    /// The host's window stays owned by the host. GPUI creates a child window that fills it,
+    /// renders into that child, and follows the host window's size.
+    pub(crate) fn new_embedded(
+        handle: AnyWindowHandle,
+        client: X11ClientStatePtr,
+        executor: ForegroundExecutor,
+        gpu_context: &BladeContext,
+        mut params: WindowParams,
+        xcb: &Rc<XCBConnection>,
+        client_side_decorations_supported: bool,
+        x_main_screen_index: usize,
+        x_window: xproto::Window,
+        atoms: &XcbAtoms,
+        scale_factor: f32,
+        appearance: WindowAppearance,
+        raw_handle: rwh::RawWindowHandle,
+    ) -> anyhow::Result<Self> {
+        let host_window = host_window(raw_handle)?;
+
+        // X11 window IDs are shared by every connection to the server, so the host's display
+        // is only needed to find out which screen its window is on.
+        let x_main_screen_index = match params.raw_display_handle {
+            Some(rwh::RawDisplayHandle::Xlib(display_handle)) => {
+                usize::try_from(display_handle.screen).map_err(|_| {
+                    WindowOpenError::InvalidHandle(
+                        "X11: Xlib display handle has a negative screen".into(),
+                    )
+                })?
+            }
+            Some(rwh::RawDisplayHandle::Xcb(display_handle)) => {
+                usize::try_from(display_handle.screen).map_err(|_| {
+                    WindowOpenError::InvalidHandle(
+                        "X11: XCB display handle has a negative screen".into(),
+                    )
+                })?
+            }
+            Some(raw_display_handle) => {
+                return Err(WindowOpenError::InvalidHandle(format!(
+                    "Expected an Xlib or XCB display handle for X11, got {:?}",
+                    raw_display_handle
+                ))
+                .into());
+            }
+            None => x_main_screen_index,
+        };
+        if xcb.setup().roots.get(x_main_screen_index).is_none() {
+            return Err(WindowOpenError::InvalidHandle(format!(
+                "X11: screen {} of the host display does not exist",
+                x_main_screen_index
+            ))
+            .into());
+        }
+
+        // Also validates that the host window exists.
+        let geometry = get_reply(
+            || format!("X11 GetGeometry of host window {} failed.", host_window),
+            xcb.get_geometry(host_window),
+        )
+        .map_err(|error| WindowOpenError::InvalidHandle(format!("{error:#}")))?;
+        trace_embedded(|| {
+            format!(
+                "GetGeometry({}) -> {}x{}",
+                host_window, geometry.width, geometry.height
+            )
+        });
+        params.bounds = Bounds::new(
+            Point::default(),
+            size(
+                px(geometry.width as f32 / scale_factor),
+                px(geometry.height as f32 / scale_factor),
+            ),
+        );
+
+        let window = Self::new(
+            handle,
+            client,
+            executor,
+            gpu_context,
+            params,
+            xcb,
+            client_side_decorations_supported,
+            x_main_screen_index,
+            x_window,
+            atoms,
+            scale_factor,
+            appearance,
+            None,
+            Some(host_window),
+        )?;
+
+        // Listen for the host window being resized, so that the embedded window keeps filling it.
+        trace_embedded(|| format!("ChangeWindowAttributes({host_window}, STRUCTURE_NOTIFY)"));
+        check_reply(
+            || "X11 ChangeWindowAttributes on host window failed.",
+            xcb.change_window_attributes(
+                host_window,
+                &xproto::ChangeWindowAttributesAux::new()
+                    .event_mask(xproto::EventMask::STRUCTURE_NOTIFY),
+            ),
+        )?;
+        xcb_flush(xcb);
+
+        // Note: We don't activate the window here, as the host controls visibility and focus.
+        Ok(window)
+    }
+}
+
+fn host_window(raw_handle: rwh::RawWindowHandle) -> Result<xproto::Window, WindowOpenError> {
+    match raw_handle {
+        rwh::RawWindowHandle::Xlib(xlib_handle) => xproto::Window::try_from(xlib_handle.window)
+            .map_err(|_| {
+                WindowOpenError::InvalidHandle("X11: Xlib window handle is out of range".into())
+            }),
+        rwh::RawWindowHandle::Xcb(xcb_handle) => Ok(xcb_handle.window.get()),
+        _ => Err(WindowOpenError::InvalidHandle(format!(
+            "Expected an Xlib or XCB window handle for X11, got {:?}",
+            raw_handle
+        ))),
+    }
+}
+
+/// Helper methods for embedded windows
+impl X11WindowStatePtr {
+    /// The host window this window is embedded in, if any.
+    pub fn embed_parent(&self) -> Option<xproto::Window> {
+        self.state.borrow().embed_parent
+    }
+
+    /// Resize an embedded window to fill its host window after the host was resized.
+    pub fn fit_to_host(&self, width: u16, height: u16) -> anyhow::Result<()> {
+        trace_embedded(|| format!("ConfigureWindow({}, {width}x{height})", self.x_window));
+        check_reply(
+            || {
+                format!(
+                    "X11 ConfigureWindow failed. width: {}, height: {}",
+                    width, height
+                )
+            },
+            self.xcb.configure_window(
+                self.x_window,
+                &xproto::ConfigureWindowAux::new()
+                    .width(width as u32)
+                    .height(height as u32),
+            ),
+        )?;
+        xcb_flush(&self.xcb);
+        Ok(())
+    }
+
+    /// Move an embedded window into another host window, keeping its X11 window and GPU
+    /// resources, and resize it to fill the new host.
+    pub fn reparent_to_host(&self, raw_handle: rwh::RawWindowHandle) -> anyhow::Result<()> {
+        let host_window = host_window(raw_handle)?;
+        // Also validates that the new host window exists.
+        let geometry = get_reply(
+            || format!("X11 GetGeometry of host window {} failed.", host_window),
+            self.xcb.get_geometry(host_window),
+        )
+        .map_err(|error| WindowOpenError::InvalidHandle(format!("{error:#}")))?;
+
+        trace_embedded(|| format!("ReparentWindow({}, {})", self.x_window, host_window));
+        check_reply(
+            || "X11 ReparentWindow failed.",
+            self.xcb.reparent_window(self.x_window, host_window, 0, 0),
+        )?;
+        trace_embedded(|| format!("ChangeWindowAttributes({host_window}, STRUCTURE_NOTIFY)"));
+        check_reply(
+            || "X11 ChangeWindowAttributes on host window failed.",
+            self.xcb.change_window_attributes(
+                host_window,
+                &xproto::ChangeWindowAttributesAux::new()
+                    .event_mask(xproto::EventMask::STRUCTURE_NOTIFY),
+            ),
+        )?;
+        // Parked windows are unmapped.
+        trace_embedded(|| format!("MapWindow({})", self.x_window));
+        check_reply(
+            || "X11 MapWindow failed.",
+            self.xcb.map_window(self.x_window),
+        )?;
+        self.state.borrow_mut().embed_parent = Some(host_window);
+        self.fit_to_host(geometry.width, geometry.height)
+    }
+
+    /// Unmap an embedded window and move it to the root window, so that the host can destroy its
+    /// window without destroying this one, until it's moved into another host window.
+    pub fn park(&self) -> anyhow::Result<()> {
+        let root = self.state.borrow().x_root_window;
+        trace_embedded(|| format!("UnmapWindow({})", self.x_window));
+        check_reply(
+            || "X11 UnmapWindow failed.",
+            self.xcb.unmap_window(self.x_window),
+        )?;
+        trace_embedded(|| format!("ReparentWindow({}, {})", self.x_window, root));
+        check_reply(
+            || "X11 ReparentWindow failed.",
+            self.xcb.reparent_window(self.x_window, root, 0, 0),
+        )?;
+        xcb_flush(&self.xcb);
+        Ok(())
+    }
+
+    /// Give keyboard focus to an embedded window. Window managers only focus top-level
+    /// windows, so embedded windows take focus themselves when they're clicked.
+    pub fn focus_embedded(&self) {
+        if self.embed_parent().is_none() {
+            return;
+        }
+        trace_embedded(|| format!("SetInputFocus({})", self.x_window));
+        check_reply(
+            || "X11 SetInputFocus for embedded window failed.",
+            self.xcb.set_input_focus(
+                xproto::InputFocus::PARENT,
+                self.x_window,
+                x11rb::CURRENT_TIME,
+            ),
+        )
+        .log_err();
+        xcb_flush(&self.xcb);
+    }
+}
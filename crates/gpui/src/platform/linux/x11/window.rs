@@ -7,7 +7,8 @@ use crate::{
     Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
     Point, PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size,
     Tiling, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea,
-    WindowDecorations, WindowKind, WindowParams, X11ClientStatePtr, px, size,
+    WindowDecorations, WindowKind, WindowOpenError, WindowParams, X11ClientStatePtr, px, size,
+    trace_embedded,
 };
 
 use blade_graphics as gpu;
@@ -28,7 +29,7 @@ use x11rb::{
 };
 
 use std::{
-    cell::RefCell, ffi::c_void, fmt::Display, num::NonZeroU32, ops::Div, ptr::NonNull, rc::Rc,
+    cell::RefCell, ffi::c_void, fmt::Display, mem, num::NonZeroU32, ops::Div, ptr::NonNull, rc::Rc,
     sync::Arc,
 };
 
@@ -64,6 +65,7 @@ x11rb::atom_manager! {
         _NET_WM_STATE_MAXIMIZED_VERT,
         _NET_WM_STATE_MAXIMIZED_HORZ,
         _NET_WM_STATE_FULLSCREEN,
+        _NET_WM_STATE_ABOVE,
         _NET_WM_STATE_HIDDEN,
         _NET_WM_STATE_FOCUSED,
         _NET_ACTIVE_WINDOW,
@@ -252,7 +254,7 @@ pub struct X11WindowState {
     client: X11ClientStatePtr,
     executor: ForegroundExecutor,
     atoms: XcbAtoms,
-    x_root_window: xproto::Window,
+    pub(crate) x_root_window: xproto::Window,
     pub(crate) counter_id: sync::Counter,
     pub(crate) last_sync_counter: Option<sync::Int64>,
     bounds: Bounds<Pixels>,
@@ -265,6 +267,9 @@ pub struct X11WindowState {
     maximized_vertical: bool,
     maximized_horizontal: bool,
     hidden: bool,
+    /// Whether the window was unmapped after being shown, which is how window managers iconify
+    /// windows under ICCCM.
+    unmapped: bool,
     active: bool,
     hovered: bool,
     fullscreen: bool,
@@ -273,6 +278,7 @@ pub struct X11WindowState {
     edge_constraints: Option<EdgeConstraints>,
     pub handle: AnyWindowHandle,
     last_insets: [u32; 4],
+    pub(crate) embed_parent: Option<xproto::Window>,
 }
 
 impl X11WindowState {
@@ -285,7 +291,7 @@ impl X11WindowState {
 pub(crate) struct X11WindowStatePtr {
     pub state: Rc<RefCell<X11WindowState>>,
     pub(crate) callbacks: Rc<RefCell<Callbacks>>,
-    xcb: Rc<XCBConnection>,
+    pub(crate) xcb: Rc<XCBConnection>,
     pub(crate) x_window: xproto::Window,
 }
 
@@ -395,6 +401,7 @@ impl X11WindowState {
         scale_factor: f32,
         appearance: WindowAppearance,
         parent_window: Option<xproto::Window>,
+        embed_parent: Option<xproto::Window>,
     ) -> anyhow::Result<Self> {
         let x_screen_index = params
             .display_id
@@ -448,14 +455,21 @@ impl X11WindowState {
             bounds.size.height = 600.into();
         }
 
+        // Embedded windows are children of the host's window and fill it, so the offset that
+        // works around rendering outside of top-level windows doesn't apply to them.
+        let x_offset = if embed_parent.is_some() { 0 } else { 2 };
+        let x_parent = embed_parent.unwrap_or(visual_set.root);
+        if let Some(embed_parent) = embed_parent {
+            trace_embedded(|| format!("CreateWindow({x_window}) in host window {embed_parent}"));
+        }
         check_reply(
             || {
                 format!(
-                    "X11 CreateWindow failed. depth: {}, x_window: {}, visual_set.root: {}, bounds.origin.x.0: {}, bounds.origin.y.0: {}, bounds.size.width.0: {}, bounds.size.height.0: {}",
+                    "X11 CreateWindow failed. depth: {}, x_window: {}, x_parent: {}, bounds.origin.x.0: {}, bounds.origin.y.0: {}, bounds.size.width.0: {}, bounds.size.height.0: {}",
                     visual.depth,
                     x_window,
-                    visual_set.root,
-                    bounds.origin.x.0 + 2,
+                    x_parent,
+                    bounds.origin.x.0 + x_offset,
                     bounds.origin.y.0,
                     bounds.size.width.0,
                     bounds.size.height.0
@@ -464,8 +478,8 @@ impl X11WindowState {
             xcb.create_window(
                 visual.depth,
                 x_window,
-                visual_set.root,
-                (bounds.origin.x.0 + 2) as i16,
+                x_parent,
+                (bounds.origin.x.0 + x_offset) as i16,
                 bounds.origin.y.0 as i16,
                 bounds.size.width.0 as u16,
                 bounds.size.height.0 as u16,
@@ -506,7 +520,7 @@ impl X11WindowState {
             }
 
             let reply = get_reply(|| "X11 GetGeometry failed.", xcb.get_geometry(x_window))?;
-            if reply.x == 0 && reply.y == 0 {
+            if embed_parent.is_none() && reply.x == 0 && reply.y == 0 {
                 bounds.origin.x.0 += 2;
                 // Work around a bug where our rendered content appears
                 // outside the window bounds when opened at the default position
@@ -661,7 +675,8 @@ impl X11WindowState {
                     // too
                     transparent: false,
                 };
-                BladeRenderer::new(gpu_context, &raw_window, config)?
+                BladeRenderer::new(gpu_context, &raw_window, config)
+                    .map_err(WindowOpenError::GpuInit)?
             };
 
             let display = Rc::new(X11Display::new(xcb, scale_factor, x_screen_index)?);
@@ -682,6 +697,7 @@ impl X11WindowState {
                 maximized_vertical: false,
                 maximized_horizontal: false,
                 hidden: false,
+                unmapped: false,
                 appearance,
                 handle,
                 background_appearance: WindowBackgroundAppearance::Opaque,
@@ -692,6 +708,7 @@ impl X11WindowState {
                 edge_constraints: None,
                 counter_id: sync_request_counter,
                 last_sync_counter: None,
+                embed_parent,
             })
         });
 
@@ -754,8 +771,8 @@ impl Drop for X11Window {
 }
 
 enum WmHintPropertyState {
-    // Remove = 0,
-    // Add = 1,
+    Remove = 0,
+    Add = 1,
     Toggle = 2,
 }
 
@@ -774,6 +791,7 @@ impl X11Window {
         scale_factor: f32,
         appearance: WindowAppearance,
         parent_window: Option<xproto::Window>,
+        embed_parent: Option<xproto::Window>,
     ) -> anyhow::Result<Self> {
         let ptr = X11WindowStatePtr {
             state: Rc::new(RefCell::new(X11WindowState::new(
@@ -790,6 +808,7 @@ impl X11Window {
                 scale_factor,
                 appearance,
                 parent_window,
+                embed_parent,
             )?)),
             callbacks: Rc::new(RefCell::new(Callbacks::default())),
             xcb: xcb.clone(),
@@ -899,13 +918,35 @@ impl X11WindowStatePtr {
     pub fn property_notify(&self, event: xproto::PropertyNotifyEvent) -> anyhow::Result<()> {
         let mut state = self.state.borrow_mut();
         if event.atom == state.atoms._NET_WM_STATE {
+            let was_hidden = state.hidden;
             self.set_wm_properties(state)?;
+            if self.state.borrow().hidden != was_hidden {
+                self.minimized_changed();
+            }
         } else if event.atom == state.atoms._GTK_EDGE_CONSTRAINTS {
             self.set_edge_constraints(state)?;
         }
         Ok(())
     }
 
+    pub fn set_mapped(&self, mapped: bool) {
+        let mut state = self.state.borrow_mut();
+        if state.unmapped != mapped {
+            return;
+        }
+        state.unmapped = !mapped;
+        drop(state);
+        self.minimized_changed();
+    }
+
+    /// Minimizing doesn't move or resize the window, so report it as a move for GPUI to notice
+    /// that the window state changed.
+    fn minimized_changed(&self) {
+        if let Some(ref mut fun) = self.callbacks.borrow_mut().moved {
+            fun();
+        }
+    }
+
     fn set_edge_constraints(
         &self,
         mut state: std::cell::RefMut<X11WindowState>,
@@ -1224,6 +1265,36 @@ impl PlatformWindow for X11Window {
         xcb_flush(&self.0.xcb);
     }
 
+    fn reparent_embedded(&self, raw_handle: rwh::RawWindowHandle) -> anyhow::Result<()> {
+        self.0.reparent_to_host(raw_handle)
+    }
+
+    fn park_embedded(&self) -> anyhow::Result<()> {
+        self.0.park()
+    }
+
+    fn set_embedded_scale_factor(&mut self, scale_factor: f32) {
+        let mut state = self.0.state.borrow_mut();
+        let old_scale_factor = mem::replace(&mut state.scale_factor, scale_factor);
+        if old_scale_factor == scale_factor {
+            return;
+        }
+        state.bounds = state
+            .bounds
+            .map(|value| px(value.0 * old_scale_factor / scale_factor));
+        let content_size = state.content_size();
+
+        let this = self.0.clone();
+        state
+            .executor
+            .spawn(async move {
+                if let Some(ref mut fun) = this.callbacks.borrow_mut().resize {
+                    fun(content_size, scale_factor);
+                }
+            })
+            .detach();
+    }
+
     fn scale_factor(&self) -> f32 {
         self.0.state.borrow().scale_factor
     }
@@ -1247,6 +1318,25 @@ impl PlatformWindow for X11Window {
         })
     }
 
+    fn warp_cursor(&self, position: Point<Pixels>) -> bool {
+        let scale_factor = self.scale_factor();
+        let result = check_reply(
+            || "X11 WarpPointer failed.",
+            self.0.xcb.warp_pointer(
+                x11rb::NONE,
+                self.0.x_window,
+                0,
+                0,
+                0,
+                0,
+                (position.x.0 * scale_factor).round() as i16,
+                (position.y.0 * scale_factor).round() as i16,
+            ),
+        );
+        xcb_flush(&self.0.xcb);
+        result.log_err().is_some()
+    }
+
     fn modifiers(&self) -> Modifiers {
         self.0
             .state
@@ -1315,6 +1405,39 @@ impl PlatformWindow for X11Window {
         xcb_flush(&self.0.xcb);
     }
 
+    fn request_keyboard_focus(&self) {
+        if self.0.state.borrow().embed_parent.is_some() {
+            trace_embedded(|| format!("SetInputFocus({})", self.0.x_window));
+        }
+        self.0
+            .xcb
+            .set_input_focus(
+                xproto::InputFocus::PARENT,
+                self.0.x_window,
+                xproto::Time::CURRENT_TIME,
+            )
+            .log_err();
+        xcb_flush(&self.0.xcb);
+    }
+
+    fn release_keyboard_focus(&self) {
+        let state = self.0.state.borrow();
+        // Hand focus to the host's window, or revert to the root window for top-level windows.
+        let focus = state.embed_parent.unwrap_or(state.x_root_window);
+        if state.embed_parent.is_some() {
+            trace_embedded(|| format!("SetInputFocus({focus})"));
+        }
+        self.0
+            .xcb
+            .set_input_focus(
+                xproto::InputFocus::PARENT,
+                focus,
+                xproto::Time::CURRENT_TIME,
+            )
+            .log_err();
+        xcb_flush(&self.0.xcb);
+    }
+
     fn is_active(&self) -> bool {
         self.0.state.borrow().active
     }
@@ -1431,6 +1554,27 @@ impl PlatformWindow for X11Window {
         self.0.state.borrow().fullscreen
     }
 
+    fn set_always_on_top(&self, always_on_top: bool) -> bool {
+        let state = self.0.state.borrow();
+        self.set_wm_hints(
+            || "X11 SendEvent to keep a window above others failed.",
+            if always_on_top {
+                WmHintPropertyState::Add
+            } else {
+                WmHintPropertyState::Remove
+            },
+            state.atoms._NET_WM_STATE_ABOVE,
+            xproto::AtomEnum::NONE.into(),
+        )
+        .log_err()
+        .is_some()
+    }
+
+    fn is_minimized(&self) -> bool {
+        let state = self.0.state.borrow();
+        state.hidden || state.unmapped
+    }
+
     fn on_request_frame(&self, callback: Box<dyn FnMut(RequestFrameOptions)>) {
         self.0.callbacks.borrow_mut().request_frame = Some(callback);
     }
@@ -23,6 +23,9 @@ use crate::platform::blade as renderer;
 
 mod attributed_string;
 
+/// View controllers for the editors of Audio Unit (AUv3) extensions.
+pub mod audio_unit;
+
 #[cfg(feature = "font-kit")]
 mod open_type;
 
@@ -30,6 +33,7 @@ mod open_type;
 mod text_system;
 
 mod platform;
+mod spell_checker;
 mod window;
 mod window_appearance;
 
@@ -50,6 +54,7 @@ pub(crate) use display::*;
 pub(crate) use display_link::*;
 pub(crate) use keyboard::*;
 pub(crate) use platform::*;
+pub(crate) use spell_checker::*;
 pub(crate) use window::*;
 
 #[cfg(feature = "font-kit")]
@@ -135,6 +140,21 @@ unsafe impl objc::Encode for NSRange {
     }
 }
 
+unsafe extern "C" {
+    fn objc_disposeClassPair(class: *mut objc::runtime::Class);
+}
+
+/// Dispose of a class registered with `ClassDecl`, so that a reloaded copy of GPUI can register
+/// a class under the same name. The class must not have any instances left.
+unsafe fn dispose_class(class: *mut *const objc::runtime::Class) {
+    unsafe {
+        let registered = class.replace(std::ptr::null());
+        if !registered.is_null() {
+            objc_disposeClassPair(registered as *mut _);
+        }
+    }
+}
+
 unsafe fn ns_string(string: &str) -> id {
     unsafe { NSString::alloc(nil).init_str(string).autorelease() }
 }
@@ -0,0 +1,427 @@
+//! View controllers for the editors of Audio Unit (AUv3) extensions.
+//!
+//! An AUv3 extension names an `AUViewController` subclass as its `NSExtensionPrincipalClass`.
+//! The host instantiates it, asks it to create the audio unit through the `AUAudioUnitFactory`
+//! protocol, and shows its view. [`register_view_controller`] declares such a class at runtime,
+//! whose view hosts an embedded GPUI window rendering the [`AudioUnitEditor`]'s view. The class
+//! must be registered before the host looks it up, such as from a `#[gpui::ctor]` function:
+//!
+//! ```ignore
+//! #[gpui::ctor]
+//! fn register() {
+//!     gpui::audio_unit::register_view_controller::<SynthEditor>("SynthViewController")
+//!         .expect("failed to register the view controller");
+//! }
+//! ```
+//!
+//! The app is attached to the extension's event loop when the first view controller loads its
+//! view. The view controller's view starts at the editor's preferred size, which is also its
+//! `preferredContentSize`, and follows the host's resizes, including live resizes from Logic Pro
+//! and GarageBand.
+
+use super::ns_string;
+use crate::{
+    AnyWindowHandle, App, AppContext as _, Application, AsyncApp, Context, Pixels, Render, Size,
+    Window, WindowOptions,
+};
+use anyhow::{Context as _, Result};
+use cocoa::{
+    appkit::{NSView, NSViewHeightSizable, NSViewWidthSizable},
+    base::{id, nil},
+    foundation::{NSInteger, NSPoint, NSRect, NSSize},
+};
+use collections::HashMap;
+use objc::{
+    Encode, Encoding, class,
+    declare::ClassDecl,
+    msg_send,
+    runtime::{Object, Protocol, Sel},
+    sel, sel_impl,
+};
+use raw_window_handle as rwh;
+use std::{
+    cell::{Cell, RefCell},
+    ffi::c_void,
+    ptr::NonNull,
+    rc::Rc,
+};
+use util::ResultExt as _;
+
+const STATE_IVAR: &str = "audioUnitState";
+
+#[link(name = "CoreAudioKit", kind = "framework")]
+unsafe extern "C" {}
+
+#[link(name = "AudioToolbox", kind = "framework")]
+unsafe extern "C" {}
+
+thread_local! {
+    static APP: RefCell<Option<(Application, AsyncApp)>> = const { RefCell::new(None) };
+    static VIEW_CONTROLLERS: RefCell<HashMap<AnyWindowHandle, id>> = RefCell::default();
+}
+
+/// `AudioComponentDescription` in `AudioToolbox/AudioComponent.h`, which identifies the audio
+/// unit that a host asks an extension to create.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AudioComponentDescription {
+    /// The four-character code of the audio unit's type, such as `aumu` for instruments.
+    pub component_type: u32,
+    /// The four-character code of the audio unit within its manufacturer's audio units.
+    pub component_sub_type: u32,
+    /// The four-character code of the audio unit's manufacturer.
+    pub component_manufacturer: u32,
+    /// Flags, which must be zero unless documented otherwise.
+    pub component_flags: u32,
+    /// Flags, which must be zero unless documented otherwise.
+    pub component_flags_mask: u32,
+}
+
+unsafe impl Encode for AudioComponentDescription {
+    fn encode() -> Encoding {
+        unsafe { Encoding::from_str("{AudioComponentDescription=IIIII}") }
+    }
+}
+
+/// The editor of an Audio Unit extension, which [`register_view_controller`] declares a view
+/// controller for.
+pub trait AudioUnitEditor: 'static {
+    /// The view rendered in the view controller's view.
+    type View: Render;
+
+    /// The application that editors run in. It's created when the first view controller of any
+    /// editor loads its view, so customize it, such as with assets, here.
+    fn application() -> Application {
+        Application::new()
+    }
+
+    /// Called once the application is attached, before the first window opens.
+    fn init(_cx: &mut App) {}
+
+    /// The size the view controller's view opens at, which is also its `preferredContentSize`.
+    fn preferred_size() -> Size<Pixels>;
+
+    /// Create the audio unit, for the `AUAudioUnitFactory` protocol. The returned `AUAudioUnit`
+    /// is an owned reference, which the view controller releases when it's deallocated.
+    fn create_audio_unit(description: AudioComponentDescription) -> Result<NonNull<c_void>>;
+
+    /// Build the view of an editor. `audio_unit` is the `AUAudioUnit` that the view controller
+    /// created, if the host asked it to create one before showing its view.
+    fn build(
+        audio_unit: Option<NonNull<c_void>>,
+        window: &mut Window,
+        cx: &mut Context<Self::View>,
+    ) -> Self::View;
+}
+
+/// Declare an `AUViewController` subclass with the given name, whose view renders the view of
+/// `E`. Name it as the extension's `NSExtensionPrincipalClass`. The class is never disposed of,
+/// as extensions run in their own process.
+pub fn register_view_controller<E: AudioUnitEditor>(class_name: &str) -> Result<()> {
+    unsafe {
+        let mut decl = ClassDecl::new(class_name, class!(AUViewController))
+            .with_context(|| format!("an Objective-C class named {class_name} already exists"))?;
+        decl.add_ivar::<*mut c_void>(STATE_IVAR);
+        decl.add_method(
+            sel!(initWithNibName:bundle:),
+            init_with_nib_name as extern "C" fn(&mut Object, Sel, id, id) -> id,
+        );
+        decl.add_method(
+            sel!(initWithCoder:),
+            init_with_coder as extern "C" fn(&mut Object, Sel, id) -> id,
+        );
+        decl.add_method(
+            sel!(loadView),
+            load_view::<E> as extern "C" fn(&Object, Sel),
+        );
+        decl.add_method(
+            sel!(viewDidLayout),
+            view_did_layout as extern "C" fn(&Object, Sel),
+        );
+        decl.add_method(
+            sel!(createAudioUnitWithComponentDescription:error:),
+            create_audio_unit::<E>
+                as extern "C" fn(&Object, Sel, AudioComponentDescription, *mut c_void) -> id,
+        );
+        decl.add_method(
+            sel!(dealloc),
+            dealloc_view_controller as extern "C" fn(&Object, Sel),
+        );
+        if let Some(protocol) = Protocol::get("AUAudioUnitFactory") {
+            decl.add_protocol(protocol);
+        }
+        decl.register();
+    }
+    Ok(())
+}
+
+/// Ask the host to resize an editor, by setting the `preferredContentSize` of the view
+/// controller hosting the given window, which hosts observe. Returns whether the window is the
+/// editor of a view controller declared with [`register_view_controller`].
+pub fn set_preferred_content_size(window: AnyWindowHandle, size: Size<Pixels>) -> bool {
+    let Some(view_controller) = VIEW_CONTROLLERS.with_borrow(|vcs| vcs.get(&window).copied())
+    else {
+        return false;
+    };
+    unsafe {
+        let () = msg_send![
+            view_controller,
+            setPreferredContentSize: NSSize::new(size.width.0 as f64, size.height.0 as f64)
+        ];
+    }
+    true
+}
+
+struct ViewControllerState {
+    audio_unit: Cell<id>,
+    window: Cell<Option<AnyWindowHandle>>,
+}
+
+/// The state of a view controller, which its initializers allocate. `this` must have been
+/// initialized.
+unsafe fn state(this: &Object) -> &ViewControllerState {
+    unsafe {
+        let state: *mut c_void = *this.get_ivar(STATE_IVAR);
+        &*(state as *const ViewControllerState)
+    }
+}
+
+/// Allocate the state of a view controller that its superclass initialized, unless that failed.
+unsafe fn init_state(this: id) -> id {
+    unsafe {
+        if !this.is_null() {
+            let state = Box::new(ViewControllerState {
+                audio_unit: Cell::new(nil),
+                window: Cell::new(None),
+            });
+            (*this).set_ivar(STATE_IVAR, Box::into_raw(state).cast::<c_void>());
+        }
+        this
+    }
+}
+
+extern "C" fn init_with_nib_name(this: &mut Object, _: Sel, nib_name: id, bundle: id) -> id {
+    unsafe {
+        let this: id = msg_send![
+            super(this, class!(AUViewController)),
+            initWithNibName: nib_name
+            bundle: bundle
+        ];
+        init_state(this)
+    }
+}
+
+extern "C" fn init_with_coder(this: &mut Object, _: Sel, coder: id) -> id {
+    unsafe {
+        let this: id = msg_send![super(this, class!(AUViewController)), initWithCoder: coder];
+        init_state(this)
+    }
+}
+
+/// The app that editors run in, attaching it to the extension's event loop the first time.
+fn app<E: AudioUnitEditor>() -> AsyncApp {
+    if let Some(cx) = attached_app() {
+        return cx;
+    }
+    let application = E::application();
+    let attached = Rc::new(RefCell::new(None));
+    application.attach({
+        let attached = attached.clone();
+        move |cx| {
+            E::init(cx);
+            attached.replace(Some(cx.to_async()));
+        }
+    });
+    let cx = attached
+        .take()
+        .expect("attaching the app calls back before returning");
+    APP.set(Some((application, cx.clone())));
+    cx
+}
+
+fn attached_app() -> Option<AsyncApp> {
+    APP.with_borrow(|app| app.as_ref().map(|(_, cx)| cx.clone()))
+}
+
+/// Shut down the app that editors run in and forget their view controllers, see
+/// [`crate::runtime::shutdown`].
+pub(crate) fn release_thread_state() {
+    VIEW_CONTROLLERS.take();
+    if let Some((application, _)) = APP.take() {
+        application.shutdown();
+    }
+}
+
+extern "C" fn load_view<E: AudioUnitEditor>(this: &Object, _: Sel) {
+    unsafe {
+        let preferred_size = E::preferred_size();
+        let content_size = NSSize::new(
+            preferred_size.width.0 as f64,
+            preferred_size.height.0 as f64,
+        );
+        let view: id = msg_send![class!(NSView), alloc];
+        let view = NSView::initWithFrame_(view, NSRect::new(NSPoint::new(0., 0.), content_size));
+        let () = msg_send![view, setAutoresizingMask: NSViewWidthSizable | NSViewHeightSizable];
+        let () = msg_send![this, setView: view];
+        let () = msg_send![view, release];
+        let () = msg_send![this, setPreferredContentSize: content_size];
+
+        let state = state(this);
+        let audio_unit = NonNull::new(state.audio_unit.get().cast::<c_void>());
+        let raw_handle = rwh::AppKitWindowHandle::new(NonNull::new_unchecked(view.cast())).into();
+        let mut cx = app::<E>();
+        let Some(window) = cx
+            .update(|cx| {
+                cx.open_window(
+                    WindowOptions::for_embedded_window(raw_handle),
+                    move |window, cx| cx.new(|cx| E::build(audio_unit, window, cx)),
+                )
+            })
+            .and_then(|window| window)
+            .context("failed to open the audio unit's editor")
+            .log_err()
+        else {
+            return;
+        };
+        let window = AnyWindowHandle::from(window);
+        state.window.set(Some(window));
+        let this = this as *const Object as id;
+        VIEW_CONTROLLERS.with_borrow_mut(|vcs| vcs.insert(window, this));
+    }
+}
+
+extern "C" fn view_did_layout(this: &Object, _: Sel) {
+    unsafe {
+        let () = msg_send![super(this, class!(AUViewController)), viewDidLayout];
+        // The window's view follows the controller's view by itself, but hosts resize the
+        // controller's view without laying it out again while they live resize it.
+        let (Some(window), Some(mut cx)) = (state(this).window.get(), attached_app()) else {
+            return;
+        };
+        let view: id = msg_send![this, view];
+        let bounds: NSRect = msg_send![view, bounds];
+        let size: Size<Pixels> = bounds.size.into();
+        window
+            .update(&mut cx, |_, window, _| window.notify_embedded_resize(size))
+            .log_err();
+    }
+}
+
+extern "C" fn create_audio_unit<E: AudioUnitEditor>(
+    this: &Object,
+    _: Sel,
+    description: AudioComponentDescription,
+    error: *mut c_void,
+) -> id {
+    unsafe {
+        match E::create_audio_unit(description) {
+            Ok(audio_unit) => {
+                let audio_unit = audio_unit.as_ptr() as id;
+                let previous = state(this).audio_unit.replace(audio_unit);
+                if !previous.is_null() {
+                    let () = msg_send![previous, release];
+                }
+                audio_unit
+            }
+            Err(err) => {
+                log::error!("failed to create the audio unit: {err:#}");
+                if !error.is_null() {
+                    let user_info: id = msg_send![
+                        class!(NSDictionary),
+                        dictionaryWithObject: ns_string(&format!("{err:#}"))
+                        forKey: ns_string("NSLocalizedDescription")
+                    ];
+                    let ns_error: id = msg_send![
+                        class!(NSError),
+                        errorWithDomain: ns_string("GPUI")
+                        code: -1 as NSInteger
+                        userInfo: user_info
+                    ];
+                    *error.cast::<id>() = ns_error;
+                }
+                nil
+            }
+        }
+    }
+}
+
+extern "C" fn dealloc_view_controller(this: &Object, _: Sel) {
+    unsafe {
+        let state: *mut c_void = *this.get_ivar(STATE_IVAR);
+        if !state.is_null() {
+            let state = Box::from_raw(state as *mut ViewControllerState);
+            if let Some(window) = state.window.get() {
+                VIEW_CONTROLLERS.with_borrow_mut(|vcs| vcs.remove(&window));
+                if let Some(mut cx) = attached_app() {
+                    window
+                        .update(&mut cx, |_, window, _| window.detach_embedded())
+                        .log_err();
+                }
+            }
+            let audio_unit = state.audio_unit.get();
+            if !audio_unit.is_null() {
+                let () = msg_send![audio_unit, release];
+            }
+        }
+        let () = msg_send![super(this, class!(AUViewController)), dealloc];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EmptyView, px, size};
+    use anyhow::anyhow;
+    use objc::runtime::Class;
+
+    struct TestEditor;
+
+    impl AudioUnitEditor for TestEditor {
+        type View = EmptyView;
+
+        fn preferred_size() -> Size<Pixels> {
+            size(px(400.), px(300.))
+        }
+
+        fn create_audio_unit(_: AudioComponentDescription) -> Result<NonNull<c_void>> {
+            Err(anyhow!("no audio unit"))
+        }
+
+        fn build(
+            _: Option<NonNull<c_void>>,
+            _: &mut Window,
+            _: &mut Context<EmptyView>,
+        ) -> EmptyView {
+            EmptyView
+        }
+    }
+
+    #[test]
+    fn test_view_controller_state() {
+        register_view_controller::<TestEditor>("GPUITestViewController").unwrap();
+        assert!(register_view_controller::<TestEditor>("GPUITestViewController").is_err());
+
+        unsafe {
+            let class = Class::get("GPUITestViewController").unwrap();
+            let view_controller: id = msg_send![class, alloc];
+            let view_controller: id = msg_send![view_controller, init];
+            let ivar: *mut c_void = *(*view_controller).get_ivar(STATE_IVAR);
+            assert!(!ivar.is_null());
+
+            // A failure to create the audio unit is reported to the host, and leaves nothing
+            // for the view controller to release.
+            let mut error: id = nil;
+            let audio_unit: id = msg_send![
+                view_controller,
+                createAudioUnitWithComponentDescription: AudioComponentDescription::default()
+                error: (&raw mut error).cast::<c_void>()
+            ];
+            assert!(audio_unit.is_null());
+            assert!(!error.is_null());
+            assert!(state(&*view_controller).audio_unit.get().is_null());
+            assert!(state(&*view_controller).window.get().is_none());
+
+            let () = msg_send![view_controller, release];
+        }
+    }
+}
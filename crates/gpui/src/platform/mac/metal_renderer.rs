@@ -25,7 +25,14 @@ use metal::{
 use objc::{self, msg_send, sel, sel_impl};
 use parking_lot::Mutex;
 
-use std::{cell::Cell, ffi::c_void, mem, ptr, sync::Arc};
+use std::{
+    cell::Cell,
+    ffi::c_void,
+    mem, ptr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use util::ResultExt as _;
 
 // Exported to metal
 pub(crate) type PointF = crate::Point<f32>;
@@ -117,6 +124,7 @@ pub(crate) struct MetalRenderer {
     path_intermediate_texture: Option<metal::Texture>,
     path_intermediate_msaa_texture: Option<metal::Texture>,
     path_sample_count: u32,
+    capture_next_frame: bool,
 }
 
 #[repr(C)]
@@ -284,6 +292,7 @@ impl MetalRenderer {
             path_intermediate_texture: None,
             path_intermediate_msaa_texture: None,
             path_sample_count: PATH_SAMPLE_COUNT,
+            capture_next_frame: false,
         }
     }
 
@@ -299,6 +308,42 @@ impl MetalRenderer {
         &self.sprite_atlas
     }
 
+    /// Record the next frame with Metal's GPU frame capture. When the app is run from Xcode,
+    /// the capture opens in Xcode; otherwise it's written to a `.gputrace` document in the
+    /// temporary directory, which requires setting `MTL_CAPTURE_ENABLED=1`.
+    pub fn capture_next_frame(&mut self) -> Result<()> {
+        let capture_manager = metal::CaptureManager::shared();
+        anyhow::ensure!(
+            capture_manager.supports_destination(metal::MTLCaptureDestination::DeveloperTools)
+                || capture_manager
+                    .supports_destination(metal::MTLCaptureDestination::GpuTraceDocument),
+            "Metal GPU capture is unavailable, set MTL_CAPTURE_ENABLED=1 to enable it"
+        );
+        self.capture_next_frame = true;
+        Ok(())
+    }
+
+    fn start_gpu_capture(&self) -> Result<()> {
+        let capture_manager = metal::CaptureManager::shared();
+        let descriptor = metal::CaptureDescriptor::new();
+        descriptor.set_capture_command_queue(&self.command_queue);
+        if capture_manager.supports_destination(metal::MTLCaptureDestination::DeveloperTools) {
+            descriptor.set_destination(metal::MTLCaptureDestination::DeveloperTools);
+        } else {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis();
+            let path = std::env::temp_dir().join(format!("gpui-{timestamp}.gputrace"));
+            log::info!("writing GPU capture to {}", path.display());
+            descriptor.set_destination(metal::MTLCaptureDestination::GpuTraceDocument);
+            descriptor.set_output_url(path);
+        }
+        capture_manager
+            .start_capture(&descriptor)
+            .map_err(|error| anyhow::anyhow!("failed to start GPU capture: {error}"))
+    }
+
     pub fn set_presents_with_transaction(&mut self, presents_with_transaction: bool) {
         self.presents_with_transaction = presents_with_transaction;
         self.layer
@@ -377,6 +422,9 @@ impl MetalRenderer {
             return;
         };
 
+        let capturing =
+            mem::take(&mut self.capture_next_frame) && self.start_gpu_capture().log_err().is_some();
+
         loop {
             let mut instance_buffer = self.instance_buffer_pool.lock().acquire(&self.device);
 
@@ -403,7 +451,7 @@ impl MetalRenderer {
                         command_buffer.present_drawable(drawable);
                         command_buffer.commit();
                     }
-                    return;
+                    break;
                 }
                 Err(err) => {
                     log::error!(
@@ -424,6 +472,10 @@ impl MetalRenderer {
                 }
             }
         }
+
+        if capturing {
+            metal::CaptureManager::shared().stop_capture();
+        }
     }
 
     fn draw_primitives(
@@ -435,6 +487,7 @@ impl MetalRenderer {
     ) -> Result<metal::CommandBuffer> {
         let command_queue = self.command_queue.clone();
         let command_buffer = command_queue.new_command_buffer();
+        command_buffer.set_label("GPUI frame");
         let alpha = if self.layer.is_opaque() { 1. } else { 0. };
         let mut instance_offset = 0;
 
@@ -449,6 +502,8 @@ impl MetalRenderer {
         );
 
         for batch in scene.batches() {
+            // Group each batch's commands so that they can be told apart in GPU captures.
+            command_encoder.push_debug_group(batch.debug_label());
             let ok = match batch {
                 PrimitiveBatch::Shadows(shadows) => self.draw_shadows(
                     shadows,
@@ -465,6 +520,7 @@ impl MetalRenderer {
                     command_encoder,
                 ),
                 PrimitiveBatch::Paths(paths) => {
+                    command_encoder.pop_debug_group();
                     command_encoder.end_encoding();
 
                     let did_draw = self.draw_paths_to_intermediate(
@@ -483,6 +539,7 @@ impl MetalRenderer {
                             color_attachment.set_load_action(metal::MTLLoadAction::Load);
                         },
                     );
+                    command_encoder.push_debug_group(batch.debug_label());
 
                     if did_draw {
                         self.draw_paths_from_intermediate(
@@ -533,6 +590,7 @@ impl MetalRenderer {
                     command_encoder,
                 ),
             };
+            command_encoder.pop_debug_group();
             if !ok {
                 command_encoder.end_encoding();
                 anyhow::bail!(
@@ -590,6 +648,7 @@ impl MetalRenderer {
         }
 
         let command_encoder = command_buffer.new_render_command_encoder(render_pass_descriptor);
+        command_encoder.set_label("Path rasterization");
         command_encoder.set_render_pipeline_state(&self.paths_rasterization_pipeline_state);
 
         align_offset(instance_offset);
@@ -1,6 +1,7 @@
 use super::{
-    BoolExt, MacKeyboardLayout, MacKeyboardMapper,
+    BoolExt, MacKeyboardLayout, MacKeyboardMapper, MacSpellChecker,
     attributed_string::{NSAttributedString, NSMutableAttributedString},
+    dispose_class, dispose_window_classes,
     events::key_to_native,
     renderer,
 };
@@ -8,9 +9,9 @@ use crate::{
     Action, AnyWindowHandle, BackgroundExecutor, ClipboardEntry, ClipboardItem, ClipboardString,
     CursorStyle, ForegroundExecutor, Image, ImageFormat, KeyContext, Keymap, MacDispatcher,
     MacDisplay, MacWindow, Menu, MenuItem, OsMenu, OwnedMenu, PathPromptOptions, Platform,
-    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem,
-    PlatformWindow, Result, SemanticVersion, SystemMenuType, Task, WindowAppearance, WindowParams,
-    hash,
+    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformSpellChecker,
+    PlatformTextSystem, PlatformWindow, PowerState, Result, SemanticVersion, SystemMenuType, Task,
+    ThermalPressure, WindowAppearance, WindowParams, hash,
 };
 use anyhow::{Context as _, anyhow};
 use block::ConcreteBlock;
@@ -28,10 +29,12 @@ use cocoa::{
     },
 };
 use core_foundation::{
+    array::{CFArray, CFArrayRef},
     base::{CFRelease, CFType, CFTypeRef, OSStatus, TCFType},
     boolean::CFBoolean,
     data::CFData,
     dictionary::{CFDictionary, CFDictionaryRef, CFMutableDictionary},
+    number::CFNumber,
     runloop::CFRunLoopRun,
     string::{CFString, CFStringRef},
 };
@@ -57,6 +60,7 @@ use std::{
     rc::Rc,
     slice, str,
     sync::{Arc, OnceLock},
+    time::Duration,
 };
 use strum::IntoEnumIterator;
 use util::{
@@ -156,6 +160,29 @@ unsafe fn build_classes() {
     }
 }
 
+/// Dispose of the Objective-C classes that GPUI registers, see [`crate::runtime::shutdown`].
+///
+/// When GPUI's application class or delegate is still installed, as it is when GPUI owns the
+/// process rather than running inside a host, those classes are left registered.
+pub(crate) unsafe fn dispose_classes() {
+    unsafe {
+        dispose_window_classes();
+        #[cfg(feature = "screen-capture")]
+        super::screen_capture::dispose_screen_capture_classes();
+
+        let app = cocoa::appkit::NSApp();
+        let app_is_gpui: BOOL = msg_send![app, isKindOfClass: APP_CLASS];
+        if app_is_gpui == NO {
+            dispose_class(&raw mut APP_CLASS);
+        }
+        let delegate: id = msg_send![app, delegate];
+        let delegate_is_gpui: BOOL = msg_send![delegate, isKindOfClass: APP_DELEGATE_CLASS];
+        if delegate_is_gpui == NO {
+            dispose_class(&raw mut APP_DELEGATE_CLASS);
+        }
+    }
+}
+
 pub(crate) struct MacPlatform(Mutex<MacPlatformState>);
 
 pub(crate) struct MacPlatformState {
@@ -507,6 +534,34 @@ impl Platform for MacPlatform {
         }
     }
 
+    fn attach(&self, on_finish_launching: Box<dyn FnOnce()>) {
+        // The host's application and delegate stay installed, and its run loop drains the main
+        // dispatch queue that GPUI's foreground tasks are dispatched to.
+        on_finish_launching();
+    }
+
+    fn is_event_loop_running(&self) -> bool {
+        if self.0.lock().headless {
+            return false;
+        }
+        unsafe {
+            // `NSApp` is only set once the host has created its application, and the display
+            // links' dispatch sources and foreground tasks already target the main queue, which
+            // the host's run loop drains in its common modes.
+            let app = cocoa::appkit::NSApp();
+            if app == nil {
+                return false;
+            }
+            let running: BOOL = msg_send![app, isRunning];
+            running == YES
+        }
+    }
+
+    fn pump(&self) {
+        // Foreground tasks run as the host's run loop drains the main dispatch queue, and running
+        // that loop from here would reenter the host.
+    }
+
     fn quit(&self) {
         // Quitting the app causes us to close windows, which invokes `Window::on_close` callbacks
         // synchronously before this method terminates. If we call `Platform::quit` while holding a
@@ -635,6 +690,15 @@ impl Platform for MacPlatform {
         options: WindowParams,
     ) -> Result<Box<dyn PlatformWindow>> {
         let renderer_context = self.0.lock().renderer_context.clone();
+        if let Some(raw_handle) = options.raw_window_handle {
+            return Ok(Box::new(MacWindow::open_embedded(
+                handle,
+                options,
+                raw_handle,
+                self.foreground_executor(),
+                renderer_context,
+            )?));
+        }
         Ok(Box::new(MacWindow::open(
             handle,
             options,
@@ -913,6 +977,43 @@ impl Platform for MacPlatform {
         self.0.lock().keyboard_mapper.clone()
     }
 
+    fn spell_checker(&self) -> Option<Rc<dyn PlatformSpellChecker>> {
+        Some(Rc::new(MacSpellChecker))
+    }
+
+    fn system_idle_time(&self) -> Option<Duration> {
+        // kCGEventSourceStateCombinedSessionState, kCGAnyInputEventType
+        let seconds = unsafe { CGEventSourceSecondsSinceLastEventType(0, u32::MAX) };
+        Some(Duration::from_secs_f64(seconds.max(0.)))
+    }
+
+    fn power_state(&self) -> PowerState {
+        unsafe {
+            let process_info = NSProcessInfo::processInfo(nil);
+            let thermal_state: NSInteger = msg_send![process_info, thermalState];
+            // Low Power Mode is only available on macOS 12 and later.
+            let supports_low_power_mode: BOOL =
+                msg_send![process_info, respondsToSelector: sel!(isLowPowerModeEnabled)];
+            let low_power_mode: BOOL = if supports_low_power_mode == YES {
+                msg_send![process_info, isLowPowerModeEnabled]
+            } else {
+                NO
+            };
+            let (on_battery, battery_level) = power_source_state();
+            PowerState {
+                on_battery,
+                battery_level,
+                low_power_mode: low_power_mode == YES,
+                thermal_pressure: match thermal_state {
+                    1 => ThermalPressure::Fair,
+                    2 => ThermalPressure::Serious,
+                    3 => ThermalPressure::Critical,
+                    _ => ThermalPressure::Nominal,
+                },
+            }
+        }
+    }
+
     fn app_path(&self) -> Result<PathBuf> {
         unsafe {
             let bundle: id = NSBundle::mainBundle();
@@ -1583,6 +1684,58 @@ unsafe extern "C" {
     pub(super) static kTISPropertyLocalizedName: CFStringRef;
 }
 
+#[link(name = "CoreGraphics", kind = "framework")]
+unsafe extern "C" {
+    fn CGEventSourceSecondsSinceLastEventType(state_id: i32, event_type: u32) -> f64;
+}
+
+#[link(name = "IOKit", kind = "framework")]
+unsafe extern "C" {
+    fn IOPSCopyPowerSourcesInfo() -> CFTypeRef;
+    fn IOPSCopyPowerSourcesList(blob: CFTypeRef) -> CFArrayRef;
+    fn IOPSGetPowerSourceDescription(blob: CFTypeRef, power_source: CFTypeRef) -> CFDictionaryRef;
+    fn IOPSGetProvidingPowerSourceType(snapshot: CFTypeRef) -> CFStringRef;
+}
+
+/// Whether the system is running on battery power, and the charge of its first battery.
+unsafe fn power_source_state() -> (bool, Option<f32>) {
+    unsafe {
+        let info = IOPSCopyPowerSourcesInfo();
+        if info.is_null() {
+            return (false, None);
+        }
+        let info = CFType::wrap_under_create_rule(info);
+
+        let providing_type = IOPSGetProvidingPowerSourceType(info.as_CFTypeRef());
+        let on_battery = !providing_type.is_null()
+            && CFString::wrap_under_get_rule(providing_type).to_string() == "Battery Power";
+
+        let sources = IOPSCopyPowerSourcesList(info.as_CFTypeRef());
+        if sources.is_null() {
+            return (on_battery, None);
+        }
+        let sources = CFArray::<CFType>::wrap_under_create_rule(sources);
+        let capacity = |description: &CFDictionary<CFString, CFType>, key: &'static str| {
+            description
+                .find(CFString::from_static_string(key))
+                .and_then(|value| value.downcast::<CFNumber>())
+                .and_then(|value| value.to_i64())
+        };
+        let battery_level = sources.iter().find_map(|source| {
+            let description =
+                IOPSGetPowerSourceDescription(info.as_CFTypeRef(), source.as_CFTypeRef());
+            if description.is_null() {
+                return None;
+            }
+            let description = CFDictionary::<CFString, CFType>::wrap_under_get_rule(description);
+            let current = capacity(&description, "Current Capacity")?;
+            let max = capacity(&description, "Max Capacity")?;
+            (max > 0).then(|| (current as f32 / max as f32).clamp(0., 1.))
+        });
+        (on_battery, battery_level)
+    }
+}
+
 mod security {
     #![allow(non_upper_case_globals)]
     use super::*;
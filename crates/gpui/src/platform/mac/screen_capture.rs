@@ -28,7 +28,7 @@ use objc::{
 };
 use std::{cell::RefCell, ffi::c_void, mem, ptr, rc::Rc};
 
-use super::NSStringExt;
+use super::{NSStringExt, dispose_class};
 
 #[derive(Clone)]
 pub struct MacScreenCaptureSource {
@@ -274,6 +274,14 @@ pub(crate) fn get_sources() -> oneshot::Receiver<Result<Vec<Rc<dyn ScreenCapture
     }
 }
 
+/// Dispose of the screen capture classes, see [`crate::runtime::shutdown`].
+pub(crate) unsafe fn dispose_screen_capture_classes() {
+    unsafe {
+        dispose_class(&raw mut DELEGATE_CLASS);
+        dispose_class(&raw mut OUTPUT_CLASS);
+    }
+}
+
 #[ctor]
 unsafe fn build_classes() {
     let mut decl = ClassDecl::new("GPUIStreamDelegate", class!(NSObject)).unwrap();
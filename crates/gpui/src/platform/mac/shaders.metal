@@ -16,6 +16,9 @@ float4 to_device_position_transformed(float2 unit_vertex, Bounds_ScaledPixels bo
 
 float2 to_tile_position(float2 unit_vertex, AtlasTile tile,
                         constant Size_DevicePixels *atlas_size);
+float4 waveform_color(float2 position, PolychromeSprite sprite,
+                      texture2d<float> atlas_texture);
+float decode_waveform_value(float high, float low);
 float4 distance_from_clip_rect(float2 unit_vertex, Bounds_ScaledPixels bounds,
                                Bounds_ScaledPixels clip_bounds);
 float4 distance_from_clip_rect_transformed(float2 unit_vertex, Bounds_ScaledPixels bounds,
@@ -708,6 +711,9 @@ fragment float4 polychrome_sprite_fragment(
       quad_sdf(input.position.xy, sprite.bounds, sprite.corner_radii);
 
   float4 color = sample;
+  if (sprite.peak_count > 0) {
+    color = waveform_color(input.position.xy, sprite, atlas_texture);
+  }
   if (sprite.grayscale) {
     float grayscale = 0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b;
     color.r = grayscale;
@@ -1028,6 +1034,48 @@ float2 to_tile_position(float2 unit_vertex, AtlasTile tile,
          float2((float)atlas_size->width, (float)atlas_size->height);
 }
 
+// Reduces the peaks of a waveform tile that fall within this pixel's column to
+// their overall extent, returning the waveform's color if the pixel lies within
+// it. See `Window::paint_waveform` for the layout of the tile.
+float4 waveform_color(float2 position, PolychromeSprite sprite,
+                      texture2d<float> atlas_texture) {
+  uint2 origin = uint2(sprite.tile.bounds.origin.x, sprite.tile.bounds.origin.y);
+  uint capacity = uint(sprite.tile.bounds.size.width) - 2;
+  float4 color = atlas_texture.read(origin);
+  float4 header = atlas_texture.read(origin + uint2(1, 0));
+  uint oldest = uint(round(header.r * 255.)) * 256 + uint(round(header.g * 255.));
+
+  float count = float(sprite.peak_count);
+  float left = (position.x - 0.5 - sprite.bounds.origin.x) / sprite.bounds.size.width;
+  float right = (position.x + 0.5 - sprite.bounds.origin.x) / sprite.bounds.size.width;
+  uint first = uint(clamp(floor(left * count), 0., count - 1.));
+  uint last = uint(clamp(ceil(right * count) - 1., float(first), count - 1.));
+
+  float minimum = 1.;
+  float maximum = -1.;
+  for (uint peak = first; peak <= last; peak++) {
+    uint slot = (oldest + peak) % capacity;
+    float4 texel = atlas_texture.read(origin + uint2(2 + slot, 0));
+    minimum = min(minimum, decode_waveform_value(texel.r, texel.g));
+    maximum = max(maximum, decode_waveform_value(texel.b, texel.a));
+  }
+
+  // Values run from 1 at the top of the bounds to -1 at the bottom. Every
+  // column covers at least one pixel, so that silence still shows as a line.
+  float half_pixel = 1. / sprite.bounds.size.height;
+  float value =
+      1. - 2. * (position.y - sprite.bounds.origin.y) / sprite.bounds.size.height;
+  if (value - half_pixel > maximum || value + half_pixel < minimum) {
+    return float4(0.);
+  }
+  return color;
+}
+
+float decode_waveform_value(float high, float low) {
+  float value = round(high * 255.) * 256. + round(low * 255.);
+  return value / 65535. * 2. - 1.;
+}
+
 // Selects corner radius based on quadrant.
 float pick_corner_radius(float2 center_to_point, Corners_ScaledPixels corner_radii) {
   if (center_to_point.x < 0.) {
@@ -0,0 +1,83 @@
+use std::{ops::Range, ptr};
+
+use cocoa::{
+    base::{NO, id, nil},
+    foundation::{NSArray, NSInteger, NSUInteger},
+};
+use objc::{class, msg_send, sel, sel_impl};
+
+use super::{NSRange, NSStringExt, ns_string};
+use crate::{PlatformSpellChecker, utf16_range_to_utf8};
+
+/// The system spell checker, `NSSpellChecker`.
+pub(crate) struct MacSpellChecker;
+
+impl MacSpellChecker {
+    unsafe fn shared() -> id {
+        unsafe { msg_send![class!(NSSpellChecker), sharedSpellChecker] }
+    }
+
+    /// `NSSpellChecker` takes languages as `en_US` rather than `en-US`, and `nil` to detect them.
+    unsafe fn language(language: Option<&str>) -> id {
+        match language {
+            Some(language) => unsafe { ns_string(&language.replace('-', "_")) },
+            None => nil,
+        }
+    }
+}
+
+impl PlatformSpellChecker for MacSpellChecker {
+    fn check(&self, text: &str, language: Option<&str>) -> Vec<Range<usize>> {
+        let mut misspelled = Vec::new();
+        unsafe {
+            let checker = Self::shared();
+            let string = ns_string(text);
+            let language = Self::language(language);
+            let length: NSUInteger = msg_send![string, length];
+            let mut offset: NSUInteger = 0;
+            while offset < length {
+                let range: NSRange = msg_send![
+                    checker,
+                    checkSpellingOfString: string
+                    startingAt: offset as NSInteger
+                    language: language
+                    wrap: NO
+                    inSpellDocumentWithTag: 0 as NSInteger
+                    wordCount: ptr::null_mut::<NSInteger>()
+                ];
+                let Some(range) = range.to_range().filter(|range| !range.is_empty()) else {
+                    break;
+                };
+                offset = range.end as NSUInteger;
+                misspelled.push(utf16_range_to_utf8(text, range));
+            }
+        }
+        misspelled
+    }
+
+    fn suggestions(&self, word: &str, language: Option<&str>) -> Vec<String> {
+        unsafe {
+            let string = ns_string(word);
+            let length: NSUInteger = msg_send![string, length];
+            let guesses: id = msg_send![
+                Self::shared(),
+                guessesForWordRange: NSRange::from(0..length as usize)
+                inString: string
+                language: Self::language(language)
+                inSpellDocumentWithTag: 0 as NSInteger
+            ];
+            if guesses == nil {
+                return Vec::new();
+            }
+            (0..guesses.count())
+                .map(|ix| guesses.objectAtIndex(ix).to_str().to_string())
+                .collect()
+        }
+    }
+
+    fn learn_word(&self, word: &str) {
+        unsafe {
+            let _: () = msg_send![Self::shared(), learnWord: ns_string(word)];
+        }
+    }
+}
@@ -229,7 +229,7 @@ impl platform::Window for StatusItem {
         unimplemented!()
     }
 
-    fn show_character_palette(&self) {
+    fn show_character_palette(&self) -> bool {
         unimplemented!()
     }
 
@@ -1,6 +1,6 @@
 use crate::{
-    Bounds, DevicePixels, Font, FontFallbacks, FontFeatures, FontId, FontMetrics, FontRun,
-    FontStyle, FontWeight, GlyphId, LineLayout, Pixels, PlatformTextSystem, Point,
+    AssetBytes, Bounds, DevicePixels, Font, FontFallbacks, FontFeatures, FontId, FontMetrics,
+    FontRun, FontStyle, FontWeight, GlyphId, LineLayout, Pixels, PlatformTextSystem, Point,
     RenderGlyphParams, Result, SUBPIXEL_VARIANTS_X, ShapedGlyph, ShapedRun, SharedString, Size,
     point, px, size, swap_rgba_pa_to_bgra,
 };
@@ -94,6 +94,10 @@ impl PlatformTextSystem for MacTextSystem {
         self.0.write().add_fonts(fonts)
     }
 
+    fn add_font_bytes(&self, fonts: Vec<AssetBytes>) -> Result<()> {
+        self.0.write().add_font_bytes(fonts)
+    }
+
     fn all_font_names(&self) -> Vec<String> {
         let mut names = Vec::new();
         let collection = core_text::font_collection::create_for_all_families();
@@ -205,6 +209,22 @@ impl MacTextSystemState {
         Ok(())
     }
 
+    fn add_font_bytes(&mut self, fonts: Vec<AssetBytes>) -> Result<()> {
+        let fonts = fonts
+            .into_iter()
+            .map(|font| {
+                let data_provider =
+                    core_graphics::data_provider::CGDataProvider::from_buffer(Arc::new(font));
+                let font = core_graphics::font::CGFont::from_data_provider(data_provider)
+                    .map_err(|()| anyhow!("Could not load a font."))?;
+                let font = font_kit::loaders::core_text::Font::from_core_graphics_font(font);
+                Ok(Handle::from_native(&font))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.memory_source.add_fonts(fonts.into_iter())?;
+        Ok(())
+    }
+
     fn load_family(
         &mut self,
         name: &str,
@@ -1,12 +1,13 @@
-use super::{BoolExt, MacDisplay, NSRange, NSStringExt, ns_string, renderer};
+use super::{BoolExt, MacDisplay, NSRange, NSStringExt, dispose_class, ns_string, renderer};
 use crate::{
-    AnyWindowHandle, Bounds, Capslock, DisplayLink, ExternalPaths, FileDropEvent,
-    ForegroundExecutor, KeyDownEvent, Keystroke, Modifiers, ModifiersChangedEvent, MouseButton,
-    MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, PlatformAtlas, PlatformDisplay,
-    PlatformInput, PlatformWindow, Point, PromptButton, PromptLevel, RequestFrameOptions,
-    SharedString, Size, SystemWindowTab, Timer, WindowAppearance, WindowBackgroundAppearance,
-    WindowBounds, WindowControlArea, WindowKind, WindowParams, dispatch_get_main_queue,
-    dispatch_sys::dispatch_async_f, platform::PlatformInputHandler, point, px, size,
+    AnyWindowHandle, AppMode, Bounds, Capslock, DisplayLink, ExternalPaths, FileDropEvent,
+    ForegroundExecutor, FrameDriver, HostWindowInfo, KeyDownEvent, Keystroke, Modifiers,
+    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels,
+    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformWindow, Point, PromptButton,
+    PromptLevel, RequestFrameOptions, SharedString, Size, SystemWindowTab, Timer, WindowAppearance,
+    WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowKind, WindowOpenError,
+    WindowParams, dispatch_get_main_queue, dispatch_sys::dispatch_async_f,
+    platform::PlatformInputHandler, point, px, size, trace_embedded,
 };
 use block::ConcreteBlock;
 use cocoa::{
@@ -26,7 +27,7 @@ use cocoa::{
     },
 };
 
-use core_graphics::display::{CGDirectDisplayID, CGPoint, CGRect};
+use core_graphics::display::{CGDirectDisplayID, CGDisplay, CGPoint, CGRect};
 use ctor::ctor;
 use futures::channel::oneshot;
 use objc::{
@@ -58,6 +59,7 @@ static mut WINDOW_CLASS: *const Class = ptr::null();
 static mut PANEL_CLASS: *const Class = ptr::null();
 static mut VIEW_CLASS: *const Class = ptr::null();
 static mut BLURRED_VIEW_CLASS: *const Class = ptr::null();
+static mut HOSTED_VIEW_CLASS: *const Class = ptr::null();
 
 #[allow(non_upper_case_globals)]
 const NSWindowStyleMaskNonactivatingPanel: NSWindowStyleMask =
@@ -65,6 +67,8 @@ const NSWindowStyleMaskNonactivatingPanel: NSWindowStyleMask =
 #[allow(non_upper_case_globals)]
 const NSNormalWindowLevel: NSInteger = 0;
 #[allow(non_upper_case_globals)]
+const NSFloatingWindowLevel: NSInteger = 3;
+#[allow(non_upper_case_globals)]
 const NSPopUpWindowLevel: NSInteger = 101;
 #[allow(non_upper_case_globals)]
 const NSTrackingMouseEnteredAndExited: NSUInteger = 0x01;
@@ -75,9 +79,16 @@ const NSTrackingActiveAlways: NSUInteger = 0x80;
 #[allow(non_upper_case_globals)]
 const NSTrackingInVisibleRect: NSUInteger = 0x200;
 #[allow(non_upper_case_globals)]
+const NSTrackingEnabledDuringMouseDrag: NSUInteger = 0x400;
+#[allow(non_upper_case_globals)]
 const NSWindowAnimationBehaviorUtilityWindow: NSInteger = 4;
 #[allow(non_upper_case_globals)]
 const NSViewLayerContentsRedrawDuringViewResize: NSInteger = 2;
+// https://developer.apple.com/documentation/quartzcore/caautoresizingmask
+#[allow(non_upper_case_globals)]
+const kCALayerWidthSizable: NSUInteger = 1 << 1;
+#[allow(non_upper_case_globals)]
+const kCALayerHeightSizable: NSUInteger = 1 << 4;
 // https://developer.apple.com/documentation/appkit/nsdragoperation
 type NSDragOperation = NSUInteger;
 #[allow(non_upper_case_globals)]
@@ -266,6 +277,35 @@ unsafe fn build_classes() {
                 decl.register()
             }
         };
+        // The view that embedded windows add to the host's view. It behaves like `GPUIView`, but
+        // hosts GPUI's Metal layer itself rather than being backed by a layer that AppKit
+        // creates, so it doesn't depend on whether the host's view is layer-backed.
+        HOSTED_VIEW_CLASS = {
+            let mut decl = ClassDecl::new("GPUIHostedView", &*VIEW_CLASS).unwrap();
+            unsafe {
+                decl.add_method(
+                    sel!(viewWillMoveToWindow:),
+                    hosted_view_will_move_to_window as extern "C" fn(&Object, Sel, id),
+                );
+                decl.add_method(
+                    sel!(viewDidMoveToWindow),
+                    hosted_view_did_move_to_window as extern "C" fn(&Object, Sel),
+                );
+                decl.add_method(
+                    sel!(viewDidHide),
+                    hosted_view_did_hide as extern "C" fn(&Object, Sel),
+                );
+                decl.add_method(
+                    sel!(viewDidUnhide),
+                    hosted_view_did_unhide as extern "C" fn(&Object, Sel),
+                );
+                decl.add_method(
+                    sel!(hostWindowDidChange:),
+                    host_window_did_change as extern "C" fn(&Object, Sel, id),
+                );
+            }
+            decl.register()
+        };
     }
 }
 
@@ -277,6 +317,17 @@ pub(crate) fn convert_mouse_position(position: NSPoint, window_height: Pixels) -
     )
 }
 
+/// Dispose of the window and view classes, see [`crate::runtime::shutdown`].
+pub(crate) unsafe fn dispose_window_classes() {
+    unsafe {
+        dispose_class(&raw mut WINDOW_CLASS);
+        dispose_class(&raw mut PANEL_CLASS);
+        dispose_class(&raw mut HOSTED_VIEW_CLASS);
+        dispose_class(&raw mut VIEW_CLASS);
+        dispose_class(&raw mut BLURRED_VIEW_CLASS);
+    }
+}
+
 unsafe fn build_window_class(name: &'static str, superclass: &Class) -> *const Class {
     unsafe {
         let mut decl = ClassDecl::new(name, superclass).unwrap();
@@ -311,6 +362,14 @@ unsafe fn build_window_class(name: &'static str, superclass: &Class) -> *const C
             sel!(windowDidMove:),
             window_did_move as extern "C" fn(&Object, Sel, id),
         );
+        decl.add_method(
+            sel!(windowDidMiniaturize:),
+            window_did_move as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(windowDidDeminiaturize:),
+            window_did_move as extern "C" fn(&Object, Sel, id),
+        );
         decl.add_method(
             sel!(windowDidChangeScreen:),
             window_did_change_screen as extern "C" fn(&Object, Sel, id),
@@ -390,7 +449,12 @@ struct MacWindowState {
     executor: ForegroundExecutor,
     native_window: id,
     native_view: NonNull<Object>,
+    // Whether `native_view` is a subview of a host's view, rather than the content of
+    // `native_window`, which then belongs to the host.
+    embedded: bool,
     blurred_view: Option<id>,
+    frame_driver: FrameDriver,
+    app_mode: AppMode,
     display_link: Option<DisplayLink>,
     renderer: renderer::Renderer,
     request_frame_callback: Option<Box<dyn FnMut(RequestFrameOptions)>>,
@@ -401,6 +465,12 @@ struct MacWindowState {
     should_close_callback: Option<Box<dyn FnMut() -> bool>>,
     close_callback: Option<Box<dyn FnOnce()>>,
     appearance_changed_callback: Option<Box<dyn FnMut()>>,
+    host_info_changed_callback: Option<Box<dyn FnMut()>>,
+    // The state of the host's view and window last reported to GPUI, for embedded windows.
+    host_info: Option<HostWindowInfo>,
+    // The host's content view and the tracking area added to it while an embedded window
+    // captures the mouse, see `start_mouse_capture`.
+    mouse_capture: Option<(id, id)>,
     input_handler: Option<PlatformInputHandler>,
     last_key_equivalent: Option<KeyDownEvent>,
     synthetic_drag_counter: usize,
@@ -475,6 +545,9 @@ impl MacWindowState {
 
     fn start_display_link(&mut self) {
         self.stop_display_link();
+        if self.frame_driver == FrameDriver::External {
+            return;
+        }
         unsafe {
             if !self
                 .native_window
@@ -537,8 +610,10 @@ impl MacWindowState {
     }
 
     fn content_size(&self) -> Size<Pixels> {
+        // Embedded views don't fill their window, so measure the view rather than the window's
+        // content view.
         let NSSize { width, height, .. } =
-            unsafe { NSView::frame(self.native_window.contentView()) }.size;
+            unsafe { NSView::frame(self.native_view.as_ptr() as id) }.size;
         size(px(width as f32), px(height as f32))
     }
 
@@ -582,31 +657,18 @@ impl MacWindow {
             display_id,
             window_min_size,
             tabbing_identifier,
-            raw_window_handle,
+            // Embedded windows are opened with `open_embedded` instead.
+            raw_window_handle: _,
+            raw_display_handle: _,
+            embedding_mode: _,
+            host_gpu_device: _,
+            background_appearance: _,
+            frame_driver,
+            app_mode,
         }: WindowParams,
         executor: ForegroundExecutor,
         renderer_context: renderer::Context,
     ) -> Self {
-        // If raw_window_handle is provided, use embedded mode
-        if let Some(raw_handle) = raw_window_handle {
-            let params_for_embedded = WindowParams {
-                bounds,
-                titlebar,
-                kind,
-                is_movable,
-                is_resizable,
-                is_minimizable,
-                focus,
-                show,
-                display_id,
-                window_min_size,
-                tabbing_identifier: tabbing_identifier.clone(),
-                raw_window_handle: Some(raw_handle),
-            };
-            return Self::open_embedded(handle, params_for_embedded, raw_handle, executor, renderer_context)
-                .expect("Failed to create embedded macOS window");
-        }
-        
         unsafe {
             let pool = NSAutoreleasePool::new(nil);
 
@@ -708,7 +770,10 @@ impl MacWindow {
                 executor,
                 native_window,
                 native_view: NonNull::new_unchecked(native_view),
+                embedded: false,
                 blurred_view: None,
+                frame_driver,
+                app_mode,
                 display_link: None,
                 renderer: renderer::new_renderer(
                     renderer_context,
@@ -725,6 +790,9 @@ impl MacWindow {
                 should_close_callback: None,
                 close_callback: None,
                 appearance_changed_callback: None,
+                host_info_changed_callback: None,
+                host_info: None,
+                mouse_capture: None,
                 input_handler: None,
                 last_key_equivalent: None,
                 synthetic_drag_counter: 0,
@@ -956,6 +1024,24 @@ impl Drop for MacWindow {
         this.renderer.destroy();
         let window = this.native_window;
         this.display_link.take();
+        if this.embedded {
+            // The host's window and view outlive this window, and other embedded windows may
+            // share them, so only remove GPUI's own view. Releasing it frees the window state.
+            this.input_handler.take();
+            this.host_info_changed_callback.take();
+            this.end_mouse_capture();
+            let view = this.native_view.as_ptr();
+            trace_embedded(|| format!("[{:?} removeFromSuperview]", view));
+            this.executor
+                .spawn(async move {
+                    unsafe {
+                        let () = msg_send![view, removeFromSuperview];
+                        let () = msg_send![view, release];
+                    }
+                })
+                .detach();
+            return;
+        }
         unsafe {
             this.native_window.setDelegate_(nil);
         }
@@ -1003,6 +1089,135 @@ impl PlatformWindow for MacWindow {
             .detach();
     }
 
+    fn notify_embedded_resize(&mut self, size: Size<Pixels>) {
+        let this = self.0.lock();
+        // Resizing the view updates the drawable size and calls the resize callback.
+        let native_view = this.native_view.as_ptr() as id;
+        this.executor
+            .spawn(async move {
+                trace_embedded(|| format!("[{:?} setFrameSize: {:?}]", native_view, size));
+                unsafe {
+                    let () = msg_send![native_view, setFrameSize: NSSize {
+                        width: size.width.0 as f64,
+                        height: size.height.0 as f64,
+                    }];
+                }
+            })
+            .detach();
+    }
+
+    fn detach_embedded(&self) {
+        let native_view = {
+            let mut this = self.0.lock();
+            if !this.embedded {
+                return;
+            }
+            this.display_link.take();
+            this.native_view.as_ptr() as id
+        };
+        // Not holding the lock, as removing the view calls back into it.
+        trace_embedded(|| format!("[{:?} removeFromSuperview]", native_view));
+        unsafe {
+            let () = msg_send![native_view, removeFromSuperview];
+        }
+    }
+
+    fn park_embedded(&self) -> anyhow::Result<()> {
+        if !self.0.lock().embedded {
+            return Err(
+                WindowOpenError::Unsupported("Only embedded windows can be parked".into()).into(),
+            );
+        }
+        // The view was retained when it was created, so it outlives being removed from its host,
+        // and stops rendering until it's added to another.
+        self.detach_embedded();
+        Ok(())
+    }
+
+    fn reparent_embedded(&self, raw_handle: rwh::RawWindowHandle) -> anyhow::Result<()> {
+        let rwh::RawWindowHandle::AppKit(appkit_handle) = raw_handle else {
+            return Err(WindowOpenError::InvalidHandle(format!(
+                "Expected AppKit window handle for macOS platform, got {:?}",
+                raw_handle
+            ))
+            .into());
+        };
+        let host_view = appkit_handle.ns_view.as_ptr() as id;
+        let native_view = {
+            let this = self.0.lock();
+            if !this.embedded {
+                return Err(WindowOpenError::Unsupported(
+                    "Only embedded windows can move to another host view".into(),
+                )
+                .into());
+            }
+            this.native_view.as_ptr() as id
+        };
+
+        // Not holding the lock, as moving the view calls back into it.
+        unsafe {
+            let is_view: bool = msg_send![host_view, isKindOfClass: class!(NSView)];
+            if !is_view {
+                return Err(WindowOpenError::InvalidHandle(format!(
+                    "Provided handle is not an NSView: {:?}",
+                    host_view
+                ))
+                .into());
+            }
+            let host_bounds: NSRect = msg_send![host_view, bounds];
+            let host_window: id = msg_send![host_view, window];
+            trace_embedded(|| format!("[{:?} addSubview: {:?}]", host_view, native_view));
+            // The view was retained when it was created, so it outlives being removed.
+            let () = msg_send![native_view, removeFromSuperview];
+            let () = msg_send![native_view, setFrame: host_bounds];
+            let () = msg_send![host_view, addSubview: native_view];
+            self.0.lock().native_window = host_window;
+        }
+        // The new host may be on another display.
+        self.setup_display_link();
+        Ok(())
+    }
+
+    fn request_keyboard_focus(&self) {
+        let this = self.0.lock();
+        let native_view = this.native_view.as_ptr() as id;
+        this.executor
+            .spawn(async move {
+                unsafe {
+                    let native_window: id = msg_send![native_view, window];
+                    trace_embedded(|| {
+                        format!(
+                            "[{:?} makeFirstResponder: {:?}]",
+                            native_window, native_view
+                        )
+                    });
+                    let _: BOOL = msg_send![native_window, makeFirstResponder: native_view];
+                }
+            })
+            .detach();
+    }
+
+    fn release_keyboard_focus(&self) {
+        let this = self.0.lock();
+        let native_view = this.native_view.as_ptr() as id;
+        this.executor
+            .spawn(async move {
+                unsafe {
+                    let native_window: id = msg_send![native_view, window];
+                    let first_responder: id = msg_send![native_window, firstResponder];
+                    if first_responder == native_view {
+                        // Hand focus to the host's view that GPUI's view is embedded in.
+                        let superview: id = msg_send![native_view, superview];
+                        trace_embedded(|| {
+                            format!("[{:?} makeFirstResponder: {:?}]", native_window, superview)
+                        });
+                        let _: BOOL = msg_send![native_window, makeFirstResponder: superview];
+                    }
+                }
+            })
+            .detach();
+    }
+
     fn merge_all_windows(&self) {
         let native_window = self.0.lock().native_window;
         unsafe extern "C" fn merge_windows_async(context: *mut std::ffi::c_void) {
@@ -1101,6 +1316,50 @@ impl PlatformWindow for MacWindow {
         convert_mouse_position(position, self.content_size().height)
     }
 
+    fn start_mouse_capture(&self) -> bool {
+        self.0.lock().start_mouse_capture()
+    }
+
+    fn end_mouse_capture(&self) {
+        self.0.lock().end_mouse_capture();
+    }
+
+    fn set_always_on_top(&self, always_on_top: bool) -> bool {
+        let level = if always_on_top {
+            NSFloatingWindowLevel
+        } else {
+            NSNormalWindowLevel
+        };
+        unsafe { self.0.lock().native_window.setLevel_(level) };
+        true
+    }
+
+    fn warp_cursor(&self, position: Point<Pixels>) -> bool {
+        let this = self.0.lock();
+        // Window coordinates are relative to the bottom left of the window.
+        let window_rect = NSRect::new(
+            NSPoint::new(
+                position.x.0 as f64,
+                (this.content_size().height - position.y).0 as f64,
+            ),
+            NSSize::new(0., 0.),
+        );
+        let screen_rect: NSRect =
+            unsafe { msg_send![this.native_window, convertRectToScreen: window_rect] };
+        drop(this);
+
+        // Global display coordinates are relative to the top left of the main display.
+        let main_display_height = CGDisplay::main().bounds().size.height;
+        let global_position = CGPoint::new(
+            screen_rect.origin.x,
+            main_display_height - screen_rect.origin.y,
+        );
+        // Warping the cursor suppresses mouse events for a moment, unless the mouse is
+        // reassociated with the cursor.
+        CGDisplay::warp_mouse_cursor_position(global_position).is_ok()
+            && CGDisplay::associate_mouse_and_mouse_cursor_position(true).is_ok()
+    }
+
     fn modifiers(&self) -> Modifiers {
         unsafe {
             let modifiers: NSEventModifierFlags = msg_send![class!(NSEvent), modifierFlags];
@@ -1185,26 +1444,33 @@ impl PlatformWindow for MacWindow {
                 let _: () = msg_send![alert, setInformativeText: ns_string(detail)];
             }
 
-            for (ix, answer) in answers
+            // NSAlert binds Return to whichever button is added first, so the key equivalents of
+            // the default and cancel answers are assigned explicitly, and the automatic one is
+            // cleared from the first button if it's neither.
+            let default_index = PromptButton::default_index(answers);
+            let cancel_index = PromptButton::cancel_index(answers);
+            let buttons = answers
                 .iter()
                 .enumerate()
                 .filter(|&(ix, _)| Some(ix) != latest_non_cancel_label.map(|(ix, _)| ix))
-            {
+                .chain(latest_non_cancel_label);
+            for (position, (ix, answer)) in buttons.enumerate() {
                 let button: id = msg_send![alert, addButtonWithTitle: ns_string(answer.label())];
                 let _: () = msg_send![button, setTag: ix as NSInteger];
 
-                if answer.is_cancel() {
-                    // Bind Escape Key to Cancel Button
-                    if let Some(key) = std::char::from_u32(super::events::ESCAPE_KEY as u32) {
-                        let _: () =
-                            msg_send![button, setKeyEquivalent: ns_string(&key.to_string())];
-                    }
+                let key_equivalent = if Some(ix) == default_index {
+                    Some("\r".to_string())
+                } else if Some(ix) == cancel_index {
+                    std::char::from_u32(super::events::ESCAPE_KEY as u32).map(|key| key.to_string())
+                } else if position == 0 {
+                    Some(String::new())
+                } else {
+                    None
+                };
+                if let Some(key_equivalent) = key_equivalent {
+                    let _: () = msg_send![button, setKeyEquivalent: ns_string(&key_equivalent)];
                 }
             }
-            if let Some((ix, answer)) = latest_non_cancel_label {
-                let button: id = msg_send![alert, addButtonWithTitle: ns_string(answer.label())];
-                let _: () = msg_send![button, setTag: ix as NSInteger];
-            }
 
             let (done_tx, done_rx) = oneshot::channel();
             let done_tx = Cell::new(Some(done_tx));
@@ -1281,6 +1547,13 @@ impl PlatformWindow for MacWindow {
         let opaque = background_appearance == WindowBackgroundAppearance::Opaque;
         this.renderer.update_transparency(!opaque);
 
+        // The host's window isn't ours to change. The layer composites over the host's views
+        // unless it's opaque, which also makes it clear to opaque black.
+        if this.embedded {
+            this.renderer.layer().set_opaque(opaque);
+            return;
+        }
+
         unsafe {
             this.native_window.setOpaque_(opaque as BOOL);
             let background_color = if opaque {
@@ -1342,7 +1615,7 @@ impl PlatformWindow for MacWindow {
         self.0.lock().move_traffic_light();
     }
 
-    fn show_character_palette(&self) {
+    fn show_character_palette(&self) -> bool {
         let this = self.0.lock();
         let window = this.native_window;
         this.executor
@@ -1353,6 +1626,7 @@ impl PlatformWindow for MacWindow {
                 }
             })
             .detach();
+        true
     }
 
     fn minimize(&self) {
@@ -1397,6 +1671,12 @@ impl PlatformWindow for MacWindow {
         }
     }
 
+    fn is_minimized(&self) -> bool {
+        let window = self.0.lock().native_window;
+        let minimized: BOOL = unsafe { msg_send![window, isMiniaturized] };
+        minimized == YES
+    }
+
     fn on_request_frame(&self, callback: Box<dyn FnMut(RequestFrameOptions)>) {
         self.0.as_ref().lock().request_frame_callback = Some(callback);
     }
@@ -1434,6 +1714,14 @@ impl PlatformWindow for MacWindow {
         self.0.lock().appearance_changed_callback = Some(callback);
     }
 
+    fn on_host_info_changed(&self, callback: Box<dyn FnMut()>) {
+        self.0.lock().host_info_changed_callback = Some(callback);
+    }
+
+    fn host_info(&self) -> Option<HostWindowInfo> {
+        self.0.lock().host_info()
+    }
+
     fn tabbed_windows(&self) -> Option<Vec<SystemWindowTab>> {
         unsafe {
             let windows: id = msg_send![self.0.lock().native_window, tabbedWindows];
@@ -1499,6 +1787,33 @@ impl PlatformWindow for MacWindow {
         self.0.lock().renderer.sprite_atlas().clone()
     }
 
+    fn trigger_gpu_capture(&self) -> anyhow::Result<()> {
+        self.0.lock().renderer.capture_next_frame()
+    }
+
+    fn refresh_rate(&self) -> Option<f32> {
+        unsafe {
+            let screen = self.0.lock().native_window.screen();
+            if screen.is_null() {
+                return None;
+            }
+            // `maximumFramesPerSecond` is only available on macOS 12 and later, and unlike the
+            // display mode it reports 120 for ProMotion displays.
+            let supports_maximum_frames_per_second: BOOL =
+                msg_send![screen, respondsToSelector: sel!(maximumFramesPerSecond)];
+            if supports_maximum_frames_per_second == YES {
+                let frames_per_second: NSInteger = msg_send![screen, maximumFramesPerSecond];
+                if frames_per_second > 0 {
+                    return Some(frames_per_second as f32);
+                }
+            }
+            CGDisplay::new(display_id_for_screen(screen))
+                .display_mode()
+                .map(|mode| mode.refresh_rate() as f32)
+                .filter(|rate| *rate > 0.)
+        }
+    }
+
     fn gpu_specs(&self) -> Option<crate::GpuSpecs> {
         None
     }
@@ -1653,6 +1968,19 @@ extern "C" fn dealloc_view(this: &Object, _: Sel) {
 }
 
 extern "C" fn handle_key_equivalent(this: &Object, _: Sel, native_event: id) -> BOOL {
+    // Key equivalents are sent to every view of the key window until one handles them, so in
+    // embedded apps, only handle them while this view has focus, leaving the host's shortcuts to
+    // the host.
+    let app_mode = unsafe { get_window_state(this) }.lock().app_mode;
+    if app_mode == AppMode::Embedded {
+        let first_responder: id = unsafe {
+            let native_window: id = msg_send![this, window];
+            msg_send![native_window, firstResponder]
+        };
+        if first_responder != this as *const Object as id {
+            return NO;
+        }
+    }
     handle_key_event(this, native_event, true)
 }
 
@@ -1989,6 +2317,8 @@ pub(crate) fn is_macos_version_at_least(version: NSOperatingSystemVersion) -> bo
     unsafe { NSProcessInfo::processInfo(nil).isOperatingSystemAtLeastVersion(version) }
 }
 
+// Also called when the window is minimized or restored, which doesn't move it, so that GPUI
+// notices that the window state changed.
 extern "C" fn window_did_move(this: &Object, _: Sel, _: id) {
     let window_state = unsafe { get_window_state(this) };
     let mut lock = window_state.as_ref().lock();
@@ -2132,12 +2462,15 @@ extern "C" fn close_window(this: &Object, _: Sel) {
 extern "C" fn make_backing_layer(this: &Object, _: Sel) -> id {
     let window_state = unsafe { get_window_state(this) };
     let window_state = window_state.as_ref().lock();
-    window_state.renderer.layer_ptr() as id
+    let layer = window_state.renderer.layer_ptr() as id;
+    trace_embedded(|| format!("[{:?} makeBackingLayer] -> {:?}", this as *const _, layer));
+    layer
 }
 
 extern "C" fn view_did_change_backing_properties(this: &Object, _: Sel) {
     let window_state = unsafe { get_window_state(this) };
     update_window_scale_factor(&window_state);
+    refresh_host_info(&window_state);
 }
 
 extern "C" fn set_frame_size(this: &Object, _: Sel, size: NSSize) {
@@ -2168,7 +2501,11 @@ extern "C" fn set_frame_size(this: &Object, _: Sel, size: NSSize) {
         drop(lock);
         callback(content_size, scale_factor);
         window_state.lock().resize_callback = Some(callback);
-    };
+    } else {
+        drop(lock);
+    }
+    // Embedded views follow the size of the host's view.
+    refresh_host_info(&window_state);
 }
 
 extern "C" fn display_layer(this: &Object, _: Sel, _: id) {
@@ -2706,10 +3043,11 @@ impl MacWindow {
                 appkit_handle.ns_view.as_ptr() as id
             }
             _ => {
-                return Err(anyhow::anyhow!(
+                return Err(WindowOpenError::InvalidHandle(format!(
                     "Expected AppKit window handle for macOS platform, got {:?}",
                     raw_handle
                 ))
+                .into());
             }
         };
 
@@ -2718,20 +3056,30 @@ impl MacWindow {
 
             // Validate that the NSView is valid
             if native_view.is_null() {
-                return Err(anyhow::anyhow!("Invalid NSView provided: null pointer"));
+                return Err(WindowOpenError::InvalidHandle(
+                    "Invalid NSView provided: null pointer".into(),
+                )
+                .into());
             }
 
             // Check if it's actually an NSView
             let is_view: bool = msg_send![native_view, isKindOfClass: class!(NSView)];
             if !is_view {
-                return Err(anyhow::anyhow!(
+                return Err(WindowOpenError::InvalidHandle(format!(
                     "Provided handle is not an NSView: {:?}",
                     native_view
-                ));
+                ))
+                .into());
             }
 
             // Get the view's bounds to determine size
             let view_bounds: NSRect = msg_send![native_view, bounds];
+            trace_embedded(|| {
+                format!(
+                    "attaching to host view {:?}, bounds {:?}",
+                    native_view, view_bounds
+                )
+            });
             let size = size(
                 Pixels(view_bounds.size.width as f32),
                 Pixels(view_bounds.size.height as f32),
@@ -2739,9 +3087,11 @@ impl MacWindow {
 
             // Get the window that contains this view (if any)
             let native_window: id = msg_send![native_view, window];
+            trace_embedded(|| format!("host view is in window {:?}", native_window));
 
-            // Create our GPUI view that will be a subview of the provided NSView
-            let gpui_view: id = msg_send![VIEW_CLASS, alloc];
+            // Create our GPUI view that will be a subview of the provided NSView. The host may
+            // draw into its own view, so GPUI never renders into the host's view or its layer.
+            let gpui_view: id = msg_send![HOSTED_VIEW_CLASS, alloc];
             let gpui_view = NSView::initWithFrame_(gpui_view, view_bounds);
             assert!(!gpui_view.is_null());
 
@@ -2766,12 +3116,29 @@ impl MacWindow {
                 false,
             );
 
+            // Setting the layer before `wantsLayer` makes the view host GPUI's Metal layer,
+            // which follows the view's size like the view follows the host's.
+            let layer = renderer.layer_ptr() as id;
+            let () = msg_send![
+                layer,
+                setAutoresizingMask: kCALayerWidthSizable | kCALayerHeightSizable
+            ];
+            let () = msg_send![gpui_view, setLayer: layer];
+            let () = msg_send![gpui_view, setWantsLayer: YES];
+            let () = msg_send![
+                gpui_view,
+                setLayerContentsRedrawPolicy: NSViewLayerContentsRedrawDuringViewResize
+            ];
+
             let window_state = Arc::new(Mutex::new(MacWindowState {
                 handle,
                 executor,
                 native_window, // This might be nil if the view isn't in a window yet
                 native_view: NonNull::new_unchecked(gpui_view),
+                embedded: true,
                 blurred_view: None,
+                frame_driver: params.frame_driver,
+                app_mode: params.app_mode,
                 display_link: None,
                 renderer,
                 request_frame_callback: None,
@@ -2782,6 +3149,9 @@ impl MacWindow {
                 should_close_callback: None,
                 close_callback: None,
                 appearance_changed_callback: None,
+                host_info_changed_callback: None,
+                host_info: None,
+                mouse_capture: None,
                 input_handler: None,
                 last_key_equivalent: None,
                 synthetic_drag_counter: 0,
@@ -2811,6 +3181,7 @@ impl MacWindow {
             );
 
             // Now it's safe to add our view as a subview
+            trace_embedded(|| format!("[{:?} addSubview: {:?}]", native_view, gpui_view));
             let () = msg_send![native_view, addSubview: gpui_view];
 
             // Register for drag and drop if we have a window
@@ -2831,6 +3202,9 @@ impl MacWindow {
             if !native_window.is_null() {
                 let can_become: bool = msg_send![gpui_view, acceptsFirstResponder];
                 if can_become {
+                    trace_embedded(|| {
+                        format!("[{:?} makeFirstResponder: {:?}]", native_window, gpui_view)
+                    });
                     let () = msg_send![native_window, makeFirstResponder: gpui_view];
                 }
             }
@@ -2847,7 +3221,10 @@ impl MacWindow {
     /// Setup display link for frame timing in embedded mode
     fn setup_display_link(&self) {
         let mut state = self.0.lock();
-        
+        if state.frame_driver == FrameDriver::External {
+            return;
+        }
+
         // Get the display for the view
         let display_id = unsafe {
             if !state.native_window.is_null() {
@@ -2891,39 +3268,187 @@ unsafe fn create_tracking_area(view: id) -> id {
     ]
 }
 
-/// Helper methods specific to embedded windows
-impl MacWindow {
-    /// Check if this is an embedded window (attached to an external NSView)
-    pub fn is_embedded(&self) -> bool {
-        let state = self.0.lock();
-        // Embedded windows might not have a native_window, or the window
-        // won't be one we created
-        // For now, we can detect this by checking if we have window callbacks
-        state.native_window.is_null() || state.move_tab_to_new_window_callback.is_none()
+impl MacWindowState {
+    fn host_info(&self) -> Option<HostWindowInfo> {
+        if !self.embedded {
+            return None;
+        }
+        unsafe {
+            let host_view: id = msg_send![self.native_view.as_ptr(), superview];
+            if host_view.is_null() {
+                return None;
+            }
+            let bounds: NSRect = msg_send![host_view, bounds];
+            let hidden: BOOL = msg_send![host_view, isHiddenOrHasHiddenAncestor];
+            let window: id = msg_send![host_view, window];
+            let (visible, minimized, foreground) = if window.is_null() {
+                (false, false, false)
+            } else {
+                let visible: BOOL = msg_send![window, isVisible];
+                let minimized: BOOL = msg_send![window, isMiniaturized];
+                let key: BOOL = msg_send![window, isKeyWindow];
+                let main: BOOL = msg_send![window, isMainWindow];
+                let app: id = msg_send![class!(NSApplication), sharedApplication];
+                let active: BOOL = msg_send![app, isActive];
+                (
+                    visible == YES && hidden == NO,
+                    minimized == YES,
+                    active == YES && (key == YES || main == YES),
+                )
+            };
+            Some(HostWindowInfo {
+                client_size: Size::<Pixels>::from(bounds.size),
+                scale_factor: get_scale_factor(window),
+                visible,
+                minimized,
+                foreground,
+            })
+        }
     }
 
-    /// Notify the embedded window of a size change from the host
-    /// This should be called when the parent NSView resizes
-    pub fn notify_host_resize(&self, new_size: Size<Pixels>) {
-        let mut state = self.0.lock();
-        
-        // Get the current scale factor from the screen
-        let scale_factor = unsafe {
-            if !state.native_window.is_null() {
-                let screen: id = msg_send![state.native_window, screen];
-                if !screen.is_null() {
-                    let backing_scale: f64 = msg_send![screen, backingScaleFactor];
-                    backing_scale as f32
-                } else {
-                    1.0
-                }
-            } else {
-                1.0
+    /// Track the mouse over the whole content view of the host's window, so that the GPUI view
+    /// keeps receiving mouse moves outside of its bounds. AppKit already sends drags to the view
+    /// that received the mouse down, wherever they go.
+    fn start_mouse_capture(&mut self) -> bool {
+        if !self.embedded {
+            return false;
+        }
+        if self.mouse_capture.is_some() {
+            return true;
+        }
+        unsafe {
+            let view = self.native_view.as_ptr();
+            let window: id = msg_send![view, window];
+            if window.is_null() {
+                return false;
+            }
+            let content_view: id = msg_send![window, contentView];
+            if content_view.is_null() {
+                return false;
+            }
+            let bounds: NSRect = msg_send![content_view, bounds];
+            let options = NSTrackingMouseMoved
+                | NSTrackingActiveAlways
+                | NSTrackingInVisibleRect
+                | NSTrackingEnabledDuringMouseDrag;
+            let tracking_area: id = msg_send![class!(NSTrackingArea), alloc];
+            let tracking_area: id = msg_send![
+                tracking_area,
+                initWithRect: bounds
+                options: options
+                owner: view
+                userInfo: nil
+            ];
+            trace_embedded(|| format!("[{:?} addTrackingArea:{:?}]", content_view, tracking_area));
+            let () = msg_send![content_view, addTrackingArea: tracking_area];
+            self.mouse_capture = Some((content_view, tracking_area));
+        }
+        true
+    }
+
+    fn end_mouse_capture(&mut self) {
+        if let Some((content_view, tracking_area)) = self.mouse_capture.take() {
+            trace_embedded(|| {
+                format!(
+                    "[{:?} removeTrackingArea:{:?}]",
+                    content_view, tracking_area
+                )
+            });
+            unsafe {
+                let () = msg_send![content_view, removeTrackingArea: tracking_area];
+                let () = msg_send![tracking_area, release];
+            }
+        }
+    }
+}
+
+/// Report the state of the host's view and window of an embedded window to GPUI if it changed
+/// since it was last reported.
+fn refresh_host_info(window_state: &Arc<Mutex<MacWindowState>>) {
+    let mut lock = window_state.lock();
+    let host_info = lock.host_info();
+    if host_info == lock.host_info {
+        return;
+    }
+    lock.host_info = host_info;
+    if let Some(mut callback) = lock.host_info_changed_callback.take() {
+        drop(lock);
+        callback();
+        window_state.lock().host_info_changed_callback = Some(callback);
+    }
+}
+
+/// The notifications of the host's window and the application that change the state reported
+/// by [`MacWindowState::host_info`].
+const HOST_WINDOW_NOTIFICATIONS: &[&str] = &[
+    "NSWindowDidMiniaturizeNotification",
+    "NSWindowDidDeminiaturizeNotification",
+    "NSWindowDidBecomeKeyNotification",
+    "NSWindowDidResignKeyNotification",
+    "NSWindowDidBecomeMainNotification",
+    "NSWindowDidResignMainNotification",
+    "NSWindowDidChangeOcclusionStateNotification",
+];
+const APPLICATION_NOTIFICATIONS: &[&str] = &[
+    "NSApplicationDidBecomeActiveNotification",
+    "NSApplicationDidResignActiveNotification",
+];
+
+extern "C" fn hosted_view_will_move_to_window(this: &Object, _: Sel, window: id) {
+    unsafe {
+        // The tracking area belongs to the window that the view is leaving
+        get_window_state(this).lock().end_mouse_capture();
+        let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+        let () = msg_send![center, removeObserver: this as *const Object as id];
+        let () = msg_send![super(this, class!(NSView)), viewWillMoveToWindow: window];
+    }
+}
+
+extern "C" fn hosted_view_did_move_to_window(this: &Object, _: Sel) {
+    unsafe {
+        let () = msg_send![super(this, class!(NSView)), viewDidMoveToWindow];
+        let window: id = msg_send![this, window];
+        if !window.is_null() {
+            let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let app: id = msg_send![class!(NSApplication), sharedApplication];
+            let names = HOST_WINDOW_NOTIFICATIONS
+                .iter()
+                .map(|name| (name, window))
+                .chain(APPLICATION_NOTIFICATIONS.iter().map(|name| (name, app)));
+            for (name, object) in names {
+                let () = msg_send![center, addObserver: this as *const Object as id
+                    selector: sel!(hostWindowDidChange:)
+                    name: ns_string(name)
+                    object: object
+                ];
             }
-        };
-        
-        if let Some(ref mut callback) = state.resize_callback {
-            callback(new_size, scale_factor);
         }
+        refresh_host_info(&get_window_state(this));
+    }
+}
+
+extern "C" fn hosted_view_did_hide(this: &Object, _: Sel) {
+    unsafe {
+        let () = msg_send![super(this, class!(NSView)), viewDidHide];
+        refresh_host_info(&get_window_state(this));
+    }
+}
+
+extern "C" fn hosted_view_did_unhide(this: &Object, _: Sel) {
+    unsafe {
+        let () = msg_send![super(this, class!(NSView)), viewDidUnhide];
+        refresh_host_info(&get_window_state(this));
+    }
+}
+
+extern "C" fn host_window_did_change(this: &Object, _: Sel, _: id) {
+    refresh_host_info(&unsafe { get_window_state(this) });
+}
+
+/// Helper methods specific to embedded windows
+impl MacWindow {
+    /// Check if this is an embedded window (attached to an external NSView)
+    pub fn is_embedded(&self) -> bool {
+        self.0.lock().embedded
     }
 }
@@ -0,0 +1,116 @@
+use std::ops::Range;
+
+use crate::{HighlightStyle, Hsla, UnderlineStyle, px};
+
+/// A spell checker for the text users type, provided by the platform, or plugged in by the app
+/// with [`App::set_spell_checker`](crate::App::set_spell_checker), such as one backed by hunspell.
+///
+/// Languages are given as BCP 47 tags, such as `en-US`, or `None` for the user's preferred
+/// language.
+pub trait PlatformSpellChecker {
+    /// Find the misspelled words in the given text, as byte ranges into it.
+    fn check(&self, text: &str, language: Option<&str>) -> Vec<Range<usize>>;
+
+    /// Suggest corrections for a misspelled word, the most likely first.
+    fn suggestions(&self, word: &str, language: Option<&str>) -> Vec<String>;
+
+    /// Add a word to the user's dictionary, so that it's no longer reported as misspelled.
+    fn learn_word(&self, _word: &str) {}
+}
+
+/// The highlights decorating misspelled words with a wavy underline of the given color, for
+/// [`StyledText::with_highlights`](crate::StyledText::with_highlights). The ranges must be sorted,
+/// as returned by [`PlatformSpellChecker::check`].
+pub fn misspelling_highlights(
+    misspelled: impl IntoIterator<Item = Range<usize>>,
+    color: Hsla,
+) -> Vec<(Range<usize>, HighlightStyle)> {
+    let style = HighlightStyle {
+        underline: Some(UnderlineStyle {
+            thickness: px(1.),
+            color: Some(color),
+            wavy: true,
+        }),
+        ..Default::default()
+    };
+    misspelled.into_iter().map(|range| (range, style)).collect()
+}
+
+/// Convert a range of UTF-16 code units in the given text, as returned by the platform spell
+/// checkers, into a byte range.
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+pub(crate) fn utf16_range_to_utf8(text: &str, range_utf16: Range<usize>) -> Range<usize> {
+    let mut utf16_ix = 0;
+    let mut start = text.len();
+    let mut end = text.len();
+    for (ix, c) in text.char_indices() {
+        if utf16_ix == range_utf16.start {
+            start = ix;
+        }
+        if utf16_ix == range_utf16.end {
+            end = ix;
+            break;
+        }
+        utf16_ix += c.len_utf16();
+    }
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::TestAppContext;
+
+    struct WordList(RefCell<Vec<String>>);
+
+    impl PlatformSpellChecker for WordList {
+        fn check(&self, text: &str, _: Option<&str>) -> Vec<Range<usize>> {
+            let words = self.0.borrow();
+            text.split_whitespace()
+                .filter(|word| !words.iter().any(|known| known == *word))
+                .map(|word| {
+                    let start = word.as_ptr() as usize - text.as_ptr() as usize;
+                    start..start + word.len()
+                })
+                .collect()
+        }
+
+        fn suggestions(&self, word: &str, _: Option<&str>) -> Vec<String> {
+            let words = self.0.borrow();
+            words
+                .iter()
+                .filter(|known| known.len() == word.len())
+                .cloned()
+                .collect()
+        }
+
+        fn learn_word(&self, word: &str) {
+            self.0.borrow_mut().push(word.to_string());
+        }
+    }
+
+    #[test]
+    fn test_utf16_range_to_utf8() {
+        let text = "héllo 🌍 wörld";
+        assert_eq!(utf16_range_to_utf8(text, 0..5), 0..6);
+        assert_eq!(utf16_range_to_utf8(text, 6..8), 7..11);
+        assert_eq!(utf16_range_to_utf8(text, 9..14), 12..18);
+    }
+
+    #[crate::test]
+    fn test_pluggable_spell_checker(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            assert!(cx.check_spelling("helo wrld", None).is_empty());
+
+            let checker = Rc::new(WordList(RefCell::new(vec!["hello".into(), "world".into()])));
+            cx.set_spell_checker(Some(checker.clone()));
+            assert_eq!(cx.check_spelling("helo world wrld", None), [0..4, 11..15]);
+            assert_eq!(cx.spelling_suggestions("wrlds", None), ["hello", "world"]);
+
+            checker.learn_word("wrld");
+            assert_eq!(cx.check_spelling("helo world wrld", None), [0..4]);
+        });
+    }
+}
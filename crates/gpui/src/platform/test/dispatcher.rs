@@ -6,7 +6,9 @@ use parking_lot::Mutex;
 use rand::prelude::*;
 use std::{
     future::Future,
+    mem,
     ops::RangeInclusive,
+    panic::Location,
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
@@ -17,6 +19,46 @@ use util::post_inc;
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 struct TestDispatcherId(usize);
 
+/// A task run by the deterministic test scheduler. See
+/// [`BackgroundExecutor::record_task_order`](crate::BackgroundExecutor::record_task_order).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScheduledTask {
+    /// Where the task was spawned, if known.
+    pub location: Option<&'static Location<'static>>,
+    /// Whether the task ran on the simulated main thread.
+    pub main_thread: bool,
+    /// The simulated time at which the task ran, relative to the start of the test.
+    pub time: Duration,
+}
+
+/// The number of tasks waiting in the deterministic test scheduler. See
+/// [`BackgroundExecutor::pending_tasks`](crate::BackgroundExecutor::pending_tasks).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PendingTasks {
+    /// Tasks waiting to run on the main thread.
+    pub foreground: usize,
+    /// Tasks waiting to run on a background thread.
+    pub background: usize,
+    /// Background tasks that will only run once nothing else is left to run.
+    pub deprioritized: usize,
+    /// Timers that haven't fired yet.
+    pub delayed: usize,
+}
+
+/// Allows the test scheduler to park until it is dropped, then restores the previous setting.
+/// See [`BackgroundExecutor::allow_parking_scoped`](crate::BackgroundExecutor::allow_parking_scoped).
+#[must_use]
+pub struct ParkingAllowed {
+    dispatcher: TestDispatcher,
+    was_allowed: bool,
+}
+
+impl Drop for ParkingAllowed {
+    fn drop(&mut self) {
+        self.dispatcher.state.lock().allow_parking = self.was_allowed;
+    }
+}
+
 #[doc(hidden)]
 pub struct TestDispatcher {
     id: TestDispatcherId,
@@ -25,6 +67,7 @@ pub struct TestDispatcher {
 
 struct TestDispatcherState {
     random: StdRng,
+    seed: Option<u64>,
     foreground: HashMap<TestDispatcherId, VecDeque<RunnableVariant>>,
     background: Vec<RunnableVariant>,
     deprioritized_background: Vec<RunnableVariant>,
@@ -39,12 +82,14 @@ struct TestDispatcherState {
     deprioritized_task_labels: HashSet<TaskLabel>,
     block_on_ticks: RangeInclusive<usize>,
     unparkers: Vec<Unparker>,
+    task_order: Option<Vec<ScheduledTask>>,
 }
 
 impl TestDispatcher {
     pub fn new(random: StdRng) -> Self {
         let state = TestDispatcherState {
             random,
+            seed: None,
             foreground: HashMap::default(),
             background: Vec::new(),
             deprioritized_background: Vec::new(),
@@ -59,6 +104,7 @@ impl TestDispatcher {
             deprioritized_task_labels: Default::default(),
             block_on_ticks: 0..=1000,
             unparkers: Default::default(),
+            task_order: None,
         };
 
         TestDispatcher {
@@ -67,6 +113,16 @@ impl TestDispatcher {
         }
     }
 
+    pub fn with_seed(seed: u64) -> Self {
+        let dispatcher = Self::new(StdRng::seed_from_u64(seed));
+        dispatcher.state.lock().seed = Some(seed);
+        dispatcher
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.state.lock().seed
+    }
+
     pub fn advance_clock(&self, by: Duration) {
         let new_now = self.state.lock().time + by;
         loop {
@@ -94,6 +150,14 @@ impl TestDispatcher {
         false
     }
 
+    pub fn next_delayed_time(&self) -> Option<Instant> {
+        let state = self.state.lock();
+        state
+            .delayed
+            .first()
+            .map(|(time, _)| state.start_time + *time)
+    }
+
     pub fn simulate_random_delay(&self) -> impl 'static + Send + Future<Output = ()> + use<> {
         struct YieldNow {
             pub(crate) count: usize,
@@ -173,6 +237,17 @@ impl TestDispatcher {
 
         let was_main_thread = state.is_main_thread;
         state.is_main_thread = main_thread;
+        let time = state.time;
+        if let Some(task_order) = state.task_order.as_mut() {
+            task_order.push(ScheduledTask {
+                location: match &runnable {
+                    RunnableVariant::Meta(runnable) => Some(runnable.metadata().location),
+                    RunnableVariant::Compat(_) => None,
+                },
+                main_thread,
+                time,
+            });
+        }
         drop(state);
 
         // todo(localcc): add timings to tests
@@ -209,6 +284,46 @@ impl TestDispatcher {
         self.state.lock().allow_parking = false
     }
 
+    pub fn allow_parking_scoped(&self) -> ParkingAllowed {
+        let was_allowed = mem::replace(&mut self.state.lock().allow_parking, true);
+        ParkingAllowed {
+            dispatcher: self.clone(),
+            was_allowed,
+        }
+    }
+
+    pub fn pending_tasks(&self) -> PendingTasks {
+        let state = self.state.lock();
+        PendingTasks {
+            foreground: state
+                .foreground
+                .values()
+                .map(|runnables| runnables.len())
+                .sum(),
+            background: state.background.len(),
+            deprioritized: state.deprioritized_background.len(),
+            delayed: state.delayed.len(),
+        }
+    }
+
+    pub fn record_task_order(&self, record: bool) {
+        let mut state = self.state.lock();
+        if record {
+            state.task_order.get_or_insert_default();
+        } else {
+            state.task_order = None;
+        }
+    }
+
+    pub fn take_task_order(&self) -> Vec<ScheduledTask> {
+        self.state
+            .lock()
+            .task_order
+            .as_mut()
+            .map(mem::take)
+            .unwrap_or_default()
+    }
+
     pub fn set_waiting_hint(&self, msg: Option<String>) {
         self.state.lock().waiting_hint = msg
     }
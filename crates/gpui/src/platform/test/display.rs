@@ -6,16 +6,40 @@ pub(crate) struct TestDisplay {
     id: DisplayId,
     uuid: uuid::Uuid,
     bounds: Bounds<Pixels>,
+    scale_factor: f32,
 }
 
 impl TestDisplay {
     pub fn new() -> Self {
+        Self::with_bounds(
+            DisplayId(1),
+            Bounds::from_corners(Point::default(), Point::new(px(1920.), px(1080.))),
+            2.0,
+        )
+    }
+
+    pub fn with_bounds(id: DisplayId, bounds: Bounds<Pixels>, scale_factor: f32) -> Self {
         TestDisplay {
-            id: DisplayId(1),
+            id,
             uuid: uuid::Uuid::new_v4(),
-            bounds: Bounds::from_corners(Point::default(), Point::new(px(1920.), px(1080.))),
+            bounds,
+            scale_factor,
+        }
+    }
+
+    /// A copy of this display, as the platform reports it after the user changed its scaling.
+    pub fn with_scale_factor(&self, scale_factor: f32) -> Self {
+        TestDisplay {
+            id: self.id,
+            uuid: self.uuid,
+            bounds: self.bounds,
+            scale_factor,
         }
     }
+
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
 }
 
 impl PlatformDisplay for TestDisplay {
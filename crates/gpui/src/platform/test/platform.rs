@@ -1,16 +1,16 @@
 use crate::{
-    AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DevicePixels,
-    DummyKeyboardMapper, ForegroundExecutor, Keymap, NoopTextSystem, Platform, PlatformDisplay,
-    PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem, PromptButton,
-    ScreenCaptureFrame, ScreenCaptureSource, ScreenCaptureStream, SourceMetadata, Task,
-    TestDisplay, TestWindow, WindowAppearance, WindowParams, size,
+    AnyWindowHandle, BackgroundExecutor, Bounds, ClipboardItem, CursorStyle, DevicePixels,
+    DisplayId, DummyKeyboardMapper, ForegroundExecutor, Keymap, NoopTextSystem, OwnedMenu, Pixels,
+    Platform, PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem,
+    PowerState, PromptButton, ScreenCaptureFrame, ScreenCaptureSource, ScreenCaptureStream,
+    SourceMetadata, Task, TestDisplay, TestWindow, WindowAppearance, WindowParams, size,
 };
 use anyhow::Result;
 use collections::VecDeque;
 use futures::channel::oneshot;
 use parking_lot::Mutex;
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     path::{Path, PathBuf},
     rc::{Rc, Weak},
     sync::Arc,
@@ -27,14 +27,18 @@ pub(crate) struct TestPlatform {
     foreground_executor: ForegroundExecutor,
 
     pub(crate) active_window: RefCell<Option<TestWindow>>,
-    active_display: Rc<dyn PlatformDisplay>,
+    displays: RefCell<Vec<Rc<TestDisplay>>>,
     active_cursor: Mutex<CursorStyle>,
     current_clipboard_item: Mutex<Option<ClipboardItem>>,
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
     current_primary_item: Mutex<Option<ClipboardItem>>,
     pub(crate) prompts: RefCell<TestPrompts>,
     screen_capture_sources: RefCell<Vec<TestScreenCaptureSource>>,
+    power_state: Cell<PowerState>,
     pub opened_url: RefCell<Option<String>>,
+    menus: RefCell<Option<Vec<OwnedMenu>>>,
+    did_quit: Cell<bool>,
+    event_loop_running: Cell<bool>,
     pub text_system: Arc<dyn PlatformTextSystem>,
     pub expect_restart: RefCell<Option<oneshot::Sender<Option<PathBuf>>>>,
     #[cfg(target_os = "windows")]
@@ -110,8 +114,9 @@ impl TestPlatform {
             foreground_executor,
             prompts: Default::default(),
             screen_capture_sources: Default::default(),
+            power_state: Default::default(),
             active_cursor: Default::default(),
-            active_display: Rc::new(TestDisplay::new()),
+            displays: RefCell::new(vec![Rc::new(TestDisplay::new())]),
             active_window: Default::default(),
             expect_restart: Default::default(),
             current_clipboard_item: Mutex::new(None),
@@ -119,6 +124,9 @@ impl TestPlatform {
             current_primary_item: Mutex::new(None),
             weak: weak.clone(),
             opened_url: Default::default(),
+            menus: Default::default(),
+            did_quit: Cell::new(false),
+            event_loop_running: Cell::new(false),
             #[cfg(target_os = "windows")]
             bitmap_factory,
             text_system,
@@ -174,6 +182,74 @@ impl TestPlatform {
         *self.screen_capture_sources.borrow_mut() = sources;
     }
 
+    pub(crate) fn set_power_state(&self, power_state: PowerState) {
+        self.power_state.set(power_state);
+    }
+
+    pub(crate) fn set_event_loop_running(&self, running: bool) {
+        self.event_loop_running.set(running);
+    }
+
+    pub(crate) fn test_display(&self, id: DisplayId) -> Option<Rc<TestDisplay>> {
+        self.displays
+            .borrow()
+            .iter()
+            .find(|display| display.id() == id)
+            .cloned()
+    }
+
+    pub(crate) fn primary_test_display(&self) -> Rc<TestDisplay> {
+        self.displays.borrow()[0].clone()
+    }
+
+    /// The display containing most of the given bounds, if any.
+    pub(crate) fn test_display_for_bounds(
+        &self,
+        bounds: Bounds<Pixels>,
+    ) -> Option<Rc<TestDisplay>> {
+        self.displays
+            .borrow()
+            .iter()
+            .map(|display| (display, display.bounds().intersect(&bounds).size))
+            .filter(|(_, overlap)| overlap.width.0 > 0. && overlap.height.0 > 0.)
+            .max_by(|(_, a), (_, b)| (a.width.0 * a.height.0).total_cmp(&(b.width.0 * b.height.0)))
+            .map(|(display, _)| display.clone())
+    }
+
+    pub(crate) fn add_display(&self, bounds: Bounds<Pixels>, scale_factor: f32) -> DisplayId {
+        let mut displays = self.displays.borrow_mut();
+        let id = DisplayId(
+            displays
+                .iter()
+                .map(|display| display.id().0)
+                .max()
+                .unwrap_or(0)
+                + 1,
+        );
+        displays.push(Rc::new(TestDisplay::with_bounds(id, bounds, scale_factor)));
+        id
+    }
+
+    pub(crate) fn remove_display(&self, id: DisplayId) {
+        let mut displays = self.displays.borrow_mut();
+        assert!(displays.len() > 1, "cannot remove the only display");
+        displays.retain(|display| display.id() != id);
+    }
+
+    pub(crate) fn set_display_scale_factor(
+        &self,
+        id: DisplayId,
+        scale_factor: f32,
+    ) -> Rc<TestDisplay> {
+        let mut displays = self.displays.borrow_mut();
+        let display = displays
+            .iter_mut()
+            .find(|display| display.id() == id)
+            .expect("no display with the given id");
+        *display = Rc::new(display.with_scale_factor(scale_factor));
+        display.clone()
+    }
+
     pub(crate) fn prompt(
         &self,
         msg: &str,
@@ -221,6 +297,10 @@ impl TestPlatform {
     pub(crate) fn did_prompt_for_new_path(&self) -> bool {
         !self.prompts.borrow().new_path.is_empty()
     }
+
+    pub(crate) fn did_quit(&self) -> bool {
+        self.did_quit.get()
+    }
 }
 
 impl Platform for TestPlatform {
@@ -246,11 +326,29 @@ impl Platform for TestPlatform {
 
     fn on_keyboard_layout_change(&self, _: Box<dyn FnMut()>) {}
 
+    fn power_state(&self) -> PowerState {
+        self.power_state.get()
+    }
+
     fn run(&self, _on_finish_launching: Box<dyn FnOnce()>) {
         unimplemented!()
     }
 
-    fn quit(&self) {}
+    fn attach(&self, on_finish_launching: Box<dyn FnOnce()>) {
+        on_finish_launching();
+    }
+
+    fn is_event_loop_running(&self) -> bool {
+        self.event_loop_running.get()
+    }
+
+    fn pump(&self) {
+        self.background_executor.run_until_parked();
+    }
+
+    fn quit(&self) {
+        self.did_quit.set(true);
+    }
 
     fn restart(&self, path: Option<PathBuf>) {
         if let Some(tx) = self.expect_restart.take() {
@@ -275,11 +373,15 @@ impl Platform for TestPlatform {
     }
 
     fn displays(&self) -> Vec<std::rc::Rc<dyn crate::PlatformDisplay>> {
-        vec![self.active_display.clone()]
+        self.displays
+            .borrow()
+            .iter()
+            .map(|display| display.clone() as Rc<dyn PlatformDisplay>)
+            .collect()
     }
 
     fn primary_display(&self) -> Option<std::rc::Rc<dyn crate::PlatformDisplay>> {
-        Some(self.active_display.clone())
+        Some(self.primary_test_display())
     }
 
     #[cfg(feature = "screen-capture")]
@@ -314,12 +416,11 @@ impl Platform for TestPlatform {
         handle: AnyWindowHandle,
         params: WindowParams,
     ) -> anyhow::Result<Box<dyn crate::PlatformWindow>> {
-        let window = TestWindow::new(
-            handle,
-            params,
-            self.weak.clone(),
-            self.active_display.clone(),
-        );
+        let display = params
+            .display_id
+            .and_then(|id| self.test_display(id))
+            .unwrap_or_else(|| self.primary_test_display());
+        let window = TestWindow::new(handle, params, self.weak.clone(), display);
         Ok(Box::new(window))
     }
 
@@ -371,7 +472,13 @@ impl Platform for TestPlatform {
         unimplemented!()
     }
 
-    fn set_menus(&self, _menus: Vec<crate::Menu>, _keymap: &Keymap) {}
+    fn set_menus(&self, menus: Vec<crate::Menu>, _keymap: &Keymap) {
+        *self.menus.borrow_mut() = Some(menus.into_iter().map(|menu| menu.owned()).collect());
+    }
+
+    fn get_menus(&self) -> Option<Vec<OwnedMenu>> {
+        self.menus.borrow().clone()
+    }
     fn set_dock_menu(&self, _menu: Vec<crate::MenuItem>, _keymap: &Keymap) {}
 
     fn add_recent_document(&self, _paths: &Path) {}
@@ -1,7 +1,8 @@
 use crate::{
-    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DispatchEventResult, GpuSpecs,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptButton, RequestFrameOptions, Size, TestPlatform, TileId, WindowAppearance,
+    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DevicePixels,
+    DispatchEventResult, GpuSpecs, HostWindowInfo, Pixels, Platform, PlatformAtlas,
+    PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point, PromptButton,
+    RequestFrameOptions, Size, TestDisplay, TestPlatform, TileId, WindowAppearance,
     WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowParams,
 };
 use collections::HashMap;
@@ -15,7 +16,7 @@ use std::{
 pub(crate) struct TestWindowState {
     pub(crate) bounds: Bounds<Pixels>,
     pub(crate) handle: AnyWindowHandle,
-    display: Rc<dyn PlatformDisplay>,
+    display: Rc<TestDisplay>,
     pub(crate) title: Option<String>,
     pub(crate) edited: bool,
     platform: Weak<TestPlatform>,
@@ -27,8 +28,15 @@ pub(crate) struct TestWindowState {
     hover_status_change_callback: Option<Box<dyn FnMut(bool)>>,
     resize_callback: Option<Box<dyn FnMut(Size<Pixels>, f32)>>,
     moved_callback: Option<Box<dyn FnMut()>>,
+    host_info_changed_callback: Option<Box<dyn FnMut()>>,
+    host_info: Option<HostWindowInfo>,
     input_handler: Option<PlatformInputHandler>,
     is_fullscreen: bool,
+    is_maximized: bool,
+    is_minimized: bool,
+    mouse_position: Point<Pixels>,
+    pub(crate) mouse_captured: bool,
+    pub(crate) always_on_top: bool,
 }
 
 #[derive(Clone)]
@@ -55,7 +63,7 @@ impl TestWindow {
         handle: AnyWindowHandle,
         params: WindowParams,
         platform: Weak<TestPlatform>,
-        display: Rc<dyn PlatformDisplay>,
+        display: Rc<TestDisplay>,
     ) -> Self {
         Self(Rc::new(Mutex::new(TestWindowState {
             bounds: params.bounds,
@@ -72,8 +80,15 @@ impl TestWindow {
             hover_status_change_callback: None,
             resize_callback: None,
             moved_callback: None,
+            host_info_changed_callback: None,
+            host_info: None,
             input_handler: None,
             is_fullscreen: false,
+            is_maximized: false,
+            is_minimized: false,
+            mouse_position: Point::default(),
+            mouse_captured: false,
+            always_on_top: false,
         })))
     }
 
@@ -89,6 +104,57 @@ impl TestWindow {
         self.0.lock().resize_callback = Some(callback);
     }
 
+    /// Move the window to the given bounds on the given display, notifying it that its scale
+    /// factor changed if the display's differs from the one it was on.
+    pub(crate) fn simulate_display_change(&self, bounds: Bounds<Pixels>, display: Rc<TestDisplay>) {
+        let mut lock = self.0.lock();
+        let resized = lock.bounds.size != bounds.size
+            || lock.display.scale_factor() != display.scale_factor();
+        let scale_factor = display.scale_factor();
+        lock.bounds = bounds;
+        lock.display = display;
+
+        let resize_callback = if resized {
+            lock.resize_callback.take()
+        } else {
+            None
+        };
+        drop(lock);
+        if let Some(mut callback) = resize_callback {
+            callback(bounds.size, scale_factor);
+            self.0.lock().resize_callback = Some(callback);
+        }
+
+        let Some(mut callback) = self.0.lock().moved_callback.take() else {
+            return;
+        };
+        callback();
+        self.0.lock().moved_callback = Some(callback);
+    }
+
+    /// Notifies the window that it moved once the current update finishes, like platforms do
+    /// when the window manager changes the window's state.
+    fn notify_moved(&self) {
+        let Some(platform) = self.0.lock().platform.upgrade() else {
+            return;
+        };
+        let this = self.clone();
+        platform
+            .foreground_executor()
+            .spawn(async move {
+                let Some(mut callback) = this.0.lock().moved_callback.take() else {
+                    return;
+                };
+                callback();
+                this.0.lock().moved_callback = Some(callback);
+            })
+            .detach();
+    }
+
+    pub(crate) fn display_id(&self) -> crate::DisplayId {
+        self.0.lock().display.id()
+    }
+
     pub(crate) fn simulate_active_status_change(&self, active: bool) {
         let mut lock = self.0.lock();
         let Some(mut callback) = lock.active_status_change_callback.take() else {
@@ -99,6 +165,17 @@ impl TestWindow {
         self.0.lock().active_status_change_callback = Some(callback);
     }
 
+    pub(crate) fn simulate_host_info_change(&self, host_info: Option<HostWindowInfo>) {
+        let mut lock = self.0.lock();
+        lock.host_info = host_info;
+        let Some(mut callback) = lock.host_info_changed_callback.take() else {
+            return;
+        };
+        drop(lock);
+        callback();
+        self.0.lock().host_info_changed_callback = Some(callback);
+    }
+
     pub fn simulate_input(&mut self, event: PlatformInput) -> bool {
         let mut lock = self.0.lock();
         let Some(mut callback) = lock.input_callback.take() else {
@@ -121,7 +198,7 @@ impl PlatformWindow for TestWindow {
     }
 
     fn is_maximized(&self) -> bool {
-        false
+        self.0.lock().is_maximized
     }
 
     fn content_size(&self) -> Size<Pixels> {
@@ -133,8 +210,27 @@ impl PlatformWindow for TestWindow {
         lock.bounds.size = size;
     }
 
+    fn reparent_embedded(
+        &self,
+        _raw_handle: raw_window_handle::RawWindowHandle,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn park_embedded(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn host_info(&self) -> Option<HostWindowInfo> {
+        self.0.lock().host_info
+    }
+
+    fn on_host_info_changed(&self, callback: Box<dyn FnMut()>) {
+        self.0.lock().host_info_changed_callback = Some(callback);
+    }
+
     fn scale_factor(&self) -> f32 {
-        2.0
+        self.0.lock().display.scale_factor()
     }
 
     fn appearance(&self) -> WindowAppearance {
@@ -142,11 +238,30 @@ impl PlatformWindow for TestWindow {
     }
 
     fn display(&self) -> Option<std::rc::Rc<dyn crate::PlatformDisplay>> {
-        Some(self.0.lock().display.clone())
+        Some(self.0.lock().display.clone() as Rc<dyn PlatformDisplay>)
     }
 
     fn mouse_position(&self) -> Point<Pixels> {
-        Point::default()
+        self.0.lock().mouse_position
+    }
+
+    fn start_mouse_capture(&self) -> bool {
+        self.0.lock().mouse_captured = true;
+        true
+    }
+
+    fn end_mouse_capture(&self) {
+        self.0.lock().mouse_captured = false;
+    }
+
+    fn set_always_on_top(&self, always_on_top: bool) -> bool {
+        self.0.lock().always_on_top = always_on_top;
+        true
+    }
+
+    fn warp_cursor(&self, position: Point<Pixels>) -> bool {
+        self.0.lock().mouse_position = position;
+        true
     }
 
     fn modifiers(&self) -> crate::Modifiers {
@@ -183,6 +298,10 @@ impl PlatformWindow for TestWindow {
     }
 
     fn activate(&self) {
+        let was_minimized = std::mem::take(&mut self.0.lock().is_minimized);
+        if was_minimized {
+            self.notify_moved();
+        }
         self.0
             .lock()
             .platform
@@ -211,27 +330,37 @@ impl PlatformWindow for TestWindow {
         self.0.lock().edited = edited;
     }
 
-    fn show_character_palette(&self) {
-        unimplemented!()
+    fn show_character_palette(&self) -> bool {
+        false
     }
 
     fn minimize(&self) {
-        unimplemented!()
+        self.0.lock().is_minimized = true;
+        self.notify_moved();
     }
 
     fn zoom(&self) {
-        unimplemented!()
+        let mut lock = self.0.lock();
+        lock.is_maximized = !lock.is_maximized;
+        drop(lock);
+        self.notify_moved();
     }
 
     fn toggle_fullscreen(&self) {
         let mut lock = self.0.lock();
         lock.is_fullscreen = !lock.is_fullscreen;
+        drop(lock);
+        self.notify_moved();
     }
 
     fn is_fullscreen(&self) -> bool {
         self.0.lock().is_fullscreen
     }
 
+    fn is_minimized(&self) -> bool {
+        self.0.lock().is_minimized
+    }
+
     fn on_request_frame(&self, _callback: Box<dyn FnMut(RequestFrameOptions)>) {}
 
     fn on_input(&self, callback: Box<dyn FnMut(crate::PlatformInput) -> DispatchEventResult>) {
@@ -359,4 +488,13 @@ impl PlatformAtlas for TestAtlas {
         let mut state = self.0.lock();
         state.tiles.remove(key);
     }
+
+    fn update(&self, key: &AtlasKey, _region: Bounds<DevicePixels>, _bytes: &[u8]) -> bool {
+        self.0.lock().tiles.contains_key(key)
+    }
+
+    fn texture_count(&self) -> usize {
+        // Every tile is allocated its own texture.
+        self.0.lock().tiles.len()
+    }
 }
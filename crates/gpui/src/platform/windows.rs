@@ -10,6 +10,8 @@ mod embedded_window;
 mod events;
 mod keyboard;
 mod platform;
+mod renderdoc;
+mod spell_checker;
 mod system_settings;
 mod util;
 mod vsync;
@@ -28,6 +30,8 @@ pub(crate) use embedded_window::*;
 pub(crate) use events::*;
 pub(crate) use keyboard::*;
 pub(crate) use platform::*;
+pub(crate) use renderdoc::*;
+pub(crate) use spell_checker::*;
 pub(crate) use system_settings::*;
 pub(crate) use util::*;
 pub(crate) use vsync::*;
@@ -92,6 +92,23 @@ impl PlatformAtlas for DirectXAtlas {
         }
     }
 
+    fn update(&self, key: &AtlasKey, region: Bounds<DevicePixels>, bytes: &[u8]) -> bool {
+        let lock = self.0.lock();
+        let Some(tile) = lock.tiles_by_key.get(key) else {
+            return false;
+        };
+        let region = Bounds::new(tile.bounds.origin + region.origin, region.size);
+        lock.texture(tile.texture_id)
+            .upload(&lock.device_context, region, bytes);
+        true
+    }
+
+    fn texture_count(&self) -> usize {
+        let lock = self.0.lock();
+        lock.monochrome_textures.textures.iter().flatten().count()
+            + lock.polychrome_textures.textures.iter().flatten().count()
+    }
+
     fn remove(&self, key: &AtlasKey) {
         let mut lock = self.0.lock();
 
@@ -13,14 +13,17 @@ use windows::Win32::{
             D3D11_FEATURE_D3D10_X_HARDWARE_OPTIONS, D3D11_FEATURE_DATA_D3D10_X_HARDWARE_OPTIONS,
             D3D11_SDK_VERSION, D3D11CreateDevice, ID3D11Device, ID3D11DeviceContext,
         },
+        Direct3D12::ID3D12Device,
         Dxgi::{
             CreateDXGIFactory2, DXGI_CREATE_FACTORY_DEBUG, DXGI_CREATE_FACTORY_FLAGS,
-            IDXGIAdapter1, IDXGIFactory6,
+            IDXGIAdapter1, IDXGIDevice, IDXGIFactory6,
         },
     },
 };
 use windows::core::Interface;
 
+use crate::{HostGpuApi, HostGpuDevice};
+
 pub(crate) fn try_to_recover_from_device_lost<T>(mut f: impl FnMut() -> Result<T>) -> Result<T> {
     (0..5)
         .map(|i| {
@@ -50,6 +53,58 @@ impl DirectXDevices {
             get_dxgi_factory(debug_layer_available).context("Creating DXGI factory")?;
         let adapter =
             get_adapter(&dxgi_factory, debug_layer_available).context("Getting DXGI adapter")?;
+        Self::new_on_adapter(adapter, dxgi_factory, debug_layer_available)
+    }
+
+    /// Use the GPU device of an embedded window's host, see [`HostGpuDevice`].
+    pub(crate) fn from_host(host_device: &HostGpuDevice) -> Result<Self> {
+        match host_device.api {
+            HostGpuApi::Direct3D11 => {
+                let device =
+                    unsafe { ID3D11Device::from_raw_borrowed(&host_device.device.as_ptr()) }
+                        .context("The host's Direct3D 11 device is null")?
+                        .clone();
+                if unsafe { device.GetCreationFlags() } & D3D11_CREATE_DEVICE_BGRA_SUPPORT.0 as u32
+                    == 0
+                {
+                    anyhow::bail!(
+                        "The host's Direct3D 11 device wasn't created with D3D11_CREATE_DEVICE_BGRA_SUPPORT"
+                    );
+                }
+                check_device_support(&device)?;
+                let device_context = unsafe { device.GetImmediateContext() }
+                    .context("Getting the host's device context")?;
+                let dxgi_device: IDXGIDevice = device.cast()?;
+                let adapter: IDXGIAdapter1 = unsafe { dxgi_device.GetAdapter() }?.cast()?;
+                let dxgi_factory: IDXGIFactory6 = unsafe { adapter.GetParent() }?;
+                log::info!("Rendering on the host's Direct3D 11 device.");
+                Ok(Self {
+                    adapter,
+                    dxgi_factory,
+                    device,
+                    device_context,
+                })
+            }
+            HostGpuApi::Direct3D12 => {
+                let device =
+                    unsafe { ID3D12Device::from_raw_borrowed(&host_device.device.as_ptr()) }
+                        .context("The host's Direct3D 12 device is null")?;
+                let debug_layer_available = check_debug_layer_available();
+                let dxgi_factory =
+                    get_dxgi_factory(debug_layer_available).context("Creating DXGI factory")?;
+                let adapter = unsafe { dxgi_factory.EnumAdapterByLuid(device.GetAdapterLuid()) }
+                    .context("Getting the host's DXGI adapter")?;
+                log::info!("Rendering on the adapter of the host's Direct3D 12 device.");
+                Self::new_on_adapter(adapter, dxgi_factory, debug_layer_available)
+            }
+        }
+    }
+
+    fn new_on_adapter(
+        adapter: IDXGIAdapter1,
+        dxgi_factory: IDXGIFactory6,
+        debug_layer_available: bool,
+    ) -> Result<Self> {
         let (device, device_context) = {
             let mut context: Option<ID3D11DeviceContext> = None;
             let mut feature_level = D3D_FEATURE_LEVEL::default();
@@ -169,6 +224,12 @@ fn get_device(
         )?;
     }
     let device = device.unwrap();
+    check_device_support(&device)?;
+    Ok(device)
+}
+
+/// Check that the device supports the features the renderer needs.
+fn check_device_support(device: &ID3D11Device) -> Result<()> {
     let mut data = D3D11_FEATURE_DATA_D3D10_X_HARDWARE_OPTIONS::default();
     unsafe {
         device
@@ -183,7 +244,7 @@ fn get_device(
         .ComputeShaders_Plus_RawAndStructuredBuffers_Via_Shader_4_x
         .as_bool()
     {
-        Ok(device)
+        Ok(())
     } else {
         Err(anyhow::anyhow!(
             "Required feature StructuredBuffer is not supported by GPU/driver"
@@ -7,16 +7,22 @@ use ::util::ResultExt;
 use anyhow::{Context, Result};
 use windows::{
     Win32::{
-        Foundation::HWND,
+        Foundation::{COLORREF, HWND, POINT, SIZE},
         Graphics::{
             Direct3D::*,
             Direct3D11::*,
             DirectComposition::*,
             DirectWrite::*,
             Dxgi::{Common::*, *},
+            Gdi::{
+                AC_SRC_ALPHA, AC_SRC_OVER, BI_RGB, BITMAPINFO, BITMAPINFOHEADER, BLENDFUNCTION,
+                CreateCompatibleDC, CreateDIBSection, DIB_RGB_COLORS, DeleteDC, DeleteObject,
+                GetDC, ReleaseDC, SelectObject, SetDIBitsToDevice,
+            },
         },
+        UI::WindowsAndMessaging::{ULW_ALPHA, UpdateLayeredWindow},
     },
-    core::Interface,
+    core::{HSTRING, Interface},
 };
 
 use crate::{
@@ -31,6 +37,27 @@ const RENDER_TARGET_FORMAT: DXGI_FORMAT = DXGI_FORMAT_B8G8R8A8_UNORM;
 // This configuration is used for MSAA rendering on paths only, and it's guaranteed to be supported by DirectX 11.
 const PATH_MULTISAMPLE_COUNT: u32 = 4;
 
+/// Why a window presents its frames through GDI instead of a DXGI swap chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum GdiPresentationReason {
+    /// The window, or the window it's embedded in, has `WS_EX_LAYERED`.
+    LayeredHost,
+    /// The window, or the window it's embedded in, has `WS_EX_LAYOUTRTL`.
+    MirroredHost,
+    /// The window is a layered child window, composited over its host's content.
+    TransparentChild,
+}
+
+impl std::fmt::Display for GdiPresentationReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LayeredHost => write!(f, "host window is layered"),
+            Self::MirroredHost => write!(f, "host window is mirrored"),
+            Self::TransparentChild => write!(f, "window is composited over its host"),
+        }
+    }
+}
+
 pub(crate) struct FontInfo {
     pub gamma_ratios: [f32; 4],
     pub grayscale_enhanced_contrast: f32,
@@ -44,6 +71,7 @@ pub(crate) struct DirectXRenderer {
     globals: DirectXGlobalElements,
     pipelines: DirectXRenderPipelines,
     direct_composition: Option<DirectComposition>,
+    gdi_presentation: Option<GdiPresentationReason>,
     font_info: &'static FontInfo,
 
     width: u32,
@@ -54,6 +82,9 @@ pub(crate) struct DirectXRenderer {
     /// In that case we want to discard the first frame that we draw as we got reset in the middle of a frame
     /// meaning we lost all the allocated gpu textures and scene resources.
     skip_draws: bool,
+
+    /// Whether frames are cleared to transparent rather than opaque black.
+    transparent: bool,
 }
 
 /// Direct3D objects
@@ -64,13 +95,17 @@ pub(crate) struct DirectXRendererDevices {
     pub(crate) device: ID3D11Device,
     pub(crate) device_context: ID3D11DeviceContext,
     dxgi_device: Option<IDXGIDevice>,
+    annotation: Option<ID3DUserDefinedAnnotation>,
 }
 
 struct DirectXResources {
     // Direct3D rendering objects
-    swap_chain: IDXGISwapChain1,
+    // Without a swap chain, frames go to an offscreen render target and are copied to the
+    // readback texture so they can be blitted onto the window with GDI.
+    swap_chain: Option<IDXGISwapChain1>,
     render_target: Option<ID3D11Texture2D>,
     render_target_view: Option<ID3D11RenderTargetView>,
+    readback_texture: Option<ID3D11Texture2D>,
 
     // Path intermediate textures (with MSAA)
     path_intermediate_texture: ID3D11Texture2D,
@@ -120,12 +155,15 @@ impl DirectXRendererDevices {
             Some(device.cast().context("Creating DXGI device")?)
         };
 
+        let annotation = device_context.cast().log_err();
+
         Ok(Self {
             adapter: adapter.clone(),
             dxgi_factory: dxgi_factory.clone(),
             device: device.clone(),
             device_context: device_context.clone(),
             dxgi_device,
+            annotation,
         })
     }
 }
@@ -135,7 +173,14 @@ impl DirectXRenderer {
         hwnd: HWND,
         directx_devices: &DirectXDevices,
         disable_direct_composition: bool,
+        gdi_presentation: Option<GdiPresentationReason>,
     ) -> Result<Self> {
+        if let Some(reason) = gdi_presentation {
+            log::warn!("Presenting window {hwnd:?} with GDI because the {reason}");
+            trace_embedded(|| format!("presenting {hwnd:?} with GDI: {reason}"));
+        }
+        // DirectComposition needs a swap chain, which GDI presentation doesn't have.
+        let disable_direct_composition = disable_direct_composition || gdi_presentation.is_some();
         if disable_direct_composition {
             log::info!("Direct Composition is disabled.");
         }
@@ -144,8 +189,15 @@ impl DirectXRenderer {
             .context("Creating DirectX devices")?;
         let atlas = Arc::new(DirectXAtlas::new(&devices.device, &devices.device_context));
 
-        let resources = DirectXResources::new(&devices, 1, 1, hwnd, disable_direct_composition)
-            .context("Creating DirectX resources")?;
+        let resources = DirectXResources::new(
+            &devices,
+            1,
+            1,
+            hwnd,
+            disable_direct_composition,
+            gdi_presentation.is_some(),
+        )
+        .context("Creating DirectX resources")?;
         let globals = DirectXGlobalElements::new(&devices.device)
             .context("Creating DirectX global elements")?;
         let pipelines = DirectXRenderPipelines::new(&devices.device)
@@ -157,7 +209,12 @@ impl DirectXRenderer {
             let composition = DirectComposition::new(devices.dxgi_device.as_ref().unwrap(), hwnd)
                 .context("Creating DirectComposition")?;
             composition
-                .set_swap_chain(&resources.swap_chain)
+                .set_swap_chain(
+                    resources
+                        .swap_chain
+                        .as_ref()
+                        .context("swap chain missing")?,
+                )
                 .context("Setting swap chain for DirectComposition")?;
             Some(composition)
         };
@@ -170,10 +227,12 @@ impl DirectXRenderer {
             globals,
             pipelines,
             direct_composition,
+            gdi_presentation,
             font_info: Self::get_font_info(),
             width: 1,
             height: 1,
             skip_draws: false,
+            transparent: true,
         })
     }
 
@@ -204,7 +263,7 @@ impl DirectXRenderer {
                     .render_target_view
                     .as_ref()
                     .context("missing render target view")?,
-                &[0.0; 4],
+                &[0.0, 0.0, 0.0, if self.transparent { 0.0 } else { 1.0 }],
             );
             device_context
                 .OMSetRenderTargets(Some(slice::from_ref(&resources.render_target_view)), None);
@@ -215,16 +274,45 @@ impl DirectXRenderer {
 
     #[inline]
     fn present(&mut self) -> Result<()> {
-        let result = unsafe {
-            self.resources
-                .as_ref()
-                .expect("resources missing")
-                .swap_chain
-                .Present(0, DXGI_PRESENT(0))
+        let resources = self.resources.as_ref().expect("resources missing");
+        let Some(swap_chain) = &resources.swap_chain else {
+            return self.present_with_gdi();
         };
+        let result = unsafe { swap_chain.Present(0, DXGI_PRESENT(0)) };
         result.ok().context("Presenting swap chain failed")
     }
 
+    fn present_with_gdi(&self) -> Result<()> {
+        let devices = self.devices.as_ref().context("devices missing")?;
+        let resources = self.resources.as_ref().context("resources missing")?;
+        let render_target = resources
+            .render_target
+            .as_ref()
+            .context("missing render target")?;
+        let readback_texture = resources
+            .readback_texture
+            .as_ref()
+            .context("missing readback texture")?;
+        unsafe {
+            devices
+                .device_context
+                .CopyResource(readback_texture, render_target);
+            let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+            devices
+                .device_context
+                .Map(readback_texture, 0, D3D11_MAP_READ, 0, Some(&mut mapped))
+                .context("Mapping readback texture")?;
+            let result = match self.gdi_presentation {
+                Some(GdiPresentationReason::TransparentChild) => {
+                    update_layered_window(self.hwnd, &mapped, self.width, self.height)
+                }
+                _ => blit_to_window(self.hwnd, &mapped, self.width, self.height),
+            };
+            devices.device_context.Unmap(readback_texture, 0);
+            result
+        }
+    }
+
     pub(crate) fn handle_device_lost(&mut self, directx_devices: &DirectXDevices) -> Result<()> {
         try_to_recover_from_device_lost(|| {
             self.handle_device_lost_impl(directx_devices)
@@ -234,6 +322,7 @@ impl DirectXRenderer {
 
     fn handle_device_lost_impl(&mut self, directx_devices: &DirectXDevices) -> Result<()> {
         let disable_direct_composition = self.direct_composition.is_none();
+        let gdi_presentation = self.gdi_presentation.is_some();
 
         unsafe {
             #[cfg(debug_assertions)]
@@ -266,6 +355,7 @@ impl DirectXRenderer {
             self.height,
             self.hwnd,
             disable_direct_composition,
+            gdi_presentation,
         )
         .context("Creating DirectX resources")?;
         let globals = DirectXGlobalElements::new(&devices.device)
@@ -278,7 +368,12 @@ impl DirectXRenderer {
         } else {
             let composition =
                 DirectComposition::new(devices.dxgi_device.as_ref().unwrap(), self.hwnd)?;
-            composition.set_swap_chain(&resources.swap_chain)?;
+            composition.set_swap_chain(
+                resources
+                    .swap_chain
+                    .as_ref()
+                    .context("swap chain missing")?,
+            )?;
             Some(composition)
         };
 
@@ -306,14 +401,22 @@ impl DirectXRenderer {
             return Ok(());
         }
         self.pre_draw()?;
+        // Only emit markers when a graphics debugger such as RenderDoc is attached.
+        let annotation = self
+            .devices
+            .as_ref()
+            .and_then(|devices| devices.annotation.clone())
+            .filter(|annotation| unsafe { annotation.GetStatus() }.as_bool());
         for batch in scene.batches() {
-            match batch {
+            if let Some(annotation) = &annotation {
+                unsafe { annotation.BeginEvent(&HSTRING::from(batch.debug_label())) };
+            }
+            let result = match batch {
                 PrimitiveBatch::Shadows(shadows) => self.draw_shadows(shadows),
                 PrimitiveBatch::Quads(quads) => self.draw_quads(quads),
-                PrimitiveBatch::Paths(paths) => {
-                    self.draw_paths_to_intermediate(paths)?;
-                    self.draw_paths_from_intermediate(paths)
-                }
+                PrimitiveBatch::Paths(paths) => self
+                    .draw_paths_to_intermediate(paths)
+                    .and_then(|_| self.draw_paths_from_intermediate(paths)),
                 PrimitiveBatch::Underlines(underlines) => self.draw_underlines(underlines),
                 PrimitiveBatch::MonochromeSprites {
                     texture_id,
@@ -324,8 +427,11 @@ impl DirectXRenderer {
                     sprites,
                 } => self.draw_polychrome_sprites(texture_id, sprites),
                 PrimitiveBatch::Surfaces(surfaces) => self.draw_surfaces(surfaces),
+            };
+            if let Some(annotation) = &annotation {
+                unsafe { annotation.EndEvent() };
             }
-            .context(format!(
+            result.context(format!(
                 "scene too large:\
                 {} paths, {} shadows, {} quads, {} underlines, {} mono, {} poly, {} surfaces",
                 scene.paths.len(),
@@ -360,17 +466,18 @@ impl DirectXRenderer {
         // The app might have moved to a monitor that's attached to a different graphics device.
         // When a graphics device is removed or reset, the desktop resolution often changes, resulting in a window size change.
         // But here we just return the error, because we are handling device lost scenarios elsewhere.
-        unsafe {
-            resources
-                .swap_chain
-                .ResizeBuffers(
-                    BUFFER_COUNT as u32,
-                    width,
-                    height,
-                    RENDER_TARGET_FORMAT,
-                    DXGI_SWAP_CHAIN_FLAG(0),
-                )
-                .context("Failed to resize swap chain")?;
+        if let Some(swap_chain) = &resources.swap_chain {
+            unsafe {
+                swap_chain
+                    .ResizeBuffers(
+                        BUFFER_COUNT as u32,
+                        width,
+                        height,
+                        RENDER_TARGET_FORMAT,
+                        DXGI_SWAP_CHAIN_FLAG(0),
+                    )
+                    .context("Failed to resize swap chain")?;
+            }
         }
 
         resources.recreate_resources(devices, width, height)?;
@@ -650,11 +757,15 @@ impl DirectXRenderer {
         .context("Failed to get gpu driver info")
         .log_err()
         .unwrap_or("Unknown Driver".to_string());
+        let driver_info = match self.gdi_presentation {
+            Some(reason) => format!("{driver_version} (presenting with GDI: {reason})"),
+            None => driver_version,
+        };
         Ok(GpuSpecs {
             is_software_emulated,
             device_name,
             driver_name,
-            driver_info: driver_version,
+            driver_info,
         })
     }
 
@@ -674,6 +785,19 @@ impl DirectXRenderer {
     pub(crate) fn mark_drawable(&mut self) {
         self.skip_draws = false;
     }
+
+    /// Clear frames to transparent, so that what's under the window shows through where nothing
+    /// is drawn, or to opaque black.
+    pub(crate) fn update_transparency(&mut self, transparent: bool) {
+        self.transparent = transparent;
+    }
+
+    /// Whether the device the renderer draws on was lost, such as an embedded window's host's.
+    pub(crate) fn is_device_lost(&self) -> bool {
+        self.devices
+            .as_ref()
+            .is_some_and(|devices| unsafe { devices.device.GetDeviceRemovedReason() }.is_err())
+    }
 }
 
 impl DirectXResources {
@@ -683,16 +807,25 @@ impl DirectXResources {
         height: u32,
         hwnd: HWND,
         disable_direct_composition: bool,
+        gdi_presentation: bool,
     ) -> Result<Self> {
-        let swap_chain = if disable_direct_composition {
-            create_swap_chain(&devices.dxgi_factory, &devices.device, hwnd, width, height)?
+        let swap_chain = if gdi_presentation {
+            None
+        } else if disable_direct_composition {
+            Some(create_swap_chain(
+                &devices.dxgi_factory,
+                &devices.device,
+                hwnd,
+                width,
+                height,
+            )?)
         } else {
-            create_swap_chain_for_composition(
+            Some(create_swap_chain_for_composition(
                 &devices.dxgi_factory,
                 &devices.device,
                 width,
                 height,
-            )?
+            )?)
         };
 
         let (
@@ -703,13 +836,19 @@ impl DirectXResources {
             path_intermediate_msaa_texture,
             path_intermediate_msaa_view,
             viewport,
-        ) = create_resources(devices, &swap_chain, width, height)?;
+        ) = create_resources(devices, swap_chain.as_ref(), width, height)?;
+        let readback_texture = if swap_chain.is_none() {
+            Some(create_readback_texture(&devices.device, width, height)?)
+        } else {
+            None
+        };
         set_rasterizer_state(&devices.device, &devices.device_context)?;
 
         Ok(Self {
             swap_chain,
             render_target: Some(render_target),
             render_target_view,
+            readback_texture,
             path_intermediate_texture,
             path_intermediate_msaa_texture,
             path_intermediate_msaa_view,
@@ -733,7 +872,10 @@ impl DirectXResources {
             path_intermediate_msaa_texture,
             path_intermediate_msaa_view,
             viewport,
-        ) = create_resources(devices, &self.swap_chain, width, height)?;
+        ) = create_resources(devices, self.swap_chain.as_ref(), width, height)?;
+        if self.swap_chain.is_none() {
+            self.readback_texture = Some(create_readback_texture(&devices.device, width, height)?);
+        }
         self.render_target = Some(render_target);
         self.render_target_view = render_target_view;
         self.path_intermediate_texture = path_intermediate_texture;
@@ -1090,7 +1232,7 @@ fn create_swap_chain(
 #[inline]
 fn create_resources(
     devices: &DirectXRendererDevices,
-    swap_chain: &IDXGISwapChain1,
+    swap_chain: Option<&IDXGISwapChain1>,
     width: u32,
     height: u32,
 ) -> Result<(
@@ -1103,7 +1245,7 @@ fn create_resources(
     D3D11_VIEWPORT,
 )> {
     let (render_target, render_target_view) =
-        create_render_target_and_its_view(swap_chain, &devices.device)?;
+        create_render_target_and_its_view(swap_chain, &devices.device, width, height)?;
     let (path_intermediate_texture, path_intermediate_srv) =
         create_path_intermediate_texture(&devices.device, width, height)?;
     let (path_intermediate_msaa_texture, path_intermediate_msaa_view) =
@@ -1122,15 +1264,184 @@ fn create_resources(
 
 #[inline]
 fn create_render_target_and_its_view(
-    swap_chain: &IDXGISwapChain1,
+    swap_chain: Option<&IDXGISwapChain1>,
     device: &ID3D11Device,
+    width: u32,
+    height: u32,
 ) -> Result<(ID3D11Texture2D, Option<ID3D11RenderTargetView>)> {
-    let render_target: ID3D11Texture2D = unsafe { swap_chain.GetBuffer(0) }?;
+    let render_target: ID3D11Texture2D = match swap_chain {
+        Some(swap_chain) => unsafe { swap_chain.GetBuffer(0) }?,
+        None => unsafe {
+            let mut output = None;
+            let desc = D3D11_TEXTURE2D_DESC {
+                Width: width,
+                Height: height,
+                MipLevels: 1,
+                ArraySize: 1,
+                Format: RENDER_TARGET_FORMAT,
+                SampleDesc: DXGI_SAMPLE_DESC {
+                    Count: 1,
+                    Quality: 0,
+                },
+                Usage: D3D11_USAGE_DEFAULT,
+                BindFlags: D3D11_BIND_RENDER_TARGET.0 as u32,
+                CPUAccessFlags: 0,
+                MiscFlags: 0,
+            };
+            device.CreateTexture2D(&desc, None, Some(&mut output))?;
+            output.unwrap()
+        },
+    };
     let mut render_target_view = None;
     unsafe { device.CreateRenderTargetView(&render_target, None, Some(&mut render_target_view))? };
     Ok((render_target, render_target_view))
 }
 
+#[inline]
+fn create_readback_texture(
+    device: &ID3D11Device,
+    width: u32,
+    height: u32,
+) -> Result<ID3D11Texture2D> {
+    let mut output = None;
+    let desc = D3D11_TEXTURE2D_DESC {
+        Width: width,
+        Height: height,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: RENDER_TARGET_FORMAT,
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Usage: D3D11_USAGE_STAGING,
+        BindFlags: 0,
+        CPUAccessFlags: D3D11_CPU_ACCESS_READ.0 as u32,
+        MiscFlags: 0,
+    };
+    unsafe { device.CreateTexture2D(&desc, None, Some(&mut output))? };
+    Ok(output.unwrap())
+}
+
+/// Copy a mapped BGRA frame onto the window's client area.
+fn blit_to_window(
+    hwnd: HWND,
+    frame: &D3D11_MAPPED_SUBRESOURCE,
+    width: u32,
+    height: u32,
+) -> Result<()> {
+    let bitmap_info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            // Rows of the mapped texture may be padded past the frame's width.
+            biWidth: (frame.RowPitch / 4) as i32,
+            // A negative height makes the bitmap top-down, like the texture.
+            biHeight: -(height as i32),
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    unsafe {
+        let hdc = GetDC(Some(hwnd));
+        let lines = SetDIBitsToDevice(
+            hdc,
+            0,
+            0,
+            width,
+            height,
+            0,
+            0,
+            0,
+            height,
+            frame.pData,
+            &bitmap_info,
+            DIB_RGB_COLORS,
+        );
+        ReleaseDC(Some(hwnd), hdc);
+        anyhow::ensure!(lines != 0, "Blitting frame to window with GDI failed");
+    }
+    Ok(())
+}
+
+/// Present a frame of premultiplied pixels on a layered window, blending it with what's under it.
+fn update_layered_window(
+    hwnd: HWND,
+    frame: &D3D11_MAPPED_SUBRESOURCE,
+    width: u32,
+    height: u32,
+) -> Result<()> {
+    let bitmap_info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width as i32,
+            // A negative height makes the bitmap top-down, like the texture.
+            biHeight: -(height as i32),
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    unsafe {
+        let screen_dc = GetDC(None);
+        let memory_dc = CreateCompatibleDC(Some(screen_dc));
+        let mut bits = std::ptr::null_mut();
+        let result = CreateDIBSection(
+            Some(memory_dc),
+            &bitmap_info,
+            DIB_RGB_COLORS,
+            &mut bits,
+            None,
+            0,
+        )
+        .context("Creating the layered window's bitmap")
+        .and_then(|bitmap| {
+            // Rows of the mapped texture may be padded past the frame's width.
+            let row_len = width as usize * 4;
+            for row in 0..height as usize {
+                std::ptr::copy_nonoverlapping(
+                    (frame.pData as *const u8).add(row * frame.RowPitch as usize),
+                    (bits as *mut u8).add(row * row_len),
+                    row_len,
+                );
+            }
+            let previous = SelectObject(memory_dc, bitmap.into());
+            let size = SIZE {
+                cx: width as i32,
+                cy: height as i32,
+            };
+            let blend = BLENDFUNCTION {
+                BlendOp: AC_SRC_OVER as u8,
+                BlendFlags: 0,
+                SourceConstantAlpha: 255,
+                AlphaFormat: AC_SRC_ALPHA as u8,
+            };
+            let result = UpdateLayeredWindow(
+                hwnd,
+                Some(screen_dc),
+                None,
+                Some(&size),
+                Some(memory_dc),
+                Some(&POINT::default()),
+                COLORREF(0),
+                Some(&blend),
+                ULW_ALPHA,
+            )
+            .context("Updating layered window");
+            SelectObject(memory_dc, previous);
+            DeleteObject(bitmap.into()).ok().log_err();
+            result
+        });
+        DeleteDC(memory_dc).ok().log_err();
+        ReleaseDC(None, screen_dc);
+        result
+    }
+}
+
 #[inline]
 fn create_path_intermediate_texture(
     device: &ID3D11Device,
@@ -180,6 +180,27 @@ impl WindowsDisplay {
     pub fn physical_bounds(&self) -> Bounds<DevicePixels> {
         self.physical_bounds
     }
+
+    /// The refresh rate of the monitor's current display mode, in frames per second.
+    pub fn refresh_rate(&self) -> Option<f32> {
+        let info = get_monitor_info(self.handle).log_err()?;
+        let mut mode = DEVMODEW {
+            dmSize: std::mem::size_of::<DEVMODEW>() as u16,
+            ..Default::default()
+        };
+        unsafe {
+            EnumDisplaySettingsW(
+                PCWSTR(info.szDevice.as_ptr()),
+                ENUM_CURRENT_SETTINGS,
+                &mut mode,
+            )
+        }
+        .as_bool()
+        .then_some(mode.dmDisplayFrequency)
+        // 0 and 1 mean the hardware's default rate, which isn't known.
+        .filter(|frequency| *frequency > 1)
+        .map(|frequency| frequency as f32)
+    }
 }
 
 impl PlatformDisplay for WindowsDisplay {
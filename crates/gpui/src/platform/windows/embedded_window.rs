@@ -3,13 +3,22 @@
 
 use super::*;
 use crate::*;
+use ::util::ResultExt;
 use anyhow::{Context as _, Result};
 use raw_window_handle as rwh;
-use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
 use std::sync::Arc;
 use windows::Win32::Foundation::*;
 use windows::Win32::Graphics::Gdi::*;
+use windows::Win32::System::Ole::RevokeDragDrop;
+use windows::Win32::System::SystemServices::USER_DEFAULT_SCREEN_DPI;
+use windows::Win32::System::Threading::GetCurrentProcessId;
+use windows::Win32::UI::Accessibility::{HWINEVENTHOOK, SetWinEventHook, UnhookWinEvent};
+use windows::Win32::UI::HiDpi::GetDpiForWindow;
+use windows::Win32::UI::Shell::{
+    DefSubclassProc, GetWindowSubclass, RemoveWindowSubclass, SetWindowSubclass,
+};
 use windows::Win32::UI::WindowsAndMessaging::*;
 
 impl WindowsWindow {
@@ -22,21 +31,36 @@ impl WindowsWindow {
     ) -> Result<Self> {
         // Extract HWND from the raw window handle
         let hwnd = match raw_handle {
-            rwh::RawWindowHandle::Win32(win32_handle) => {
-                HWND(win32_handle.hwnd.get() as isize)
-            }
+            rwh::RawWindowHandle::Win32(win32_handle) => HWND(win32_handle.hwnd.get() as _),
             _ => {
-                return Err(anyhow::anyhow!(
+                return Err(WindowOpenError::InvalidHandle(format!(
                     "Expected Win32 window handle for Windows platform, got {:?}",
                     raw_handle
                 ))
+                .into());
             }
         };
 
         // Validate that the HWND is valid
-        if hwnd.0 == 0 || unsafe { !IsWindow(hwnd).as_bool() } {
-            return Err(anyhow::anyhow!("Invalid HWND provided: {:?}", hwnd));
+        trace_embedded(|| format!("attaching to host window {:?}", hwnd));
+        if hwnd.is_invalid() || unsafe { !IsWindow(Some(hwnd)).as_bool() } {
+            return Err(WindowOpenError::InvalidHandle(format!(
+                "Invalid HWND provided: {:?}",
+                hwnd
+            ))
+            .into());
+        }
+
+        // Several windows can be embedded in one process, but only one at a time in each host
+        if attached_window(hwnd).is_some() {
+            return Err(WindowOpenError::InvalidHandle(format!(
+                "A GPUI window is already attached to HWND {:?}",
+                hwnd
+            ))
+            .into());
         }
+        // A window that was attached to this host before may still be detaching
+        unsafe { forget_detached_window(hwnd) };
 
         let WindowCreationInfo {
             executor,
@@ -63,13 +87,14 @@ impl WindowsWindow {
             GetClientRect(hwnd, &mut rect)
                 .context("Failed to get client rect for embedded window")?;
         }
+        trace_embedded(|| format!("GetClientRect({:?}) -> {:?}", hwnd, rect));
 
         // Create a synthetic CREATESTRUCTW for WindowsWindowState::new
         let cs = CREATESTRUCTW {
             lpCreateParams: std::ptr::null_mut(),
-            hInstance: HINSTANCE(0),
-            hMenu: HMENU(0),
-            hwndParent: HWND(0),
+            hInstance: HINSTANCE::default(),
+            hMenu: HMENU::default(),
+            hwndParent: HWND::default(),
             cy: rect.bottom - rect.top,
             cx: rect.right - rect.left,
             y: 0,
@@ -80,6 +105,7 @@ impl WindowsWindow {
             dwExStyle: 0,
         };
 
+        let gdi_presentation = gdi_presentation_reason(hwnd);
         let state = RefCell::new(WindowsWindowState::new(
             hwnd,
             &directx_devices,
@@ -89,6 +115,7 @@ impl WindowsWindow {
             params.window_min_size,
             appearance,
             disable_direct_composition,
+            gdi_presentation,
             invalidate_devices.clone(),
         )?);
 
@@ -99,6 +126,10 @@ impl WindowsWindow {
             handle,
             hide_title_bar: false, // Embedded windows don't control the title bar
             is_movable: false,     // Host controls movement
+            is_child: false,
+            is_attached: true,
+            detached: Cell::new(false),
+            mouse_captured: Cell::new(false),
             executor,
             windows_version,
             validation_number,
@@ -107,47 +138,641 @@ impl WindowsWindow {
             system_settings: RefCell::new(WindowsSystemSettings::new(display)),
         });
 
-        // Set up the window state pointer so events can find our state
-        // This allows the window procedure to access our WindowsWindowInner
-        unsafe {
-            set_window_long(hwnd, GWLP_USERDATA, Rc::as_ptr(&inner) as isize);
-        }
+        // The host owns its window procedure and user data, so layer GPUI's message handling on
+        // top of them with a subclass, which also tells when the host moves to a display with a
+        // different DPI, or is destroyed
+        watch_host_window(&inner)?;
+        watch_host_info(&inner);
 
         // Register for drag and drop
-        unsafe {
-            if let Err(e) = RegisterDragDrop(hwnd, &DropTarget(Rc::downgrade(&inner))) {
-                log::error!("Failed to register drag and drop for embedded window: {}", e);
-            }
-        }
+        trace_embedded(|| format!("RegisterDragDrop({:?})", hwnd));
+        register_drag_drop(&inner).log_err();
 
-        // Increment the reference count since we're storing it in GWLP_USERDATA
-        // This prevents the Rc from being dropped while the HWND is still alive
+        // Hold a reference on behalf of the host's window until this window detaches from it,
+        // so that it stays alive while the host can still send it messages
         Rc::increment_strong_count(Rc::as_ptr(&inner));
 
         // Note: We do NOT call ShowWindow or SetFocus here, as the host controls visibility and focus
 
         Ok(Self(inner))
     }
+
+    /// Create a WindowsWindow as a child of an existing HWND, leaving the host's window procedure
+    /// and user data untouched
+    pub(crate) fn new_embedded_child(
+        handle: AnyWindowHandle,
+        params: WindowParams,
+        creation_info: WindowCreationInfo,
+        raw_handle: rwh::RawWindowHandle,
+    ) -> Result<Self> {
+        let rwh::RawWindowHandle::Win32(win32_handle) = raw_handle else {
+            return Err(WindowOpenError::InvalidHandle(format!(
+                "Expected Win32 window handle for Windows platform, got {:?}",
+                raw_handle
+            ))
+            .into());
+        };
+        let parent = HWND(win32_handle.hwnd.get() as _);
+        trace_embedded(|| format!("creating a child window of host window {:?}", parent));
+        if unsafe { !IsWindow(Some(parent)).as_bool() } {
+            return Err(WindowOpenError::InvalidHandle(format!(
+                "Invalid HWND provided: {:?}",
+                parent
+            ))
+            .into());
+        }
+
+        Self::new_with_parent(handle, params, creation_info, Some(parent))
+    }
+}
+
+impl WindowsWindowInner {
+    /// Release the host's window that this window is attached to: revoke the drop target, remove
+    /// the subclass and drop the reference held on the host's behalf. Does nothing if this window
+    /// isn't attached, or was already detached.
+    pub(crate) fn detach_from_host(&self) {
+        if !self.is_attached || self.detached.replace(true) {
+            return;
+        }
+        let hwnd = self.hwnd;
+        trace_embedded(|| format!("detaching from host window {:?}", hwnd));
+        unwatch_host_info(self);
+        unsafe {
+            // Another window may have been attached to the host since this one was closed, in
+            // which case the host's subclass and drop target belong to that window.
+            if host_subclass_data(hwnd).is_some_and(|weak| (*weak).as_ptr() == self as *const Self)
+            {
+                trace_embedded(|| format!("RevokeDragDrop({:?})", hwnd));
+                RevokeDragDrop(hwnd).log_err();
+                unwatch_host_window(hwnd);
+            }
+            // The host's window isn't destroyed along with this one, so tell the platform that
+            // this window is gone, as `WM_DESTROY` would.
+            PostMessageW(
+                Some(self.platform_window_handle),
+                WM_GPUI_CLOSE_ONE_WINDOW,
+                WPARAM(self.validation_number),
+                LPARAM(hwnd.0 as isize),
+            )
+            .log_err();
+            // Release the reference held on the host's behalf. Callers hold another one, so this
+            // doesn't free `self`.
+            Rc::decrement_strong_count(self as *const Self);
+        }
+    }
+
+    /// The host's window: the window itself when attached, or its parent when it's a child window
+    fn host_hwnd(&self) -> Option<HWND> {
+        if self.is_attached {
+            Some(self.hwnd)
+        } else if self.is_child {
+            unsafe { GetParent(self.hwnd) }.ok()
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn host_info(&self) -> Option<HostWindowInfo> {
+        let host = self.host_hwnd()?;
+        let mut rect = RECT::default();
+        unsafe { GetClientRect(host, &mut rect) }.ok()?;
+        let scale_factor = unsafe { GetDpiForWindow(host) } as f32 / USER_DEFAULT_SCREEN_DPI as f32;
+        let root = unsafe { GetAncestor(host, GA_ROOT) };
+        Some(HostWindowInfo {
+            client_size: size(
+                DevicePixels(rect.right - rect.left),
+                DevicePixels(rect.bottom - rect.top),
+            )
+            .to_pixels(scale_factor),
+            scale_factor,
+            visible: unsafe { IsWindowVisible(host) }.as_bool(),
+            minimized: unsafe { IsIconic(root) }.as_bool(),
+            foreground: unsafe { GetForegroundWindow() } == root,
+        })
+    }
+
+    /// Report the state of the host's window to GPUI if it changed since it was last reported
+    pub(crate) fn refresh_host_info(self: &Rc<Self>) {
+        let host_info = self.host_info();
+        let Ok(mut lock) = self.state.try_borrow_mut() else {
+            // The host's window changed while GPUI was updating this window, such as when
+            // resizing it, so report the change afterwards
+            let this = self.clone();
+            self.executor
+                .spawn(async move { this.refresh_host_info() })
+                .detach();
+            return;
+        };
+        if host_info == lock.host_info {
+            return;
+        }
+        lock.host_info = host_info;
+        let Some(mut callback) = lock.callbacks.host_info_changed.take() else {
+            return;
+        };
+        drop(lock);
+        callback();
+        self.state.borrow_mut().callbacks.host_info_changed = Some(callback);
+    }
+
+    /// Close this window because its host's window is being destroyed.
+    fn handle_host_destroyed(&self) {
+        let callback = self.state.borrow_mut().callbacks.close.take();
+        if let Some(callback) = callback {
+            callback();
+        }
+        self.detach_from_host();
+    }
+}
+
+/// Check whether `hwnd` or its top-level window has a style that DXGI swap chains can't present
+/// to, in which case the window has to be presented with GDI instead.
+pub(crate) fn gdi_presentation_reason(hwnd: HWND) -> Option<GdiPresentationReason> {
+    let root = unsafe { GetAncestor(hwnd, GA_ROOT) };
+    let mut reason = None;
+    for window in [hwnd, root] {
+        if window.is_invalid() {
+            continue;
+        }
+        let ex_style = WINDOW_EX_STYLE(unsafe { get_window_long(window, GWL_EXSTYLE) } as u32);
+        trace_embedded(|| {
+            format!(
+                "GetWindowLongPtrW({:?}, GWL_EXSTYLE) -> {:?}",
+                window, ex_style
+            )
+        });
+        if ex_style.contains(WS_EX_LAYERED) {
+            return Some(GdiPresentationReason::LayeredHost);
+        }
+        if ex_style.contains(WS_EX_LAYOUTRTL) {
+            reason = Some(GdiPresentationReason::MirroredHost);
+        }
+    }
+    reason
 }
 
 /// Helper methods for embedded windows
 impl WindowsWindow {
-    /// Notify an embedded window of resize events from the host
-    /// This should be called by the host application when the window size changes
-    pub fn notify_host_resize(&self, new_size: Size<Pixels>) {
-        let mut state = self.0.state.borrow_mut();
-        state.logical_size = new_size;
-        
-        if let Some(callback) = &mut state.callbacks.resize {
-            callback(new_size, state.scale_factor);
+    /// Move a child window into another host window, keeping its window and GPU resources, and
+    /// resize it to fill the new host
+    pub(crate) fn reparent_child(&self, raw_handle: rwh::RawWindowHandle) -> Result<()> {
+        if !self.0.is_child {
+            return Err(WindowOpenError::Unsupported(
+                "Only windows embedded with EmbeddingMode::Child can move to another host window"
+                    .into(),
+            )
+            .into());
         }
+        let rwh::RawWindowHandle::Win32(win32_handle) = raw_handle else {
+            return Err(WindowOpenError::InvalidHandle(format!(
+                "Expected Win32 window handle for Windows platform, got {:?}",
+                raw_handle
+            ))
+            .into());
+        };
+        let parent = HWND(win32_handle.hwnd.get() as _);
+        if unsafe { !IsWindow(Some(parent)).as_bool() } {
+            return Err(WindowOpenError::InvalidHandle(format!(
+                "Invalid HWND provided: {:?}",
+                parent
+            ))
+            .into());
+        }
+
+        let hwnd = self.0.hwnd;
+        trace_embedded(|| format!("SetParent({:?}, {:?})", hwnd, parent));
+        unsafe { SetParent(hwnd, Some(parent)) }.context("Failed to move window to new host")?;
+        let mut rect = RECT::default();
+        unsafe { GetClientRect(parent, &mut rect) }
+            .context("unable to get the client rect of the parent window")?;
+        // The child window handles the resulting WM_SIZE like any other window
+        unsafe {
+            SetWindowPos(
+                hwnd,
+                None,
+                0,
+                0,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                SWP_NOZORDER | SWP_NOACTIVATE | SWP_SHOWWINDOW,
+            )
+        }
+        .context("Failed to resize window to fill new host")?;
+
+        // The new host may be on a display with a different DPI
+        let scale_factor =
+            unsafe { GetDpiForWindow(parent) } as f32 / USER_DEFAULT_SCREEN_DPI as f32;
+        if scale_factor != self.0.state.borrow().scale_factor {
+            let this = self.0.clone();
+            self.0
+                .executor
+                .spawn(async move { this.handle_scale_factor_change(hwnd, scale_factor) })
+                .detach();
+        }
+        Ok(())
     }
 
-    /// Check if this is an embedded window (attached to an external HWND)
+    /// Hide a child window and move it out of its host window, so that the host can destroy
+    /// its window without destroying this one, until it's moved into another host
+    pub(crate) fn park_child(&self) -> Result<()> {
+        if !self.0.is_child {
+            return Err(WindowOpenError::Unsupported(
+                "Only windows embedded with EmbeddingMode::Child can be parked".into(),
+            )
+            .into());
+        }
+        let hwnd = self.0.hwnd;
+        trace_embedded(|| format!("SetWindowPos({:?}, SWP_HIDEWINDOW)", hwnd));
+        // Unlike `ShowWindow`, this reports failures
+        unsafe {
+            SetWindowPos(
+                hwnd,
+                None,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE | SWP_HIDEWINDOW,
+            )
+        }
+        .context("Failed to hide window")?;
+        // A message-only parent keeps the window alive and receiving messages, off screen
+        trace_embedded(|| format!("SetParent({:?}, HWND_MESSAGE)", hwnd));
+        unsafe { SetParent(hwnd, Some(HWND_MESSAGE)) }.context("Failed to park window")?;
+        Ok(())
+    }
+
+    /// Check if this is an embedded window (attached to or a child of an external HWND)
     /// This can be used to skip certain operations that don't apply to embedded windows
     pub fn is_embedded(&self) -> bool {
-        // In a full implementation, you might want to store this as a flag
-        // For now, we can check if we're movable (embedded windows are not)
-        !self.0.is_movable
+        self.0.is_attached || self.0.is_child
+    }
+}
+
+thread_local! {
+    /// The hook that reports host windows changing size, and how many child windows use it
+    static HOST_SIZE_HOOK: Cell<Option<(HWINEVENTHOOK, usize)>> = const { Cell::new(None) };
+}
+
+/// Keep the child windows embedded in host windows filling them as the hosts are resized.
+/// Many hosts don't allow subclassing their windows, so this listens for size changes of every
+/// window in the process instead. Balanced by [`unwatch_host_sizes`].
+pub(crate) fn watch_host_sizes() {
+    HOST_SIZE_HOOK.with(|hook| {
+        if let Some((handle, count)) = hook.get() {
+            hook.set(Some((handle, count + 1)));
+            return;
+        }
+        let handle = unsafe {
+            SetWinEventHook(
+                EVENT_OBJECT_LOCATIONCHANGE,
+                EVENT_OBJECT_LOCATIONCHANGE,
+                None,
+                Some(host_location_changed),
+                GetCurrentProcessId(),
+                0,
+                WINEVENT_OUTOFCONTEXT,
+            )
+        };
+        trace_embedded(|| {
+            format!(
+                "SetWinEventHook(EVENT_OBJECT_LOCATIONCHANGE) -> {:?}",
+                handle
+            )
+        });
+        if handle.is_invalid() {
+            log::error!("Failed to watch host windows, child windows won't follow their size");
+            return;
+        }
+        hook.set(Some((handle, 1)));
+    });
+}
+
+/// Stop keeping a child window filling its host, see [`watch_host_sizes`]
+pub(crate) fn unwatch_host_sizes() {
+    HOST_SIZE_HOOK.with(|hook| match hook.get() {
+        Some((handle, 1)) => {
+            trace_embedded(|| format!("UnhookWinEvent({:?})", handle));
+            unsafe { UnhookWinEvent(handle) }
+                .ok()
+                .context("Failed to unhook host size changes")
+                .log_err();
+            hook.set(None);
+        }
+        Some((handle, count)) => hook.set(Some((handle, count - 1))),
+        None => {}
+    });
+}
+
+unsafe extern "system" fn host_location_changed(
+    _hook: HWINEVENTHOOK,
+    _event: u32,
+    hwnd: HWND,
+    id_object: i32,
+    id_child: i32,
+    _event_thread: u32,
+    _event_time: u32,
+) {
+    if id_object != OBJID_WINDOW.0 || id_child != CHILDID_SELF as i32 {
+        return;
+    }
+    let mut child = None;
+    loop {
+        child = unsafe { FindWindowExW(Some(hwnd), child, WINDOW_CLASS_NAME, PCWSTR::null()) }.ok();
+        let Some(child) = child else {
+            break;
+        };
+        if !window_from_hwnd(child).is_some_and(|inner| inner.is_child) {
+            continue;
+        }
+        let mut host_rect = RECT::default();
+        let mut child_rect = RECT::default();
+        unsafe {
+            if GetClientRect(hwnd, &mut host_rect).is_err()
+                || GetClientRect(child, &mut child_rect).is_err()
+                || host_rect == child_rect
+            {
+                continue;
+            }
+        }
+        trace_embedded(|| format!("host window {:?} resized to {:?}", hwnd, host_rect));
+        // The child window handles the resulting WM_SIZE like any other window
+        unsafe {
+            SetWindowPos(
+                child,
+                None,
+                0,
+                0,
+                host_rect.right - host_rect.left,
+                host_rect.bottom - host_rect.top,
+                SWP_NOZORDER | SWP_NOACTIVATE | SWP_NOMOVE,
+            )
+            .log_err();
+        }
+    }
+}
+
+thread_local! {
+    /// The embedded windows that report the state of their host's window, see [`watch_host_info`]
+    static HOST_INFO_WATCHERS: RefCell<Vec<Weak<WindowsWindowInner>>> =
+        const { RefCell::new(Vec::new()) };
+    /// The hooks that report windows being shown, hidden, minimized or brought to the foreground
+    static HOST_STATE_HOOKS: Cell<Option<[HWINEVENTHOOK; 2]>> = const { Cell::new(None) };
+}
+
+/// Report changes to the state of an embedded window's host window, see
+/// [`WindowsWindowInner::host_info`]. The host's window and its children are sent messages when
+/// they're resized or shown, but other windows becoming the foreground window and the host's
+/// top-level window being minimized are only reported by events, which this listens for.
+/// Balanced by [`unwatch_host_info`].
+pub(crate) fn watch_host_info(inner: &Rc<WindowsWindowInner>) {
+    inner.state.borrow_mut().host_info = inner.host_info();
+    HOST_INFO_WATCHERS.with_borrow_mut(|watchers| watchers.push(Rc::downgrade(inner)));
+    HOST_STATE_HOOKS.with(|hooks| {
+        if hooks.get().is_some() {
+            return;
+        }
+        let set_hook = |min, max, process| unsafe {
+            SetWinEventHook(
+                min,
+                max,
+                None,
+                Some(host_state_changed),
+                process,
+                0,
+                WINEVENT_OUTOFCONTEXT,
+            )
+        };
+        // The foreground window may belong to any process, but the host's windows belong to
+        // this one
+        let system = set_hook(EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_MINIMIZEEND, 0);
+        let process = unsafe { GetCurrentProcessId() };
+        let object = set_hook(EVENT_OBJECT_SHOW, EVENT_OBJECT_HIDE, process);
+        trace_embedded(|| format!("SetWinEventHook(host state) -> {:?}, {:?}", system, object));
+        if system.is_invalid() || object.is_invalid() {
+            log::error!("Failed to watch host windows, their state won't be reported");
+        }
+        hooks.set(Some([system, object]));
+    });
+}
+
+/// Stop reporting changes to the state of an embedded window's host window, see
+/// [`watch_host_info`]
+pub(crate) fn unwatch_host_info(inner: &WindowsWindowInner) {
+    let empty = HOST_INFO_WATCHERS.with_borrow_mut(|watchers| {
+        watchers.retain(|watcher| {
+            watcher.strong_count() > 0 && watcher.as_ptr() != inner as *const WindowsWindowInner
+        });
+        watchers.is_empty()
+    });
+    if !empty {
+        return;
+    }
+    if let Some(hooks) = HOST_STATE_HOOKS.with(|hooks| hooks.take()) {
+        for hook in hooks {
+            if !hook.is_invalid() {
+                trace_embedded(|| format!("UnhookWinEvent({:?})", hook));
+                unsafe { UnhookWinEvent(hook) }
+                    .ok()
+                    .context("Failed to unhook host state changes")
+                    .log_err();
+            }
+        }
+    }
+}
+
+unsafe extern "system" fn host_state_changed(
+    _hook: HWINEVENTHOOK,
+    event: u32,
+    _hwnd: HWND,
+    id_object: i32,
+    id_child: i32,
+    _event_thread: u32,
+    _event_time: u32,
+) {
+    let relevant = match event {
+        EVENT_SYSTEM_FOREGROUND | EVENT_SYSTEM_MINIMIZESTART | EVENT_SYSTEM_MINIMIZEEND => true,
+        EVENT_OBJECT_SHOW | EVENT_OBJECT_HIDE => {
+            id_object == OBJID_WINDOW.0 && id_child == CHILDID_SELF as i32
+        }
+        _ => false,
+    };
+    if !relevant {
+        return;
+    }
+    // Reporting the change runs GPUI's callbacks, which may open or close embedded windows
+    let watchers = HOST_INFO_WATCHERS.with_borrow(|watchers| watchers.clone());
+    for watcher in watchers {
+        if let Some(inner) = watcher.upgrade() {
+            inner.refresh_host_info();
+        }
+    }
+}
+
+/// Subclass ID for the procedure that layers GPUI's message handling on top of an attached host
+/// window's own procedure
+const HOST_WINDOW_SUBCLASS_ID: usize = 1;
+
+thread_local! {
+    /// The host windows that have a subclass installed by [`watch_host_window`]
+    static SUBCLASSED_HOSTS: RefCell<Vec<HWND>> = const { RefCell::new(Vec::new()) };
+}
+
+fn watch_host_window(inner: &Rc<WindowsWindowInner>) -> Result<()> {
+    let weak = Box::into_raw(Box::new(Rc::downgrade(inner)));
+    let subclassed = unsafe {
+        SetWindowSubclass(
+            inner.hwnd,
+            Some(host_window_procedure),
+            HOST_WINDOW_SUBCLASS_ID,
+            weak as usize,
+        )
+    };
+    trace_embedded(|| format!("SetWindowSubclass({:?}) -> {:?}", inner.hwnd, subclassed));
+    if !subclassed.as_bool() {
+        drop(unsafe { Box::from_raw(weak) });
+        anyhow::bail!("Failed to subclass host window {:?}", inner.hwnd);
+    }
+    SUBCLASSED_HOSTS.with_borrow_mut(|hosts| hosts.push(inner.hwnd));
+    Ok(())
+}
+
+/// The reference to the attached window held by the subclass of `hwnd`, if it has one
+pub(crate) unsafe fn host_subclass_data(hwnd: HWND) -> Option<*mut Weak<WindowsWindowInner>> {
+    let mut weak = 0;
+    let subclassed = unsafe {
+        GetWindowSubclass(
+            hwnd,
+            Some(host_window_procedure),
+            HOST_WINDOW_SUBCLASS_ID,
+            Some(&mut weak),
+        )
+    };
+    subclassed
+        .as_bool()
+        .then_some(weak as *mut Weak<WindowsWindowInner>)
+}
+
+/// The window attached to `hwnd`, if it's the host of a window that's still open
+fn attached_window(hwnd: HWND) -> Option<Rc<WindowsWindowInner>> {
+    unsafe { (*host_subclass_data(hwnd)?).upgrade() }
+}
+
+/// Release a host from a window that was closed, but hasn't detached from it yet, so that
+/// another window can attach to it
+unsafe fn forget_detached_window(hwnd: HWND) {
+    unsafe {
+        if host_subclass_data(hwnd).is_some() {
+            trace_embedded(|| format!("RevokeDragDrop({:?})", hwnd));
+            RevokeDragDrop(hwnd).log_err();
+            unwatch_host_window(hwnd);
+        }
+    }
+}
+
+/// Remove the subclass from a host window, freeing the reference that it holds
+pub(crate) unsafe fn unwatch_host_window(hwnd: HWND) {
+    unsafe {
+        if let Some(weak) = host_subclass_data(hwnd) {
+            trace_embedded(|| format!("RemoveWindowSubclass({:?})", hwnd));
+            RemoveWindowSubclass(hwnd, Some(host_window_procedure), HOST_WINDOW_SUBCLASS_ID)
+                .ok()
+                .context("Failed to remove host window subclass")
+                .log_err();
+            drop(Box::from_raw(weak));
+        }
+    }
+    SUBCLASSED_HOSTS.with_borrow_mut(|hosts| hosts.retain(|host| *host != hwnd));
+}
+
+/// Remove the event hooks and host window subclasses that embedded windows left behind, see
+/// [`crate::runtime::shutdown`]. Hosts may keep their windows after unloading GPUI, and the
+/// hooks and subclasses would call into the unloaded library.
+pub(crate) fn release_host_hooks() {
+    let hooks = HOST_SIZE_HOOK
+        .take()
+        .map(|(hook, _)| hook)
+        .into_iter()
+        .chain(HOST_STATE_HOOKS.take().into_iter().flatten());
+    for hook in hooks {
+        if !hook.is_invalid() {
+            trace_embedded(|| format!("UnhookWinEvent({:?})", hook));
+            unsafe { UnhookWinEvent(hook) }
+                .ok()
+                .context("Failed to unhook host windows")
+                .log_err();
+        }
+    }
+    HOST_INFO_WATCHERS.take();
+    for hwnd in SUBCLASSED_HOSTS.take() {
+        unsafe { forget_detached_window(hwnd) };
+    }
+}
+
+/// Whether any event hook or host window subclass is installed on this thread
+#[cfg(test)]
+pub(crate) fn has_host_hooks() -> bool {
+    HOST_SIZE_HOOK.get().is_some()
+        || HOST_STATE_HOOKS.get().is_some()
+        || SUBCLASSED_HOSTS.with_borrow(|hosts| !hosts.is_empty())
+}
+
+unsafe extern "system" fn host_window_procedure(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    _subclass_id: usize,
+    weak: usize,
+) -> LRESULT {
+    match msg {
+        WM_DPICHANGED => {
+            // Let the host apply the suggested bounds first, then rescale to fill them
+            let result = unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) };
+            let weak = unsafe { &*(weak as *const Weak<WindowsWindowInner>) };
+            if let Some(inner) = weak.upgrade() {
+                let new_dpi = wparam.loword() as f32;
+                trace_embedded(|| format!("host window {:?} moved to DPI {}", hwnd, new_dpi));
+                inner.handle_scale_factor_change(hwnd, new_dpi / USER_DEFAULT_SCREEN_DPI as f32);
+                inner.refresh_host_info();
+            }
+            result
+        }
+        // Sent after the host's window is resized, shown or hidden
+        WM_SIZE | WM_WINDOWPOSCHANGED => {
+            let result = unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) };
+            let weak = unsafe { &*(weak as *const Weak<WindowsWindowInner>) };
+            if let Some(inner) = weak.upgrade() {
+                inner.refresh_host_info();
+            }
+            result
+        }
+        WM_CAPTURECHANGED => {
+            let weak = unsafe { &*(weak as *const Weak<WindowsWindowInner>) };
+            if let Some(inner) = weak.upgrade() {
+                inner.handle_capture_changed_msg(hwnd, lparam);
+            }
+            unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) }
+        }
+        // GPUI posts these to its windows, so they're meant for the attached window rather than
+        // the host
+        WM_GPUI_CURSOR_STYLE_CHANGED | WM_GPUI_FORCE_UPDATE_WINDOW | WM_GPUI_GPU_DEVICE_LOST => {
+            let weak = unsafe { &*(weak as *const Weak<WindowsWindowInner>) };
+            match weak.upgrade() {
+                Some(inner) => inner.handle_msg(hwnd, msg, wparam, lparam),
+                None => unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) },
+            }
+        }
+        WM_NCDESTROY => {
+            trace_embedded(|| format!("host window {:?} destroyed", hwnd));
+            // Detaching frees the subclass's reference, so upgrade it first
+            let inner = unsafe { &*(weak as *const Weak<WindowsWindowInner>) }.upgrade();
+            match inner {
+                Some(inner) => inner.handle_host_destroyed(),
+                None => unsafe { unwatch_host_window(hwnd) },
+            }
+            unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) }
+        }
+        _ => unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) },
     }
 }
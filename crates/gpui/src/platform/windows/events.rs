@@ -41,6 +41,10 @@ impl WindowsWindowInner {
     ) -> LRESULT {
         let handled = match msg {
             WM_ACTIVATE => self.handle_activate_msg(wparam),
+            // Child windows aren't activated, so they follow keyboard focus instead.
+            WM_SETFOCUS if self.is_child => self.handle_active_status_change(true),
+            WM_KILLFOCUS if self.is_child => self.handle_active_status_change(false),
+            WM_MOUSEACTIVATE if self.is_child => self.handle_child_mouse_activate_msg(handle),
             WM_CREATE => self.handle_create_msg(handle),
             WM_MOVE => self.handle_move_msg(handle, lparam),
             WM_SIZE => self.handle_size_msg(wparam, lparam),
@@ -50,6 +54,10 @@ impl WindowsWindowInner {
             WM_TIMER => self.handle_timer_msg(handle, wparam),
             WM_NCCALCSIZE => self.handle_calc_client_size(handle, wparam, lparam),
             WM_DPICHANGED => self.handle_dpi_changed_msg(handle, wparam, lparam),
+            // Child windows aren't sent WM_DPICHANGED, only this once their parent has moved.
+            WM_DPICHANGED_AFTERPARENT if self.is_child => {
+                self.handle_dpi_changed_after_parent_msg(handle)
+            }
             WM_DISPLAYCHANGE => self.handle_display_change_msg(handle),
             WM_NCHITTEST => self.handle_hit_test_msg(handle, msg, wparam, lparam),
             WM_PAINT => self.handle_paint_msg(handle),
@@ -91,6 +99,7 @@ impl WindowsWindowInner {
             WM_XBUTTONUP => {
                 self.handle_xbutton_msg(handle, wparam, lparam, Self::handle_mouse_up_msg)
             }
+            WM_CAPTURECHANGED => self.handle_capture_changed_msg(handle, lparam),
             WM_MOUSEWHEEL => self.handle_mouse_wheel_msg(handle, wparam, lparam),
             WM_MOUSEHWHEEL => self.handle_mouse_horizontal_wheel_msg(handle, wparam, lparam),
             WM_SYSKEYUP => self.handle_syskeyup_msg(wparam, lparam),
@@ -108,6 +117,10 @@ impl WindowsWindowInner {
             WM_GPUI_GPU_DEVICE_LOST => self.handle_device_lost(lparam),
             _ => None,
         };
+        // Child windows follow the size and DPI of their host's window
+        if self.is_child && matches!(msg, WM_SIZE | WM_DPICHANGED_AFTERPARENT) {
+            self.refresh_host_info();
+        }
         if let Some(n) = handled {
             LRESULT(n)
         } else {
@@ -192,7 +205,7 @@ impl WindowsWindowInner {
         Some(0)
     }
 
-    fn handle_size_change(
+    pub(crate) fn handle_size_change(
         &self,
         device_size: Size<DevicePixels>,
         scale_factor: f32,
@@ -431,13 +444,24 @@ impl WindowsWindowInner {
         if handled { Some(0) } else { Some(1) }
     }
 
+    pub(crate) fn handle_capture_changed_msg(&self, handle: HWND, lparam: LPARAM) -> Option<isize> {
+        // Another window, or the system, took the mouse away
+        if HWND(lparam.0 as _) != handle {
+            self.mouse_captured.set(false);
+        }
+        None
+    }
+
     fn handle_mouse_up_msg(
         &self,
         _handle: HWND,
         button: MouseButton,
         lparam: LPARAM,
     ) -> Option<isize> {
-        unsafe { ReleaseCapture().log_err() };
+        // An explicit capture outlives the button, until GPUI is asked to end it
+        if !self.mouse_captured.get() {
+            unsafe { ReleaseCapture().log_err() };
+        }
         let mut lock = self.state.borrow_mut();
         let Some(mut func) = lock.callbacks.input.take() else {
             return Some(1);
@@ -701,7 +725,10 @@ impl WindowsWindowInner {
     }
 
     fn handle_activate_msg(self: &Rc<Self>, wparam: WPARAM) -> Option<isize> {
-        let activated = wparam.loword() > 0;
+        self.handle_active_status_change(wparam.loword() > 0)
+    }
+
+    fn handle_active_status_change(self: &Rc<Self>, activated: bool) -> Option<isize> {
         let this = self.clone();
         self.executor
             .spawn(async move {
@@ -717,6 +744,13 @@ impl WindowsWindowInner {
         None
     }
 
+    fn handle_child_mouse_activate_msg(&self, handle: HWND) -> Option<isize> {
+        // Clicking a child window activates the host's top-level window, but doesn't give the
+        // child window keyboard focus.
+        unsafe { SetFocus(Some(handle)).log_err() };
+        None
+    }
+
     fn handle_create_msg(&self, handle: HWND) -> Option<isize> {
         if self.hide_title_bar {
             notify_frame_changed(handle);
@@ -797,6 +831,35 @@ impl WindowsWindowInner {
         Some(0)
     }
 
+    fn handle_dpi_changed_after_parent_msg(&self, handle: HWND) -> Option<isize> {
+        let new_dpi = unsafe { GetDpiForWindow(handle) } as f32;
+        self.handle_scale_factor_change(handle, new_dpi / USER_DEFAULT_SCREEN_DPI as f32);
+        Some(0)
+    }
+
+    /// Rescale an embedded window whose host has moved it to a display with a different scale
+    /// factor. The host's window procedure handles the move itself, so only the content is
+    /// updated here.
+    pub(crate) fn handle_scale_factor_change(&self, handle: HWND, new_scale_factor: f32) {
+        let mut rect = RECT::default();
+        unsafe { GetClientRect(handle, &mut rect) }
+            .context("unable to get client rect after scale factor has changed")
+            .log_err();
+        trace_embedded(|| format!("GetClientRect({:?}) -> {:?}", handle, rect));
+        let monitor = unsafe { MonitorFromWindow(handle, MONITOR_DEFAULTTONEAREST) };
+
+        let mut lock = self.state.borrow_mut();
+        lock.scale_factor = new_scale_factor;
+        lock.display = WindowsDisplay::new_with_handle(monitor);
+        drop(lock);
+
+        let device_size = size(
+            DevicePixels(rect.right - rect.left),
+            DevicePixels(rect.bottom - rect.top),
+        );
+        self.handle_size_change(device_size, new_scale_factor, true);
+    }
+
     /// The following conditions will trigger this event:
     /// 1. The monitor on which the window is located goes offline or changes resolution.
     /// 2. Another monitor goes offline, is plugged in, or changes resolution.
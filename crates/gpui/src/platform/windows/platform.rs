@@ -1,5 +1,5 @@
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     ffi::OsStr,
     path::{Path, PathBuf},
     rc::{Rc, Weak},
@@ -7,6 +7,7 @@ use std::{
         Arc,
         atomic::{AtomicBool, Ordering},
     },
+    time::{Duration, Instant},
 };
 
 use ::util::{ResultExt, paths::SanitizedPath};
@@ -21,7 +22,13 @@ use windows::{
         Foundation::*,
         Graphics::{Direct3D11::ID3D11Device, Gdi::*},
         Security::Credentials::*,
-        System::{Com::*, LibraryLoader::*, Ole::*, SystemInformation::*},
+        System::{
+            Com::*,
+            LibraryLoader::*,
+            Ole::*,
+            Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS},
+            SystemInformation::*,
+        },
         UI::{Input::KeyboardAndMouse::*, Shell::*, WindowsAndMessaging::*},
     },
     core::*,
@@ -53,6 +60,8 @@ struct WindowsPlatformInner {
     validation_number: usize,
     main_receiver: flume::Receiver<RunnableVariant>,
     dispatcher: Arc<WindowsDispatcher>,
+    main_thread_budget: Cell<MainThreadBudget>,
+    main_thread_metrics: Cell<MainThreadMetrics>,
 }
 
 pub(crate) struct WindowsPlatformState {
@@ -131,7 +140,7 @@ impl WindowsPlatform {
                 0,
                 Some(HWND_MESSAGE),
                 None,
-                None,
+                Some(get_module_handle().into()),
                 Some(&raw const context as *const _),
             )
         };
@@ -257,7 +266,7 @@ impl WindowsPlatform {
         let text_system = Arc::downgrade(&self.text_system);
         let invalidate_devices = self.invalidate_devices.clone();
 
-        std::thread::Builder::new()
+        let vsync_thread = std::thread::Builder::new()
             .name("VSyncProvider".to_owned())
             .spawn(move || {
                 let vsync_provider = VSyncProvider::new();
@@ -287,6 +296,7 @@ impl WindowsPlatform {
                 }
             })
             .unwrap();
+        crate::runtime::register_background_thread(vsync_thread);
     }
 }
 
@@ -339,6 +349,54 @@ impl Platform for WindowsPlatform {
             .keyboard_layout_change = Some(callback);
     }
 
+    fn spell_checker(&self) -> Option<Rc<dyn PlatformSpellChecker>> {
+        Some(Rc::new(WindowsSpellChecker::new()?))
+    }
+
+    fn system_idle_time(&self) -> Option<Duration> {
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+            return None;
+        }
+        let now = unsafe { GetTickCount() };
+        Some(Duration::from_millis(now.wrapping_sub(info.dwTime) as u64))
+    }
+
+    fn power_state(&self) -> PowerState {
+        let mut status = SYSTEM_POWER_STATUS::default();
+        if unsafe { GetSystemPowerStatus(&mut status) }
+            .log_err()
+            .is_none()
+        {
+            return PowerState::default();
+        }
+        // A battery flag of 128 means there is no system battery, and 255 means the status is
+        // unknown. Windows doesn't report thermal pressure to applications.
+        let has_battery = status.BatteryFlag & 128 == 0 && status.BatteryFlag != 255;
+        PowerState {
+            on_battery: status.ACLineStatus == 0,
+            battery_level: (has_battery && status.BatteryLifePercent <= 100)
+                .then(|| status.BatteryLifePercent as f32 / 100.),
+            low_power_mode: status.SystemStatusFlag == 1,
+            thermal_pressure: ThermalPressure::Nominal,
+        }
+    }
+
+    fn set_main_thread_budget(&self, budget: MainThreadBudget) {
+        self.inner.main_thread_budget.set(budget);
+    }
+
+    fn main_thread_budget(&self) -> MainThreadBudget {
+        self.inner.main_thread_budget.get()
+    }
+
+    fn main_thread_metrics(&self) -> MainThreadMetrics {
+        self.inner.main_thread_metrics.get()
+    }
+
     fn run(&self, on_finish_launching: Box<dyn 'static + FnOnce()>) {
         on_finish_launching();
         self.begin_vsync_thread();
@@ -357,6 +415,30 @@ impl Platform for WindowsPlatform {
             .with_callback(|callbacks| &mut callbacks.quit, |callback| callback());
     }
 
+    fn attach(&self, on_finish_launching: Box<dyn 'static + FnOnce()>) {
+        on_finish_launching();
+        self.begin_vsync_thread();
+    }
+
+    fn pump(&self) {
+        // Run the tasks dispatched to the main thread. Input and paint messages are left to the
+        // host's message loop, which dispatches them to GPUI's windows along with its own.
+        let mut msg = MSG::default();
+        unsafe {
+            while PeekMessageW(
+                &mut msg,
+                Some(self.handle),
+                0,
+                0,
+                PM_REMOVE | PM_QS_POSTMESSAGE,
+            )
+            .as_bool()
+            {
+                DispatchMessageW(&msg);
+            }
+        }
+    }
+
     fn quit(&self) {
         self.foreground_executor()
             .spawn(async { unsafe { PostQuitMessage(0) } })
@@ -447,13 +529,25 @@ impl Platform for WindowsPlatform {
         options: WindowParams,
     ) -> Result<Box<dyn PlatformWindow>> {
         let window = if let Some(raw_handle) = options.raw_window_handle {
-            // Embedded mode: attach to existing window handle
-            WindowsWindow::new_embedded(handle, options, self.generate_creation_info(), raw_handle)?
+            let mut creation_info = self.generate_creation_info();
+            if let Some(host_device) = &options.host_gpu_device {
+                creation_info.directx_devices =
+                    DirectXDevices::from_host(host_device).map_err(WindowOpenError::GpuInit)?;
+            }
+            match options.embedding_mode {
+                // Embedded mode: attach to existing window handle
+                EmbeddingMode::Attach => {
+                    WindowsWindow::new_embedded(handle, options, creation_info, raw_handle)?
+                }
+                EmbeddingMode::Child => {
+                    WindowsWindow::new_embedded_child(handle, options, creation_info, raw_handle)?
+                }
+            }
         } else {
             // Normal mode: create a new OS window
             WindowsWindow::new(handle, options, self.generate_creation_info())?
         };
-        
+
         let window_handle = window.get_raw_handle();
         self.raw_window_handles.write().push(window_handle.into());
 
@@ -737,6 +831,8 @@ impl WindowsPlatformInner {
                 .main_receiver
                 .take()
                 .context("missing main receiver")?,
+            main_thread_budget: Cell::default(),
+            main_thread_metrics: Cell::default(),
         }))
     }
 
@@ -765,7 +861,11 @@ impl WindowsPlatformInner {
             | WM_GPUI_TASK_DISPATCHED_ON_MAIN_THREAD
             | WM_GPUI_DOCK_MENU_ACTION
             | WM_GPUI_KEYBOARD_LAYOUT_CHANGED
-            | WM_GPUI_GPU_DEVICE_LOST => self.handle_gpui_events(msg, wparam, lparam),
+            | WM_GPUI_GPU_DEVICE_LOST => self.handle_gpui_events(handle, msg, wparam, lparam),
+            WM_TIMER if wparam.0 == MAIN_THREAD_YIELD_TIMER_ID => {
+                unsafe { KillTimer(Some(handle), MAIN_THREAD_YIELD_TIMER_ID).log_err() };
+                self.run_foreground_task(handle)
+            }
             _ => None,
         };
         if let Some(result) = handled {
@@ -775,7 +875,13 @@ impl WindowsPlatformInner {
         }
     }
 
-    fn handle_gpui_events(&self, message: u32, wparam: WPARAM, lparam: LPARAM) -> Option<isize> {
+    fn handle_gpui_events(
+        &self,
+        handle: HWND,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> Option<isize> {
         if wparam.0 != self.validation_number {
             log::error!("Wrong validation number while processing message: {message}");
             return None;
@@ -785,7 +891,7 @@ impl WindowsPlatformInner {
                 self.close_one_window(HWND(lparam.0 as _));
                 Some(0)
             }
-            WM_GPUI_TASK_DISPATCHED_ON_MAIN_THREAD => self.run_foreground_task(),
+            WM_GPUI_TASK_DISPATCHED_ON_MAIN_THREAD => self.run_foreground_task(handle),
             WM_GPUI_DOCK_MENU_ACTION => self.handle_dock_action_event(lparam.0 as _),
             WM_GPUI_KEYBOARD_LAYOUT_CHANGED => self.handle_keyboard_layout_change(),
             WM_GPUI_GPU_DEVICE_LOST => self.handle_device_lost(lparam),
@@ -808,11 +914,18 @@ impl WindowsPlatformInner {
         lock.is_empty()
     }
 
-    #[inline]
-    fn run_foreground_task(&self) -> Option<isize> {
-        loop {
-            for runnable in self.main_receiver.drain() {
+    fn run_foreground_task(&self, handle: HWND) -> Option<isize> {
+        let budget = self.main_thread_budget.get();
+        let start = Instant::now();
+        let mut tasks_run = 0;
+        let exhausted = loop {
+            if budget.is_exhausted(tasks_run, start.elapsed()) {
+                break true;
+            }
+            if let Ok(runnable) = self.main_receiver.try_recv() {
                 WindowsDispatcher::execute_runnable(runnable);
+                tasks_run += 1;
+                continue;
             }
 
             // Someone could enqueue a Runnable here. The flag is still true, so they will not PostMessage.
@@ -825,17 +938,55 @@ impl WindowsPlatformInner {
                     let _ = dispatcher.wake_posted.swap(true, Ordering::AcqRel);
 
                     WindowsDispatcher::execute_runnable(runnable);
+                    tasks_run += 1;
                     continue;
                 }
                 _ => {
-                    break;
+                    break false;
                 }
             }
+        };
+
+        let mut metrics = self.main_thread_metrics.get();
+        metrics.record_wakeup(tasks_run, start.elapsed(), exhausted);
+        self.main_thread_metrics.set(metrics);
+
+        // The wake flag is still set, so nobody else will post a wakeup for the remaining tasks.
+        if exhausted {
+            self.schedule_foreground_wakeup(handle, budget.yield_behavior);
         }
 
         Some(0)
     }
 
+    fn schedule_foreground_wakeup(&self, handle: HWND, yield_behavior: MainThreadYield) {
+        match yield_behavior {
+            MainThreadYield::Requeue => unsafe {
+                PostMessageW(
+                    Some(handle),
+                    WM_GPUI_TASK_DISPATCHED_ON_MAIN_THREAD,
+                    WPARAM(self.validation_number),
+                    LPARAM(0),
+                )
+                .log_err();
+            },
+            // Windows only generates WM_TIMER when there are no other messages in the queue, so
+            // the host's input and paint messages are handled before GPUI resumes.
+            MainThreadYield::Defer(delay) => unsafe {
+                let delay = delay
+                    .as_millis()
+                    .clamp(USER_TIMER_MINIMUM as u128, USER_TIMER_MAXIMUM as u128)
+                    as u32;
+                if SetTimer(Some(handle), MAIN_THREAD_YIELD_TIMER_ID, delay, None) == 0 {
+                    log::error!(
+                        "unable to create main thread yield timer: {}",
+                        std::io::Error::last_os_error()
+                    );
+                }
+            },
+        }
+    }
+
     fn handle_dock_action_event(&self, action_idx: usize) -> Option<isize> {
         let Some(action) = self
             .state
@@ -885,6 +1036,9 @@ impl Drop for WindowsPlatform {
     }
 }
 
+/// Timer used to resume running foreground tasks after yielding with [`MainThreadYield::Defer`].
+const MAIN_THREAD_YIELD_TIMER_ID: usize = 1;
+
 pub(crate) struct WindowCreationInfo {
     pub(crate) icon: HICON,
     pub(crate) executor: ForegroundExecutor,
@@ -1166,11 +1320,22 @@ fn register_platform_window_class() {
     let wc = WNDCLASSW {
         lpfnWndProc: Some(window_procedure),
         lpszClassName: PCWSTR(PLATFORM_WINDOW_CLASS_NAME.as_ptr()),
+        hInstance: get_module_handle().into(),
         ..Default::default()
     };
     unsafe { RegisterClassW(&wc) };
 }
 
+/// Unregister GPUI's window classes, see [`crate::runtime::shutdown`]. The classes are
+/// registered against GPUI's own module, so a reloaded copy would otherwise find them still
+/// pointing at the unloaded window procedures.
+pub(crate) fn unregister_window_classes() {
+    unregister_window_class();
+    unsafe { UnregisterClassW(PLATFORM_WINDOW_CLASS_NAME, Some(get_module_handle().into())) }
+        .context("unregistering platform window class")
+        .log_err();
+}
+
 unsafe extern "system" fn window_procedure(
     hwnd: HWND,
     msg: u32,
@@ -0,0 +1,54 @@
+use std::{ffi::c_void, sync::OnceLock};
+
+use anyhow::{Result, anyhow};
+use windows::{
+    Win32::System::LibraryLoader::{GetModuleHandleW, GetProcAddress},
+    core::{s, w},
+};
+
+/// `eRENDERDOC_API_Version_1_1_2`, the oldest API version that provides `TriggerCapture`.
+const RENDERDOC_API_VERSION: i32 = 10102;
+
+type GetApiFn = unsafe extern "C" fn(version: i32, api: *mut *mut c_void) -> i32;
+
+/// The start of RenderDoc's `RENDERDOC_API_1_1_2` function table, up to the functions GPUI uses.
+#[repr(C)]
+struct RenderDocApi {
+    _unused: [*const c_void; 15],
+    trigger_capture: unsafe extern "C" fn(),
+}
+
+struct RenderDoc(&'static RenderDocApi);
+
+// The function table is immutable and RenderDoc's functions are thread-safe.
+unsafe impl Send for RenderDoc {}
+unsafe impl Sync for RenderDoc {}
+
+static RENDERDOC: OnceLock<Option<RenderDoc>> = OnceLock::new();
+
+/// RenderDoc's in-application API, if the application was launched from or injected by
+/// RenderDoc.
+fn renderdoc() -> Option<&'static RenderDocApi> {
+    RENDERDOC
+        .get_or_init(|| unsafe {
+            let module = GetModuleHandleW(w!("renderdoc.dll")).ok()?;
+            let get_api: GetApiFn =
+                std::mem::transmute(GetProcAddress(module, s!("RENDERDOC_GetAPI"))?);
+            let mut api = std::ptr::null_mut();
+            if get_api(RENDERDOC_API_VERSION, &mut api) != 1 || api.is_null() {
+                return None;
+            }
+            Some(RenderDoc(&*(api as *const RenderDocApi)))
+        })
+        .as_ref()
+        .map(|renderdoc| renderdoc.0)
+}
+
+/// Ask RenderDoc to capture the next frame presented by the application.
+pub(crate) fn trigger_renderdoc_capture() -> Result<()> {
+    let api = renderdoc().ok_or_else(|| {
+        anyhow!("GPU capture requires the application to be launched from RenderDoc")
+    })?;
+    unsafe { (api.trigger_capture)() };
+    Ok(())
+}
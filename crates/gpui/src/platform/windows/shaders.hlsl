@@ -1127,7 +1127,7 @@ float4 monochrome_sprite_fragment(MonochromeSpriteFragmentInput input): SV_Targe
 
 struct PolychromeSprite {
     uint order;
-    uint pad;
+    uint peak_count;
     uint grayscale;
     float opacity;
     Bounds bounds;
@@ -1167,12 +1167,56 @@ PolychromeSpriteVertexOutput polychrome_sprite_vertex(uint vertex_id: SV_VertexI
     return output;
 }
 
+float decode_waveform_value(float high, float low) {
+    float value = round(high * 255.) * 256. + round(low * 255.);
+    return value / 65535. * 2. - 1.;
+}
+
+// Reduces the peaks of a waveform tile that fall within this pixel's column to their overall
+// extent, returning the waveform's color if the pixel lies within it. See
+// `Window::paint_waveform` for the layout of the tile.
+float4 waveform_color(float2 position, PolychromeSprite sprite) {
+    int2 origin = sprite.tile.bounds.origin;
+    uint capacity = uint(sprite.tile.bounds.size.x) - 2;
+    float4 color = t_sprite.Load(int3(origin, 0));
+    float4 header = t_sprite.Load(int3(origin + int2(1, 0), 0));
+    uint oldest = uint(round(header.r * 255.)) * 256 + uint(round(header.g * 255.));
+
+    float count = float(sprite.peak_count);
+    float left = (position.x - 0.5 - sprite.bounds.origin.x) / sprite.bounds.size.x;
+    float right = (position.x + 0.5 - sprite.bounds.origin.x) / sprite.bounds.size.x;
+    uint first = uint(clamp(floor(left * count), 0., count - 1.));
+    uint last = uint(clamp(ceil(right * count) - 1., float(first), count - 1.));
+
+    float minimum = 1.;
+    float maximum = -1.;
+    [loop]
+    for (uint peak = first; peak <= last; peak++) {
+        uint slot = (oldest + peak) % capacity;
+        float4 texel = t_sprite.Load(int3(origin + int2(2 + slot, 0), 0));
+        minimum = min(minimum, decode_waveform_value(texel.r, texel.g));
+        maximum = max(maximum, decode_waveform_value(texel.b, texel.a));
+    }
+
+    // Values run from 1 at the top of the bounds to -1 at the bottom. Every column covers at
+    // least one pixel, so that silence still shows as a line.
+    float half_pixel = 1. / sprite.bounds.size.y;
+    float value = 1. - 2. * (position.y - sprite.bounds.origin.y) / sprite.bounds.size.y;
+    if (value - half_pixel > maximum || value + half_pixel < minimum) {
+        return float4(0., 0., 0., 0.);
+    }
+    return color;
+}
+
 float4 polychrome_sprite_fragment(PolychromeSpriteFragmentInput input): SV_Target {
     PolychromeSprite sprite = poly_sprites[input.sprite_id];
     float4 sample = t_sprite.Sample(s_sprite, input.tile_position);
     float distance = quad_sdf(input.position.xy, sprite.bounds, sprite.corner_radii);
 
     float4 color = sample;
+    if (sprite.peak_count > 0) {
+        color = waveform_color(input.position.xy, sprite);
+    }
     if ((sprite.grayscale & 0xFFu) != 0u) {
         float3 grayscale = dot(color.rgb, GRAYSCALE_FACTORS);
         color = float4(grayscale, sample.a);
@@ -0,0 +1,109 @@
+use std::{cell::RefCell, ops::Range};
+
+use collections::HashMap;
+use util::ResultExt;
+use windows::{
+    Win32::{
+        Foundation::S_OK,
+        Globalization::{
+            GetUserDefaultLocaleName, ISpellChecker, ISpellCheckerFactory, SpellCheckerFactory,
+        },
+        System::{
+            Com::{CLSCTX_INPROC_SERVER, CoCreateInstance, CoTaskMemFree},
+            SystemServices::LOCALE_NAME_MAX_LENGTH,
+        },
+    },
+    core::{HSTRING, PWSTR},
+};
+
+use crate::{PlatformSpellChecker, utf16_range_to_utf8};
+
+/// The system spell checker, through the Spell Checking API.
+pub(crate) struct WindowsSpellChecker {
+    factory: ISpellCheckerFactory,
+    checkers: RefCell<HashMap<String, Option<ISpellChecker>>>,
+}
+
+impl WindowsSpellChecker {
+    pub(crate) fn new() -> Option<Self> {
+        let factory = unsafe { CoCreateInstance(&SpellCheckerFactory, None, CLSCTX_INPROC_SERVER) }
+            .log_err()?;
+        Some(Self {
+            factory,
+            checkers: RefCell::default(),
+        })
+    }
+
+    /// The spell checker for the given language, or the user's default one, if it's supported.
+    fn checker(&self, language: Option<&str>) -> Option<ISpellChecker> {
+        let language = match language {
+            Some(language) => language.to_string(),
+            None => {
+                let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+                let len = unsafe { GetUserDefaultLocaleName(&mut buffer) };
+                if len <= 1 {
+                    return None;
+                }
+                String::from_utf16_lossy(&buffer[..len as usize - 1])
+            }
+        };
+        self.checkers
+            .borrow_mut()
+            .entry(language)
+            .or_insert_with_key(|language| {
+                let language = HSTRING::from(language.as_str());
+                let supported = unsafe { self.factory.IsSupported(&language) }.log_err()?;
+                if !supported.as_bool() {
+                    return None;
+                }
+                unsafe { self.factory.CreateSpellChecker(&language) }.log_err()
+            })
+            .clone()
+    }
+}
+
+impl PlatformSpellChecker for WindowsSpellChecker {
+    fn check(&self, text: &str, language: Option<&str>) -> Vec<Range<usize>> {
+        let Some(checker) = self.checker(language) else {
+            return Vec::new();
+        };
+        let Some(errors) = unsafe { checker.Check(&HSTRING::from(text)) }.log_err() else {
+            return Vec::new();
+        };
+        let mut misspelled = Vec::new();
+        while let Ok(error) = unsafe { errors.Next() } {
+            let (Ok(start), Ok(len)) = (unsafe { error.StartIndex() }, unsafe { error.Length() })
+            else {
+                break;
+            };
+            let range_utf16 = start as usize..(start + len) as usize;
+            misspelled.push(utf16_range_to_utf8(text, range_utf16));
+        }
+        misspelled
+    }
+
+    fn suggestions(&self, word: &str, language: Option<&str>) -> Vec<String> {
+        let Some(checker) = self.checker(language) else {
+            return Vec::new();
+        };
+        let Some(guesses) = unsafe { checker.Suggest(&HSTRING::from(word)) }.log_err() else {
+            return Vec::new();
+        };
+        let mut suggestions = Vec::new();
+        let mut guess = [PWSTR::null()];
+        let mut fetched = 0;
+        while unsafe { guesses.Next(&mut guess, Some(&mut fetched)) } == S_OK && fetched == 1 {
+            if let Some(guess) = unsafe { guess[0].to_string() }.log_err() {
+                suggestions.push(guess);
+            }
+            unsafe { CoTaskMemFree(Some(guess[0].0 as _)) };
+        }
+        suggestions
+    }
+
+    fn learn_word(&self, word: &str) {
+        if let Some(checker) = self.checker(None) {
+            unsafe { checker.Add(&HSTRING::from(word)) }.log_err();
+        }
+    }
+}
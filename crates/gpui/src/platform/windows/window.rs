@@ -1,7 +1,7 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     num::NonZeroIsize,
     path::PathBuf,
     rc::{Rc, Weak},
@@ -37,6 +37,8 @@ pub struct WindowsWindowState {
     pub fullscreen_restore_bounds: Bounds<Pixels>,
     pub border_offset: WindowBorderOffset,
     pub appearance: WindowAppearance,
+    /// The state of the host's window last reported to GPUI, for embedded windows.
+    pub host_info: Option<HostWindowInfo>,
     pub scale_factor: f32,
     pub restore_from_minimized: Option<Box<dyn FnMut(RequestFrameOptions)>>,
 
@@ -70,6 +72,17 @@ pub(crate) struct WindowsWindowInner {
     pub(crate) handle: AnyWindowHandle,
     pub(crate) hide_title_bar: bool,
     pub(crate) is_movable: bool,
+    /// Whether this is a child window embedded in a host's window, see [`EmbeddingMode::Child`].
+    pub(crate) is_child: bool,
+    /// Whether this window renders into a host's window, see [`EmbeddingMode::Attach`]. The host
+    /// owns the window, so it isn't destroyed along with this one.
+    pub(crate) is_attached: bool,
+    /// Whether this window released the host's window it was attached to, see
+    /// [`WindowsWindowInner::detach_from_host`].
+    pub(crate) detached: Cell<bool>,
+    /// Whether GPUI was asked to keep receiving the mouse outside of the window, see
+    /// [`PlatformWindow::start_mouse_capture`].
+    pub(crate) mouse_captured: Cell<bool>,
     pub(crate) executor: ForegroundExecutor,
     pub(crate) windows_version: WindowsVersion,
     pub(crate) validation_number: usize,
@@ -87,6 +100,7 @@ impl WindowsWindowState {
         min_size: Option<Size<Pixels>>,
         appearance: WindowAppearance,
         disable_direct_composition: bool,
+        gdi_presentation: Option<GdiPresentationReason>,
         invalidate_devices: Arc<AtomicBool>,
     ) -> Result<Self> {
         let scale_factor = {
@@ -107,8 +121,15 @@ impl WindowsWindowState {
         };
         let border_offset = WindowBorderOffset::default();
         let restore_from_minimized = None;
-        let renderer = DirectXRenderer::new(hwnd, directx_devices, disable_direct_composition)
-            .context("Creating DirectX renderer")?;
+        let host_info = None;
+        let renderer = DirectXRenderer::new(
+            hwnd,
+            directx_devices,
+            disable_direct_composition,
+            gdi_presentation,
+        )
+        .context("Creating DirectX renderer")
+        .map_err(WindowOpenError::GpuInit)?;
         let callbacks = Callbacks::default();
         let input_handler = None;
         let pending_surrogate = None;
@@ -126,6 +147,7 @@ impl WindowsWindowState {
             fullscreen_restore_bounds,
             border_offset,
             appearance,
+            host_info,
             scale_factor,
             restore_from_minimized,
             min_size,
@@ -217,6 +239,7 @@ impl WindowsWindowInner {
             context.min_size,
             context.appearance,
             context.disable_direct_composition,
+            context.gdi_presentation,
             context.invalidate_devices.clone(),
         )?);
 
@@ -227,6 +250,10 @@ impl WindowsWindowInner {
             handle: context.handle,
             hide_title_bar: context.hide_title_bar,
             is_movable: context.is_movable,
+            is_child: context.is_child,
+            is_attached: false,
+            detached: Cell::new(false),
+            mouse_captured: Cell::new(false),
             executor: context.executor.clone(),
             windows_version: context.windows_version,
             validation_number: context.validation_number,
@@ -349,6 +376,7 @@ pub(crate) struct Callbacks {
     pub(crate) close: Option<Box<dyn FnOnce()>>,
     pub(crate) hit_test_window_control: Option<Box<dyn FnMut() -> Option<WindowControlArea>>>,
     pub(crate) appearance_changed: Option<Box<dyn FnMut()>>,
+    pub(crate) host_info_changed: Option<Box<dyn FnMut()>>,
 }
 
 struct WindowCreateContext {
@@ -357,6 +385,7 @@ struct WindowCreateContext {
     hide_title_bar: bool,
     display: WindowsDisplay,
     is_movable: bool,
+    is_child: bool,
     min_size: Option<Size<Pixels>>,
     executor: ForegroundExecutor,
     current_cursor: Option<HCURSOR>,
@@ -367,6 +396,7 @@ struct WindowCreateContext {
     platform_window_handle: HWND,
     appearance: WindowAppearance,
     disable_direct_composition: bool,
+    gdi_presentation: Option<GdiPresentationReason>,
     directx_devices: DirectXDevices,
     invalidate_devices: Arc<AtomicBool>,
 }
@@ -376,6 +406,16 @@ impl WindowsWindow {
         handle: AnyWindowHandle,
         params: WindowParams,
         creation_info: WindowCreationInfo,
+    ) -> Result<Self> {
+        Self::new_with_parent(handle, params, creation_info, None)
+    }
+
+    /// Create a window, as a child window filling `parent` if given.
+    pub(crate) fn new_with_parent(
+        handle: AnyWindowHandle,
+        params: WindowParams,
+        creation_info: WindowCreationInfo,
+        parent: Option<HWND>,
     ) -> Result<Self> {
         let WindowCreationInfo {
             icon,
@@ -391,11 +431,12 @@ impl WindowsWindow {
             invalidate_devices,
         } = creation_info;
         register_window_class(icon);
-        let hide_title_bar = params
-            .titlebar
-            .as_ref()
-            .map(|titlebar| titlebar.appears_transparent)
-            .unwrap_or(true);
+        let hide_title_bar = parent.is_none()
+            && params
+                .titlebar
+                .as_ref()
+                .map(|titlebar| titlebar.appears_transparent)
+                .unwrap_or(true);
         let window_name = HSTRING::from(
             params
                 .titlebar
@@ -405,7 +446,12 @@ impl WindowsWindow {
                 .unwrap_or(""),
         );
 
-        let (mut dwexstyle, dwstyle) = if params.kind == WindowKind::PopUp {
+        let (mut dwexstyle, dwstyle) = if parent.is_some() {
+            (
+                WINDOW_EX_STYLE(0),
+                WS_CHILD | WS_VISIBLE | WS_CLIPCHILDREN | WS_CLIPSIBLINGS,
+            )
+        } else if params.kind == WindowKind::PopUp {
             (WS_EX_TOOLWINDOW, WINDOW_STYLE(0x0))
         } else {
             let mut dwstyle = WS_SYSMENU;
@@ -420,12 +466,27 @@ impl WindowsWindow {
 
             (WS_EX_APPWINDOW, dwstyle)
         };
-        if !disable_direct_composition {
+        // Child windows can only be composited over their host's content as layered windows, which
+        // swap chains can't present to.
+        let gdi_presentation = parent.and_then(|parent| {
+            if params.background_appearance == WindowBackgroundAppearance::Opaque {
+                gdi_presentation_reason(parent)
+            } else {
+                dwexstyle |= WS_EX_LAYERED;
+                Some(GdiPresentationReason::TransparentChild)
+            }
+        });
+        // GDI presentation draws through the redirection bitmap, so it has to be kept.
+        if !disable_direct_composition && gdi_presentation.is_none() {
             dwexstyle |= WS_EX_NOREDIRECTIONBITMAP;
         }
 
         let hinstance = get_module_handle();
-        let display = if let Some(display_id) = params.display_id {
+        let display = if let Some(parent) = parent {
+            WindowsDisplay::new_with_handle(unsafe {
+                MonitorFromWindow(parent, MONITOR_DEFAULTTOPRIMARY)
+            })
+        } else if let Some(display_id) = params.display_id {
             // if we obtain a display_id, then this ID must be valid.
             WindowsDisplay::new(display_id).unwrap()
         } else {
@@ -438,6 +499,7 @@ impl WindowsWindow {
             hide_title_bar,
             display,
             is_movable: params.is_movable,
+            is_child: parent.is_some(),
             min_size: params.window_min_size,
             executor,
             current_cursor,
@@ -448,20 +510,29 @@ impl WindowsWindow {
             platform_window_handle,
             appearance,
             disable_direct_composition,
+            gdi_presentation,
             directx_devices,
             invalidate_devices,
         };
+        let (x, y, width, height) = if let Some(parent) = parent {
+            let mut rect = RECT::default();
+            unsafe { GetClientRect(parent, &mut rect) }
+                .context("unable to get the client rect of the parent window")?;
+            (0, 0, rect.right - rect.left, rect.bottom - rect.top)
+        } else {
+            (CW_USEDEFAULT, CW_USEDEFAULT, CW_USEDEFAULT, CW_USEDEFAULT)
+        };
         let creation_result = unsafe {
             CreateWindowExW(
                 dwexstyle,
                 WINDOW_CLASS_NAME,
                 &window_name,
                 dwstyle,
-                CW_USEDEFAULT,
-                CW_USEDEFAULT,
-                CW_USEDEFAULT,
-                CW_USEDEFAULT,
-                None,
+                x,
+                y,
+                width,
+                height,
+                parent,
                 None,
                 Some(hinstance.into()),
                 Some(&context as *const _ as *const _),
@@ -475,6 +546,13 @@ impl WindowsWindow {
         let this = this.unwrap();
 
         register_drag_drop(&this)?;
+        // Child windows fill their parent and are shown along with it, so there's no placement
+        // to restore.
+        if parent.is_some() {
+            watch_host_sizes();
+            watch_host_info(&this);
+            return Ok(Self(this));
+        }
         configure_dwm_dark_mode(hwnd, appearance);
         this.state.borrow_mut().border_offset.update(hwnd)?;
         let placement = retrieve_window_placement(
@@ -521,11 +599,19 @@ impl Drop for WindowsWindow {
         self.0
             .executor
             .spawn(async move {
+                if this.is_attached {
+                    this.detach_from_host();
+                    return;
+                }
                 let handle = this.hwnd;
                 unsafe {
                     RevokeDragDrop(handle).log_err();
                     DestroyWindow(handle).log_err();
                 }
+                if this.is_child {
+                    unwatch_host_sizes();
+                    unwatch_host_info(&this);
+                }
             })
             .detach();
     }
@@ -578,6 +664,77 @@ impl PlatformWindow for WindowsWindow {
             .detach();
     }
 
+    fn notify_embedded_resize(&mut self, size: Size<Pixels>) {
+        if self.0.is_child {
+            self.resize(size);
+            return;
+        }
+
+        // An attached window shares the host's window procedure, so it never receives WM_SIZE.
+        let this = self.0.clone();
+        self.0
+            .executor
+            .spawn(async move {
+                let scale_factor = this.state.borrow().scale_factor;
+                this.handle_size_change(size.to_device_pixels(scale_factor), scale_factor, true);
+            })
+            .detach();
+    }
+
+    fn request_keyboard_focus(&self) {
+        let hwnd = self.0.hwnd;
+        self.0
+            .executor
+            .spawn(async move {
+                trace_embedded(|| format!("SetFocus({:?})", hwnd));
+                unsafe { SetFocus(Some(hwnd)).log_err() };
+            })
+            .detach();
+    }
+
+    fn release_keyboard_focus(&self) {
+        // An attached window is the host's own window, so it keeps focus either way.
+        if !self.0.is_child {
+            return;
+        }
+        let hwnd = self.0.hwnd;
+        self.0
+            .executor
+            .spawn(async move {
+                unsafe {
+                    if let Ok(parent) = GetParent(hwnd) {
+                        trace_embedded(|| format!("SetFocus({:?})", parent));
+                        SetFocus(Some(parent)).log_err();
+                    }
+                }
+            })
+            .detach();
+    }
+
+    fn detach_embedded(&self) {
+        self.0.detach_from_host();
+    }
+
+    fn reparent_embedded(&self, raw_handle: rwh::RawWindowHandle) -> Result<()> {
+        self.reparent_child(raw_handle)
+    }
+
+    fn park_embedded(&self) -> Result<()> {
+        self.park_child()
+    }
+
+    fn host_info(&self) -> Option<HostWindowInfo> {
+        self.0.host_info()
+    }
+
+    fn set_embedded_scale_factor(&mut self, scale_factor: f32) {
+        let this = self.0.clone();
+        self.0
+            .executor
+            .spawn(async move { this.handle_scale_factor_change(this.hwnd, scale_factor) })
+            .detach();
+    }
+
     fn scale_factor(&self) -> f32 {
         self.0.state.borrow().scale_factor
     }
@@ -603,6 +760,56 @@ impl PlatformWindow for WindowsWindow {
         logical_point(point.x as f32, point.y as f32, scale_factor)
     }
 
+    fn warp_cursor(&self, position: Point<Pixels>) -> bool {
+        let scale_factor = self.scale_factor();
+        let mut point = POINT {
+            x: (position.x.0 * scale_factor).round() as i32,
+            y: (position.y.0 * scale_factor).round() as i32,
+        };
+        unsafe {
+            ClientToScreen(self.0.hwnd, &mut point).as_bool()
+                && SetCursorPos(point.x, point.y)
+                    .context("unable to set cursor position")
+                    .log_err()
+                    .is_some()
+        }
+    }
+
+    fn start_mouse_capture(&self) -> bool {
+        // Attached windows capture the host's window, whose procedure forwards the mouse to GPUI
+        unsafe { SetCapture(self.0.hwnd) };
+        self.0.mouse_captured.set(true);
+        true
+    }
+
+    fn end_mouse_capture(&self) {
+        if self.0.mouse_captured.replace(false) && unsafe { GetCapture() } == self.0.hwnd {
+            unsafe { ReleaseCapture().log_err() };
+        }
+    }
+
+    fn set_always_on_top(&self, always_on_top: bool) -> bool {
+        let insert_after = if always_on_top {
+            HWND_TOPMOST
+        } else {
+            HWND_NOTOPMOST
+        };
+        unsafe {
+            SetWindowPos(
+                self.0.hwnd,
+                Some(insert_after),
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            )
+        }
+        .context("unable to change the window's topmost state")
+        .log_err()
+        .is_some()
+    }
+
     fn modifiers(&self) -> Modifiers {
         current_modifiers()
     }
@@ -663,18 +870,24 @@ impl PlatformWindow for WindowsWindow {
                         hints_encoded = HSTRING::from(hints);
                         config.pszContent = PCWSTR::from_raw(hints_encoded.as_ptr());
                     };
+                    let default_index = PromptButton::default_index(&answers);
+                    let cancel_index = PromptButton::cancel_index(&answers);
                     let mut button_id_map = Vec::with_capacity(answers.len());
                     let mut buttons = Vec::new();
                     let mut btn_encoded = Vec::new();
                     for (index, btn) in answers.iter().enumerate() {
                         let encoded = HSTRING::from(btn.label().as_ref());
-                        let button_id = match btn {
-                            PromptButton::Ok(_) => IDOK.0,
-                            PromptButton::Cancel(_) => IDCANCEL.0,
-                            // the first few low integer values are reserved for known buttons
-                            // so for simplicity we just go backwards from -1
-                            PromptButton::Other(_) => -(index as i32) - 1,
+                        // Escape answers with IDCANCEL, so only the cancel answer gets that ID.
+                        // The first few low integer values are reserved for known buttons, so
+                        // the other answers count backwards from -1.
+                        let button_id = if Some(index) == cancel_index {
+                            IDCANCEL.0
+                        } else {
+                            -(index as i32) - 1
                         };
+                        if Some(index) == default_index {
+                            config.nDefaultButton = button_id;
+                        }
                         button_id_map.push(button_id);
                         buttons.push(TASKDIALOG_BUTTON {
                             nButtonID: button_id,
@@ -773,6 +986,18 @@ impl PlatformWindow for WindowsWindow {
 
     fn set_background_appearance(&self, background_appearance: WindowBackgroundAppearance) {
         let hwnd = self.0.hwnd;
+        // Backdrops only apply to top-level windows, and the host's window isn't ours to change.
+        // Attached windows are composited over the host's content, and child windows are when
+        // they were opened with a transparent background.
+        if self.0.is_attached || self.0.is_child {
+            let transparent = background_appearance != WindowBackgroundAppearance::Opaque;
+            self.0
+                .state
+                .borrow_mut()
+                .renderer
+                .update_transparency(transparent);
+            return;
+        }
 
         // using Dwm APIs for Mica and MicaAlt backdrops.
         // others follow the set_window_composition_attribute approach
@@ -797,6 +1022,32 @@ impl PlatformWindow for WindowsWindow {
         }
     }
 
+    fn show_character_palette(&self) -> bool {
+        // There's no API to open the emoji panel, so press its shortcut, Win+., which opens it for
+        // the focused control of the foreground window.
+        if !self.is_active() {
+            return false;
+        }
+        let key = |vk, flags| INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: vk,
+                    dwFlags: flags,
+                    ..Default::default()
+                },
+            },
+        };
+        let inputs = [
+            key(VK_LWIN, KEYBD_EVENT_FLAGS(0)),
+            key(VK_OEM_PERIOD, KEYBD_EVENT_FLAGS(0)),
+            key(VK_OEM_PERIOD, KEYEVENTF_KEYUP),
+            key(VK_LWIN, KEYEVENTF_KEYUP),
+        ];
+        let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+        sent == inputs.len() as u32
+    }
+
     fn minimize(&self) {
         unsafe { ShowWindowAsync(self.0.hwnd, SW_MINIMIZE).ok().log_err() };
     }
@@ -823,6 +1074,18 @@ impl PlatformWindow for WindowsWindow {
         self.0.state.borrow().is_fullscreen()
     }
 
+    fn is_minimized(&self) -> bool {
+        unsafe { IsIconic(self.0.hwnd) }.as_bool()
+    }
+
+    fn restore(&self) {
+        if self.is_fullscreen() {
+            self.toggle_fullscreen();
+        } else {
+            unsafe { ShowWindowAsync(self.0.hwnd, SW_RESTORE).ok().log_err() };
+        }
+    }
+
     fn on_request_frame(&self, callback: Box<dyn FnMut(RequestFrameOptions)>) {
         self.0.state.borrow_mut().callbacks.request_frame = Some(callback);
     }
@@ -863,14 +1126,31 @@ impl PlatformWindow for WindowsWindow {
         self.0.state.borrow_mut().callbacks.appearance_changed = Some(callback);
     }
 
+    fn on_host_info_changed(&self, callback: Box<dyn FnMut()>) {
+        self.0.state.borrow_mut().callbacks.host_info_changed = Some(callback);
+    }
+
     fn draw(&self, scene: &Scene) {
-        self.0.state.borrow_mut().renderer.draw(scene).log_err();
+        let mut lock = self.0.state.borrow_mut();
+        if lock.renderer.draw(scene).log_err().is_none() && lock.renderer.is_device_lost() {
+            // GPUI only watches its own device, so the loss of a host's device has to be reported.
+            lock.invalidate_devices
+                .store(true, std::sync::atomic::Ordering::Release);
+        }
     }
 
     fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
         self.0.state.borrow().renderer.sprite_atlas()
     }
 
+    fn trigger_gpu_capture(&self) -> Result<()> {
+        trigger_renderdoc_capture()
+    }
+
+    fn refresh_rate(&self) -> Option<f32> {
+        self.0.state.borrow().display.refresh_rate()
+    }
+
     fn get_raw_handle(&self) -> HWND {
         self.0.hwnd
     }
@@ -1166,7 +1446,7 @@ enum WindowOpenState {
     Windowed,
 }
 
-const WINDOW_CLASS_NAME: PCWSTR = w!("Zed::Window");
+pub(crate) const WINDOW_CLASS_NAME: PCWSTR = w!("Zed::Window");
 
 fn register_window_class(icon_handle: HICON) {
     static ONCE: Once = Once::new();
@@ -1184,6 +1464,12 @@ fn register_window_class(icon_handle: HICON) {
     });
 }
 
+pub(crate) fn unregister_window_class() {
+    unsafe { UnregisterClassW(WINDOW_CLASS_NAME, Some(get_module_handle().into())) }
+        .context("unregistering window class")
+        .log_err();
+}
+
 unsafe extern "system" fn window_procedure(
     hwnd: HWND,
     msg: u32,
@@ -1232,6 +1518,12 @@ pub(crate) fn window_from_hwnd(hwnd: HWND) -> Option<Rc<WindowsWindowInner>> {
         return None;
     }
 
+    // The user data of a host's window belongs to the host, so the window attached to it is
+    // found through its subclass instead.
+    if let Some(weak) = unsafe { host_subclass_data(hwnd) } {
+        return unsafe { (*weak).upgrade() };
+    }
+
     let ptr = unsafe { get_window_long(hwnd, GWLP_USERDATA) } as *mut Weak<WindowsWindowInner>;
     if !ptr.is_null() {
         let inner = unsafe { &*ptr };
@@ -1241,7 +1533,7 @@ pub(crate) fn window_from_hwnd(hwnd: HWND) -> Option<Rc<WindowsWindowInner>> {
     }
 }
 
-fn get_module_handle() -> HMODULE {
+pub(crate) fn get_module_handle() -> HMODULE {
     unsafe {
         let mut h_module = std::mem::zeroed();
         GetModuleHandleExW(
@@ -1255,7 +1547,7 @@ fn get_module_handle() -> HMODULE {
     }
 }
 
-fn register_drag_drop(window: &Rc<WindowsWindowInner>) -> Result<()> {
+pub(crate) fn register_drag_drop(window: &Rc<WindowsWindowInner>) -> Result<()> {
     let window_handle = window.hwnd;
     let handler = WindowsDragDropHandler(window.clone());
     // The lifetime of `IDropTarget` is handled by Windows, it won't release until
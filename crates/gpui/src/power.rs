@@ -0,0 +1,267 @@
+use std::time::Duration;
+
+use util::ResultExt as _;
+
+use crate::{App, Global, SubscriberSet, Subscription, Task};
+
+const POWER_STATE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const LOW_BATTERY_LEVEL: f32 = 0.2;
+
+/// How strongly the system is working to keep its temperature under control.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ThermalPressure {
+    /// No thermal pressure, or the platform doesn't report it.
+    #[default]
+    Nominal,
+    /// Slightly elevated temperature. Fans may be audible.
+    Fair,
+    /// High temperature. The system is reducing performance to cool down.
+    Serious,
+    /// Critical temperature. The system needs to cool down immediately.
+    Critical,
+}
+
+/// The battery and thermal state of the system. See [`App::power_state`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PowerState {
+    /// Whether the system is running on battery power.
+    pub on_battery: bool,
+    /// The remaining battery charge between 0 and 1, if the system has a battery.
+    pub battery_level: Option<f32>,
+    /// Whether the user has turned on a system-wide power saving mode, such as
+    /// Low Power Mode on macOS or battery saver on Windows.
+    pub low_power_mode: bool,
+    /// The current thermal pressure.
+    pub thermal_pressure: ThermalPressure,
+}
+
+impl PowerState {
+    /// Whether the application should reduce its rendering work: the system is in a power
+    /// saving mode, under serious thermal pressure, or running on a low battery.
+    pub fn is_constrained(&self) -> bool {
+        self.low_power_mode
+            || self.thermal_pressure >= ThermalPressure::Serious
+            || (self.on_battery
+                && self
+                    .battery_level
+                    .is_some_and(|level| level <= LOW_BATTERY_LEVEL))
+    }
+}
+
+/// Rendering adjustments that GPUI applies automatically while the [`PowerState`] is
+/// constrained. See [`App::set_adaptive_rendering`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveRendering {
+    /// The highest rate at which windows are redrawn while constrained, in frames per second.
+    pub max_frame_rate: u32,
+    /// Whether windows with a [`WindowBackgroundAppearance::Blurred`](crate::WindowBackgroundAppearance::Blurred)
+    /// background are drawn with a transparent background instead while constrained.
+    pub disable_blur: bool,
+}
+
+impl Default for AdaptiveRendering {
+    fn default() -> Self {
+        Self {
+            max_frame_rate: 30,
+            disable_blur: true,
+        }
+    }
+}
+
+type PowerStateHandler = Box<dyn FnMut(&PowerState, &mut App) -> bool + 'static>;
+
+struct PowerMonitor {
+    state: PowerState,
+    observers: SubscriberSet<(), PowerStateHandler>,
+    adaptive_rendering: Option<AdaptiveRendering>,
+    _poll_task: Task<()>,
+}
+
+impl Global for PowerMonitor {}
+
+impl App {
+    /// The current battery and thermal state of the system.
+    ///
+    /// Once [`App::observe_power_state`] or [`App::set_adaptive_rendering`] has been called,
+    /// this returns the state recorded by the last periodic check rather than querying the
+    /// platform.
+    pub fn power_state(&self) -> PowerState {
+        self.try_global::<PowerMonitor>()
+            .map_or_else(|| self.platform.power_state(), |monitor| monitor.state)
+    }
+
+    /// Invoke the given callback whenever the battery or thermal state of the system changes.
+    /// The state is checked every few seconds while the application is running.
+    pub fn observe_power_state(
+        &mut self,
+        mut callback: impl FnMut(&PowerState, &mut App) + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.power_monitor().observers.insert(
+            (),
+            Box::new(move |state, cx| {
+                callback(state, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Lower the frame rate and drop window blur effects while the [`PowerState`] is
+    /// constrained, for example to keep a plugin UI from draining a laptop battery during a
+    /// live performance. Pass `None` to always render at full quality, which is the default.
+    pub fn set_adaptive_rendering(&mut self, adaptive_rendering: Option<AdaptiveRendering>) {
+        self.power_monitor().adaptive_rendering = adaptive_rendering;
+        self.apply_adaptive_rendering();
+    }
+
+    /// The adaptive rendering settings passed to [`App::set_adaptive_rendering`], if any.
+    pub fn adaptive_rendering(&self) -> Option<AdaptiveRendering> {
+        self.try_global::<PowerMonitor>()
+            .and_then(|monitor| monitor.adaptive_rendering)
+    }
+
+    /// The minimum time between frames imposed by adaptive rendering, if any.
+    pub(crate) fn adaptive_frame_interval(&self) -> Option<Duration> {
+        let monitor = self.try_global::<PowerMonitor>()?;
+        let adaptive_rendering = monitor.adaptive_rendering?;
+        (monitor.state.is_constrained() && adaptive_rendering.max_frame_rate > 0)
+            .then(|| Duration::from_secs(1) / adaptive_rendering.max_frame_rate)
+    }
+
+    /// Whether adaptive rendering currently replaces blurred window backgrounds.
+    pub(crate) fn adaptive_blur_disabled(&self) -> bool {
+        self.try_global::<PowerMonitor>().is_some_and(|monitor| {
+            monitor.state.is_constrained()
+                && monitor
+                    .adaptive_rendering
+                    .is_some_and(|adaptive_rendering| adaptive_rendering.disable_blur)
+        })
+    }
+
+    fn power_monitor(&mut self) -> &mut PowerMonitor {
+        if !self.has_global::<PowerMonitor>() {
+            let poll_task = self.spawn(async move |cx| {
+                loop {
+                    cx.background_executor()
+                        .timer(POWER_STATE_POLL_INTERVAL)
+                        .await;
+                    if cx.update(|cx| cx.refresh_power_state()).is_err() {
+                        break;
+                    }
+                }
+            });
+            let monitor = PowerMonitor {
+                state: self.platform.power_state(),
+                observers: SubscriberSet::new(),
+                adaptive_rendering: None,
+                _poll_task: poll_task,
+            };
+            self.set_global(monitor);
+        }
+        self.global_mut::<PowerMonitor>()
+    }
+
+    fn refresh_power_state(&mut self) {
+        let state = self.platform.power_state();
+        let monitor = self.global_mut::<PowerMonitor>();
+        if monitor.state == state {
+            return;
+        }
+        let was_constrained = monitor.state.is_constrained();
+        monitor.state = state;
+        let observers = monitor.observers.clone();
+
+        if was_constrained != state.is_constrained() {
+            self.apply_adaptive_rendering();
+        }
+        observers.retain(&(), |callback| callback(&state, self));
+    }
+
+    fn apply_adaptive_rendering(&mut self) {
+        let blur_disabled = self.adaptive_blur_disabled();
+        for window in self.windows() {
+            window
+                .update(self, |_, window, _| {
+                    window.set_blur_suppressed(blur_disabled);
+                    window.refresh();
+                })
+                .log_err();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::TestAppContext;
+
+    use super::*;
+
+    #[crate::test]
+    fn test_power_state_observers(cx: &mut TestAppContext) {
+        let observed = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            cx.set_adaptive_rendering(Some(AdaptiveRendering::default()));
+            cx.observe_power_state({
+                let observed = observed.clone();
+                move |state, _| observed.borrow_mut().push(*state)
+            })
+        });
+        cx.update(|cx| assert_eq!(cx.adaptive_frame_interval(), None));
+
+        let constrained = PowerState {
+            on_battery: true,
+            battery_level: Some(0.1),
+            ..Default::default()
+        };
+        cx.simulate_power_state(constrained);
+        cx.executor().advance_clock(POWER_STATE_POLL_INTERVAL);
+        assert_eq!(*observed.borrow(), [constrained]);
+        cx.update(|cx| {
+            assert_eq!(cx.power_state(), constrained);
+            assert_eq!(
+                cx.adaptive_frame_interval(),
+                Some(Duration::from_secs(1) / 30)
+            );
+            assert!(cx.adaptive_blur_disabled());
+        });
+
+        // Observers aren't notified when nothing changed.
+        cx.executor().advance_clock(POWER_STATE_POLL_INTERVAL);
+        assert_eq!(observed.borrow().len(), 1);
+
+        cx.simulate_power_state(PowerState::default());
+        cx.executor().advance_clock(POWER_STATE_POLL_INTERVAL);
+        assert_eq!(observed.borrow().len(), 2);
+        cx.update(|cx| assert_eq!(cx.adaptive_frame_interval(), None));
+    }
+
+    #[crate::test]
+    fn test_adaptive_frame_throttling(cx: &mut TestAppContext) {
+        cx.simulate_power_state(PowerState {
+            low_power_mode: true,
+            ..Default::default()
+        });
+        cx.update(|cx| {
+            cx.set_adaptive_rendering(Some(AdaptiveRendering::default()));
+            let executor = cx.background_executor().clone();
+            cx.set_frame_clock(move || executor.now());
+        });
+        let cx = cx.add_empty_window();
+        let interval = cx.update(|_, cx| cx.adaptive_frame_interval().unwrap());
+
+        cx.update(|window, cx| assert!(window.is_frame_throttled(cx)));
+        cx.executor().advance_clock(interval / 2);
+        cx.update(|window, cx| assert!(window.is_frame_throttled(cx)));
+        cx.executor().advance_clock(interval / 2);
+        cx.update(|window, cx| assert!(!window.is_frame_throttled(cx)));
+
+        // Drawing for any reason restarts the interval.
+        cx.update(|window, cx| {
+            window.draw(cx).clear();
+            assert!(window.is_frame_throttled(cx));
+        });
+    }
+}
@@ -0,0 +1,91 @@
+//! Process-wide state that GPUI keeps outside of any [`App`](crate::App).
+//!
+//! Hosts that load GPUI from a dynamic library and unload it again, such as plugin hosts, must
+//! call [`shutdown`] before unloading it. Otherwise, the platform keeps window classes and
+//! threads around that point into the unloaded library, and the next load fails to register its
+//! window classes or crashes when an old thread wakes up.
+
+use std::{cell::RefCell, thread::JoinHandle};
+
+use parking_lot::Mutex;
+
+use crate::{Application, ELEMENT_ARENA, GLOBAL_THREAD_TIMINGS};
+
+static BACKGROUND_THREADS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+thread_local! {
+    /// The applications that [`Application::run`] attached to an event loop that the host
+    /// already ran, which nothing else holds on to
+    static ATTACHED_APPLICATIONS: RefCell<Vec<Application>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Hand a background thread over to the runtime, so that [`shutdown`] waits for it. The thread
+/// must exit on its own once the platform that spawned it has been dropped.
+pub(crate) fn register_background_thread(thread: JoinHandle<()>) {
+    BACKGROUND_THREADS.lock().push(thread);
+}
+
+/// Keep an application that [`Application::run`] attached to the host's event loop alive until
+/// [`shutdown`].
+pub(crate) fn keep_attached_application(application: Application) {
+    ATTACHED_APPLICATIONS.with_borrow_mut(|applications| applications.push(application));
+}
+
+/// Release the process-wide state that GPUI holds, so that the library can be unloaded.
+///
+/// This shuts down the applications that GPUI attached to the host's event loop itself, such as
+/// with [`Application::run`], removes the hooks that embedded windows installed in the host's
+/// windows, unregisters GPUI's window classes, waits for its background threads to exit, and
+/// drops the elements that the last frame left behind on this thread, along with the references
+/// they hold. It must be called on the main thread, after every other [`Application`] has been
+/// dropped and every window closed. GPUI can't be used again until it's reloaded.
+pub fn shutdown() {
+    release_thread_state();
+    crate::platform::shutdown_platform();
+
+    let threads = std::mem::take(&mut *BACKGROUND_THREADS.lock());
+    for thread in threads {
+        let name = thread.thread().name().unwrap_or("unnamed").to_owned();
+        if thread.join().is_err() {
+            log::error!("background thread {name} panicked during shutdown");
+        }
+    }
+
+    GLOBAL_THREAD_TIMINGS
+        .lock()
+        .retain(|thread| thread.timings.strong_count() > 0);
+}
+
+/// Release the state that GPUI keeps in the calling thread's thread-locals, see [`shutdown`].
+pub(crate) fn release_thread_state() {
+    let applications = ATTACHED_APPLICATIONS.take();
+    for application in applications {
+        application.shutdown();
+    }
+    crate::platform::release_platform_thread_state();
+    ELEMENT_ARENA.with_borrow_mut(|element_arena| element_arena.trim());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestAppContext;
+    use std::rc::Rc;
+
+    #[crate::test]
+    fn test_release_thread_state(cx: &mut TestAppContext) {
+        cx.simulate_running_event_loop();
+        let strong_count = Rc::strong_count(&cx.app);
+        Application(cx.app.clone()).run(|_| {});
+        cx.add_empty_window();
+        #[cfg(target_os = "windows")]
+        crate::platform::watch_host_sizes();
+
+        release_thread_state();
+        assert_eq!(Rc::strong_count(&cx.app), strong_count);
+        assert!(cx.windows().is_empty());
+        assert!(ATTACHED_APPLICATIONS.with_borrow(|applications| applications.is_empty()));
+        #[cfg(target_os = "windows")]
+        assert!(!crate::platform::has_host_hooks());
+    }
+}
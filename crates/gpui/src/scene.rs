@@ -115,7 +115,11 @@ impl Scene {
     }
 
     pub fn replay(&mut self, range: Range<usize>, prev_scene: &Scene) {
-        for operation in &prev_scene.paint_operations[range] {
+        self.insert_operations(&prev_scene.paint_operations[range]);
+    }
+
+    pub fn insert_operations(&mut self, operations: &[PaintOperation]) {
+        for operation in operations {
             match operation {
                 PaintOperation::Primitive(primitive) => self.insert_primitive(primitive.clone()),
                 PaintOperation::StartLayer(bounds) => self.push_layer(*bounds),
@@ -189,12 +193,118 @@ pub(crate) enum PrimitiveKind {
     Surface,
 }
 
+#[derive(Clone)]
 pub(crate) enum PaintOperation {
     Primitive(Primitive),
     StartLayer(Bounds<ScaledPixels>),
     EndLayer,
 }
 
+impl PaintOperation {
+    /// Returns this operation as if everything it paints had been drawn `factor` times as large,
+    /// scaling about `origin`, and clipped to `content_mask`.
+    pub(crate) fn scaled(
+        &self,
+        origin: Point<ScaledPixels>,
+        factor: f32,
+        content_mask: &ContentMask<ScaledPixels>,
+    ) -> Self {
+        let scale_point = |point: Point<ScaledPixels>| Point {
+            x: origin.x + (point.x - origin.x) * factor,
+            y: origin.y + (point.y - origin.y) * factor,
+        };
+        let scale_bounds = |bounds: Bounds<ScaledPixels>| Bounds {
+            origin: scale_point(bounds.origin),
+            size: bounds.size.map(|length| length * factor),
+        };
+        let scale_mask = |mask: &ContentMask<ScaledPixels>| ContentMask {
+            bounds: scale_bounds(mask.bounds).intersect(&content_mask.bounds),
+        };
+
+        let primitive = match self {
+            PaintOperation::Primitive(primitive) => primitive,
+            PaintOperation::StartLayer(bounds) => {
+                return PaintOperation::StartLayer(
+                    scale_bounds(*bounds).intersect(&content_mask.bounds),
+                );
+            }
+            PaintOperation::EndLayer => return PaintOperation::EndLayer,
+        };
+        let primitive = match primitive {
+            Primitive::Shadow(shadow) => Primitive::Shadow(Shadow {
+                blur_radius: shadow.blur_radius * factor,
+                bounds: scale_bounds(shadow.bounds),
+                corner_radii: shadow.corner_radii.map(|radius| *radius * factor),
+                content_mask: scale_mask(&shadow.content_mask),
+                ..shadow.clone()
+            }),
+            Primitive::Quad(quad) => Primitive::Quad(Quad {
+                bounds: scale_bounds(quad.bounds),
+                content_mask: scale_mask(&quad.content_mask),
+                corner_radii: quad.corner_radii.map(|radius| *radius * factor),
+                border_widths: quad.border_widths.map(|width| *width * factor),
+                ..quad.clone()
+            }),
+            Primitive::Path(path) => {
+                let mut path = path.clone();
+                path.bounds = scale_bounds(path.bounds);
+                path.content_mask = scale_mask(&path.content_mask);
+                path.start = scale_point(path.start);
+                path.current = scale_point(path.current);
+                for vertex in &mut path.vertices {
+                    vertex.xy_position = scale_point(vertex.xy_position);
+                    vertex.content_mask = scale_mask(&vertex.content_mask);
+                }
+                Primitive::Path(path)
+            }
+            Primitive::Underline(underline) => Primitive::Underline(Underline {
+                bounds: scale_bounds(underline.bounds),
+                content_mask: scale_mask(&underline.content_mask),
+                thickness: underline.thickness * factor,
+                ..underline.clone()
+            }),
+            Primitive::MonochromeSprite(sprite) => {
+                // The transformation is applied to the sprite's final position, so its translation
+                // has to absorb the scaling for the sprite to end up where the unscaled one would.
+                let matrix = sprite.transformation.rotation_scale;
+                let translation = sprite.transformation.translation;
+                let origin = [origin.x.0, origin.y.0];
+                let transformed_origin = [
+                    matrix[0][0] * origin[0] + matrix[0][1] * origin[1],
+                    matrix[1][0] * origin[0] + matrix[1][1] * origin[1],
+                ];
+                let transformation = TransformationMatrix {
+                    rotation_scale: matrix,
+                    translation: [
+                        translation[0] * factor
+                            + (1. - factor) * (origin[0] - transformed_origin[0]),
+                        translation[1] * factor
+                            + (1. - factor) * (origin[1] - transformed_origin[1]),
+                    ],
+                };
+                Primitive::MonochromeSprite(MonochromeSprite {
+                    bounds: scale_bounds(sprite.bounds),
+                    content_mask: scale_mask(&sprite.content_mask),
+                    transformation,
+                    ..sprite.clone()
+                })
+            }
+            Primitive::PolychromeSprite(sprite) => Primitive::PolychromeSprite(PolychromeSprite {
+                bounds: scale_bounds(sprite.bounds),
+                content_mask: scale_mask(&sprite.content_mask),
+                corner_radii: sprite.corner_radii.map(|radius| *radius * factor),
+                ..sprite.clone()
+            }),
+            Primitive::Surface(surface) => Primitive::Surface(PaintSurface {
+                bounds: scale_bounds(surface.bounds),
+                content_mask: scale_mask(&surface.content_mask),
+                ..surface.clone()
+            }),
+        };
+        PaintOperation::Primitive(primitive)
+    }
+}
+
 #[derive(Clone)]
 pub(crate) enum Primitive {
     Shadow(Shadow),
@@ -448,6 +558,28 @@ pub(crate) enum PrimitiveBatch<'a> {
     Surfaces(&'a [PaintSurface]),
 }
 
+impl PrimitiveBatch<'_> {
+    /// The name under which this batch is grouped in GPU debuggers.
+    #[cfg_attr(
+        not(any(
+            all(target_os = "macos", not(feature = "macos-blade")),
+            target_os = "windows"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) fn debug_label(&self) -> &'static str {
+        match self {
+            PrimitiveBatch::Shadows(_) => "Shadows",
+            PrimitiveBatch::Quads(_) => "Quads",
+            PrimitiveBatch::Paths(_) => "Paths",
+            PrimitiveBatch::Underlines(_) => "Underlines",
+            PrimitiveBatch::MonochromeSprites { .. } => "Monochrome sprites",
+            PrimitiveBatch::PolychromeSprites { .. } => "Polychrome sprites",
+            PrimitiveBatch::Surfaces(_) => "Surfaces",
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 #[repr(C)]
 pub(crate) struct Quad {
@@ -638,7 +770,10 @@ impl From<MonochromeSprite> for Primitive {
 #[repr(C)]
 pub(crate) struct PolychromeSprite {
     pub order: DrawOrder,
-    pub pad: u32, // align to 8 bytes
+    /// When non-zero, the tile doesn't hold an image but a waveform of this many peaks, which
+    /// the fragment shader reduces to the extent of the peaks under each pixel. See
+    /// `Window::paint_waveform` for the layout of the tile.
+    pub peak_count: u32,
     pub grayscale: bool,
     pub opacity: f32,
     pub bounds: Bounds<ScaledPixels>,
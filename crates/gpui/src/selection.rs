@@ -0,0 +1,295 @@
+use std::collections::BTreeSet;
+
+use crate::{Keystroke, Modifiers};
+
+/// How many items a [`SelectionModel`] can select.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// At most one item is selected.
+    #[default]
+    Single,
+    /// Any number of items can be selected, by toggling items with the platform's secondary
+    /// modifier and selecting ranges with shift.
+    Multiple,
+}
+
+/// The selected items of a list, grid or tree, by index.
+///
+/// Keep one alongside a [`ListState`](crate::ListState) or
+/// [`UniformListScrollHandle`](crate::UniformListScrollHandle), update it from the items' click
+/// handlers with [`SelectionModel::click`] and from key handlers with
+/// [`SelectionModel::handle_keystroke`], and query [`SelectionModel::is_selected`] when rendering
+/// items. Grids are navigated by row with [`SelectionModel::with_columns`], and trees are
+/// navigated by their visible rows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelectionModel {
+    mode: SelectionMode,
+    item_count: usize,
+    columns: usize,
+    selected: BTreeSet<usize>,
+    anchor: Option<usize>,
+    cursor: Option<usize>,
+}
+
+impl SelectionModel {
+    /// Create an empty selection over the given number of items.
+    pub fn new(mode: SelectionMode, item_count: usize) -> Self {
+        Self {
+            mode,
+            item_count,
+            columns: 1,
+            selected: BTreeSet::new(),
+            anchor: None,
+            cursor: None,
+        }
+    }
+
+    /// Lay the items out in rows of the given number of columns, so moving up and down moves by
+    /// a row, and moving left and right moves by an item.
+    pub fn with_columns(mut self, columns: usize) -> Self {
+        self.columns = columns.max(1);
+        self
+    }
+
+    /// The selection mode.
+    pub fn mode(&self) -> SelectionMode {
+        self.mode
+    }
+
+    /// The number of items that can be selected.
+    pub fn item_count(&self) -> usize {
+        self.item_count
+    }
+
+    /// Update the number of items, such as when the list's items change. Selected items past the
+    /// new end are deselected.
+    pub fn set_item_count(&mut self, item_count: usize) {
+        self.item_count = item_count;
+        self.selected.split_off(&item_count);
+        self.anchor = self.anchor.filter(|&ix| ix < item_count);
+        self.cursor = self.cursor.filter(|&ix| ix < item_count);
+    }
+
+    /// Whether the item at the given index is selected.
+    pub fn is_selected(&self, ix: usize) -> bool {
+        self.selected.contains(&ix)
+    }
+
+    /// The indices of the selected items, in ascending order.
+    pub fn selected(&self) -> impl Iterator<Item = usize> + '_ {
+        self.selected.iter().copied()
+    }
+
+    /// The number of selected items.
+    pub fn selected_count(&self) -> usize {
+        self.selected.len()
+    }
+
+    /// The item that keyboard navigation moves from, usually the last item clicked. Lists should
+    /// keep this item visible.
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    /// The item that ranges selected with shift extend from.
+    pub fn anchor(&self) -> Option<usize> {
+        self.anchor
+    }
+
+    /// Select only the item at the given index, and move the anchor and cursor to it.
+    pub fn select(&mut self, ix: usize) {
+        if ix >= self.item_count {
+            return;
+        }
+        self.selected.clear();
+        self.selected.insert(ix);
+        self.anchor = Some(ix);
+        self.cursor = Some(ix);
+    }
+
+    /// Add the item at the given index to the selection, or remove it if it was selected. In
+    /// [`SelectionMode::Single`], this selects only that item.
+    pub fn toggle(&mut self, ix: usize) {
+        if ix >= self.item_count {
+            return;
+        }
+        if self.mode == SelectionMode::Single {
+            self.select(ix);
+            return;
+        }
+        if !self.selected.remove(&ix) {
+            self.selected.insert(ix);
+        }
+        self.anchor = Some(ix);
+        self.cursor = Some(ix);
+    }
+
+    /// Select the items between the anchor and the given index, replacing the range that was
+    /// last selected from the same anchor. In [`SelectionMode::Single`], this selects only that
+    /// item.
+    pub fn extend_to(&mut self, ix: usize) {
+        if ix >= self.item_count {
+            return;
+        }
+        let Some(anchor) = self.anchor.filter(|_| self.mode == SelectionMode::Multiple) else {
+            self.select(ix);
+            return;
+        };
+        if let Some(cursor) = self.cursor {
+            for previous in anchor.min(cursor)..=anchor.max(cursor) {
+                self.selected.remove(&previous);
+            }
+        }
+        self.selected.extend(anchor.min(ix)..=anchor.max(ix));
+        self.cursor = Some(ix);
+    }
+
+    /// Update the selection for a click on the item at the given index: the platform's secondary
+    /// modifier toggles the item, shift extends the selection to it, and a plain click selects
+    /// only it.
+    pub fn click(&mut self, ix: usize, modifiers: &Modifiers) {
+        if modifiers.shift {
+            self.extend_to(ix);
+        } else if modifiers.secondary() {
+            self.toggle(ix);
+        } else {
+            self.select(ix);
+        }
+    }
+
+    /// Select every item. In [`SelectionMode::Single`], this does nothing.
+    pub fn select_all(&mut self) {
+        if self.mode == SelectionMode::Multiple {
+            self.selected.extend(0..self.item_count);
+        }
+    }
+
+    /// Deselect every item.
+    pub fn clear(&mut self) {
+        self.selected.clear();
+        self.anchor = None;
+        self.cursor = None;
+    }
+
+    /// Move the cursor by the given number of items, clamped to the items, and select the item it
+    /// lands on. With `extend`, the selection is extended to it instead. Without a cursor, the
+    /// first item is selected when moving forward and the last when moving backward. Returns the
+    /// new cursor.
+    pub fn move_cursor(&mut self, delta: isize, extend: bool) -> Option<usize> {
+        if self.item_count == 0 {
+            return None;
+        }
+        let ix = match self.cursor {
+            Some(cursor) => cursor.saturating_add_signed(delta),
+            None if delta < 0 => self.item_count - 1,
+            None => 0,
+        };
+        self.move_cursor_to(ix.min(self.item_count - 1), extend)
+    }
+
+    fn move_cursor_to(&mut self, ix: usize, extend: bool) -> Option<usize> {
+        if extend {
+            self.extend_to(ix);
+        } else {
+            self.select(ix);
+        }
+        self.cursor
+    }
+
+    /// Update the selection for a keystroke, returning whether it was handled: the arrow keys
+    /// move the cursor, home and end move it to the first and last item, shift extends the
+    /// selection as the cursor moves, and the platform's secondary modifier with `a` selects
+    /// every item. Left and right are only handled for grids, see
+    /// [`SelectionModel::with_columns`].
+    pub fn handle_keystroke(&mut self, keystroke: &Keystroke) -> bool {
+        let modifiers = &keystroke.modifiers;
+        if keystroke.key == "a" && modifiers.secondary() && !modifiers.shift {
+            self.select_all();
+            return self.mode == SelectionMode::Multiple;
+        }
+
+        let columns = self.columns as isize;
+        let extend = modifiers.shift;
+        match keystroke.key.as_str() {
+            "up" => self.move_cursor(-columns, extend),
+            "down" => self.move_cursor(columns, extend),
+            "left" if columns > 1 => self.move_cursor(-1, extend),
+            "right" if columns > 1 => self.move_cursor(1, extend),
+            "home" if self.item_count > 0 => self.move_cursor_to(0, extend),
+            "end" if self.item_count > 0 => self.move_cursor_to(self.item_count - 1, extend),
+            _ => return false,
+        };
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selected(model: &SelectionModel) -> Vec<usize> {
+        model.selected().collect()
+    }
+
+    #[test]
+    fn test_single_selection() {
+        let mut model = SelectionModel::new(SelectionMode::Single, 5);
+        model.select(1);
+        model.toggle(3);
+        assert_eq!(selected(&model), [3]);
+        model.click(0, &Modifiers::shift());
+        assert_eq!(selected(&model), [0]);
+        model.select_all();
+        assert_eq!(selected(&model), [0]);
+        model.select(7);
+        assert_eq!(selected(&model), [0]);
+    }
+
+    #[test]
+    fn test_multiple_selection_clicks() {
+        let mut model = SelectionModel::new(SelectionMode::Multiple, 10);
+        model.click(2, &Modifiers::none());
+        model.click(5, &Modifiers::shift());
+        assert_eq!(selected(&model), [2, 3, 4, 5]);
+
+        // Extending again from the same anchor replaces the previous range.
+        model.click(0, &Modifiers::shift());
+        assert_eq!(selected(&model), [0, 1, 2]);
+
+        model.click(8, &Modifiers::secondary_key());
+        assert_eq!(selected(&model), [0, 1, 2, 8]);
+        assert_eq!(model.anchor(), Some(8));
+        model.click(1, &Modifiers::secondary_key());
+        assert_eq!(selected(&model), [0, 2, 8]);
+
+        model.set_item_count(5);
+        assert_eq!(selected(&model), [0, 2]);
+        assert_eq!(model.cursor(), Some(1));
+        assert_eq!(model.anchor(), Some(1));
+    }
+
+    #[test]
+    fn test_keyboard_navigation() {
+        let mut model = SelectionModel::new(SelectionMode::Multiple, 10);
+        assert!(model.handle_keystroke(&Keystroke::parse("down").unwrap()));
+        assert_eq!(selected(&model), [0]);
+        model.handle_keystroke(&Keystroke::parse("shift-down").unwrap());
+        model.handle_keystroke(&Keystroke::parse("shift-down").unwrap());
+        assert_eq!(selected(&model), [0, 1, 2]);
+        model.handle_keystroke(&Keystroke::parse("shift-end").unwrap());
+        assert_eq!(selected(&model), (0..10).collect::<Vec<_>>());
+        model.handle_keystroke(&Keystroke::parse("home").unwrap());
+        assert_eq!(selected(&model), [0]);
+        assert!(!model.handle_keystroke(&Keystroke::parse("left").unwrap()));
+        assert!(!model.handle_keystroke(&Keystroke::parse("x").unwrap()));
+
+        let mut grid = SelectionModel::new(SelectionMode::Single, 12).with_columns(4);
+        grid.select(5);
+        grid.handle_keystroke(&Keystroke::parse("down").unwrap());
+        assert_eq!(grid.cursor(), Some(9));
+        grid.handle_keystroke(&Keystroke::parse("right").unwrap());
+        assert_eq!(grid.cursor(), Some(10));
+        grid.handle_keystroke(&Keystroke::parse("down").unwrap());
+        assert_eq!(grid.cursor(), Some(11));
+    }
+}
@@ -0,0 +1,328 @@
+use std::rc::Rc;
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::FxHashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AnyWindowHandle, App, Bounds, Global, Pixels, SharedString, Window, WindowBounds, WindowOptions,
+};
+
+/// The placement of a window as recorded in a [`WindowSession`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowPlacement {
+    /// Whether the window was windowed, maximized or fullscreen.
+    pub mode: WindowPlacementMode,
+    /// The window's bounds in screen coordinates. For maximized and fullscreen windows, these
+    /// are the bounds the window restores to.
+    pub bounds: Bounds<Pixels>,
+    /// The UUID of the display the window was on, if known.
+    pub display_uuid: Option<Uuid>,
+    /// The window's content zoom.
+    #[serde(default = "default_content_zoom")]
+    pub content_zoom: f32,
+}
+
+fn default_content_zoom() -> f32 {
+    1.0
+}
+
+/// How a window was displayed when its [`WindowPlacement`] was recorded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowPlacementMode {
+    /// A regular window with the recorded bounds.
+    Windowed,
+    /// A maximized window.
+    Maximized,
+    /// A fullscreen window.
+    Fullscreen,
+}
+
+impl WindowPlacement {
+    /// Record the current placement of the given window.
+    pub fn of_window(window: &Window, cx: &App) -> Self {
+        let (mode, bounds) = match window.window_bounds() {
+            WindowBounds::Windowed(bounds) => (WindowPlacementMode::Windowed, bounds),
+            WindowBounds::Maximized(bounds) => (WindowPlacementMode::Maximized, bounds),
+            WindowBounds::Fullscreen(bounds) => (WindowPlacementMode::Fullscreen, bounds),
+        };
+        Self {
+            mode,
+            bounds,
+            display_uuid: window.display(cx).and_then(|display| display.uuid().ok()),
+            content_zoom: window.content_zoom(),
+        }
+    }
+
+    /// The [`WindowBounds`] to open a window with in order to restore this placement.
+    pub fn window_bounds(&self) -> WindowBounds {
+        match self.mode {
+            WindowPlacementMode::Windowed => WindowBounds::Windowed(self.bounds),
+            WindowPlacementMode::Maximized => WindowBounds::Maximized(self.bounds),
+            WindowPlacementMode::Fullscreen => WindowBounds::Fullscreen(self.bounds),
+        }
+    }
+
+    /// Window options that restore this placement. If the recorded display is no longer
+    /// connected, the window is opened on the primary display, centered with its recorded size.
+    pub fn window_options(&self, cx: &App) -> WindowOptions {
+        let display = self.display_uuid.and_then(|uuid| {
+            cx.displays()
+                .into_iter()
+                .find(|display| display.uuid().ok() == Some(uuid))
+        });
+        let window_bounds = if display.is_some() || self.display_uuid.is_none() {
+            self.window_bounds()
+        } else {
+            let bounds = Bounds::centered(None, self.bounds.size, cx);
+            match self.mode {
+                WindowPlacementMode::Windowed => WindowBounds::Windowed(bounds),
+                WindowPlacementMode::Maximized => WindowBounds::Maximized(bounds),
+                WindowPlacementMode::Fullscreen => WindowBounds::Fullscreen(bounds),
+            }
+        };
+        WindowOptions {
+            window_bounds: Some(window_bounds),
+            display_id: display.map(|display| display.id()),
+            content_zoom: self.content_zoom,
+            ..Default::default()
+        }
+    }
+}
+
+/// A window recorded in a [`WindowSession`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowSessionEntry {
+    /// The kind of window, as passed to [`App::register_session_window_kind`].
+    pub kind: SharedString,
+    /// Where the window was placed.
+    pub placement: WindowPlacement,
+    /// The state blob the application recorded for this window.
+    pub state: serde_json::Value,
+}
+
+/// A snapshot of the application's open windows, which can be persisted and used to reopen
+/// the same windows on the next launch.
+///
+/// GPUI does not decide where sessions are stored; use [`WindowSession::to_json`] and
+/// [`WindowSession::from_json`] to persist them wherever the application keeps its state.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowSession {
+    /// The recorded windows, ordered from back to front.
+    pub windows: Vec<WindowSessionEntry>,
+}
+
+impl WindowSession {
+    /// Serialize this session to JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).context("serializing window session")
+    }
+
+    /// Deserialize a session previously serialized with [`WindowSession::to_json`].
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("deserializing window session")
+    }
+}
+
+type RestoreWindowFn =
+    dyn Fn(WindowOptions, serde_json::Value, &mut App) -> Result<AnyWindowHandle>;
+type SaveWindowStateFn = dyn Fn(&mut Window, &mut App) -> serde_json::Value;
+
+struct TrackedWindow {
+    handle: AnyWindowHandle,
+    kind: SharedString,
+    save_state: Rc<SaveWindowStateFn>,
+}
+
+#[derive(Default)]
+struct SessionManager {
+    restorers: FxHashMap<SharedString, Rc<RestoreWindowFn>>,
+    tracked_windows: Vec<TrackedWindow>,
+}
+
+impl Global for SessionManager {}
+
+impl App {
+    /// Register how to reopen windows of the given kind when restoring a [`WindowSession`].
+    ///
+    /// The callback receives window options that restore the recorded placement, along with
+    /// the state blob recorded for the window. It should open the window and call
+    /// [`App::track_window_in_session`] so that the window is recorded again in future sessions.
+    pub fn register_session_window_kind(
+        &mut self,
+        kind: impl Into<SharedString>,
+        restore: impl Fn(WindowOptions, serde_json::Value, &mut App) -> Result<AnyWindowHandle>
+        + 'static,
+    ) {
+        self.default_global::<SessionManager>()
+            .restorers
+            .insert(kind.into(), Rc::new(restore));
+    }
+
+    /// Include the given window in sessions captured with [`App::capture_window_session`].
+    /// `save_state` is called at capture time to record the window's application state.
+    pub fn track_window_in_session(
+        &mut self,
+        handle: impl Into<AnyWindowHandle>,
+        kind: impl Into<SharedString>,
+        save_state: impl Fn(&mut Window, &mut App) -> serde_json::Value + 'static,
+    ) {
+        let handle = handle.into();
+        let manager = self.default_global::<SessionManager>();
+        manager
+            .tracked_windows
+            .retain(|tracked| tracked.handle != handle);
+        manager.tracked_windows.push(TrackedWindow {
+            handle,
+            kind: kind.into(),
+            save_state: Rc::new(save_state),
+        });
+    }
+
+    /// Stop including the given window in captured sessions.
+    pub fn untrack_window_in_session(&mut self, handle: impl Into<AnyWindowHandle>) {
+        let handle = handle.into();
+        if let Some(manager) = self.try_global::<SessionManager>()
+            && manager
+                .tracked_windows
+                .iter()
+                .any(|tracked| tracked.handle == handle)
+        {
+            self.global_mut::<SessionManager>()
+                .tracked_windows
+                .retain(|tracked| tracked.handle != handle);
+        }
+    }
+
+    /// Record the placement and state of every tracked window that is still open.
+    pub fn capture_window_session(&mut self) -> WindowSession {
+        let Some(manager) = self.try_global::<SessionManager>() else {
+            return WindowSession::default();
+        };
+        let tracked_windows = manager
+            .tracked_windows
+            .iter()
+            .map(|tracked| {
+                (
+                    tracked.handle,
+                    tracked.kind.clone(),
+                    tracked.save_state.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut windows = Vec::with_capacity(tracked_windows.len());
+        let mut closed_windows = Vec::new();
+        for (handle, kind, save_state) in tracked_windows {
+            let entry = handle.update(self, |_, window, cx| WindowSessionEntry {
+                kind,
+                placement: WindowPlacement::of_window(window, cx),
+                state: save_state(window, cx),
+            });
+            match entry {
+                Ok(entry) => windows.push(entry),
+                Err(_) => closed_windows.push(handle),
+            }
+        }
+
+        if !closed_windows.is_empty() {
+            self.global_mut::<SessionManager>()
+                .tracked_windows
+                .retain(|tracked| !closed_windows.contains(&tracked.handle));
+        }
+
+        WindowSession { windows }
+    }
+
+    /// Reopen the windows recorded in a session, using the callbacks registered with
+    /// [`App::register_session_window_kind`]. Returns one result per recorded window, in order.
+    pub fn restore_window_session(
+        &mut self,
+        session: WindowSession,
+    ) -> Vec<Result<AnyWindowHandle>> {
+        session
+            .windows
+            .into_iter()
+            .map(|entry| {
+                let restore = self
+                    .try_global::<SessionManager>()
+                    .and_then(|manager| manager.restorers.get(&entry.kind).cloned())
+                    .ok_or_else(|| {
+                        anyhow!("no restorer registered for window kind {}", entry.kind)
+                    })?;
+                let options = entry.placement.window_options(self);
+                restore(options, entry.state, self)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as gpui, AppContext as _, Context, IntoElement, Render, TestAppContext, Window, div,
+        point, px, size,
+    };
+
+    use super::*;
+
+    struct Document(String);
+
+    impl Render for Document {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    fn open_document(
+        options: WindowOptions,
+        state: serde_json::Value,
+        cx: &mut App,
+    ) -> Result<AnyWindowHandle> {
+        let title = state.as_str().unwrap_or_default().to_string();
+        let document = cx.new(|_| Document(title));
+        let handle = cx.open_window(options, |_, _| document.clone())?;
+        cx.track_window_in_session(handle, "document", move |_, cx| {
+            serde_json::Value::String(document.read(cx).0.clone())
+        });
+        Ok(handle.into())
+    }
+
+    #[gpui::test]
+    fn test_window_session_round_trip(cx: &mut TestAppContext) {
+        let bounds = Bounds::new(point(px(10.), px(20.)), size(px(300.), px(200.)));
+        let session = cx.update(|cx| {
+            cx.register_session_window_kind("document", open_document);
+            let options = WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                ..Default::default()
+            };
+            open_document(options, "notes.txt".into(), cx).unwrap();
+            cx.capture_window_session()
+        });
+
+        assert_eq!(session.windows.len(), 1);
+        assert_eq!(session.windows[0].kind, "document");
+        assert_eq!(session.windows[0].placement.bounds, bounds);
+        assert_eq!(session.windows[0].state, "notes.txt");
+
+        let session = WindowSession::from_json(&session.to_json().unwrap()).unwrap();
+        cx.update(|cx| {
+            for window in cx.windows() {
+                window
+                    .update(cx, |_, window, _| window.remove_window())
+                    .unwrap();
+            }
+        });
+        cx.run_until_parked();
+
+        let restored = cx.update(|cx| {
+            assert!(cx.capture_window_session().windows.is_empty());
+            let results = cx.restore_window_session(session.clone());
+            assert!(results.iter().all(|result| result.is_ok()));
+            cx.capture_window_session()
+        });
+        assert_eq!(restored, session);
+    }
+}
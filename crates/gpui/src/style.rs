@@ -260,6 +260,16 @@ pub struct Style {
     /// The opacity of this element
     pub opacity: Option<f32>,
 
+    /// The order in which this element is painted among its siblings, see [`Styled::z_index`].
+    pub z_index: Option<i32>,
+
+    /// Whether this element keeps the z-indices of its descendants from affecting its own order
+    /// among its siblings, see [`Styled::new_stacking_context`].
+    pub stacking_context: bool,
+
+    /// Whether children are aligned by their first text baselines, see [`Styled::align_baseline`].
+    pub align_baselines: bool,
+
     /// The grid columns of this element
     /// Equivalent to the Tailwind `grid-cols-<number>`
     pub grid_cols: Option<u16>,
@@ -332,6 +342,12 @@ pub enum TextOverflow {
     /// Truncate the text when it doesn't fit, and represent this truncation by displaying the
     /// provided string.
     Truncate(SharedString),
+    /// Truncate the text in the middle when it doesn't fit, keeping its start and end, and
+    /// represent this truncation by displaying the provided string. Useful for file paths.
+    TruncateMiddle(SharedString),
+    /// Cut the text off when it doesn't fit, fading out its last characters rather than
+    /// displaying an ellipsis.
+    Fade,
 }
 
 /// How to align text within the element
@@ -769,6 +785,9 @@ impl Default for Style {
             text: TextStyleRefinement::default(),
             mouse_cursor: None,
             opacity: None,
+            z_index: None,
+            stacking_context: false,
+            align_baselines: false,
             grid_rows: None,
             grid_cols: None,
             grid_location: None,
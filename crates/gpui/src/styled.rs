@@ -124,6 +124,21 @@ pub trait Styled: Sized {
         self.overflow_hidden().whitespace_nowrap().text_ellipsis()
     }
 
+    /// Sets the truncate to prevent text from wrapping and truncate overflowing text in the middle
+    /// with an ellipsis (…) if needed, keeping its start and end.
+    fn truncate_middle(self) -> Self {
+        self.overflow_hidden()
+            .whitespace_nowrap()
+            .text_overflow(TextOverflow::TruncateMiddle(ELLIPSIS))
+    }
+
+    /// Sets the truncate to prevent text from wrapping and fade out overflowing text if needed.
+    fn truncate_fade(self) -> Self {
+        self.overflow_hidden()
+            .whitespace_nowrap()
+            .text_overflow(TextOverflow::Fade)
+    }
+
     /// Sets number of lines to show before truncating the text.
     /// [Docs](https://tailwindcss.com/docs/line-clamp)
     fn line_clamp(mut self, lines: usize) -> Self {
@@ -272,6 +287,18 @@ pub trait Styled: Sized {
         self
     }
 
+    /// Aligns the children of this flex row by their first text baselines, so that text in
+    /// different sizes and the icons next to it line up. Unlike [`Styled::items_baseline`], this
+    /// uses the baselines of text elements rather than the bottom of each text box.
+    ///
+    /// Children are laid out against the start of the cross axis and then shifted down onto the
+    /// shared baseline, which doesn't grow the row, so leave room if the baselines differ a lot.
+    fn align_baseline(mut self) -> Self {
+        self.style().align_items = Some(AlignItems::FlexStart);
+        self.style().align_baselines = Some(true);
+        self
+    }
+
     /// Sets the element to justify flex items against the start of the container's main axis.
     /// [Docs](https://tailwindcss.com/docs/justify-content#start)
     fn justify_start(mut self) -> Self {
@@ -650,6 +677,38 @@ pub trait Styled: Sized {
         self
     }
 
+    /// Sets the value of an OpenType feature of the font of this element and its children,
+    /// e.g. `"ss01"` to enable a stylistic set. Features set on an element replace the ones it
+    /// would otherwise inherit, so set all the features a subtree needs on the same element.
+    fn font_feature(mut self, tag: &str, value: u32) -> Self {
+        let text_style = self.text_style().get_or_insert_with(Default::default);
+        let features = text_style.font_features.take().unwrap_or_default();
+        text_style.font_features = Some(features.with(tag, value));
+        self
+    }
+
+    /// Uses digits of equal width (`tnum`), so that changing numbers in meters, timecodes and
+    /// table columns don't move the text around them.
+    fn tabular_nums(self) -> Self {
+        self.font_feature("tnum", 1)
+    }
+
+    /// Uses a slashed zero (`zero`) to tell it apart from the letter O.
+    fn slashed_zero(self) -> Self {
+        self.font_feature("zero", 1)
+    }
+
+    /// Draws lowercase letters as small capitals (`smcp`).
+    fn small_caps(self) -> Self {
+        self.font_feature("smcp", 1)
+    }
+
+    /// Enables or disables standard and contextual ligatures (`liga` and `calt`).
+    fn ligatures(self, enabled: bool) -> Self {
+        let value = enabled as u32;
+        self.font_feature("liga", value).font_feature("calt", value)
+    }
+
     /// Sets the line height of this element and its children.
     fn line_height(mut self, line_height: impl Into<DefiniteLength>) -> Self {
         self.text_style()
@@ -664,6 +723,28 @@ pub trait Styled: Sized {
         self
     }
 
+    /// Sets the z-index of this element, which orders it among the other children of its parent
+    /// div. Children are painted and hit-tested in ascending z-index, keeping their order when
+    /// the z-index is equal. The default is 0, so a negative z-index paints beneath siblings.
+    ///
+    /// Setting a z-index also starts a new stacking context, see [`Styled::new_stacking_context`].
+    /// Elements drawn with [`deferred`](crate::deferred), such as popovers, are painted after the
+    /// rest of the window regardless of z-index; use [`Deferred::with_priority`](crate::Deferred::with_priority)
+    /// to order those among each other.
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.style().z_index = Some(z_index);
+        self
+    }
+
+    /// Start a new stacking context on this element. An element without one is ordered among its
+    /// siblings by the highest z-index of its descendants, so raising a descendant raises the
+    /// branch that contains it. A stacking context contains the z-indices of its descendants,
+    /// which then only order them among each other.
+    fn new_stacking_context(mut self) -> Self {
+        self.style().stacking_context = Some(true);
+        self
+    }
+
     /// Sets the grid columns of this element.
     fn grid_cols(mut self, cols: u16) -> Self {
         self.style().grid_cols = Some(cols);
@@ -774,3 +855,107 @@ pub trait Styled: Sized {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        App, Context, InteractiveElement, IntoElement, Modifiers, MouseButton, MouseDownEvent,
+        ParentElement, Render, Styled, TestAppContext, Window, div, point, px,
+    };
+
+    #[crate::test]
+    fn test_z_index(cx: &mut TestAppContext) {
+        struct StackView {
+            isolate: bool,
+            hits: Rc<RefCell<Vec<&'static str>>>,
+        }
+
+        impl Render for StackView {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                let record = |name| {
+                    let hits = self.hits.clone();
+                    move |_: &MouseDownEvent, _: &mut Window, _: &mut App| {
+                        hits.borrow_mut().push(name)
+                    }
+                };
+
+                let mut branch = div().absolute().size(px(50.)).child(
+                    div()
+                        .size_full()
+                        .z_index(1)
+                        .occlude()
+                        .on_mouse_down(MouseButton::Left, record("raised")),
+                );
+                if self.isolate {
+                    branch = branch.new_stacking_context();
+                }
+
+                div().size(px(100.)).child(branch).child(
+                    div()
+                        .absolute()
+                        .size(px(50.))
+                        .occlude()
+                        .on_mouse_down(MouseButton::Left, record("covering")),
+                )
+            }
+        }
+
+        let hits = Rc::new(RefCell::new(Vec::new()));
+        let (view, cx) = cx.add_window_view(|_, _| StackView {
+            isolate: false,
+            hits: hits.clone(),
+        });
+        cx.run_until_parked();
+
+        // The raised descendant lifts its whole branch above the later sibling.
+        cx.simulate_mouse_down(
+            point(px(10.), px(10.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(hits.take(), ["raised"]);
+
+        // A stacking context keeps the descendant's z-index from escaping the branch.
+        view.update(cx, |view, cx| {
+            view.isolate = true;
+            cx.notify();
+        });
+        cx.run_until_parked();
+        cx.simulate_mouse_down(
+            point(px(10.), px(10.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(hits.take(), ["covering"]);
+    }
+
+    #[crate::test]
+    fn test_align_baseline(cx: &mut TestAppContext) {
+        struct RowView;
+
+        impl Render for RowView {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                div()
+                    .flex()
+                    .align_baseline()
+                    .child(
+                        div()
+                            .pt(px(10.))
+                            .child(div().size(px(10.)))
+                            .debug_selector(|| "padded".into()),
+                    )
+                    .child(div().size(px(5.)).debug_selector(|| "leaf".into()))
+            }
+        }
+
+        let (_, cx) = cx.add_window_view(|_, _| RowView);
+        cx.run_until_parked();
+
+        // The padded child's baseline is the bottom of its own first child, 20px down.
+        let padded = cx.debug_bounds("padded").unwrap();
+        let leaf = cx.debug_bounds("leaf").unwrap();
+        assert_eq!(leaf.bottom() - padded.top(), px(20.));
+    }
+}
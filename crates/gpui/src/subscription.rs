@@ -1,8 +1,10 @@
+use crate::EntityId;
 use collections::{BTreeMap, BTreeSet};
 use std::{
     cell::{Cell, RefCell},
     fmt::Debug,
     mem,
+    panic::Location,
     rc::Rc,
 };
 use util::post_inc;
@@ -26,6 +28,9 @@ struct SubscriberSetState<EmitterKey, Callback> {
 struct Subscriber<Callback> {
     active: Rc<Cell<bool>>,
     callback: Callback,
+    subscriber: Option<EntityId>,
+    location: &'static Location<'static>,
+    invocations: usize,
 }
 
 impl<EmitterKey, Callback> SubscriberSet<EmitterKey, Callback>
@@ -45,11 +50,26 @@ where
     /// are inert, meaning that they won't be listed when calling `[SubscriberSet::remove]` or `[SubscriberSet::retain]`.
     /// This method returns a tuple of a [`Subscription`] and an `impl FnOnce`, and you can use the latter
     /// to activate the [`Subscription`].
+    #[track_caller]
     pub fn insert(
         &self,
         emitter_key: EmitterKey,
         callback: Callback,
     ) -> (Subscription, impl FnOnce() + use<EmitterKey, Callback>) {
+        self.insert_for(emitter_key, None, callback)
+    }
+
+    /// Like [`SubscriberSet::insert`], but records the entity on whose behalf the subscription
+    /// is made, so that [`App::audit_subscriptions`](crate::App::audit_subscriptions) can
+    /// report subscriptions that outlive it.
+    #[track_caller]
+    pub fn insert_for(
+        &self,
+        emitter_key: EmitterKey,
+        subscriber: Option<EntityId>,
+        callback: Callback,
+    ) -> (Subscription, impl FnOnce() + use<EmitterKey, Callback>) {
+        let location = Location::caller();
         let active = Rc::new(Cell::new(false));
         let mut lock = self.0.borrow_mut();
         let subscriber_id = post_inc(&mut lock.next_subscriber_id);
@@ -62,6 +82,9 @@ where
                 Subscriber {
                     active: active.clone(),
                     callback,
+                    subscriber,
+                    location,
+                    invocations: 0,
                 },
             );
         let this = self.0.clone();
@@ -113,8 +136,18 @@ where
 
     /// Call the given callback for each subscriber to the given emitter.
     /// If the callback returns false, the subscriber is removed.
-    pub fn retain<F>(&self, emitter: &EmitterKey, mut f: F)
+    pub fn retain<F>(&self, emitter: &EmitterKey, f: F)
+    where
+        F: FnMut(&mut Callback) -> bool,
+    {
+        self.retain_matching(emitter, |_| true, f)
+    }
+
+    /// Like [`SubscriberSet::retain`], but only calls the given callback for subscribers that
+    /// satisfy the predicate. The others are kept, and aren't counted as invoked.
+    pub fn retain_matching<P, F>(&self, emitter: &EmitterKey, predicate: P, mut f: F)
     where
+        P: Fn(&Callback) -> bool,
         F: FnMut(&mut Callback) -> bool,
     {
         let Some(mut subscribers) = self
@@ -128,7 +161,8 @@ where
         };
 
         subscribers.retain(|_, subscriber| {
-            if subscriber.active.get() {
+            if subscriber.active.get() && predicate(&subscriber.callback) {
+                subscriber.invocations += 1;
                 f(&mut subscriber.callback)
             } else {
                 true
@@ -153,6 +187,69 @@ where
     }
 }
 
+impl<Callback> SubscriberSet<EntityId, Callback>
+where
+    Callback: 'static,
+{
+    /// Describe the active subscriptions in this set, excluding any whose callbacks are
+    /// currently being invoked.
+    pub fn audit(&self, kind: SubscriptionKind) -> Vec<SubscriptionInfo> {
+        let state = self.0.borrow();
+        state
+            .subscribers
+            .iter()
+            .filter_map(|(emitter, subscribers)| Some((*emitter, subscribers.as_ref()?)))
+            .flat_map(|(emitter, subscribers)| {
+                subscribers
+                    .values()
+                    .filter(|subscriber| subscriber.active.get())
+                    .map(move |subscriber| SubscriptionInfo {
+                        kind,
+                        emitter,
+                        subscriber: subscriber.subscriber,
+                        location: subscriber.location,
+                        invocations: subscriber.invocations,
+                    })
+            })
+            .collect()
+    }
+}
+
+/// The kind of a subscription reported by [`App::audit_subscriptions`](crate::App::audit_subscriptions).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubscriptionKind {
+    /// A subscription to an entity's notifications, such as one made with `observe`.
+    Observe,
+    /// A subscription to an entity's events, such as one made with `subscribe`.
+    Subscribe,
+}
+
+/// A subscription to an entity, as reported by [`App::audit_subscriptions`](crate::App::audit_subscriptions).
+#[derive(Clone, Debug)]
+pub struct SubscriptionInfo {
+    /// Whether this subscription observes notifications or events.
+    pub kind: SubscriptionKind,
+    /// The entity being observed.
+    pub emitter: EntityId,
+    /// The entity that made the subscription, if it was made through that entity's context.
+    pub subscriber: Option<EntityId>,
+    /// Where the subscription was made.
+    pub location: &'static Location<'static>,
+    /// How many times the subscription's callback has been invoked.
+    pub invocations: usize,
+}
+
+/// The result of [`App::audit_subscriptions`](crate::App::audit_subscriptions).
+#[derive(Clone, Debug, Default)]
+pub struct SubscriptionAudit {
+    /// Subscriptions made by an entity that has since been released. Their callbacks will do
+    /// nothing, but they stay registered until the entity they observe notifies or emits
+    /// again, or is released itself. These usually come from detached subscriptions.
+    pub dangling: Vec<SubscriptionInfo>,
+    /// Subscriptions whose callbacks have never been invoked.
+    pub never_fired: Vec<SubscriptionInfo>,
+}
+
 /// A handle to a subscription created by GPUI. When dropped, the subscription
 /// is cancelled and the callback will no longer be invoked.
 #[must_use]
@@ -207,3 +304,87 @@ impl std::fmt::Debug for Subscription {
         f.debug_struct("Subscription").finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{AppContext as _, Context, EventEmitter, SubscriptionKind, TestAppContext};
+
+    #[crate::test]
+    fn test_audit_subscriptions_ignores_other_event_types(cx: &mut TestAppContext) {
+        struct Model;
+        struct EventA;
+        struct EventB;
+        impl EventEmitter<EventA> for Model {}
+        impl EventEmitter<EventB> for Model {}
+
+        let model = cx.new(|_| Model);
+        let _subscription = cx.update(|cx| cx.subscribe(&model, |_, _: &EventA, _| {}));
+        let never_fired = |cx: &mut TestAppContext| {
+            cx.update(|cx| cx.audit_subscriptions())
+                .never_fired
+                .into_iter()
+                .filter(|subscription| subscription.emitter == model.entity_id())
+                .count()
+        };
+
+        model.update(cx, |_, cx| cx.emit(EventB));
+        assert_eq!(never_fired(cx), 1);
+
+        model.update(cx, |_, cx| cx.emit(EventA));
+        assert_eq!(never_fired(cx), 0);
+    }
+
+    #[crate::test]
+    fn test_audit_subscriptions(cx: &mut TestAppContext) {
+        struct Model;
+        impl EventEmitter<()> for Model {}
+
+        let model = cx.new(|_| Model);
+        let observer = cx.new(|cx: &mut Context<Model>| {
+            cx.observe(&model, |_, _, _| {}).detach();
+            cx.subscribe(&model, |_, _, _: &(), _| {}).detach();
+            Model
+        });
+        let audit = |cx: &mut TestAppContext| {
+            let mut audit = cx.update(|cx| cx.audit_subscriptions());
+            audit
+                .dangling
+                .retain(|subscription| subscription.emitter == model.entity_id());
+            audit
+                .never_fired
+                .retain(|subscription| subscription.emitter == model.entity_id());
+            audit
+        };
+
+        let subscriptions = audit(cx);
+        assert!(subscriptions.dangling.is_empty());
+        assert_eq!(subscriptions.never_fired.len(), 2);
+        assert!(
+            subscriptions
+                .never_fired
+                .iter()
+                .all(|subscription| subscription.location.file().ends_with("subscription.rs"))
+        );
+
+        model.update(cx, |_, cx| cx.notify());
+        let subscriptions = audit(cx);
+        assert_eq!(subscriptions.never_fired.len(), 1);
+        assert_eq!(
+            subscriptions.never_fired[0].kind,
+            SubscriptionKind::Subscribe
+        );
+
+        // The detached subscriptions linger until the model notifies or emits again.
+        cx.update(|_| drop(observer));
+        let subscriptions = audit(cx);
+        assert_eq!(subscriptions.dangling.len(), 2);
+
+        model.update(cx, |_, cx| {
+            cx.notify();
+            cx.emit(());
+        });
+        let subscriptions = audit(cx);
+        assert!(subscriptions.dangling.is_empty());
+        assert!(subscriptions.never_fired.is_empty());
+    }
+}
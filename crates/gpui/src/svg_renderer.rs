@@ -118,7 +118,7 @@ impl SvgRenderer {
 
         if let Some(bytes) = bytes {
             render_pixmap(bytes)
-        } else if let Some(bytes) = self.asset_source.load(&params.path)? {
+        } else if let Some(bytes) = self.asset_source.load_bytes(&params.path)? {
             render_pixmap(&bytes)
         } else {
             Ok(None)
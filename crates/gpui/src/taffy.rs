@@ -29,6 +29,7 @@ struct NodeContext {
 pub struct TaffyLayoutEngine {
     taffy: TaffyTree<NodeContext>,
     absolute_layout_bounds: FxHashMap<LayoutId, Bounds<Pixels>>,
+    baselines: FxHashMap<LayoutId, Pixels>,
     computed_layouts: FxHashSet<LayoutId>,
     layout_bounds_scratch_space: Vec<LayoutId>,
 }
@@ -42,6 +43,7 @@ impl TaffyLayoutEngine {
         TaffyLayoutEngine {
             taffy,
             absolute_layout_bounds: FxHashMap::default(),
+            baselines: FxHashMap::default(),
             computed_layouts: FxHashSet::default(),
             layout_bounds_scratch_space: Vec::new(),
         }
@@ -50,6 +52,7 @@ impl TaffyLayoutEngine {
     pub fn clear(&mut self) {
         self.taffy.clear();
         self.absolute_layout_bounds.clear();
+        self.baselines.clear();
         self.computed_layouts.clear();
     }
 
@@ -102,6 +105,26 @@ impl TaffyLayoutEngine {
             .into()
     }
 
+    pub fn set_baseline(&mut self, id: LayoutId, baseline: Pixels) {
+        self.baselines.insert(id, baseline);
+    }
+
+    /// The distance from the top of the node to its first baseline: the one it was given, or else
+    /// its first child's, or else its bottom edge.
+    pub fn baseline(&self, id: LayoutId, scale_factor: f32) -> Pixels {
+        if let Some(baseline) = self.baselines.get(&id) {
+            return *baseline;
+        }
+
+        if let Some(child) = self.taffy.children(id.0).expect(EXPECT_MESSAGE).first() {
+            let child_top = self.taffy.layout(*child).expect(EXPECT_MESSAGE).location.y;
+            Pixels(child_top / scale_factor) + self.baseline(LayoutId(*child), scale_factor)
+        } else {
+            let height = self.taffy.layout(id.0).expect(EXPECT_MESSAGE).size.height;
+            Pixels(height / scale_factor)
+        }
+    }
+
     // Used to understand performance
     #[allow(dead_code)]
     fn count_all_children(&self, parent: LayoutId) -> anyhow::Result<u32> {
@@ -260,7 +283,7 @@ impl TaffyLayoutEngine {
 }
 
 /// A unique identifier for a layout node, generated when requesting a layout from Taffy
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 #[repr(transparent)]
 pub struct LayoutId(NodeId);
 
@@ -25,14 +25,20 @@
 //!   assert!(true)
 //! }
 //! ```
-use crate::{Entity, Subscription, TestAppContext, TestDispatcher};
+mod fuzz;
+
+pub use fuzz::*;
+
+use crate::{
+    BackgroundExecutor, Entity, ForegroundExecutor, Subscription, TestAppContext, TestDispatcher,
+};
 use futures::StreamExt as _;
-use rand::prelude::*;
 use smol::channel;
 use std::{
     env,
     panic::{self, RefUnwindSafe},
     pin::Pin,
+    sync::Arc,
 };
 
 /// Run the given test function with the configured parameters.
@@ -54,7 +60,7 @@ pub fn run_test(
                 eprintln!("seed = {seed}");
             }
             let result = panic::catch_unwind(|| {
-                let dispatcher = TestDispatcher::new(StdRng::seed_from_u64(seed));
+                let dispatcher = TestDispatcher::with_seed(seed);
                 test_fn(dispatcher, seed);
             });
 
@@ -82,6 +88,25 @@ pub fn run_test(
     }
 }
 
+/// Run the given async function to completion under the deterministic scheduler used by the
+/// `gpui::test` macro, for test harnesses that don't use the macro.
+///
+/// Tasks spawned on the given executors run one at a time, in an order determined by the seed,
+/// and timers only fire when the simulated clock is advanced or nothing else is left to run.
+/// Running the same function with the same seed runs its tasks in the same order.
+pub fn run_deterministic<R>(
+    seed: u64,
+    f: impl AsyncFnOnce(BackgroundExecutor, ForegroundExecutor) -> R,
+) -> R {
+    let dispatcher = TestDispatcher::with_seed(seed);
+    let background_executor = BackgroundExecutor::new(Arc::new(dispatcher.clone()));
+    let foreground_executor = ForegroundExecutor::new(Arc::new(dispatcher.clone()));
+    let result =
+        background_executor.block_test(f(background_executor.clone(), foreground_executor));
+    dispatcher.run_until_parked();
+    result
+}
+
 fn calculate_seeds(
     iterations: u64,
     explicit_seeds: &[u64],
@@ -159,3 +184,45 @@ pub fn observe<T: 'static>(entity: &Entity<T>, cx: &mut TestAppContext) -> Obser
 
     Observation { rx, _subscription }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use parking_lot::Mutex;
+
+    use super::*;
+
+    fn run_interleaved(seed: u64) -> (Vec<usize>, usize) {
+        run_deterministic(seed, async |background, foreground| {
+            assert_eq!(background.seed(), Some(seed));
+            background.record_task_order(true);
+
+            let order = Arc::new(Mutex::new(Vec::new()));
+            let tasks = (0..10)
+                .map(|ix| {
+                    let order = order.clone();
+                    background.spawn(async move { order.lock().push(ix) })
+                })
+                .collect::<Vec<_>>();
+            let timer = background.timer(Duration::from_secs(1));
+            assert_eq!(background.pending_tasks().background, 10);
+            assert_eq!(background.pending_tasks().delayed, 1);
+
+            for task in tasks {
+                task.await;
+            }
+            foreground.spawn(timer).await;
+
+            let order = order.lock().clone();
+            (order, background.take_task_order().len())
+        })
+    }
+
+    #[test]
+    fn test_run_deterministic() {
+        assert_eq!(run_interleaved(7), run_interleaved(7));
+        let orders = (0..10).map(|seed| run_interleaved(seed).0);
+        assert!(orders.collect::<collections::HashSet<_>>().len() > 1);
+    }
+}
@@ -0,0 +1,352 @@
+use rand::{Rng, SeedableRng as _, rngs::StdRng};
+
+use crate::{
+    AnyElement, App, Bounds, Capslock, ClickEvent, Context, Div, FocusHandle, InteractiveElement,
+    IntoElement, KeyDownEvent, KeyUpEvent, Keystroke, Length, Modifiers, ModifiersChangedEvent,
+    MouseButton, MouseDownEvent, MouseExitEvent, MouseMoveEvent, MouseUpEvent, ParentElement,
+    Pixels, PlatformInput, Point, Render, ScrollDelta, ScrollWheelEvent,
+    StatefulInteractiveElement, Styled, TouchPhase, VisualContext as _, VisualTestContext, Window,
+    div, point, px, relative, size,
+};
+
+const RANDOM_KEYSTROKES: &[&str] = &[
+    "a",
+    "shift-a",
+    "space",
+    "enter",
+    "escape",
+    "tab",
+    "shift-tab",
+    "backspace",
+    "up",
+    "down",
+    "left",
+    "right",
+    "ctrl-c",
+    "cmd-a",
+    "alt-f4",
+];
+
+const MOUSE_BUTTONS: &[MouseButton] = &[MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+/// Generate a random input event for a window with the given bounds, in any order a host
+/// might deliver it: mouse buttons can be released without being pressed, keys can be released
+/// before they're pressed, and the mouse can be outside of the window.
+pub fn random_input_event(rng: &mut impl Rng, bounds: Bounds<Pixels>) -> PlatformInput {
+    let position = random_position(rng, bounds);
+    let modifiers = random_modifiers(rng);
+    let button = MOUSE_BUTTONS[rng.random_range(0..MOUSE_BUTTONS.len())];
+    match rng.random_range(0..100) {
+        0..30 => PlatformInput::MouseMove(MouseMoveEvent {
+            position,
+            pressed_button: rng.random_bool(0.3).then_some(button),
+            modifiers,
+        }),
+        30..45 => PlatformInput::MouseDown(MouseDownEvent {
+            button,
+            position,
+            modifiers,
+            click_count: rng.random_range(1..=3),
+            first_mouse: rng.random_bool(0.1),
+        }),
+        45..60 => PlatformInput::MouseUp(MouseUpEvent {
+            button,
+            position,
+            modifiers,
+            click_count: rng.random_range(1..=3),
+        }),
+        60..70 => PlatformInput::ScrollWheel(ScrollWheelEvent {
+            position,
+            delta: if rng.random() {
+                ScrollDelta::Pixels(point(
+                    px(rng.random_range(-200.0..200.0)),
+                    px(rng.random_range(-200.0..200.0)),
+                ))
+            } else {
+                ScrollDelta::Lines(point(
+                    rng.random_range(-5.0..5.0),
+                    rng.random_range(-5.0..5.0),
+                ))
+            },
+            modifiers,
+            touch_phase: match rng.random_range(0..3) {
+                0 => TouchPhase::Started,
+                1 => TouchPhase::Moved,
+                _ => TouchPhase::Ended,
+            },
+        }),
+        70..85 => PlatformInput::KeyDown(KeyDownEvent {
+            keystroke: random_keystroke(rng),
+            is_held: rng.random_bool(0.1),
+            prefer_character_input: false,
+        }),
+        85..92 => PlatformInput::KeyUp(KeyUpEvent {
+            keystroke: random_keystroke(rng),
+        }),
+        92..97 => PlatformInput::ModifiersChanged(ModifiersChangedEvent {
+            modifiers,
+            capslock: Capslock { on: rng.random() },
+        }),
+        _ => PlatformInput::MouseExited(MouseExitEvent {
+            position,
+            pressed_button: None,
+            modifiers,
+        }),
+    }
+}
+
+fn random_position(rng: &mut impl Rng, bounds: Bounds<Pixels>) -> Point<Pixels> {
+    // Mostly within the window, but sometimes just outside of it.
+    let margin = px(20.);
+    point(
+        px(rng.random_range(
+            (bounds.left() - margin).0..=(bounds.right() + margin).0.max(bounds.left().0),
+        )),
+        px(rng.random_range(
+            (bounds.top() - margin).0..=(bounds.bottom() + margin).0.max(bounds.top().0),
+        )),
+    )
+}
+
+fn random_modifiers(rng: &mut impl Rng) -> Modifiers {
+    Modifiers {
+        control: rng.random_bool(0.1),
+        alt: rng.random_bool(0.1),
+        shift: rng.random_bool(0.2),
+        platform: rng.random_bool(0.1),
+        function: rng.random_bool(0.05),
+    }
+}
+
+fn random_keystroke(rng: &mut impl Rng) -> Keystroke {
+    Keystroke::parse(RANDOM_KEYSTROKES[rng.random_range(0..RANDOM_KEYSTROKES.len())]).unwrap()
+}
+
+/// A view that renders a random tree of elements with random layout styles, some of which
+/// are focusable, scrollable or clickable. Clicking an element or pressing a key in a focused
+/// element changes the tree and moves focus, so that a sequence of random input events
+/// exercises layout, dispatch and focus changes together.
+pub struct RandomElementTree {
+    seed: u64,
+    max_depth: usize,
+    focus_handles: Vec<FocusHandle>,
+}
+
+impl RandomElementTree {
+    /// Create a tree generated from the given seed, nested at most `max_depth` levels deep.
+    pub fn new(seed: u64, max_depth: usize, cx: &mut Context<Self>) -> Self {
+        Self {
+            seed,
+            max_depth,
+            focus_handles: (0..4).map(|_| cx.focus_handle()).collect(),
+        }
+    }
+
+    fn random_element(
+        &self,
+        rng: &mut StdRng,
+        depth: usize,
+        next_id: &mut usize,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let mut element = random_style(div(), rng);
+        if depth < self.max_depth {
+            for _ in 0..rng.random_range(0..4) {
+                element = element.child(self.random_element(rng, depth + 1, next_id, cx));
+            }
+        }
+        if rng.random_bool(0.2) {
+            element = element.child("text");
+        }
+        if rng.random_bool(0.3) {
+            let focus_handle = &self.focus_handles[rng.random_range(0..self.focus_handles.len())];
+            element = element.track_focus(focus_handle).on_key_down(cx.listener(
+                |this, _: &KeyDownEvent, window, cx| {
+                    this.seed = this.seed.wrapping_add(1);
+                    let ix = this.seed as usize % this.focus_handles.len();
+                    window.focus(&this.focus_handles[ix]);
+                    cx.notify();
+                },
+            ));
+        }
+        if rng.random_bool(0.5) {
+            return element.into_any_element();
+        }
+
+        *next_id += 1;
+        let mut element =
+            element
+                .id(*next_id)
+                .on_click(cx.listener(|this, _: &ClickEvent, _, cx| {
+                    this.seed = this.seed.wrapping_add(1);
+                    cx.notify();
+                }));
+        if rng.random_bool(0.3) {
+            element = element.overflow_scroll();
+        }
+        element.into_any_element()
+    }
+}
+
+impl Render for RandomElementTree {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        div()
+            .size_full()
+            .child(self.random_element(&mut rng, 0, &mut 0, cx))
+    }
+}
+
+fn random_style(element: Div, rng: &mut impl Rng) -> Div {
+    let mut element = element.w(random_length(rng)).h(random_length(rng));
+    if rng.random() {
+        element = element.flex();
+        element = match rng.random_range(0..4) {
+            0 => element.flex_row(),
+            1 => element.flex_col(),
+            2 => element.flex_row_reverse(),
+            _ => element.flex_col_reverse(),
+        };
+        if rng.random_bool(0.3) {
+            element = element.flex_wrap();
+        }
+        element = element.gap(px(rng.random_range(0.0..20.0)));
+    }
+    if rng.random_bool(0.3) {
+        element = element.flex_grow();
+    }
+    if rng.random_bool(0.2) {
+        element = element.flex_shrink_0();
+    }
+    if rng.random_bool(0.3) {
+        element = element.p(px(rng.random_range(0.0..30.0)));
+    }
+    if rng.random_bool(0.2) {
+        // Negative margins are allowed and let elements overlap.
+        element = element.m(px(rng.random_range(-30.0..30.0)));
+    }
+    if rng.random_bool(0.2) {
+        element = element.min_w(px(rng.random_range(0.0..200.0)));
+    }
+    if rng.random_bool(0.2) {
+        element = element.max_h(px(rng.random_range(0.0..200.0)));
+    }
+    if rng.random_bool(0.15) {
+        element = element
+            .absolute()
+            .top(px(rng.random_range(-50.0..500.0)))
+            .left(px(rng.random_range(-50.0..500.0)));
+    }
+    if rng.random_bool(0.2) {
+        element = element.overflow_hidden();
+    }
+    element
+}
+
+fn random_length(rng: &mut impl Rng) -> Length {
+    match rng.random_range(0..4) {
+        0 => px(rng.random_range(0.0..600.0)).into(),
+        1 => relative(rng.random_range(0.0..1.5)).into(),
+        2 => px(0.).into(),
+        _ => Length::Auto,
+    }
+}
+
+impl VisualTestContext {
+    /// Simulate a random sequence of input events and window resizes, such as a host
+    /// application might deliver to an embedded window, checking the window's invariants
+    /// after each one with [`VisualTestContext::assert_window_invariants`].
+    pub fn simulate_random_events(&mut self, rng: &mut impl Rng, count: usize) {
+        for _ in 0..count {
+            if rng.random_bool(0.05) {
+                self.simulate_resize(size(
+                    px(rng.random_range(0.0..1200.0)),
+                    px(rng.random_range(0.0..900.0)),
+                ));
+            } else {
+                let bounds = Bounds {
+                    origin: Point::default(),
+                    size: self.update(|window, _| window.viewport_size()),
+                };
+                let event = random_input_event(rng, bounds);
+                self.test_window(self.window_handle()).simulate_input(event);
+            }
+            self.run_until_parked();
+            self.assert_window_invariants();
+        }
+    }
+
+    /// Assert that the last frame the window drew is well formed: every element has finite,
+    /// non-negative bounds, the focused element is the one the window considers focused, and
+    /// every stack pushed while drawing has been popped again.
+    pub fn assert_window_invariants(&mut self) {
+        self.update(|window, _: &mut App| {
+            assert!(window.element_id_stack.is_empty(), "unbalanced element ids");
+            assert!(window.text_style_stack.is_empty(), "unbalanced text styles");
+            assert!(
+                window.content_mask_stack.is_empty(),
+                "unbalanced content masks"
+            );
+            assert!(window.element_offset_stack.is_empty(), "unbalanced offsets");
+            assert!(
+                window.image_cache_stack.is_empty(),
+                "unbalanced image caches"
+            );
+            assert!(window.rendered_entity_stack.is_empty(), "unbalanced views");
+
+            for hitbox in &window.rendered_frame.hitboxes {
+                let Bounds { origin, size } = hitbox.bounds;
+                assert!(
+                    [origin.x, origin.y, size.width, size.height]
+                        .iter()
+                        .all(|value| value.0.is_finite()),
+                    "hitbox has non-finite bounds: {:?}",
+                    hitbox.bounds
+                );
+                assert!(
+                    size.width.0 >= 0. && size.height.0 >= 0.,
+                    "hitbox has negative size: {:?}",
+                    hitbox.bounds
+                );
+            }
+            for quad in &window.rendered_frame.scene.quads {
+                let Bounds { origin, size } = quad.bounds;
+                assert!(
+                    [origin.x, origin.y, size.width, size.height]
+                        .iter()
+                        .all(|value| value.0.is_finite()),
+                    "quad has non-finite bounds: {:?}",
+                    quad.bounds
+                );
+            }
+
+            if let Some(focus) = window.rendered_frame.focus {
+                assert_eq!(
+                    Some(focus),
+                    window.focus,
+                    "frame was drawn with a stale focus"
+                );
+                assert_eq!(
+                    window.rendered_frame.focus_path().last(),
+                    Some(&focus),
+                    "focused element is missing from the dispatch tree"
+                );
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TestAppContext;
+
+    use super::*;
+
+    #[crate::test(iterations = 20)]
+    fn test_random_input_and_layout(cx: &mut TestAppContext, mut rng: StdRng) {
+        let seed = rng.random();
+        let (_, cx) = cx.add_window_view(|_, cx| RandomElementTree::new(seed, 4, cx));
+        cx.assert_window_invariants();
+        cx.simulate_random_events(&mut rng, 100);
+    }
+}
@@ -13,8 +13,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    Bounds, DevicePixels, Hsla, Pixels, PlatformTextSystem, Point, Result, SharedString, Size,
-    StrikethroughStyle, UnderlineStyle, px,
+    AssetBytes, Bounds, DevicePixels, Hsla, Pixels, PlatformTextSystem, Point, Result,
+    SharedString, Size, StrikethroughStyle, UnderlineStyle, px,
 };
 use anyhow::{Context as _, anyhow};
 use collections::FxHashMap;
@@ -105,6 +105,13 @@ impl TextSystem {
         self.platform_text_system.add_fonts(fonts)
     }
 
+    /// Add fonts loaded with [`AssetSource::load_bytes`](crate::AssetSource::load_bytes) to the
+    /// text system. On Linux and macOS the bytes are shared rather than copied, so fonts from a
+    /// [`MappedAssetSource`](crate::MappedAssetSource) stay memory-mapped.
+    pub fn add_font_bytes(&self, fonts: Vec<AssetBytes>) -> Result<()> {
+        self.platform_text_system.add_font_bytes(fonts)
+    }
+
     /// Get the FontId for the configure font family and style.
     fn font_id(&self, font: &Font) -> Result<FontId> {
         fn clone_font_id_result(font_id: &Result<FontId>) -> Result<FontId> {
@@ -185,6 +192,18 @@ impl TextSystem {
             .platform_text_system
             .glyph_for_char(font_id, character)
             .with_context(|| format!("glyph not found for character '{character}'"))?;
+        self.glyph_typographic_bounds(font_id, font_size, glyph_id)
+    }
+
+    /// Get the typographic bounds for the given glyph, in the given font and size. Use this
+    /// for the glyphs of a shaped line, where ligatures and font fallback mean that glyphs
+    /// don't correspond to characters one to one.
+    pub fn glyph_typographic_bounds(
+        &self,
+        font_id: FontId,
+        font_size: Pixels,
+        glyph_id: GlyphId,
+    ) -> Result<Bounds<Pixels>> {
         let bounds = self
             .platform_text_system
             .typographic_bounds(font_id, glyph_id)?;
@@ -240,6 +259,11 @@ impl TextSystem {
         self.read_metrics(font_id, |metrics| metrics.units_per_em)
     }
 
+    /// Get all metrics of the given font, such as its underline position and thickness.
+    pub fn font_metrics(&self, font_id: FontId) -> FontMetrics {
+        self.read_metrics(font_id, |metrics| *metrics)
+    }
+
     /// Get the height of a capital letter in the given font and size.
     pub fn cap_height(&self, font_id: FontId, font_size: Pixels) -> Pixels {
         self.read_metrics(font_id, |metrics| metrics.cap_height(font_size))
@@ -481,7 +505,7 @@ impl WindowTextSystem {
                 wrap_width,
                 max_wrap_lines.map(|max| max.saturating_sub(wrapped_lines)),
             );
-            wrapped_lines += layout.wrap_boundaries.len();
+            wrapped_lines += layout.wrap_boundaries.len() + 1;
 
             lines.push(WrappedLine {
                 layout,
@@ -879,6 +903,11 @@ pub struct FontMetrics {
 }
 
 impl FontMetrics {
+    /// Returns the number of font units that make up the "em square".
+    pub fn units_per_em(&self) -> u32 {
+        self.units_per_em
+    }
+
     /// Returns the vertical distance from the baseline of the font to the top of the glyph covers in pixels.
     pub fn ascent(&self, font_size: Pixels) -> Pixels {
         Pixels((self.ascent / self.units_per_em as f32) * font_size.0)
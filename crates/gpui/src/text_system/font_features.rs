@@ -13,6 +13,18 @@ impl FontFeatures {
         Self(Arc::new(vec![("calt".into(), 0)]))
     }
 
+    /// Returns a copy of these features with `tag` set to `value`, replacing any previous
+    /// value for the same tag.
+    pub fn with(&self, tag: impl Into<String>, value: u32) -> Self {
+        let tag = tag.into();
+        let mut features = self.0.as_ref().clone();
+        match features.iter_mut().find(|(feature, _)| *feature == tag) {
+            Some((_, existing)) => *existing = value,
+            None => features.push((tag, value)),
+        }
+        Self(Arc::new(features))
+    }
+
     /// Get the tag name list of the font OpenType features
     /// only enabled or disabled features are returned
     pub fn tag_value_list(&self) -> &[(String, u32)] {
@@ -1,6 +1,6 @@
 use crate::{FontId, FontRun, Pixels, PlatformTextSystem, SharedString, TextRun, px};
 use collections::HashMap;
-use std::{borrow::Cow, iter, sync::Arc};
+use std::{borrow::Cow, iter, ops::Range, sync::Arc};
 
 /// The GPUI line wrapper, used to wrap lines of text to a given width.
 pub struct LineWrapper {
@@ -164,6 +164,121 @@ impl LineWrapper {
         (line, Cow::Borrowed(runs))
     }
 
+    /// Truncate a line of text to the given width with this wrapper's font and font size, by
+    /// replacing its middle with the given separator so that its start and end stay visible.
+    pub fn truncate_line_middle<'a>(
+        &mut self,
+        line: SharedString,
+        truncate_width: Pixels,
+        truncation_separator: &str,
+        runs: &'a [TextRun],
+    ) -> (SharedString, Cow<'a, [TextRun]>) {
+        if self.width_for_str(&line).floor() <= truncate_width {
+            return (line, Cow::Borrowed(runs));
+        }
+
+        let available_width = truncate_width - self.width_for_str(truncation_separator);
+        let mut prefix_width = px(0.);
+        let mut prefix_end = 0;
+        for (ix, c) in line.char_indices() {
+            let char_width = self.width_for_char(c);
+            if prefix_width + char_width > available_width * 0.5 {
+                break;
+            }
+            prefix_width += char_width;
+            prefix_end = ix + c.len_utf8();
+        }
+
+        let mut suffix_width = px(0.);
+        let mut suffix_start = line.len();
+        for (ix, c) in line[prefix_end..].char_indices().rev() {
+            let char_width = self.width_for_char(c);
+            if prefix_width + suffix_width + char_width > available_width {
+                break;
+            }
+            suffix_width += char_width;
+            suffix_start = prefix_end + ix;
+        }
+
+        let result = SharedString::from(format!(
+            "{}{}{}",
+            &line[..prefix_end],
+            truncation_separator,
+            &line[suffix_start..]
+        ));
+        let runs = runs_after_middle_truncation(
+            runs,
+            prefix_end..suffix_start,
+            truncation_separator.len(),
+        );
+        (result, Cow::Owned(runs))
+    }
+
+    /// Cut a line of text off at the given width with this wrapper's font and font size, fading
+    /// out the characters in the last `fade_width` before it. The character crossing the width is
+    /// kept, so that it's clipped rather than leaving a gap.
+    pub fn fade_line<'a>(
+        &mut self,
+        line: SharedString,
+        truncate_width: Pixels,
+        fade_width: Pixels,
+        runs: &'a [TextRun],
+    ) -> (SharedString, Cow<'a, [TextRun]>) {
+        if self.width_for_str(&line).floor() <= truncate_width {
+            return (line, Cow::Borrowed(runs));
+        }
+
+        let fade_start = truncate_width - fade_width;
+        let mut end = line.len();
+        let mut faded = Vec::new();
+        let mut x = px(0.);
+        for (ix, c) in line.char_indices() {
+            if x >= truncate_width {
+                end = ix;
+                break;
+            }
+            let char_width = self.width_for_char(c);
+            let center = x + char_width * 0.5;
+            if center > fade_start {
+                let opacity = ((truncate_width - center) / fade_width).clamp(0., 1.);
+                faded.push((ix..ix + c.len_utf8(), opacity));
+            }
+            x += char_width;
+        }
+
+        let fade_ix = faded.first().map_or(end, |(range, _)| range.start);
+        let mut faded = faded.into_iter().peekable();
+        let mut result_runs = Vec::with_capacity(runs.len() + faded.len());
+        let mut run_start = 0;
+        for run in runs {
+            if run_start >= end {
+                break;
+            }
+            let run_end = (run_start + run.len).min(end);
+            let plain_end = run_end.min(fade_ix);
+            if plain_end > run_start {
+                result_runs.push(TextRun {
+                    len: plain_end - run_start,
+                    ..run.clone()
+                });
+            }
+            while let Some((range, opacity)) = faded.next_if(|(range, _)| range.end <= run_end) {
+                result_runs.push(TextRun {
+                    len: range.len(),
+                    color: run.color.opacity(opacity),
+                    background_color: run.background_color.map(|color| color.opacity(opacity)),
+                    ..run.clone()
+                });
+            }
+            run_start += run.len;
+        }
+
+        (
+            SharedString::from(line[..end].to_string()),
+            Cow::Owned(result_runs),
+        )
+    }
+
     /// Any character in this list should be treated as a word character,
     /// meaning it can be part of a word that should not be wrapped.
     pub(crate) fn is_word_char(c: char) -> bool {
@@ -190,6 +305,12 @@ impl LineWrapper {
         matches!(c, '⋯')
     }
 
+    fn width_for_str(&mut self, text: &str) -> Pixels {
+        text.chars()
+            .map(|c| self.width_for_char(c))
+            .fold(px(0.), |a, x| a + x)
+    }
+
     #[inline(always)]
     fn width_for_char(&mut self, c: char) -> Pixels {
         if (c as u32) < 128 {
@@ -238,6 +359,30 @@ fn update_runs_after_truncation(result: &str, ellipsis: &str, runs: &mut Vec<Tex
     }
 }
 
+/// Replace the given range of the truncated text with a separator of the given length in its runs,
+/// styling the separator like the text it starts at.
+fn runs_after_middle_truncation(
+    runs: &[TextRun],
+    truncated: Range<usize>,
+    separator_len: usize,
+) -> Vec<TextRun> {
+    let mut result = Vec::with_capacity(runs.len());
+    let mut run_start = 0;
+    for run in runs {
+        let run_end = run_start + run.len;
+        let mut len = run_end.min(truncated.start).saturating_sub(run_start)
+            + run_end.saturating_sub(truncated.end.max(run_start));
+        if (run_start..run_end).contains(&truncated.start) {
+            len += separator_len;
+        }
+        if len > 0 {
+            result.push(TextRun { len, ..run.clone() });
+        }
+        run_start = run_end;
+    }
+    result
+}
+
 /// A fragment of a line that can be wrapped.
 pub enum LineFragment<'a> {
     /// A text fragment consisting of characters.
@@ -315,7 +460,9 @@ impl Boundary {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Font, FontFeatures, FontStyle, FontWeight, TestAppContext, TestDispatcher, font};
+    use crate::{
+        Font, FontFeatures, FontStyle, FontWeight, TestAppContext, TestDispatcher, black, font,
+    };
     #[cfg(target_os = "macos")]
     use crate::{TextRun, WindowTextSystem, WrapBoundary};
     use rand::prelude::*;
@@ -613,6 +760,53 @@ mod tests {
         perform_test("abcdefgh…", &[4, 4, 4], &[4, 4, 3]);
     }
 
+    #[test]
+    fn test_truncate_line_middle() {
+        let mut wrapper = build_wrapper();
+
+        let runs = generate_test_runs(&[4, 4, 4]);
+        let (result, result_runs) =
+            wrapper.truncate_line_middle("abcdefghijkl".into(), px(70.), "…", &runs);
+        assert_eq!(result, "abc…jkl");
+        assert_eq!(
+            result_runs.iter().map(|run| run.len).collect::<Vec<_>>(),
+            &[6, 3]
+        );
+
+        let (result, result_runs) =
+            wrapper.truncate_line_middle("abcdefghijkl".into(), px(120.), "…", &runs);
+        assert_eq!(result, "abcdefghijkl");
+        assert!(matches!(result_runs, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_fade_line() {
+        let mut wrapper = build_wrapper();
+
+        let runs = generate_test_runs(&[8, 4])
+            .into_iter()
+            .map(|run| TextRun {
+                color: black(),
+                ..run
+            })
+            .collect::<Vec<_>>();
+        let (result, result_runs) =
+            wrapper.fade_line("abcdefghijkl".into(), px(60.), px(20.), &runs);
+        assert_eq!(result, "abcdef");
+        assert_eq!(
+            result_runs
+                .iter()
+                .map(|run| (run.len, run.color.a))
+                .collect::<Vec<_>>(),
+            &[(4, 1.), (1, 0.75), (1, 0.25)]
+        );
+
+        let (result, result_runs) =
+            wrapper.fade_line("abcdefghijkl".into(), px(120.), px(20.), &runs);
+        assert_eq!(result, "abcdefghijkl");
+        assert!(matches!(result_runs, Cow::Borrowed(_)));
+    }
+
     #[test]
     fn test_is_word_char() {
         #[track_caller]
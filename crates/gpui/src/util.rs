@@ -1,6 +1,7 @@
 use crate::{BackgroundExecutor, Task};
 use std::{
     future::Future,
+    ops::Range,
     pin::Pin,
     sync::atomic::{AtomicUsize, Ordering::SeqCst},
     task,
@@ -142,12 +143,39 @@ pub(crate) fn atomic_incr_if_not_zero(counter: &AtomicUsize) -> usize {
     }
 }
 
+/// The contiguous ranges of slots in a ring buffer of `capacity` slots that hold the last `count`
+/// items written, where `end` is the slot the next item will be written to.
+pub(crate) fn ring_ranges(end: usize, count: usize, capacity: usize) -> Vec<Range<usize>> {
+    let count = count.min(capacity);
+    if count == 0 {
+        return Vec::new();
+    }
+    let end = end % capacity;
+    if count <= end {
+        vec![end - count..end]
+    } else if end == 0 {
+        vec![capacity - count..capacity]
+    } else {
+        vec![capacity - (count - end)..capacity, 0..end]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::TestAppContext;
 
     use super::*;
 
+    #[test]
+    fn test_ring_ranges() {
+        assert_eq!(ring_ranges(3, 2, 8), vec![1..3]);
+        assert_eq!(ring_ranges(0, 2, 8), vec![6..8]);
+        assert_eq!(ring_ranges(2, 5, 8), vec![5..8, 0..2]);
+        assert_eq!(ring_ranges(2, 20, 8), vec![2..8, 0..2]);
+        assert_eq!(ring_ranges(8, 8, 8), vec![0..8]);
+        assert!(ring_ranges(4, 0, 8).is_empty());
+    }
+
     #[gpui::test]
     async fn test_with_timeout(cx: &mut TestAppContext) {
         Task::ready(())
@@ -1,22 +1,26 @@
 #[cfg(any(feature = "inspector", debug_assertions))]
 use crate::Inspector;
 use crate::{
-    Action, AnyDrag, AnyElement, AnyImageCache, AnyTooltip, AnyView, App, AppContext, Arena, Asset,
-    AsyncWindowContext, AvailableSpace, Background, BorderStyle, Bounds, BoxShadow, Capslock,
-    Context, Corners, CursorStyle, Decorations, DevicePixels, DispatchActionListener,
-    DispatchNodeId, DispatchTree, DisplayId, Edges, Effect, Entity, EntityId, EventEmitter,
-    FileDropEvent, FontId, Global, GlobalElementId, GlyphId, GpuSpecs, Hsla, InputHandler, IsZero,
-    KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke, KeystrokeEvent, LayoutId,
-    LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite, MouseButton, MouseEvent,
-    MouseMoveEvent, MouseUpEvent, Path, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
-    PlatformInputHandler, PlatformWindow, Point, PolychromeSprite, PromptButton, PromptLevel, Quad,
-    Render, RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams, Replay, ResizeEdge,
-    SMOOTH_SVG_SCALE_FACTOR, SUBPIXEL_VARIANTS_X, SUBPIXEL_VARIANTS_Y, ScaledPixels, Scene, Shadow,
-    SharedString, Size, StrikethroughStyle, Style, SubscriberSet, Subscription, SystemWindowTab,
+    Action, ActionInterception, AnyDrag, AnyElement, AnyImageCache, AnyTooltip, AnyView, App,
+    AppContext, Arena, Asset, AsyncApp, AsyncWindowContext, AtlasKey, AvailableSpace, Axis,
+    Background, BorderStyle, Bounds, BoxShadow, Capslock, Context, Corners, CursorStyle,
+    Decorations, DevicePixels, DispatchActionListener, DispatchNodeId, DispatchTree, DisplayId,
+    Edges, Effect, Entity, EntityId, EventEmitter, FileDropEvent, FontId, FrameDriver, Global,
+    GlobalElementId, GlyphId, GpuSpecs, HostWindowInfo, Hsla, ImageId, InputHandler, IsZero,
+    KeyBinding, KeyContext, KeyDownEvent, KeyEvent, KeybindingKeystroke, Keystroke, KeystrokeEvent,
+    LayoutId, LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite, MouseButton,
+    MouseDownEvent, MouseEvent, MouseExitEvent, MouseMoveEvent, MouseUpEvent, PaintOperation, Path,
+    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
+    Point, PolychromeSprite, PortalHost, PromptButton, PromptLevel, Quad, Render,
+    RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams, Replay,
+    RequestFrameOptions, ResizeEdge, SMOOTH_SVG_SCALE_FACTOR, SUBPIXEL_VARIANTS_X,
+    SUBPIXEL_VARIANTS_Y, ScaledPixels, Scene, ScrollDelta, ScrollWheelEvent, Shadow, SharedString,
+    Size, SizeConstraints, StrikethroughStyle, Style, SubscriberSet, Subscription, SystemWindowTab,
     SystemWindowTabController, TabStopMap, TaffyLayoutEngine, Task, TextStyle, TextStyleRefinement,
-    TransformationMatrix, Underline, UnderlineStyle, WindowAppearance, WindowBackgroundAppearance,
-    WindowBounds, WindowControls, WindowDecorations, WindowOptions, WindowParams, WindowTextSystem,
-    point, prelude::*, px, rems, size, transparent_black,
+    TouchPhase, TransformationMatrix, Underline, UnderlineStyle, WindowAppearance,
+    WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations, WindowOpenError,
+    WindowOptions, WindowParams, WindowState, WindowTextSystem, action::run_action_interceptors,
+    point, prelude::*, px, rems, size, trace_embedded, transparent_black,
 };
 use anyhow::{Context as _, Result, anyhow};
 use collections::{FxHashMap, FxHashSet};
@@ -25,6 +29,7 @@ use core_video::pixel_buffer::CVPixelBuffer;
 use derive_more::{Deref, DerefMut};
 use futures::FutureExt;
 use futures::channel::oneshot;
+use futures::future::{self, LocalBoxFuture, Shared};
 use itertools::FoldWhile::{Continue, Done};
 use itertools::Itertools;
 use parking_lot::RwLock;
@@ -53,12 +58,30 @@ use util::post_inc;
 use util::{ResultExt, measure};
 use uuid::Uuid;
 
+mod embedded;
+mod emoji_picker;
+mod frame_clock;
+mod frame_stats;
+#[cfg(any(test, feature = "test-support"))]
+mod layout_tree;
+mod mirror;
 mod prompts;
 
 use crate::util::atomic_incr_if_not_zero;
+pub use embedded::*;
+pub use emoji_picker::*;
+use frame_clock::FrameClock;
+pub(crate) use frame_clock::FrameTimestampSource;
+pub use frame_clock::{AnimationClock, FrameTiming};
+pub use frame_stats::*;
+#[cfg(any(test, feature = "test-support"))]
+pub use layout_tree::*;
+pub use mirror::*;
 pub use prompts::*;
 
 pub(crate) const DEFAULT_WINDOW_SIZE: Size<Pixels> = size(px(1536.), px(864.));
+const MIN_CONTENT_ZOOM: f32 = 0.25;
+const MAX_CONTENT_ZOOM: f32 = 5.0;
 
 /// A 6:5 aspect ratio minimum window size to be used for functional,
 /// additional-to-main-Zed windows, like the settings and rules library windows.
@@ -186,6 +209,36 @@ impl WindowInvalidator {
 
 type AnyObserver = Box<dyn FnMut(&mut Window, &mut App) -> bool + 'static>;
 
+pub(crate) type AnyWindowStateObserver =
+    Box<dyn FnMut(WindowState, &mut Window, &mut App) -> bool + 'static>;
+
+fn platform_window_state(platform_window: &dyn PlatformWindow) -> WindowState {
+    if platform_window.is_minimized() {
+        WindowState::Minimized
+    } else if platform_window.is_fullscreen() {
+        WindowState::Fullscreen
+    } else if platform_window.is_maximized() {
+        WindowState::Maximized
+    } else if let Decorations::Client { tiling } = platform_window.window_decorations()
+        && tiling.is_tiled()
+    {
+        WindowState::Tiled(tiling)
+    } else {
+        WindowState::Normal
+    }
+}
+
+type HostResizeRequestCallback =
+    Box<dyn FnMut(Size<Pixels>, &mut Window, &mut App) -> Size<Pixels> + 'static>;
+
+type EmbeddedResizeCallback = Box<dyn FnMut(Size<Pixels>, &mut Window) + 'static>;
+
+type CloseRequestHandler =
+    Box<dyn FnMut(&mut Window, &mut App) -> LocalBoxFuture<'static, bool> + 'static>;
+
+type AnyActionInterceptor =
+    Box<dyn FnMut(&dyn Action, &mut Window, &mut App) -> ActionInterception + 'static>;
+
 pub(crate) type AnyWindowFocusListener =
     Box<dyn FnMut(&WindowFocusEvent, &mut Window, &mut App) -> bool + 'static>;
 
@@ -665,6 +718,7 @@ pub(crate) struct DeferredDraw {
     parent_node: DispatchNodeId,
     element_id_stack: SmallVec<[ElementId; 32]>,
     text_style_stack: Vec<TextStyleRefinement>,
+    context_value_stack: Vec<Rc<dyn Any>>,
     element: Option<AnyElement>,
     absolute_offset: Point<Pixels>,
     prepaint_range: Range<PrepaintStateIndex>,
@@ -687,6 +741,8 @@ pub(crate) struct Frame {
     pub(crate) cursor_styles: Vec<CursorStyleRequest>,
     #[cfg(any(test, feature = "test-support"))]
     pub(crate) debug_bounds: FxHashMap<String, Bounds<Pixels>>,
+    #[cfg(any(test, feature = "test-support"))]
+    pub(crate) layout_tree: layout_tree::LayoutTreeRecording,
     #[cfg(any(feature = "inspector", debug_assertions))]
     pub(crate) next_inspector_instance_ids: FxHashMap<Rc<crate::InspectorElementPath>, usize>,
     #[cfg(any(feature = "inspector", debug_assertions))]
@@ -702,6 +758,8 @@ pub(crate) struct PrepaintStateIndex {
     dispatch_tree_index: usize,
     accessed_element_states_index: usize,
     line_layout_index: LineLayoutIndex,
+    #[cfg(any(test, feature = "test-support"))]
+    layout_tree_index: usize,
 }
 
 #[derive(Clone, Default)]
@@ -735,6 +793,9 @@ impl Frame {
             #[cfg(any(test, feature = "test-support"))]
             debug_bounds: FxHashMap::default(),
 
+            #[cfg(any(test, feature = "test-support"))]
+            layout_tree: Default::default(),
+
             #[cfg(any(feature = "inspector", debug_assertions))]
             next_inspector_instance_ids: FxHashMap::default(),
 
@@ -759,6 +820,9 @@ impl Frame {
         self.tab_stops.clear();
         self.focus = None;
 
+        #[cfg(any(test, feature = "test-support"))]
+        self.layout_tree.clear();
+
         #[cfg(any(feature = "inspector", debug_assertions))]
         {
             self.next_inspector_instance_ids.clear();
@@ -828,11 +892,48 @@ enum InputModality {
     Keyboard,
 }
 
+/// A texture in a window's sprite atlas that is updated in place rather than re-uploaded,
+/// created with [`Window::create_streaming_texture`]. The texture is removed from the atlas
+/// when this handle is dropped.
+pub struct StreamingTexture {
+    key: AtlasKey,
+    size: Size<DevicePixels>,
+    atlas: Arc<dyn PlatformAtlas>,
+}
+
+impl StreamingTexture {
+    /// The size of the texture.
+    pub fn size(&self) -> Size<DevicePixels> {
+        self.size
+    }
+
+    /// Replace the pixels of `region`, in device pixels relative to the texture's origin, with
+    /// the given BGRA bytes. Returns false if the texture is no longer in the atlas, in which
+    /// case it should be recreated.
+    pub fn update(&self, region: Bounds<DevicePixels>, bytes: &[u8]) -> bool {
+        debug_assert_eq!(
+            bytes.len(),
+            region.size.width.0.max(0) as usize * region.size.height.0.max(0) as usize * 4
+        );
+        self.atlas.update(&self.key, region, bytes)
+    }
+}
+
+impl Drop for StreamingTexture {
+    fn drop(&mut self) {
+        self.atlas.remove(&self.key);
+    }
+}
+
 /// Holds the state for a specific window.
 pub struct Window {
     pub(crate) handle: AnyWindowHandle,
     pub(crate) invalidator: WindowInvalidator,
     pub(crate) removed: bool,
+    should_close_handler: RefCell<Option<Rc<dyn Fn(&mut Window, &mut App) -> bool>>>,
+    close_request_handlers: SubscriberSet<(), CloseRequestHandler>,
+    pending_close_request: Option<Shared<LocalBoxFuture<'static, bool>>>,
+    close_when_approved: bool,
     pub(crate) platform_window: Box<dyn PlatformWindow>,
     display_id: Option<DisplayId>,
     sprite_atlas: Arc<dyn PlatformAtlas>,
@@ -845,9 +946,12 @@ pub struct Window {
     rem_size_override_stack: SmallVec<[Pixels; 8]>,
     pub(crate) viewport_size: Size<Pixels>,
     layout_engine: Option<TaffyLayoutEngine>,
+    layout_z_indices: FxHashMap<LayoutId, i32>,
+    z_index_scopes: Vec<i32>,
     pub(crate) root: Option<AnyView>,
     pub(crate) element_id_stack: SmallVec<[ElementId; 32]>,
     pub(crate) text_style_stack: Vec<TextStyleRefinement>,
+    pub(crate) context_value_stack: Vec<Rc<dyn Any>>,
     pub(crate) rendered_entity_stack: Vec<EntityId>,
     pub(crate) element_offset_stack: Vec<Point<Pixels>>,
     pub(crate) element_opacity: f32,
@@ -860,23 +964,43 @@ pub struct Window {
     pub(crate) next_tooltip_id: TooltipId,
     pub(crate) tooltip_bounds: Option<TooltipBounds>,
     next_frame_callbacks: Rc<RefCell<Vec<FrameCallback>>>,
+    pub(crate) frame_driver: FrameDriver,
+    request_frame: Rc<RefCell<dyn FnMut(RequestFrameOptions)>>,
     pub(crate) dirty_views: FxHashSet<EntityId>,
+    pub(crate) notified_entities: FxHashSet<EntityId>,
     focus_listeners: SubscriberSet<(), AnyWindowFocusListener>,
     pub(crate) focus_lost_listeners: SubscriberSet<(), AnyObserver>,
+    action_interceptors: SubscriberSet<(), AnyActionInterceptor>,
     default_prevented: bool,
+    stopped_scroll_axes: SmallVec<[Axis; 2]>,
     mouse_position: Point<Pixels>,
+    pressed_button: Option<MouseButton>,
     mouse_hit_test: HitTest,
     modifiers: Modifiers,
     capslock: Capslock,
     scale_factor: f32,
+    content_zoom: f32,
     pub(crate) bounds_observers: SubscriberSet<(), AnyObserver>,
+    window_state: WindowState,
+    pub(crate) window_state_observers: SubscriberSet<(), AnyWindowStateObserver>,
+    container_sizes: FxHashMap<SharedString, Size<Pixels>>,
     appearance: WindowAppearance,
     pub(crate) appearance_observers: SubscriberSet<(), AnyObserver>,
+    pub(crate) host_info_observers: SubscriberSet<(), AnyObserver>,
+    background_appearance: Cell<WindowBackgroundAppearance>,
+    blur_suppressed: Cell<bool>,
+    frame_clock: FrameClock,
+    frame_stats_overlay: bool,
+    atlas_uploads: Cell<usize>,
+    rendered_frame_atlas_uploads: usize,
     active: Rc<Cell<bool>>,
     hovered: Rc<Cell<bool>>,
     pub(crate) needs_present: Rc<Cell<bool>>,
     pub(crate) last_input_timestamp: Rc<Cell<Instant>>,
     last_input_modality: InputModality,
+    low_latency_input: bool,
+    painted_since_frame_request: bool,
+    latched_input: Option<PlatformInput>,
     pub(crate) refreshing: bool,
     pub(crate) activation_observers: SubscriberSet<(), AnyObserver>,
     pub(crate) focus: Option<FocusId>,
@@ -885,6 +1009,12 @@ pub struct Window {
     pending_modifier: ModifierState,
     pub(crate) pending_input_observers: SubscriberSet<(), AnyObserver>,
     prompt: Option<RenderablePromptHandle>,
+    emoji_picker: Option<Entity<FallbackEmojiPicker>>,
+    pub(crate) portal_host: PortalHost,
+    embedded: bool,
+    size_constraints: SizeConstraints,
+    host_resize_request_callback: Option<HostResizeRequestCallback>,
+    embedded_resize_callback: Option<EmbeddedResizeCallback>,
     pub(crate) client_inset: Option<Pixels>,
     #[cfg(any(feature = "inspector", debug_assertions))]
     inspector: Option<Entity<Inspector>>,
@@ -1000,6 +1130,17 @@ fn default_bounds(display_id: Option<DisplayId>, cx: &mut App) -> Bounds<Pixels>
     }
 }
 
+fn effective_background_appearance(
+    background_appearance: WindowBackgroundAppearance,
+    blur_suppressed: bool,
+) -> WindowBackgroundAppearance {
+    if blur_suppressed && background_appearance == WindowBackgroundAppearance::Blurred {
+        WindowBackgroundAppearance::Transparent
+    } else {
+        background_appearance
+    }
+}
+
 impl Window {
     pub(crate) fn new(
         handle: AnyWindowHandle,
@@ -1023,8 +1164,14 @@ impl Window {
             #[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
             tabbing_identifier,
             raw_window_handle,
+            raw_display_handle,
+            embedding_mode,
+            host_gpu_device,
+            frame_driver,
+            content_zoom,
         } = options;
 
+        let embedded = raw_window_handle.is_some();
         let bounds = window_bounds
             .map(|bounds| bounds.get_bounds())
             .unwrap_or_else(|| default_bounds(display_id, cx));
@@ -1044,6 +1191,12 @@ impl Window {
                 #[cfg(target_os = "macos")]
                 tabbing_identifier,
                 raw_window_handle,
+                raw_display_handle,
+                embedding_mode,
+                host_gpu_device,
+                background_appearance: window_background,
+                frame_driver,
+                app_mode: cx.app_mode,
             },
         )?;
 
@@ -1055,11 +1208,12 @@ impl Window {
 
         let display_id = platform_window.display().map(|display| display.id());
         let sprite_atlas = platform_window.sprite_atlas();
-        let mouse_position = platform_window.mouse_position();
+        let content_zoom = content_zoom.clamp(MIN_CONTENT_ZOOM, MAX_CONTENT_ZOOM);
+        let mouse_position = platform_window.mouse_position().map(|c| c / content_zoom);
         let modifiers = platform_window.modifiers();
         let capslock = platform_window.capslock();
-        let content_size = platform_window.content_size();
-        let scale_factor = platform_window.scale_factor();
+        let content_size = platform_window.content_size().map(|c| c / content_zoom);
+        let scale_factor = platform_window.scale_factor() * content_zoom;
         let appearance = platform_window.appearance();
         let text_system = Arc::new(WindowTextSystem::new(cx.text_system().clone()));
         let invalidator = WindowInvalidator::new();
@@ -1068,10 +1222,16 @@ impl Window {
         let needs_present = Rc::new(Cell::new(false));
         let next_frame_callbacks: Rc<RefCell<Vec<FrameCallback>>> = Default::default();
         let last_input_timestamp = Rc::new(Cell::new(Instant::now()));
+        let frame_clock = FrameClock::new(platform_window.refresh_rate())
+            .with_timestamp_source(cx.frame_timestamp_source.clone());
 
         platform_window
             .request_decorations(window_decorations.unwrap_or(WindowDecorations::Server));
-        platform_window.set_background_appearance(window_background);
+        let blur_suppressed = cx.adaptive_blur_disabled();
+        platform_window.set_background_appearance(effective_background_appearance(
+            window_background,
+            blur_suppressed,
+        ));
 
         if let Some(ref window_open_state) = window_bounds {
             match window_open_state {
@@ -1091,14 +1251,22 @@ impl Window {
                 });
             }
         }));
-        platform_window.on_request_frame(Box::new({
+        let request_frame = Rc::new(RefCell::new({
             let mut cx = cx.to_async();
             let invalidator = invalidator.clone();
             let active = active.clone();
             let needs_present = needs_present.clone();
             let next_frame_callbacks = next_frame_callbacks.clone();
             let last_input_timestamp = last_input_timestamp.clone();
-            move |request_frame_options| {
+            move |request_frame_options: RequestFrameOptions| {
+                handle
+                    .update(&mut cx, |_, window, cx| {
+                        let frame_requested_at = window.frame_clock.now();
+                        window.frame_clock.frame_requested(frame_requested_at);
+                        window.painted_since_frame_request = false;
+                        window.flush_latched_input(cx);
+                    })
+                    .log_err();
                 let next_frame_callbacks = next_frame_callbacks.take();
                 if !next_frame_callbacks.is_empty() {
                     handle
@@ -1117,7 +1285,15 @@ impl Window {
                     || (active.get()
                         && last_input_timestamp.get().elapsed() < Duration::from_secs(1));
 
-                if invalidator.is_dirty() || request_frame_options.force_render {
+                // Under adaptive rendering, a dirty window stays dirty until enough time has
+                // passed since it was last drawn, by any path, to stay within the reduced frame
+                // rate. The platform keeps requesting frames, so it's drawn on a later one.
+                let throttled = !request_frame_options.force_render
+                    && handle
+                        .update(&mut cx, |_, window, cx| window.is_frame_throttled(cx))
+                        .unwrap_or(false);
+
+                if (invalidator.is_dirty() && !throttled) || request_frame_options.force_render {
                     measure("frame duration", || {
                         handle
                             .update(&mut cx, |_, window, cx| {
@@ -1135,12 +1311,21 @@ impl Window {
                 }
 
                 handle
-                    .update(&mut cx, |_, window, _| {
-                        window.complete_frame();
-                    })
+                    .update(&mut cx, |_, window, _| window.complete_frame())
                     .log_err();
             }
         }));
+        if frame_driver == FrameDriver::Platform {
+            platform_window.on_request_frame(Box::new({
+                let request_frame = request_frame.clone();
+                move |request_frame_options| {
+                    // The host may be rendering a frame with `AnyWindowHandle::render_frame`.
+                    if let Ok(mut request_frame) = request_frame.try_borrow_mut() {
+                        request_frame(request_frame_options);
+                    }
+                }
+            }));
+        }
         platform_window.on_resize(Box::new({
             let mut cx = cx.to_async();
             move |_, _| {
@@ -1157,6 +1342,14 @@ impl Window {
                     .log_err();
             }
         }));
+        platform_window.on_should_close(Box::new({
+            let mut cx = cx.to_async();
+            move || {
+                handle
+                    .update(&mut cx, |_, window, cx| window.close_requested(cx))
+                    .unwrap_or(true)
+            }
+        }));
         platform_window.on_appearance_changed(Box::new({
             let mut cx = cx.to_async();
             move || {
@@ -1165,23 +1358,20 @@ impl Window {
                     .log_err();
             }
         }));
+        platform_window.on_host_info_changed(Box::new({
+            let mut cx = cx.to_async();
+            move || {
+                handle
+                    .update(&mut cx, |_, window, cx| window.host_info_changed(cx))
+                    .log_err();
+            }
+        }));
         platform_window.on_active_status_change(Box::new({
             let mut cx = cx.to_async();
             move |active| {
                 handle
                     .update(&mut cx, |_, window, cx| {
-                        window.active.set(active);
-                        window.modifiers = window.platform_window.modifiers();
-                        window.capslock = window.platform_window.capslock();
-                        window
-                            .activation_observers
-                            .clone()
-                            .retain(&(), |callback| callback(window, cx));
-
-                        window.bounds_changed(cx);
-                        window.refresh();
-
-                        SystemWindowTabController::update_last_active(cx, window.handle.id);
+                        window.active_status_changed(active, cx)
                     })
                     .log_err();
             }
@@ -1200,10 +1390,18 @@ impl Window {
         platform_window.on_input({
             let mut cx = cx.to_async();
             Box::new(move |event| {
-                handle
-                    .update(&mut cx, |_, window, cx| window.dispatch_event(event, cx))
+                let result = handle
+                    .update(&mut cx, |_, window, cx| {
+                        window.dispatch_platform_input(event, cx)
+                    })
                     .log_err()
-                    .unwrap_or(DispatchEventResult::default())
+                    .unwrap_or(DispatchEventResult::default());
+                // Paint once the effects of the input have been flushed, so that the views it
+                // notified are redrawn.
+                handle
+                    .update(&mut cx, |_, window, cx| window.paint_on_input(cx))
+                    .log_err();
+                result
             })
         });
         platform_window.on_hit_test_window_control({
@@ -1279,11 +1477,16 @@ impl Window {
         }
 
         platform_window.map_window().unwrap();
+        let window_state = platform_window_state(platform_window.as_ref());
 
         Ok(Window {
             handle,
             invalidator,
             removed: false,
+            should_close_handler: RefCell::new(None),
+            close_request_handlers: SubscriberSet::new(),
+            pending_close_request: None,
+            close_when_approved: false,
             platform_window,
             display_id,
             sprite_atlas,
@@ -1292,9 +1495,12 @@ impl Window {
             rem_size_override_stack: SmallVec::new(),
             viewport_size: content_size,
             layout_engine: Some(TaffyLayoutEngine::new()),
+            layout_z_indices: FxHashMap::default(),
+            z_index_scopes: Vec::new(),
             root: None,
             element_id_stack: SmallVec::default(),
             text_style_stack: Vec::new(),
+            context_value_stack: Vec::new(),
             rendered_entity_stack: Vec::new(),
             element_offset_stack: Vec::new(),
             content_mask_stack: Vec::new(),
@@ -1303,26 +1509,46 @@ impl Window {
             rendered_frame: Frame::new(DispatchTree::new(cx.keymap.clone(), cx.actions.clone())),
             next_frame: Frame::new(DispatchTree::new(cx.keymap.clone(), cx.actions.clone())),
             next_frame_callbacks,
+            frame_driver,
+            request_frame,
             next_hitbox_id: HitboxId(0),
             next_tooltip_id: TooltipId::default(),
             tooltip_bounds: None,
             dirty_views: FxHashSet::default(),
+            notified_entities: FxHashSet::default(),
             focus_listeners: SubscriberSet::new(),
             focus_lost_listeners: SubscriberSet::new(),
+            action_interceptors: SubscriberSet::new(),
             default_prevented: true,
+            stopped_scroll_axes: SmallVec::new(),
             mouse_position,
+            pressed_button: None,
             mouse_hit_test: HitTest::default(),
             modifiers,
             capslock,
             scale_factor,
+            content_zoom,
             bounds_observers: SubscriberSet::new(),
+            window_state,
+            window_state_observers: SubscriberSet::new(),
+            container_sizes: FxHashMap::default(),
             appearance,
             appearance_observers: SubscriberSet::new(),
+            host_info_observers: SubscriberSet::new(),
+            background_appearance: Cell::new(window_background),
+            blur_suppressed: Cell::new(blur_suppressed),
+            frame_clock,
+            frame_stats_overlay: false,
+            atlas_uploads: Cell::new(0),
+            rendered_frame_atlas_uploads: 0,
             active,
             hovered,
             needs_present,
             last_input_timestamp,
             last_input_modality: InputModality::Mouse,
+            low_latency_input: false,
+            painted_since_frame_request: false,
+            latched_input: None,
             refreshing: false,
             activation_observers: SubscriberSet::new(),
             focus: None,
@@ -1331,6 +1557,12 @@ impl Window {
             pending_modifier: ModifierState::default(),
             pending_input_observers: SubscriberSet::new(),
             prompt: None,
+            emoji_picker: None,
+            portal_host: PortalHost::default(),
+            embedded,
+            size_constraints: SizeConstraints::default(),
+            host_resize_request_callback: None,
+            embedded_resize_callback: None,
             client_inset: None,
             image_cache_stack: Vec::new(),
             #[cfg(any(feature = "inspector", debug_assertions))]
@@ -1346,9 +1578,13 @@ impl Window {
     }
 }
 
+/// The outcome of dispatching an input event to a window, see [`Window::dispatch_event`].
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub(crate) struct DispatchEventResult {
+pub struct DispatchEventResult {
+    /// Whether no handler stopped the event's propagation, so it should be passed on to the
+    /// platform or host.
     pub propagate: bool,
+    /// Whether a handler asked for the platform's default behavior to be skipped.
     pub default_prevented: bool,
 }
 
@@ -1408,6 +1644,21 @@ impl Window {
         subscription
     }
 
+    /// Registers a callback to be invoked with every action before it is dispatched in this window.
+    /// Like [`App::intercept_actions`], the callback can observe, replace or block the action.
+    ///
+    /// Window interceptors run in the order they were registered, after all interceptors
+    /// registered on the app. They don't see actions dispatched to global listeners outside of
+    /// any window.
+    pub fn intercept_actions(
+        &self,
+        f: impl FnMut(&dyn Action, &mut Window, &mut App) -> ActionInterception + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.action_interceptors.insert((), Box::new(f));
+        activate();
+        subscription
+    }
+
     /// Replaces the root entity of the window with a new one.
     pub fn replace_root<E>(
         &mut self,
@@ -1438,6 +1689,12 @@ impl Window {
         self.handle
     }
 
+    /// Whether this window is embedded in a host-provided native window, as opened with
+    /// [`WindowOptions::for_embedded_window`].
+    pub fn is_embedded(&self) -> bool {
+        self.embedded
+    }
+
     /// Mark the window as dirty, scheduling it to be redrawn on the next frame.
     pub fn refresh(&mut self) {
         if self.invalidator.not_drawing() {
@@ -1520,6 +1777,20 @@ impl Window {
         style
     }
 
+    /// The value of type `T` provided by the nearest ancestor element with
+    /// [`InteractiveElement::provide_context`](crate::InteractiveElement::provide_context), if any. Use this while rendering to read values
+    /// such as themes or feature flags that cascade down the element tree, instead of passing
+    /// them through every component's constructor.
+    ///
+    /// Values are only available while the providing element's descendants are laid out and
+    /// prepainted, which is when views and components are rendered.
+    pub fn context_value<T: 'static>(&self) -> Option<&T> {
+        self.context_value_stack
+            .iter()
+            .rev()
+            .find_map(|value| value.downcast_ref::<T>())
+    }
+
     /// Check if the platform window is maximized
     /// On some platforms (namely Windows) this is different than the bounds being the size of the display
     pub fn is_maximized(&self) -> bool {
@@ -1623,6 +1894,7 @@ impl Window {
     /// Subscribe to events emitted by a entity.
     /// The entity to which you're subscribing must implement the [`EventEmitter`] trait.
     /// The callback will be invoked a handle to the emitting entity, the event, and a window context for the current window.
+    #[track_caller]
     pub fn observe<T: 'static>(
         &mut self,
         observed: &Entity<T>,
@@ -1634,6 +1906,7 @@ impl Window {
         let window_handle = self.handle;
         cx.new_observer(
             entity_id,
+            None,
             Box::new(move |cx| {
                 window_handle
                     .update(cx, |_, window, cx| {
@@ -1652,6 +1925,7 @@ impl Window {
     /// Subscribe to events emitted by a entity.
     /// The entity to which you're subscribing must implement the [`EventEmitter`] trait.
     /// The callback will be invoked a handle to the emitting entity, the event, and a window context for the current window.
+    #[track_caller]
     pub fn subscribe<Emitter, Evt>(
         &mut self,
         entity: &Entity<Emitter>,
@@ -1667,6 +1941,7 @@ impl Window {
         let window_handle = self.handle;
         cx.new_subscription(
             entity_id,
+            None,
             (
                 TypeId::of::<Evt>(),
                 Box::new(move |event, cx| {
@@ -1720,6 +1995,19 @@ impl Window {
         RefCell::borrow_mut(&self.next_frame_callbacks).push(Box::new(callback));
     }
 
+    /// Like [`Window::on_next_frame`], but the callback also receives the frame's
+    /// [`FrameTiming`], such as when the frame is expected to reach the display. This allows
+    /// animations and audio-visual sync to target the time their output will actually be seen.
+    pub fn on_next_frame_with_timing(
+        &self,
+        callback: impl FnOnce(&FrameTiming, &mut Window, &mut App) + 'static,
+    ) {
+        self.on_next_frame(move |window, cx| {
+            let timing = window.frame_clock.timing();
+            callback(&timing, window, cx)
+        });
+    }
+
     /// Schedule a frame to be drawn on the next animation frame.
     ///
     /// This is useful for elements that need to animate continuously, such as a video player or an animated GIF.
@@ -1731,6 +2019,47 @@ impl Window {
         self.on_next_frame(move |_, cx| cx.notify(entity));
     }
 
+    /// The time at which the frame currently being drawn started. Every element drawn in a
+    /// frame sees the same timestamp, so animations should compute their progress from it
+    /// rather than from [`Instant::now`].
+    pub fn frame_timestamp(&self) -> Instant {
+        self.frame_clock.timestamp()
+    }
+
+    /// The time used to drive this window's animations, sampled at the start of the frame
+    /// currently being drawn. This follows the clock set with [`Window::set_animation_clock`],
+    /// and is the time elapsed since the window was created otherwise.
+    pub fn animation_time(&self) -> Duration {
+        self.frame_clock.animation_time()
+    }
+
+    /// Drive this window's animations from the given clock rather than wall time, starting from
+    /// the next frame. Animations that are running when the clock changes jump to its new time.
+    pub fn set_animation_clock(&mut self, animation_clock: Box<dyn AnimationClock>) {
+        self.frame_clock.set_animation_clock(Some(animation_clock));
+        self.refresh();
+    }
+
+    /// Drive this window's animations from wall time again, after
+    /// [`Window::set_animation_clock`].
+    pub fn reset_animation_clock(&mut self) {
+        self.frame_clock.set_animation_clock(None);
+        self.refresh();
+    }
+
+    /// The expected time between this window's frames. While the platform is requesting
+    /// frames continuously this is measured from recent frames, so it follows variable refresh
+    /// rates such as ProMotion's. Otherwise it's derived from the display's refresh rate.
+    pub fn frame_interval(&self) -> Duration {
+        self.frame_clock.interval()
+    }
+
+    /// The nominal refresh rate of the display the window is on, in frames per second, if the
+    /// platform reports it. For variable refresh rate displays this is the maximum rate.
+    pub fn display_refresh_rate(&self) -> Option<f32> {
+        self.frame_clock.display_refresh_rate()
+    }
+
     /// Spawn the future returned by the given closure on the application thread pool.
     /// The closure is provided a handle to the current window and an `AsyncWindowContext` for
     /// use within your future.
@@ -1748,15 +2077,24 @@ impl Window {
     }
 
     fn bounds_changed(&mut self, cx: &mut App) {
-        self.scale_factor = self.platform_window.scale_factor();
-        self.viewport_size = self.platform_window.content_size();
+        self.update_zoomed_metrics();
         self.display_id = self.platform_window.display().map(|display| display.id());
+        self.frame_clock
+            .set_display_refresh_rate(self.platform_window.refresh_rate());
 
         self.refresh();
 
         self.bounds_observers
             .clone()
             .retain(&(), |callback| callback(self, cx));
+
+        let window_state = self.window_state();
+        if window_state != self.window_state {
+            self.window_state = window_state;
+            self.window_state_observers
+                .clone()
+                .retain(&(), |callback| callback(window_state, self, cx));
+        }
     }
 
     /// Returns the bounds of the current window in the global coordinate space, which could span across multiple displays.
@@ -1764,11 +2102,265 @@ impl Window {
         self.platform_window.bounds()
     }
 
-    /// Set the content size of the window.
+    /// Set the content size of the window. Embedded windows whose host resizes them, see
+    /// [`Window::on_embedded_resize`], ask the host to instead.
     pub fn resize(&mut self, size: Size<Pixels>) {
+        if let Some(mut callback) = self.embedded_resize_callback.take() {
+            callback(size, self);
+            if self.embedded_resize_callback.is_none() {
+                self.embedded_resize_callback = Some(callback);
+            }
+            return;
+        }
         self.platform_window.resize(size);
     }
 
+    /// Tell an embedded window that the host's native window it renders into, see
+    /// [`WindowOptions::raw_window_handle`], was resized to the given content size. Hosts should
+    /// call this from their own resize handling, as not every platform reports the new size to
+    /// GPUI. The window is laid out at the new size on a later frame.
+    pub fn notify_embedded_resize(&mut self, size: Size<Pixels>) {
+        trace_embedded(|| format!("{:?}: host resized to {:?}", self.handle.window_id(), size));
+        self.platform_window.notify_embedded_resize(size);
+    }
+
+    /// The sizes this window accepts, see [`Window::set_size_constraints`].
+    pub fn size_constraints(&self) -> SizeConstraints {
+        self.size_constraints
+    }
+
+    /// Set the sizes an embedded window accepts. Hosts that negotiate sizes, like plugin hosts,
+    /// query these and pass the sizes they'd like to resize to through
+    /// [`Window::adjust_embedded_size`], so the window isn't stretched to sizes its layout can't
+    /// handle.
+    pub fn set_size_constraints(&mut self, constraints: SizeConstraints) {
+        trace_embedded(|| {
+            format!(
+                "{:?}: size constraints changed to {:?}",
+                self.handle.window_id(),
+                constraints
+            )
+        });
+        self.size_constraints = constraints;
+    }
+
+    /// Register a callback that adjusts the sizes the host of an embedded window asks to resize
+    /// it to, see [`Window::adjust_embedded_size`]. The size the callback returns is still kept
+    /// within the window's [`SizeConstraints`]. Only one callback is kept, so registering another
+    /// replaces it.
+    pub fn on_host_resize_request(
+        &mut self,
+        callback: impl FnMut(Size<Pixels>, &mut Window, &mut App) -> Size<Pixels> + 'static,
+    ) {
+        self.host_resize_request_callback = Some(Box::new(callback));
+    }
+
+    /// Register a callback that asks the host of an embedded window to resize it, which
+    /// [`Window::resize`] calls instead of resizing the window. Plugin hosts own the size of the
+    /// windows their plugins' editors are embedded in, so the callback passes the size to an API
+    /// like VST3's `IPlugFrame::resizeView`, and the host reports the size it resized to with
+    /// [`Window::notify_embedded_resize`]. Only one callback is kept, so registering another
+    /// replaces it.
+    pub fn on_embedded_resize(
+        &mut self,
+        callback: impl FnMut(Size<Pixels>, &mut Window) + 'static,
+    ) {
+        self.embedded_resize_callback = Some(Box::new(callback));
+    }
+
+    /// Return the size closest to the one the host of an embedded window would like to resize it
+    /// to, that the window accepts. This is what plugin hosts ask for with calls like CLAP's
+    /// `adjust_size` or VST3's `checkSizeConstraint`, before resizing their window and calling
+    /// [`Window::notify_embedded_resize`]. If the window isn't resizable, its current size is
+    /// returned.
+    pub fn adjust_embedded_size(&mut self, size: Size<Pixels>, cx: &mut App) -> Size<Pixels> {
+        if !self.size_constraints.resizable {
+            return self.viewport_size;
+        }
+        let mut adjusted = size;
+        if let Some(mut callback) = self.host_resize_request_callback.take() {
+            adjusted = callback(size, self, cx);
+            if self.host_resize_request_callback.is_none() {
+                self.host_resize_request_callback = Some(callback);
+            }
+        }
+        let adjusted = self.size_constraints.constrain(adjusted);
+        trace_embedded(|| {
+            format!(
+                "{:?}: host asked for {:?}, adjusted to {:?}",
+                self.handle.window_id(),
+                size,
+                adjusted
+            )
+        });
+        adjusted
+    }
+
+    /// Take keyboard focus from the host of an embedded window, and treat the window as active
+    /// until [`Window::release_keyboard_focus`] or [`Window::notify_embedded_focus_lost`] is
+    /// called. Plugin hosts often swallow the platform's focus messages, so embedded windows
+    /// should hand focus back and forth explicitly, for example when a text field is clicked.
+    pub fn request_keyboard_focus(&mut self, cx: &mut App) {
+        trace_embedded(|| format!("{:?}: requesting keyboard focus", self.handle.window_id()));
+        self.platform_window.request_keyboard_focus();
+        self.active_status_changed(true, cx);
+    }
+
+    /// Hand keyboard focus back to the host of an embedded window, see
+    /// [`Window::request_keyboard_focus`].
+    pub fn release_keyboard_focus(&mut self, cx: &mut App) {
+        trace_embedded(|| format!("{:?}: releasing keyboard focus", self.handle.window_id()));
+        self.platform_window.release_keyboard_focus();
+        self.active_status_changed(false, cx);
+    }
+
+    /// Tell an embedded window that its host has taken keyboard focus back, see
+    /// [`Window::request_keyboard_focus`]. Observers registered with
+    /// [`Context::observe_window_activation`] are notified as the window becomes inactive.
+    pub fn notify_embedded_focus_lost(&mut self, cx: &mut App) {
+        trace_embedded(|| format!("{:?}: host took keyboard focus", self.handle.window_id()));
+        self.active_status_changed(false, cx);
+    }
+
+    fn active_status_changed(&mut self, active: bool, cx: &mut App) {
+        self.active.set(active);
+        self.modifiers = self.platform_window.modifiers();
+        self.capslock = self.platform_window.capslock();
+        self.activation_observers
+            .clone()
+            .retain(&(), |callback| callback(self, cx));
+
+        self.bounds_changed(cx);
+        self.refresh();
+
+        SystemWindowTabController::update_last_active(cx, self.handle.id);
+    }
+
+    /// Tell an embedded window that the host's native window it renders into moved to a display
+    /// with a different scale factor, such as a monitor with a different DPI. Text and layout are
+    /// rescaled on a later frame. macOS and Wayland report the new scale factor to GPUI by
+    /// themselves, so this is ignored there. On Windows, GPUI also watches the host's window for
+    /// DPI changes, so hosts mostly need this on X11.
+    pub fn set_embedded_scale_factor(&mut self, scale_factor: f32) {
+        trace_embedded(|| {
+            format!(
+                "{:?}: host scale factor changed to {}",
+                self.handle.window_id(),
+                scale_factor
+            )
+        });
+        self.platform_window.set_embedded_scale_factor(scale_factor);
+    }
+
+    /// The state of the host's native window that an embedded window renders into: its size,
+    /// scale factor, and whether it's shown, minimized or in the foreground. Returns `None` for
+    /// windows that aren't embedded, and on platforms that can't tell, which is currently all but
+    /// Windows and macOS. See [`Window::observe_host_info`] to be told when it changes.
+    pub fn host_info(&self) -> Option<HostWindowInfo> {
+        self.platform_window.host_info()
+    }
+
+    /// Registers a callback to be invoked when the state of the host's native window of an
+    /// embedded window changes, see [`Window::host_info`].
+    pub fn observe_host_info(
+        &self,
+        mut callback: impl FnMut(&mut Window, &mut App) + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.host_info_observers.insert(
+            (),
+            Box::new(move |window, cx| {
+                callback(window, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    fn host_info_changed(&mut self, cx: &mut App) {
+        trace_embedded(|| {
+            format!(
+                "{:?}: host changed to {:?}",
+                self.handle.window_id(),
+                self.host_info()
+            )
+        });
+        self.host_info_observers
+            .clone()
+            .retain(&(), |callback| callback(self, cx));
+    }
+
+    /// Detach an embedded window from the host's native window it renders into, and close it.
+    /// Unlike [`Window::remove_window`], GPUI lets go of the host's window right away, so hosts
+    /// should call this before destroying their window. On Windows, GPUI also detaches by itself
+    /// when the host's window is destroyed.
+    pub fn detach_embedded(&mut self) {
+        trace_embedded(|| format!("{:?}: detaching from host", self.handle.window_id()));
+        self.platform_window.detach_embedded();
+        self.remove_window();
+    }
+
+    /// Move an embedded window into another native window provided by its host, such as when a
+    /// plugin host docks or undocks a plugin's editor. The window keeps its state, native surface
+    /// and GPU resources, and is resized to fill the new host window. Windows parked with
+    /// [`Window::park_embedded`] are shown again.
+    ///
+    /// On Windows, this requires the window to have been opened with
+    /// [`EmbeddingMode::Child`](crate::EmbeddingMode::Child).
+    /// This isn't supported on Wayland, where the window's surface can't change its parent.
+    pub fn reparent(&mut self, raw_handle: raw_window_handle::RawWindowHandle) -> Result<()> {
+        if !self.embedded {
+            return Err(WindowOpenError::Unsupported(
+                "Only embedded windows can move to another host window".into(),
+            )
+            .into());
+        }
+        trace_embedded(|| {
+            format!(
+                "{:?}: moving to host {:?}",
+                self.handle.window_id(),
+                raw_handle
+            )
+        });
+        self.platform_window.reparent_embedded(raw_handle)?;
+        self.refresh();
+        Ok(())
+    }
+
+    /// Take an embedded window out of the native window provided by its host and hide it, without
+    /// closing it, such as when a plugin host removes a plugin's editor to attach it to another
+    /// window. The host can then destroy its window, and the window keeps its state until it's
+    /// moved into another native window with [`Window::reparent`], or closed.
+    ///
+    /// Like [`Window::reparent`], this requires the window to have been opened with
+    /// [`EmbeddingMode::Child`](crate::EmbeddingMode::Child) on Windows, and isn't supported on
+    /// Wayland.
+    pub fn park_embedded(&mut self) -> Result<()> {
+        if !self.embedded {
+            return Err(
+                WindowOpenError::Unsupported("Only embedded windows can be parked".into()).into(),
+            );
+        }
+        trace_embedded(|| format!("{:?}: parking", self.handle.window_id()));
+        self.platform_window.park_embedded()
+    }
+
+    /// Dispatch input that the host of an embedded window delivered through its own callbacks,
+    /// rather than through the native window, as if the platform had reported it. Unlike
+    /// [`Window::dispatch_event`], the input takes the same path as the platform's input, so it's
+    /// latched with [`Window::set_low_latency_input`] and the window repaints early in response.
+    pub fn dispatch_embedded_input(
+        &mut self,
+        event: PlatformInput,
+        cx: &mut App,
+    ) -> DispatchEventResult {
+        trace_embedded(|| format!("{:?}: injected {:?}", self.handle.window_id(), event));
+        let result = self.dispatch_platform_input(event, cx);
+        // Paint once the effects of the input have been flushed, as for the platform's input.
+        self.defer(cx, |window, cx| window.paint_on_input(cx));
+        result
+    }
+
     /// Returns whether or not the window is currently fullscreen
     pub fn is_fullscreen(&self) -> bool {
         self.platform_window.is_fullscreen()
@@ -1782,6 +2374,28 @@ impl Window {
             .retain(&(), |callback| callback(self, cx));
     }
 
+    /// Returns the size the container with the given name had when it was last drawn.
+    /// See [`InteractiveElement::container`](crate::InteractiveElement::container).
+    pub fn container_size(&self, name: &str) -> Option<Size<Pixels>> {
+        self.container_sizes.get(name).copied()
+    }
+
+    /// Records the measured size of a named container, redrawing the window once the current
+    /// frame is done if it changed so that container queries see the new size.
+    pub(crate) fn record_container_size(
+        &mut self,
+        name: SharedString,
+        size: Size<Pixels>,
+        cx: &mut App,
+    ) {
+        if self.container_sizes.insert(name, size) != Some(size) {
+            let handle = self.handle;
+            cx.defer(move |cx| {
+                handle.update(cx, |_, window, _| window.refresh()).ok();
+            });
+        }
+    }
+
     /// Returns the appearance of the current window.
     pub fn appearance(&self) -> WindowAppearance {
         self.appearance
@@ -1817,6 +2431,44 @@ impl Window {
         self.platform_window.zoom();
     }
 
+    /// Maximize the window at the platform level, if it isn't already maximized.
+    pub fn maximize_window(&self) {
+        if !self.platform_window.is_maximized() {
+            self.platform_window.zoom();
+        }
+    }
+
+    /// Restore the window to its normal state at the platform level, bringing it back from being
+    /// minimized, maximized or fullscreen.
+    pub fn restore_window(&self) {
+        self.platform_window.restore();
+    }
+
+    /// Returns the state the window manager currently shows the window in.
+    ///
+    /// A window that is both maximized and fullscreen is reported as fullscreen, and a maximized
+    /// window isn't reported as tiled even if all of its edges are.
+    pub fn window_state(&self) -> WindowState {
+        platform_window_state(self.platform_window.as_ref())
+    }
+
+    /// Registers a callback to be invoked when the window is minimized, maximized, made
+    /// fullscreen, tiled or restored, with the new [`Window::window_state`].
+    pub fn on_window_state_changed(
+        &self,
+        mut callback: impl FnMut(WindowState, &mut Window, &mut App) + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.window_state_observers.insert(
+            (),
+            Box::new(move |window_state, window, cx| {
+                callback(window_state, window, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Opens the native title bar context menu, useful when implementing client side decorations (Wayland and X11)
     pub fn show_window_menu(&self, position: Point<Pixels>) {
         self.platform_window.show_window_menu(position)
@@ -1861,10 +2513,32 @@ impl Window {
         self.platform_window.set_app_id(app_id);
     }
 
-    /// Sets the window background appearance.
+    /// Sets the window background appearance. While [`App::set_adaptive_rendering`] is
+    /// reducing rendering work, a blurred background is drawn as transparent instead.
     pub fn set_background_appearance(&self, background_appearance: WindowBackgroundAppearance) {
+        self.background_appearance.set(background_appearance);
         self.platform_window
-            .set_background_appearance(background_appearance);
+            .set_background_appearance(effective_background_appearance(
+                background_appearance,
+                self.blur_suppressed.get(),
+            ));
+    }
+
+    pub(crate) fn set_blur_suppressed(&self, blur_suppressed: bool) {
+        if self.blur_suppressed.replace(blur_suppressed) != blur_suppressed {
+            self.set_background_appearance(self.background_appearance.get());
+        }
+    }
+
+    /// Whether adaptive rendering is holding back the next frame because the last one was
+    /// drawn too recently.
+    pub(crate) fn is_frame_throttled(&self, cx: &App) -> bool {
+        cx.adaptive_frame_interval().is_some_and(|interval| {
+            self.frame_clock
+                .now()
+                .saturating_duration_since(self.frame_clock.timestamp())
+                < interval
+        })
     }
 
     /// Mark the window as dirty at the platform level.
@@ -1880,9 +2554,38 @@ impl Window {
             .find(|display| Some(display.id()) == self.display_id)
     }
 
-    /// Show the platform character palette.
-    pub fn show_character_palette(&self) {
-        self.platform_window.show_character_palette();
+    /// Show the platform's emoji and symbol picker for the focused text input, the character
+    /// palette on macOS or the emoji panel on Windows, which inserts the chosen characters through
+    /// the input's [`InputHandler`]. Embedded windows, and platforms without a native picker, show
+    /// a [`FallbackEmojiPicker`] next to the input's cursor instead.
+    pub fn show_character_palette(&mut self, cx: &mut App) {
+        if !self.embedded && self.platform_window.show_character_palette() {
+            return;
+        }
+
+        let position = self
+            .platform_window
+            .take_input_handler()
+            .and_then(|mut input_handler| {
+                let bounds = input_handler.selected_bounds(self, cx);
+                self.platform_window.set_input_handler(input_handler);
+                bounds
+            })
+            .map_or(self.mouse_position(), |bounds| bounds.bottom_left());
+        self.emoji_picker = Some(cx.new(|_| FallbackEmojiPicker::new(position)));
+        self.refresh();
+    }
+
+    /// Close the [`FallbackEmojiPicker`], if it's shown. Native pickers are closed by the user.
+    pub fn hide_character_palette(&mut self) {
+        if self.emoji_picker.take().is_some() {
+            self.refresh();
+        }
+    }
+
+    /// Whether the [`FallbackEmojiPicker`] is shown in this window.
+    pub fn is_showing_fallback_emoji_picker(&self) -> bool {
+        self.emoji_picker.is_some()
     }
 
     /// The scale factor of the display associated with the window. For example, it could
@@ -1892,6 +2595,94 @@ impl Window {
         self.scale_factor
     }
 
+    /// Returns the zoom applied to this window's content, see [`Window::set_content_zoom`].
+    pub fn content_zoom(&self) -> f32 {
+        self.content_zoom
+    }
+
+    /// Zoom all of the window's content by the given factor, like a browser's zoom. Layout is
+    /// performed in a correspondingly smaller or larger viewport and everything is rendered at
+    /// the zoomed scale, so text is rasterized at its zoomed size and stays crisp. Input
+    /// positions are converted so elements keep receiving them in their own coordinates.
+    ///
+    /// The zoom is independent of the display's scale factor and is kept when the window
+    /// moves between displays. It's clamped between 0.25 and 5.0, and recorded in the window's
+    /// [`WindowPlacement`](crate::WindowPlacement) so that sessions restore it.
+    pub fn set_content_zoom(&mut self, zoom: f32) {
+        let zoom = zoom.clamp(MIN_CONTENT_ZOOM, MAX_CONTENT_ZOOM);
+        if zoom == self.content_zoom {
+            return;
+        }
+
+        let previous_zoom = self.content_zoom;
+        self.content_zoom = zoom;
+        self.mouse_position = self.mouse_position.map(|c| c * previous_zoom / zoom);
+        self.update_zoomed_metrics();
+        self.refresh();
+    }
+
+    fn update_zoomed_metrics(&mut self) {
+        self.scale_factor = self.platform_window.scale_factor() * self.content_zoom;
+        self.viewport_size = self
+            .platform_window
+            .content_size()
+            .map(|c| c / self.content_zoom);
+    }
+
+    /// Converts positions in input from the platform, which are in unzoomed window
+    /// coordinates, into the coordinates that elements are laid out in.
+    fn unzoom_input(&self, event: PlatformInput) -> PlatformInput {
+        if self.content_zoom == 1. {
+            return event;
+        }
+
+        let zoom = self.content_zoom;
+        let unzoom = |position: Point<Pixels>| position.map(|c| c / zoom);
+        match event {
+            PlatformInput::MouseDown(event) => PlatformInput::MouseDown(MouseDownEvent {
+                position: unzoom(event.position),
+                ..event
+            }),
+            PlatformInput::MouseUp(event) => PlatformInput::MouseUp(MouseUpEvent {
+                position: unzoom(event.position),
+                ..event
+            }),
+            PlatformInput::MouseMove(event) => PlatformInput::MouseMove(MouseMoveEvent {
+                position: unzoom(event.position),
+                ..event
+            }),
+            PlatformInput::MouseExited(event) => PlatformInput::MouseExited(MouseExitEvent {
+                position: unzoom(event.position),
+                ..event
+            }),
+            PlatformInput::ScrollWheel(event) => PlatformInput::ScrollWheel(ScrollWheelEvent {
+                position: unzoom(event.position),
+                delta: match event.delta {
+                    ScrollDelta::Pixels(delta) => ScrollDelta::Pixels(unzoom(delta)),
+                    ScrollDelta::Lines(delta) => ScrollDelta::Lines(delta),
+                },
+                ..event
+            }),
+            PlatformInput::FileDrop(FileDropEvent::Entered { position, paths }) => {
+                PlatformInput::FileDrop(FileDropEvent::Entered {
+                    position: unzoom(position),
+                    paths,
+                })
+            }
+            PlatformInput::FileDrop(FileDropEvent::Pending { position }) => {
+                PlatformInput::FileDrop(FileDropEvent::Pending {
+                    position: unzoom(position),
+                })
+            }
+            PlatformInput::FileDrop(FileDropEvent::Submit { position }) => {
+                PlatformInput::FileDrop(FileDropEvent::Submit {
+                    position: unzoom(position),
+                })
+            }
+            event => event,
+        }
+    }
+
     /// The size of an em for the base font of the application. Adjusting this value allows the
     /// UI to scale, just like zooming a web page.
     pub fn rem_size(&self) -> Pixels {
@@ -1960,6 +2751,40 @@ impl Window {
         self.default_prevented
     }
 
+    /// Stop the given axis of the scroll wheel event currently being dispatched from scrolling
+    /// any further elements, while the other axis continues to propagate. For example, a
+    /// horizontal slider can consume horizontal scrolling, while vertical scrolling still reaches
+    /// the scroll container it's in. Use [`App::stop_propagation`] to consume both axes.
+    pub fn stop_scroll_propagation(&mut self, axis: Axis) {
+        if !self.stopped_scroll_axes.contains(&axis) {
+            self.stopped_scroll_axes.push(axis);
+        }
+    }
+
+    /// Obtain whether the given axis of the scroll wheel event currently being dispatched was
+    /// consumed with [`Window::stop_scroll_propagation`].
+    pub fn is_scroll_propagation_stopped(&self, axis: Axis) -> bool {
+        self.stopped_scroll_axes.contains(&axis)
+    }
+
+    /// The scroll delta of the given event in pixels, with the axes consumed by
+    /// [`Window::stop_scroll_propagation`] set to zero. Scroll containers use this to only
+    /// scroll along the axes that weren't consumed by the elements they contain.
+    pub fn unconsumed_scroll_delta(
+        &self,
+        event: &ScrollWheelEvent,
+        line_height: Pixels,
+    ) -> Point<Pixels> {
+        let mut delta = event.delta.pixel_delta(line_height);
+        if self.is_scroll_propagation_stopped(Axis::Horizontal) {
+            delta.x = Pixels::ZERO;
+        }
+        if self.is_scroll_propagation_stopped(Axis::Vertical) {
+            delta.y = Pixels::ZERO;
+        }
+        delta
+    }
+
     /// Determine whether the given action is available along the dispatch path to the currently focused element.
     pub fn is_action_available(&self, action: &dyn Action, cx: &mut App) -> bool {
         let node_id =
@@ -1974,11 +2799,95 @@ impl Window {
         self.mouse_position
     }
 
+    /// Move the cursor to the given position relative to the window, such as to recenter it
+    /// while dragging a control that adjusts a value by the distance dragged. Returns whether
+    /// the cursor was moved, after which [`Window::mouse_position`] reports the new position.
+    ///
+    /// Platforms differ in how they support this:
+    /// - On macOS, warping the cursor doesn't generate a mouse move event, so follow it with
+    ///   [`Window::post_synthetic_mouse_move`] if hover states should reflect the new position.
+    /// - On Windows and X11, the platform sends a mouse move event to the new position.
+    /// - On Wayland, applications can't move the cursor, so this returns `false`.
+    pub fn warp_cursor(&mut self, position: Point<Pixels>) -> bool {
+        let zoom = self.content_zoom;
+        if !self.platform_window.warp_cursor(position.map(|c| c * zoom)) {
+            return false;
+        }
+        self.mouse_position = position;
+        true
+    }
+
+    /// Dispatch a mouse move event to this window at the cursor's current position, at the end
+    /// of the current effect cycle. See [`App::post_synthetic_mouse_move`].
+    pub fn post_synthetic_mouse_move(&self, cx: &mut App) {
+        let handle = self.handle;
+        cx.defer(move |cx| {
+            handle
+                .update(cx, |_, window, cx| window.dispatch_synthetic_mouse_move(cx))
+                .ok();
+        });
+    }
+
+    /// Keep the window above other applications' windows, for example as a picture-in-picture
+    /// overlay, or return it to the normal stacking order. Returns whether the platform supports
+    /// it, which Wayland doesn't.
+    pub fn set_always_on_top(&self, always_on_top: bool) -> bool {
+        self.platform_window.set_always_on_top(always_on_top)
+    }
+
+    /// Keep receiving mouse events while the cursor is outside of the window, such as while
+    /// dragging a knob in an embedded window whose host's window surrounds it, until
+    /// [`Window::end_mouse_capture`]. Call it from a mouse down handler and end the capture on
+    /// mouse up. Returns whether the platform supports it.
+    ///
+    /// Platforms differ in how they support this:
+    /// - On Windows, the window captures the mouse, or the host's window for embedded windows
+    ///   that attach to it, whose procedure should keep forwarding the mouse.
+    /// - On macOS, only embedded windows support it, by tracking the mouse over the host's
+    ///   window. Mouse events outside of the host's window are only delivered while a button is
+    ///   held.
+    pub fn start_mouse_capture(&mut self) -> bool {
+        trace_embedded(|| format!("{:?}: start mouse capture", self.handle.window_id()));
+        self.platform_window.start_mouse_capture()
+    }
+
+    /// Stop receiving mouse events from outside of the window, see
+    /// [`Window::start_mouse_capture`].
+    pub fn end_mouse_capture(&mut self) {
+        trace_embedded(|| format!("{:?}: end mouse capture", self.handle.window_id()));
+        self.platform_window.end_mouse_capture();
+    }
+
+    /// Dispatch a mouse move event at the cursor's last known position, with the buttons and
+    /// modifiers last reported by the platform.
+    pub(crate) fn dispatch_synthetic_mouse_move(&mut self, cx: &mut App) {
+        self.dispatch_event(
+            PlatformInput::MouseMove(MouseMoveEvent {
+                position: self.mouse_position.map(|c| c * self.content_zoom),
+                pressed_button: self.pressed_button,
+                modifiers: self.modifiers,
+            }),
+            cx,
+        );
+    }
+
     /// The current state of the keyboard's modifiers
     pub fn modifiers(&self) -> Modifiers {
         self.modifiers
     }
 
+    /// The modifiers held right now, as reported by the platform. Unlike [`Window::modifiers`],
+    /// which reflects the last event received by this window, this includes modifiers that were
+    /// pressed or released while another window was active. For embedded windows, the host
+    /// owns the keyboard, so this is the state from the events it forwarded.
+    pub fn current_modifiers(&self) -> Modifiers {
+        if self.embedded {
+            self.modifiers
+        } else {
+            self.platform_window.modifiers()
+        }
+    }
+
     /// Returns true if the last input event was keyboard-based (key press, tab navigation, etc.)
     /// This is used for focus-visible styling to show focus indicators only for keyboard navigation.
     pub fn last_input_was_keyboard(&self) -> bool {
@@ -1998,6 +2907,7 @@ impl Window {
     /// the contents of the new [`Scene`], use [`Self::present`].
     #[profiling::function]
     pub fn draw(&mut self, cx: &mut App) -> ArenaClearNeeded {
+        self.frame_clock.begin_frame(self.frame_clock.now());
         self.invalidate_entities();
         cx.entities.clear_accessed();
         debug_assert!(self.rendered_entity_stack.is_empty());
@@ -2010,6 +2920,7 @@ impl Window {
         }
         self.draw_roots(cx);
         self.dirty_views.clear();
+        self.notified_entities.clear();
         self.next_frame.window_active = self.active.get();
 
         // Register requested input handler with the platform window.
@@ -2019,6 +2930,7 @@ impl Window {
         }
 
         self.layout_engine.as_mut().unwrap().clear();
+        self.layout_z_indices.clear();
         self.text_system().finish_frame();
         self.next_frame.finish(&mut self.rendered_frame);
 
@@ -2027,6 +2939,7 @@ impl Window {
         let previous_window_active = self.rendered_frame.window_active;
         mem::swap(&mut self.rendered_frame, &mut self.next_frame);
         self.next_frame.clear();
+        self.rendered_frame_atlas_uploads = self.atlas_uploads.take();
         let current_focus_path = self.rendered_frame.focus_path();
         let current_window_active = self.rendered_frame.window_active;
 
@@ -2062,6 +2975,8 @@ impl Window {
         self.refreshing = false;
         self.invalidator.set_phase(DrawPhase::None);
         self.needs_present.set(true);
+        self.painted_since_frame_request = true;
+        self.frame_clock.end_frame(self.frame_clock.now());
 
         ArenaClearNeeded
     }
@@ -2084,6 +2999,7 @@ impl Window {
     fn invalidate_entities(&mut self) {
         let mut views = self.invalidator.take_views();
         for entity in views.drain() {
+            self.notified_entities.insert(entity);
             self.mark_view_dirty(entity);
         }
         self.invalidator.replace_views(views);
@@ -2129,6 +3045,12 @@ impl Window {
             (0..self.next_frame.deferred_draws.len()).collect::<SmallVec<[_; 8]>>();
         sorted_deferred_draws.sort_by_key(|ix| self.next_frame.deferred_draws[*ix].priority);
         self.prepaint_deferred_draws(&sorted_deferred_draws, cx);
+        let mut portal_elements = self.prepaint_portals(root_size, cx);
+        let mut emoji_picker_element = self.emoji_picker.clone().map(|emoji_picker| {
+            let mut element = AnyView::from(emoji_picker).into_any();
+            element.prepaint_as_root(Point::default(), root_size.into(), self, cx);
+            element
+        });
 
         let mut prompt_element = None;
         let mut active_drag_element = None;
@@ -2139,14 +3061,21 @@ impl Window {
             prompt_element = Some(element);
             self.prompt = Some(prompt);
         } else if let Some(active_drag) = cx.active_drag.take() {
-            let mut element = active_drag.view.clone().into_any();
-            let offset = self.mouse_position() - active_drag.cursor_offset;
-            element.prepaint_as_root(offset, AvailableSpace::min_size(), self, cx);
-            active_drag_element = Some(element);
+            // Only draw the dragged element in the window the cursor is over.
+            if cx
+                .active_drag_window
+                .is_none_or(|window| window == self.handle)
+            {
+                let mut element = active_drag.view.clone().into_any();
+                let offset = self.mouse_position() - active_drag.cursor_offset;
+                element.prepaint_as_root(offset, AvailableSpace::min_size(), self, cx);
+                active_drag_element = Some(element);
+            }
             cx.active_drag = Some(active_drag);
         } else {
             tooltip_element = self.prepaint_tooltip(cx);
         }
+        let frame_stats_element = self.prepaint_frame_stats_overlay(cx);
 
         self.mouse_hit_test = self.next_frame.hit_test(self.mouse_position);
 
@@ -2158,6 +3087,12 @@ impl Window {
         self.paint_inspector(inspector_element, cx);
 
         self.paint_deferred_draws(&sorted_deferred_draws, cx);
+        for (source_view, element) in &mut portal_elements {
+            self.with_rendered_view(*source_view, |window| element.paint(window, cx));
+        }
+        if let Some(mut emoji_picker_element) = emoji_picker_element {
+            emoji_picker_element.paint(self, cx);
+        }
 
         if let Some(mut prompt_element) = prompt_element {
             prompt_element.paint(self, cx);
@@ -2167,10 +3102,33 @@ impl Window {
             tooltip_element.paint(self, cx);
         }
 
+        if let Some(mut frame_stats_element) = frame_stats_element {
+            frame_stats_element.paint(self, cx);
+        }
+
         #[cfg(any(feature = "inspector", debug_assertions))]
         self.paint_inspector_hitbox(cx);
     }
 
+    fn prepaint_portals(
+        &mut self,
+        root_size: Size<Pixels>,
+        cx: &mut App,
+    ) -> Vec<(EntityId, AnyElement)> {
+        self.portal_host
+            .contents()
+            .into_iter()
+            .map(|content| {
+                let element = self.with_rendered_view(content.source_view, |window| {
+                    let mut element = (content.render)(window, cx);
+                    element.prepaint_as_root(Point::default(), root_size.into(), window, cx);
+                    element
+                });
+                (content.source_view, element)
+            })
+            .collect()
+    }
+
     fn prepaint_tooltip(&mut self, cx: &mut App) -> Option<AnyElement> {
         // Use indexing instead of iteration to avoid borrowing self for the duration of the loop.
         for tooltip_request_index in (0..self.next_frame.tooltip_requests.len()).rev() {
@@ -2250,6 +3208,8 @@ impl Window {
                 .clone_from(&deferred_draw.element_id_stack);
             self.text_style_stack
                 .clone_from(&deferred_draw.text_style_stack);
+            self.context_value_stack
+                .clone_from(&deferred_draw.context_value_stack);
             self.next_frame
                 .dispatch_tree
                 .set_active_node(deferred_draw.parent_node);
@@ -2275,6 +3235,7 @@ impl Window {
         self.next_frame.deferred_draws = deferred_draws;
         self.element_id_stack.clear();
         self.text_style_stack.clear();
+        self.context_value_stack.clear();
     }
 
     fn paint_deferred_draws(&mut self, deferred_draw_indices: &[usize], cx: &mut App) {
@@ -2312,6 +3273,8 @@ impl Window {
             dispatch_tree_index: self.next_frame.dispatch_tree.len(),
             accessed_element_states_index: self.next_frame.accessed_element_states.len(),
             line_layout_index: self.text_system.layout_index(),
+            #[cfg(any(test, feature = "test-support"))]
+            layout_tree_index: self.next_frame.layout_tree.len(),
         }
     }
 
@@ -2335,6 +3298,11 @@ impl Window {
         );
         self.text_system
             .reuse_layouts(range.start.line_layout_index..range.end.line_layout_index);
+        #[cfg(any(test, feature = "test-support"))]
+        self.next_frame.layout_tree.reuse(
+            &self.rendered_frame.layout_tree,
+            range.start.layout_tree_index..range.end.layout_tree_index,
+        );
 
         let reused_subtree = self.next_frame.dispatch_tree.reuse_subtree(
             range.start.dispatch_tree_index..range.end.dispatch_tree_index,
@@ -2355,6 +3323,7 @@ impl Window {
                     parent_node: reused_subtree.refresh_node_id(deferred_draw.parent_node),
                     element_id_stack: deferred_draw.element_id_stack.clone(),
                     text_style_stack: deferred_draw.text_style_stack.clone(),
+                    context_value_stack: deferred_draw.context_value_stack.clone(),
                     priority: deferred_draw.priority,
                     element: None,
                     absolute_offset: deferred_draw.absolute_offset,
@@ -2435,6 +3404,24 @@ impl Window {
         }
     }
 
+    /// Push context values onto the stack, and call a function with them available through
+    /// [`Window::context_value`]. This method should only be called as part of element drawing.
+    #[inline]
+    pub fn with_context_values<F, R>(&mut self, values: &[Rc<dyn Any>], f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        self.invalidator.debug_assert_paint_or_prepaint();
+        if values.is_empty() {
+            return f(self);
+        }
+        let len = self.context_value_stack.len();
+        self.context_value_stack.extend(values.iter().cloned());
+        let result = f(self);
+        self.context_value_stack.truncate(len);
+        result
+    }
+
     /// Updates the cursor style at the platform level. This method should only be called
     /// during the paint phase of element drawing.
     pub fn set_cursor_style(&mut self, style: CursorStyle, hitbox: &Hitbox) {
@@ -2856,6 +3843,7 @@ impl Window {
             parent_node,
             element_id_stack: self.element_id_stack.clone(),
             text_style_stack: self.text_style_stack.clone(),
+            context_value_stack: self.context_value_stack.clone(),
             priority,
             element: Some(element),
             absolute_offset,
@@ -2890,6 +3878,79 @@ impl Window {
         result
     }
 
+    /// Prepaints the contents of a copy that is drawn `scale` times its size into `bounds`, see
+    /// [`crate::thumbnail_of`]. The contents are laid out in `bounds` divided by `scale`, cached
+    /// views within them are rendered afresh, and the hitboxes they insert are discarded since
+    /// they wouldn't line up with the copy on screen.
+    ///
+    /// This method should only be called as part of the prepaint phase of element drawing.
+    pub(crate) fn prepaint_scaled<R>(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        scale: f32,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.invalidator.debug_assert_prepaint();
+
+        let hitboxes_index = self.next_frame.hitboxes.len();
+        let refreshing = mem::replace(&mut self.refreshing, true);
+        // Replaces rather than intersects the current mask, which is in scaled coordinates.
+        self.content_mask_stack.push(ContentMask {
+            bounds: Bounds::new(bounds.origin, bounds.size / scale),
+        });
+        let result = f(self);
+        self.content_mask_stack.pop();
+        self.refreshing = refreshing;
+        self.next_frame.hitboxes.truncate(hitboxes_index);
+        result
+    }
+
+    /// Paints contents prepainted with [`Window::prepaint_scaled`] into a separate scene, then
+    /// inserts them scaled by `scale` about the origin of `bounds` and clipped to `bounds`.
+    /// Returns the inserted operations, so that they can be replayed with
+    /// [`Window::replay_scaled`] on frames where the contents aren't painted again.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub(crate) fn paint_scaled(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        scale: f32,
+        f: impl FnOnce(&mut Self),
+    ) -> Vec<PaintOperation> {
+        self.invalidator.debug_assert_paint();
+
+        let scale_factor = self.scale_factor();
+        let content_mask = ContentMask { bounds }
+            .intersect(&self.content_mask())
+            .scale(scale_factor);
+        let scene = mem::take(&mut self.next_frame.scene);
+        let refreshing = mem::replace(&mut self.refreshing, true);
+        self.content_mask_stack.push(ContentMask {
+            bounds: Bounds::new(bounds.origin, bounds.size / scale),
+        });
+        f(self);
+        self.content_mask_stack.pop();
+        self.refreshing = refreshing;
+        let contents = mem::replace(&mut self.next_frame.scene, scene);
+
+        let origin = bounds.origin.scale(scale_factor);
+        let operations = contents
+            .paint_operations
+            .iter()
+            .map(|operation| operation.scaled(origin, scale, &content_mask))
+            .collect::<Vec<_>>();
+        self.next_frame.scene.insert_operations(&operations);
+        operations
+    }
+
+    /// Inserts operations previously returned by [`Window::paint_scaled`] into the scene.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub(crate) fn replay_scaled(&mut self, operations: &[PaintOperation]) {
+        self.invalidator.debug_assert_paint();
+        self.next_frame.scene.insert_operations(operations);
+    }
+
     /// Paint one or more drop shadows into the scene for the next frame at the current z-index.
     ///
     /// This method should only be called as part of the paint phase of element drawing.
@@ -3067,6 +4128,7 @@ impl Window {
             let tile = self
                 .sprite_atlas
                 .get_or_insert_with(&params.clone().into(), &mut || {
+                    self.atlas_uploads.set(self.atlas_uploads.get() + 1);
                     let (size, bytes) = self.text_system().rasterize_glyph(&params)?;
                     Ok(Some((size, Cow::Owned(bytes))))
                 })?
@@ -3123,6 +4185,7 @@ impl Window {
             let tile = self
                 .sprite_atlas
                 .get_or_insert_with(&params.clone().into(), &mut || {
+                    self.atlas_uploads.set(self.atlas_uploads.get() + 1);
                     let (size, bytes) = self.text_system().rasterize_glyph(&params)?;
                     Ok(Some((size, Cow::Owned(bytes))))
                 })?
@@ -3137,7 +4200,7 @@ impl Window {
 
             self.next_frame.scene.insert_primitive(PolychromeSprite {
                 order: 0,
-                pad: 0,
+                peak_count: 0,
                 grayscale: false,
                 bounds,
                 corner_radii: Default::default(),
@@ -3181,6 +4244,7 @@ impl Window {
                     else {
                         return Ok(None);
                     };
+                    self.atlas_uploads.set(self.atlas_uploads.get() + 1);
                     Ok(Some((size, Cow::Owned(bytes))))
                 })?
         else {
@@ -3238,6 +4302,7 @@ impl Window {
         let tile = self
             .sprite_atlas
             .get_or_insert_with(&params.into(), &mut || {
+                self.atlas_uploads.set(self.atlas_uploads.get() + 1);
                 Ok(Some((
                     data.size(frame_index),
                     Cow::Borrowed(
@@ -3253,7 +4318,7 @@ impl Window {
 
         self.next_frame.scene.insert_primitive(PolychromeSprite {
             order: 0,
-            pad: 0,
+            peak_count: 0,
             grayscale,
             bounds: bounds
                 .map_origin(|origin| origin.floor())
@@ -3300,6 +4365,119 @@ impl Window {
         Ok(())
     }
 
+    /// Allocate a texture in the sprite atlas that stays alive until the returned handle is
+    /// dropped and can be updated in place with [`StreamingTexture::update`]. The texture
+    /// starts out transparent.
+    pub fn create_streaming_texture(
+        &mut self,
+        size: Size<DevicePixels>,
+    ) -> Result<StreamingTexture> {
+        let key = AtlasKey::Image(RenderImageParams {
+            image_id: ImageId::next(),
+            frame_index: 0,
+        });
+        let bytes = vec![0; size.width.0.max(0) as usize * size.height.0.max(0) as usize * 4];
+        self.sprite_atlas.get_or_insert_with(&key, &mut || {
+            self.atlas_uploads.set(self.atlas_uploads.get() + 1);
+            Ok(Some((size, Cow::Borrowed(bytes.as_slice()))))
+        })?;
+        Ok(StreamingTexture {
+            key,
+            size,
+            atlas: self.sprite_atlas.clone(),
+        })
+    }
+
+    /// Paint the `source` region of a streaming texture, in device pixels relative to the
+    /// texture's origin, into `bounds`.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn paint_streaming_texture(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        corner_radii: Corners<Pixels>,
+        texture: &StreamingTexture,
+        source: Bounds<DevicePixels>,
+    ) -> Result<()> {
+        self.invalidator.debug_assert_paint();
+
+        let Some(mut tile) = self
+            .sprite_atlas
+            .get_or_insert_with(&texture.key, &mut || Ok(None))?
+        else {
+            return Ok(());
+        };
+        tile.bounds = Bounds::new(tile.bounds.origin + source.origin, source.size);
+
+        let scale_factor = self.scale_factor();
+        let bounds = bounds.scale(scale_factor);
+        let content_mask = self.content_mask().scale(scale_factor);
+        let corner_radii = corner_radii.scale(scale_factor);
+        let opacity = self.element_opacity();
+        self.next_frame.scene.insert_primitive(PolychromeSprite {
+            order: 0,
+            peak_count: 0,
+            grayscale: false,
+            bounds: bounds
+                .map_origin(|origin| origin.floor())
+                .map_size(|size| size.ceil()),
+            content_mask,
+            corner_radii,
+            tile,
+            opacity,
+        });
+        Ok(())
+    }
+
+    /// Paint a waveform whose peaks are stored in a streaming texture. The peaks are reduced
+    /// to one `(min, max)` extent per pixel column on the GPU, so the cost of painting doesn't
+    /// depend on how many peaks there are.
+    ///
+    /// The texture must be one pixel tall. Its first pixel holds the waveform's color, its
+    /// second the index of the oldest peak in the red and green bytes (high byte first), and
+    /// the remaining pixels are a ring of peaks in which each pixel stores the minimum in its
+    /// red and green bytes and the maximum in its blue and alpha bytes, as 16-bit values
+    /// mapping `-1.0..=1.0` to the bottom and top of `bounds`. Peaks are drawn oldest first,
+    /// starting from the oldest index and wrapping around, and `peak_count` of them are shown.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn paint_waveform(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        texture: &StreamingTexture,
+        peak_count: usize,
+    ) -> Result<()> {
+        self.invalidator.debug_assert_paint();
+
+        let capacity = (texture.size.width.0 as usize).saturating_sub(2);
+        let peak_count = peak_count.min(capacity);
+        if peak_count == 0 || texture.size.height.0 != 1 {
+            return Ok(());
+        }
+        let Some(tile) = self
+            .sprite_atlas
+            .get_or_insert_with(&texture.key, &mut || Ok(None))?
+        else {
+            return Ok(());
+        };
+
+        let scale_factor = self.scale_factor();
+        let bounds = bounds.scale(scale_factor);
+        let content_mask = self.content_mask().scale(scale_factor);
+        let opacity = self.element_opacity();
+        self.next_frame.scene.insert_primitive(PolychromeSprite {
+            order: 0,
+            peak_count: peak_count as u32,
+            grayscale: false,
+            bounds,
+            content_mask,
+            corner_radii: Corners::default(),
+            tile,
+            opacity,
+        });
+        Ok(())
+    }
+
     /// Add a node to the layout tree for the current frame. Takes the `Style` of the element for which
     /// layout is being requested, along with the layout ids of any children. This method is called during
     /// calls to the [`Element::request_layout`] trait method and enables any element to participate in layout.
@@ -3319,12 +4497,66 @@ impl Window {
         let rem_size = self.rem_size();
         let scale_factor = self.scale_factor();
 
-        self.layout_engine.as_mut().unwrap().request_layout(
+        #[cfg(any(test, feature = "test-support"))]
+        let layout_style = LayoutTreeStyle::from(&style);
+        let layout_id = self.layout_engine.as_mut().unwrap().request_layout(
             style,
             rem_size,
             scale_factor,
             &cx.layout_id_buffer,
-        )
+        );
+        #[cfg(any(test, feature = "test-support"))]
+        self.next_frame
+            .layout_tree
+            .record_style(layout_id, layout_style);
+        layout_id
+    }
+
+    /// Lay out an element's children with `f`, then record the z-index that orders the returned
+    /// layout among its siblings: its own z-index if it has one, and otherwise the highest
+    /// z-index among its descendants unless it starts a stacking context.
+    pub(crate) fn with_z_index(
+        &mut self,
+        z_index: Option<i32>,
+        stacking_context: bool,
+        f: impl FnOnce(&mut Self) -> LayoutId,
+    ) -> LayoutId {
+        self.z_index_scopes.push(0);
+        let layout_id = f(self);
+        let descendants = self.z_index_scopes.pop().unwrap_or_default();
+
+        let z_index = match z_index {
+            Some(z_index) => z_index,
+            None if stacking_context => 0,
+            None => descendants,
+        };
+        if z_index != 0 {
+            self.layout_z_indices.insert(layout_id, z_index);
+        }
+        if let Some(parent) = self.z_index_scopes.last_mut() {
+            *parent = (*parent).max(z_index);
+        }
+        layout_id
+    }
+
+    /// Returns the order in which to paint the elements with the given layouts, or `None` if
+    /// none of them has a z-index and they should be painted in order.
+    pub(crate) fn z_index_order(&self, layout_ids: &[LayoutId]) -> Option<SmallVec<[usize; 8]>> {
+        if !layout_ids
+            .iter()
+            .any(|layout_id| self.layout_z_indices.contains_key(layout_id))
+        {
+            return None;
+        }
+
+        let mut order = (0..layout_ids.len()).collect::<SmallVec<[usize; 8]>>();
+        order.sort_by_key(|ix| {
+            self.layout_z_indices
+                .get(&layout_ids[*ix])
+                .copied()
+                .unwrap_or_default()
+        });
+        Some(order)
     }
 
     /// Add a node to the layout tree for the current frame. Instead of taking a `Style` and children,
@@ -3344,10 +4576,18 @@ impl Window {
 
         let rem_size = self.rem_size();
         let scale_factor = self.scale_factor();
-        self.layout_engine
+        #[cfg(any(test, feature = "test-support"))]
+        let layout_style = LayoutTreeStyle::from(&style);
+        let layout_id = self
+            .layout_engine
             .as_mut()
             .unwrap()
-            .request_measured_layout(style, rem_size, scale_factor, measure)
+            .request_measured_layout(style, rem_size, scale_factor, measure);
+        #[cfg(any(test, feature = "test-support"))]
+        self.next_frame
+            .layout_tree
+            .record_style(layout_id, layout_style);
+        layout_id
     }
 
     /// Compute the layout for the given id within the given available space.
@@ -3371,19 +4611,41 @@ impl Window {
     /// Obtain the bounds computed for the given LayoutId relative to the window. This method will usually be invoked by
     /// GPUI itself automatically in order to pass your element its `Bounds` automatically.
     ///
-    /// This method should only be called as part of element drawing.
-    pub fn layout_bounds(&mut self, layout_id: LayoutId) -> Bounds<Pixels> {
+    /// This method should only be called as part of element drawing.
+    pub fn layout_bounds(&mut self, layout_id: LayoutId) -> Bounds<Pixels> {
+        self.invalidator.debug_assert_prepaint();
+
+        let scale_factor = self.scale_factor();
+        let mut bounds = self
+            .layout_engine
+            .as_mut()
+            .unwrap()
+            .layout_bounds(layout_id, scale_factor)
+            .map(Into::into);
+        bounds.origin += self.element_offset();
+        bounds
+    }
+
+    /// Set the distance from the top of the given layout node to its first text baseline, which
+    /// [`Styled::align_baseline`](crate::Styled::align_baseline) aligns. Text elements set this
+    /// automatically; nodes without one use their first child's baseline, or their bottom edge.
+    pub fn set_layout_baseline(&mut self, layout_id: LayoutId, baseline: Pixels) {
+        self.layout_engine
+            .as_mut()
+            .unwrap()
+            .set_baseline(layout_id, baseline);
+    }
+
+    /// Obtain the distance from the top of the given layout node to its first text baseline.
+    ///
+    /// This method should only be called as part of element drawing, after layout.
+    pub fn layout_baseline(&self, layout_id: LayoutId) -> Pixels {
         self.invalidator.debug_assert_prepaint();
 
-        let scale_factor = self.scale_factor();
-        let mut bounds = self
-            .layout_engine
-            .as_mut()
+        self.layout_engine
+            .as_ref()
             .unwrap()
-            .layout_bounds(layout_id, scale_factor)
-            .map(Into::into);
-        bounds.origin += self.element_offset();
-        bounds
+            .baseline(layout_id, self.scale_factor())
     }
 
     /// This method should be called during `prepaint`. You can use
@@ -3564,6 +4826,56 @@ impl Window {
         ));
     }
 
+    /// Register a shortcut on the window for the next frame, which calls the given listener when the
+    /// keystroke is typed while the current node is on the focused dispatch path. Shortcuts of
+    /// nodes closer to the focused node take precedence. Key bindings whose context is set by a
+    /// node inside the current one take precedence over the shortcut, which in turn takes
+    /// precedence over all other key bindings. Unlike key bindings, shortcuts only consist of a
+    /// single keystroke.
+    ///
+    /// This is a fairly low-level method, so prefer using event handlers on elements unless you have
+    /// a specific need to register a global listener.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn on_shortcut(
+        &mut self,
+        keystroke: Keystroke,
+        listener: impl Fn(&KeyDownEvent, &mut Window, &mut App) + 'static,
+    ) {
+        self.invalidator.debug_assert_paint();
+
+        self.next_frame.dispatch_tree.on_shortcut(
+            KeybindingKeystroke::from_keystroke(keystroke),
+            Rc::new(listener),
+        );
+    }
+
+    /// Register a modifiers changed event listener for the next frame, which is called while the
+    /// given hitbox is hovered, whether or not it contains the focused element. This backs
+    /// [`InteractiveElement::on_hover_modifiers_changed`](crate::InteractiveElement::on_hover_modifiers_changed),
+    /// so that the element under the mouse can react to modifier changes without the mouse moving.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub(crate) fn on_hovered_modifiers_changed(
+        &mut self,
+        hitbox: &Hitbox,
+        listener: impl Fn(&ModifiersChangedEvent, &mut Window, &mut App) + 'static,
+    ) {
+        self.invalidator.debug_assert_paint();
+
+        let hitbox = hitbox.clone();
+        self.next_frame.mouse_listeners.push(Some(Box::new(
+            move |event: &dyn Any, phase: DispatchPhase, window: &mut Window, cx: &mut App| {
+                if let Some(event) = event.downcast_ref()
+                    && phase.bubble()
+                    && hitbox.is_hovered(window)
+                {
+                    listener(event, window, cx)
+                }
+            },
+        )));
+    }
+
     /// Register a listener to be called when the given focus handle or one of its descendants receives focus.
     /// This does not fire if the given focus handle - or one of its descendants - was previously focused.
     /// Returns a subscription and persists until the subscription is dropped.
@@ -3666,10 +4978,144 @@ impl Window {
             .unwrap_or_else(|| action.name().to_string())
     }
 
+    /// Returns whether low-latency input is enabled, see [`Window::set_low_latency_input`].
+    pub fn low_latency_input(&self) -> bool {
+        self.low_latency_input
+    }
+
+    /// Enable or disable low-latency input for this window, for surfaces like knobs, sliders
+    /// and embedded editors where the delay between input and its effect on screen matters
+    /// more than the cost of extra frames.
+    ///
+    /// When enabled, input that invalidates the window is painted and presented right away,
+    /// rather than waiting for the platform to request the next frame. Once the window has
+    /// painted since the last frame request, further mouse moves and pixel scrolls are
+    /// coalesced and dispatched just before the next frame is drawn, so that the frame shows
+    /// the latest cursor and scroll positions without re-rendering for every event.
+    pub fn set_low_latency_input(&mut self, enabled: bool) {
+        self.low_latency_input = enabled;
+    }
+
+    fn dispatch_platform_input(
+        &mut self,
+        event: PlatformInput,
+        cx: &mut App,
+    ) -> DispatchEventResult {
+        let event = if self.low_latency_input && self.painted_since_frame_request {
+            match self.latch_input(event) {
+                Some(event) => event,
+                None => return DispatchEventResult::default(),
+            }
+        } else {
+            event
+        };
+        self.flush_latched_input(cx);
+        self.dispatch_event(event, cx)
+    }
+
+    /// Holds a mouse move or pixel scroll until the next frame, merging it with the input that
+    /// is already held. Returns events that can't be latched.
+    fn latch_input(&mut self, event: PlatformInput) -> Option<PlatformInput> {
+        let latched = match (self.latched_input.take(), event) {
+            (None, PlatformInput::MouseMove(event)) => PlatformInput::MouseMove(event),
+            (None, PlatformInput::ScrollWheel(event))
+                if matches!(event.delta, ScrollDelta::Pixels(_))
+                    && matches!(event.touch_phase, TouchPhase::Moved) =>
+            {
+                PlatformInput::ScrollWheel(event)
+            }
+            (Some(PlatformInput::MouseMove(previous)), PlatformInput::MouseMove(event))
+                if previous.pressed_button == event.pressed_button
+                    && previous.modifiers == event.modifiers =>
+            {
+                PlatformInput::MouseMove(event)
+            }
+            (Some(PlatformInput::ScrollWheel(previous)), PlatformInput::ScrollWheel(event))
+                if previous.modifiers == event.modifiers
+                    && matches!(event.touch_phase, TouchPhase::Moved) =>
+            {
+                match (previous.delta, event.delta) {
+                    (ScrollDelta::Pixels(previous_delta), ScrollDelta::Pixels(delta)) => {
+                        PlatformInput::ScrollWheel(ScrollWheelEvent {
+                            delta: ScrollDelta::Pixels(previous_delta + delta),
+                            ..event
+                        })
+                    }
+                    _ => {
+                        self.latched_input = Some(PlatformInput::ScrollWheel(previous));
+                        return Some(PlatformInput::ScrollWheel(event));
+                    }
+                }
+            }
+            (previous, event) => {
+                self.latched_input = previous;
+                return Some(event);
+            }
+        };
+        self.latched_input = Some(latched);
+        None
+    }
+
+    /// Dispatches the input held by [`Window::latch_input`], if any.
+    pub(crate) fn flush_latched_input(&mut self, cx: &mut App) {
+        if let Some(event) = self.latched_input.take() {
+            self.dispatch_event(event, cx);
+        }
+    }
+
+    fn paint_on_input(&mut self, cx: &mut App) {
+        if !self.low_latency_input
+            || self.painted_since_frame_request
+            || !self.invalidator.is_dirty()
+            || !self.invalidator.not_drawing()
+            || cx.adaptive_frame_interval().is_some()
+        {
+            return;
+        }
+
+        let arena_clear_needed = self.draw(cx);
+        self.present();
+        arena_clear_needed.clear();
+    }
+
     /// Dispatch a mouse or keyboard event on the window.
     #[profiling::function]
     pub fn dispatch_event(&mut self, event: PlatformInput, cx: &mut App) -> DispatchEventResult {
+        let event = self.unzoom_input(event);
         self.last_input_timestamp.set(Instant::now());
+        cx.last_input_time = cx.background_executor.now();
+        cx.user_input_observers
+            .clone()
+            .retain(&(), |callback| callback(cx));
+        cx.history_trigger = Some(event.history_trigger());
+
+        if self.forward_drag_event(&event, cx) {
+            return DispatchEventResult {
+                propagate: false,
+                default_prevented: false,
+            };
+        }
+
+        // Hosts of embedded windows forward key events, but not necessarily modifier changes, so
+        // synthesize those from the modifiers of the forwarded keystrokes.
+        if self.embedded {
+            let modifiers = match &event {
+                PlatformInput::KeyDown(key_down) => Some(key_down.keystroke.modifiers),
+                PlatformInput::KeyUp(key_up) => Some(key_up.keystroke.modifiers),
+                _ => None,
+            };
+            if let Some(modifiers) = modifiers
+                && modifiers != self.modifiers
+            {
+                self.dispatch_event(
+                    PlatformInput::ModifiersChanged(ModifiersChangedEvent {
+                        modifiers,
+                        capslock: self.capslock,
+                    }),
+                    cx,
+                );
+            }
+        }
 
         // Track whether this input was keyboard-based for focus-visible styling
         self.last_input_modality = match &event {
@@ -3684,22 +5130,27 @@ impl Window {
         cx.propagate_event = true;
         // Handlers may set this to true by calling `prevent_default`.
         self.default_prevented = false;
+        // Handlers may add to this by calling `stop_scroll_propagation`.
+        self.stopped_scroll_axes.clear();
 
         let event = match event {
             // Track the mouse position with our own state, since accessing the platform
             // API for the mouse position can only occur on the main thread.
             PlatformInput::MouseMove(mouse_move) => {
                 self.mouse_position = mouse_move.position;
+                self.pressed_button = mouse_move.pressed_button;
                 self.modifiers = mouse_move.modifiers;
                 PlatformInput::MouseMove(mouse_move)
             }
             PlatformInput::MouseDown(mouse_down) => {
                 self.mouse_position = mouse_down.position;
+                self.pressed_button = Some(mouse_down.button);
                 self.modifiers = mouse_down.modifiers;
                 PlatformInput::MouseDown(mouse_down)
             }
             PlatformInput::MouseUp(mouse_up) => {
                 self.mouse_position = mouse_up.position;
+                self.pressed_button = None;
                 self.modifiers = mouse_up.modifiers;
                 PlatformInput::MouseUp(mouse_up)
             }
@@ -3766,6 +5217,14 @@ impl Window {
             self.dispatch_mouse_event(any_mouse_event, cx);
         } else if let Some(any_key_event) = event.keyboard_event() {
             self.dispatch_key_event(any_key_event, cx);
+
+            // Modifier changes are also delivered to hovered elements that opted in with
+            // `on_hover_modifiers_changed`, so that they can react without the mouse moving.
+            if let PlatformInput::ModifiersChanged(modifiers_changed) = &event
+                && cx.propagate_event
+            {
+                self.dispatch_mouse_event(modifiers_changed, cx);
+            }
         }
 
         DispatchEventResult {
@@ -3774,6 +5233,54 @@ impl Window {
         }
     }
 
+    /// While something is being dragged, the platform keeps delivering mouse events to the
+    /// window in which the drag started, even when the cursor is over another window. Forward
+    /// those events to the app's window under the cursor, so that the drag can be dropped
+    /// there, returning whether the event was forwarded.
+    fn forward_drag_event(&mut self, event: &PlatformInput, cx: &mut App) -> bool {
+        if !cx.has_active_drag() {
+            return false;
+        }
+        let position = match event {
+            PlatformInput::MouseMove(event) => event.position,
+            PlatformInput::MouseUp(event) => event.position,
+            _ => return false,
+        };
+        if Bounds::new(Point::default(), self.viewport_size).contains(&position) {
+            return false;
+        }
+
+        let screen_position = self.bounds().origin + position.map(|c| c * self.content_zoom);
+        let windows = cx.window_stack().unwrap_or_else(|| cx.windows());
+        let Some((target, target_origin)) = windows.into_iter().find_map(|handle| {
+            // This window has been taken out of the app while it is being updated.
+            let window = cx.windows.get(handle.window_id())?.as_ref()?;
+            let bounds = window.bounds();
+            bounds
+                .contains(&screen_position)
+                .then_some((handle, bounds.origin))
+        }) else {
+            return false;
+        };
+
+        let mut event = event.clone();
+        match &mut event {
+            PlatformInput::MouseMove(event) => event.position = screen_position - target_origin,
+            PlatformInput::MouseUp(event) => event.position = screen_position - target_origin,
+            _ => {}
+        }
+        // Stop drawing the dragged element in this window.
+        self.refresh();
+        cx.defer(move |cx| {
+            target
+                .update(cx, |_, window, cx| {
+                    window.dispatch_event(event, cx);
+                })
+                .log_err();
+        });
+        true
+    }
+
     fn dispatch_mouse_event(&mut self, event: &dyn Any, cx: &mut App) {
         let hit_test = self.rendered_frame.hit_test(self.mouse_position());
         if hit_test != self.mouse_hit_test {
@@ -3816,12 +5323,21 @@ impl Window {
         if cx.has_active_drag() {
             if event.is::<MouseMoveEvent>() {
                 // If this was a mouse move event, redraw the window so that the
-                // active drag can follow the mouse cursor.
+                // active drag can follow the mouse cursor, and redraw the window
+                // it came from if the drag moved over from another window.
                 self.refresh();
+                if let Some(previous_window) = cx.active_drag_window.replace(self.handle)
+                    && previous_window != self.handle
+                    && let Some(Some(previous_window)) =
+                        cx.windows.get_mut(previous_window.window_id())
+                {
+                    previous_window.refresh();
+                }
             } else if event.is::<MouseUpEvent>() {
                 // If this was a mouse up event, cancel the active drag and redraw
                 // the window.
                 cx.active_drag = None;
+                cx.active_drag_window = None;
                 self.refresh();
             }
         }
@@ -3882,6 +5398,19 @@ impl Window {
             return;
         }
 
+        if let Some(key_down_event) = event.downcast_ref::<KeyDownEvent>()
+            && self.pending_input.is_none()
+            && let Some(listener) = self
+                .rendered_frame
+                .dispatch_tree
+                .shortcut_listener(&keystroke, &dispatch_path)
+        {
+            listener(key_down_event, self, cx);
+            cx.propagate_event = false;
+            self.dispatch_keystroke_observers(event, None, self.context_stack(), cx);
+            return;
+        }
+
         let mut currently_pending = self.pending_input.take().unwrap_or_default();
         if currently_pending.focus.is_some() && currently_pending.focus != self.focus {
             currently_pending = PendingInput::default();
@@ -4134,7 +5663,32 @@ impl Window {
         action: &dyn Action,
         cx: &mut App,
     ) {
+        let replacement = match cx.intercept_action(action) {
+            ActionInterception::Continue => None,
+            ActionInterception::Replace(replacement) => Some(replacement),
+            ActionInterception::Block => {
+                cx.propagate_event = false;
+                return;
+            }
+        };
+        let interceptors = self.action_interceptors.clone();
+        let replacement = match run_action_interceptors(
+            &interceptors,
+            action,
+            replacement,
+            |interceptor, action| interceptor(action, self, cx),
+        ) {
+            ActionInterception::Continue => None,
+            ActionInterception::Replace(replacement) => Some(replacement),
+            ActionInterception::Block => {
+                cx.propagate_event = false;
+                return;
+            }
+        };
+        let action = replacement.as_deref().unwrap_or(action);
+
         let dispatch_path = self.rendered_frame.dispatch_tree.dispatch_path(node_id);
+        cx.history_trigger = Some(SharedString::new_static(action.name()));
 
         // Capture phase for global actions.
         cx.propagate_event = true;
@@ -4277,6 +5831,12 @@ impl Window {
     /// Present a platform dialog.
     /// The provided message will be presented, along with buttons for each answer.
     /// When a button is clicked, the returned Receiver will receive the index of the clicked button.
+    ///
+    /// On macOS the dialog is shown as a sheet attached to this window, and on Windows as a task
+    /// dialog owned by it. The answer at [`PromptButton::default_index`] is the default button,
+    /// activated with enter, and the one at [`PromptButton::cancel_index`] is activated with
+    /// escape. Embedded windows, and platforms without native dialogs, render the prompt inside
+    /// the window instead, with the same keys.
     pub fn prompt<T>(
         &mut self,
         level: PromptLevel,
@@ -4299,13 +5859,15 @@ impl Window {
             .collect::<Vec<_>>();
 
         let receiver = match &prompt_builder {
-            PromptBuilder::Default => self
+            // Native dialogs run a nested modal loop, which would block the host application
+            // of an embedded window, so those always get a prompt rendered by GPUI instead.
+            PromptBuilder::Default if !self.embedded => self
                 .platform_window
                 .prompt(level, message, detail, &answers)
                 .unwrap_or_else(|| {
                     self.build_custom_prompt(&prompt_builder, level, message, detail, &answers, cx)
                 }),
-            PromptBuilder::Custom(_) => {
+            PromptBuilder::Default | PromptBuilder::Custom(_) => {
                 self.build_custom_prompt(&prompt_builder, level, message, detail, &answers, cx)
             }
         };
@@ -4465,15 +6027,125 @@ impl Window {
 
     /// Register a callback that can interrupt the closing of the current window based the returned boolean.
     /// If the callback returns false, the window won't be closed.
+    ///
+    /// Only one such callback can be registered per window, replacing the previous one. Use
+    /// [`Window::on_close_requested`] to register handlers that need to wait on a prompt.
     pub fn on_window_should_close(
         &self,
-        cx: &App,
+        _cx: &App,
         f: impl Fn(&mut Window, &mut App) -> bool + 'static,
     ) {
-        let mut cx = self.to_async(cx);
-        self.platform_window.on_should_close(Box::new(move || {
-            cx.update(|window, cx| f(window, cx)).unwrap_or(true)
-        }))
+        self.should_close_handler.replace(Some(Rc::new(f)));
+    }
+
+    /// Register a handler to be invoked when the user or the platform asks to close the window,
+    /// which can veto or delay closing, e.g. until the user answered an unsaved changes prompt.
+    ///
+    /// The window closes once the futures returned by all handlers resolve to true, and stays open
+    /// if any of them resolves to false. Further close requests are ignored while handlers are
+    /// pending.
+    pub fn on_close_requested<Fut>(
+        &self,
+        mut handler: impl FnMut(&mut Window, &mut App) -> Fut + 'static,
+    ) -> Subscription
+    where
+        Fut: 'static + Future<Output = bool>,
+    {
+        let (subscription, activate) = self.close_request_handlers.insert(
+            (),
+            Box::new(move |window, cx| handler(window, cx).boxed_local()),
+        );
+        activate();
+        subscription
+    }
+
+    /// Close the window as if the user had asked to, giving the handlers registered with
+    /// [`Window::on_window_should_close`] and [`Window::on_close_requested`] a chance to veto it.
+    pub fn request_close(&mut self, cx: &mut App) {
+        if self.close_requested(cx) {
+            self.remove_window();
+        }
+    }
+
+    /// Returns whether the window should close right away. If close request handlers are still
+    /// pending, the window is removed once they all approve instead.
+    fn close_requested(&mut self, cx: &mut App) -> bool {
+        if self.pending_close_request.is_none() {
+            let mut approval = self.close_request_approval(cx);
+            if let Some(approved) = approval.as_mut().now_or_never() {
+                return approved;
+            }
+            self.track_close_request(approval, cx);
+        }
+        // The request may have been made by `App::request_quit`, which doesn't close the window
+        // by itself.
+        self.close_when_approved = true;
+        false
+    }
+
+    /// Returns a future that resolves to whether the close request handlers approved closing the
+    /// window, joining the pending close request if there is one rather than invoking them again.
+    pub(crate) fn shared_close_request_approval(
+        &mut self,
+        cx: &mut App,
+    ) -> LocalBoxFuture<'static, bool> {
+        if let Some(pending_close_request) = &self.pending_close_request {
+            return pending_close_request.clone().boxed_local();
+        }
+
+        let mut approval = self.close_request_approval(cx);
+        if let Some(approved) = approval.as_mut().now_or_never() {
+            return future::ready(approved).boxed_local();
+        }
+        self.track_close_request(approval, cx).boxed_local()
+    }
+
+    fn track_close_request(
+        &mut self,
+        approval: LocalBoxFuture<'static, bool>,
+        cx: &mut App,
+    ) -> Shared<LocalBoxFuture<'static, bool>> {
+        let approval = approval.shared();
+        self.pending_close_request = Some(approval.clone());
+        self.spawn(cx, {
+            let approval = approval.clone();
+            async move |cx| {
+                let approved = approval.await;
+                cx.update(|window, _| {
+                    window.pending_close_request = None;
+                    if mem::take(&mut window.close_when_approved) && approved {
+                        window.remove_window();
+                    }
+                })
+                .ok();
+            }
+        })
+        .detach();
+        approval
+    }
+
+    /// Invokes all close request handlers, returning a future that resolves to whether they all
+    /// approved closing the window.
+    fn close_request_approval(&mut self, cx: &mut App) -> LocalBoxFuture<'static, bool> {
+        let should_close = self.should_close_handler.borrow().clone();
+        if let Some(should_close) = should_close
+            && !should_close(self, cx)
+        {
+            return future::ready(false).boxed_local();
+        }
+
+        let mut approvals = Vec::new();
+        self.close_request_handlers.clone().retain(&(), |handler| {
+            approvals.push(handler(self, cx));
+            true
+        });
+        async move {
+            future::join_all(approvals)
+                .await
+                .into_iter()
+                .all(|approved| approved)
+        }
+        .boxed_local()
     }
 
     /// Register an action listener on this node for the next frame. The type of action
@@ -4525,6 +6197,19 @@ impl Window {
         self.platform_window.gpu_specs()
     }
 
+    /// Capture the GPU commands of the window's next frame for inspection in a graphics
+    /// debugger. Each kind of primitive is drawn in its own labeled group of commands.
+    ///
+    /// On macOS, this uses Metal's frame capture: run the application from Xcode, or set
+    /// `MTL_CAPTURE_ENABLED=1` to write a `.gputrace` document to the temporary directory.
+    /// On Windows, the application must have been launched from RenderDoc. Returns an error if
+    /// capturing isn't available.
+    pub fn trigger_gpu_capture(&mut self) -> Result<()> {
+        self.platform_window.trigger_gpu_capture()?;
+        self.refresh();
+        Ok(())
+    }
+
     /// Perform titlebar double-click action.
     /// This is macOS specific.
     pub fn titlebar_double_click(&self) {
@@ -4987,6 +6672,22 @@ impl AnyWindowHandle {
 
         cx.read_window(&view, read)
     }
+
+    /// Lay out, paint and present the window synchronously if it changed, after running the
+    /// callbacks registered with [`Window::on_next_frame`]. Windows opened with
+    /// [`FrameDriver::External`] only render when this is called, such as from the host's idle or
+    /// timer callback, which is necessary in hosts whose windows never receive vsync callbacks.
+    ///
+    /// This will fail if the window has been closed, or if it's called while the app is being
+    /// updated or the window is already rendering a frame.
+    pub fn render_frame(self, cx: &mut AsyncApp) -> Result<()> {
+        let request_frame = self.update(cx, |_, window, _| window.request_frame.clone())?;
+        let mut request_frame = request_frame
+            .try_borrow_mut()
+            .context("the window is already rendering a frame")?;
+        request_frame(RequestFrameOptions::default());
+        Ok(())
+    }
 }
 
 impl HasWindowHandle for Window {
@@ -5248,3 +6949,306 @@ pub fn outline(
         border_style,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        AppContext as _, Bounds, Context, InteractiveElement, IntoElement, Modifiers, MouseButton,
+        MouseMoveEvent, Pixels, Point, Render, StatefulInteractiveElement, Styled, TestAppContext,
+        VisualContext, Window, WindowState, div, point, px, size,
+    };
+
+    #[crate::test]
+    fn test_simulated_displays(cx: &mut TestAppContext) {
+        let window = cx.add_empty_window().window_handle();
+        window
+            .update(cx, |_, window, _| assert_eq!(window.scale_factor(), 2.))
+            .unwrap();
+
+        let external_display = cx.add_display(
+            Bounds::new(point(px(1920.), px(0.)), size(px(2560.), px(1440.))),
+            1.,
+        );
+        cx.simulate_window_move(window, point(px(2000.), px(100.)));
+        window
+            .update(cx, |_, window, cx| {
+                assert_eq!(window.scale_factor(), 1.);
+                assert_eq!(
+                    window.display(cx).map(|display| display.id()),
+                    Some(external_display)
+                );
+            })
+            .unwrap();
+
+        cx.set_display_scale_factor(external_display, 1.5);
+        window
+            .update(cx, |_, window, _| assert_eq!(window.scale_factor(), 1.5))
+            .unwrap();
+
+        cx.remove_display(external_display);
+        window
+            .update(cx, |_, window, _| assert_eq!(window.scale_factor(), 2.))
+            .unwrap();
+    }
+
+    #[crate::test]
+    fn test_window_state(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let states = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|window, _| {
+            window.on_window_state_changed({
+                let states = states.clone();
+                move |state, _, _| states.borrow_mut().push(state)
+            })
+        });
+        assert_eq!(
+            cx.update(|window, _| window.window_state()),
+            WindowState::Normal
+        );
+
+        cx.update(|window, _| window.maximize_window());
+        cx.run_until_parked();
+        cx.update(|window, _| window.maximize_window());
+        cx.run_until_parked();
+        cx.update(|window, _| window.toggle_fullscreen());
+        cx.run_until_parked();
+        cx.update(|window, _| window.restore_window());
+        cx.run_until_parked();
+        assert_eq!(
+            cx.update(|window, _| window.window_state()),
+            WindowState::Maximized
+        );
+
+        cx.update(|window, _| window.minimize_window());
+        cx.run_until_parked();
+        cx.update(|window, _| window.restore_window());
+        cx.run_until_parked();
+        cx.update(|window, _| window.restore_window());
+        cx.run_until_parked();
+
+        assert_eq!(
+            *states.borrow(),
+            [
+                WindowState::Maximized,
+                WindowState::Fullscreen,
+                WindowState::Maximized,
+                WindowState::Minimized,
+                WindowState::Maximized,
+                WindowState::Normal,
+            ]
+        );
+    }
+
+    #[crate::test]
+    fn test_close_requested(cx: &mut TestAppContext) {
+        let window = cx.add_empty_window();
+        let handle = window.window_handle();
+        let requests = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = window.update(|window, _| {
+            window.on_close_requested({
+                let requests = requests.clone();
+                move |_, _| {
+                    let (tx, rx) = futures::channel::oneshot::channel();
+                    requests.borrow_mut().push(tx);
+                    async move { rx.await.unwrap_or(false) }
+                }
+            })
+        });
+
+        assert!(!window.simulate_close());
+        assert!(!window.simulate_close());
+        assert_eq!(requests.borrow().len(), 1);
+
+        requests.borrow_mut().pop().unwrap().send(false).unwrap();
+        window.run_until_parked();
+        assert!(window.windows().contains(&handle));
+
+        assert!(!window.simulate_close());
+        requests.borrow_mut().pop().unwrap().send(true).unwrap();
+        window.run_until_parked();
+        assert!(!window.windows().contains(&handle));
+    }
+
+    #[crate::test]
+    fn test_drag_between_windows(cx: &mut TestAppContext) {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Payload(usize);
+
+        struct DragPreview;
+        impl Render for DragPreview {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                div().size(px(10.))
+            }
+        }
+
+        struct Source;
+        impl Render for Source {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                div()
+                    .id("source")
+                    .size_full()
+                    .on_drag(Payload(7), |_, _, _, cx| cx.new(|_| DragPreview))
+            }
+        }
+
+        struct Target {
+            dropped: Option<Payload>,
+        }
+        impl Render for Target {
+            fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+                div()
+                    .size_full()
+                    .on_drop(cx.listener(|this, payload: &Payload, _, _| {
+                        this.dropped = Some(*payload);
+                    }))
+            }
+        }
+
+        let target = cx.add_window(|_, _| Target { dropped: None });
+        cx.simulate_window_resize(target.into(), size(px(500.), px(500.)));
+        cx.simulate_window_move(target.into(), point(px(1000.), px(0.)));
+        let (_, cx) = cx.add_window_view(|_, _| Source);
+        cx.simulate_resize(size(px(500.), px(500.)));
+        let source = cx.window_handle();
+        let source_origin = cx.update(|window, _| window.bounds().origin);
+        let target_origin = target
+            .update(cx, |_, window, _| window.bounds().origin)
+            .unwrap();
+
+        let modifiers = Modifiers::default();
+        cx.simulate_mouse_down(point(px(10.), px(10.)), MouseButton::Left, modifiers);
+        cx.simulate_mouse_move(point(px(30.), px(30.)), MouseButton::Left, modifiers);
+        assert_eq!(cx.update(|_, cx| cx.active_drag_window()), Some(source));
+
+        // The platform keeps sending mouse events to the source window while dragging.
+        let over_target = target_origin + point(px(20.), px(20.)) - source_origin;
+        cx.simulate_mouse_move(over_target, MouseButton::Left, modifiers);
+        assert_eq!(
+            cx.update(|_, cx| cx.active_drag_window()),
+            Some(target.into())
+        );
+
+        cx.simulate_mouse_up(over_target, MouseButton::Left, modifiers);
+        assert_eq!(
+            target.update(cx, |target, _, _| target.dropped).unwrap(),
+            Some(Payload(7))
+        );
+        assert_eq!(cx.update(|_, cx| cx.active_drag_window()), None);
+    }
+
+    #[crate::test]
+    fn test_content_zoom(cx: &mut TestAppContext) {
+        struct ZoomView {
+            hits: Rc<RefCell<Vec<Point<Pixels>>>>,
+        }
+
+        impl Render for ZoomView {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                let hits = self.hits.clone();
+                div()
+                    .size(px(100.))
+                    .debug_selector(|| "target".into())
+                    .on_mouse_down(MouseButton::Left, move |event, _, _| {
+                        hits.borrow_mut().push(event.position)
+                    })
+            }
+        }
+
+        let hits = Rc::new(RefCell::new(Vec::new()));
+        let (_, cx) = cx.add_window_view(|_, _| ZoomView { hits: hits.clone() });
+        cx.simulate_resize(size(px(800.), px(600.)));
+        cx.update(|window, _| window.set_content_zoom(2.));
+        cx.run_until_parked();
+
+        assert_eq!(
+            cx.update(|window, _| window.viewport_size()),
+            size(px(400.), px(300.))
+        );
+        assert_eq!(
+            cx.debug_bounds("target").unwrap().size,
+            size(px(100.), px(100.))
+        );
+
+        // Input positions are converted into the zoomed coordinates elements are laid out in.
+        cx.simulate_mouse_down(
+            point(px(150.), px(150.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert_eq!(hits.take(), [point(px(75.), px(75.))]);
+
+        cx.simulate_mouse_down(
+            point(px(250.), px(250.)),
+            MouseButton::Left,
+            Modifiers::none(),
+        );
+        assert!(hits.take().is_empty());
+    }
+
+    #[crate::test]
+    fn test_warp_cursor(cx: &mut TestAppContext) {
+        struct MoveView {
+            moves: Vec<Point<Pixels>>,
+        }
+
+        impl Render for MoveView {
+            fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+                div().size_full().on_mouse_move(
+                    cx.listener(|this, event: &MouseMoveEvent, _, _| {
+                        this.moves.push(event.position)
+                    }),
+                )
+            }
+        }
+
+        let warped = cx.add_window(|_, _| MoveView { moves: Vec::new() });
+        let active = cx.add_window(|_, _| MoveView { moves: Vec::new() });
+        active
+            .update(cx, |_, window, _| window.activate_window())
+            .unwrap();
+        cx.run_until_parked();
+
+        warped
+            .update(cx, |_, window, cx| {
+                window.set_content_zoom(2.);
+                assert!(window.warp_cursor(point(px(30.), px(40.))));
+                assert_eq!(window.mouse_position(), point(px(30.), px(40.)));
+                let platform_position = window
+                    .platform_window
+                    .as_test()
+                    .map(|window| window.0.lock().mouse_position);
+                assert_eq!(platform_position, Some(point(px(60.), px(80.))));
+                window.post_synthetic_mouse_move(cx);
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        // The synthetic move goes to the warped window rather than the active one.
+        let warped_moves = warped.update(cx, |view, _, _| view.moves.clone()).unwrap();
+        assert_eq!(warped_moves, [point(px(30.), px(40.))]);
+        let active_moves = active.update(cx, |view, _, _| view.moves.clone()).unwrap();
+        assert!(active_moves.is_empty());
+    }
+
+    #[crate::test]
+    fn test_embedded_resize(cx: &mut TestAppContext) {
+        let window = cx.add_empty_window().window_handle();
+        let requested = Rc::new(RefCell::new(Vec::new()));
+        window
+            .update(cx, |_, window, _| {
+                window.on_embedded_resize({
+                    let requested = requested.clone();
+                    move |size, _| requested.borrow_mut().push(size)
+                });
+                window.resize(size(px(300.), px(200.)));
+                window.resize(size(px(400.), px(250.)));
+            })
+            .unwrap();
+        assert_eq!(
+            *requested.borrow(),
+            [size(px(300.), px(200.)), size(px(400.), px(250.))]
+        );
+    }
+}
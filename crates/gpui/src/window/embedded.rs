@@ -0,0 +1,446 @@
+use anyhow::Result;
+use derive_more::Deref;
+
+use crate::{
+    AnyWindowHandle, App, AppContext, DispatchEventResult, HostWindowInfo, Pixels, PlatformInput,
+    Size,
+};
+
+/// A handle to a window embedded in a native window provided by its host, see
+/// [`WindowOptions::raw_window_handle`](crate::WindowOptions::raw_window_handle), which gives
+/// access to the operations that only apply to embedded windows. Get one from any handle to the
+/// window with [`AnyWindowHandle::embedded`]. Like other window handles, it doesn't keep the
+/// window alive.
+#[derive(Deref, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EmbeddedWindowHandle(AnyWindowHandle);
+
+impl AnyWindowHandle {
+    /// Get an [`EmbeddedWindowHandle`] for this window, if it's open and embedded.
+    pub fn embedded(&self, cx: &App) -> Option<EmbeddedWindowHandle> {
+        let window = cx.windows.get(self.id)?.as_deref()?;
+        window.is_embedded().then_some(EmbeddedWindowHandle(*self))
+    }
+}
+
+impl From<EmbeddedWindowHandle> for AnyWindowHandle {
+    fn from(handle: EmbeddedWindowHandle) -> Self {
+        handle.0
+    }
+}
+
+impl EmbeddedWindowHandle {
+    /// Tell the window that the host's native window was resized to the given content size. See
+    /// [`Window::notify_embedded_resize`](crate::Window::notify_embedded_resize).
+    pub fn notify_resize(&self, size: Size<Pixels>, cx: &mut impl AppContext) -> Result<()> {
+        self.0
+            .update(cx, |_, window, _| window.notify_embedded_resize(size))
+    }
+
+    /// Return the size closest to the one the host would like to resize the window to, that the
+    /// window accepts. See [`Window::adjust_embedded_size`](crate::Window::adjust_embedded_size).
+    pub fn adjust_size(
+        &self,
+        size: Size<Pixels>,
+        cx: &mut impl AppContext,
+    ) -> Result<Size<Pixels>> {
+        self.0
+            .update(cx, |_, window, cx| window.adjust_embedded_size(size, cx))
+    }
+
+    /// Tell the window that the host's native window moved to a display with a different scale
+    /// factor. See
+    /// [`Window::set_embedded_scale_factor`](crate::Window::set_embedded_scale_factor).
+    pub fn set_scale_factor(&self, scale_factor: f32, cx: &mut impl AppContext) -> Result<()> {
+        self.0.update(cx, |_, window, _| {
+            window.set_embedded_scale_factor(scale_factor)
+        })
+    }
+
+    /// The state of the host's native window, such as whether it's shown. See
+    /// [`Window::host_info`](crate::Window::host_info).
+    pub fn host_info(&self, cx: &mut impl AppContext) -> Result<Option<HostWindowInfo>> {
+        self.0.update(cx, |_, window, _| window.host_info())
+    }
+
+    /// Keep delivering mouse events to the window while the cursor is outside of its bounds. See
+    /// [`Window::start_mouse_capture`](crate::Window::start_mouse_capture).
+    pub fn start_mouse_capture(&self, cx: &mut impl AppContext) -> Result<bool> {
+        self.0
+            .update(cx, |_, window, _| window.start_mouse_capture())
+    }
+
+    /// Stop delivering mouse events from outside of the window's bounds. See
+    /// [`Window::end_mouse_capture`](crate::Window::end_mouse_capture).
+    pub fn end_mouse_capture(&self, cx: &mut impl AppContext) -> Result<()> {
+        self.0.update(cx, |_, window, _| window.end_mouse_capture())
+    }
+
+    /// Dispatch input that the host delivered through its own callbacks. See
+    /// [`Window::dispatch_embedded_input`](crate::Window::dispatch_embedded_input).
+    pub fn dispatch_input(
+        &self,
+        event: PlatformInput,
+        cx: &mut impl AppContext,
+    ) -> Result<DispatchEventResult> {
+        self.0.update(cx, |_, window, cx| {
+            window.dispatch_embedded_input(event, cx)
+        })
+    }
+
+    /// Move the window into another native window provided by the host. See
+    /// [`Window::reparent`](crate::Window::reparent).
+    pub fn reparent(
+        &self,
+        raw_handle: raw_window_handle::RawWindowHandle,
+        cx: &mut impl AppContext,
+    ) -> Result<()> {
+        self.0
+            .update(cx, |_, window, _| window.reparent(raw_handle))?
+    }
+
+    /// Take the window out of the host's native window and hide it, without closing it. See
+    /// [`Window::park_embedded`](crate::Window::park_embedded).
+    pub fn park(&self, cx: &mut impl AppContext) -> Result<()> {
+        self.0.update(cx, |_, window, _| window.park_embedded())?
+    }
+
+    /// Detach the window from the host's native window, and close it. See
+    /// [`Window::detach_embedded`](crate::Window::detach_embedded).
+    pub fn detach(&self, cx: &mut impl AppContext) -> Result<()> {
+        self.0.update(cx, |_, window, _| window.detach_embedded())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        num::NonZeroIsize,
+        rc::Rc,
+    };
+
+    use raw_window_handle::{RawWindowHandle, Win32WindowHandle};
+
+    use crate::{
+        AnyWindowHandle, AppContext as _, Context, FrameDriver, HostWindowInfo, IntoElement,
+        Render, Size, SizeConstraints, Styled, TestAppContext, VisualContext, Window, WindowHandle,
+        WindowOpenError, WindowOptions, div, px, size,
+    };
+
+    #[derive(Default)]
+    struct PluginEditor {
+        instance: usize,
+        edits: usize,
+        renders: Rc<Cell<usize>>,
+    }
+
+    impl Render for PluginEditor {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            self.renders.set(self.renders.get() + 1);
+            div().size_full()
+        }
+    }
+
+    /// A handle to a host's native window at the given address, which the test platform never
+    /// dereferences.
+    fn host(address: isize) -> RawWindowHandle {
+        Win32WindowHandle::new(NonZeroIsize::new(address).unwrap()).into()
+    }
+
+    fn open_editor(
+        cx: &mut TestAppContext,
+        options: WindowOptions,
+        editor: PluginEditor,
+    ) -> WindowHandle<PluginEditor> {
+        cx.update(|cx| cx.open_window(options, |_, cx| cx.new(|_| editor)))
+            .unwrap()
+    }
+
+    #[crate::test]
+    fn test_multiple_embedded_windows(cx: &mut TestAppContext) {
+        // Each instance of a plugin embeds its own editor in its own host view.
+        let open_instance = |cx: &mut TestAppContext, instance: usize| {
+            open_editor(
+                cx,
+                WindowOptions::for_embedded_window(host(0x1000 + instance as isize)),
+                PluginEditor {
+                    instance,
+                    ..Default::default()
+                },
+            )
+        };
+        let close_instance = |cx: &mut TestAppContext, window: WindowHandle<PluginEditor>| {
+            window
+                .update(cx, |_, window, _| window.remove_window())
+                .unwrap();
+            cx.run_until_parked();
+        };
+        let open_instances = |cx: &mut TestAppContext| {
+            let mut instances = cx.update(|cx| {
+                cx.windows()
+                    .into_iter()
+                    .filter_map(|window| window.downcast::<PluginEditor>())
+                    .map(|window| window.read_with(cx, |editor, _| editor.instance).unwrap())
+                    .collect::<Vec<_>>()
+            });
+            instances.sort();
+            instances
+        };
+
+        let first = open_instance(cx, 1);
+        let second = open_instance(cx, 2);
+        assert_eq!(open_instances(cx), [1, 2]);
+
+        close_instance(cx, first);
+        let third = open_instance(cx, 3);
+        assert_eq!(open_instances(cx), [2, 3]);
+
+        // A closed instance's host can embed a new editor.
+        let reopened = open_instance(cx, 1);
+        close_instance(cx, second);
+        assert_eq!(open_instances(cx), [1, 3]);
+
+        close_instance(cx, third);
+        close_instance(cx, reopened);
+        assert!(open_instances(cx).is_empty());
+    }
+
+    #[crate::test]
+    fn test_detach_embedded_window(cx: &mut TestAppContext) {
+        let window = open_editor(
+            cx,
+            WindowOptions::for_embedded_window(host(0x1000)),
+            PluginEditor::default(),
+        );
+        let editor = window.root(cx).unwrap().downgrade();
+
+        window
+            .update(cx, |_, window, _| {
+                assert!(window.is_embedded());
+                window.detach_embedded();
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        assert!(cx.update(|cx| cx.windows().is_empty()));
+        assert!(editor.upgrade().is_none());
+    }
+
+    #[crate::test]
+    fn test_reparent_embedded_window(cx: &mut TestAppContext) {
+        let window = open_editor(
+            cx,
+            WindowOptions::for_embedded_window(host(0x1000)),
+            PluginEditor::default(),
+        );
+        window.update(cx, |editor, _, _| editor.edits = 3).unwrap();
+
+        // Docking the editor into another host view keeps the window and its state.
+        window
+            .update(cx, |_, window, _| window.reparent(host(0x2000)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(window.read_with(cx, |editor, _| editor.edits).unwrap(), 3);
+
+        // Popping the editor out parks it while the host destroys its view, until it's attached
+        // to the new one.
+        window
+            .update(cx, |_, window, _| window.park_embedded())
+            .unwrap()
+            .unwrap();
+        window
+            .update(cx, |_, window, _| window.reparent(host(0x3000)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(window.read_with(cx, |editor, _| editor.edits).unwrap(), 3);
+
+        let (_, cx) = cx.add_window_view(|_, _| PluginEditor::default());
+        let error = cx.update(|window, _| window.reparent(host(0x3000)).unwrap_err());
+        assert!(matches!(
+            error.downcast_ref::<WindowOpenError>(),
+            Some(WindowOpenError::Unsupported(_))
+        ));
+    }
+
+    #[crate::test]
+    fn test_embedded_size_negotiation(cx: &mut TestAppContext) {
+        let window = open_editor(
+            cx,
+            WindowOptions::for_embedded_window(host(0x1000)),
+            PluginEditor::default(),
+        );
+        let embedded = cx.update(|cx| window.embedded(cx)).unwrap();
+        window
+            .update(cx, |_, window, _| {
+                window.set_size_constraints(SizeConstraints {
+                    min_size: Some(size(px(200.), px(100.))),
+                    max_size: Some(size(px(800.), px(600.))),
+                    aspect_ratio: Some(2.),
+                    ..Default::default()
+                })
+            })
+            .unwrap();
+
+        // Sizes the host asks for are clamped, keeping the aspect ratio, and the host resizes its
+        // view to the size the window accepted.
+        let negotiated = embedded.adjust_size(size(px(1000.), px(300.)), cx).unwrap();
+        assert_eq!(negotiated, size(px(800.), px(400.)));
+        embedded.notify_resize(negotiated, cx).unwrap();
+        let bounds = window.update(cx, |_, window, _| window.bounds()).unwrap();
+        assert_eq!(bounds.size, negotiated);
+
+        // The layout can snap the size before the constraints apply.
+        window
+            .update(cx, |_, window, _| {
+                window.on_host_resize_request(|size, _, _| Size {
+                    width: px((size.width.0 / 100.).floor() * 100.),
+                    height: size.height,
+                })
+            })
+            .unwrap();
+        assert_eq!(
+            embedded.adjust_size(size(px(650.), px(100.)), cx).unwrap(),
+            size(px(600.), px(300.))
+        );
+
+        let viewport_size = window
+            .update(cx, |_, window, _| {
+                window.set_size_constraints(SizeConstraints {
+                    resizable: false,
+                    ..window.size_constraints()
+                });
+                window.viewport_size()
+            })
+            .unwrap();
+        assert_eq!(
+            embedded.adjust_size(size(px(400.), px(200.)), cx).unwrap(),
+            viewport_size
+        );
+    }
+
+    #[crate::test]
+    fn test_externally_driven_frames(cx: &mut TestAppContext) {
+        let renders = Rc::new(Cell::new(0));
+        let window = open_editor(
+            cx,
+            WindowOptions::for_embedded_window(host(0x1000))
+                .with_frame_driver(FrameDriver::External),
+            PluginEditor {
+                renders: renders.clone(),
+                ..Default::default()
+            },
+        );
+        assert_eq!(renders.get(), 1);
+
+        // Changes wait for the host's next tick.
+        let next_frame = Rc::new(Cell::new(false));
+        window
+            .update(cx, |_, window, cx| {
+                let next_frame = next_frame.clone();
+                window.on_next_frame(move |_, _| next_frame.set(true));
+                cx.notify();
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(renders.get(), 1);
+        assert!(!next_frame.get());
+
+        let mut async_cx = cx.to_async();
+        AnyWindowHandle::from(window)
+            .render_frame(&mut async_cx)
+            .unwrap();
+        assert_eq!(renders.get(), 2);
+        assert!(next_frame.get());
+
+        // Nothing changed since the last frame.
+        AnyWindowHandle::from(window)
+            .render_frame(&mut async_cx)
+            .unwrap();
+        assert_eq!(renders.get(), 2);
+
+        // Rendering from inside an update fails instead of reentering the app.
+        let result = cx.update(|cx| AnyWindowHandle::from(window).render_frame(&mut cx.to_async()));
+        assert!(result.is_err());
+    }
+
+    #[crate::test]
+    fn test_embedded_window_handle(cx: &mut TestAppContext) {
+        let window = open_editor(
+            cx,
+            WindowOptions::for_embedded_window(host(0x1000)),
+            PluginEditor::default(),
+        );
+        let embedded = cx.update(|cx| window.embedded(cx)).unwrap();
+
+        embedded
+            .notify_resize(size(px(300.), px(200.)), cx)
+            .unwrap();
+        let bounds = window.update(cx, |_, window, _| window.bounds()).unwrap();
+        assert_eq!(bounds.size, size(px(300.), px(200.)));
+
+        embedded.detach(cx).unwrap();
+        cx.run_until_parked();
+        assert!(cx.update(|cx| window.embedded(cx)).is_none());
+        assert!(embedded.detach(cx).is_err());
+
+        let standalone = cx.add_empty_window().window_handle();
+        assert!(cx.update(|cx| standalone.embedded(cx)).is_none());
+    }
+
+    #[crate::test]
+    fn test_embedded_host_info(cx: &mut TestAppContext) {
+        let window = open_editor(
+            cx,
+            WindowOptions::for_embedded_window(host(0x1000)),
+            PluginEditor::default(),
+        );
+        let embedded = cx.update(|cx| window.embedded(cx)).unwrap();
+        assert_eq!(embedded.host_info(cx).unwrap(), None);
+
+        let reported = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = window
+            .update(cx, |_, window, _| {
+                let reported = reported.clone();
+                window.observe_host_info(move |window, _| {
+                    reported
+                        .borrow_mut()
+                        .push(window.host_info().unwrap().visible)
+                })
+            })
+            .unwrap();
+
+        // A plugin host hides the editor's window when it's closed, rather than destroying it.
+        let mut host_info = HostWindowInfo {
+            client_size: size(px(400.), px(300.)),
+            scale_factor: 2.,
+            visible: true,
+            minimized: false,
+            foreground: true,
+        };
+        let test_window = cx.test_window(window.into());
+        test_window.simulate_host_info_change(Some(host_info));
+        host_info.visible = false;
+        test_window.simulate_host_info_change(Some(host_info));
+
+        assert_eq!(*reported.borrow(), [true, false]);
+        assert_eq!(embedded.host_info(cx).unwrap(), Some(host_info));
+    }
+
+    #[crate::test]
+    fn test_embedded_mouse_capture(cx: &mut TestAppContext) {
+        let window = open_editor(
+            cx,
+            WindowOptions::for_embedded_window(host(0x1000)),
+            PluginEditor::default(),
+        );
+        let embedded = cx.update(|cx| window.embedded(cx)).unwrap();
+        let test_window = cx.test_window(window.into());
+        assert!(!test_window.0.lock().mouse_captured);
+
+        // A knob starts capturing the mouse when it's pressed, and ends on release.
+        assert!(embedded.start_mouse_capture(cx).unwrap());
+        assert!(test_window.0.lock().mouse_captured);
+        embedded.end_mouse_capture(cx).unwrap();
+        assert!(!test_window.0.lock().mouse_captured);
+    }
+}
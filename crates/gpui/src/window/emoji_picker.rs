@@ -0,0 +1,295 @@
+use crate::{
+    App, Context, InteractiveElement, IntoElement, MouseButton, ParentElement, Pixels, Point,
+    Render, StatefulInteractiveElement, Styled, anchored, div, opaque_grey,
+    prelude::FluentBuilder as _, px, white,
+};
+
+use super::Window;
+
+/// The characters offered by the [`FallbackEmojiPicker`], by category.
+const CATEGORIES: &[(&str, &[&str])] = &[
+    (
+        "Smileys",
+        &[
+            "😀", "😃", "😄", "😁", "😆", "😅", "😂", "🙂", "😉", "😊", "😇", "😍", "😘", "😋",
+            "😜", "🤔", "😐", "😑", "😏", "😬", "😌", "😴", "😎", "🤓", "😕", "😟", "😮", "😢",
+            "😭", "😱", "😡", "🥳",
+        ],
+    ),
+    (
+        "People",
+        &[
+            "👍", "👎", "👌", "✌️", "🤞", "👏", "🙌", "🙏", "👋", "💪", "👀", "🤝", "👉", "👈",
+            "👆", "👇",
+        ],
+    ),
+    (
+        "Nature",
+        &[
+            "🐶", "🐱", "🐭", "🦊", "🐻", "🐼", "🐸", "🐵", "🐦", "🐢", "🐝", "🌱", "🌲", "🌸",
+            "🌻", "🍀", "🌍", "🌙", "⭐", "☀️", "⛅", "🌈", "⚡", "❄️",
+        ],
+    ),
+    (
+        "Food",
+        &[
+            "🍎", "🍋", "🍌", "🍉", "🍇", "🍓", "🍒", "🥑", "🥕", "🌽", "🍞", "🧀", "🍕", "🍔",
+            "🌮", "🍣", "🍰", "🍪", "☕", "🍵", "🍺", "🍷",
+        ],
+    ),
+    (
+        "Objects",
+        &[
+            "⌚", "💻", "⌨️", "🖱️", "📱", "📷", "💡", "🔋", "📦", "📌", "📎", "✏️", "📝", "📅",
+            "🔒", "🔑", "🔨", "🔧", "⚙️", "🧪", "🚀", "🎉", "🎁", "🏆",
+        ],
+    ),
+    (
+        "Symbols",
+        &[
+            "❤️", "💔", "💯", "✅", "❌", "⚠️", "❓", "❗", "➕", "➖", "✖️", "➗", "→", "←", "↑",
+            "↓", "©", "®", "™", "°", "€", "£", "¥", "§",
+        ],
+    ),
+];
+
+/// The emoji and symbol picker GPUI renders inside the window, for embedded windows and on
+/// platforms without a native one, see [`Window::show_character_palette`].
+///
+/// Like a native picker, it doesn't take focus, so the text input it was shown for keeps
+/// receiving input, and clicking a character inserts it into that input. It's closed by clicking
+/// outside of it, or with [`Window::hide_character_palette`].
+pub struct FallbackEmojiPicker {
+    position: Point<Pixels>,
+    category: usize,
+}
+
+impl FallbackEmojiPicker {
+    pub(crate) fn new(position: Point<Pixels>) -> Self {
+        Self {
+            position,
+            category: 0,
+        }
+    }
+}
+
+impl Render for FallbackEmojiPicker {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (_, characters) = CATEGORIES[self.category];
+        let picker = div()
+            .id("emoji-picker")
+            .occlude()
+            .cursor_default()
+            .on_mouse_down_out(|_, window, _| window.hide_character_palette())
+            .w(px(288.))
+            .bg(white())
+            .border_1()
+            .border_color(opaque_grey(0.2, 0.5))
+            .rounded_lg()
+            .overflow_hidden()
+            .p_1()
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap_1()
+                    .pb_1()
+                    .border_b_1()
+                    .border_color(opaque_grey(0.2, 0.2))
+                    .children(
+                        CATEGORIES
+                            .iter()
+                            .enumerate()
+                            .map(|(ix, (name, characters))| {
+                                div()
+                                    .id(*name)
+                                    .px_1()
+                                    .rounded_xs()
+                                    .cursor_pointer()
+                                    .when(ix == self.category, |this| {
+                                        this.bg(opaque_grey(0.5, 0.2))
+                                    })
+                                    .child(characters[0])
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        this.category = ix;
+                                        cx.notify();
+                                    }))
+                            }),
+                    ),
+            )
+            .child(
+                div()
+                    .id("emoji-picker-characters")
+                    .flex()
+                    .flex_row()
+                    .flex_wrap()
+                    .max_h(px(192.))
+                    .overflow_y_scroll()
+                    .pt_1()
+                    .children(characters.iter().map(|character| {
+                        div()
+                            .id(*character)
+                            .size(px(34.))
+                            .flex()
+                            .items_center()
+                            .justify_center()
+                            .rounded_xs()
+                            .cursor_pointer()
+                            .hover(|style| style.bg(opaque_grey(0.5, 0.2)))
+                            .child(*character)
+                            // Handle the mouse down, so that nothing else reacts to it, such as
+                            // the text input moving its cursor.
+                            .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                            .on_click(move |_, window, cx| {
+                                window.insert_from_character_palette(character, cx);
+                            })
+                    })),
+            );
+
+        anchored()
+            .position(self.position)
+            .snap_to_window_with_margin(px(8.))
+            .child(picker)
+    }
+}
+
+impl Window {
+    /// Insert text chosen in the [`FallbackEmojiPicker`] into the focused text input, like a
+    /// native picker does, returning whether there was one.
+    pub(crate) fn insert_from_character_palette(&mut self, text: &str, cx: &mut App) -> bool {
+        let Some(mut input_handler) = self.platform_window.take_input_handler() else {
+            return false;
+        };
+        input_handler.dispatch_input(text, self, cx);
+        self.platform_window.set_input_handler(input_handler);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, ops::Range, rc::Rc};
+
+    use super::*;
+    use crate::{
+        Bounds, FocusHandle, InputHandler, TestAppContext, UTF16Selection, canvas, point, size,
+    };
+
+    struct Input {
+        focus_handle: FocusHandle,
+        text: Rc<RefCell<String>>,
+    }
+
+    struct InputTextHandler(Rc<RefCell<String>>);
+
+    impl InputHandler for InputTextHandler {
+        fn selected_text_range(
+            &mut self,
+            _: bool,
+            _: &mut Window,
+            _: &mut App,
+        ) -> Option<UTF16Selection> {
+            let len = self.0.borrow().encode_utf16().count();
+            Some(UTF16Selection {
+                range: len..len,
+                reversed: false,
+            })
+        }
+
+        fn marked_text_range(&mut self, _: &mut Window, _: &mut App) -> Option<Range<usize>> {
+            None
+        }
+
+        fn text_for_range(
+            &mut self,
+            _: Range<usize>,
+            _: &mut Option<Range<usize>>,
+            _: &mut Window,
+            _: &mut App,
+        ) -> Option<String> {
+            None
+        }
+
+        fn replace_text_in_range(
+            &mut self,
+            _: Option<Range<usize>>,
+            text: &str,
+            _: &mut Window,
+            _: &mut App,
+        ) {
+            self.0.borrow_mut().push_str(text);
+        }
+
+        fn replace_and_mark_text_in_range(
+            &mut self,
+            _: Option<Range<usize>>,
+            _: &str,
+            _: Option<Range<usize>>,
+            _: &mut Window,
+            _: &mut App,
+        ) {
+        }
+
+        fn unmark_text(&mut self, _: &mut Window, _: &mut App) {}
+
+        fn bounds_for_range(
+            &mut self,
+            _: Range<usize>,
+            _: &mut Window,
+            _: &mut App,
+        ) -> Option<Bounds<Pixels>> {
+            Some(Bounds::new(point(px(40.), px(10.)), size(px(1.), px(20.))))
+        }
+
+        fn character_index_for_point(
+            &mut self,
+            _: Point<Pixels>,
+            _: &mut Window,
+            _: &mut App,
+        ) -> Option<usize> {
+            None
+        }
+    }
+
+    impl Render for Input {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let focus_handle = self.focus_handle.clone();
+            let text = self.text.clone();
+            div().size(px(200.)).child(canvas(
+                |_, _, _| {},
+                move |_, _, window, cx| {
+                    window.handle_input(&focus_handle, InputTextHandler(text), cx)
+                },
+            ))
+        }
+    }
+
+    #[crate::test]
+    fn test_fallback_emoji_picker_inserts_into_focused_input(cx: &mut TestAppContext) {
+        let text = Rc::new(RefCell::new(String::from("hi")));
+        let (view, cx) = cx.add_window_view(|_, cx| Input {
+            focus_handle: cx.focus_handle(),
+            text: text.clone(),
+        });
+        cx.update(|window, cx| window.focus(&view.read(cx).focus_handle));
+        cx.run_until_parked();
+
+        cx.update(|window, cx| {
+            // The test platform has no native picker, so GPUI shows its own.
+            window.show_character_palette(cx);
+            assert!(window.is_showing_fallback_emoji_picker());
+            assert_eq!(
+                window.emoji_picker.as_ref().unwrap().read(cx).position,
+                point(px(40.), px(30.))
+            );
+        });
+        cx.run_until_parked();
+
+        cx.update(|window, cx| {
+            assert!(window.insert_from_character_palette("🎉", cx));
+            window.hide_character_palette();
+            assert!(!window.is_showing_fallback_emoji_picker());
+        });
+        assert_eq!(*text.borrow(), "hi🎉");
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    collections::VecDeque,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use smallvec::SmallVec;
+
+/// The refresh interval assumed until the platform reports a refresh rate or enough frames
+/// have been observed to measure one.
+const DEFAULT_FRAME_INTERVAL: Duration = Duration::from_nanos(16_666_667);
+
+/// Gaps between frame requests longer than this mean that the display link was paused, so
+/// they aren't counted towards the measured refresh interval.
+const MAX_MEASURED_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How much each new frame contributes to the measured refresh interval. Low enough to smooth
+/// out scheduling jitter, high enough to follow variable refresh rates within a few frames.
+const MEASUREMENT_WEIGHT: f32 = 0.25;
+
+/// How many of the most recent frame durations are kept for [`FrameTiming`].
+const FRAME_DURATION_HISTORY: usize = 8;
+
+/// Timing information for a frame, passed to callbacks registered with
+/// [`Window::on_next_frame_with_timing`](crate::Window::on_next_frame_with_timing).
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameTiming {
+    /// The time at which the platform requested this frame.
+    pub requested_at: Instant,
+    /// The time at which the frame is expected to reach the display, one frame interval after
+    /// it was requested.
+    pub predicted_present_time: Instant,
+    /// The expected time between frames, see [`Window::frame_interval`](crate::Window::frame_interval).
+    pub interval: Duration,
+    /// How long drawing the most recent frames took, from oldest to newest.
+    pub previous_frame_durations: SmallVec<[Duration; FRAME_DURATION_HISTORY]>,
+}
+
+/// A source of time for a window's animations, see
+/// [`Window::set_animation_clock`](crate::Window::set_animation_clock).
+///
+/// Plugin UIs can implement this on top of the host's audio transport, so that playhead cursors
+/// and beat-synced effects follow the host's sample clock rather than wall time.
+pub trait AnimationClock {
+    /// The current time of this clock, measured from an arbitrary but fixed origin. It's read
+    /// once at the start of each frame. If it stops advancing, animations pause; if it moves
+    /// backwards, animations that started after the new time restart.
+    fn now(&self) -> Duration;
+}
+
+impl<F: Fn() -> Duration> AnimationClock for F {
+    fn now(&self) -> Duration {
+        self()
+    }
+}
+
+/// The source of the timestamps of every window's frames, set with
+/// [`Application::with_frame_clock`](crate::Application::with_frame_clock).
+pub(crate) type FrameTimestampSource = Rc<dyn Fn() -> Instant>;
+
+/// Tracks the timing of a window's frames, so that animations step by the time that actually
+/// passes between frames rather than assuming a 60Hz display.
+pub(crate) struct FrameClock {
+    timestamp: Instant,
+    epoch: Instant,
+    animation_time: Duration,
+    animation_clock: Option<Box<dyn AnimationClock>>,
+    timestamp_source: Option<FrameTimestampSource>,
+    last_request: Option<Instant>,
+    measured_interval: Option<Duration>,
+    display_refresh_rate: Option<f32>,
+    frame_durations: VecDeque<Duration>,
+}
+
+impl FrameClock {
+    pub(crate) fn new(display_refresh_rate: Option<f32>) -> Self {
+        let now = Instant::now();
+        Self {
+            timestamp: now,
+            epoch: now,
+            animation_time: Duration::ZERO,
+            animation_clock: None,
+            timestamp_source: None,
+            last_request: None,
+            measured_interval: None,
+            display_refresh_rate: display_refresh_rate.filter(|rate| *rate > 0.),
+            frame_durations: VecDeque::with_capacity(FRAME_DURATION_HISTORY),
+        }
+    }
+
+    /// Read frame timestamps from the given source rather than [`Instant::now`].
+    pub(crate) fn with_timestamp_source(mut self, source: Option<FrameTimestampSource>) -> Self {
+        if let Some(source) = &source {
+            self.epoch = source();
+            self.timestamp = self.epoch;
+        }
+        self.timestamp_source = source;
+        self
+    }
+
+    /// The current time of the frame timestamp source.
+    pub(crate) fn now(&self) -> Instant {
+        match &self.timestamp_source {
+            Some(source) => source(),
+            None => Instant::now(),
+        }
+    }
+
+    /// The time at which the current frame started drawing.
+    pub(crate) fn timestamp(&self) -> Instant {
+        self.timestamp
+    }
+
+    /// The time of the animation clock when the current frame started drawing. Without a
+    /// custom clock, this is the time elapsed since the window was created.
+    pub(crate) fn animation_time(&self) -> Duration {
+        self.animation_time
+    }
+
+    pub(crate) fn set_animation_clock(&mut self, animation_clock: Option<Box<dyn AnimationClock>>) {
+        self.animation_clock = animation_clock;
+    }
+
+    /// The refresh rate reported by the platform for the window's display.
+    pub(crate) fn display_refresh_rate(&self) -> Option<f32> {
+        self.display_refresh_rate
+    }
+
+    pub(crate) fn set_display_refresh_rate(&mut self, display_refresh_rate: Option<f32>) {
+        let display_refresh_rate = display_refresh_rate.filter(|rate| *rate > 0.);
+        if self.display_refresh_rate != display_refresh_rate {
+            self.display_refresh_rate = display_refresh_rate;
+            // The window moved to another display, so earlier measurements no longer apply.
+            self.measured_interval = None;
+        }
+    }
+
+    /// The expected time between frames: the measured interval between recent frame requests
+    /// if the platform is requesting frames continuously, otherwise the interval implied by the
+    /// display's refresh rate.
+    pub(crate) fn interval(&self) -> Duration {
+        self.measured_interval
+            .or_else(|| {
+                self.display_refresh_rate
+                    .map(|rate| Duration::from_secs_f32(1. / rate))
+            })
+            .unwrap_or(DEFAULT_FRAME_INTERVAL)
+    }
+
+    /// Record the start of a frame that is about to be drawn.
+    pub(crate) fn begin_frame(&mut self, now: Instant) {
+        self.timestamp = now;
+        self.animation_time = match &self.animation_clock {
+            Some(animation_clock) => animation_clock.now(),
+            None => now.saturating_duration_since(self.epoch),
+        };
+    }
+
+    /// Record that the frame started by [`FrameClock::begin_frame`] finished drawing.
+    pub(crate) fn end_frame(&mut self, now: Instant) {
+        if self.frame_durations.len() == FRAME_DURATION_HISTORY {
+            self.frame_durations.pop_front();
+        }
+        self.frame_durations
+            .push_back(now.saturating_duration_since(self.timestamp));
+    }
+
+    /// Timing information for the most recently requested frame.
+    pub(crate) fn timing(&self) -> FrameTiming {
+        let requested_at = self.last_request.unwrap_or(self.timestamp);
+        let interval = self.interval();
+        FrameTiming {
+            requested_at,
+            predicted_present_time: requested_at + interval,
+            interval,
+            previous_frame_durations: self.frame_durations.iter().copied().collect(),
+        }
+    }
+
+    /// Record that the platform requested a frame at the given time, whether or not the window
+    /// needed to be redrawn.
+    pub(crate) fn frame_requested(&mut self, now: Instant) {
+        if let Some(last_request) = self.last_request.replace(now) {
+            let elapsed = now.saturating_duration_since(last_request);
+            if !elapsed.is_zero() && elapsed <= MAX_MEASURED_FRAME_INTERVAL {
+                self.measured_interval = Some(match self.measured_interval {
+                    Some(interval) => {
+                        interval.mul_f32(1. - MEASUREMENT_WEIGHT)
+                            + elapsed.mul_f32(MEASUREMENT_WEIGHT)
+                    }
+                    None => elapsed,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+    use crate::{Context, IntoElement, Render, Styled, TestAppContext, Window, div};
+
+    fn assert_interval(clock: &FrameClock, expected: Duration) {
+        let difference = clock.interval().as_secs_f32() - expected.as_secs_f32();
+        assert!(
+            difference.abs() < 1e-5,
+            "expected an interval of {expected:?}, got {:?}",
+            clock.interval()
+        );
+    }
+
+    #[test]
+    fn test_frame_clock_interval() {
+        let mut clock = FrameClock::new(None);
+        assert_eq!(clock.interval(), DEFAULT_FRAME_INTERVAL);
+
+        clock.set_display_refresh_rate(Some(120.));
+        assert_interval(&clock, Duration::from_secs(1) / 120);
+
+        // Frames requested at 120Hz.
+        let start = Instant::now();
+        let frame = Duration::from_micros(8_333);
+        for ix in 0..10 {
+            clock.frame_requested(start + frame * ix);
+        }
+        assert_interval(&clock, frame);
+
+        // The display drops to 60Hz, as ProMotion displays do when content is mostly static.
+        for ix in 5..35 {
+            clock.frame_requested(start + frame * 2 * ix);
+        }
+        assert_interval(&clock, frame * 2);
+
+        // A long pause isn't mistaken for a slow refresh rate.
+        clock.frame_requested(start + frame * 70 + Duration::from_secs(1));
+        assert_interval(&clock, frame * 2);
+    }
+
+    #[test]
+    fn test_frame_timing() {
+        let mut clock = FrameClock::new(Some(100.));
+        let start = Instant::now();
+        clock.frame_requested(start);
+
+        let timing = clock.timing();
+        assert_eq!(timing.requested_at, start);
+        assert_eq!(timing.predicted_present_time, start + timing.interval);
+        assert!(timing.previous_frame_durations.is_empty());
+
+        // Only the most recent frame durations are kept.
+        for ix in 0..FRAME_DURATION_HISTORY as u32 + 2 {
+            let frame_start = start + Duration::from_millis(10) * ix;
+            clock.begin_frame(frame_start);
+            clock.end_frame(frame_start + Duration::from_millis(1) * (ix + 1));
+        }
+        let durations = clock.timing().previous_frame_durations;
+        assert_eq!(durations.len(), FRAME_DURATION_HISTORY);
+        assert_eq!(durations[0], Duration::from_millis(3));
+        assert_eq!(
+            durations[FRAME_DURATION_HISTORY - 1],
+            Duration::from_millis(FRAME_DURATION_HISTORY as u64 + 2)
+        );
+    }
+
+    #[test]
+    fn test_animation_clock() {
+        let mut clock = FrameClock::new(None);
+        let start = clock.epoch;
+        clock.begin_frame(start + Duration::from_millis(40));
+        assert_eq!(clock.animation_time(), Duration::from_millis(40));
+
+        // A custom clock is sampled at the start of each frame, whatever the wall time.
+        let samples = Rc::new(Cell::new(48_000u64));
+        clock.set_animation_clock(Some(Box::new({
+            let samples = samples.clone();
+            move || Duration::from_secs_f64(samples.get() as f64 / 48_000.)
+        })));
+        clock.begin_frame(start + Duration::from_millis(50));
+        assert_eq!(clock.animation_time(), Duration::from_secs(1));
+
+        // The transport is stopped.
+        clock.begin_frame(start + Duration::from_millis(60));
+        assert_eq!(clock.animation_time(), Duration::from_secs(1));
+
+        samples.set(24_000);
+        clock.begin_frame(start + Duration::from_millis(70));
+        assert_eq!(clock.animation_time(), Duration::from_millis(500));
+
+        clock.set_animation_clock(None);
+        clock.begin_frame(start + Duration::from_millis(80));
+        assert_eq!(clock.animation_time(), Duration::from_millis(80));
+    }
+
+    #[test]
+    fn test_timestamp_source() {
+        // A host transport at a fixed position, mapped onto `Instant`s.
+        let origin = Instant::now();
+        let position = Rc::new(Cell::new(Duration::from_secs(10)));
+        let mut clock = FrameClock::new(None).with_timestamp_source(Some(Rc::new({
+            let position = position.clone();
+            move || origin + position.get()
+        })));
+        assert_eq!(clock.now(), origin + Duration::from_secs(10));
+
+        position.set(Duration::from_secs(12));
+        clock.begin_frame(clock.now());
+        assert_eq!(clock.timestamp(), origin + Duration::from_secs(12));
+        assert_eq!(clock.animation_time(), Duration::from_secs(2));
+    }
+
+    #[crate::test]
+    fn test_injected_frame_clock(cx: &mut TestAppContext) {
+        struct Playhead;
+
+        impl Render for Playhead {
+            fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+                div().size_full()
+            }
+        }
+
+        // A host transport, whose position is mapped onto `Instant`s.
+        let origin = Instant::now();
+        let transport = Rc::new(Cell::new(Duration::from_secs(4)));
+        cx.update(|cx| {
+            let transport = transport.clone();
+            cx.set_frame_clock(move || origin + transport.get())
+        });
+
+        let (view, cx) = cx.add_window_view(|_, _| Playhead);
+        transport.set(Duration::from_secs(5));
+        view.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        cx.update(|window, _| {
+            assert_eq!(window.frame_timestamp(), origin + Duration::from_secs(5));
+            assert_eq!(window.animation_time(), Duration::from_secs(1));
+        });
+    }
+}
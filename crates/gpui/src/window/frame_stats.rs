@@ -0,0 +1,192 @@
+use crate::{
+    AnyElement, App, AvailableSpace, IntoElement, ParentElement, PrimitiveBatch, Scene, Styled,
+    div, hsla, point, px, white,
+};
+
+use super::Window;
+
+/// Statistics about the contents of the last frame a window drew. See [`Window::frame_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// The number of quads, which includes backgrounds and borders.
+    pub quads: usize,
+    /// The number of box shadows.
+    pub shadows: usize,
+    /// The number of text underlines and strikethroughs.
+    pub underlines: usize,
+    /// The number of vector paths.
+    pub paths: usize,
+    /// The total number of vertices in the frame's vector paths.
+    pub path_vertices: usize,
+    /// The number of glyphs and icons, which are drawn as monochrome sprites.
+    pub monochrome_sprites: usize,
+    /// The number of images and emoji, which are drawn as polychrome sprites.
+    pub polychrome_sprites: usize,
+    /// The number of platform surfaces, such as video frames.
+    pub surfaces: usize,
+    /// The number of draw calls the renderer issues for the frame: one per batch of consecutive
+    /// primitives of the same kind, plus one per batch of paths to rasterize them.
+    pub draw_calls: usize,
+    /// The number of glyphs and images that were rasterized and uploaded to the sprite atlas
+    /// while drawing the frame.
+    pub atlas_uploads: usize,
+    /// The number of sprite atlas textures currently resident on the GPU.
+    pub atlas_textures: usize,
+}
+
+impl FrameStats {
+    pub(crate) fn new(scene: &Scene, atlas_uploads: usize, atlas_textures: usize) -> Self {
+        let draw_calls = scene
+            .batches()
+            .map(|batch| match batch {
+                PrimitiveBatch::Paths(_) => 2,
+                _ => 1,
+            })
+            .sum();
+        Self {
+            quads: scene.quads.len(),
+            shadows: scene.shadows.len(),
+            underlines: scene.underlines.len(),
+            paths: scene.paths.len(),
+            path_vertices: scene.paths.iter().map(|path| path.vertices.len()).sum(),
+            monochrome_sprites: scene.monochrome_sprites.len(),
+            polychrome_sprites: scene.polychrome_sprites.len(),
+            surfaces: scene.surfaces.len(),
+            draw_calls,
+            atlas_uploads,
+            atlas_textures,
+        }
+    }
+}
+
+impl Window {
+    /// Statistics about the contents of the last frame this window drew, to help find out what
+    /// makes a UI expensive to render.
+    pub fn frame_stats(&self) -> FrameStats {
+        FrameStats::new(
+            &self.rendered_frame.scene,
+            self.rendered_frame_atlas_uploads,
+            self.sprite_atlas.texture_count(),
+        )
+    }
+
+    /// Show the [`FrameStats`] of each frame in an overlay in the top right corner of the
+    /// window. The overlay is drawn on top of the window's contents and adds a few primitives
+    /// of its own to the counts.
+    pub fn set_frame_stats_overlay(&mut self, visible: bool) {
+        if self.frame_stats_overlay != visible {
+            self.frame_stats_overlay = visible;
+            self.refresh();
+        }
+    }
+
+    /// Whether the frame stats overlay is visible. See [`Window::set_frame_stats_overlay`].
+    pub fn frame_stats_overlay(&self) -> bool {
+        self.frame_stats_overlay
+    }
+
+    pub(super) fn prepaint_frame_stats_overlay(&mut self, cx: &mut App) -> Option<AnyElement> {
+        if !self.frame_stats_overlay {
+            return None;
+        }
+
+        let stats = self.frame_stats();
+        let mut element = div()
+            .flex()
+            .flex_col()
+            .p_1()
+            .bg(hsla(0., 0., 0., 0.75))
+            .text_color(white())
+            .text_xs()
+            .child(format!("draw calls: {}", stats.draw_calls))
+            .child(format!("quads: {}", stats.quads))
+            .child(format!("shadows: {}", stats.shadows))
+            .child(format!("underlines: {}", stats.underlines))
+            .child(format!(
+                "paths: {} ({} vertices)",
+                stats.paths, stats.path_vertices
+            ))
+            .child(format!("glyphs and icons: {}", stats.monochrome_sprites))
+            .child(format!("images: {}", stats.polychrome_sprites))
+            .child(format!("surfaces: {}", stats.surfaces))
+            .child(format!("atlas uploads: {}", stats.atlas_uploads))
+            .child(format!("atlas textures: {}", stats.atlas_textures))
+            .into_any_element();
+
+        let size = element.layout_as_root(AvailableSpace::min_size(), self, cx);
+        let origin = point(self.viewport_size.width - size.width, px(0.));
+        self.with_absolute_element_offset(origin, |window| element.prepaint(window, cx));
+        Some(element)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, Render, RenderImage, TestAppContext, img, red};
+    use image::{Frame, RgbaImage};
+    use smallvec::SmallVec;
+    use std::sync::Arc;
+
+    struct Swatches {
+        image: Arc<RenderImage>,
+    }
+
+    impl Render for Swatches {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .child(div().size(px(10.)).bg(red()))
+                .child(div().size(px(10.)).bg(red()))
+                .child(img(self.image.clone()).size(px(10.)))
+        }
+    }
+
+    fn add_swatches(cx: &mut TestAppContext) -> &mut crate::VisualTestContext {
+        let image = Arc::new(RenderImage::new(SmallVec::from_const([Frame::new(
+            RgbaImage::new(1, 1),
+        )])));
+        let (_, cx) = cx.add_window_view(|_, _| Swatches { image });
+        cx.run_until_parked();
+        cx
+    }
+
+    #[crate::test]
+    fn test_frame_stats(cx: &mut TestAppContext) {
+        let cx = add_swatches(cx);
+
+        let stats = cx.update(|window, _| window.frame_stats());
+        assert_eq!(stats.quads, 2);
+        assert_eq!(stats.polychrome_sprites, 1);
+        // The quads don't overlap the image, so they're drawn in a single batch before it.
+        assert_eq!(stats.draw_calls, 2);
+        assert_eq!(stats.atlas_uploads, 1);
+        assert_eq!(stats.atlas_textures, 1);
+
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+        let stats = cx.update(|window, _| window.frame_stats());
+        assert_eq!(stats.polychrome_sprites, 1);
+        assert_eq!(stats.atlas_uploads, 0);
+        assert_eq!(stats.atlas_textures, 1);
+    }
+
+    #[crate::test]
+    fn test_frame_stats_overlay(cx: &mut TestAppContext) {
+        let cx = add_swatches(cx);
+        assert!(!cx.update(|window, _| window.frame_stats_overlay()));
+
+        cx.update(|window, _| window.set_frame_stats_overlay(true));
+        cx.run_until_parked();
+        let stats = cx.update(|window, _| {
+            assert!(window.frame_stats_overlay());
+            window.frame_stats()
+        });
+        // The overlay's background.
+        assert_eq!(stats.quads, 3);
+
+        cx.update(|window, _| window.set_frame_stats_overlay(false));
+        cx.run_until_parked();
+        assert_eq!(cx.update(|window, _| window.frame_stats()).quads, 2);
+    }
+}
@@ -0,0 +1,313 @@
+use std::{any::type_name, fmt, ops::Range};
+
+use collections::FxHashMap;
+use serde::Serialize;
+
+use crate::{
+    Bounds, Display, ElementId, FlexDirection, LayoutId, Overflow, Pixels, Point, Position, Style,
+};
+
+use super::Window;
+
+/// A snapshot of the elements a window laid out in its last frame, with their bounds and the
+/// styles that determine how their children are laid out. See [`Window::debug_layout_tree`].
+///
+/// The tree formats as an indented outline with one element per line, so that it can be
+/// compared against a golden file and any layout change shows up as a readable diff. It can
+/// also be serialized, for example as JSON.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct LayoutTree {
+    /// The elements laid out as roots, in the order they were drawn: the window's root view,
+    /// followed by any deferred elements, prompts, drag previews and tooltips.
+    pub roots: Vec<LayoutTreeNode>,
+}
+
+/// An element in a [`LayoutTree`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LayoutTreeNode {
+    /// The name of the element's type without module paths, such as `Stateful<Div>`. Wrapper
+    /// elements that share their child's layout, such as views and components, are folded into
+    /// the element they render.
+    pub element: String,
+    /// The element's id, if it has one.
+    pub id: Option<String>,
+    /// The bounds of the element in window coordinates.
+    pub bounds: Bounds<Pixels>,
+    /// The element's layout styles.
+    pub style: LayoutTreeStyle,
+    /// The element's children, in the order they were drawn.
+    pub children: Vec<LayoutTreeNode>,
+}
+
+/// The styles of an element in a [`LayoutTree`] that most affect layout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct LayoutTreeStyle {
+    /// How the element lays out its children.
+    pub display: Display,
+    /// The main axis of a flex container.
+    pub flex_direction: FlexDirection,
+    /// Whether the element is positioned relative to its place in the layout or absolutely.
+    pub position: Position,
+    /// How the element handles children that overflow it.
+    pub overflow: Point<Overflow>,
+}
+
+impl From<&Style> for LayoutTreeStyle {
+    fn from(style: &Style) -> Self {
+        Self {
+            display: style.display,
+            flex_direction: style.flex_direction,
+            position: style.position,
+            overflow: style.overflow,
+        }
+    }
+}
+
+impl fmt::Display for LayoutTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for root in &self.roots {
+            root.fmt_indented(f, 0)?;
+        }
+        Ok(())
+    }
+}
+
+impl LayoutTreeNode {
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}{}", "", self.element, indent = depth * 2)?;
+        if let Some(id) = &self.id {
+            write!(f, " #{id}")?;
+        }
+        let Bounds { origin, size } = self.bounds;
+        write!(
+            f,
+            " ({}, {}) {}x{}",
+            origin.x.0, origin.y.0, size.width.0, size.height.0
+        )?;
+
+        let style = &self.style;
+        match style.display {
+            Display::Flex => write!(f, " flex {:?}", style.flex_direction)?,
+            display => write!(f, " {display:?}")?,
+        }
+        if style.position == Position::Absolute {
+            write!(f, " absolute")?;
+        }
+        if style.overflow.x != Overflow::Visible {
+            write!(f, " overflow-x:{:?}", style.overflow.x)?;
+        }
+        if style.overflow.y != Overflow::Visible {
+            write!(f, " overflow-y:{:?}", style.overflow.y)?;
+        }
+        writeln!(f)?;
+
+        for child in &self.children {
+            child.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// The layout tree of a frame as it is drawn, stored in drawing order with each node pointing
+/// at its parent.
+#[derive(Default)]
+pub(crate) struct LayoutTreeRecording {
+    nodes: Vec<RecordedLayoutNode>,
+    stack: Vec<usize>,
+    styles: FxHashMap<LayoutId, LayoutTreeStyle>,
+}
+
+struct RecordedLayoutNode {
+    parent: Option<usize>,
+    layout_id: LayoutId,
+    node: LayoutTreeNode,
+}
+
+impl LayoutTreeRecording {
+    pub(crate) fn clear(&mut self) {
+        self.nodes.clear();
+        self.stack.clear();
+        self.styles.clear();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub(crate) fn record_style(&mut self, layout_id: LayoutId, style: LayoutTreeStyle) {
+        self.styles.insert(layout_id, style);
+    }
+
+    /// Record that an element of type `E` is being prepainted, returning whether it was added
+    /// as a new node that must be popped once its children have been prepainted.
+    pub(crate) fn push<E>(
+        &mut self,
+        id: Option<&ElementId>,
+        layout_id: LayoutId,
+        bounds: Bounds<Pixels>,
+    ) -> bool {
+        let element = short_type_name(type_name::<E>());
+        let parent = self.stack.last().copied();
+        if let Some(parent) = parent.map(|parent| &mut self.nodes[parent])
+            && parent.layout_id == layout_id
+        {
+            parent.node.element = element;
+            if let Some(id) = id {
+                parent.node.id = Some(id.to_string());
+            }
+            return false;
+        }
+
+        self.stack.push(self.nodes.len());
+        self.nodes.push(RecordedLayoutNode {
+            parent,
+            layout_id,
+            node: LayoutTreeNode {
+                element,
+                id: id.map(ToString::to_string),
+                bounds,
+                style: self.styles.get(&layout_id).copied().unwrap_or_default(),
+                children: Vec::new(),
+            },
+        });
+        true
+    }
+
+    pub(crate) fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    /// Copy the nodes recorded for a range of prepaint state that is reused from the previous
+    /// frame, attaching their roots to the node currently being prepainted.
+    pub(crate) fn reuse(&mut self, previous: &Self, range: Range<usize>) {
+        let parent = self.stack.last().copied();
+        let offset = self.nodes.len();
+        for recorded in &previous.nodes[range.clone()] {
+            self.nodes.push(RecordedLayoutNode {
+                parent: match recorded.parent {
+                    Some(ix) if range.contains(&ix) => Some(ix - range.start + offset),
+                    _ => parent,
+                },
+                layout_id: recorded.layout_id,
+                node: recorded.node.clone(),
+            });
+        }
+    }
+
+    fn to_tree(&self) -> LayoutTree {
+        let mut nodes = self
+            .nodes
+            .iter()
+            .map(|recorded| recorded.node.clone())
+            .collect::<Vec<_>>();
+        let mut roots = Vec::new();
+        // Parents are always recorded before their children, so popping nodes from the end
+        // attaches each node before its parent is popped, in reverse drawing order.
+        while let Some(node) = nodes.pop() {
+            match self.nodes[nodes.len()].parent {
+                Some(parent) => nodes[parent].children.push(node),
+                None => roots.push(node),
+            }
+        }
+        roots.reverse();
+        for root in &mut roots {
+            reverse_children(root);
+        }
+        LayoutTree { roots }
+    }
+}
+
+fn reverse_children(node: &mut LayoutTreeNode) {
+    node.children.reverse();
+    for child in &mut node.children {
+        reverse_children(child);
+    }
+}
+
+/// Strips the module paths from a type name, including those of its generic arguments.
+fn short_type_name(name: &str) -> String {
+    let mut short_name = String::new();
+    let mut segment_start = 0;
+    for (ix, character) in name.char_indices() {
+        if matches!(
+            character,
+            '<' | '>' | ',' | ' ' | '&' | '(' | ')' | '[' | ']' | ';'
+        ) {
+            let segment = &name[segment_start..ix];
+            short_name.push_str(segment.rsplit("::").next().unwrap_or(segment));
+            short_name.push(character);
+            segment_start = ix + character.len_utf8();
+        }
+    }
+    let segment = &name[segment_start..];
+    short_name.push_str(segment.rsplit("::").next().unwrap_or(segment));
+    short_name
+}
+
+impl Window {
+    /// A snapshot of the elements laid out in the last frame this window drew, for catching
+    /// layout regressions by comparing it against a golden file. Unlike
+    /// [`VisualTestContext::debug_bounds`](crate::VisualTestContext::debug_bounds), this covers
+    /// every element, not just the ones with a debug selector.
+    pub fn debug_layout_tree(&self) -> LayoutTree {
+        self.rendered_frame.layout_tree.to_tree()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Context, IntoElement, ParentElement, Render, Styled, TestAppContext, Window, div, px, size,
+    };
+
+    use super::short_type_name;
+
+    struct Sidebar;
+
+    impl Render for Sidebar {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .id("root")
+                .size_full()
+                .flex()
+                .child(div().id("sidebar").w(px(100.)).h_full().flex().flex_col())
+                .child(
+                    div()
+                        .id("content")
+                        .flex_1()
+                        .overflow_y_hidden()
+                        .child(div().absolute().size(px(10.))),
+                )
+        }
+    }
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(
+            short_type_name("gpui::elements::div::Stateful<gpui::elements::div::Div>"),
+            "Stateful<Div>"
+        );
+        assert_eq!(
+            short_type_name("gpui::element::Component<(ui::Label, &str)>"),
+            "Component<(Label, &str)>"
+        );
+    }
+
+    #[crate::test]
+    fn test_debug_layout_tree(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_, _| Sidebar);
+        cx.simulate_resize(size(px(400.), px(300.)));
+        cx.run_until_parked();
+
+        let tree = cx.update(|window, _| window.debug_layout_tree());
+        assert_eq!(
+            tree.to_string(),
+            "\
+Stateful<Div> #root (0, 0) 400x300 flex Row
+  Stateful<Div> #sidebar (0, 0) 100x300 flex Column
+  Stateful<Div> #content (100, 0) 300x300 Block overflow-y:Hidden
+    Div (100, 0) 10x10 Block absolute
+"
+        );
+    }
+}
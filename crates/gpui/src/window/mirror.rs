@@ -0,0 +1,225 @@
+use anyhow::Result;
+
+use crate::{
+    AnyView, App, AppContext as _, Context, FocusHandle, InteractiveElement, IntoElement,
+    ParentElement, Render, Styled, Subscription, WindowHandle, WindowOptions, div,
+};
+
+use super::Window;
+
+/// Options for [`App::open_mirror_window`].
+#[derive(Debug, Default)]
+pub struct MirrorWindowOptions {
+    /// The options used to open the mirror window.
+    pub window: WindowOptions,
+    /// Keep the mirror window above other applications' windows, as a picture-in-picture
+    /// overlay. See [`Window::set_always_on_top`].
+    pub always_on_top: bool,
+    /// Whether mouse and keyboard input in the mirror window reaches the mirrored view.
+    /// When `false`, the mirror is display only.
+    pub route_input: bool,
+}
+
+/// The root view of a window opened with [`App::open_mirror_window`].
+pub struct MirrorView {
+    source: AnyView,
+    route_input: bool,
+    focus_handle: FocusHandle,
+    _focus_subscriptions: [Subscription; 2],
+}
+
+impl MirrorView {
+    fn new(
+        source: AnyView,
+        route_input: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let focus_handle = cx.focus_handle();
+        // The mirrored view may focus one of its elements in this window too, which would route
+        // keystrokes to it.
+        let focus_subscriptions = [
+            cx.on_focus_in(&focus_handle, window, |this, window, _| {
+                this.keep_focus_out_of_source(window)
+            }),
+            cx.on_blur(&focus_handle, window, |this, window, _| {
+                this.keep_focus_out_of_source(window)
+            }),
+        ];
+        Self {
+            source,
+            route_input,
+            focus_handle,
+            _focus_subscriptions: focus_subscriptions,
+        }
+    }
+
+    /// The view being mirrored.
+    pub fn source(&self) -> &AnyView {
+        &self.source
+    }
+
+    /// Whether input in the mirror window reaches the mirrored view.
+    pub fn routes_input(&self) -> bool {
+        self.route_input
+    }
+
+    /// Set whether input in the mirror window reaches the mirrored view.
+    pub fn set_route_input(&mut self, route_input: bool, cx: &mut Context<Self>) {
+        if self.route_input != route_input {
+            self.route_input = route_input;
+            cx.notify();
+        }
+    }
+
+    /// Keystrokes are dispatched to the focused element and its ancestors, so while input isn't
+    /// routed, focus is kept on the mirror's root, above the mirrored view.
+    fn keep_focus_out_of_source(&self, window: &mut Window) {
+        if !self.route_input && !self.focus_handle.is_focused(window) {
+            window.focus(&self.focus_handle);
+        }
+    }
+}
+
+impl Render for MirrorView {
+    fn render(&mut self, window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.keep_focus_out_of_source(window);
+        let mut root = div()
+            .relative()
+            .size_full()
+            .track_focus(&self.focus_handle)
+            .child(self.source.clone());
+        if !self.route_input {
+            root = root.child(div().absolute().top_0().left_0().size_full().occlude());
+        }
+        root
+    }
+}
+
+impl App {
+    /// Open a window that renders a live copy of `source`, for example to pop a meter or
+    /// visualizer out of an embedded plugin editor into a floating window.
+    ///
+    /// The same view is rendered in both windows, so any notification from it redraws both.
+    /// To mirror part of an element tree, move that part into its own view. The mirrored view
+    /// stays alive for as long as the mirror window is open.
+    pub fn open_mirror_window(
+        &mut self,
+        source: impl Into<AnyView>,
+        options: MirrorWindowOptions,
+    ) -> Result<WindowHandle<MirrorView>> {
+        let source = source.into();
+        let always_on_top = options.always_on_top;
+        let route_input = options.route_input;
+        self.open_window(options.window, move |window, cx| {
+            if always_on_top {
+                window.set_always_on_top(true);
+            }
+            cx.new(|cx| MirrorView::new(source, route_input, window, cx))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Entity, KeyDownEvent, Modifiers, StatefulInteractiveElement as _, TestAppContext,
+        VisualTestContext, point, px,
+    };
+
+    struct Meter {
+        focus_handle: FocusHandle,
+        clicks: usize,
+        keys: usize,
+    }
+
+    impl Render for Meter {
+        fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            // Grabs focus in whichever window the meter is rendered in.
+            window.focus(&self.focus_handle);
+            div()
+                .id("meter")
+                .track_focus(&self.focus_handle)
+                .size_full()
+                .on_click(cx.listener(|this, _, _, _| this.clicks += 1))
+                .on_key_down(cx.listener(|this, _: &KeyDownEvent, _, _| this.keys += 1))
+        }
+    }
+
+    fn open_mirror(
+        route_input: bool,
+        cx: &mut TestAppContext,
+    ) -> (Entity<Meter>, WindowHandle<MirrorView>) {
+        let meter = cx.new(|cx| Meter {
+            focus_handle: cx.focus_handle(),
+            clicks: 0,
+            keys: 0,
+        });
+        let mirror = cx
+            .update(|cx| {
+                cx.open_mirror_window(
+                    meter.clone(),
+                    MirrorWindowOptions {
+                        always_on_top: true,
+                        route_input,
+                        ..Default::default()
+                    },
+                )
+            })
+            .unwrap();
+        cx.run_until_parked();
+        (meter, mirror)
+    }
+
+    fn click_and_type(mirror: WindowHandle<MirrorView>, cx: &mut TestAppContext) {
+        let mut cx = VisualTestContext::from_window(mirror.into(), cx);
+        cx.simulate_click(point(px(10.), px(10.)), Modifiers::none());
+        cx.simulate_keystrokes("a b");
+        cx.run_until_parked();
+    }
+
+    #[crate::test]
+    fn test_mirror_routes_input(cx: &mut TestAppContext) {
+        let (meter, mirror) = open_mirror(true, cx);
+        click_and_type(mirror, cx);
+        meter.read_with(cx, |meter, _| {
+            assert_eq!(meter.clicks, 1);
+            assert_eq!(meter.keys, 2);
+        });
+    }
+
+    #[crate::test]
+    fn test_display_only_mirror_blocks_input(cx: &mut TestAppContext) {
+        let (meter, mirror) = open_mirror(false, cx);
+        click_and_type(mirror, cx);
+        meter.read_with(cx, |meter, _| {
+            assert_eq!(meter.clicks, 0);
+            assert_eq!(meter.keys, 0);
+        });
+
+        mirror
+            .update(cx, |mirror, _, cx| mirror.set_route_input(true, cx))
+            .unwrap();
+        cx.run_until_parked();
+        click_and_type(mirror, cx);
+        meter.read_with(cx, |meter, _| {
+            assert_eq!(meter.clicks, 1);
+            assert_eq!(meter.keys, 2);
+        });
+    }
+
+    #[crate::test]
+    fn test_mirror_always_on_top(cx: &mut TestAppContext) {
+        let (_, mirror) = open_mirror(false, cx);
+        mirror
+            .update(cx, |_, window, _| {
+                let always_on_top = window
+                    .platform_window
+                    .as_test()
+                    .map(|window| window.0.lock().always_on_top);
+                assert_eq!(always_on_top, Some(true));
+            })
+            .unwrap();
+    }
+}
@@ -4,8 +4,9 @@ use futures::channel::oneshot;
 
 use crate::{
     AnyView, App, AppContext as _, Context, Entity, EventEmitter, FocusHandle, Focusable,
-    InteractiveElement, IntoElement, ParentElement, PromptButton, PromptLevel, Render,
-    StatefulInteractiveElement, Styled, div, opaque_grey, white,
+    InteractiveElement, IntoElement, KeyDownEvent, ParentElement, PromptButton, PromptLevel,
+    Render, StatefulInteractiveElement, Styled, div, opaque_grey, prelude::FluentBuilder as _,
+    white,
 };
 
 use super::Window;
@@ -101,9 +102,21 @@ pub struct FallbackPromptRenderer {
 
 impl Render for FallbackPromptRenderer {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let default_index = PromptButton::default_index(&self.actions);
         let prompt = div()
             .cursor_default()
             .track_focus(&self.focus)
+            .on_key_down(cx.listener(|this, event: &KeyDownEvent, _, cx| {
+                let answer = match event.keystroke.key.as_str() {
+                    "enter" => PromptButton::default_index(&this.actions),
+                    "escape" => PromptButton::cancel_index(&this.actions),
+                    _ => None,
+                };
+                if let Some(answer) = answer {
+                    cx.emit(PromptResponse(answer));
+                    cx.stop_propagation();
+                }
+            }))
             .w_72()
             .bg(white())
             .rounded_lg()
@@ -133,6 +146,7 @@ impl Render for FallbackPromptRenderer {
                     .flex_row()
                     .justify_around()
                     .border_1()
+                    .when(Some(ix) == default_index, |this| this.border_2())
                     .border_color(opaque_grey(0.2, 0.5))
                     .mt_1()
                     .rounded_xs()
@@ -230,3 +244,68 @@ impl Deref for PromptBuilder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TestAppContext, VisualTestContext};
+
+    #[test]
+    fn test_prompt_button_indices() {
+        let answers = [
+            PromptButton::new("Don't Save"),
+            PromptButton::cancel("Cancel"),
+            PromptButton::ok("Save"),
+        ];
+        assert_eq!(PromptButton::default_index(&answers), Some(2));
+        assert_eq!(PromptButton::cancel_index(&answers), Some(1));
+
+        // Without an ok button, the first answer that isn't a cancel button is the default.
+        let answers = [PromptButton::cancel("Cancel"), PromptButton::new("Retry")];
+        assert_eq!(PromptButton::default_index(&answers), Some(1));
+
+        let answers = [PromptButton::cancel("Close")];
+        assert_eq!(PromptButton::default_index(&answers), None);
+        assert_eq!(PromptButton::cancel_index(&[PromptButton::ok("Ok")]), None);
+    }
+
+    struct EmptyView;
+
+    impl Render for EmptyView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    fn show_prompt(cx: &mut VisualTestContext) -> oneshot::Receiver<usize> {
+        let answers = [
+            PromptButton::new("Don't Save"),
+            PromptButton::cancel("Cancel"),
+            PromptButton::ok("Save"),
+        ];
+        let response = cx.update(|window, cx| {
+            window.prompt(PromptLevel::Warning, "Save changes?", None, &answers, cx)
+        });
+        cx.run_until_parked();
+        response
+    }
+
+    #[crate::test]
+    async fn test_fallback_prompt_keys(cx: &mut TestAppContext) {
+        cx.update(|cx| cx.set_prompt_builder(fallback_prompt_renderer));
+        let (_, cx) = cx.add_window_view(|_, _| EmptyView);
+
+        let response = show_prompt(cx);
+        cx.simulate_keystrokes("enter");
+        assert_eq!(response.await, Ok(2));
+
+        let response = show_prompt(cx);
+        cx.simulate_keystrokes("escape");
+        assert_eq!(response.await, Ok(1));
+
+        // Other keys leave the prompt open.
+        let mut response = show_prompt(cx);
+        cx.simulate_keystrokes("a");
+        assert_eq!(response.try_recv(), Ok(None));
+    }
+}
@@ -0,0 +1,596 @@
+//! A CLAP `clap_plugin_gui` extension for plugin editors written with GPUI.
+//!
+//! CLAP plugins return the extension from `clap_plugin.get_extension` when the host asks for
+//! [`CLAP_EXT_GUI`]. [`gui_extension`] returns an extension that opens a GPUI window embedded in
+//! the native window that the host provides through `set_parent`, follows the host's resizes and
+//! scale changes, and negotiates sizes with the window's
+//! [`SizeConstraints`](gpui::SizeConstraints). Embedded editors are supported with the `win32`,
+//! `cocoa` and `x11` window APIs; floating editors aren't.
+//!
+//! The app must run inside the host's event loop, as started with
+//! [`Application::attach`](gpui::Application::attach), and be pumped from the host's idle or
+//! timer callbacks. CLAP calls the extension on the host's main thread, which must be the thread
+//! that the app runs on. Calls from other threads fail, logging an error, rather than touching
+//! the app.
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, c_char, c_ulong, c_void},
+    marker::PhantomData,
+    num::NonZeroIsize,
+    ptr::NonNull,
+    rc::Rc,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use gpui::{
+    AnyWindowHandle, App, AppContext as _, AsyncApp, Context, MainThreadOnly, Pixels, Render, Size,
+    SizeConstraints, Window, WindowOptions, px, size,
+};
+use raw_window_handle as rwh;
+use util::{ResultExt as _, maybe};
+
+/// The ID of the gui extension, which hosts pass to `clap_plugin.get_extension`.
+pub const CLAP_EXT_GUI: &CStr = c"clap.gui";
+
+const CLAP_WINDOW_API_WIN32: &CStr = c"win32";
+const CLAP_WINDOW_API_COCOA: &CStr = c"cocoa";
+const CLAP_WINDOW_API_X11: &CStr = c"x11";
+
+#[cfg(target_os = "windows")]
+const NATIVE_WINDOW_API: &CStr = CLAP_WINDOW_API_WIN32;
+#[cfg(target_os = "macos")]
+const NATIVE_WINDOW_API: &CStr = CLAP_WINDOW_API_COCOA;
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const NATIVE_WINDOW_API: &CStr = CLAP_WINDOW_API_X11;
+
+/// The denominator of the aspect ratios reported in `get_resize_hints`.
+const ASPECT_RATIO_DENOMINATOR: u32 = 1000;
+
+/// `clap_window` in `ext/gui.h`.
+#[repr(C)]
+struct ClapWindow {
+    api: *const c_char,
+    handle: ClapWindowHandle,
+}
+
+#[repr(C)]
+union ClapWindowHandle {
+    ptr: *mut c_void,
+    x11: c_ulong,
+}
+
+/// `clap_gui_resize_hints` in `ext/gui.h`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct ClapGuiResizeHints {
+    can_resize_horizontally: bool,
+    can_resize_vertically: bool,
+    preserve_aspect_ratio: bool,
+    aspect_ratio_width: u32,
+    aspect_ratio_height: u32,
+}
+
+/// `clap_plugin_gui` in `ext/gui.h`. Every function takes the `clap_plugin` first.
+#[repr(C)]
+struct ClapPluginGui {
+    is_api_supported: unsafe extern "C" fn(*const c_void, *const c_char, bool) -> bool,
+    get_preferred_api: unsafe extern "C" fn(*const c_void, *mut *const c_char, *mut bool) -> bool,
+    create: unsafe extern "C" fn(*const c_void, *const c_char, bool) -> bool,
+    destroy: unsafe extern "C" fn(*const c_void),
+    set_scale: unsafe extern "C" fn(*const c_void, f64) -> bool,
+    get_size: unsafe extern "C" fn(*const c_void, *mut u32, *mut u32) -> bool,
+    can_resize: unsafe extern "C" fn(*const c_void) -> bool,
+    get_resize_hints: unsafe extern "C" fn(*const c_void, *mut ClapGuiResizeHints) -> bool,
+    adjust_size: unsafe extern "C" fn(*const c_void, *mut u32, *mut u32) -> bool,
+    set_size: unsafe extern "C" fn(*const c_void, u32, u32) -> bool,
+    set_parent: unsafe extern "C" fn(*const c_void, *const ClapWindow) -> bool,
+    set_transient: unsafe extern "C" fn(*const c_void, *const ClapWindow) -> bool,
+    suggest_title: unsafe extern "C" fn(*const c_void, *const c_char),
+    show: unsafe extern "C" fn(*const c_void) -> bool,
+    hide: unsafe extern "C" fn(*const c_void) -> bool,
+}
+
+/// Implemented by plugins to find the [`ClapGui`] of a plugin instance, for [`gui_extension`].
+pub trait ClapGuiPlugin: 'static {
+    /// Return the editor of the given plugin instance.
+    ///
+    /// # Safety
+    ///
+    /// `plugin` is the `clap_plugin` pointer that the host passed to the extension, which points
+    /// to an instance created by this plugin. The editor must live as long as the instance.
+    unsafe fn gui<'a>(plugin: *const c_void) -> &'a ClapGui;
+}
+
+/// Return the `clap_plugin_gui` extension of plugins of type `P`, for plugins to return from
+/// `clap_plugin.get_extension` when the host asks for [`CLAP_EXT_GUI`].
+pub fn gui_extension<P: ClapGuiPlugin>() -> *const c_void {
+    let extension: &'static ClapPluginGui = &GuiExtension::<P>::EXTENSION;
+    (extension as *const ClapPluginGui).cast()
+}
+
+type OpenEditor = dyn Fn(WindowOptions, &mut App) -> Result<AnyWindowHandle>;
+
+/// The editor of a CLAP plugin instance, which opens a window rendering a GPUI view once the
+/// host provides a parent window for it. Plugins keep one per instance, and return it from
+/// [`ClapGuiPlugin::gui`].
+pub struct ClapGui {
+    state: MainThreadOnly<RefCell<ClapGuiState>>,
+}
+
+struct ClapGuiState {
+    cx: AsyncApp,
+    open: Rc<OpenEditor>,
+    created: bool,
+    window: Option<AnyWindowHandle>,
+    size: Size<Pixels>,
+    scale_factor: Option<f32>,
+    visible: bool,
+}
+
+impl Drop for ClapGuiState {
+    fn drop(&mut self) {
+        // Hosts should call `destroy` before destroying the plugin, but not all of them do.
+        if let Some(window) = self.window.take() {
+            window
+                .update(&mut self.cx, |_, window, _| window.detach_embedded())
+                .log_err();
+        }
+    }
+}
+
+impl ClapGui {
+    /// Create an editor that opens a window rendering the view built by `build` when the host
+    /// provides its parent window. `size` is the size that the host is told the editor has
+    /// before the window opens.
+    ///
+    /// Sizes are exchanged with the host in physical pixels, except with the `cocoa` API, where
+    /// they're in points. Hosts' requests to resize the editor are adjusted with
+    /// [`Window::adjust_embedded_size`].
+    pub fn new<V: Render + 'static>(
+        cx: &AsyncApp,
+        size: Size<Pixels>,
+        build: impl Fn(&mut Window, &mut Context<V>) -> V + 'static,
+    ) -> Self {
+        let build = Rc::new(build);
+        let open: Rc<OpenEditor> = Rc::new(move |options, cx| {
+            let build = build.clone();
+            let handle =
+                cx.open_window(options, move |window, cx| cx.new(|cx| build(window, cx)))?;
+            Ok(handle.into())
+        });
+        Self {
+            state: MainThreadOnly::new(RefCell::new(ClapGuiState {
+                cx: cx.clone(),
+                open,
+                created: false,
+                window: None,
+                size,
+                scale_factor: None,
+                visible: false,
+            })),
+        }
+    }
+
+    /// The editor's window, once the host has provided its parent window.
+    pub fn window(&self) -> Option<AnyWindowHandle> {
+        self.state().ok()?.borrow().window
+    }
+
+    /// Whether the host has shown the editor. The host shows and hides the parent window of
+    /// embedded editors itself, so plugins use this to pause work that only the editor needs,
+    /// such as metering.
+    pub fn is_visible(&self) -> bool {
+        self.state().is_ok_and(|state| state.borrow().visible)
+    }
+
+    #[track_caller]
+    fn state(&self) -> Result<&RefCell<ClapGuiState>> {
+        self.state.try_get()
+    }
+
+    /// The app and the editor's window, without holding the state borrowed while the window is
+    /// updated, in case the host calls back into the extension.
+    #[track_caller]
+    fn app_and_window(&self) -> Result<(AsyncApp, Option<AnyWindowHandle>)> {
+        let state = self.state()?.borrow();
+        Ok((state.cx.clone(), state.window))
+    }
+
+    /// The scale of the sizes exchanged with the host before the window opens.
+    fn pending_scale(&self) -> Result<f32> {
+        let scale_factor = self.state()?.borrow().scale_factor.unwrap_or(1.);
+        Ok(size_scale(|| scale_factor))
+    }
+
+    fn set_visible(&self, visible: bool) -> bool {
+        maybe!({
+            self.state()?.borrow_mut().visible = visible;
+            anyhow::Ok(true)
+        })
+        .log_err()
+        .unwrap_or(false)
+    }
+}
+
+struct GuiExtension<P>(PhantomData<P>);
+
+impl<P: ClapGuiPlugin> GuiExtension<P> {
+    const EXTENSION: ClapPluginGui = ClapPluginGui {
+        is_api_supported,
+        get_preferred_api,
+        create: Self::create,
+        destroy: Self::destroy,
+        set_scale: Self::set_scale,
+        get_size: Self::get_size,
+        can_resize: Self::can_resize,
+        get_resize_hints: Self::get_resize_hints,
+        adjust_size: Self::adjust_size,
+        set_size: Self::set_size,
+        set_parent: Self::set_parent,
+        set_transient,
+        suggest_title,
+        show: Self::show,
+        hide: Self::hide,
+    };
+
+    unsafe extern "C" fn create(plugin: *const c_void, api: *const c_char, floating: bool) -> bool {
+        if !unsafe { is_api_supported(plugin, api, floating) } {
+            return false;
+        }
+        let gui = unsafe { P::gui(plugin) };
+        maybe!({
+            gui.state()?.borrow_mut().created = true;
+            anyhow::Ok(true)
+        })
+        .log_err()
+        .unwrap_or(false)
+    }
+
+    unsafe extern "C" fn destroy(plugin: *const c_void) {
+        let gui = unsafe { P::gui(plugin) };
+        maybe!({
+            let (mut cx, window) = gui.app_and_window()?;
+            {
+                let mut state = gui.state()?.borrow_mut();
+                state.created = false;
+                state.window = None;
+                state.visible = false;
+            }
+            if let Some(window) = window {
+                window.update(&mut cx, |_, window, _| window.detach_embedded())?;
+            }
+            anyhow::Ok(())
+        })
+        .log_err();
+    }
+
+    unsafe extern "C" fn set_scale(plugin: *const c_void, scale: f64) -> bool {
+        // With the `cocoa` API, sizes are in points and the system reports the scale factor.
+        if cfg!(target_os = "macos") || scale <= 0. {
+            return false;
+        }
+        let gui = unsafe { P::gui(plugin) };
+        maybe!({
+            let (mut cx, window) = gui.app_and_window()?;
+            gui.state()?.borrow_mut().scale_factor = Some(scale as f32);
+            if let Some(window) = window {
+                window.update(&mut cx, |_, window, _| {
+                    window.set_embedded_scale_factor(scale as f32)
+                })?;
+            }
+            anyhow::Ok(true)
+        })
+        .log_err()
+        .unwrap_or(false)
+    }
+
+    unsafe extern "C" fn get_size(
+        plugin: *const c_void,
+        width: *mut u32,
+        height: *mut u32,
+    ) -> bool {
+        if width.is_null() || height.is_null() {
+            return false;
+        }
+        let gui = unsafe { P::gui(plugin) };
+        maybe!({
+            let (mut cx, window) = gui.app_and_window()?;
+            let (size, scale) = match window {
+                Some(window) => window.update(&mut cx, |_, window, _| {
+                    (window.viewport_size(), window_scale(window))
+                })?,
+                None => (gui.state()?.borrow().size, gui.pending_scale()?),
+            };
+            unsafe { (*width, *height) = host_size(size, scale) };
+            anyhow::Ok(true)
+        })
+        .log_err()
+        .unwrap_or(false)
+    }
+
+    unsafe extern "C" fn can_resize(plugin: *const c_void) -> bool {
+        let gui = unsafe { P::gui(plugin) };
+        maybe!({
+            let (mut cx, window) = gui.app_and_window()?;
+            match window {
+                Some(window) => {
+                    window.update(&mut cx, |_, window, _| window.size_constraints().resizable)
+                }
+                None => Ok(true),
+            }
+        })
+        .log_err()
+        .unwrap_or(false)
+    }
+
+    unsafe extern "C" fn get_resize_hints(
+        plugin: *const c_void,
+        hints: *mut ClapGuiResizeHints,
+    ) -> bool {
+        let Some(hints) = (unsafe { hints.as_mut() }) else {
+            return false;
+        };
+        let gui = unsafe { P::gui(plugin) };
+        maybe!({
+            let (mut cx, window) = gui.app_and_window()?;
+            let constraints = match window {
+                Some(window) => window.update(&mut cx, |_, window, _| window.size_constraints())?,
+                None => SizeConstraints::default(),
+            };
+            *hints = resize_hints(&constraints);
+            anyhow::Ok(true)
+        })
+        .log_err()
+        .unwrap_or(false)
+    }
+
+    unsafe extern "C" fn adjust_size(
+        plugin: *const c_void,
+        width: *mut u32,
+        height: *mut u32,
+    ) -> bool {
+        if width.is_null() || height.is_null() {
+            return false;
+        }
+        let gui = unsafe { P::gui(plugin) };
+        maybe!({
+            let (mut cx, window) = gui.app_and_window()?;
+            let Some(window) = window else {
+                return anyhow::Ok(true);
+            };
+            window.update(&mut cx, |_, window, cx| {
+                let scale = window_scale(window);
+                let size = unsafe { logical_size(*width, *height, scale) };
+                let size = window.adjust_embedded_size(size, cx);
+                unsafe { (*width, *height) = host_size(size, scale) };
+            })?;
+            anyhow::Ok(true)
+        })
+        .log_err()
+        .unwrap_or(false)
+    }
+
+    unsafe extern "C" fn set_size(plugin: *const c_void, width: u32, height: u32) -> bool {
+        let gui = unsafe { P::gui(plugin) };
+        maybe!({
+            let (mut cx, window) = gui.app_and_window()?;
+            let size = match window {
+                Some(window) => window.update(&mut cx, |_, window, _| {
+                    let size = logical_size(width, height, window_scale(window));
+                    window.notify_embedded_resize(size);
+                    size
+                })?,
+                None => logical_size(width, height, gui.pending_scale()?),
+            };
+            gui.state()?.borrow_mut().size = size;
+            anyhow::Ok(true)
+        })
+        .log_err()
+        .unwrap_or(false)
+    }
+
+    unsafe extern "C" fn set_parent(plugin: *const c_void, parent: *const ClapWindow) -> bool {
+        let gui = unsafe { P::gui(plugin) };
+        maybe!({
+            anyhow::ensure!(
+                gui.state()?.borrow().created,
+                "the host set the parent before creating the editor"
+            );
+            let parent = unsafe { parent.as_ref() }.context("the parent window is null")?;
+            let (mut cx, window) = gui.app_and_window()?;
+            if let Some(window) = window {
+                let raw_handle = unsafe { raw_window_handle(parent) }?;
+                window.update(&mut cx, |_, window, _| window.reparent(raw_handle))??;
+                return anyhow::Ok(true);
+            }
+
+            let options = unsafe { window_options(parent) }?;
+            let (open, scale_factor) = {
+                let state = gui.state()?.borrow();
+                (state.open.clone(), state.scale_factor)
+            };
+            let window = cx.update(|cx| open(options, cx))??;
+            if let Some(scale_factor) = scale_factor.filter(|_| !cfg!(target_os = "macos")) {
+                window.update(&mut cx, |_, window, _| {
+                    window.set_embedded_scale_factor(scale_factor)
+                })?;
+            }
+            gui.state()?.borrow_mut().window = Some(window);
+            anyhow::Ok(true)
+        })
+        .log_err()
+        .unwrap_or(false)
+    }
+
+    unsafe extern "C" fn show(plugin: *const c_void) -> bool {
+        unsafe { P::gui(plugin) }.set_visible(true)
+    }
+
+    unsafe extern "C" fn hide(plugin: *const c_void) -> bool {
+        unsafe { P::gui(plugin) }.set_visible(false)
+    }
+}
+
+unsafe extern "C" fn is_api_supported(
+    _plugin: *const c_void,
+    api: *const c_char,
+    floating: bool,
+) -> bool {
+    !floating && !api.is_null() && unsafe { CStr::from_ptr(api) } == NATIVE_WINDOW_API
+}
+
+unsafe extern "C" fn get_preferred_api(
+    _plugin: *const c_void,
+    api: *mut *const c_char,
+    floating: *mut bool,
+) -> bool {
+    if api.is_null() || floating.is_null() {
+        return false;
+    }
+    unsafe {
+        *api = NATIVE_WINDOW_API.as_ptr();
+        *floating = false;
+    }
+    true
+}
+
+unsafe extern "C" fn set_transient(_plugin: *const c_void, _window: *const ClapWindow) -> bool {
+    // Only floating editors have a transient window, and they aren't supported.
+    false
+}
+
+unsafe extern "C" fn suggest_title(_plugin: *const c_void, _title: *const c_char) {
+    // Only floating editors have a title, and they aren't supported.
+}
+
+unsafe fn raw_window_handle(parent: &ClapWindow) -> Result<rwh::RawWindowHandle> {
+    anyhow::ensure!(!parent.api.is_null(), "the window API is null");
+    let api = unsafe { CStr::from_ptr(parent.api) };
+    if api == CLAP_WINDOW_API_WIN32 {
+        let hwnd = unsafe { parent.handle.ptr } as isize;
+        let hwnd = NonZeroIsize::new(hwnd).context("HWND is null")?;
+        Ok(rwh::Win32WindowHandle::new(hwnd).into())
+    } else if api == CLAP_WINDOW_API_COCOA {
+        let ns_view = NonNull::new(unsafe { parent.handle.ptr }).context("NSView is null")?;
+        Ok(rwh::AppKitWindowHandle::new(ns_view).into())
+    } else if api == CLAP_WINDOW_API_X11 {
+        Ok(rwh::XlibWindowHandle::new(unsafe { parent.handle.x11 }).into())
+    } else {
+        Err(anyhow!("unsupported window API {api:?}"))
+    }
+}
+
+unsafe fn window_options(parent: &ClapWindow) -> Result<WindowOptions> {
+    let raw_handle = unsafe { raw_window_handle(parent) }?;
+    Ok(match raw_handle {
+        // The host owns the HWND and its window procedure, so render into a child window of it.
+        rwh::RawWindowHandle::Win32(_) => WindowOptions::for_embedded_child_window(raw_handle),
+        _ => WindowOptions::for_embedded_window(raw_handle),
+    })
+}
+
+/// The scale of the sizes exchanged with the host: CLAP sizes are in physical pixels, except with
+/// the `cocoa` API, where they're in points like GPUI's.
+fn size_scale(scale_factor: impl FnOnce() -> f32) -> f32 {
+    if cfg!(target_os = "macos") {
+        1.
+    } else {
+        scale_factor()
+    }
+}
+
+fn window_scale(window: &Window) -> f32 {
+    size_scale(|| window.scale_factor())
+}
+
+fn logical_size(width: u32, height: u32, scale: f32) -> Size<Pixels> {
+    size(px(width as f32 / scale), px(height as f32 / scale))
+}
+
+fn host_size(size: Size<Pixels>, scale: f32) -> (u32, u32) {
+    (
+        (f32::from(size.width) * scale).round() as u32,
+        (f32::from(size.height) * scale).round() as u32,
+    )
+}
+
+fn resize_hints(constraints: &SizeConstraints) -> ClapGuiResizeHints {
+    let fixed = |axis: fn(&Size<Pixels>) -> Pixels| {
+        matches!(
+            (constraints.min_size, constraints.max_size),
+            (Some(min), Some(max)) if axis(&min) >= axis(&max)
+        )
+    };
+    let aspect_ratio = constraints.aspect_ratio.filter(|ratio| *ratio > 0.);
+    ClapGuiResizeHints {
+        can_resize_horizontally: constraints.resizable && !fixed(|size| size.width),
+        can_resize_vertically: constraints.resizable && !fixed(|size| size.height),
+        preserve_aspect_ratio: aspect_ratio.is_some(),
+        aspect_ratio_width: aspect_ratio.map_or(0, |ratio| {
+            (ratio * ASPECT_RATIO_DENOMINATOR as f32).round() as u32
+        }),
+        aspect_ratio_height: aspect_ratio.map_or(0, |_| ASPECT_RATIO_DENOMINATOR),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_support() {
+        unsafe {
+            assert!(is_api_supported(
+                std::ptr::null(),
+                NATIVE_WINDOW_API.as_ptr(),
+                false
+            ));
+            assert!(!is_api_supported(
+                std::ptr::null(),
+                NATIVE_WINDOW_API.as_ptr(),
+                true
+            ));
+            assert!(!is_api_supported(
+                std::ptr::null(),
+                c"wayland".as_ptr(),
+                false
+            ));
+        }
+    }
+
+    #[test]
+    fn test_host_sizes() {
+        assert_eq!(logical_size(400, 300, 2.), size(px(200.), px(150.)));
+        assert_eq!(host_size(size(px(100.5), px(50.)), 2.), (201, 100));
+    }
+
+    #[test]
+    fn test_resize_hints() {
+        assert_eq!(
+            resize_hints(&SizeConstraints::default()),
+            ClapGuiResizeHints {
+                can_resize_horizontally: true,
+                can_resize_vertically: true,
+                ..Default::default()
+            }
+        );
+
+        let constraints = SizeConstraints {
+            min_size: Some(size(px(200.), px(100.))),
+            max_size: Some(size(px(800.), px(100.))),
+            aspect_ratio: Some(1.5),
+            ..Default::default()
+        };
+        assert_eq!(
+            resize_hints(&constraints),
+            ClapGuiResizeHints {
+                can_resize_horizontally: true,
+                can_resize_vertically: false,
+                preserve_aspect_ratio: true,
+                aspect_ratio_width: 1500,
+                aspect_ratio_height: 1000,
+            }
+        );
+    }
+}
@@ -0,0 +1,205 @@
+/*
+ * C bindings for embedding GPUI windows in C and C++ hosts.
+ *
+ * The UI is written in Rust and registered by name with
+ * `gpui_ffi::register_root_component`. The host creates an app, runs it, and opens embedded
+ * windows that render a registered component inside the host's own native windows.
+ *
+ * All functions must be called on the thread that created the app, and fail when called from
+ * other threads. Functions returning `bool` return false and log the error on failure.
+ *
+ * To debug windows that stay blank in a host, set the GPUI_EMBEDDED_TRACE environment variable to
+ * 1. GPUI then logs every call it makes on the host's native windows, with timestamps.
+ */
+
+#ifndef GPUI_FFI_H
+#define GPUI_FFI_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define GPUI_WINDOW_HANDLE_APPKIT 0
+#define GPUI_WINDOW_HANDLE_WIN32 1
+#define GPUI_WINDOW_HANDLE_XLIB 2
+#define GPUI_WINDOW_HANDLE_XCB 3
+#define GPUI_WINDOW_HANDLE_WAYLAND 4
+
+#define GPUI_EMBEDDING_MODE_ATTACH 0
+#define GPUI_EMBEDDING_MODE_CHILD 1
+
+#define GPUI_FRAME_DRIVER_PLATFORM 0
+#define GPUI_FRAME_DRIVER_EXTERNAL 1
+
+#define GPUI_EVENT_MOUSE_MOVE 0
+#define GPUI_EVENT_MOUSE_DOWN 1
+#define GPUI_EVENT_MOUSE_UP 2
+#define GPUI_EVENT_SCROLL_WHEEL 3
+#define GPUI_EVENT_KEY_DOWN 4
+#define GPUI_EVENT_KEY_UP 5
+
+#define GPUI_MOUSE_BUTTON_NONE 0
+#define GPUI_MOUSE_BUTTON_LEFT 1
+#define GPUI_MOUSE_BUTTON_RIGHT 2
+#define GPUI_MOUSE_BUTTON_MIDDLE 3
+
+#define GPUI_MODIFIER_CONTROL (1u << 0)
+#define GPUI_MODIFIER_ALT (1u << 1)
+#define GPUI_MODIFIER_SHIFT (1u << 2)
+#define GPUI_MODIFIER_PLATFORM (1u << 3)
+#define GPUI_MODIFIER_FUNCTION (1u << 4)
+
+typedef struct GpuiApp GpuiApp;
+
+/* A native window provided by the host. */
+typedef struct GpuiRawWindowHandle {
+    /* One of the GPUI_WINDOW_HANDLE_* constants. */
+    uint32_t kind;
+    /* The NSView*, HWND or wl_surface*, for handles of those kinds. */
+    void *window;
+    /* The X11 window ID, for Xlib and XCB handles. */
+    uint64_t xid;
+    /* The Display*, xcb_connection_t* or wl_display* that the window belongs to. */
+    void *display;
+    /* The X11 screen that the window is on. */
+    int32_t screen;
+    /* One of the GPUI_EMBEDDING_MODE_* constants. Only used on Windows. */
+    uint32_t embedding_mode;
+    /*
+     * One of the GPUI_FRAME_DRIVER_* constants. With GPUI_FRAME_DRIVER_EXTERNAL, the window only
+     * renders when gpui_window_render_frame is called.
+     */
+    uint32_t frame_driver;
+} GpuiRawWindowHandle;
+
+/* An input event to inject into a window. */
+typedef struct GpuiInputEvent {
+    /* One of the GPUI_EVENT_* constants. */
+    uint32_t kind;
+    /* The pointer position in logical pixels, relative to the window's top left corner. */
+    float x;
+    float y;
+    /* One of the GPUI_MOUSE_BUTTON_* constants. For mouse moves, the button held down. */
+    uint32_t button;
+    uint32_t click_count;
+    /* The scrolled distance in logical pixels. */
+    float scroll_delta_x;
+    float scroll_delta_y;
+    /* A combination of the GPUI_MODIFIER_* flags. */
+    uint32_t modifiers;
+    /* For key events, a keystroke such as "a", "enter" or "cmd-s". */
+    const char *keystroke;
+    /* For key down events, whether the key is being held down and repeating. */
+    bool is_held;
+} GpuiInputEvent;
+
+/* The sizes a window accepts, in logical pixels. Zero means there's no constraint. */
+typedef struct GpuiSizeConstraints {
+    float preferred_width;
+    float preferred_height;
+    float min_width;
+    float min_height;
+    float max_width;
+    float max_height;
+    /* The ratio of width to height the window keeps. */
+    float aspect_ratio;
+    /* Whether the host may resize the window at all. */
+    bool resizable;
+} GpuiSizeConstraints;
+
+typedef void (*GpuiLaunchCallback)(GpuiApp *app, void *user_data);
+
+/* Create an app. Free it with gpui_app_free. */
+GpuiApp *gpui_app_new(void);
+
+/* Run the app until it quits, calling on_launch once it has started. */
+bool gpui_app_run(GpuiApp *app, GpuiLaunchCallback on_launch, void *user_data);
+
+/*
+ * Start the app inside the host's event loop, calling on_launch once it has started. Unlike
+ * gpui_app_run, this returns right away, and the host must call gpui_app_pump regularly, for
+ * example from its idle or timer callbacks.
+ */
+bool gpui_app_attach(GpuiApp *app, GpuiLaunchCallback on_launch, void *user_data);
+
+/* Process the work that an app started with gpui_app_attach has pending, without blocking. */
+bool gpui_app_pump(GpuiApp *app);
+
+/*
+ * Ask the app to quit, which makes gpui_app_run return. Apps started with gpui_app_attach shut
+ * down without stopping the host's event loop instead.
+ */
+bool gpui_app_quit(GpuiApp *app);
+
+/*
+ * Free an app once gpui_app_run has returned or an attached app has quit, or if it was never
+ * started.
+ */
+void gpui_app_free(GpuiApp *app);
+
+/*
+ * Release the registered root components and the state that GPUI keeps for the process, so that
+ * the library can be unloaded. Call this after freeing every app. GPUI can't be used again until
+ * the library is reloaded.
+ */
+void gpui_shutdown(void);
+
+/*
+ * Open a window rendering the root component registered under component_name, embedded in the
+ * host's native window. Returns the window's ID, or 0 if it couldn't be opened.
+ */
+uint64_t gpui_window_open_embedded(GpuiApp *app, const GpuiRawWindowHandle *handle,
+                                   const char *component_name);
+
+/*
+ * Close a window opened with gpui_window_open_embedded, detaching it from the host's native window
+ * right away. Call this before destroying the host's window.
+ */
+bool gpui_window_close(GpuiApp *app, uint64_t window_id);
+
+/* Tell a window that the host's window was resized to the given size, in logical pixels. */
+bool gpui_window_notify_resize(GpuiApp *app, uint64_t window_id, float width, float height);
+
+/*
+ * Lay out, paint and present a window opened with GPUI_FRAME_DRIVER_EXTERNAL if it changed. Call
+ * this from the host's idle or timer callback, after gpui_app_pump for attached apps.
+ */
+bool gpui_window_render_frame(GpuiApp *app, uint64_t window_id);
+
+/* Write the sizes a window accepts to `constraints`. */
+bool gpui_window_get_size_constraints(GpuiApp *app, uint64_t window_id,
+                                      GpuiSizeConstraints *constraints);
+
+/*
+ * Adjust the size, in logical pixels, that the host would like to resize a window to, to the
+ * closest size the window accepts, like CLAP's adjust_size or VST3's checkSizeConstraint.
+ */
+bool gpui_window_adjust_size(GpuiApp *app, uint64_t window_id, float *width, float *height);
+
+/*
+ * Move a window into another native window of the host, such as when the host docks or undocks
+ * the plugin's editor. The window keeps its state and GPU resources. Returns whether it moved.
+ */
+bool gpui_window_reparent(GpuiApp *app, uint64_t window_id, const GpuiRawWindowHandle *handle);
+
+/*
+ * Take a window out of the host's native window and hide it without closing it, so that the host
+ * can destroy its window, such as when it pops out the plugin's editor. Move it into the host's
+ * new window with gpui_window_reparent. Returns whether it was parked.
+ */
+bool gpui_window_park(GpuiApp *app, uint64_t window_id);
+
+/*
+ * Dispatch an input event that the host received to a window. Returns whether GPUI handled the
+ * event, in which case the host shouldn't process it further.
+ */
+bool gpui_window_dispatch_event(GpuiApp *app, uint64_t window_id, const GpuiInputEvent *event);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GPUI_FFI_H */
@@ -0,0 +1,739 @@
+//! A C ABI for embedding GPUI windows in hosts written in C or C++, such as plugin frameworks.
+//!
+//! The UI itself is still written in Rust: a root component is registered under a name with
+//! [`register_root_component`], and the host opens embedded windows that render it by name.
+//! See `include/gpui_ffi.h` for the C declarations.
+//!
+//! All functions must be called on the thread that runs the app, and not from within a
+//! callback that GPUI is already running, with the exception of the launch callbacks passed to
+//! [`gpui_app_run`] and [`gpui_app_attach`]. Calls from other threads fail, logging an error that points at the function
+//! that was called, rather than touching the app.
+
+use std::{
+    cell::{Cell, RefCell},
+    ffi::{CStr, c_char, c_ulong, c_void},
+    num::{NonZeroIsize, NonZeroU32},
+    ptr::NonNull,
+    rc::Rc,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use gpui::{
+    AnyWindowHandle, App, AppContext as _, Application, AsyncApp, Context, EmbeddingMode,
+    FrameDriver, KeyDownEvent, KeyUpEvent, Keystroke, MainThreadOnly, Modifiers, MouseButton,
+    MouseDownEvent, MouseMoveEvent, MouseUpEvent, PlatformInput, Render, ScrollDelta,
+    ScrollWheelEvent, TouchPhase, Window, WindowOptions, point, px, size,
+};
+use raw_window_handle as rwh;
+use util::{ResultExt as _, maybe};
+
+pub const GPUI_WINDOW_HANDLE_APPKIT: u32 = 0;
+pub const GPUI_WINDOW_HANDLE_WIN32: u32 = 1;
+pub const GPUI_WINDOW_HANDLE_XLIB: u32 = 2;
+pub const GPUI_WINDOW_HANDLE_XCB: u32 = 3;
+pub const GPUI_WINDOW_HANDLE_WAYLAND: u32 = 4;
+
+pub const GPUI_EMBEDDING_MODE_ATTACH: u32 = 0;
+pub const GPUI_EMBEDDING_MODE_CHILD: u32 = 1;
+
+pub const GPUI_FRAME_DRIVER_PLATFORM: u32 = 0;
+pub const GPUI_FRAME_DRIVER_EXTERNAL: u32 = 1;
+
+pub const GPUI_EVENT_MOUSE_MOVE: u32 = 0;
+pub const GPUI_EVENT_MOUSE_DOWN: u32 = 1;
+pub const GPUI_EVENT_MOUSE_UP: u32 = 2;
+pub const GPUI_EVENT_SCROLL_WHEEL: u32 = 3;
+pub const GPUI_EVENT_KEY_DOWN: u32 = 4;
+pub const GPUI_EVENT_KEY_UP: u32 = 5;
+
+pub const GPUI_MOUSE_BUTTON_NONE: u32 = 0;
+pub const GPUI_MOUSE_BUTTON_LEFT: u32 = 1;
+pub const GPUI_MOUSE_BUTTON_RIGHT: u32 = 2;
+pub const GPUI_MOUSE_BUTTON_MIDDLE: u32 = 3;
+
+pub const GPUI_MODIFIER_CONTROL: u32 = 1 << 0;
+pub const GPUI_MODIFIER_ALT: u32 = 1 << 1;
+pub const GPUI_MODIFIER_SHIFT: u32 = 1 << 2;
+pub const GPUI_MODIFIER_PLATFORM: u32 = 1 << 3;
+pub const GPUI_MODIFIER_FUNCTION: u32 = 1 << 4;
+
+/// A native window provided by the host, see `GpuiRawWindowHandle` in `gpui_ffi.h`.
+#[repr(C)]
+pub struct GpuiRawWindowHandle {
+    /// One of the `GPUI_WINDOW_HANDLE_*` constants.
+    pub kind: u32,
+    /// The `NSView*`, `HWND` or `wl_surface*`, for handles of those kinds.
+    pub window: *mut c_void,
+    /// The X11 window ID, for Xlib and XCB handles.
+    pub xid: u64,
+    /// The `Display*`, `xcb_connection_t*` or `wl_display*` that the window belongs to.
+    pub display: *mut c_void,
+    /// The X11 screen that the window is on.
+    pub screen: i32,
+    /// One of the `GPUI_EMBEDDING_MODE_*` constants. Only used on Windows.
+    pub embedding_mode: u32,
+    /// One of the `GPUI_FRAME_DRIVER_*` constants.
+    pub frame_driver: u32,
+}
+
+/// An input event to inject into a window, see `GpuiInputEvent` in `gpui_ffi.h`.
+#[repr(C)]
+pub struct GpuiInputEvent {
+    /// One of the `GPUI_EVENT_*` constants.
+    pub kind: u32,
+    /// The pointer position in logical pixels, relative to the window's top left corner.
+    pub x: f32,
+    pub y: f32,
+    /// One of the `GPUI_MOUSE_BUTTON_*` constants. For mouse moves, the button held down.
+    pub button: u32,
+    pub click_count: u32,
+    /// The scrolled distance in logical pixels.
+    pub scroll_delta_x: f32,
+    pub scroll_delta_y: f32,
+    /// A combination of the `GPUI_MODIFIER_*` flags.
+    pub modifiers: u32,
+    /// For key events, a keystroke such as `"a"`, `"enter"` or `"cmd-s"`.
+    pub keystroke: *const c_char,
+    /// For key down events, whether the key is being held down and repeating.
+    pub is_held: bool,
+}
+
+/// The sizes a window accepts, see `GpuiSizeConstraints` in `gpui_ffi.h`. Sizes are in logical
+/// pixels, and zero means there's no constraint.
+#[repr(C)]
+pub struct GpuiSizeConstraints {
+    pub preferred_width: f32,
+    pub preferred_height: f32,
+    pub min_width: f32,
+    pub min_height: f32,
+    pub max_width: f32,
+    pub max_height: f32,
+    /// The ratio of width to height the window keeps.
+    pub aspect_ratio: f32,
+    /// Whether the host may resize the window at all.
+    pub resizable: bool,
+}
+
+pub type GpuiLaunchCallback = unsafe extern "C" fn(app: *mut GpuiApp, user_data: *mut c_void);
+
+type OpenRootComponent = dyn Fn(WindowOptions, &mut App) -> Result<AnyWindowHandle>;
+
+thread_local! {
+    static ROOT_COMPONENTS: RefCell<HashMap<String, Rc<OpenRootComponent>>> =
+        RefCell::new(HashMap::default());
+}
+
+/// Register a root component that C hosts can open embedded windows for by name, with
+/// `gpui_window_open_embedded`. Registering a name again replaces the earlier component.
+pub fn register_root_component<V: Render + 'static>(
+    name: impl Into<String>,
+    build: impl Fn(&mut Window, &mut Context<V>) -> V + 'static,
+) {
+    let build = Rc::new(build);
+    let open: Rc<OpenRootComponent> = Rc::new(move |options, cx| {
+        let build = build.clone();
+        let handle = cx.open_window(options, move |window, cx| cx.new(|cx| build(window, cx)))?;
+        Ok(handle.into())
+    });
+    ROOT_COMPONENTS.with_borrow_mut(|components| components.insert(name.into(), open));
+}
+
+/// An application created with `gpui_app_new`. It can only be used on the thread that created it.
+pub struct GpuiApp(MainThreadOnly<GpuiAppState>);
+
+struct GpuiAppState {
+    application: Cell<Option<Application>>,
+    attached: RefCell<Option<Application>>,
+    cx: RefCell<Option<AsyncApp>>,
+}
+
+impl GpuiAppState {
+    fn async_app(&self) -> Result<AsyncApp> {
+        self.cx.borrow().clone().context("the app isn't running")
+    }
+
+    fn window(&self, window_id: u64) -> Result<(AsyncApp, AnyWindowHandle)> {
+        let cx = self.async_app()?;
+        let window = cx
+            .update(|cx| {
+                cx.windows()
+                    .into_iter()
+                    .find(|window| window.window_id().as_u64() == window_id)
+            })?
+            .with_context(|| format!("no window with ID {window_id}"))?;
+        Ok((cx, window))
+    }
+}
+
+#[track_caller]
+unsafe fn app_ref<'a>(app: *const GpuiApp) -> Result<&'a GpuiAppState> {
+    unsafe { app.as_ref() }.context("app is null")?.0.try_get()
+}
+
+/// Create an application. Free it with `gpui_app_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn gpui_app_new() -> *mut GpuiApp {
+    Box::into_raw(Box::new(GpuiApp(MainThreadOnly::new(GpuiAppState {
+        application: Cell::new(Some(Application::new())),
+        attached: RefCell::new(None),
+        cx: RefCell::new(None),
+    }))))
+}
+
+/// Run the application until it quits, calling `on_launch` once it has started. Other functions
+/// can be called from `on_launch` and from the host's callbacks while the app is running.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_app_run(
+    app: *mut GpuiApp,
+    on_launch: Option<GpuiLaunchCallback>,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(application) = unsafe { app_ref(app) }
+        .and_then(|app_ref| {
+            app_ref
+                .application
+                .take()
+                .context("the app has already been run")
+        })
+        .log_err()
+    else {
+        return false;
+    };
+
+    application.run(move |cx| {
+        let app_ref = unsafe { &*app }.0.get();
+        app_ref.cx.replace(Some(cx.to_async()));
+        if let Some(on_launch) = on_launch {
+            // Run the callback outside of this update, so that it can call back into the app.
+            cx.spawn(async move |_| unsafe { on_launch(app, user_data) })
+                .detach();
+        }
+    });
+
+    unsafe { &*app }.0.get().cx.replace(None);
+    true
+}
+
+/// Start the application inside the host's event loop, for hosts such as plugin hosts that
+/// already run one, and call `on_launch` once it has started. Unlike `gpui_app_run`, this returns
+/// right away, and the host must call `gpui_app_pump` regularly, for example from its idle or
+/// timer callbacks.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_app_attach(
+    app: *mut GpuiApp,
+    on_launch: Option<GpuiLaunchCallback>,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(application) = unsafe { app_ref(app) }
+        .and_then(|app_ref| {
+            app_ref
+                .application
+                .take()
+                .context("the app has already been started")
+        })
+        .log_err()
+    else {
+        return false;
+    };
+
+    application.attach(move |cx| {
+        let app_ref = unsafe { &*app }.0.get();
+        app_ref.cx.replace(Some(cx.to_async()));
+    });
+    unsafe { &*app }.0.get().attached.replace(Some(application));
+
+    if let Some(on_launch) = on_launch {
+        unsafe { on_launch(app, user_data) };
+    }
+    true
+}
+
+/// Process the work that an application started with `gpui_app_attach` has pending, and return
+/// without waiting for more.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_app_pump(app: *mut GpuiApp) -> bool {
+    maybe!({
+        let app_ref = unsafe { app_ref(app) }?;
+        let attached = app_ref.attached.borrow();
+        attached
+            .as_ref()
+            .context("the app wasn't started with gpui_app_attach")?
+            .pump();
+        anyhow::Ok(())
+    })
+    .log_err()
+    .is_some()
+}
+
+/// Ask the application to quit, which makes `gpui_app_run` return. Applications started with
+/// `gpui_app_attach` shut down without stopping the host's event loop instead.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_app_quit(app: *mut GpuiApp) -> bool {
+    unsafe { app_ref(app) }
+        .and_then(|app| app.async_app()?.update(|cx| cx.quit()))
+        .log_err()
+        .is_some()
+}
+
+/// Free an application once `gpui_app_run` has returned or an attached application has quit, or
+/// if it was never started.
+///
+/// # Safety
+///
+/// `app` must be null, or have been returned by `gpui_app_new` and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_app_free(app: *mut GpuiApp) {
+    if !app.is_null() {
+        drop(unsafe { Box::from_raw(app) });
+    }
+}
+
+/// Release the registered root components and the state that GPUI keeps for the process, so
+/// that the library can be unloaded, see `gpui::runtime::shutdown`. Call this after freeing every
+/// app. GPUI can't be used again until the library is reloaded.
+#[unsafe(no_mangle)]
+pub extern "C" fn gpui_shutdown() {
+    ROOT_COMPONENTS.take();
+    gpui::runtime::shutdown();
+}
+
+/// Open a window rendering the root component registered under `component_name`, embedded in
+/// the host's native window. Returns the window's ID, or 0 if it couldn't be opened.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed, `handle` must point to a valid
+/// `GpuiRawWindowHandle` whose pointers are valid, and `component_name` must be a
+/// nul-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_window_open_embedded(
+    app: *mut GpuiApp,
+    handle: *const GpuiRawWindowHandle,
+    component_name: *const c_char,
+) -> u64 {
+    maybe!({
+        let app = unsafe { app_ref(app) }?;
+        let handle = unsafe { handle.as_ref() }.context("window handle is null")?;
+        anyhow::ensure!(!component_name.is_null(), "component name is null");
+        let component_name = unsafe { CStr::from_ptr(component_name) }.to_str()?;
+
+        let open = ROOT_COMPONENTS
+            .with_borrow(|components| components.get(component_name).cloned())
+            .with_context(|| format!("no root component named {component_name:?}"))?;
+        let options = embedded_window_options(handle)?;
+        let window = app.async_app()?.update(|cx| open(options, cx))??;
+        anyhow::Ok(window.window_id().as_u64())
+    })
+    .log_err()
+    .unwrap_or(0)
+}
+
+/// Close a window opened with `gpui_window_open_embedded`, detaching it from the host's native
+/// window right away. Call this before destroying the host's window.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_window_close(app: *mut GpuiApp, window_id: u64) -> bool {
+    maybe!({
+        let (mut cx, window) = unsafe { app_ref(app) }?.window(window_id)?;
+        window.update(&mut cx, |_, window, _| window.detach_embedded())
+    })
+    .log_err()
+    .is_some()
+}
+
+/// Tell a window that the host's native window it's embedded in was resized to the given
+/// content size, in logical pixels.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_window_notify_resize(
+    app: *mut GpuiApp,
+    window_id: u64,
+    width: f32,
+    height: f32,
+) -> bool {
+    maybe!({
+        let (mut cx, window) = unsafe { app_ref(app) }?.window(window_id)?;
+        window.update(&mut cx, |_, window, _| {
+            window.notify_embedded_resize(size(px(width), px(height)))
+        })
+    })
+    .log_err()
+    .is_some()
+}
+
+/// Lay out, paint and present a window opened with `GPUI_FRAME_DRIVER_EXTERNAL` if it changed.
+/// Call this from the host's idle or timer callback, after `gpui_app_pump` for attached apps.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_window_render_frame(app: *mut GpuiApp, window_id: u64) -> bool {
+    maybe!({
+        let (mut cx, window) = unsafe { app_ref(app) }?.window(window_id)?;
+        window.render_frame(&mut cx)
+    })
+    .log_err()
+    .is_some()
+}
+
+/// Write the sizes a window accepts to `constraints`, for hosts that ask plugins whether they
+/// can be resized and how.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed, and `constraints` must point
+/// to a writable `GpuiSizeConstraints`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_window_get_size_constraints(
+    app: *mut GpuiApp,
+    window_id: u64,
+    constraints: *mut GpuiSizeConstraints,
+) -> bool {
+    maybe!({
+        let out = unsafe { constraints.as_mut() }.context("size constraints are null")?;
+        let (mut cx, window) = unsafe { app_ref(app) }?.window(window_id)?;
+        let constraints = window.update(&mut cx, |_, window, _| window.size_constraints())?;
+        let (preferred, min, max) = (
+            constraints.preferred_size.unwrap_or_default(),
+            constraints.min_size.unwrap_or_default(),
+            constraints.max_size.unwrap_or_default(),
+        );
+        *out = GpuiSizeConstraints {
+            preferred_width: preferred.width.into(),
+            preferred_height: preferred.height.into(),
+            min_width: min.width.into(),
+            min_height: min.height.into(),
+            max_width: max.width.into(),
+            max_height: max.height.into(),
+            aspect_ratio: constraints.aspect_ratio.unwrap_or(0.),
+            resizable: constraints.resizable,
+        };
+        anyhow::Ok(())
+    })
+    .log_err()
+    .is_some()
+}
+
+/// Adjust the size, in logical pixels, that the host would like to resize a window to, to the
+/// closest size the window accepts, like CLAP's `adjust_size` or VST3's `checkSizeConstraint`.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed, and `width` and `height` must
+/// point to writable floats.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_window_adjust_size(
+    app: *mut GpuiApp,
+    window_id: u64,
+    width: *mut f32,
+    height: *mut f32,
+) -> bool {
+    maybe!({
+        let width = unsafe { width.as_mut() }.context("width is null")?;
+        let height = unsafe { height.as_mut() }.context("height is null")?;
+        let (mut cx, window) = unsafe { app_ref(app) }?.window(window_id)?;
+        let adjusted = window.update(&mut cx, |_, window, cx| {
+            window.adjust_embedded_size(size(px(*width), px(*height)), cx)
+        })?;
+        *width = adjusted.width.into();
+        *height = adjusted.height.into();
+        anyhow::Ok(())
+    })
+    .log_err()
+    .is_some()
+}
+
+/// Move a window into another native window of the host, such as when the host docks or undocks
+/// the plugin's editor. The window keeps its state and GPU resources. Only the handle's window
+/// is used, its kind must match the window's current host.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed, and `handle` must point to a
+/// valid `GpuiRawWindowHandle` whose pointers are valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_window_reparent(
+    app: *mut GpuiApp,
+    window_id: u64,
+    handle: *const GpuiRawWindowHandle,
+) -> bool {
+    maybe!({
+        let handle = unsafe { handle.as_ref() }.context("window handle is null")?;
+        let raw_handle = embedded_window_options(handle)?
+            .raw_window_handle
+            .context("window handle is missing")?;
+        let (mut cx, window) = unsafe { app_ref(app) }?.window(window_id)?;
+        window.update(&mut cx, |_, window, _| window.reparent(raw_handle))?
+    })
+    .log_err()
+    .is_some()
+}
+
+/// Take a window out of the host's native window and hide it without closing it, so that the
+/// host can destroy its window, such as when it pops out the plugin's editor. Move it into the
+/// host's new window with `gpui_window_reparent`.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_window_park(app: *mut GpuiApp, window_id: u64) -> bool {
+    maybe!({
+        let (mut cx, window) = unsafe { app_ref(app) }?.window(window_id)?;
+        window.update(&mut cx, |_, window, _| window.park_embedded())?
+    })
+    .log_err()
+    .is_some()
+}
+
+/// Dispatch an input event that the host received to a window. Returns whether GPUI handled the
+/// event, in which case the host shouldn't process it further.
+///
+/// # Safety
+///
+/// `app` must have been returned by `gpui_app_new` and not freed, and `event` must point to a
+/// valid `GpuiInputEvent`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_window_dispatch_event(
+    app: *mut GpuiApp,
+    window_id: u64,
+    event: *const GpuiInputEvent,
+) -> bool {
+    maybe!({
+        let event = unsafe { event.as_ref() }.context("event is null")?;
+        let event = unsafe { platform_input(event) }?;
+        let (mut cx, window) = unsafe { app_ref(app) }?.window(window_id)?;
+        let result = window.update(&mut cx, |_, window, cx| {
+            window.dispatch_embedded_input(event, cx)
+        })?;
+        anyhow::Ok(!result.propagate)
+    })
+    .log_err()
+    .unwrap_or(false)
+}
+
+fn embedded_window_options(handle: &GpuiRawWindowHandle) -> Result<WindowOptions> {
+    let (window, display): (rwh::RawWindowHandle, Option<rwh::RawDisplayHandle>) = match handle.kind
+    {
+        GPUI_WINDOW_HANDLE_APPKIT => (
+            rwh::AppKitWindowHandle::new(NonNull::new(handle.window).context("NSView is null")?)
+                .into(),
+            None,
+        ),
+        GPUI_WINDOW_HANDLE_WIN32 => (
+            rwh::Win32WindowHandle::new(
+                NonZeroIsize::new(handle.window as isize).context("HWND is null")?,
+            )
+            .into(),
+            None,
+        ),
+        GPUI_WINDOW_HANDLE_XLIB => (
+            rwh::XlibWindowHandle::new(handle.xid as c_ulong).into(),
+            Some(rwh::XlibDisplayHandle::new(NonNull::new(handle.display), handle.screen).into()),
+        ),
+        GPUI_WINDOW_HANDLE_XCB => (
+            rwh::XcbWindowHandle::new(
+                u32::try_from(handle.xid)
+                    .ok()
+                    .and_then(NonZeroU32::new)
+                    .context("invalid XCB window ID")?,
+            )
+            .into(),
+            Some(rwh::XcbDisplayHandle::new(NonNull::new(handle.display), handle.screen).into()),
+        ),
+        GPUI_WINDOW_HANDLE_WAYLAND => (
+            rwh::WaylandWindowHandle::new(
+                NonNull::new(handle.window).context("wl_surface is null")?,
+            )
+            .into(),
+            Some(
+                rwh::WaylandDisplayHandle::new(
+                    NonNull::new(handle.display).context("wl_display is null")?,
+                )
+                .into(),
+            ),
+        ),
+        kind => return Err(anyhow!("unknown window handle kind {kind}")),
+    };
+    let embedding_mode = match handle.embedding_mode {
+        GPUI_EMBEDDING_MODE_ATTACH => EmbeddingMode::Attach,
+        GPUI_EMBEDDING_MODE_CHILD => EmbeddingMode::Child,
+        mode => return Err(anyhow!("unknown embedding mode {mode}")),
+    };
+    let frame_driver = match handle.frame_driver {
+        GPUI_FRAME_DRIVER_PLATFORM => FrameDriver::Platform,
+        GPUI_FRAME_DRIVER_EXTERNAL => FrameDriver::External,
+        driver => return Err(anyhow!("unknown frame driver {driver}")),
+    };
+
+    let mut options = WindowOptions::for_embedded_window(window)
+        .with_embedding_mode(embedding_mode)
+        .with_frame_driver(frame_driver);
+    if let Some(display) = display {
+        options = options.with_raw_display_handle(display);
+    }
+    Ok(options)
+}
+
+unsafe fn platform_input(event: &GpuiInputEvent) -> Result<PlatformInput> {
+    let position = point(px(event.x), px(event.y));
+    let modifiers = modifiers(event.modifiers);
+    let click_count = event.click_count.max(1) as usize;
+    let button = || mouse_button(event.button)?.context("mouse button events need a button");
+
+    Ok(match event.kind {
+        GPUI_EVENT_MOUSE_MOVE => PlatformInput::MouseMove(MouseMoveEvent {
+            position,
+            pressed_button: mouse_button(event.button)?,
+            modifiers,
+        }),
+        GPUI_EVENT_MOUSE_DOWN => PlatformInput::MouseDown(MouseDownEvent {
+            button: button()?,
+            position,
+            modifiers,
+            click_count,
+            first_mouse: false,
+        }),
+        GPUI_EVENT_MOUSE_UP => PlatformInput::MouseUp(MouseUpEvent {
+            button: button()?,
+            position,
+            modifiers,
+            click_count,
+        }),
+        GPUI_EVENT_SCROLL_WHEEL => PlatformInput::ScrollWheel(ScrollWheelEvent {
+            position,
+            delta: ScrollDelta::Pixels(point(px(event.scroll_delta_x), px(event.scroll_delta_y))),
+            modifiers,
+            touch_phase: TouchPhase::Moved,
+        }),
+        GPUI_EVENT_KEY_DOWN => PlatformInput::KeyDown(KeyDownEvent {
+            keystroke: unsafe { keystroke(event) }?,
+            is_held: event.is_held,
+            prefer_character_input: false,
+        }),
+        GPUI_EVENT_KEY_UP => PlatformInput::KeyUp(KeyUpEvent {
+            keystroke: unsafe { keystroke(event) }?,
+        }),
+        kind => return Err(anyhow!("unknown event kind {kind}")),
+    })
+}
+
+fn modifiers(flags: u32) -> Modifiers {
+    Modifiers {
+        control: flags & GPUI_MODIFIER_CONTROL != 0,
+        alt: flags & GPUI_MODIFIER_ALT != 0,
+        shift: flags & GPUI_MODIFIER_SHIFT != 0,
+        platform: flags & GPUI_MODIFIER_PLATFORM != 0,
+        function: flags & GPUI_MODIFIER_FUNCTION != 0,
+    }
+}
+
+fn mouse_button(button: u32) -> Result<Option<MouseButton>> {
+    Ok(match button {
+        GPUI_MOUSE_BUTTON_NONE => None,
+        GPUI_MOUSE_BUTTON_LEFT => Some(MouseButton::Left),
+        GPUI_MOUSE_BUTTON_RIGHT => Some(MouseButton::Right),
+        GPUI_MOUSE_BUTTON_MIDDLE => Some(MouseButton::Middle),
+        button => return Err(anyhow!("unknown mouse button {button}")),
+    })
+}
+
+unsafe fn keystroke(event: &GpuiInputEvent) -> Result<Keystroke> {
+    anyhow::ensure!(!event.keystroke.is_null(), "key events need a keystroke");
+    let source = unsafe { CStr::from_ptr(event.keystroke) }.to_str()?;
+    let mut keystroke = Keystroke::parse(source)?;
+    keystroke.modifiers |= modifiers(event.modifiers);
+    Ok(keystroke)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::EmptyView;
+
+    #[test]
+    fn test_platform_input() {
+        let keystroke = c"cmd-s";
+        let event = GpuiInputEvent {
+            kind: GPUI_EVENT_KEY_DOWN,
+            x: 0.,
+            y: 0.,
+            button: GPUI_MOUSE_BUTTON_NONE,
+            click_count: 0,
+            scroll_delta_x: 0.,
+            scroll_delta_y: 0.,
+            modifiers: GPUI_MODIFIER_SHIFT,
+            keystroke: keystroke.as_ptr(),
+            is_held: false,
+        };
+        let PlatformInput::KeyDown(key_down) = (unsafe { platform_input(&event) }).unwrap() else {
+            panic!("expected a key down event");
+        };
+        assert_eq!(key_down.keystroke.key, "s");
+        assert!(key_down.keystroke.modifiers.platform);
+        assert!(key_down.keystroke.modifiers.shift);
+
+        let event = GpuiInputEvent {
+            kind: GPUI_EVENT_MOUSE_DOWN,
+            x: 10.,
+            y: 20.,
+            button: GPUI_MOUSE_BUTTON_RIGHT,
+            keystroke: std::ptr::null(),
+            ..event
+        };
+        let PlatformInput::MouseDown(mouse_down) = (unsafe { platform_input(&event) }).unwrap()
+        else {
+            panic!("expected a mouse down event");
+        };
+        assert_eq!(mouse_down.button, MouseButton::Right);
+        assert_eq!(mouse_down.position, point(px(10.), px(20.)));
+        assert_eq!(mouse_down.click_count, 1);
+
+        let event = GpuiInputEvent {
+            button: GPUI_MOUSE_BUTTON_NONE,
+            ..event
+        };
+        assert!(unsafe { platform_input(&event) }.is_err());
+    }
+
+    #[test]
+    fn test_shutdown_releases_root_components() {
+        let captured = Rc::new(());
+        register_root_component("editor", {
+            let captured = captured.clone();
+            move |_, _| {
+                let _captured = &captured;
+                EmptyView
+            }
+        });
+        assert_eq!(Rc::strong_count(&captured), 2);
+
+        gpui_shutdown();
+        assert_eq!(Rc::strong_count(&captured), 1);
+        assert!(ROOT_COMPONENTS.with_borrow(|components| components.is_empty()));
+    }
+}
@@ -0,0 +1,1059 @@
+//! A VST3 `IPlugView` for plugin editors written with GPUI.
+//!
+//! VST3 plugins return an `IPlugView` from `IEditController::createView`, and the host attaches
+//! it to a native window of its own. [`create_plug_view`] returns a view that opens a GPUI window
+//! embedded in that native window, follows the host's resizes, negotiates sizes with the
+//! window's [`SizeConstraints`](gpui::SizeConstraints), and forwards the keys that the host
+//! delivers to the view. When the host removes the view, its window is
+//! [parked](gpui::Window::park_embedded) rather than closed, so hosts that move editors to
+//! another window, such as when popping them out, attach the same window again with its state.
+//!
+//! The app must run inside the host's event loop, as started with
+//! [`Application::attach`](gpui::Application::attach). On Linux, the view pumps the app from the
+//! file descriptor and timer callbacks of the host's `IRunLoop` once the host sets its frame. The
+//! view also asks the host to resize it through its frame when its window is
+//! [resized](gpui::Window::resize). Hosts call the view on their UI thread, which must be the
+//! thread that the app runs on. Calls from other threads fail, logging an error, rather than
+//! touching the app.
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, c_char, c_ulong, c_void},
+    num::NonZeroIsize,
+    ptr::NonNull,
+    rc::Rc,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use gpui::{
+    AnyWindowHandle, App, AppContext as _, AsyncApp, Context, KeyDownEvent, KeyUpEvent, Keystroke,
+    MainThreadOnly, Modifiers, Pixels, PlatformInput, Render, ScrollDelta, ScrollWheelEvent, Size,
+    TouchPhase, Window, WindowOptions, point, px, size,
+};
+use raw_window_handle as rwh;
+use util::{ResultExt as _, maybe};
+
+type TResult = i32;
+type Tuid = [u8; 16];
+
+// VST3 uses COM's result codes on Windows, and its own elsewhere.
+#[cfg(target_os = "windows")]
+mod result {
+    pub const OK: super::TResult = 0;
+    pub const FALSE: super::TResult = 1;
+    pub const INVALID_ARGUMENT: super::TResult = 0x80070057_u32 as i32;
+    pub const NO_INTERFACE: super::TResult = 0x80004002_u32 as i32;
+}
+#[cfg(not(target_os = "windows"))]
+mod result {
+    pub const OK: super::TResult = 0;
+    pub const FALSE: super::TResult = 1;
+    pub const INVALID_ARGUMENT: super::TResult = 2;
+    pub const NO_INTERFACE: super::TResult = -1;
+}
+
+const FUNKNOWN_IID: Tuid = uid(0x00000000, 0x00000000, 0xC0000000, 0x00000046);
+const IPLUGVIEW_IID: Tuid = uid(0x5BC32507, 0xD06049EA, 0xA6151B52, 0x2B755B29);
+const IPLUGFRAME_IID: Tuid = uid(0x367FAF01, 0xAFA94693, 0x8D4DA2A0, 0xED0882A3);
+
+const PLATFORM_TYPE_HWND: &CStr = c"HWND";
+const PLATFORM_TYPE_NSVIEW: &CStr = c"NSView";
+const PLATFORM_TYPE_X11: &CStr = c"X11EmbedWindowID";
+
+#[cfg(target_os = "windows")]
+const NATIVE_PLATFORM_TYPE: &CStr = PLATFORM_TYPE_HWND;
+#[cfg(target_os = "macos")]
+const NATIVE_PLATFORM_TYPE: &CStr = PLATFORM_TYPE_NSVIEW;
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const NATIVE_PLATFORM_TYPE: &CStr = PLATFORM_TYPE_X11;
+
+// `KeyModifier` in `keycodes.h`.
+const MODIFIER_SHIFT: i16 = 1 << 0;
+const MODIFIER_ALTERNATE: i16 = 1 << 1;
+const MODIFIER_COMMAND: i16 = 1 << 2;
+const MODIFIER_CONTROL: i16 = 1 << 3;
+
+// `VirtualKeyCodes` in `keycodes.h`.
+const KEY_BACK: i16 = 1;
+const KEY_TAB: i16 = 2;
+const KEY_RETURN: i16 = 4;
+const KEY_ESCAPE: i16 = 6;
+const KEY_SPACE: i16 = 7;
+const KEY_END: i16 = 9;
+const KEY_HOME: i16 = 10;
+const KEY_LEFT: i16 = 11;
+const KEY_UP: i16 = 12;
+const KEY_RIGHT: i16 = 13;
+const KEY_DOWN: i16 = 14;
+const KEY_PAGEUP: i16 = 15;
+const KEY_PAGEDOWN: i16 = 16;
+const KEY_ENTER: i16 = 19;
+const KEY_INSERT: i16 = 21;
+const KEY_DELETE: i16 = 22;
+const KEY_F1: i16 = 40;
+const KEY_F12: i16 = 51;
+
+/// Build an interface ID like VST3's `INLINE_UID`, which lays the ID out like COM's GUIDs on
+/// Windows.
+const fn uid(l1: u32, l2: u32, l3: u32, l4: u32) -> Tuid {
+    let l1 = if cfg!(target_os = "windows") {
+        l1.to_le_bytes()
+    } else {
+        l1.to_be_bytes()
+    };
+    let l2 = l2.to_be_bytes();
+    let l2 = if cfg!(target_os = "windows") {
+        [l2[1], l2[0], l2[3], l2[2]]
+    } else {
+        l2
+    };
+    let l3 = l3.to_be_bytes();
+    let l4 = l4.to_be_bytes();
+    [
+        l1[0], l1[1], l1[2], l1[3], l2[0], l2[1], l2[2], l2[3], l3[0], l3[1], l3[2], l3[3], l4[0],
+        l4[1], l4[2], l4[3],
+    ]
+}
+
+/// `ViewRect` in `iplugview.h`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct ViewRect {
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+}
+
+/// The `FUnknown` methods that the vtable of every VST3 interface starts with.
+#[repr(C)]
+struct UnknownVtbl {
+    query_interface:
+        unsafe extern "system" fn(*mut c_void, *const Tuid, *mut *mut c_void) -> TResult,
+    add_ref: unsafe extern "system" fn(*mut c_void) -> u32,
+    release: unsafe extern "system" fn(*mut c_void) -> u32,
+}
+
+/// A counted reference to a VST3 object, whose vtable `V` starts with [`UnknownVtbl`]. The
+/// reference is released when this is dropped.
+struct ObjectRef<V> {
+    object: NonNull<*const V>,
+}
+
+impl<V> ObjectRef<V> {
+    /// Take a reference of our own to an object that the host passed to the view.
+    unsafe fn from_borrowed(object: *mut c_void) -> Option<Self> {
+        let this = Self {
+            object: NonNull::new(object.cast())?,
+        };
+        unsafe { (this.unknown().add_ref)(this.as_ptr()) };
+        Some(this)
+    }
+
+    fn as_ptr(&self) -> *mut c_void {
+        self.object.as_ptr().cast()
+    }
+
+    fn vtbl(&self) -> &V {
+        unsafe { &**self.object.as_ptr() }
+    }
+
+    fn unknown(&self) -> &UnknownVtbl {
+        unsafe { &*(*self.object.as_ptr()).cast::<UnknownVtbl>() }
+    }
+
+    /// Ask the object for another of its interfaces.
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    fn query<W>(&self, iid: &Tuid) -> Option<ObjectRef<W>> {
+        let mut object = std::ptr::null_mut();
+        let result = unsafe { (self.unknown().query_interface)(self.as_ptr(), iid, &mut object) };
+        if result != result::OK {
+            return None;
+        }
+        // The object added the reference that's returned.
+        Some(ObjectRef {
+            object: NonNull::new(object.cast())?,
+        })
+    }
+}
+
+impl<V> Clone for ObjectRef<V> {
+    fn clone(&self) -> Self {
+        unsafe { (self.unknown().add_ref)(self.as_ptr()) };
+        Self {
+            object: self.object,
+        }
+    }
+}
+
+impl<V> Drop for ObjectRef<V> {
+    fn drop(&mut self) {
+        unsafe { (self.unknown().release)(self.as_ptr()) };
+    }
+}
+
+/// `IPlugFrame` in `iplugview.h`, which the host passes to `setFrame`.
+#[repr(C)]
+struct PlugFrameVtbl {
+    unknown: UnknownVtbl,
+    resize_view: unsafe extern "system" fn(*mut c_void, *mut PlugView, *mut ViewRect) -> TResult,
+}
+
+/// The `FUnknown` and `IPlugView` methods, in the order of `iplugview.h`.
+#[repr(C)]
+struct PlugViewVtbl {
+    query_interface:
+        unsafe extern "system" fn(*mut PlugView, *const Tuid, *mut *mut c_void) -> TResult,
+    add_ref: unsafe extern "system" fn(*mut PlugView) -> u32,
+    release: unsafe extern "system" fn(*mut PlugView) -> u32,
+    is_platform_type_supported: unsafe extern "system" fn(*mut PlugView, *const c_char) -> TResult,
+    attached: unsafe extern "system" fn(*mut PlugView, *mut c_void, *const c_char) -> TResult,
+    removed: unsafe extern "system" fn(*mut PlugView) -> TResult,
+    on_wheel: unsafe extern "system" fn(*mut PlugView, f32) -> TResult,
+    on_key_down: unsafe extern "system" fn(*mut PlugView, u16, i16, i16) -> TResult,
+    on_key_up: unsafe extern "system" fn(*mut PlugView, u16, i16, i16) -> TResult,
+    get_size: unsafe extern "system" fn(*mut PlugView, *mut ViewRect) -> TResult,
+    on_size: unsafe extern "system" fn(*mut PlugView, *mut ViewRect) -> TResult,
+    on_focus: unsafe extern "system" fn(*mut PlugView, u8) -> TResult,
+    set_frame: unsafe extern "system" fn(*mut PlugView, *mut c_void) -> TResult,
+    can_resize: unsafe extern "system" fn(*mut PlugView) -> TResult,
+    check_size_constraint: unsafe extern "system" fn(*mut PlugView, *mut ViewRect) -> TResult,
+}
+
+static PLUG_VIEW_VTBL: PlugViewVtbl = PlugViewVtbl {
+    query_interface,
+    add_ref,
+    release,
+    is_platform_type_supported,
+    attached,
+    removed,
+    on_wheel,
+    on_key_down,
+    on_key_up,
+    get_size,
+    on_size,
+    on_focus,
+    set_frame,
+    can_resize,
+    check_size_constraint,
+};
+
+type OpenEditor = dyn Fn(WindowOptions, &mut App) -> Result<AnyWindowHandle>;
+
+#[repr(C)]
+struct PlugView {
+    vtbl: &'static PlugViewVtbl,
+    ref_count: AtomicU32,
+    state: MainThreadOnly<RefCell<PlugViewState>>,
+}
+
+struct PlugViewState {
+    cx: AsyncApp,
+    open: Rc<OpenEditor>,
+    /// The window, which is parked while the view isn't attached.
+    window: Option<AnyWindowHandle>,
+    attached: bool,
+    size: Size<Pixels>,
+    /// The frame that the host set, through which the view asks it to resize the view.
+    frame: Option<ObjectRef<PlugFrameVtbl>>,
+    /// The view's callbacks in the host's run loop, which pump the app.
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    run_loop: Option<run_loop::Registration>,
+}
+
+impl Drop for PlugViewState {
+    fn drop(&mut self) {
+        // Hosts should call `removed` before releasing the view, but not all of them do.
+        if let Some(window) = self.window.take() {
+            window
+                .update(&mut self.cx, |_, window, _| window.detach_embedded())
+                .log_err();
+        }
+    }
+}
+
+/// Create a VST3 `IPlugView` that opens a window rendering the view built by `build` when the
+/// host attaches it, for plugins to return from `IEditController::createView`. The host owns the
+/// returned reference, and releases it once it's done with the view. `size` is the size that the
+/// host is told the editor has before it's attached.
+///
+/// Sizes are exchanged with the host in physical pixels, except on macOS, where they're in
+/// points. Hosts' requests to resize the editor are adjusted with
+/// [`Window::adjust_embedded_size`].
+pub fn create_plug_view<V: Render + 'static>(
+    cx: &AsyncApp,
+    size: Size<Pixels>,
+    build: impl Fn(&mut Window, &mut Context<V>) -> V + 'static,
+) -> *mut c_void {
+    let build = Rc::new(build);
+    let open: Rc<OpenEditor> = Rc::new(move |options, cx| {
+        let build = build.clone();
+        let handle = cx.open_window(options, move |window, cx| cx.new(|cx| build(window, cx)))?;
+        Ok(handle.into())
+    });
+    let view = Box::new(PlugView {
+        vtbl: &PLUG_VIEW_VTBL,
+        ref_count: AtomicU32::new(1),
+        state: MainThreadOnly::new(RefCell::new(PlugViewState {
+            cx: cx.clone(),
+            open,
+            window: None,
+            attached: false,
+            size,
+            frame: None,
+            #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+            run_loop: None,
+        })),
+    });
+    Box::into_raw(view).cast()
+}
+
+impl PlugView {
+    #[track_caller]
+    fn state(&self) -> Result<&RefCell<PlugViewState>> {
+        self.state.try_get()
+    }
+
+    /// The app and the window that the view is attached to, without holding the state borrowed
+    /// while the window is updated, in case the host calls back into the view.
+    #[track_caller]
+    fn window(&self) -> Result<(AsyncApp, Option<AnyWindowHandle>)> {
+        let state = self.state()?.borrow();
+        Ok((state.cx.clone(), state.window))
+    }
+}
+
+/// VST3 sizes are in physical pixels, except on macOS, where they're in points like GPUI's.
+fn view_rect_scale(window: &Window) -> f32 {
+    if cfg!(target_os = "macos") {
+        1.
+    } else {
+        window.scale_factor()
+    }
+}
+
+fn rect_size(rect: &ViewRect, scale: f32) -> Size<Pixels> {
+    size(
+        px((rect.right - rect.left) as f32 / scale),
+        px((rect.bottom - rect.top) as f32 / scale),
+    )
+}
+
+fn set_rect_size(rect: &mut ViewRect, size: Size<Pixels>, scale: f32) {
+    rect.right = rect.left + (f32::from(size.width) * scale).round() as i32;
+    rect.bottom = rect.top + (f32::from(size.height) * scale).round() as i32;
+}
+
+unsafe extern "system" fn query_interface(
+    this: *mut PlugView,
+    iid: *const Tuid,
+    object: *mut *mut c_void,
+) -> TResult {
+    if iid.is_null() || object.is_null() {
+        return result::INVALID_ARGUMENT;
+    }
+    let iid = unsafe { &*iid };
+    if *iid == FUNKNOWN_IID || *iid == IPLUGVIEW_IID {
+        unsafe {
+            add_ref(this);
+            *object = this.cast();
+        }
+        result::OK
+    } else {
+        unsafe { *object = std::ptr::null_mut() };
+        result::NO_INTERFACE
+    }
+}
+
+unsafe extern "system" fn add_ref(this: *mut PlugView) -> u32 {
+    unsafe { &*this }.ref_count.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "system" fn release(this: *mut PlugView) -> u32 {
+    let ref_count = unsafe { &*this }.ref_count.fetch_sub(1, Ordering::AcqRel) - 1;
+    if ref_count == 0 {
+        drop(unsafe { Box::from_raw(this) });
+    }
+    ref_count
+}
+
+unsafe extern "system" fn is_platform_type_supported(
+    _this: *mut PlugView,
+    platform_type: *const c_char,
+) -> TResult {
+    if platform_type.is_null() {
+        return result::INVALID_ARGUMENT;
+    }
+    if unsafe { CStr::from_ptr(platform_type) } == NATIVE_PLATFORM_TYPE {
+        result::OK
+    } else {
+        result::FALSE
+    }
+}
+
+unsafe extern "system" fn attached(
+    this: *mut PlugView,
+    parent: *mut c_void,
+    platform_type: *const c_char,
+) -> TResult {
+    let view = unsafe { &*this };
+    maybe!({
+        let options = unsafe { window_options(parent, platform_type) }?;
+        let (mut cx, window) = view.window()?;
+        anyhow::ensure!(
+            !view.state()?.borrow().attached,
+            "the view is already attached"
+        );
+        if let Some(window) = window {
+            let raw_handle = options
+                .raw_window_handle
+                .context("window handle is missing")?;
+            let reparented = window
+                .update(&mut cx, |_, window, _| window.reparent(raw_handle))
+                .and_then(|reparented| reparented)
+                .context("failed to attach the parked window")
+                .log_err();
+            if reparented.is_some() {
+                view.state()?.borrow_mut().attached = true;
+                return anyhow::Ok(result::OK);
+            }
+            // Fall back to opening another window.
+            view.state()?.borrow_mut().window = None;
+            window
+                .update(&mut cx, |_, window, _| window.detach_embedded())
+                .log_err();
+        }
+        let open = view.state()?.borrow().open.clone();
+        let window = cx.update(|cx| open(options, cx))??;
+        window.update(&mut cx, |_, window, _| {
+            window.on_embedded_resize(move |size, window| request_resize(this, size, window))
+        })?;
+        let mut state = view.state()?.borrow_mut();
+        state.window = Some(window);
+        state.attached = true;
+        anyhow::Ok(result::OK)
+    })
+    .log_err()
+    .unwrap_or(result::FALSE)
+}
+
+unsafe fn window_options(
+    parent: *mut c_void,
+    platform_type: *const c_char,
+) -> Result<WindowOptions> {
+    anyhow::ensure!(!platform_type.is_null(), "platform type is null");
+    let platform_type = unsafe { CStr::from_ptr(platform_type) };
+    if platform_type == PLATFORM_TYPE_HWND {
+        // The host owns the HWND and its window procedure, so render into a child window of it.
+        let hwnd = NonZeroIsize::new(parent as isize).context("HWND is null")?;
+        Ok(WindowOptions::for_embedded_child_window(
+            rwh::Win32WindowHandle::new(hwnd).into(),
+        ))
+    } else if platform_type == PLATFORM_TYPE_NSVIEW {
+        let ns_view = NonNull::new(parent).context("NSView is null")?;
+        Ok(WindowOptions::for_embedded_window(
+            rwh::AppKitWindowHandle::new(ns_view).into(),
+        ))
+    } else if platform_type == PLATFORM_TYPE_X11 {
+        // Hosts only pass their window, which is on the display that GPUI connects to.
+        let screen = x11_screen(&std::env::var("DISPLAY").unwrap_or_default());
+        Ok(WindowOptions::for_embedded_window_with_display(
+            rwh::XlibWindowHandle::new(parent as c_ulong).into(),
+            rwh::XlibDisplayHandle::new(None, screen).into(),
+        ))
+    } else {
+        Err(anyhow!("unsupported platform type {platform_type:?}"))
+    }
+}
+
+/// The screen of an X display name like `:0.1`, which is 0 unless the name picks another one.
+fn x11_screen(display: &str) -> i32 {
+    display
+        .rsplit_once(':')
+        .and_then(|(_, display)| display.split_once('.'))
+        .and_then(|(_, screen)| screen.parse().ok())
+        .unwrap_or(0)
+}
+
+unsafe extern "system" fn removed(this: *mut PlugView) -> TResult {
+    let view = unsafe { &*this };
+    maybe!({
+        let (mut cx, window) = view.window()?;
+        let window = window.context("the view isn't attached")?;
+        anyhow::ensure!(view.state()?.borrow().attached, "the view isn't attached");
+        view.state()?.borrow_mut().attached = false;
+        // Keep the window for when the host attaches the view again, unless it can't be parked.
+        let parked = window.update(&mut cx, |_, window, _| window.park_embedded())?;
+        if parked.is_err() {
+            view.state()?.borrow_mut().window = None;
+            window.update(&mut cx, |_, window, _| window.detach_embedded())?;
+        }
+        anyhow::Ok(result::OK)
+    })
+    .log_err()
+    .unwrap_or(result::FALSE)
+}
+
+unsafe extern "system" fn on_wheel(this: *mut PlugView, distance: f32) -> TResult {
+    dispatch(unsafe { &*this }, |window| {
+        Some(PlatformInput::ScrollWheel(ScrollWheelEvent {
+            position: window.mouse_position(),
+            delta: ScrollDelta::Lines(point(0., distance)),
+            modifiers: window.modifiers(),
+            touch_phase: TouchPhase::Moved,
+        }))
+    })
+}
+
+unsafe extern "system" fn on_key_down(
+    this: *mut PlugView,
+    character: u16,
+    key_code: i16,
+    modifiers: i16,
+) -> TResult {
+    dispatch(unsafe { &*this }, |_| {
+        Some(PlatformInput::KeyDown(KeyDownEvent {
+            keystroke: keystroke(character, key_code, modifiers)?,
+            is_held: false,
+            prefer_character_input: false,
+        }))
+    })
+}
+
+unsafe extern "system" fn on_key_up(
+    this: *mut PlugView,
+    character: u16,
+    key_code: i16,
+    modifiers: i16,
+) -> TResult {
+    dispatch(unsafe { &*this }, |_| {
+        Some(PlatformInput::KeyUp(KeyUpEvent {
+            keystroke: keystroke(character, key_code, modifiers)?,
+        }))
+    })
+}
+
+/// Dispatch input that the host delivered to the view, returning whether GPUI handled it, in
+/// which case the host shouldn't process it further.
+fn dispatch(view: &PlugView, event: impl FnOnce(&Window) -> Option<PlatformInput>) -> TResult {
+    maybe!({
+        let (mut cx, window) = view.window()?;
+        let window = window.context("the view isn't attached")?;
+        let handled = window.update(&mut cx, |_, window, cx| {
+            let event = event(window)?;
+            Some(!window.dispatch_embedded_input(event, cx).propagate)
+        })?;
+        anyhow::Ok(if handled == Some(true) {
+            result::OK
+        } else {
+            result::FALSE
+        })
+    })
+    .log_err()
+    .unwrap_or(result::FALSE)
+}
+
+unsafe extern "system" fn get_size(this: *mut PlugView, rect: *mut ViewRect) -> TResult {
+    let view = unsafe { &*this };
+    let Some(rect) = (unsafe { rect.as_mut() }) else {
+        return result::INVALID_ARGUMENT;
+    };
+    maybe!({
+        let (mut cx, window) = view.window()?;
+        let (size, scale) = match window {
+            Some(window) => window.update(&mut cx, |_, window, _| {
+                (window.viewport_size(), view_rect_scale(window))
+            })?,
+            None => (view.state()?.borrow().size, 1.),
+        };
+        set_rect_size(rect, size, scale);
+        anyhow::Ok(result::OK)
+    })
+    .log_err()
+    .unwrap_or(result::FALSE)
+}
+
+unsafe extern "system" fn on_size(this: *mut PlugView, rect: *mut ViewRect) -> TResult {
+    let view = unsafe { &*this };
+    let Some(rect) = (unsafe { rect.as_ref() }) else {
+        return result::INVALID_ARGUMENT;
+    };
+    maybe!({
+        let (mut cx, window) = view.window()?;
+        let size = match window {
+            Some(window) => window.update(&mut cx, |_, window, _| {
+                let size = rect_size(rect, view_rect_scale(window));
+                window.notify_embedded_resize(size);
+                size
+            })?,
+            None => rect_size(rect, 1.),
+        };
+        view.state()?.borrow_mut().size = size;
+        anyhow::Ok(result::OK)
+    })
+    .log_err()
+    .unwrap_or(result::FALSE)
+}
+
+unsafe extern "system" fn on_focus(this: *mut PlugView, state: u8) -> TResult {
+    let view = unsafe { &*this };
+    maybe!({
+        let (mut cx, window) = view.window()?;
+        let window = window.context("the view isn't attached")?;
+        window.update(&mut cx, |_, window, cx| {
+            if state != 0 {
+                window.request_keyboard_focus(cx);
+            } else {
+                window.notify_embedded_focus_lost(cx);
+            }
+        })?;
+        anyhow::Ok(result::OK)
+    })
+    .log_err()
+    .unwrap_or(result::FALSE)
+}
+
+unsafe extern "system" fn set_frame(this: *mut PlugView, frame: *mut c_void) -> TResult {
+    let view = unsafe { &*this };
+    maybe!({
+        let previous = {
+            let mut state = view.state()?.borrow_mut();
+            #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+            drop(state.run_loop.take());
+            state.frame.take()
+        };
+        drop(previous);
+
+        let frame = unsafe { ObjectRef::<PlugFrameVtbl>::from_borrowed(frame) };
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let run_loop = match &frame {
+            Some(frame) => {
+                let cx = view.state()?.borrow().cx.clone();
+                run_loop::Registration::new(frame, &cx)
+                    .context("failed to pump the app from the host's run loop")
+                    .log_err()
+            }
+            None => None,
+        };
+        let mut state = view.state()?.borrow_mut();
+        state.frame = frame;
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        {
+            state.run_loop = run_loop;
+        }
+        anyhow::Ok(result::OK)
+    })
+    .log_err()
+    .unwrap_or(result::FALSE)
+}
+
+/// Ask the host to resize the view to the size that its window asked for. Hosts resize the view
+/// with `onSize` before `resizeView` returns, so this waits until the window is no longer being
+/// updated.
+fn request_resize(this: *mut PlugView, size: Size<Pixels>, window: &Window) {
+    let mut rect = ViewRect::default();
+    set_rect_size(&mut rect, size, view_rect_scale(window));
+    let Some((cx, _)) = unsafe { &*this }.window().log_err() else {
+        return;
+    };
+    // Keep the view alive until the host has been asked.
+    unsafe { add_ref(this) };
+    cx.foreground_executor()
+        .spawn(async move {
+            maybe!({
+                let view = unsafe { &*this };
+                let frame = view
+                    .state()?
+                    .borrow()
+                    .frame
+                    .clone()
+                    .context("the host didn't set the view's frame")?;
+                let result = unsafe { (frame.vtbl().resize_view)(frame.as_ptr(), this, &mut rect) };
+                anyhow::ensure!(result == result::OK, "the host didn't resize the view");
+                anyhow::Ok(())
+            })
+            .log_err();
+            unsafe { release(this) };
+        })
+        .detach();
+}
+
+unsafe extern "system" fn can_resize(this: *mut PlugView) -> TResult {
+    let view = unsafe { &*this };
+    maybe!({
+        let (mut cx, window) = view.window()?;
+        let resizable = match window {
+            Some(window) => {
+                window.update(&mut cx, |_, window, _| window.size_constraints().resizable)?
+            }
+            None => true,
+        };
+        anyhow::Ok(if resizable { result::OK } else { result::FALSE })
+    })
+    .log_err()
+    .unwrap_or(result::FALSE)
+}
+
+unsafe extern "system" fn check_size_constraint(
+    this: *mut PlugView,
+    rect: *mut ViewRect,
+) -> TResult {
+    let view = unsafe { &*this };
+    let Some(rect) = (unsafe { rect.as_mut() }) else {
+        return result::INVALID_ARGUMENT;
+    };
+    maybe!({
+        let (mut cx, window) = view.window()?;
+        let Some(window) = window else {
+            return anyhow::Ok(result::OK);
+        };
+        window.update(&mut cx, |_, window, cx| {
+            let scale = view_rect_scale(window);
+            let size = window.adjust_embedded_size(rect_size(rect, scale), cx);
+            set_rect_size(rect, size, scale);
+        })?;
+        anyhow::Ok(result::OK)
+    })
+    .log_err()
+    .unwrap_or(result::FALSE)
+}
+
+fn modifiers(flags: i16) -> Modifiers {
+    // VST3's command key is the control key outside of macOS, where its control key is the
+    // Windows or super key.
+    let (command, control) = (flags & MODIFIER_COMMAND != 0, flags & MODIFIER_CONTROL != 0);
+    Modifiers {
+        shift: flags & MODIFIER_SHIFT != 0,
+        alt: flags & MODIFIER_ALTERNATE != 0,
+        control: if cfg!(target_os = "macos") {
+            control
+        } else {
+            command
+        },
+        platform: if cfg!(target_os = "macos") {
+            command
+        } else {
+            control
+        },
+        function: false,
+    }
+}
+
+/// Translate a key that the host delivered to the view. Hosts pass either a virtual key code, or
+/// the character that the key types.
+fn keystroke(character: u16, key_code: i16, flags: i16) -> Option<Keystroke> {
+    let modifiers = modifiers(flags);
+    let types_text = !modifiers.control && !modifiers.platform;
+    let key = match key_code {
+        0 => {
+            let character = char::from_u32(character as u32).filter(|c| !c.is_control())?;
+            return Some(Keystroke {
+                modifiers,
+                key: character.to_lowercase().to_string(),
+                key_char: types_text.then(|| character.to_string()),
+            });
+        }
+        KEY_F1..=KEY_F12 => {
+            return Some(Keystroke {
+                modifiers,
+                key: format!("f{}", key_code - KEY_F1 + 1),
+                key_char: None,
+            });
+        }
+        KEY_SPACE => {
+            return Some(Keystroke {
+                modifiers,
+                key: "space".into(),
+                key_char: types_text.then(|| " ".into()),
+            });
+        }
+        KEY_BACK => "backspace",
+        KEY_TAB => "tab",
+        KEY_RETURN | KEY_ENTER => "enter",
+        KEY_ESCAPE => "escape",
+        KEY_END => "end",
+        KEY_HOME => "home",
+        KEY_LEFT => "left",
+        KEY_UP => "up",
+        KEY_RIGHT => "right",
+        KEY_DOWN => "down",
+        KEY_PAGEUP => "pageup",
+        KEY_PAGEDOWN => "pagedown",
+        KEY_INSERT => "insert",
+        KEY_DELETE => "delete",
+        _ => return None,
+    };
+    Some(Keystroke {
+        modifiers,
+        key: key.into(),
+        key_char: None,
+    })
+}
+
+/// Pumping the app from the host's event loop on Linux, where hosts hand plugins file
+/// descriptor and timer callbacks through `Linux::IRunLoop`, which the host's frame implements.
+/// On macOS and Windows, the host's event loop processes the app's work by itself.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod run_loop {
+    use super::*;
+
+    const IRUNLOOP_IID: Tuid = uid(0x18C35366, 0x97764F1A, 0x9C5B8385, 0x7A871389);
+    const IEVENTHANDLER_IID: Tuid = uid(0x561E65C9, 0x13A0496F, 0x813A2C35, 0x654D7983);
+    const ITIMERHANDLER_IID: Tuid = uid(0x10BDD94F, 0x41424774, 0x821FAD8F, 0xECA72CA9);
+
+    /// How often the app is pumped for its timers, which don't make its file descriptor readable.
+    const TIMER_INTERVAL_MS: u64 = 16;
+
+    type FileDescriptor = i32;
+
+    /// `Linux::IRunLoop` in `iplugview.h`.
+    #[repr(C)]
+    struct RunLoopVtbl {
+        unknown: UnknownVtbl,
+        register_event_handler:
+            unsafe extern "system" fn(*mut c_void, *mut c_void, FileDescriptor) -> TResult,
+        unregister_event_handler: unsafe extern "system" fn(*mut c_void, *mut c_void) -> TResult,
+        register_timer: unsafe extern "system" fn(*mut c_void, *mut c_void, u64) -> TResult,
+        unregister_timer: unsafe extern "system" fn(*mut c_void, *mut c_void) -> TResult,
+    }
+
+    /// `Linux::IEventHandler` in `iplugview.h`.
+    #[repr(C)]
+    struct EventHandlerVtbl {
+        unknown: UnknownVtbl,
+        on_fd_is_set: unsafe extern "system" fn(*mut c_void, FileDescriptor),
+    }
+
+    /// `Linux::ITimerHandler` in `iplugview.h`.
+    #[repr(C)]
+    struct TimerHandlerVtbl {
+        unknown: UnknownVtbl,
+        on_timer: unsafe extern "system" fn(*mut c_void),
+    }
+
+    const HANDLER_UNKNOWN_VTBL: UnknownVtbl = UnknownVtbl {
+        query_interface: handler_query_interface,
+        add_ref: handler_add_ref,
+        release: handler_release,
+    };
+
+    static EVENT_HANDLER_VTBL: EventHandlerVtbl = EventHandlerVtbl {
+        unknown: HANDLER_UNKNOWN_VTBL,
+        on_fd_is_set,
+    };
+
+    static TIMER_HANDLER_VTBL: TimerHandlerVtbl = TimerHandlerVtbl {
+        unknown: HANDLER_UNKNOWN_VTBL,
+        on_timer,
+    };
+
+    /// An event or timer handler, which pumps the app whenever the host calls it.
+    #[repr(C)]
+    struct Handler {
+        /// The start of an [`EventHandlerVtbl`] or a [`TimerHandlerVtbl`].
+        vtbl: &'static UnknownVtbl,
+        iid: Tuid,
+        ref_count: AtomicU32,
+        cx: MainThreadOnly<AsyncApp>,
+    }
+
+    fn handler<V>(vtbl: &'static V, iid: Tuid, cx: &AsyncApp) -> ObjectRef<V> {
+        let handler = Box::new(Handler {
+            // The vtable starts with its `FUnknown` methods.
+            vtbl: unsafe { &*(vtbl as *const V).cast::<UnknownVtbl>() },
+            iid,
+            ref_count: AtomicU32::new(1),
+            cx: MainThreadOnly::new(cx.clone()),
+        });
+        ObjectRef {
+            object: NonNull::from(Box::leak(handler)).cast(),
+        }
+    }
+
+    unsafe extern "system" fn handler_query_interface(
+        this: *mut c_void,
+        iid: *const Tuid,
+        object: *mut *mut c_void,
+    ) -> TResult {
+        if iid.is_null() || object.is_null() {
+            return result::INVALID_ARGUMENT;
+        }
+        let handler = unsafe { &*this.cast::<Handler>() };
+        let iid = unsafe { &*iid };
+        if *iid == FUNKNOWN_IID || *iid == handler.iid {
+            unsafe {
+                handler_add_ref(this);
+                *object = this;
+            }
+            result::OK
+        } else {
+            unsafe { *object = std::ptr::null_mut() };
+            result::NO_INTERFACE
+        }
+    }
+
+    unsafe extern "system" fn handler_add_ref(this: *mut c_void) -> u32 {
+        let handler = unsafe { &*this.cast::<Handler>() };
+        handler.ref_count.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    unsafe extern "system" fn handler_release(this: *mut c_void) -> u32 {
+        let handler = unsafe { &*this.cast::<Handler>() };
+        let ref_count = handler.ref_count.fetch_sub(1, Ordering::AcqRel) - 1;
+        if ref_count == 0 {
+            drop(unsafe { Box::from_raw(this.cast::<Handler>()) });
+        }
+        ref_count
+    }
+
+    unsafe extern "system" fn on_fd_is_set(this: *mut c_void, _fd: FileDescriptor) {
+        unsafe { pump(this) };
+    }
+
+    unsafe extern "system" fn on_timer(this: *mut c_void) {
+        unsafe { pump(this) };
+    }
+
+    unsafe fn pump(this: *mut c_void) {
+        let handler = unsafe { &*this.cast::<Handler>() };
+        handler.cx.try_get().and_then(|cx| cx.pump()).log_err();
+    }
+
+    /// The view's handlers, registered with the host's run loop until this is dropped.
+    pub(super) struct Registration {
+        run_loop: ObjectRef<RunLoopVtbl>,
+        event_handler: Option<ObjectRef<EventHandlerVtbl>>,
+        timer_handler: ObjectRef<TimerHandlerVtbl>,
+    }
+
+    impl Registration {
+        pub(super) fn new(frame: &ObjectRef<PlugFrameVtbl>, cx: &AsyncApp) -> Result<Self> {
+            let run_loop = frame
+                .query::<RunLoopVtbl>(&IRUNLOOP_IID)
+                .context("the host's frame doesn't implement IRunLoop")?;
+            let timer_handler = handler(&TIMER_HANDLER_VTBL, ITIMERHANDLER_IID, cx);
+            let result = unsafe {
+                (run_loop.vtbl().register_timer)(
+                    run_loop.as_ptr(),
+                    timer_handler.as_ptr(),
+                    TIMER_INTERVAL_MS,
+                )
+            };
+            anyhow::ensure!(result == result::OK, "the host didn't register the timer");
+            let mut registration = Self {
+                run_loop,
+                event_handler: None,
+                timer_handler,
+            };
+
+            // Input and tasks are processed as soon as they arrive, rather than on the next timer.
+            if let Some(fd) = cx.event_fd()? {
+                let event_handler = handler(&EVENT_HANDLER_VTBL, IEVENTHANDLER_IID, cx);
+                let run_loop = &registration.run_loop;
+                let result = unsafe {
+                    (run_loop.vtbl().register_event_handler)(
+                        run_loop.as_ptr(),
+                        event_handler.as_ptr(),
+                        fd,
+                    )
+                };
+                if result == result::OK {
+                    registration.event_handler = Some(event_handler);
+                } else {
+                    log::warn!("the host didn't register the event handler, pumping on its timer");
+                }
+            }
+            Ok(registration)
+        }
+    }
+
+    impl Drop for Registration {
+        fn drop(&mut self) {
+            let run_loop = &self.run_loop;
+            unsafe {
+                (run_loop.vtbl().unregister_timer)(run_loop.as_ptr(), self.timer_handler.as_ptr());
+                if let Some(event_handler) = &self.event_handler {
+                    (run_loop.vtbl().unregister_event_handler)(
+                        run_loop.as_ptr(),
+                        event_handler.as_ptr(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interface_ids() {
+        #[cfg(target_os = "windows")]
+        assert_eq!(
+            IPLUGVIEW_IID,
+            [
+                0x07, 0x25, 0xC3, 0x5B, 0x60, 0xD0, 0xEA, 0x49, 0xA6, 0x15, 0x1B, 0x52, 0x2B, 0x75,
+                0x5B, 0x29
+            ]
+        );
+        #[cfg(not(target_os = "windows"))]
+        assert_eq!(
+            IPLUGVIEW_IID,
+            [
+                0x5B, 0xC3, 0x25, 0x07, 0xD0, 0x60, 0x49, 0xEA, 0xA6, 0x15, 0x1B, 0x52, 0x2B, 0x75,
+                0x5B, 0x29
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keystroke() {
+        let typed = keystroke('S' as u16, 0, MODIFIER_SHIFT).unwrap();
+        assert_eq!(typed.key, "s");
+        assert_eq!(typed.key_char.as_deref(), Some("S"));
+        assert!(typed.modifiers.shift);
+
+        let arrow = keystroke(0, KEY_LEFT, MODIFIER_COMMAND).unwrap();
+        assert_eq!(arrow.key, "left");
+        assert!(arrow.modifiers.secondary());
+        assert_eq!(arrow.key_char, None);
+
+        assert_eq!(keystroke(0, KEY_F1 + 4, 0).unwrap().key, "f5");
+        assert_eq!(
+            keystroke(0, KEY_SPACE, 0).unwrap().key_char.as_deref(),
+            Some(" ")
+        );
+        assert!(keystroke(0, 0, 0).is_none());
+    }
+
+    #[test]
+    fn test_x11_screen() {
+        assert_eq!(x11_screen(":0"), 0);
+        assert_eq!(x11_screen(":1.2"), 2);
+        assert_eq!(x11_screen("localhost:10.1"), 1);
+        assert_eq!(x11_screen(""), 0);
+    }
+
+    #[test]
+    fn test_view_rect_sizes() {
+        let mut rect = ViewRect {
+            left: 10,
+            top: 20,
+            right: 410,
+            bottom: 320,
+        };
+        assert_eq!(rect_size(&rect, 2.), size(px(200.), px(150.)));
+        set_rect_size(&mut rect, size(px(100.), px(50.)), 2.);
+        assert_eq!(
+            rect,
+            ViewRect {
+                left: 10,
+                top: 20,
+                right: 210,
+                bottom: 120,
+            }
+        );
+    }
+}
@@ -483,7 +483,7 @@ impl TerminalView {
                 )
             });
         } else {
-            window.show_character_palette();
+            window.show_character_palette(cx);
         }
     }
 
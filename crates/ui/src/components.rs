@@ -1,8 +1,10 @@
 mod avatar;
 mod banner;
 mod button;
+mod calendar;
 mod callout;
 mod chip;
+mod combobox;
 mod content_group;
 mod context_menu;
 mod data_table;
@@ -46,8 +48,10 @@ mod stories;
 pub use avatar::*;
 pub use banner::*;
 pub use button::*;
+pub use calendar::*;
 pub use callout::*;
 pub use chip::*;
+pub use combobox::*;
 pub use content_group::*;
 pub use context_menu::*;
 pub use data_table::*;
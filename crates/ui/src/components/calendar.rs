@@ -0,0 +1,1950 @@
+use std::{fmt, ops::Range, sync::OnceLock};
+
+use gpui::{
+    Action, Bounds, Corner, ElementInputHandler, Entity, EntityInputHandler, EventEmitter,
+    FocusHandle, Focusable, FontWeight, Global, KeyContext, Point, Stateful, Subscription,
+    UTF16Selection, actions, anchored, canvas, deferred,
+};
+use menu::{Cancel, Confirm};
+
+use crate::prelude::*;
+
+actions!(
+    calendar,
+    [
+        /// Moves the calendar cursor to the previous day.
+        PreviousDay,
+        /// Moves the calendar cursor to the next day.
+        NextDay,
+        /// Moves the calendar cursor to the same day of the previous week.
+        PreviousWeek,
+        /// Moves the calendar cursor to the same day of the next week.
+        NextWeek,
+        /// Moves the calendar cursor to the same day of the previous month.
+        PreviousMonth,
+        /// Moves the calendar cursor to the same day of the next month.
+        NextMonth,
+        /// Moves the calendar cursor to the same day of the previous year.
+        PreviousYear,
+        /// Moves the calendar cursor to the same day of the next year.
+        NextYear,
+        /// Moves the calendar cursor to the first day of its week.
+        StartOfWeek,
+        /// Moves the calendar cursor to the last day of its week.
+        EndOfWeek,
+        /// Moves to the previous field of a date or time picker.
+        PreviousField,
+        /// Moves to the next field of a date or time picker.
+        NextField,
+        /// Steps the active field of a date or time picker up.
+        Increment,
+        /// Steps the active field of a date or time picker down.
+        Decrement,
+        /// Deletes the last digit typed into the active field of a date or time picker, or clears
+        /// the field.
+        ClearField,
+        /// Opens the calendar dropdown of a date picker.
+        OpenCalendar,
+    ]
+);
+
+/// A day of the week.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Weekday {
+    /// Monday.
+    Monday,
+    /// Tuesday.
+    Tuesday,
+    /// Wednesday.
+    Wednesday,
+    /// Thursday.
+    Thursday,
+    /// Friday.
+    Friday,
+    /// Saturday.
+    Saturday,
+    /// Sunday.
+    Sunday,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// The number of days since Monday.
+    pub fn days_from_monday(self) -> usize {
+        self as usize
+    }
+
+    /// The day the given number of days later, wrapping around the week.
+    pub fn add_days(self, days: i64) -> Weekday {
+        Self::ALL[(self as i64 + days).rem_euclid(7) as usize]
+    }
+}
+
+/// A date in the proleptic Gregorian calendar, without a time zone.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// The given date, or `None` if the month or day doesn't exist.
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Self> {
+        ((1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month))
+            .then_some(Self { year, month, day })
+    }
+
+    /// The year.
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// The month, from 1 to 12.
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// The day of the month, from 1.
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// The day of the week.
+    pub fn weekday(&self) -> Weekday {
+        // 1970-01-01 was a Thursday.
+        Weekday::Thursday.add_days(self.days_since_epoch())
+    }
+
+    /// The date the given number of days later, or earlier if negative.
+    pub fn add_days(&self, days: i64) -> Self {
+        Self::from_days_since_epoch(self.days_since_epoch() + days)
+    }
+
+    /// The same day the given number of months later, or earlier if negative, or the last day of
+    /// that month if it's shorter.
+    pub fn add_months(&self, months: i32) -> Self {
+        let months = self.year as i64 * 12 + self.month as i64 - 1 + months as i64;
+        let year = months.div_euclid(12) as i32;
+        let month = months.rem_euclid(12) as u8 + 1;
+        Self {
+            year,
+            month,
+            day: self.day.min(days_in_month(year, month)),
+        }
+    }
+
+    /// The first day of the month of this date.
+    pub fn first_of_month(&self) -> Self {
+        Self { day: 1, ..*self }
+    }
+
+    fn clamp_to(self, min: Option<Date>, max: Option<Date>) -> Self {
+        let date = min.map_or(self, |min| self.max(min));
+        max.map_or(date, |max| date.min(max))
+    }
+
+    /// The number of days since 1970-01-01.
+    fn days_since_epoch(&self) -> i64 {
+        let year = self.year as i64 - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year =
+            (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    fn from_days_since_epoch(days: i64) -> Self {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        Self {
+            year: (year_of_era + era * 400 + (month <= 2) as i64) as i32,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The number of days in the given month, from 1 to 12, of the given year.
+pub fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// A time of day, to the minute, without a time zone.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    hour: u8,
+    minute: u8,
+}
+
+impl Time {
+    /// The given time, or `None` if the hour isn't from 0 to 23 or the minute from 0 to 59.
+    pub fn new(hour: u8, minute: u8) -> Option<Self> {
+        (hour < 24 && minute < 60).then_some(Self { hour, minute })
+    }
+
+    /// The hour, from 0 to 23.
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// The minute, from 0 to 59.
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    fn clamp_to(self, min: Option<Time>, max: Option<Time>) -> Self {
+        let time = min.map_or(self, |min| self.max(min));
+        max.map_or(time, |max| time.min(max))
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+/// The order of the fields of a date, as written in a locale.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum DateOrder {
+    /// Such as 31/12/2024.
+    DayMonthYear,
+    /// Such as 12/31/2024.
+    #[default]
+    MonthDayYear,
+    /// Such as 2024-12-31.
+    YearMonthDay,
+}
+
+impl DateOrder {
+    fn fields(self) -> [DateField; 3] {
+        match self {
+            DateOrder::DayMonthYear => [DateField::Day, DateField::Month, DateField::Year],
+            DateOrder::MonthDayYear => [DateField::Month, DateField::Day, DateField::Year],
+            DateOrder::YearMonthDay => [DateField::Year, DateField::Month, DateField::Day],
+        }
+    }
+}
+
+/// How dates and times are written and laid out in calendars, for the locale of the user
+/// interface. Set it as a global for the whole application, or on individual widgets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarLocale {
+    /// The names of the months, from January.
+    pub month_names: [SharedString; 12],
+    /// The short names of the weekdays shown above the days of a calendar, from Monday.
+    pub weekday_names: [SharedString; 7],
+    /// The weekday in the first column of a calendar.
+    pub first_day_of_week: Weekday,
+    /// The order of the fields of a date.
+    pub date_order: DateOrder,
+    /// The character between the fields of a date.
+    pub date_separator: char,
+    /// Whether times use a 12-hour clock, with the names of the periods, instead of a 24-hour one.
+    pub twelve_hour_clock: bool,
+    /// The names of the periods of a 12-hour clock, before and after noon.
+    pub day_periods: [SharedString; 2],
+}
+
+impl Global for CalendarLocale {}
+
+impl Default for CalendarLocale {
+    fn default() -> Self {
+        Self {
+            month_names: [
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ]
+            .map(SharedString::new_static),
+            weekday_names: ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"].map(SharedString::new_static),
+            first_day_of_week: Weekday::Sunday,
+            date_order: DateOrder::MonthDayYear,
+            date_separator: '/',
+            twelve_hour_clock: true,
+            day_periods: ["AM", "PM"].map(SharedString::new_static),
+        }
+    }
+}
+
+impl CalendarLocale {
+    /// The locale set as a global, or else the [platform's](CalendarLocale::platform).
+    pub fn global(cx: &App) -> Self {
+        cx.try_global::<Self>()
+            .cloned()
+            .unwrap_or_else(|| Self::platform().clone())
+    }
+
+    /// The default, US English, locale with the month names, weekday names and first day of the
+    /// week of the user's locale in the operating system, where they can be read.
+    pub fn platform() -> &'static Self {
+        static PLATFORM_LOCALE: OnceLock<CalendarLocale> = OnceLock::new();
+        PLATFORM_LOCALE.get_or_init(|| {
+            let mut locale = Self::default();
+            if let Some(month_names) = platform::month_names() {
+                locale.month_names = month_names;
+            }
+            if let Some(weekday_names) = platform::weekday_names() {
+                locale.weekday_names = weekday_names;
+            }
+            if let Some(first_day_of_week) = platform::first_day_of_week() {
+                locale.first_day_of_week = first_day_of_week;
+            }
+            locale
+        })
+    }
+
+    /// The name of the given month, from 1 to 12.
+    pub fn month_name(&self, month: u8) -> &SharedString {
+        &self.month_names[(month.clamp(1, 12) - 1) as usize]
+    }
+
+    /// The short name of the given weekday.
+    pub fn weekday_name(&self, weekday: Weekday) -> &SharedString {
+        &self.weekday_names[weekday.days_from_monday()]
+    }
+
+    /// The weekdays in the order of the columns of a calendar.
+    pub fn weekdays(&self) -> [Weekday; 7] {
+        let mut weekdays = [self.first_day_of_week; 7];
+        for (ix, weekday) in weekdays.iter_mut().enumerate() {
+            *weekday = self.first_day_of_week.add_days(ix as i64);
+        }
+        weekdays
+    }
+
+    /// Write the given date with numeric fields, such as 12/31/2024.
+    pub fn format_date(&self, date: Date) -> String {
+        let separator = self.date_separator;
+        self.date_order
+            .fields()
+            .map(|field| field.format(Some(date.field(field))))
+            .join(&separator.to_string())
+    }
+
+    /// Write the given time, such as 4:05 PM or 16:05.
+    pub fn format_time(&self, time: Time) -> String {
+        if self.twelve_hour_clock {
+            let period = &self.day_periods[(time.hour >= 12) as usize];
+            format!("{}:{:02} {period}", twelve_hour(time.hour), time.minute)
+        } else {
+            time.to_string()
+        }
+    }
+}
+
+/// The hour of a 12-hour clock, from 1 to 12, for the given hour from 0 to 23.
+fn twelve_hour(hour: u8) -> u8 {
+    match hour % 12 {
+        0 => 12,
+        hour => hour,
+    }
+}
+
+/// The events emitted by a [`Calendar`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalendarEvent {
+    /// The user selected a day, by clicking it or confirming the one under the cursor.
+    Selected(Date),
+}
+
+/// A month view of the days, for selecting one of them. The `calendar` actions move the cursor
+/// between the days, months and years, and [`menu::Confirm`] selects the day under it. Its key
+/// context is `Calendar`.
+pub struct Calendar {
+    focus_handle: FocusHandle,
+    locale: CalendarLocale,
+    min: Option<Date>,
+    max: Option<Date>,
+    selected: Option<Date>,
+    /// The day the keyboard moves from, whose month is shown.
+    cursor: Date,
+    /// Whether the calendar is the dropdown of a [`DatePicker`], which keeps the focus and
+    /// forwards the calendar actions.
+    embedded: bool,
+}
+
+impl Calendar {
+    /// Create a calendar showing the month of the given date, such as today, with nothing
+    /// selected.
+    pub fn new(reference: Date, cx: &mut Context<Self>) -> Self {
+        Self {
+            focus_handle: cx.focus_handle(),
+            locale: CalendarLocale::global(cx),
+            min: None,
+            max: None,
+            selected: None,
+            cursor: reference,
+            embedded: false,
+        }
+    }
+
+    /// Use the given locale instead of the global one.
+    pub fn locale(mut self, locale: CalendarLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Don't allow selecting days before the given one.
+    pub fn min(mut self, min: Date) -> Self {
+        self.min = Some(min);
+        self.cursor = self.cursor.clamp_to(self.min, self.max);
+        self
+    }
+
+    /// Don't allow selecting days after the given one.
+    pub fn max(mut self, max: Date) -> Self {
+        self.max = Some(max);
+        self.cursor = self.cursor.clamp_to(self.min, self.max);
+        self
+    }
+
+    /// Change the range of days that can be selected.
+    pub fn set_range(&mut self, min: Option<Date>, max: Option<Date>, cx: &mut Context<Self>) {
+        self.min = min;
+        self.max = max;
+        self.cursor = self.cursor.clamp_to(min, max);
+        cx.notify();
+    }
+
+    /// Whether the given day can be selected.
+    pub fn is_selectable(&self, date: Date) -> bool {
+        self.min.is_none_or(|min| date >= min) && self.max.is_none_or(|max| date <= max)
+    }
+
+    /// The selected day.
+    pub fn selected(&self) -> Option<Date> {
+        self.selected
+    }
+
+    /// Select the given day, and show its month, without emitting an event.
+    pub fn set_selected(&mut self, date: Option<Date>, cx: &mut Context<Self>) {
+        self.selected = date;
+        if let Some(date) = date {
+            self.cursor = date.clamp_to(self.min, self.max);
+        }
+        cx.notify();
+    }
+
+    /// Select the given day, as if the user clicked it, if it can be selected.
+    pub fn select(&mut self, date: Date, cx: &mut Context<Self>) {
+        if !self.is_selectable(date) {
+            return;
+        }
+        self.selected = Some(date);
+        self.cursor = date;
+        cx.emit(CalendarEvent::Selected(date));
+        cx.notify();
+    }
+
+    /// The day the keyboard moves from.
+    pub fn cursor(&self) -> Date {
+        self.cursor
+    }
+
+    /// Move the cursor to the given day, or the closest one that can be selected, showing its
+    /// month.
+    pub fn set_cursor(&mut self, date: Date, cx: &mut Context<Self>) {
+        self.cursor = date.clamp_to(self.min, self.max);
+        cx.notify();
+    }
+
+    /// The year and month shown.
+    pub fn visible_month(&self) -> (i32, u8) {
+        (self.cursor.year, self.cursor.month)
+    }
+
+    /// Show the previous month.
+    pub fn show_previous_month(&mut self, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_months(-1), cx);
+    }
+
+    /// Show the next month.
+    pub fn show_next_month(&mut self, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_months(1), cx);
+    }
+
+    /// The first day shown, in the first column of the week of the first of the month.
+    fn first_visible_day(&self) -> Date {
+        let first = self.cursor.first_of_month();
+        let offset = first.weekday().days_from_monday() as i64
+            - self.locale.first_day_of_week.days_from_monday() as i64;
+        first.add_days(-offset.rem_euclid(7))
+    }
+
+    /// The column of the cursor, counted from the first day of the week.
+    fn cursor_column(&self) -> i64 {
+        (self.cursor.weekday().days_from_monday() as i64
+            - self.locale.first_day_of_week.days_from_monday() as i64)
+            .rem_euclid(7)
+    }
+
+    fn previous_day(&mut self, _: &PreviousDay, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_days(-1), cx);
+    }
+
+    fn next_day(&mut self, _: &NextDay, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_days(1), cx);
+    }
+
+    fn previous_week(&mut self, _: &PreviousWeek, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_days(-7), cx);
+    }
+
+    fn next_week(&mut self, _: &NextWeek, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_days(7), cx);
+    }
+
+    fn previous_month(&mut self, _: &PreviousMonth, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_months(-1), cx);
+    }
+
+    fn next_month(&mut self, _: &NextMonth, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_months(1), cx);
+    }
+
+    fn previous_year(&mut self, _: &PreviousYear, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_months(-12), cx);
+    }
+
+    fn next_year(&mut self, _: &NextYear, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_months(12), cx);
+    }
+
+    fn start_of_week(&mut self, _: &StartOfWeek, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_days(-self.cursor_column()), cx);
+    }
+
+    fn end_of_week(&mut self, _: &EndOfWeek, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(self.cursor.add_days(6 - self.cursor_column()), cx);
+    }
+
+    fn confirm(&mut self, _: &Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        self.select(self.cursor, cx);
+    }
+
+    /// Handle the actions moving the cursor of the given calendar on the element with the focus,
+    /// the calendar's own or that of the [`DatePicker`] whose dropdown it is.
+    fn on_cursor_actions(element: Stateful<Div>, calendar: &Entity<Self>) -> Stateful<Div> {
+        fn on_action<A: Action>(
+            element: Stateful<Div>,
+            calendar: &Entity<Calendar>,
+            handler: fn(&mut Calendar, &A, &mut Window, &mut Context<Calendar>),
+        ) -> Stateful<Div> {
+            let calendar = calendar.downgrade();
+            element.on_action(move |action: &A, window, cx| {
+                calendar
+                    .update(cx, |calendar, cx| handler(calendar, action, window, cx))
+                    .ok();
+            })
+        }
+
+        let element = on_action(element, calendar, Self::previous_day);
+        let element = on_action(element, calendar, Self::next_day);
+        let element = on_action(element, calendar, Self::previous_week);
+        let element = on_action(element, calendar, Self::next_week);
+        let element = on_action(element, calendar, Self::previous_month);
+        let element = on_action(element, calendar, Self::next_month);
+        let element = on_action(element, calendar, Self::previous_year);
+        let element = on_action(element, calendar, Self::next_year);
+        let element = on_action(element, calendar, Self::start_of_week);
+        on_action(element, calendar, Self::end_of_week)
+    }
+}
+
+impl EventEmitter<CalendarEvent> for Calendar {}
+
+impl Focusable for Calendar {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for Calendar {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focused = self.embedded || self.focus_handle.is_focused(window);
+        let (year, month) = self.visible_month();
+        let first = self.first_visible_day();
+        let colors = cx.theme().colors();
+
+        let header = h_flex()
+            .justify_between()
+            .pb_1()
+            .child(
+                IconButton::new("calendar-previous-month", IconName::ChevronLeft)
+                    .icon_size(IconSize::Small)
+                    .on_click(cx.listener(|this, _, _, cx| this.show_previous_month(cx))),
+            )
+            .child(
+                Label::new(format!("{} {year}", self.locale.month_name(month)))
+                    .weight(FontWeight::SEMIBOLD),
+            )
+            .child(
+                IconButton::new("calendar-next-month", IconName::ChevronRight)
+                    .icon_size(IconSize::Small)
+                    .on_click(cx.listener(|this, _, _, cx| this.show_next_month(cx))),
+            );
+
+        let weekdays = h_flex().children(self.locale.weekdays().map(|weekday| {
+            h_flex().w(px(32.)).justify_center().child(
+                Label::new(self.locale.weekday_name(weekday).clone())
+                    .size(LabelSize::XSmall)
+                    .color(Color::Muted),
+            )
+        }));
+
+        let weeks = (0..6).map(|week| {
+            h_flex().children((0..7).map(|day| {
+                let date = first.add_days(week * 7 + day);
+                let selectable = self.is_selectable(date);
+                let color = if !selectable {
+                    Color::Disabled
+                } else if date.month != month {
+                    Color::Muted
+                } else {
+                    Color::Default
+                };
+                h_flex()
+                    .id(("calendar-day", (week * 7 + day) as usize))
+                    .size(px(32.))
+                    .justify_center()
+                    .rounded_xs()
+                    .when(selectable, |this| {
+                        this.cursor_pointer()
+                            .hover(|style| style.bg(colors.element_hover))
+                            .on_click(cx.listener(move |this, _, _, cx| this.select(date, cx)))
+                    })
+                    .when(self.selected == Some(date), |this| {
+                        this.bg(colors.element_selected)
+                    })
+                    .when(focused && date == self.cursor, |this| {
+                        this.border_1().border_color(colors.border_focused)
+                    })
+                    .child(Label::new(date.day.to_string()).color(color))
+            }))
+        });
+
+        v_flex()
+            .id("calendar")
+            .when(!self.embedded, |this| {
+                this.key_context("Calendar")
+                    .track_focus(&self.focus_handle)
+                    .on_action(cx.listener(Self::confirm))
+                    .map(|this| Self::on_cursor_actions(this, &cx.entity()))
+            })
+            .p_1()
+            .child(header)
+            .child(weekdays)
+            .children(weeks)
+    }
+}
+
+/// The fields of a date, indexing the fields being entered in a [`DatePicker`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum DateField {
+    Year,
+    Month,
+    Day,
+}
+
+impl DateField {
+    /// The number of digits, and the largest value, that can be typed into the field.
+    fn limits(self) -> (usize, i32) {
+        match self {
+            DateField::Year => (4, 9999),
+            DateField::Month => (2, 12),
+            DateField::Day => (2, 31),
+        }
+    }
+
+    fn format(self, value: Option<i32>) -> String {
+        let (digits, _) = self.limits();
+        match value {
+            Some(value) => format!("{value:0digits$}"),
+            None => "-".repeat(digits),
+        }
+    }
+}
+
+impl Date {
+    fn field(&self, field: DateField) -> i32 {
+        match field {
+            DateField::Year => self.year,
+            DateField::Month => self.month as i32,
+            DateField::Day => self.day as i32,
+        }
+    }
+}
+
+/// The events emitted by a [`DatePicker`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatePickerEvent {
+    /// The user changed the date, or cleared one of its fields.
+    Changed(Option<Date>),
+}
+
+/// A field for entering a date, by typing the numbers of its fields, stepping them up and down,
+/// or picking a day from a [`Calendar`] dropdown. The date is accepted as soon as all of its
+/// fields are entered, moved into the allowed range.
+///
+/// Its key context is `DatePicker`, with `showing_calendar` while the dropdown is open, when it
+/// handles the calendar actions.
+pub struct DatePicker {
+    focus_handle: FocusHandle,
+    locale: CalendarLocale,
+    /// The date the calendar shows and stepping starts from while there's no value.
+    reference: Date,
+    min: Option<Date>,
+    max: Option<Date>,
+    value: Option<Date>,
+    /// The fields being entered, indexed by [`DateField`].
+    fields: [Option<i32>; 3],
+    /// The index of the active field, in the order of the locale.
+    active: usize,
+    /// The digits typed into the active field.
+    typed: String,
+    calendar: Option<(Entity<Calendar>, Subscription)>,
+    _blur: Subscription,
+}
+
+impl DatePicker {
+    /// Create an empty date picker, whose calendar starts at the month of the given date, such as
+    /// today.
+    pub fn new(reference: Date, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let focus_handle = cx.focus_handle();
+        let blur = cx.on_blur(&focus_handle, window, |this, _, cx| {
+            this.finish_field(cx);
+            this.close_calendar(cx);
+        });
+        Self {
+            focus_handle,
+            locale: CalendarLocale::global(cx),
+            reference,
+            min: None,
+            max: None,
+            value: None,
+            fields: [None; 3],
+            active: 0,
+            typed: String::new(),
+            calendar: None,
+            _blur: blur,
+        }
+    }
+
+    /// Use the given locale instead of the global one.
+    pub fn locale(mut self, locale: CalendarLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Don't allow dates before the given one.
+    pub fn min(mut self, min: Date) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// Don't allow dates after the given one.
+    pub fn max(mut self, max: Date) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// The entered date.
+    pub fn value(&self) -> Option<Date> {
+        self.value
+    }
+
+    /// Set the date, without emitting an event.
+    pub fn set_value(&mut self, value: Option<Date>, cx: &mut Context<Self>) {
+        self.value = value.map(|value| value.clamp_to(self.min, self.max));
+        self.fields = self.value.map_or([None; 3], |value| {
+            [DateField::Year, DateField::Month, DateField::Day]
+                .map(|field| Some(value.field(field)))
+        });
+        self.typed.clear();
+        cx.notify();
+    }
+
+    /// Whether the calendar dropdown is open.
+    pub fn is_open(&self) -> bool {
+        self.calendar.is_some()
+    }
+
+    /// Open the calendar dropdown, showing the entered date.
+    pub fn open_calendar(&mut self, cx: &mut Context<Self>) {
+        if self.calendar.is_some() {
+            return;
+        }
+        let (locale, min, max, value) = (self.locale.clone(), self.min, self.max, self.value);
+        let reference = value.unwrap_or(self.reference);
+        let calendar = cx.new(|cx| {
+            let mut calendar = Calendar::new(reference, cx).locale(locale);
+            calendar.embedded = true;
+            calendar.set_range(min, max, cx);
+            calendar.selected = value;
+            calendar
+        });
+        let subscription = cx.subscribe(&calendar, |this, _, CalendarEvent::Selected(date), cx| {
+            this.close_calendar(cx);
+            this.set_value(Some(*date), cx);
+            cx.emit(DatePickerEvent::Changed(Some(*date)));
+        });
+        self.calendar = Some((calendar, subscription));
+        cx.notify();
+    }
+
+    /// Close the calendar dropdown without changing the date.
+    pub fn close_calendar(&mut self, cx: &mut Context<Self>) {
+        if self.calendar.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    fn active_field(&self) -> DateField {
+        self.locale.date_order.fields()[self.active]
+    }
+
+    /// The date being entered, or the reference date, with the entered fields.
+    fn draft(&self) -> Date {
+        let reference = self.value.unwrap_or(self.reference);
+        let year = self.fields[DateField::Year as usize].unwrap_or(reference.year);
+        let month = self.fields[DateField::Month as usize]
+            .unwrap_or(reference.month as i32)
+            .clamp(1, 12) as u8;
+        let day = self.fields[DateField::Day as usize]
+            .unwrap_or(reference.day as i32)
+            .clamp(1, days_in_month(year, month) as i32) as u8;
+        Date { year, month, day }
+    }
+
+    /// Accept the date if all of its fields are entered, and tell the subscribers if it changed.
+    fn commit(&mut self, cx: &mut Context<Self>) {
+        if self.fields.iter().all(Option::is_some) {
+            let date = self.draft().clamp_to(self.min, self.max);
+            let changed = self.value != Some(date);
+            self.set_value(Some(date), cx);
+            if changed {
+                cx.emit(DatePickerEvent::Changed(Some(date)));
+            }
+        }
+        cx.notify();
+    }
+
+    fn finish_field(&mut self, cx: &mut Context<Self>) {
+        if !self.typed.is_empty() {
+            self.typed.clear();
+            self.commit(cx);
+        }
+    }
+
+    fn move_to_field(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.finish_field(cx);
+        self.active = ix.min(2);
+        cx.notify();
+    }
+
+    /// Step the active field up or down, wrapping the month and day around.
+    fn step(&mut self, delta: i32, cx: &mut Context<Self>) {
+        self.typed.clear();
+        let draft = self.draft();
+        let field = self.active_field();
+        let value = match (field, self.fields[field as usize]) {
+            (_, None) => draft.field(field),
+            (DateField::Year, Some(_)) => draft.year + delta,
+            (DateField::Month, Some(_)) => (draft.month as i32 - 1 + delta).rem_euclid(12) + 1,
+            (DateField::Day, Some(_)) => {
+                let days = days_in_month(draft.year, draft.month) as i32;
+                (draft.day as i32 - 1 + delta).rem_euclid(days) + 1
+            }
+        };
+        self.fields[field as usize] = Some(value);
+        for field in [DateField::Year, DateField::Month, DateField::Day] {
+            self.fields[field as usize].get_or_insert(draft.field(field));
+        }
+        self.commit(cx);
+    }
+
+    fn type_digit(&mut self, digit: char, cx: &mut Context<Self>) {
+        let field = self.active_field();
+        let (digits, max) = field.limits();
+        self.typed.push(digit);
+        let value = self.typed.parse::<i32>().unwrap_or_default();
+        self.fields[field as usize] = Some(value);
+        if self.typed.len() >= digits || value * 10 > max {
+            self.move_to_field(self.active + 1, cx);
+        }
+        cx.notify();
+    }
+
+    fn previous_field(&mut self, _: &PreviousField, _: &mut Window, cx: &mut Context<Self>) {
+        if self.active == 0 {
+            cx.propagate();
+            return;
+        }
+        self.move_to_field(self.active - 1, cx);
+    }
+
+    fn next_field(&mut self, _: &NextField, _: &mut Window, cx: &mut Context<Self>) {
+        if self.active == 2 {
+            cx.propagate();
+            return;
+        }
+        self.move_to_field(self.active + 1, cx);
+    }
+
+    fn increment(&mut self, _: &Increment, _: &mut Window, cx: &mut Context<Self>) {
+        self.step(1, cx);
+    }
+
+    fn decrement(&mut self, _: &Decrement, _: &mut Window, cx: &mut Context<Self>) {
+        self.step(-1, cx);
+    }
+
+    fn clear_field(&mut self, _: &ClearField, _: &mut Window, cx: &mut Context<Self>) {
+        if self.typed.pop().is_some() {
+            let field = self.active_field();
+            self.fields[field as usize] = self.typed.parse().ok();
+        } else {
+            self.fields[self.active_field() as usize] = None;
+            if self.value.take().is_some() {
+                cx.emit(DatePickerEvent::Changed(None));
+            }
+        }
+        cx.notify();
+    }
+
+    fn show_calendar(&mut self, _: &OpenCalendar, _: &mut Window, cx: &mut Context<Self>) {
+        self.open_calendar(cx);
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some((calendar, _)) = &self.calendar {
+            calendar.update(cx, |calendar, cx| calendar.confirm(&Confirm, window, cx));
+        } else {
+            self.finish_field(cx);
+        }
+    }
+
+    fn cancel(&mut self, _: &Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.is_open() {
+            cx.propagate();
+            return;
+        }
+        self.close_calendar(cx);
+    }
+}
+
+impl EventEmitter<DatePickerEvent> for DatePicker {}
+
+impl Focusable for DatePicker {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EntityInputHandler for DatePicker {
+    fn text_for_range(
+        &mut self,
+        _: Range<usize>,
+        _: &mut Option<Range<usize>>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<String> {
+        None
+    }
+
+    fn selected_text_range(
+        &mut self,
+        _: bool,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<UTF16Selection> {
+        Some(UTF16Selection {
+            range: 0..0,
+            reversed: false,
+        })
+    }
+
+    fn marked_text_range(&self, _: &mut Window, _: &mut Context<Self>) -> Option<Range<usize>> {
+        None
+    }
+
+    fn unmark_text(&mut self, _: &mut Window, _: &mut Context<Self>) {}
+
+    fn replace_text_in_range(
+        &mut self,
+        _: Option<Range<usize>>,
+        text: &str,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        for digit in text.chars().filter(char::is_ascii_digit) {
+            self.type_digit(digit, cx);
+        }
+    }
+
+    fn replace_and_mark_text_in_range(
+        &mut self,
+        _: Option<Range<usize>>,
+        _: &str,
+        _: Option<Range<usize>>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) {
+    }
+
+    fn bounds_for_range(
+        &mut self,
+        _: Range<usize>,
+        element_bounds: Bounds<Pixels>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<Bounds<Pixels>> {
+        Some(element_bounds)
+    }
+
+    fn character_index_for_point(
+        &mut self,
+        _: Point<Pixels>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<usize> {
+        None
+    }
+}
+
+impl Render for DatePicker {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focused = self.focus_handle.is_focused(window);
+        let fields = self.locale.date_order.fields();
+        let segments = fields.iter().enumerate().map(|(ix, field)| {
+            let text = if ix == self.active && !self.typed.is_empty() {
+                self.typed.clone()
+            } else {
+                field.format(self.fields[*field as usize])
+            };
+            segment(("date-field", ix), text, focused && ix == self.active, cx)
+                .on_click(cx.listener(move |this, _, _, cx| this.move_to_field(ix, cx)))
+                .into_any_element()
+        });
+        let mut segments = segments.collect::<Vec<_>>();
+        for ix in [2, 1] {
+            let separator = self.locale.date_separator.to_string();
+            segments.insert(
+                ix,
+                Label::new(separator).color(Color::Muted).into_any_element(),
+            );
+        }
+
+        let mut key_context = KeyContext::new_with_defaults();
+        key_context.add("DatePicker");
+        if self.is_open() {
+            key_context.add("showing_calendar");
+        }
+
+        let dropdown = self.calendar.as_ref().map(|(calendar, _)| {
+            deferred(
+                anchored().anchor(Corner::TopLeft).child(
+                    v_flex()
+                        .id("date-picker-dropdown")
+                        .occlude()
+                        .elevation_2(cx)
+                        .mt_1()
+                        .overflow_hidden()
+                        .child(calendar.clone()),
+                ),
+            )
+            .with_priority(1)
+        });
+
+        v_flex()
+            .child(
+                field("date-picker", &self.focus_handle, focused, cx.entity(), cx)
+                    .key_context(key_context)
+                    .on_action(cx.listener(Self::previous_field))
+                    .on_action(cx.listener(Self::next_field))
+                    .on_action(cx.listener(Self::increment))
+                    .on_action(cx.listener(Self::decrement))
+                    .on_action(cx.listener(Self::clear_field))
+                    .on_action(cx.listener(Self::show_calendar))
+                    .on_action(cx.listener(Self::confirm))
+                    .on_action(cx.listener(Self::cancel))
+                    .when_some(self.calendar.as_ref(), |this, (calendar, _)| {
+                        Calendar::on_cursor_actions(this, calendar)
+                    })
+                    .children(segments)
+                    .child(
+                        IconButton::new("date-picker-toggle", IconName::ChevronDown)
+                            .icon_size(IconSize::Small)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                window.focus(&this.focus_handle);
+                                if this.is_open() {
+                                    this.close_calendar(cx);
+                                } else {
+                                    this.open_calendar(cx);
+                                }
+                            })),
+                    ),
+            )
+            .children(dropdown)
+    }
+}
+
+/// The fields of a time being entered in a [`TimePicker`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum TimeField {
+    Hour,
+    Minute,
+    Period,
+}
+
+/// The events emitted by a [`TimePicker`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimePickerEvent {
+    /// The user changed the time, or cleared one of its fields.
+    Changed(Option<Time>),
+}
+
+/// A field for entering a time of day, by typing the numbers of the hour and minute, stepping
+/// them up and down, and with a 12-hour clock typing A or P for the period. The time is accepted
+/// as soon as the hour and minute are entered, moved into the allowed range.
+///
+/// Its key context is `TimePicker`.
+pub struct TimePicker {
+    focus_handle: FocusHandle,
+    locale: CalendarLocale,
+    min: Option<Time>,
+    max: Option<Time>,
+    minute_step: u8,
+    value: Option<Time>,
+    /// The hour being entered, from 0 to 23.
+    hour: Option<u8>,
+    minute: Option<u8>,
+    pm: bool,
+    /// The index of the active field in [`TimePicker::fields`].
+    active: usize,
+    /// The digits typed into the active field.
+    typed: String,
+    _blur: Subscription,
+}
+
+impl TimePicker {
+    /// Create an empty time picker.
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let focus_handle = cx.focus_handle();
+        let blur = cx.on_blur(&focus_handle, window, |this, _, cx| this.finish_field(cx));
+        Self {
+            focus_handle,
+            locale: CalendarLocale::global(cx),
+            min: None,
+            max: None,
+            minute_step: 1,
+            value: None,
+            hour: None,
+            minute: None,
+            pm: false,
+            active: 0,
+            typed: String::new(),
+            _blur: blur,
+        }
+    }
+
+    /// Use the given locale instead of the global one.
+    pub fn locale(mut self, locale: CalendarLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Don't allow times before the given one.
+    pub fn min(mut self, min: Time) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// Don't allow times after the given one.
+    pub fn max(mut self, max: Time) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Step the minutes up and down by the given number, 1 by default.
+    pub fn minute_step(mut self, step: u8) -> Self {
+        self.minute_step = step.clamp(1, 30);
+        self
+    }
+
+    /// The entered time.
+    pub fn value(&self) -> Option<Time> {
+        self.value
+    }
+
+    /// Set the time, without emitting an event.
+    pub fn set_value(&mut self, value: Option<Time>, cx: &mut Context<Self>) {
+        self.value = value.map(|value| value.clamp_to(self.min, self.max));
+        self.hour = self.value.map(|value| value.hour);
+        self.minute = self.value.map(|value| value.minute);
+        if let Some(value) = self.value {
+            self.pm = value.hour >= 12;
+        }
+        self.typed.clear();
+        cx.notify();
+    }
+
+    fn fields(&self) -> &'static [TimeField] {
+        if self.locale.twelve_hour_clock {
+            &[TimeField::Hour, TimeField::Minute, TimeField::Period]
+        } else {
+            &[TimeField::Hour, TimeField::Minute]
+        }
+    }
+
+    fn active_field(&self) -> TimeField {
+        self.fields()[self.active]
+    }
+
+    /// Accept the time if the hour and minute are entered, and tell the subscribers if it changed.
+    fn commit(&mut self, cx: &mut Context<Self>) {
+        if let Some((hour, minute)) = self.hour.zip(self.minute) {
+            let time = Time {
+                hour: hour.min(23),
+                minute: minute.min(59),
+            }
+            .clamp_to(self.min, self.max);
+            let changed = self.value != Some(time);
+            self.set_value(Some(time), cx);
+            if changed {
+                cx.emit(TimePickerEvent::Changed(Some(time)));
+            }
+        }
+        cx.notify();
+    }
+
+    fn finish_field(&mut self, cx: &mut Context<Self>) {
+        if !self.typed.is_empty() {
+            self.typed.clear();
+            self.commit(cx);
+        }
+    }
+
+    fn move_to_field(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.finish_field(cx);
+        self.active = ix.min(self.fields().len() - 1);
+        cx.notify();
+    }
+
+    fn set_pm(&mut self, pm: bool, cx: &mut Context<Self>) {
+        self.pm = pm;
+        self.hour = self.hour.map(|hour| hour % 12 + if pm { 12 } else { 0 });
+        self.commit(cx);
+    }
+
+    /// Step the active field up or down, wrapping around.
+    fn step(&mut self, delta: i32, cx: &mut Context<Self>) {
+        self.typed.clear();
+        match self.active_field() {
+            TimeField::Hour => {
+                let hour = self
+                    .hour
+                    .map_or(0, |hour| (hour as i32 + delta).rem_euclid(24));
+                self.hour = Some(hour as u8);
+                self.pm = hour >= 12;
+            }
+            TimeField::Minute => {
+                let step = self.minute_step as i32;
+                let minute = self.minute.map_or(0, |minute| {
+                    // Snap to the step first, so stepping from 07 by 15 gives 15 and 00.
+                    let snapped = minute as i32 / step * step;
+                    let moved = if delta < 0 && snapped < minute as i32 {
+                        snapped
+                    } else {
+                        snapped + delta * step
+                    };
+                    moved.rem_euclid(60)
+                });
+                self.minute = Some(minute as u8);
+            }
+            TimeField::Period => return self.set_pm(!self.pm, cx),
+        }
+        self.minute.get_or_insert(0);
+        self.commit(cx);
+    }
+
+    fn type_digit(&mut self, digit: char, cx: &mut Context<Self>) {
+        let twelve_hour_clock = self.locale.twelve_hour_clock;
+        let max = match self.active_field() {
+            TimeField::Hour if twelve_hour_clock => 12,
+            TimeField::Hour => 23,
+            TimeField::Minute => 59,
+            TimeField::Period => return,
+        };
+        self.typed.push(digit);
+        let value = self.typed.parse::<u8>().unwrap_or_default();
+        match self.active_field() {
+            TimeField::Hour if twelve_hour_clock => {
+                self.hour = Some(value % 12 + if self.pm { 12 } else { 0 })
+            }
+            TimeField::Hour => self.hour = Some(value),
+            _ => self.minute = Some(value),
+        }
+        if self.typed.len() >= 2 || value as u32 * 10 > max {
+            self.move_to_field(self.active + 1, cx);
+        }
+        cx.notify();
+    }
+
+    fn previous_field(&mut self, _: &PreviousField, _: &mut Window, cx: &mut Context<Self>) {
+        if self.active == 0 {
+            cx.propagate();
+            return;
+        }
+        self.move_to_field(self.active - 1, cx);
+    }
+
+    fn next_field(&mut self, _: &NextField, _: &mut Window, cx: &mut Context<Self>) {
+        if self.active == self.fields().len() - 1 {
+            cx.propagate();
+            return;
+        }
+        self.move_to_field(self.active + 1, cx);
+    }
+
+    fn increment(&mut self, _: &Increment, _: &mut Window, cx: &mut Context<Self>) {
+        self.step(1, cx);
+    }
+
+    fn decrement(&mut self, _: &Decrement, _: &mut Window, cx: &mut Context<Self>) {
+        self.step(-1, cx);
+    }
+
+    fn clear_field(&mut self, _: &ClearField, _: &mut Window, cx: &mut Context<Self>) {
+        let field = self.active_field();
+        let value = match self.typed.pop() {
+            Some(_) => self.typed.parse::<u8>().ok(),
+            None => {
+                if self.value.take().is_some() {
+                    cx.emit(TimePickerEvent::Changed(None));
+                }
+                None
+            }
+        };
+        match field {
+            TimeField::Hour if self.locale.twelve_hour_clock => {
+                self.hour = value.map(|value| value % 12 + if self.pm { 12 } else { 0 })
+            }
+            TimeField::Hour => self.hour = value,
+            TimeField::Minute => self.minute = value,
+            TimeField::Period => {}
+        }
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        self.finish_field(cx);
+    }
+}
+
+impl EventEmitter<TimePickerEvent> for TimePicker {}
+
+impl Focusable for TimePicker {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EntityInputHandler for TimePicker {
+    fn text_for_range(
+        &mut self,
+        _: Range<usize>,
+        _: &mut Option<Range<usize>>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<String> {
+        None
+    }
+
+    fn selected_text_range(
+        &mut self,
+        _: bool,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<UTF16Selection> {
+        Some(UTF16Selection {
+            range: 0..0,
+            reversed: false,
+        })
+    }
+
+    fn marked_text_range(&self, _: &mut Window, _: &mut Context<Self>) -> Option<Range<usize>> {
+        None
+    }
+
+    fn unmark_text(&mut self, _: &mut Window, _: &mut Context<Self>) {}
+
+    fn replace_text_in_range(
+        &mut self,
+        _: Option<Range<usize>>,
+        text: &str,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let twelve_hour_clock = self.locale.twelve_hour_clock;
+        for char in text.chars() {
+            match char {
+                '0'..='9' => self.type_digit(char, cx),
+                'a' | 'A' if twelve_hour_clock => self.set_pm(false, cx),
+                'p' | 'P' if twelve_hour_clock => self.set_pm(true, cx),
+                _ => {}
+            }
+        }
+    }
+
+    fn replace_and_mark_text_in_range(
+        &mut self,
+        _: Option<Range<usize>>,
+        _: &str,
+        _: Option<Range<usize>>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) {
+    }
+
+    fn bounds_for_range(
+        &mut self,
+        _: Range<usize>,
+        element_bounds: Bounds<Pixels>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<Bounds<Pixels>> {
+        Some(element_bounds)
+    }
+
+    fn character_index_for_point(
+        &mut self,
+        _: Point<Pixels>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<usize> {
+        None
+    }
+}
+
+impl Render for TimePicker {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focused = self.focus_handle.is_focused(window);
+        let twelve_hour_clock = self.locale.twelve_hour_clock;
+        let segments = self.fields().iter().enumerate().map(|(ix, field)| {
+            let text = if ix == self.active && !self.typed.is_empty() {
+                format!("{:0>2}", self.typed)
+            } else {
+                match field {
+                    TimeField::Hour => self.hour.map_or("--".into(), |hour| {
+                        let hour = if twelve_hour_clock {
+                            twelve_hour(hour)
+                        } else {
+                            hour
+                        };
+                        format!("{hour:02}")
+                    }),
+                    TimeField::Minute => self
+                        .minute
+                        .map_or("--".into(), |minute| format!("{minute:02}")),
+                    TimeField::Period => self.locale.day_periods[self.pm as usize].to_string(),
+                }
+            };
+            segment(("time-field", ix), text, focused && ix == self.active, cx)
+                .when(*field == TimeField::Period, |this| this.ml_1())
+                .on_click(cx.listener(move |this, _, _, cx| this.move_to_field(ix, cx)))
+                .into_any_element()
+        });
+        let mut segments = segments.collect::<Vec<_>>();
+        segments.insert(1, Label::new(":").color(Color::Muted).into_any_element());
+
+        field("time-picker", &self.focus_handle, focused, cx.entity(), cx)
+            .key_context("TimePicker")
+            .on_action(cx.listener(Self::previous_field))
+            .on_action(cx.listener(Self::next_field))
+            .on_action(cx.listener(Self::increment))
+            .on_action(cx.listener(Self::decrement))
+            .on_action(cx.listener(Self::clear_field))
+            .on_action(cx.listener(Self::confirm))
+            .children(segments)
+    }
+}
+
+/// The text field the date and time pickers render their fields in, taking the typed digits from
+/// the given entity's input handler.
+fn field<V: EntityInputHandler>(
+    id: &'static str,
+    focus_handle: &FocusHandle,
+    focused: bool,
+    entity: Entity<V>,
+    cx: &App,
+) -> Stateful<Div> {
+    let colors = cx.theme().colors();
+    let input_focus_handle = focus_handle.clone();
+    h_flex()
+        .id(id)
+        .track_focus(focus_handle)
+        .relative()
+        .px_2()
+        .py_1()
+        .bg(colors.editor_background)
+        .border_1()
+        .border_color(if focused {
+            colors.border_focused
+        } else {
+            colors.border
+        })
+        .rounded_sm()
+        .child(
+            canvas(
+                |_, _, _| {},
+                move |bounds, _, window, cx| {
+                    window.handle_input(
+                        &input_focus_handle,
+                        ElementInputHandler::new(bounds, entity),
+                        cx,
+                    )
+                },
+            )
+            .absolute()
+            .size_full(),
+        )
+}
+
+/// One of the fields of a date or time picker, highlighted when it's the active one.
+fn segment(id: impl Into<ElementId>, text: String, active: bool, cx: &App) -> Stateful<Div> {
+    div()
+        .id(id)
+        .px_0p5()
+        .rounded_xs()
+        .cursor_default()
+        .when(active, |this| this.bg(cx.theme().colors().element_selected))
+        .child(Label::new(text))
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use core_foundation::{array::CFArray, base::TCFType, string::CFString};
+    use core_foundation_sys::{
+        array::CFArrayRef,
+        base::{CFIndex, CFRelease, CFTypeRef, kCFAllocatorDefault},
+        date_formatter::{
+            CFDateFormatterCopyProperty, CFDateFormatterCreate, CFDateFormatterKey,
+            kCFDateFormatterMonthSymbols, kCFDateFormatterNoStyle,
+            kCFDateFormatterShortWeekdaySymbols,
+        },
+        locale::CFLocaleCopyCurrent,
+    };
+    use gpui::SharedString;
+
+    use super::Weekday;
+
+    unsafe extern "C" {
+        fn CFCalendarCopyCurrent() -> CFTypeRef;
+        fn CFCalendarGetFirstWeekday(calendar: CFTypeRef) -> CFIndex;
+    }
+
+    /// The symbols of the user's locale for the given date formatter property.
+    fn symbols(key: CFDateFormatterKey) -> Vec<SharedString> {
+        unsafe {
+            let locale = CFLocaleCopyCurrent();
+            let formatter = CFDateFormatterCreate(
+                kCFAllocatorDefault,
+                locale,
+                kCFDateFormatterNoStyle,
+                kCFDateFormatterNoStyle,
+            );
+            let symbols = CFDateFormatterCopyProperty(formatter, key);
+            CFRelease(formatter as CFTypeRef);
+            CFRelease(locale as CFTypeRef);
+            if symbols.is_null() {
+                return Vec::new();
+            }
+            CFArray::<CFString>::wrap_under_create_rule(symbols as CFArrayRef)
+                .iter()
+                .map(|symbol| SharedString::from(symbol.to_string()))
+                .collect()
+        }
+    }
+
+    pub fn month_names() -> Option<[SharedString; 12]> {
+        symbols(unsafe { kCFDateFormatterMonthSymbols })
+            .try_into()
+            .ok()
+    }
+
+    pub fn weekday_names() -> Option<[SharedString; 7]> {
+        let mut names: [SharedString; 7] = symbols(unsafe { kCFDateFormatterShortWeekdaySymbols })
+            .try_into()
+            .ok()?;
+        // The symbols start on Sunday.
+        names.rotate_left(1);
+        Some(names)
+    }
+
+    pub fn first_day_of_week() -> Option<Weekday> {
+        let first = unsafe {
+            let calendar = CFCalendarCopyCurrent();
+            let first = CFCalendarGetFirstWeekday(calendar);
+            CFRelease(calendar);
+            first
+        };
+        // The weekdays are numbered from 1 for Sunday.
+        (1..=7)
+            .contains(&first)
+            .then(|| Weekday::Sunday.add_days(first as i64 - 1))
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use gpui::SharedString;
+    use windows::{
+        Win32::Globalization::{
+            GetLocaleInfoEx, LOCALE_IFIRSTDAYOFWEEK, LOCALE_SMONTHNAME1, LOCALE_SSHORTESTDAYNAME1,
+        },
+        core::PCWSTR,
+    };
+
+    use super::Weekday;
+
+    /// The given information about the user's locale.
+    fn locale_info(kind: u32) -> Option<String> {
+        let mut buffer = [0u16; 80];
+        let len = unsafe { GetLocaleInfoEx(PCWSTR::null(), kind, Some(&mut buffer[..])) };
+        // The length includes the terminating null.
+        (len > 1).then(|| String::from_utf16_lossy(&buffer[..len as usize - 1]))
+    }
+
+    /// The given number of consecutive pieces of information about the user's locale, such as the
+    /// names of the months.
+    fn locale_names<const N: usize>(first_kind: u32) -> Option<[SharedString; N]> {
+        (0..N as u32)
+            .map(|ix| locale_info(first_kind + ix).map(SharedString::from))
+            .collect::<Option<Vec<_>>>()?
+            .try_into()
+            .ok()
+    }
+
+    pub fn month_names() -> Option<[SharedString; 12]> {
+        locale_names(LOCALE_SMONTHNAME1)
+    }
+
+    pub fn weekday_names() -> Option<[SharedString; 7]> {
+        locale_names(LOCALE_SSHORTESTDAYNAME1)
+    }
+
+    pub fn first_day_of_week() -> Option<Weekday> {
+        // The weekdays are numbered from 0 for Monday.
+        let first = locale_info(LOCALE_IFIRSTDAYOFWEEK)?.parse::<i64>().ok()?;
+        (0..7)
+            .contains(&first)
+            .then(|| Weekday::Monday.add_days(first))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::ffi::CStr;
+
+    use gpui::SharedString;
+
+    use super::Weekday;
+
+    /// The given number of consecutive items of the user's time locale, such as the names of the
+    /// months.
+    fn time_items<const N: usize>(first_item: libc::nl_item) -> Option<[SharedString; N]> {
+        unsafe {
+            let locale = libc::newlocale(libc::LC_TIME_MASK, c"".as_ptr(), std::ptr::null_mut());
+            if locale.is_null() {
+                return None;
+            }
+            let items = (0..N as libc::nl_item)
+                .map(|ix| {
+                    let item = libc::nl_langinfo_l(first_item + ix, locale);
+                    (!item.is_null()).then(|| {
+                        SharedString::from(CStr::from_ptr(item).to_string_lossy().into_owned())
+                    })
+                })
+                .collect::<Option<Vec<_>>>();
+            libc::freelocale(locale);
+            items?.try_into().ok()
+        }
+    }
+
+    pub fn month_names() -> Option<[SharedString; 12]> {
+        time_items(libc::MON_1)
+    }
+
+    pub fn weekday_names() -> Option<[SharedString; 7]> {
+        let mut names: [SharedString; 7] = time_items(libc::ABDAY_1)?;
+        // The names start on Sunday.
+        names.rotate_left(1);
+        Some(names)
+    }
+
+    /// The first day of the week in the region of the user's time locale. The C library only
+    /// exposes it through an extension, so it's looked up from the region instead.
+    pub fn first_day_of_week() -> Option<Weekday> {
+        let locale = ["LC_ALL", "LC_TIME", "LANG"]
+            .into_iter()
+            .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))?;
+        let (_, region) = locale.split(['.', '@']).next()?.split_once('_')?;
+        let weekday = match region {
+            "AG" | "AS" | "BD" | "BR" | "BS" | "BT" | "BW" | "BZ" | "CA" | "CO" | "DM" | "DO"
+            | "ET" | "GT" | "GU" | "HK" | "HN" | "ID" | "IL" | "IN" | "JM" | "JP" | "KE" | "KH"
+            | "KR" | "LA" | "MH" | "MM" | "MO" | "MT" | "MX" | "MZ" | "NI" | "NP" | "PA" | "PE"
+            | "PH" | "PK" | "PR" | "PT" | "PY" | "SA" | "SG" | "SV" | "TH" | "TT" | "TW" | "UM"
+            | "US" | "VE" | "VI" | "WS" | "YE" | "ZA" | "ZW" => Weekday::Sunday,
+            "AE" | "AF" | "BH" | "DJ" | "DZ" | "EG" | "IQ" | "IR" | "JO" | "KW" | "LY" | "OM"
+            | "QA" | "SD" | "SY" => Weekday::Saturday,
+            "MV" => Weekday::Friday,
+            _ => Weekday::Monday,
+        };
+        Some(weekday)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod platform {
+    use gpui::SharedString;
+
+    use super::Weekday;
+
+    pub fn month_names() -> Option<[SharedString; 12]> {
+        None
+    }
+
+    pub fn weekday_names() -> Option<[SharedString; 7]> {
+        None
+    }
+
+    pub fn first_day_of_week() -> Option<Weekday> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use gpui::{KeyBinding, TestAppContext};
+
+    use super::*;
+
+    fn date(year: i32, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = settings::SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            let picker = Some("DatePicker || TimePicker");
+            let calendar = Some("Calendar || (DatePicker && showing_calendar)");
+            cx.bind_keys([
+                KeyBinding::new("enter", Confirm, None),
+                KeyBinding::new("escape", Cancel, None),
+                KeyBinding::new("left", PreviousField, picker),
+                KeyBinding::new("right", NextField, picker),
+                KeyBinding::new("up", Increment, picker),
+                KeyBinding::new("down", Decrement, picker),
+                KeyBinding::new("backspace", ClearField, picker),
+                KeyBinding::new("space", OpenCalendar, Some("DatePicker")),
+                KeyBinding::new("left", PreviousDay, calendar),
+                KeyBinding::new("right", NextDay, calendar),
+                KeyBinding::new("up", PreviousWeek, calendar),
+                KeyBinding::new("down", NextWeek, calendar),
+                KeyBinding::new("pageup", PreviousMonth, calendar),
+                KeyBinding::new("pagedown", NextMonth, calendar),
+                KeyBinding::new("home", StartOfWeek, calendar),
+                KeyBinding::new("end", EndOfWeek, calendar),
+                KeyBinding::new("space", Confirm, calendar),
+            ]);
+        });
+    }
+
+    #[test]
+    fn test_date_arithmetic() {
+        assert_eq!(Date::new(2023, 2, 29), None);
+        assert_eq!(date(2024, 2, 29).weekday(), Weekday::Thursday);
+        assert_eq!(date(1970, 1, 1).add_days(-1), date(1969, 12, 31));
+        assert_eq!(date(2024, 12, 25).add_days(10), date(2025, 1, 4));
+        assert_eq!(date(2024, 1, 31).add_months(1), date(2024, 2, 29));
+        assert_eq!(date(2024, 1, 31).add_months(-13), date(2022, 12, 31));
+        assert_eq!(date(1600, 3, 1).add_days(-1), date(1600, 2, 29));
+        assert_eq!(date(2000, 1, 1).weekday(), Weekday::Saturday);
+
+        let locale = CalendarLocale {
+            date_order: DateOrder::DayMonthYear,
+            date_separator: '.',
+            twelve_hour_clock: false,
+            ..Default::default()
+        };
+        assert_eq!(locale.format_date(date(2024, 3, 7)), "07.03.2024");
+        assert_eq!(locale.format_time(Time::new(16, 5).unwrap()), "16:05");
+        let locale = CalendarLocale::default();
+        assert_eq!(locale.format_date(date(2024, 3, 7)), "03/07/2024");
+        assert_eq!(locale.format_time(Time::new(0, 5).unwrap()), "12:05 AM");
+    }
+
+    #[gpui::test]
+    fn test_calendar_keyboard_and_range(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.set_global(CalendarLocale {
+                first_day_of_week: Weekday::Monday,
+                ..Default::default()
+            })
+        });
+        let (calendar, cx) = cx.add_window_view(|_, cx| {
+            Calendar::new(date(2024, 3, 14), cx)
+                .min(date(2024, 3, 4))
+                .max(date(2024, 4, 10))
+        });
+        calendar.update_in(cx, |calendar, window, _| {
+            window.focus(&calendar.focus_handle)
+        });
+        let selected = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|_, cx| {
+            let selected = selected.clone();
+            cx.subscribe(&calendar, move |_, CalendarEvent::Selected(date), _| {
+                selected.borrow_mut().push(*date)
+            })
+            .detach()
+        });
+
+        calendar.update(cx, |calendar, _| {
+            // March 2024 starts on a Friday, so the weeks start on Monday the 26th of February.
+            assert_eq!(calendar.first_visible_day(), date(2024, 2, 26));
+        });
+        cx.simulate_keystrokes("home up up");
+        calendar.update(cx, |calendar, _| {
+            assert_eq!(calendar.cursor(), date(2024, 3, 4))
+        });
+        cx.simulate_keystrokes("end pagedown pagedown enter");
+        calendar.update(cx, |calendar, cx| {
+            assert_eq!(calendar.cursor(), date(2024, 4, 10));
+            assert_eq!(calendar.visible_month(), (2024, 4));
+            calendar.select(date(2024, 4, 11), cx);
+        });
+        assert_eq!(*selected.borrow(), [date(2024, 4, 10)]);
+    }
+
+    #[gpui::test]
+    fn test_date_picker_keyboard_entry(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (picker, cx) = cx.add_window_view(|window, cx| {
+            DatePicker::new(date(2024, 6, 1), window, cx)
+                .locale(CalendarLocale {
+                    date_order: DateOrder::DayMonthYear,
+                    ..Default::default()
+                })
+                .max(date(2024, 12, 31))
+        });
+        picker.update_in(cx, |picker, window, _| window.focus(&picker.focus_handle));
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|_, cx| {
+            let changes = changes.clone();
+            cx.subscribe(&picker, move |_, DatePickerEvent::Changed(date), _| {
+                changes.borrow_mut().push(*date)
+            })
+            .detach()
+        });
+
+        // Typing 4 into the day can't be followed by another digit, so it moves to the month.
+        cx.simulate_input("4022024");
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.value(), Some(date(2024, 2, 4)))
+        });
+        cx.simulate_keystrokes("left up up right right");
+        cx.simulate_input("2025");
+        cx.simulate_keystrokes("backspace");
+
+        cx.simulate_keystrokes("space right enter");
+        picker.update(cx, |picker, _| assert!(!picker.is_open()));
+        assert_eq!(
+            *changes.borrow(),
+            [
+                Some(date(2024, 2, 4)),
+                Some(date(2024, 3, 4)),
+                Some(date(2024, 4, 4)),
+                Some(date(2024, 12, 31)),
+                None,
+                Some(date(2024, 6, 2)),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_time_picker_keyboard_entry(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (picker, cx) = cx.add_window_view(|window, cx| {
+            TimePicker::new(window, cx)
+                .locale(CalendarLocale::default())
+                .minute_step(15)
+                .min(Time::new(6, 0).unwrap())
+        });
+        picker.update_in(cx, |picker, window, _| window.focus(&picker.focus_handle));
+
+        cx.simulate_input("730p");
+        picker.update(cx, |picker, cx| {
+            assert_eq!(picker.value(), Time::new(19, 30));
+            picker.set_value(Time::new(9, 7), cx);
+        });
+        cx.simulate_keystrokes("left up up");
+        picker.update(cx, |picker, _| assert_eq!(picker.value(), Time::new(9, 30)));
+        cx.simulate_keystrokes("right");
+        cx.simulate_input("a");
+        picker.update(cx, |picker, _| assert_eq!(picker.value(), Time::new(9, 30)));
+        cx.simulate_keystrokes("left left");
+        cx.simulate_input("5");
+        picker.update(cx, |picker, _| assert_eq!(picker.value(), Time::new(6, 0)));
+    }
+}
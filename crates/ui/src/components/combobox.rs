@@ -0,0 +1,729 @@
+use std::{ops::Range, time::Duration};
+
+use gpui::{
+    Bounds, Corner, ElementInputHandler, EntityInputHandler, EventEmitter, FocusHandle, Focusable,
+    FontWeight, HighlightStyle, Point, StyledText, Subscription, Task, UTF16Selection, actions,
+    anchored, canvas, deferred,
+};
+use menu::{Cancel, Confirm, SelectNext, SelectPrevious};
+
+use crate::prelude::*;
+
+actions!(
+    combobox,
+    [
+        /// Deletes the last character typed into a combobox.
+        Backspace
+    ]
+);
+
+/// A source of suggestions for a [`Combobox`], fetched asynchronously for what the user typed.
+pub trait SuggestionSource: 'static {
+    /// Fetch the suggestions for the given query, the most relevant first. The combobox only
+    /// shows those that match the query, so sources can return more than that, such as every
+    /// suggestion they have.
+    fn suggestions(&mut self, query: &str, cx: &mut App) -> Task<gpui::Result<Vec<SharedString>>>;
+
+    /// How long to wait after the user stops typing before fetching the suggestions.
+    fn debounce(&self) -> Duration {
+        Duration::from_millis(150)
+    }
+}
+
+/// Which values a [`Combobox`] accepts.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ComboboxMode {
+    /// Any text the user types, completed with a suggestion when one is selected.
+    #[default]
+    FreeText,
+    /// Only one of the suggestions. When the dropdown is dismissed, the text reverts to the last
+    /// accepted value.
+    Strict,
+}
+
+/// The events emitted by a [`Combobox`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComboboxEvent {
+    /// The user accepted a value, by confirming or clicking a suggestion.
+    Confirmed(SharedString),
+}
+
+/// A suggestion matching the query of a [`Combobox`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestionMatch {
+    /// The suggestion.
+    pub text: SharedString,
+    /// The byte ranges of the suggestion that match the query.
+    pub ranges: Vec<Range<usize>>,
+}
+
+/// A text field with a dropdown of the suggestions from a [`SuggestionSource`] that match what the
+/// user typed, such as a search box or a parameter-entry field. Keep a `Combobox` entity on your
+/// view, render it as a child, and subscribe to its [`ComboboxEvent`]s.
+///
+/// The `menu` actions select and confirm the suggestions, and [`menu::Cancel`] closes the
+/// dropdown. Its key context is `Combobox`.
+pub struct Combobox<S: SuggestionSource> {
+    source: S,
+    mode: ComboboxMode,
+    focus_handle: FocusHandle,
+    placeholder: SharedString,
+    query: String,
+    /// The UTF-16 range of the query being composed by an input method.
+    marked_range: Option<Range<usize>>,
+    value: Option<SharedString>,
+    matches: Vec<SuggestionMatch>,
+    selected: Option<usize>,
+    open: bool,
+    error: Option<SharedString>,
+    /// Dropped when the query changes, cancelling a fetch for the previous one.
+    fetch: Option<Task<()>>,
+    _blur: Subscription,
+}
+
+impl<S: SuggestionSource> Combobox<S> {
+    /// Create a combobox with the suggestions from the given source.
+    pub fn new(source: S, mode: ComboboxMode, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let focus_handle = cx.focus_handle();
+        let blur = cx.on_blur(&focus_handle, window, |this, _, cx| this.dismiss(cx));
+        Self {
+            source,
+            mode,
+            focus_handle,
+            placeholder: SharedString::default(),
+            query: String::new(),
+            marked_range: None,
+            value: None,
+            matches: Vec::new(),
+            selected: None,
+            open: false,
+            error: None,
+            fetch: None,
+            _blur: blur,
+        }
+    }
+
+    /// Show the given text while the field is empty.
+    pub fn placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// The suggestion source.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// The suggestion source, for changing what it fetches. Call [`Combobox::refresh`]
+    /// afterwards to fetch the suggestions again.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// The text in the field.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// The last accepted value.
+    pub fn value(&self) -> Option<&SharedString> {
+        self.value.as_ref()
+    }
+
+    /// Set the accepted value, and the text in the field, without emitting an event.
+    pub fn set_value(&mut self, value: Option<SharedString>, cx: &mut Context<Self>) {
+        self.query = value.as_deref().unwrap_or_default().to_string();
+        self.value = value;
+        self.close(cx);
+    }
+
+    /// Replace the text in the field, as if the user typed it, and fetch its suggestions.
+    pub fn set_query(&mut self, query: impl Into<String>, cx: &mut Context<Self>) {
+        self.query = query.into();
+        self.marked_range = None;
+        self.refresh(cx);
+    }
+
+    /// Open the dropdown, and fetch the suggestions for the text in the field after the source's
+    /// debounce.
+    pub fn refresh(&mut self, cx: &mut Context<Self>) {
+        self.open = true;
+        let query = self.query.clone();
+        let debounce = self.source.debounce();
+        self.fetch = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(debounce).await;
+            let Ok(suggestions) = this.update(cx, |this, cx| this.source.suggestions(&query, cx))
+            else {
+                return;
+            };
+            let result = suggestions.await;
+            this.update(cx, |this, cx| this.finish_fetch(&query, result, cx))
+                .ok();
+        }));
+        cx.notify();
+    }
+
+    /// Whether the dropdown is open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Whether the suggestions for the text in the field are being fetched.
+    pub fn is_loading(&self) -> bool {
+        self.fetch.is_some()
+    }
+
+    /// The suggestions that match the text in the field, as of the last fetch.
+    pub fn matches(&self) -> &[SuggestionMatch] {
+        &self.matches
+    }
+
+    /// The index of the selected suggestion in [`Combobox::matches`].
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Select the next suggestion, wrapping around, opening the dropdown if it's closed.
+    pub fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        if self.composing(cx) {
+            return;
+        }
+        if !self.open {
+            self.refresh(cx);
+        }
+        if !self.matches.is_empty() {
+            self.selected = Some(self.selected.map_or(0, |ix| (ix + 1) % self.matches.len()));
+        }
+        cx.notify();
+    }
+
+    /// Select the previous suggestion, wrapping around, opening the dropdown if it's closed.
+    pub fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        if self.composing(cx) {
+            return;
+        }
+        if !self.open {
+            self.refresh(cx);
+        }
+        if !self.matches.is_empty() {
+            let last = self.matches.len() - 1;
+            self.selected = Some(
+                self.selected
+                    .map_or(last, |ix| ix.checked_sub(1).unwrap_or(last)),
+            );
+        }
+        cx.notify();
+    }
+
+    /// Accept the selected suggestion, or in [`ComboboxMode::FreeText`] the text in the field if
+    /// none is selected, and close the dropdown.
+    pub fn confirm(&mut self, _: &Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        if self.composing(cx) {
+            return;
+        }
+        let value = match self.selected.and_then(|ix| self.matches.get(ix)) {
+            Some(suggestion) => suggestion.text.clone(),
+            None if self.mode == ComboboxMode::FreeText => SharedString::from(self.query.clone()),
+            None => return,
+        };
+        self.query = value.to_string();
+        self.value = Some(value.clone());
+        self.close(cx);
+        cx.emit(ComboboxEvent::Confirmed(value));
+    }
+
+    /// Close the dropdown, if it's open, without accepting anything.
+    pub fn cancel(&mut self, _: &Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.open || self.composing(cx) {
+            cx.propagate();
+            return;
+        }
+        self.dismiss(cx);
+    }
+
+    /// Close the dropdown without accepting anything. In [`ComboboxMode::Strict`], the text in the
+    /// field reverts to the last accepted value.
+    pub fn dismiss(&mut self, cx: &mut Context<Self>) {
+        if self.mode == ComboboxMode::Strict {
+            self.query = self.value.as_deref().unwrap_or_default().to_string();
+        }
+        self.close(cx);
+    }
+
+    fn backspace(&mut self, _: &Backspace, _: &mut Window, cx: &mut Context<Self>) {
+        if self.query.is_empty() || self.composing(cx) {
+            cx.propagate();
+            return;
+        }
+        self.query.pop();
+        self.refresh(cx);
+    }
+
+    /// Whether an input method is composing text, which then handles the keys instead.
+    fn composing(&self, cx: &mut Context<Self>) -> bool {
+        let composing = self.marked_range.is_some();
+        if composing {
+            cx.propagate();
+        }
+        composing
+    }
+
+    fn close(&mut self, cx: &mut Context<Self>) {
+        self.open = false;
+        self.marked_range = None;
+        self.matches.clear();
+        self.selected = None;
+        self.error = None;
+        self.fetch = None;
+        cx.notify();
+    }
+
+    fn finish_fetch(
+        &mut self,
+        query: &str,
+        result: gpui::Result<Vec<SharedString>>,
+        cx: &mut Context<Self>,
+    ) {
+        self.fetch = None;
+        match result {
+            Ok(suggestions) => {
+                self.matches = suggestions
+                    .into_iter()
+                    .filter_map(|text| {
+                        let ranges = match_ranges(query, &text)?;
+                        Some(SuggestionMatch { text, ranges })
+                    })
+                    .collect();
+                self.error = None;
+            }
+            Err(error) => {
+                self.matches.clear();
+                self.error = Some(format!("{error:#}").into());
+            }
+        }
+        self.selected =
+            (self.mode == ComboboxMode::Strict && !self.matches.is_empty()).then_some(0);
+        cx.notify();
+    }
+
+    /// The byte range of the query for the given range of UTF-16 code units.
+    fn range_from_utf16(&self, range_utf16: &Range<usize>) -> Range<usize> {
+        let offset_from_utf16 = |offset_utf16: usize| {
+            let mut utf16_count = 0;
+            let mut utf8_offset = 0;
+            for char in self.query.chars() {
+                if utf16_count >= offset_utf16 {
+                    break;
+                }
+                utf16_count += char.len_utf16();
+                utf8_offset += char.len_utf8();
+            }
+            utf8_offset
+        };
+        offset_from_utf16(range_utf16.start)..offset_from_utf16(range_utf16.end)
+    }
+}
+
+/// Find the characters of the query in the suggestion, in order and ignoring case, returning the
+/// byte ranges of the suggestion that matched, or `None` if it doesn't match.
+fn match_ranges(query: &str, suggestion: &str) -> Option<Vec<Range<usize>>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut candidates = suggestion.char_indices();
+    for query_char in query.chars() {
+        let (ix, char) = candidates
+            .by_ref()
+            .find(|(_, char)| char.to_lowercase().eq(query_char.to_lowercase()))?;
+        let end = ix + char.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == ix => last.end = end,
+            _ => ranges.push(ix..end),
+        }
+    }
+    Some(ranges)
+}
+
+impl<S: SuggestionSource> EventEmitter<ComboboxEvent> for Combobox<S> {}
+
+impl<S: SuggestionSource> Focusable for Combobox<S> {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl<S: SuggestionSource> EntityInputHandler for Combobox<S> {
+    fn text_for_range(
+        &mut self,
+        range_utf16: Range<usize>,
+        adjusted_range: &mut Option<Range<usize>>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<String> {
+        let range = self.range_from_utf16(&range_utf16);
+        adjusted_range.replace(range_utf16);
+        Some(self.query[range].to_string())
+    }
+
+    fn selected_text_range(
+        &mut self,
+        _: bool,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<UTF16Selection> {
+        // The cursor is always at the end of the text.
+        let len = self.query.encode_utf16().count();
+        Some(UTF16Selection {
+            range: len..len,
+            reversed: false,
+        })
+    }
+
+    fn marked_text_range(&self, _: &mut Window, _: &mut Context<Self>) -> Option<Range<usize>> {
+        self.marked_range.clone()
+    }
+
+    fn unmark_text(&mut self, _: &mut Window, _: &mut Context<Self>) {
+        self.marked_range = None;
+    }
+
+    fn replace_text_in_range(
+        &mut self,
+        range_utf16: Option<Range<usize>>,
+        text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.replace_and_mark_text_in_range(range_utf16, text, None, window, cx);
+        self.marked_range = None;
+    }
+
+    fn replace_and_mark_text_in_range(
+        &mut self,
+        range_utf16: Option<Range<usize>>,
+        new_text: &str,
+        _: Option<Range<usize>>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let len = self.query.encode_utf16().count();
+        let range_utf16 = range_utf16
+            .or(self.marked_range.clone())
+            .unwrap_or(len..len);
+        let range = self.range_from_utf16(&range_utf16);
+        self.query.replace_range(range, new_text);
+        let start = range_utf16.start;
+        self.marked_range =
+            (!new_text.is_empty()).then(|| start..start + new_text.encode_utf16().count());
+        self.refresh(cx);
+    }
+
+    fn bounds_for_range(
+        &mut self,
+        _: Range<usize>,
+        element_bounds: Bounds<Pixels>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<Bounds<Pixels>> {
+        Some(element_bounds)
+    }
+
+    fn character_index_for_point(
+        &mut self,
+        _: Point<Pixels>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<usize> {
+        None
+    }
+}
+
+impl<S: SuggestionSource> Render for Combobox<S> {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let entity = cx.entity();
+        let focus_handle = self.focus_handle.clone();
+        let focused = focus_handle.is_focused(window);
+        let colors = cx.theme().colors();
+        let field = h_flex()
+            .id("combobox-field")
+            .key_context("Combobox")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::backspace))
+            .on_click(cx.listener(|this, _, window, cx| {
+                window.focus(&this.focus_handle);
+                if !this.open {
+                    this.refresh(cx);
+                }
+            }))
+            .relative()
+            .w_full()
+            .px_2()
+            .py_1()
+            .bg(colors.editor_background)
+            .border_1()
+            .border_color(if focused {
+                colors.border_focused
+            } else {
+                colors.border
+            })
+            .rounded_sm()
+            .cursor_text()
+            .map(|this| {
+                if self.query.is_empty() {
+                    this.child(Label::new(self.placeholder.clone()).color(Color::Placeholder))
+                } else {
+                    this.child(Label::new(self.query.clone()))
+                }
+            })
+            .when(focused, |this| {
+                this.child(div().w(px(1.)).h_4().bg(colors.text))
+            })
+            .child(
+                canvas(
+                    |_, _, _| {},
+                    move |bounds, _, window, cx| {
+                        window.handle_input(
+                            &focus_handle,
+                            ElementInputHandler::new(bounds, entity),
+                            cx,
+                        )
+                    },
+                )
+                .absolute()
+                .size_full(),
+            );
+
+        let dropdown = self.open.then(|| {
+            let rows = if let Some(error) = &self.error {
+                vec![
+                    div()
+                        .px_2()
+                        .py_1()
+                        .child(Label::new(error.clone()).color(Color::Error))
+                        .into_any_element(),
+                ]
+            } else if self.matches.is_empty() {
+                let message = if self.is_loading() {
+                    "Loading…"
+                } else {
+                    "No suggestions"
+                };
+                vec![
+                    div()
+                        .px_2()
+                        .py_1()
+                        .child(Label::new(message).color(Color::Muted))
+                        .into_any_element(),
+                ]
+            } else {
+                let highlight = HighlightStyle {
+                    font_weight: Some(FontWeight::BOLD),
+                    ..Default::default()
+                };
+                self.matches
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, suggestion)| {
+                        let highlights = suggestion
+                            .ranges
+                            .iter()
+                            .map(|range| (range.clone(), highlight));
+                        div()
+                            .id(ix)
+                            .px_2()
+                            .py_1()
+                            .cursor_pointer()
+                            .hover(|style| style.bg(colors.ghost_element_hover))
+                            .when(self.selected == Some(ix), |this| {
+                                this.bg(colors.ghost_element_selected)
+                            })
+                            .child(
+                                StyledText::new(suggestion.text.clone())
+                                    .with_highlights(highlights),
+                            )
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.selected = Some(ix);
+                                this.confirm(&Confirm, window, cx);
+                            }))
+                            .into_any_element()
+                    })
+                    .collect()
+            };
+            deferred(
+                anchored().anchor(Corner::TopLeft).child(
+                    v_flex()
+                        .id("combobox-dropdown")
+                        .occlude()
+                        .elevation_2(cx)
+                        .min_w(px(160.))
+                        .max_h(px(240.))
+                        .overflow_y_scroll()
+                        .mt_1()
+                        .py_1()
+                        .children(rows),
+                ),
+            )
+            .with_priority(1)
+        });
+
+        v_flex().child(field).children(dropdown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use gpui::{KeyBinding, TestAppContext, VisualTestContext};
+
+    use super::*;
+
+    struct Fruits {
+        queries: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl SuggestionSource for Fruits {
+        fn suggestions(
+            &mut self,
+            query: &str,
+            _: &mut App,
+        ) -> Task<gpui::Result<Vec<SharedString>>> {
+            self.queries.borrow_mut().push(query.to_string());
+            Task::ready(Ok(vec![
+                "apple".into(),
+                "apricot".into(),
+                "banana".into(),
+                "pineapple".into(),
+            ]))
+        }
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = settings::SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            cx.bind_keys([
+                KeyBinding::new("down", SelectNext, None),
+                KeyBinding::new("up", SelectPrevious, None),
+                KeyBinding::new("enter", Confirm, None),
+                KeyBinding::new("escape", Cancel, None),
+                KeyBinding::new("backspace", Backspace, Some("Combobox")),
+            ]);
+        });
+    }
+
+    fn settle(cx: &mut VisualTestContext) {
+        cx.executor().advance_clock(Duration::from_millis(200));
+        cx.run_until_parked();
+    }
+
+    #[test]
+    fn test_match_ranges() {
+        assert_eq!(match_ranges("", "apple"), Some(vec![]));
+        assert_eq!(match_ranges("ap", "Apricot"), Some(vec![0..2]));
+        assert_eq!(match_ranges("apl", "pineapple"), Some(vec![4..6, 7..8]));
+        assert_eq!(match_ranges("ÉT", "été"), Some(vec![0..2, 2..3]));
+        assert_eq!(match_ranges("pa", "apple"), None);
+    }
+
+    #[gpui::test]
+    fn test_debounced_suggestions(cx: &mut TestAppContext) {
+        init_test(cx);
+        let queries = Rc::new(RefCell::new(Vec::new()));
+        let (combobox, cx) = cx.add_window_view(|window, cx| {
+            Combobox::new(
+                Fruits {
+                    queries: queries.clone(),
+                },
+                ComboboxMode::FreeText,
+                window,
+                cx,
+            )
+        });
+        combobox.update_in(cx, |combobox, window, _| {
+            window.focus(&combobox.focus_handle)
+        });
+
+        cx.simulate_input("ax");
+        cx.simulate_keystrokes("backspace");
+        cx.simulate_input("p");
+        combobox.update(cx, |combobox, _| assert!(combobox.is_loading()));
+        settle(cx);
+        assert_eq!(*queries.borrow(), ["ap"]);
+
+        let texts = combobox.read_with(cx, |combobox, _| {
+            assert_eq!(combobox.selected_index(), None);
+            combobox
+                .matches()
+                .iter()
+                .map(|suggestion| suggestion.text.to_string())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(texts, ["apple", "apricot", "pineapple"]);
+
+        cx.simulate_keystrokes("up");
+        combobox.read_with(cx, |combobox, _| {
+            assert_eq!(combobox.selected_index(), Some(2))
+        });
+        cx.simulate_keystrokes("down");
+        combobox.read_with(cx, |combobox, _| {
+            assert_eq!(combobox.selected_index(), Some(0))
+        });
+    }
+
+    #[gpui::test]
+    fn test_free_text_and_strict_modes(cx: &mut TestAppContext) {
+        init_test(cx);
+        let confirmed = Rc::new(RefCell::new(Vec::new()));
+        for mode in [ComboboxMode::FreeText, ComboboxMode::Strict] {
+            let (combobox, cx) = cx.add_window_view(|window, cx| {
+                Combobox::new(
+                    Fruits {
+                        queries: Rc::default(),
+                    },
+                    mode,
+                    window,
+                    cx,
+                )
+            });
+            combobox.update_in(cx, |combobox, window, _| {
+                window.focus(&combobox.focus_handle)
+            });
+            let confirmed = confirmed.clone();
+            cx.update(|_, cx| {
+                cx.subscribe(&combobox, move |_, ComboboxEvent::Confirmed(value), _| {
+                    confirmed.borrow_mut().push(value.clone())
+                })
+                .detach()
+            });
+
+            cx.simulate_input("xyz");
+            settle(cx);
+            combobox.read_with(cx, |combobox, _| assert!(combobox.matches().is_empty()));
+            cx.simulate_keystrokes("enter");
+
+            combobox.update(cx, |combobox, cx| combobox.set_query("ban", cx));
+            settle(cx);
+            cx.simulate_keystrokes("down enter");
+            combobox.read_with(cx, |combobox, _| {
+                assert!(!combobox.is_open());
+                assert_eq!(combobox.query(), "banana");
+            });
+
+            combobox.update(cx, |combobox, cx| combobox.set_query("pear", cx));
+            cx.simulate_keystrokes("escape");
+            let expected_query = match mode {
+                ComboboxMode::FreeText => "pear",
+                ComboboxMode::Strict => "banana",
+            };
+            combobox.read_with(cx, |combobox, _| {
+                assert_eq!(combobox.query(), expected_query)
+            });
+        }
+        assert_eq!(*confirmed.borrow(), ["xyz", "banana", "banana"]);
+    }
+}
@@ -1,6 +1,6 @@
 use gpui::{
-    AnyElement, AnyView, ClickEvent, ElementId, Hsla, IntoElement, KeybindingKeystroke, Keystroke,
-    Styled, Window, div, hsla, prelude::*,
+    AnyElement, AnyView, Binding, ClickEvent, ElementId, Hsla, IntoElement, KeybindingKeystroke,
+    Keystroke, Styled, Window, div, hsla, prelude::*,
 };
 use settings::KeybindSource;
 use std::{rc::Rc, sync::Arc};
@@ -47,6 +47,7 @@ pub struct Checkbox {
     disabled: bool,
     placeholder: bool,
     on_click: Option<Box<dyn Fn(&ToggleState, &ClickEvent, &mut Window, &mut App) + 'static>>,
+    binding: Option<Binding<bool>>,
     filled: bool,
     style: ToggleStyle,
     tooltip: Option<Box<dyn Fn(&mut Window, &mut App) -> AnyView>>,
@@ -104,6 +105,13 @@ impl Checkbox {
         self
     }
 
+    /// Binds the [`Checkbox`] to a `bool`, which it is checked from when rendered and which
+    /// is written back when clicked, before any click handler is called.
+    pub fn bind(mut self, binding: Binding<bool>) -> Self {
+        self.binding = Some(binding);
+        self
+    }
+
     /// Sets the `fill` setting of the checkbox, indicating whether it should be filled.
     pub fn fill(mut self) -> Self {
         self.filled = true;
@@ -176,7 +184,20 @@ impl Checkbox {
 }
 
 impl RenderOnce for Checkbox {
-    fn render(self, _: &mut Window, cx: &mut App) -> impl IntoElement {
+    fn render(mut self, _: &mut Window, cx: &mut App) -> impl IntoElement {
+        if let Some(binding) = self.binding.take() {
+            if let Some(checked) = binding.get(cx) {
+                self.toggle_state = checked.into();
+            }
+            let on_click = self.on_click.take();
+            self.on_click = Some(Box::new(move |state, click, window, cx| {
+                binding.set(*state == ToggleState::Selected, cx);
+                if let Some(on_click) = &on_click {
+                    on_click(state, click, window, cx);
+                }
+            }));
+        }
+
         let group_id = format!("checkbox_group_{:?}", self.id);
         let color = if self.disabled {
             Color::Disabled
@@ -350,6 +371,7 @@ pub struct Switch {
     toggle_state: ToggleState,
     disabled: bool,
     on_click: Option<Rc<dyn Fn(&ToggleState, &mut Window, &mut App) + 'static>>,
+    binding: Option<Binding<bool>>,
     label: Option<SharedString>,
     label_position: Option<SwitchLabelPosition>,
     label_size: LabelSize,
@@ -367,6 +389,7 @@ impl Switch {
             toggle_state: state,
             disabled: false,
             on_click: None,
+            binding: None,
             label: None,
             label_position: None,
             label_size: LabelSize::Small,
@@ -398,6 +421,13 @@ impl Switch {
         self
     }
 
+    /// Binds the [`Switch`] to a `bool`, which it is turned on from when rendered and which is
+    /// written back when clicked, before any click handler is called.
+    pub fn bind(mut self, binding: Binding<bool>) -> Self {
+        self.binding = Some(binding);
+        self
+    }
+
     /// Sets the label of the [`Switch`].
     pub fn label(mut self, label: impl Into<SharedString>) -> Self {
         self.label = Some(label.into());
@@ -435,7 +465,20 @@ impl Switch {
 }
 
 impl RenderOnce for Switch {
-    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+    fn render(mut self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        if let Some(binding) = self.binding.take() {
+            if let Some(on) = binding.get(cx) {
+                self.toggle_state = on.into();
+            }
+            let on_click = self.on_click.take();
+            self.on_click = Some(Rc::new(move |state, window, cx| {
+                binding.set(*state == ToggleState::Selected, cx);
+                if let Some(on_click) = &on_click {
+                    on_click(state, window, cx);
+                }
+            }));
+        }
+
         let is_on = self.toggle_state == ToggleState::Selected;
         let adjust_ratio = if is_light(cx) { 1.5 } else { 1.0 };
 
@@ -1,6 +1,8 @@
 use component::{example_group, single_example};
-use editor::{Editor, EditorElement, EditorStyle};
-use gpui::{App, Entity, FocusHandle, Focusable, FontStyle, Hsla, Length, TextStyle};
+use editor::{Editor, EditorElement, EditorEvent, EditorStyle};
+use gpui::{
+    App, Binding, Entity, FocusHandle, Focusable, FontStyle, Hsla, Length, Subscription, TextStyle,
+};
 use settings::Settings;
 use std::sync::Arc;
 use theme::ThemeSettings;
@@ -41,6 +43,8 @@ pub struct InputField {
     tab_index: Option<isize>,
     /// Whether this field is a tab stop (can be focused via Tab key).
     tab_stop: bool,
+    /// Keeps the text in sync with the binding passed to [`InputField::bind`], if any.
+    _binding_subscriptions: Vec<Subscription>,
 }
 
 impl Focusable for InputField {
@@ -69,6 +73,7 @@ impl InputField {
             min_width: px(192.).into(),
             tab_index: None,
             tab_stop: true,
+            _binding_subscriptions: Vec::new(),
         }
     }
 
@@ -102,6 +107,38 @@ impl InputField {
         self
     }
 
+    /// Binds the field to a string, which it displays, including when it's changed elsewhere,
+    /// and which every edit is written back to.
+    pub fn bind(mut self, binding: Binding<String>, window: &mut Window, cx: &mut App) -> Self {
+        if let Some(text) = binding.get(cx) {
+            self.set_text(text, window, cx);
+        }
+        let write_back = cx.subscribe(&self.editor, {
+            let binding = binding.clone();
+            move |editor, event: &EditorEvent, cx| {
+                if let EditorEvent::BufferEdited = event {
+                    let text = editor.read(cx).text(cx);
+                    if binding.get(cx).as_ref() != Some(&text) {
+                        binding.set(text, cx);
+                    }
+                }
+            }
+        });
+        let read_back = binding.observe(window, cx, {
+            let editor = self.editor.clone();
+            let binding = binding.clone();
+            move |window, cx| {
+                if let Some(text) = binding.get(cx)
+                    && text != editor.read(cx).text(cx)
+                {
+                    editor.update(cx, |editor, cx| editor.set_text(text, window, cx));
+                }
+            }
+        });
+        self._binding_subscriptions = vec![write_back, read_back];
+        self
+    }
+
     pub fn set_disabled(&mut self, disabled: bool, cx: &mut Context<Self>) {
         self.disabled = disabled;
         self.editor
@@ -6,7 +6,7 @@ use std::{
 };
 
 use editor::{Editor, EditorStyle};
-use gpui::{ClickEvent, Entity, FocusHandle, Focusable, FontWeight, Modifiers};
+use gpui::{Binding, ClickEvent, Entity, FocusHandle, Focusable, FontWeight, Modifiers};
 
 use settings::{CenteredPaddingSettings, CodeFade, DelayMs, InactiveOpacity, MinimumContrast};
 use ui::prelude::*;
@@ -249,6 +249,7 @@ pub struct NumberField<T = usize> {
     max_value: T,
     on_reset: Option<Box<dyn Fn(&ClickEvent, &mut Window, &mut App) + 'static>>,
     on_change: Rc<dyn Fn(&T, &mut Window, &mut App) + 'static>,
+    binding: Option<Binding<T>>,
     tab_index: Option<isize>,
 }
 
@@ -275,6 +276,7 @@ impl<T: NumberFieldType> NumberField<T> {
             max_value: T::max_value(),
             on_reset: None,
             on_change: Rc::new(|_, _, _| {}),
+            binding: None,
             tab_index: None,
         }
     }
@@ -326,6 +328,13 @@ impl<T: NumberFieldType> NumberField<T> {
         self.on_change = Rc::new(on_change);
         self
     }
+
+    /// Binds the field to a value, which it displays when rendered instead of the value it
+    /// was created with, and which is written back when changed, before `on_change` is called.
+    pub fn bind(mut self, binding: Binding<T>) -> Self {
+        self.binding = Some(binding);
+        self
+    }
 }
 
 impl<T: NumberFieldType> IntoElement for NumberField<T> {
@@ -337,7 +346,18 @@ impl<T: NumberFieldType> IntoElement for NumberField<T> {
 }
 
 impl<T: NumberFieldType> RenderOnce for NumberField<T> {
-    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+    fn render(mut self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        if let Some(binding) = self.binding.take() {
+            if let Some(value) = binding.get(cx) {
+                self.value = value;
+            }
+            let on_change = self.on_change.clone();
+            self.on_change = Rc::new(move |value, window, cx| {
+                binding.set(*value, cx);
+                on_change(value, window, cx);
+            });
+        }
+
         let mut tab_index = self.tab_index;
 
         let get_step = {